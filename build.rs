@@ -0,0 +1,14 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        // A vendored protoc, so building with `grpc` needs no system install
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+        // SAFETY: build scripts are single-threaded
+        unsafe { std::env::set_var("PROTOC", protoc) };
+        tonic_prost_build::configure()
+            .build_client(true)
+            .compile_protos(&["proto/granary.proto"], &["proto"])
+            .expect("compile proto/granary.proto");
+    }
+    println!("cargo:rerun-if-changed=proto/granary.proto");
+}
@@ -0,0 +1,5 @@
+-- Add cron schedule support to workers
+-- schedule: optional cron expression; next_fire_at: persisted next fire time (RFC3339)
+
+ALTER TABLE workers ADD COLUMN schedule TEXT;
+ALTER TABLE workers ADD COLUMN next_fire_at TEXT;
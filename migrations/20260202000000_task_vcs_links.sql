@@ -0,0 +1,14 @@
+-- Link tasks to git branches and commits
+
+CREATE TABLE IF NOT EXISTS task_vcs_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,      -- 'branch' or 'commit'
+    ref_name TEXT NOT NULL,  -- branch name or full commit SHA
+    summary TEXT,            -- commit subject line
+    created_at TEXT NOT NULL,
+    UNIQUE(task_id, kind, ref_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_vcs_links_task ON task_vcs_links(task_id);
+CREATE INDEX IF NOT EXISTS idx_task_vcs_links_ref ON task_vcs_links(ref_name);
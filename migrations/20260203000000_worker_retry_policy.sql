@@ -0,0 +1,4 @@
+-- Add per-worker retry policy
+-- retry_policy: optional JSON-encoded RetryPolicy; NULL uses the default policy
+
+ALTER TABLE workers ADD COLUMN retry_policy TEXT;
@@ -0,0 +1,4 @@
+-- Add per-run execution timeout to workers
+-- timeout_secs: runs exceeding this wall-clock time are killed; NULL means no timeout
+
+ALTER TABLE workers ADD COLUMN timeout_secs INTEGER;
@@ -0,0 +1,13 @@
+-- Custom field values for tasks and projects.
+-- Field names and types are declared in the config table as field.<name>.
+
+CREATE TABLE IF NOT EXISTS custom_fields (
+    entity_type TEXT NOT NULL,  -- 'task' or 'project'
+    entity_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (entity_type, entity_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_custom_fields_name_value ON custom_fields(name, value);
@@ -0,0 +1,6 @@
+-- Archived tasks are hidden from default listings, summaries and search.
+-- Archiving is independent of status, so a task keeps its status when restored.
+
+ALTER TABLE tasks ADD COLUMN archived_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_tasks_archived_at ON tasks(archived_at);
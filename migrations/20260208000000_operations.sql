@@ -0,0 +1,23 @@
+-- Log of mutating CLI operations, used by `granary undo`.
+-- Each operation groups the row changes made by one command, recorded by
+-- connection-local triggers as JSON snapshots of the row before and after.
+
+CREATE TABLE IF NOT EXISTS operations (
+    id TEXT PRIMARY KEY,
+    command TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    undone_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_operations_created_at ON operations(created_at);
+
+CREATE TABLE IF NOT EXISTS operation_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    operation_id TEXT NOT NULL,
+    table_name TEXT NOT NULL,
+    action TEXT NOT NULL,  -- 'insert', 'update' or 'delete'
+    before TEXT,           -- JSON row before the change, NULL for inserts
+    after TEXT             -- JSON row after the change, NULL for deletes
+);
+
+CREATE INDEX IF NOT EXISTS idx_operation_changes_operation_id ON operation_changes(operation_id);
@@ -0,0 +1,27 @@
+-- Attribute recorded operations to an actor and make the change log
+-- append-only, so it doubles as an audit trail for `granary audit`.
+
+ALTER TABLE operations ADD COLUMN actor TEXT;
+ALTER TABLE operations ADD COLUMN session_id TEXT;
+-- 0 for operations that cannot be undone, such as an undo itself
+ALTER TABLE operations ADD COLUMN undoable INTEGER NOT NULL DEFAULT 1;
+
+CREATE INDEX IF NOT EXISTS idx_operations_actor ON operations(actor);
+
+CREATE TRIGGER IF NOT EXISTS operations_no_delete
+BEFORE DELETE ON operations
+BEGIN
+    SELECT RAISE(ABORT, 'The operation log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS operation_changes_no_update
+BEFORE UPDATE ON operation_changes
+BEGIN
+    SELECT RAISE(ABORT, 'The operation log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS operation_changes_no_delete
+BEFORE DELETE ON operation_changes
+BEGIN
+    SELECT RAISE(ABORT, 'The operation log is append-only');
+END;
@@ -0,0 +1,26 @@
+-- Links between local entities and their copies in external trackers,
+-- used by `granary sync`. Timestamps record both sides as of the last sync,
+-- so a later sync can tell which side changed since.
+
+CREATE TABLE IF NOT EXISTS sync_links (
+    provider TEXT NOT NULL,           -- e.g. 'linear'
+    entity_type TEXT NOT NULL,        -- 'initiative', 'project' or 'task'
+    local_id TEXT NOT NULL,
+    remote_id TEXT NOT NULL,
+    remote_key TEXT,                  -- human-readable remote id, e.g. 'ENG-42'
+    local_updated_at TEXT NOT NULL,
+    remote_updated_at TEXT NOT NULL,
+    synced_at TEXT NOT NULL,
+    PRIMARY KEY (provider, entity_type, local_id),
+    UNIQUE (provider, entity_type, remote_id)
+);
+
+-- Latest remote update seen per provider and entity type, so a sync only
+-- pulls what changed since the previous one.
+CREATE TABLE IF NOT EXISTS sync_cursors (
+    provider TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    cursor TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (provider, entity_type)
+);
@@ -0,0 +1,5 @@
+-- Who created each task and checkpoint. Comments already have an author.
+-- Filled from the actor the creating command ran as, so older rows are NULL.
+
+ALTER TABLE tasks ADD COLUMN author TEXT;
+ALTER TABLE checkpoints ADD COLUMN author TEXT;
@@ -0,0 +1,29 @@
+-- Remotes the workspace is synchronised with by `granary push` and
+-- `granary pull`. A remote holds batches of row changes taken from the
+-- operation log; these tables record how far each side has got. They are
+-- local to the machine and are never synchronised themselves.
+
+CREATE TABLE IF NOT EXISTS remotes (
+    name TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    pushed_change_id INTEGER NOT NULL DEFAULT 0,  -- last operation_changes.id pushed
+    pushed_at TEXT,
+    pulled_at TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- Batches on a remote that this machine pushed or has applied
+CREATE TABLE IF NOT EXISTS remote_batches (
+    remote TEXT NOT NULL,
+    batch TEXT NOT NULL,
+    synced_at TEXT NOT NULL,
+    PRIMARY KEY (remote, batch)
+);
+
+-- When each synchronised row last changed, for last-writer-wins on pull
+CREATE TABLE IF NOT EXISTS remote_row_clocks (
+    table_name TEXT NOT NULL,
+    row_key TEXT NOT NULL,   -- JSON array of the row's primary key values
+    changed_at TEXT NOT NULL,
+    PRIMARY KEY (table_name, row_key)
+);
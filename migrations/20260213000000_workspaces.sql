@@ -0,0 +1,10 @@
+-- Registry of workspaces on this machine, kept in the global database so
+-- `granary workspaces` can list them and `--workspace <name>` can find one.
+-- Workspace databases get the table too but leave it empty.
+
+CREATE TABLE IF NOT EXISTS workspaces (
+    name TEXT PRIMARY KEY,
+    root TEXT NOT NULL UNIQUE,    -- absolute path of the directory holding .granary/
+    created_at TEXT NOT NULL,
+    last_used_at TEXT NOT NULL    -- when a command last ran in the workspace
+);
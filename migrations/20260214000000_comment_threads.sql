@@ -0,0 +1,6 @@
+-- Threaded comments: a reply points at the comment it answers, which is on
+-- the same task or project. Top-level comments have no parent comment.
+
+ALTER TABLE comments ADD COLUMN parent_comment_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_comments_parent_comment ON comments(parent_comment_id);
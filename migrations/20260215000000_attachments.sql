@@ -0,0 +1,15 @@
+-- Files attached to tasks, stored under .granary/attachments/<task-id>/
+
+CREATE TABLE IF NOT EXISTS attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,        -- file name, unique per task
+    path TEXT NOT NULL,        -- stored file, relative to the workspace root
+    source TEXT NOT NULL,      -- the file that was attached
+    mode TEXT NOT NULL,        -- 'copy' or 'symlink'
+    size_bytes INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE(task_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_task ON attachments(task_id);
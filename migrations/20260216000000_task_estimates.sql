@@ -0,0 +1,4 @@
+-- Task estimates in points or hours, whichever a workspace uses. Rolled up
+-- per project and initiative by summaries and charted by burndown reports.
+
+ALTER TABLE tasks ADD COLUMN estimate REAL;
@@ -0,0 +1,10 @@
+-- Tasks each rule has fired for, so the daemon fires a rule once per task
+-- until the task changes again
+
+CREATE TABLE IF NOT EXISTS rule_firings (
+    rule TEXT NOT NULL,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    task_version INTEGER NOT NULL,  -- task version after the rule fired
+    fired_at TEXT NOT NULL,
+    PRIMARY KEY (rule, task_id)
+);
@@ -0,0 +1,9 @@
+-- Due date reminders the daemon has sent, so each is sent once per due date
+
+CREATE TABLE IF NOT EXISTS task_reminders (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,      -- due_soon or overdue
+    due_at TEXT NOT NULL,    -- the due date the reminder was sent for
+    sent_at TEXT NOT NULL,
+    PRIMARY KEY (task_id, kind)
+);
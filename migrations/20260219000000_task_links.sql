@@ -0,0 +1,12 @@
+-- Typed relationships between tasks, beyond blocking dependencies.
+-- Each row reads "task_id <kind> linked_task_id", e.g. a duplicates b.
+
+CREATE TABLE IF NOT EXISTS task_links (
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    linked_task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,  -- relates_to, duplicates or caused_by
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (task_id, linked_task_id, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_links_linked ON task_links(linked_task_id);
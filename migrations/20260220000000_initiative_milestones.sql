@@ -0,0 +1,28 @@
+-- Milestones: named checkpoints within an initiative with an optional
+-- target date. Progress is measured over the attached projects and tasks.
+
+CREATE TABLE IF NOT EXISTS initiative_milestones (
+    id TEXT PRIMARY KEY,
+    initiative_id TEXT NOT NULL REFERENCES initiatives(id) ON DELETE CASCADE,
+    milestone_number INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    target_date TEXT,
+    completed_at TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_milestones_initiative ON initiative_milestones(initiative_id);
+
+CREATE TABLE IF NOT EXISTS milestone_projects (
+    milestone_id TEXT NOT NULL REFERENCES initiative_milestones(id) ON DELETE CASCADE,
+    project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    PRIMARY KEY (milestone_id, project_id)
+);
+
+CREATE TABLE IF NOT EXISTS milestone_tasks (
+    milestone_id TEXT NOT NULL REFERENCES initiative_milestones(id) ON DELETE CASCADE,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    PRIMARY KEY (milestone_id, task_id)
+);
@@ -0,0 +1,39 @@
+-- Every change of a task's status, recorded by trigger so transitions made
+-- by any command, sync or undo are kept. `granary report velocity` buckets
+-- completions from here.
+
+CREATE TABLE IF NOT EXISTS task_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    from_status TEXT,  -- NULL for backfilled rows
+    to_status TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_status_history_task ON task_status_history(task_id);
+CREATE INDEX IF NOT EXISTS idx_status_history_to ON task_status_history(to_status, changed_at);
+
+CREATE TRIGGER IF NOT EXISTS tasks_record_status_change
+AFTER UPDATE OF status ON tasks
+WHEN OLD.status IS NOT NEW.status
+BEGIN
+    INSERT INTO task_status_history (task_id, from_status, to_status, changed_at)
+    VALUES (NEW.id, OLD.status, NEW.status, NEW.updated_at);
+END;
+
+-- Backfill the completion of tasks already done, from the completion time,
+-- else the latest event that marked them done, else their last update
+INSERT INTO task_status_history (task_id, from_status, to_status, changed_at)
+SELECT t.id, NULL, 'done', COALESCE(
+    t.completed_at,
+    (
+        SELECT MAX(e.created_at) FROM events e
+        WHERE e.entity_type = 'task' AND e.entity_id = t.id
+          AND (e.event_type = 'task.completed'
+               OR (e.event_type = 'task.status_changed'
+                   AND json_extract(e.payload, '$.new_status') = 'done'))
+    ),
+    t.updated_at
+)
+FROM tasks t
+WHERE t.status = 'done';
@@ -0,0 +1,7 @@
+-- Add debounce and throttle settings to workers
+-- debounce_secs: wait until no matching event has arrived for this long, then start one run
+-- throttle_secs: start at most one run per this many seconds
+-- Events that arrive while a run is held back are coalesced into it; NULL disables either
+
+ALTER TABLE workers ADD COLUMN debounce_secs INTEGER;
+ALTER TABLE workers ADD COLUMN throttle_secs INTEGER;
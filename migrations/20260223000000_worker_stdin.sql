@@ -0,0 +1,4 @@
+-- Let workers send the triggering event and a context pack to runners on stdin
+-- stdin: 1 to pipe a JSON document to each runner's stdin, 0 to leave it alone
+
+ALTER TABLE workers ADD COLUMN stdin INTEGER NOT NULL DEFAULT 0;
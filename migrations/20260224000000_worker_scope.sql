@@ -0,0 +1,15 @@
+-- Cross-workspace workers
+-- global: the worker receives events from every registered workspace, not only its own
+-- runs.instance_path: workspace a run belongs to, when it is not its worker's (global workers)
+-- worker_cursors: per-workspace event cursors of global workers
+
+ALTER TABLE workers ADD COLUMN global INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE runs ADD COLUMN instance_path TEXT;
+
+CREATE TABLE IF NOT EXISTS worker_cursors (
+    worker_id TEXT NOT NULL REFERENCES workers(id) ON DELETE CASCADE,
+    instance_path TEXT NOT NULL,
+    last_event_id INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (worker_id, instance_path)
+);
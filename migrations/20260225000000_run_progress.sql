@@ -0,0 +1,6 @@
+-- Runner progress reporting
+-- progress: last percentage (0-100) a runner reported with a GRANARY_PROGRESS line
+-- progress_message: text that came with the last report
+
+ALTER TABLE runs ADD COLUMN progress INTEGER;
+ALTER TABLE runs ADD COLUMN progress_message TEXT;
@@ -0,0 +1,5 @@
+-- Pausing workers
+-- on_pause: what a paused worker does with new events: 'queue' leaves them for
+-- when it resumes, 'drop' skips them
+
+ALTER TABLE workers ADD COLUMN on_pause TEXT NOT NULL DEFAULT 'queue';
@@ -0,0 +1,7 @@
+-- Run queue ordering
+-- priority: queued runs with a higher priority start first; `granary runs bump`
+-- raises it above the other runs queued for the same worker
+
+ALTER TABLE runs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_runs_queue ON runs(worker_id, status, priority DESC, created_at);
@@ -0,0 +1,5 @@
+-- Graceful termination of runs
+-- exited_gracefully: set when a run is stopped or times out; 1 if it exited
+-- within its runner's grace period after SIGTERM, 0 if it had to be killed
+
+ALTER TABLE runs ADD COLUMN exited_gracefully INTEGER;
@@ -0,0 +1,9 @@
+-- Resource usage of runs, recorded when the runner process exits
+-- max_rss_kb: peak resident set size in KiB (Unix only)
+-- user_cpu_ms, system_cpu_ms: CPU time in milliseconds (Unix only)
+-- duration_ms: wall time from spawn to exit in milliseconds
+
+ALTER TABLE runs ADD COLUMN max_rss_kb INTEGER;
+ALTER TABLE runs ADD COLUMN user_cpu_ms INTEGER;
+ALTER TABLE runs ADD COLUMN system_cpu_ms INTEGER;
+ALTER TABLE runs ADD COLUMN duration_ms INTEGER;
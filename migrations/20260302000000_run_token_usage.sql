@@ -0,0 +1,7 @@
+-- Tokens and cost AI runners report on GRANARY_USAGE lines, summed per run
+-- input_tokens, output_tokens: model tokens
+-- cost_usd: cost in US dollars
+
+ALTER TABLE runs ADD COLUMN input_tokens INTEGER;
+ALTER TABLE runs ADD COLUMN output_tokens INTEGER;
+ALTER TABLE runs ADD COLUMN cost_usd REAL;
@@ -0,0 +1,2 @@
+-- Claims held by a session block other sessions' status changes
+ALTER TABLE tasks ADD COLUMN claim_session_id TEXT;
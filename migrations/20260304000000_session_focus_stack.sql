@@ -0,0 +1,3 @@
+-- Tasks a session set aside with `granary focus push`: a JSON array of
+-- task IDs, bottom first. focus_task_id is its top.
+ALTER TABLE sessions ADD COLUMN focus_stack TEXT;
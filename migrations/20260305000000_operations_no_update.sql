@@ -0,0 +1,16 @@
+-- Recorded operations can only be marked undone, once: who ran a command,
+-- what it was and when can't be rewritten.
+
+CREATE TRIGGER IF NOT EXISTS operations_no_update
+BEFORE UPDATE ON operations
+WHEN OLD.undone_at IS NOT NULL
+    OR NEW.undone_at IS NULL
+    OR NEW.id IS NOT OLD.id
+    OR NEW.command IS NOT OLD.command
+    OR NEW.created_at IS NOT OLD.created_at
+    OR NEW.actor IS NOT OLD.actor
+    OR NEW.session_id IS NOT OLD.session_id
+    OR NEW.undoable IS NOT OLD.undoable
+BEGIN
+    SELECT RAISE(ABORT, 'The operation log is append-only');
+END;
@@ -0,0 +1,559 @@
+//! gRPC API for granaryd.
+//!
+//! Enabled with `granaryd --grpc <addr>` in builds with the `grpc` feature.
+//! The service is defined in `proto/granary.proto`, from which clients in
+//! other languages can be generated. Like the REST API, daemon calls are
+//! translated into the [`Operation`] the CLI sends over IPC and handled by
+//! [`dispatch_request`]; workspace queries open the workspace named in the
+//! request and go through the services layer.
+//!
+//! Every call must carry the daemon auth token as
+//! `authorization: Bearer <token>` metadata. Log following is only
+//! available over IPC.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tonic::transport::Server;
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request as GrpcRequest, Response as GrpcResponse, Status};
+
+use granary::daemon::protocol::{Operation, Request, StartWorkerRequest};
+use granary::daemon::worker_manager::WorkerManager;
+use granary::error::{GranaryError, exit_codes};
+use granary::models::{self, LogFilter};
+use granary::services::{self, Workspace};
+
+use crate::dispatch_request;
+
+pub mod proto {
+    tonic::include_proto!("granary.v1");
+}
+
+use proto::granary_server::{Granary, GranaryServer};
+
+/// Default number of log lines returned by the logs calls
+const DEFAULT_LOG_LINES: i32 = 50;
+
+type GrpcResult<T> = Result<GrpcResponse<T>, Status>;
+
+pub struct GrpcService {
+    manager: Arc<WorkerManager>,
+    next_id: AtomicU64,
+}
+
+/// Serve the gRPC API until a shutdown signal is received
+pub async fn serve(
+    listener: TcpListener,
+    manager: Arc<WorkerManager>,
+    token: String,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<(), tonic::transport::Error> {
+    let service = GrpcService {
+        manager,
+        next_id: AtomicU64::new(1),
+    };
+    let expected = format!("Bearer {}", token);
+    let server = GranaryServer::with_interceptor(service, move |request: GrpcRequest<()>| {
+        let authorized = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| constant_time_eq(value.as_bytes(), expected.as_bytes()));
+        if authorized {
+            Ok(request)
+        } else {
+            tracing::warn!("gRPC request rejected: missing or invalid token");
+            Err(Status::unauthenticated("Authentication failed"))
+        }
+    });
+
+    Server::builder()
+        .add_service(server)
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), async move {
+            while shutdown_rx.changed().await.is_ok() {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        })
+        .await
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl GrpcService {
+    /// Run an operation through the IPC dispatcher, returning its body
+    async fn call(&self, op: Operation) -> Result<serde_json::Value, Status> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (response, _) = dispatch_request(Request::new(id, op), &self.manager).await;
+
+        if response.ok {
+            Ok(response.body.unwrap_or(serde_json::Value::Null))
+        } else {
+            let message = response.error.unwrap_or_default();
+            if message.contains("not found") {
+                Err(Status::not_found(message))
+            } else {
+                Err(Status::invalid_argument(message))
+            }
+        }
+    }
+
+    /// Run an operation and decode its body as `T`
+    async fn call_as<T: serde::de::DeserializeOwned>(&self, op: Operation) -> Result<T, Status> {
+        let body = self.call(op).await?;
+        serde_json::from_value(body).map_err(|e| Status::internal(e.to_string()))
+    }
+
+    async fn call_logs(&self, op: Operation) -> GrpcResult<proto::LogsResponse> {
+        let body = self.call(op).await?;
+        let logs = body["logs"].as_str().unwrap_or_default().to_string();
+        Ok(GrpcResponse::new(proto::LogsResponse { logs }))
+    }
+}
+
+/// Map a services error to a gRPC status
+fn status(error: GranaryError) -> Status {
+    let message = error.to_string();
+    match error.exit_code() {
+        exit_codes::NOT_FOUND => Status::not_found(message),
+        exit_codes::USER_ERROR => Status::invalid_argument(message),
+        _ => Status::internal(message),
+    }
+}
+
+async fn workspace_pool(root: &str) -> Result<sqlx::SqlitePool, Status> {
+    if root.is_empty() {
+        return Err(Status::invalid_argument("workspace is required"));
+    }
+    let workspace = Workspace::open(root).map_err(status)?;
+    workspace.pool().await.map_err(status)
+}
+
+#[tonic::async_trait]
+impl Granary for GrpcService {
+    async fn ping(&self, _: GrpcRequest<proto::PingRequest>) -> GrpcResult<proto::PingResponse> {
+        let body = self.call(Operation::Ping).await?;
+        Ok(GrpcResponse::new(proto::PingResponse {
+            version: body["version"].as_str().unwrap_or_default().to_string(),
+            status: body["status"].as_str().unwrap_or_default().to_string(),
+        }))
+    }
+
+    async fn list_workers(
+        &self,
+        request: GrpcRequest<proto::ListWorkersRequest>,
+    ) -> GrpcResult<proto::ListWorkersResponse> {
+        let all = request.into_inner().all;
+        let workers: Vec<models::Worker> = self.call_as(Operation::ListWorkers { all }).await?;
+        Ok(GrpcResponse::new(proto::ListWorkersResponse {
+            workers: workers.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_worker(
+        &self,
+        request: GrpcRequest<proto::WorkerRequest>,
+    ) -> GrpcResult<proto::Worker> {
+        let worker_id = request.into_inner().worker_id;
+        let worker: models::Worker = self.call_as(Operation::GetWorker { worker_id }).await?;
+        Ok(GrpcResponse::new(worker.into()))
+    }
+
+    async fn start_worker(
+        &self,
+        request: GrpcRequest<proto::StartWorkerRequest>,
+    ) -> GrpcResult<proto::Worker> {
+        let request = request.into_inner();
+        let op = Operation::StartWorker(StartWorkerRequest {
+            runner_name: request.runner_name,
+            command: request.command,
+            args: request.args,
+            event_type: request.event_type,
+            filters: request.filters,
+            concurrency: request.concurrency.unwrap_or(1),
+            instance_path: request.instance_path,
+            schedule: request.schedule,
+            timeout_secs: request.timeout_secs,
+            stdin: request.stdin,
+            global: request.global,
+            ..Default::default()
+        });
+        let worker: models::Worker = self.call_as(op).await?;
+        Ok(GrpcResponse::new(worker.into()))
+    }
+
+    async fn stop_worker(
+        &self,
+        request: GrpcRequest<proto::StopWorkerRequest>,
+    ) -> GrpcResult<proto::Empty> {
+        let request = request.into_inner();
+        let op = Operation::StopWorker {
+            worker_id: request.worker_id,
+            stop_runs: request.stop_runs,
+        };
+        self.call(op).await?;
+        Ok(GrpcResponse::new(proto::Empty {}))
+    }
+
+    async fn pause_worker(
+        &self,
+        request: GrpcRequest<proto::WorkerRequest>,
+    ) -> GrpcResult<proto::Worker> {
+        let worker_id = request.into_inner().worker_id;
+        let worker: models::Worker = self.call_as(Operation::PauseWorker { worker_id }).await?;
+        Ok(GrpcResponse::new(worker.into()))
+    }
+
+    async fn resume_worker(
+        &self,
+        request: GrpcRequest<proto::WorkerRequest>,
+    ) -> GrpcResult<proto::Worker> {
+        let worker_id = request.into_inner().worker_id;
+        let worker: models::Worker = self.call_as(Operation::ResumeWorker { worker_id }).await?;
+        Ok(GrpcResponse::new(worker.into()))
+    }
+
+    async fn prune_workers(
+        &self,
+        _: GrpcRequest<proto::Empty>,
+    ) -> GrpcResult<proto::PruneWorkersResponse> {
+        let body = self.call(Operation::PruneWorkers).await?;
+        Ok(GrpcResponse::new(proto::PruneWorkersResponse {
+            pruned: body["pruned"].as_i64().unwrap_or_default() as i32,
+        }))
+    }
+
+    async fn worker_logs(
+        &self,
+        request: GrpcRequest<proto::WorkerLogsRequest>,
+    ) -> GrpcResult<proto::LogsResponse> {
+        let request = request.into_inner();
+        self.call_logs(Operation::WorkerLogs {
+            worker_id: request.worker_id,
+            follow: false,
+            lines: request.lines.unwrap_or(DEFAULT_LOG_LINES),
+        })
+        .await
+    }
+
+    async fn list_runs(
+        &self,
+        request: GrpcRequest<proto::ListRunsRequest>,
+    ) -> GrpcResult<proto::ListRunsResponse> {
+        let request = request.into_inner();
+        let op = Operation::ListRuns {
+            worker_id: request.worker_id,
+            status: request.status,
+            all: request.all,
+        };
+        let runs: Vec<models::Run> = self.call_as(op).await?;
+        Ok(GrpcResponse::new(proto::ListRunsResponse {
+            runs: runs.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_run(&self, request: GrpcRequest<proto::RunRequest>) -> GrpcResult<proto::Run> {
+        let run_id = request.into_inner().run_id;
+        let run: models::Run = self.call_as(Operation::GetRun { run_id }).await?;
+        Ok(GrpcResponse::new(run.into()))
+    }
+
+    async fn stop_run(&self, request: GrpcRequest<proto::RunRequest>) -> GrpcResult<proto::Empty> {
+        let run_id = request.into_inner().run_id;
+        self.call(Operation::StopRun { run_id }).await?;
+        Ok(GrpcResponse::new(proto::Empty {}))
+    }
+
+    async fn pause_run(&self, request: GrpcRequest<proto::RunRequest>) -> GrpcResult<proto::Empty> {
+        let run_id = request.into_inner().run_id;
+        self.call(Operation::PauseRun { run_id }).await?;
+        Ok(GrpcResponse::new(proto::Empty {}))
+    }
+
+    async fn resume_run(
+        &self,
+        request: GrpcRequest<proto::RunRequest>,
+    ) -> GrpcResult<proto::Empty> {
+        let run_id = request.into_inner().run_id;
+        self.call(Operation::ResumeRun { run_id }).await?;
+        Ok(GrpcResponse::new(proto::Empty {}))
+    }
+
+    async fn retry_run(&self, request: GrpcRequest<proto::RunRequest>) -> GrpcResult<proto::Run> {
+        let run_id = request.into_inner().run_id;
+        let run: models::Run = self.call_as(Operation::RetryRun { run_id }).await?;
+        Ok(GrpcResponse::new(run.into()))
+    }
+
+    async fn run_logs(
+        &self,
+        request: GrpcRequest<proto::RunLogsRequest>,
+    ) -> GrpcResult<proto::LogsResponse> {
+        let request = request.into_inner();
+        let stream = request
+            .stream
+            .map(|s| s.parse())
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        self.call_logs(Operation::RunLogs {
+            run_id: request.run_id,
+            follow: false,
+            lines: request.lines.unwrap_or(DEFAULT_LOG_LINES),
+            filter: LogFilter {
+                stream,
+                since: request.since,
+                until: request.until,
+            },
+        })
+        .await
+    }
+
+    async fn list_projects(
+        &self,
+        request: GrpcRequest<proto::ListProjectsRequest>,
+    ) -> GrpcResult<proto::ListProjectsResponse> {
+        let request = request.into_inner();
+        let pool = workspace_pool(&request.workspace).await?;
+        let projects = services::list_projects(&pool, request.include_archived)
+            .await
+            .map_err(status)?;
+        Ok(GrpcResponse::new(proto::ListProjectsResponse {
+            projects: projects.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: GrpcRequest<proto::ListTasksRequest>,
+    ) -> GrpcResult<proto::ListTasksResponse> {
+        let request = request.into_inner();
+        let pool = workspace_pool(&request.workspace).await?;
+        let mut tasks = match &request.project_id {
+            Some(project_id) => {
+                let project = services::get_project(&pool, project_id)
+                    .await
+                    .map_err(status)?;
+                services::list_tasks_by_project(&pool, &project.id, request.include_archived).await
+            }
+            None => services::list_all_tasks(&pool, request.include_archived).await,
+        }
+        .map_err(status)?;
+        if let Some(status) = &request.status {
+            let status: models::TaskStatus = status.parse().map_err(|_| {
+                Status::invalid_argument(format!("Invalid task status: {}", status))
+            })?;
+            tasks.retain(|t| t.status == status.as_str());
+        }
+        Ok(GrpcResponse::new(proto::ListTasksResponse {
+            tasks: tasks.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_task(
+        &self,
+        request: GrpcRequest<proto::GetTaskRequest>,
+    ) -> GrpcResult<proto::Task> {
+        let request = request.into_inner();
+        let pool = workspace_pool(&request.workspace).await?;
+        let task = services::get_task(&pool, &request.task_id)
+            .await
+            .map_err(status)?;
+        Ok(GrpcResponse::new(task.into()))
+    }
+}
+
+impl From<models::Worker> for proto::Worker {
+    fn from(worker: models::Worker) -> Self {
+        Self {
+            args: worker.args_vec(),
+            filters: worker.filters_vec(),
+            id: worker.id,
+            runner_name: worker.runner_name,
+            command: worker.command,
+            event_type: worker.event_type,
+            concurrency: worker.concurrency,
+            instance_path: worker.instance_path,
+            status: worker.status,
+            error_message: worker.error_message,
+            pid: worker.pid,
+            schedule: worker.schedule,
+            next_fire_at: worker.next_fire_at,
+            created_at: worker.created_at,
+            updated_at: worker.updated_at,
+            stopped_at: worker.stopped_at,
+        }
+    }
+}
+
+impl From<models::Run> for proto::Run {
+    fn from(run: models::Run) -> Self {
+        Self {
+            args: run.args_vec(),
+            id: run.id,
+            worker_id: run.worker_id,
+            event_id: run.event_id,
+            event_type: run.event_type,
+            entity_id: run.entity_id,
+            command: run.command,
+            status: run.status,
+            exit_code: run.exit_code,
+            error_message: run.error_message,
+            attempt: run.attempt,
+            max_attempts: run.max_attempts,
+            next_retry_at: run.next_retry_at,
+            pid: run.pid,
+            log_path: run.log_path,
+            started_at: run.started_at,
+            completed_at: run.completed_at,
+            created_at: run.created_at,
+            updated_at: run.updated_at,
+        }
+    }
+}
+
+impl From<models::Project> for proto::Project {
+    fn from(project: models::Project) -> Self {
+        Self {
+            tags: project.tags_vec(),
+            id: project.id,
+            slug: project.slug,
+            name: project.name,
+            description: project.description,
+            owner: project.owner,
+            status: project.status,
+            created_at: project.created_at,
+            updated_at: project.updated_at,
+        }
+    }
+}
+
+impl From<models::Task> for proto::Task {
+    fn from(task: models::Task) -> Self {
+        Self {
+            tags: task.tags_vec(),
+            id: task.id,
+            project_id: task.project_id,
+            parent_task_id: task.parent_task_id,
+            title: task.title,
+            description: task.description,
+            status: task.status,
+            priority: task.priority,
+            owner: task.owner,
+            labels: task.labels,
+            blocked_reason: task.blocked_reason,
+            due_at: task.due_at,
+            started_at: task.started_at,
+            completed_at: task.completed_at,
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+            fields: task.fields.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use granary::db::connection::{create_pool, run_migrations};
+    use granary::models::CreateProject;
+    use proto::granary_client::GranaryClient;
+    use tonic::transport::Channel;
+
+    const TOKEN: &str = "test-token";
+
+    async fn start() -> (
+        GranaryClient<Channel>,
+        tempfile::TempDir,
+        watch::Sender<bool>,
+    ) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pool = create_pool(&temp_dir.path().join("test.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let manager = Arc::new(WorkerManager::new(pool));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        tokio::spawn(serve(listener, manager, TOKEN.to_string(), shutdown_rx));
+        let client = GranaryClient::connect(url).await.unwrap();
+        (client, temp_dir, shutdown_tx)
+    }
+
+    fn authorized<T>(message: T) -> GrpcRequest<T> {
+        let mut request = GrpcRequest::new(message);
+        request.metadata_mut().insert(
+            "authorization",
+            format!("Bearer {}", TOKEN).parse().unwrap(),
+        );
+        request
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let (mut client, _temp, _shutdown) = start().await;
+
+        let err = client.ping(proto::PingRequest {}).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+        let pong = client
+            .ping(authorized(proto::PingRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(pong.status, "running");
+    }
+
+    #[tokio::test]
+    async fn test_runs_and_workspace_queries() {
+        let (mut client, _temp, _shutdown) = start().await;
+
+        let err = client
+            .get_run(authorized(proto::RunRequest {
+                run_id: "run-missing".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let workspace_dir = tempfile::tempdir().unwrap();
+        let workspace = Workspace::create(workspace_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Automation".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let root = workspace_dir.path().to_string_lossy().to_string();
+
+        let projects = client
+            .list_projects(authorized(proto::ListProjectsRequest {
+                workspace: root.clone(),
+                include_archived: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner()
+            .projects;
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].id, project.id);
+
+        let err = client
+            .get_task(authorized(proto::GetTaskRequest {
+                workspace: root,
+                task_id: format!("{}-task-9", project.id),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+}
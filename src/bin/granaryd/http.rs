@@ -0,0 +1,379 @@
+//! REST API for granaryd.
+//!
+//! Enabled with `granaryd --http <addr>`. Each route is translated into the
+//! same [`Operation`] the CLI sends over IPC and handled by
+//! [`dispatch_request`], so both transports behave identically.
+//!
+//! Every request must carry the daemon auth token (`~/.granary/daemon/auth.token`)
+//! as `Authorization: Bearer <token>`. Log following and daemon shutdown are
+//! only available over IPC.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use axum::extract::{Path, Query, Request as HttpRequest, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response as HttpResponse};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+use granary::daemon::protocol::{Operation, Request, StartWorkerRequest};
+use granary::daemon::worker_manager::WorkerManager;
+use granary::models::{LogFilter, LogStream};
+
+use crate::dispatch_request;
+
+/// Default number of log lines returned by the logs endpoints
+const DEFAULT_LOG_LINES: i32 = 50;
+
+struct HttpState {
+    manager: Arc<WorkerManager>,
+    token: String,
+    next_id: AtomicU64,
+}
+
+#[derive(Deserialize)]
+struct AllQuery {
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Deserialize)]
+struct StopWorkerQuery {
+    #[serde(default)]
+    stop_runs: bool,
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    lines: Option<i32>,
+}
+
+#[derive(Deserialize)]
+struct RunLogsQuery {
+    lines: Option<i32>,
+    stream: Option<LogStream>,
+    since: Option<String>,
+    until: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ListRunsQuery {
+    worker_id: Option<String>,
+    status: Option<String>,
+    #[serde(default)]
+    all: bool,
+}
+
+/// Build the REST API router
+pub fn router(manager: Arc<WorkerManager>, token: String) -> Router {
+    let state = Arc::new(HttpState {
+        manager,
+        token,
+        next_id: AtomicU64::new(1),
+    });
+
+    Router::new()
+        .route("/v1/ping", get(ping))
+        .route("/v1/workers", get(list_workers).post(start_worker))
+        .route("/v1/workers/prune", post(prune_workers))
+        .route("/v1/workers/{id}", get(get_worker).delete(stop_worker))
+        .route("/v1/workers/{id}/pause", post(pause_worker))
+        .route("/v1/workers/{id}/resume", post(resume_worker))
+        .route("/v1/workers/{id}/logs", get(worker_logs))
+        .route("/v1/runs", get(list_runs))
+        .route("/v1/runs/{id}", get(get_run))
+        .route("/v1/runs/{id}/stop", post(stop_run))
+        .route("/v1/runs/{id}/pause", post(pause_run))
+        .route("/v1/runs/{id}/resume", post(resume_run))
+        .route("/v1/runs/{id}/retry", post(retry_run))
+        .route("/v1/runs/{id}/logs", get(run_logs))
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            require_token,
+        ))
+        .with_state(state)
+}
+
+/// Serve the REST API until a shutdown signal is received
+pub async fn serve(
+    listener: TcpListener,
+    manager: Arc<WorkerManager>,
+    token: String,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(manager, token))
+        .with_graceful_shutdown(async move {
+            while shutdown_rx.changed().await.is_ok() {
+                if *shutdown_rx.borrow() {
+                    break;
+                }
+            }
+        })
+        .await
+}
+
+/// Reject requests without a valid bearer token
+async fn require_token(
+    State(state): State<Arc<HttpState>>,
+    request: HttpRequest,
+    next: Next,
+) -> HttpResponse {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), state.token.as_bytes()));
+
+    if !authorized {
+        tracing::warn!("HTTP request rejected: missing or invalid token");
+        return error_response(StatusCode::UNAUTHORIZED, "Authentication failed");
+    }
+    next.run(request).await
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn error_response(status: StatusCode, message: &str) -> HttpResponse {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Run an operation through the IPC dispatcher and map the result to HTTP
+async fn call(state: &HttpState, op: Operation) -> HttpResponse {
+    let id = state.next_id.fetch_add(1, Ordering::Relaxed);
+    let (response, _) = dispatch_request(Request::new(id, op), &state.manager).await;
+
+    if response.ok {
+        match response.body {
+            Some(body) => Json(body).into_response(),
+            None => StatusCode::NO_CONTENT.into_response(),
+        }
+    } else {
+        let message = response.error.unwrap_or_default();
+        let status = if message.contains("not found") {
+            StatusCode::NOT_FOUND
+        } else {
+            StatusCode::BAD_REQUEST
+        };
+        error_response(status, &message)
+    }
+}
+
+async fn ping(State(state): State<Arc<HttpState>>) -> HttpResponse {
+    call(&state, Operation::Ping).await
+}
+
+async fn list_workers(
+    State(state): State<Arc<HttpState>>,
+    Query(query): Query<AllQuery>,
+) -> HttpResponse {
+    call(&state, Operation::ListWorkers { all: query.all }).await
+}
+
+async fn start_worker(
+    State(state): State<Arc<HttpState>>,
+    Json(request): Json<StartWorkerRequest>,
+) -> HttpResponse {
+    call(&state, Operation::StartWorker(request)).await
+}
+
+async fn prune_workers(State(state): State<Arc<HttpState>>) -> HttpResponse {
+    call(&state, Operation::PruneWorkers).await
+}
+
+async fn get_worker(
+    State(state): State<Arc<HttpState>>,
+    Path(worker_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::GetWorker { worker_id }).await
+}
+
+async fn stop_worker(
+    State(state): State<Arc<HttpState>>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<StopWorkerQuery>,
+) -> HttpResponse {
+    let op = Operation::StopWorker {
+        worker_id,
+        stop_runs: query.stop_runs,
+    };
+    call(&state, op).await
+}
+
+async fn pause_worker(
+    State(state): State<Arc<HttpState>>,
+    Path(worker_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::PauseWorker { worker_id }).await
+}
+
+async fn resume_worker(
+    State(state): State<Arc<HttpState>>,
+    Path(worker_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::ResumeWorker { worker_id }).await
+}
+
+async fn worker_logs(
+    State(state): State<Arc<HttpState>>,
+    Path(worker_id): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> HttpResponse {
+    let op = Operation::WorkerLogs {
+        worker_id,
+        follow: false,
+        lines: query.lines.unwrap_or(DEFAULT_LOG_LINES),
+    };
+    call(&state, op).await
+}
+
+async fn list_runs(
+    State(state): State<Arc<HttpState>>,
+    Query(query): Query<ListRunsQuery>,
+) -> HttpResponse {
+    let op = Operation::ListRuns {
+        worker_id: query.worker_id,
+        status: query.status,
+        all: query.all,
+    };
+    call(&state, op).await
+}
+
+async fn get_run(State(state): State<Arc<HttpState>>, Path(run_id): Path<String>) -> HttpResponse {
+    call(&state, Operation::GetRun { run_id }).await
+}
+
+async fn stop_run(State(state): State<Arc<HttpState>>, Path(run_id): Path<String>) -> HttpResponse {
+    call(&state, Operation::StopRun { run_id }).await
+}
+
+async fn pause_run(
+    State(state): State<Arc<HttpState>>,
+    Path(run_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::PauseRun { run_id }).await
+}
+
+async fn resume_run(
+    State(state): State<Arc<HttpState>>,
+    Path(run_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::ResumeRun { run_id }).await
+}
+
+async fn retry_run(
+    State(state): State<Arc<HttpState>>,
+    Path(run_id): Path<String>,
+) -> HttpResponse {
+    call(&state, Operation::RetryRun { run_id }).await
+}
+
+async fn run_logs(
+    State(state): State<Arc<HttpState>>,
+    Path(run_id): Path<String>,
+    Query(query): Query<RunLogsQuery>,
+) -> HttpResponse {
+    let op = Operation::RunLogs {
+        run_id,
+        follow: false,
+        lines: query.lines.unwrap_or(DEFAULT_LOG_LINES),
+        filter: LogFilter {
+            stream: query.stream,
+            since: query.since,
+            until: query.until,
+        },
+    };
+    call(&state, op).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use granary::db::connection::{create_pool, run_migrations};
+
+    const TOKEN: &str = "test-token";
+
+    async fn start() -> (String, tempfile::TempDir, watch::Sender<bool>) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pool = create_pool(&temp_dir.path().join("test.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let manager = Arc::new(WorkerManager::new(pool));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        tokio::spawn(serve(listener, manager, TOKEN.to_string(), shutdown_rx));
+        (base, temp_dir, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn test_requires_token() {
+        let (base, _temp, _shutdown) = start().await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get(format!("{}/v1/ping", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = client
+            .get(format!("{}/v1/ping", base))
+            .bearer_auth("wrong")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = client
+            .get(format!("{}/v1/ping", base))
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["status"], "running");
+    }
+
+    #[tokio::test]
+    async fn test_workers_and_runs() {
+        let (base, _temp, _shutdown) = start().await;
+        let client = reqwest::Client::new();
+
+        let workers: Vec<serde_json::Value> = client
+            .get(format!("{}/v1/workers?all=true", base))
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(workers.is_empty());
+
+        let response = client
+            .get(format!("{}/v1/runs/run-missing", base))
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = client
+            .get(format!("{}/v1/runs?status=running", base))
+            .bearer_auth(TOKEN)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
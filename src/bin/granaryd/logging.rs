@@ -0,0 +1,220 @@
+//! Structured file logging for granaryd.
+//!
+//! Events are written as JSON lines to `~/.granary/daemon/daemon.log`, which
+//! `granary daemon logs` reads and pretty-prints. The file is rotated when it
+//! grows past [`MAX_LOG_BYTES`] or when the day changes: `daemon.log` becomes
+//! `daemon.log.1`, older files shift up, and at most [`MAX_ROTATED_FILES`]
+//! rotated files are kept.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::EnvFilter;
+
+/// Name of the active log file inside the daemon directory
+pub const LOG_FILE_NAME: &str = "daemon.log";
+
+/// Size at which the active log file is rotated
+pub const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Number of rotated log files kept next to the active one
+pub const MAX_ROTATED_FILES: usize = 5;
+
+/// Default filter when neither `--log-level` nor `RUST_LOG` is set
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Initialize JSON logging to the daemon log file.
+///
+/// `level` accepts a level (`debug`) or a full filter directive
+/// (`granary=debug,sqlx=warn`). When it is `None`, `RUST_LOG` is used,
+/// falling back to [`DEFAULT_LOG_LEVEL`].
+///
+/// The returned `WorkerGuard` must be kept alive for the duration of the
+/// program to ensure all logs are flushed.
+pub fn init(daemon_dir: &Path, level: Option<&str>) -> anyhow::Result<WorkerGuard> {
+    let filter = match level {
+        Some(level) => parse_filter(level)?,
+        None => {
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_LEVEL))
+        }
+    };
+
+    let file = RotatingFile::open(daemon_dir, MAX_LOG_BYTES, MAX_ROTATED_FILES)?;
+
+    // Make it non-blocking so logging never stalls request handling
+    let (non_blocking, guard) = tracing_appender::non_blocking(file);
+
+    tracing_subscriber::fmt()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_span_list(false)
+        .with_target(true)
+        .with_env_filter(filter)
+        .with_writer(non_blocking)
+        .init();
+
+    Ok(guard)
+}
+
+/// Parse a `--log-level` value.
+///
+/// A bare word must be a level name; otherwise `verbose` would silently be
+/// read as a target filter and disable all other logging.
+fn parse_filter(level: &str) -> anyhow::Result<EnvFilter> {
+    const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error", "off"];
+
+    let is_directive = level.contains('=') || level.contains(',');
+    if !is_directive && !LEVELS.contains(&level.to_lowercase().as_str()) {
+        anyhow::bail!(
+            "Invalid log level '{}'. Expected one of: {}, or a filter like 'granary=debug'",
+            level,
+            LEVELS.join(", ")
+        );
+    }
+    EnvFilter::try_new(level).map_err(|e| anyhow::anyhow!("Invalid log level '{}': {}", level, e))
+}
+
+/// A log file that rotates by size and by day.
+pub struct RotatingFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+    opened_on: NaiveDate,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    /// Open (or create) the active log file in `dir` for appending
+    pub fn open(dir: &Path, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE_NAME);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        let opened_on = metadata
+            .modified()
+            .map(|t| chrono::DateTime::<Local>::from(t).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            file,
+            size: metadata.len(),
+            opened_on,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            self.dir.join(LOG_FILE_NAME)
+        } else {
+            self.dir.join(format!("{}.{}", LOG_FILE_NAME, index))
+        }
+    }
+
+    /// Rotate before writing `incoming` bytes if the file is too large or
+    /// was started on an earlier day
+    fn rotate_if_needed(&mut self, incoming: u64, today: NaiveDate) -> io::Result<()> {
+        let too_large = self.size > 0 && self.size + incoming > self.max_bytes;
+        let new_day = today != self.opened_on && self.size > 0;
+        if !too_large && !new_day {
+            return Ok(());
+        }
+
+        // Shift daemon.log.N-1 -> daemon.log.N, ..., daemon.log -> daemon.log.1.
+        // Renames can fail on Windows while a reader holds the file open; in
+        // that case keep appending to the current file.
+        let _ = fs::remove_file(self.path(self.keep));
+        for index in (0..self.keep).rev() {
+            let from = self.path(index);
+            if from.exists() && fs::rename(&from, self.path(index + 1)).is_err() && index == 0 {
+                self.opened_on = today;
+                return Ok(());
+            }
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(0))?;
+        self.size = 0;
+        self.opened_on = today;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.rotate_if_needed(buf.len() as u64, Local::now().date_naive())?;
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotated(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_rotates_by_size_and_keeps_limit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingFile::open(temp_dir.path(), 10, 2).unwrap();
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(
+            rotated(temp_dir.path()),
+            ["daemon.log", "daemon.log.1", "daemon.log.2"]
+        );
+        let read = |name: &str| fs::read_to_string(temp_dir.path().join(name)).unwrap();
+        assert_eq!(read("daemon.log"), "fourth\n");
+        assert_eq!(read("daemon.log.1"), "third\n");
+        assert_eq!(read("daemon.log.2"), "second\n");
+    }
+
+    #[test]
+    fn test_rotates_on_new_day() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingFile::open(temp_dir.path(), MAX_LOG_BYTES, 2).unwrap();
+        file.write_all(b"yesterday\n").unwrap();
+
+        let tomorrow = file.opened_on.succ_opt().unwrap();
+        file.rotate_if_needed(6, tomorrow).unwrap();
+        file.write_all(b"today\n").unwrap();
+
+        let read = |name: &str| fs::read_to_string(temp_dir.path().join(name)).unwrap();
+        assert_eq!(read("daemon.log"), "today\n");
+        assert_eq!(read("daemon.log.1"), "yesterday\n");
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert!(parse_filter("debug").is_ok());
+        assert!(parse_filter("WARN").is_ok());
+        assert!(parse_filter("granary=debug,sqlx=warn").is_ok());
+        assert!(parse_filter("verbose").is_err());
+        assert!(parse_filter("granary=loud").is_err());
+    }
+}
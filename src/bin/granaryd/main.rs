@@ -0,0 +1,765 @@
+//! Granary daemon - manages worker lifecycles via IPC.
+//!
+//! The granaryd binary is a long-running background process that:
+//! - Accepts IPC connections from the CLI over Unix domain sockets (Unix) or
+//!   named pipes (Windows)
+//! - Manages worker lifecycles (start, stop, query)
+//! - Handles graceful shutdown on SIGTERM/SIGINT (Unix) or Ctrl+C, console
+//!   close and system shutdown (Windows)
+//! - Restores workers that were running before the daemon stopped
+//! - Delivers workspace events to configured webhooks
+//! - Optionally serves the same operations over a REST API (`--http <addr>`)
+//!   and, in builds with the `grpc` feature, a gRPC API (`--grpc <addr>`)
+//!
+//! ## Usage
+//!
+//! The daemon is typically started automatically by the CLI when needed.
+//! `granary daemon install` registers it with systemd, launchd or Task
+//! Scheduler to start on login instead.
+//! Manual start: `granaryd`, or `granaryd --http 127.0.0.1:7878` to also
+//! expose the REST API (authenticated with the same token as IPC). Use
+//! `--log-level debug` (or `RUST_LOG`) for more verbose logs.
+//!
+//! ## Files
+//!
+//! - `~/.granary/daemon/granaryd.sock` - Unix socket for IPC (Unix)
+//! - `\\.\pipe\granaryd-{username}` - Named pipe for IPC (Windows)
+//! - `~/.granary/daemon/granaryd.pid` - PID file for process tracking
+//! - `~/.granary/daemon/daemon.log` - Daemon log file (JSON lines, rotated to `daemon.log.N`)
+
+#[cfg(feature = "grpc")]
+mod grpc;
+mod http;
+mod logging;
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use tokio::select;
+
+use granary::daemon::IpcConnection;
+use granary::daemon::auto_start::daemon_pid;
+use granary::daemon::backups::BackupScheduler;
+use granary::daemon::listener::IpcListener;
+use granary::daemon::notifications::NotificationDispatcher;
+use granary::daemon::protocol::{LogTarget, LogsResponse, Operation, Request, Response};
+use granary::daemon::reminders::ReminderScheduler;
+use granary::daemon::rules::RuleScheduler;
+use granary::daemon::transport::IpcEndpoint;
+use granary::daemon::webhooks::WebhookDispatcher;
+use granary::daemon::worker_manager::WorkerManager;
+use granary::models::LogFilter;
+use granary::services::global_config as global_config_service;
+
+/// Command-line options for granaryd
+#[derive(Parser)]
+#[command(name = "granaryd", version, about = "Granary daemon")]
+struct Args {
+    /// Serve the REST API on this address (e.g., 127.0.0.1:7878)
+    #[arg(long)]
+    http: Option<SocketAddr>,
+
+    /// Serve the gRPC API on this address (e.g., 127.0.0.1:7879). Requires the `grpc` feature
+    #[arg(long)]
+    grpc: Option<SocketAddr>,
+
+    /// Log level or filter (e.g., debug, granary=trace). Defaults to RUST_LOG, then info
+    #[arg(long)]
+    log_level: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    if args.grpc.is_some() && !cfg!(feature = "grpc") {
+        anyhow::bail!(
+            "granaryd was built without the `grpc` feature. Rebuild with `cargo install granary --features grpc`"
+        );
+    }
+
+    // Ensure daemon directory exists
+    let daemon_dir = global_config_service::daemon_dir()?;
+    std::fs::create_dir_all(&daemon_dir)?;
+
+    // Initialize logging to daemon log file
+    let _guard = logging::init(&daemon_dir, args.log_level.as_deref())?;
+
+    tracing::info!("granaryd starting, version {}", env!("CARGO_PKG_VERSION"));
+
+    // Pre-generate the auth token so clients can read it before connecting
+    // The token is created at ~/.granary/daemon/auth.token with 0600 permissions
+    let auth_token = global_config_service::get_or_create_auth_token()?;
+    tracing::debug!(
+        "Auth token ready at {:?}",
+        global_config_service::daemon_auth_token_path()?
+    );
+
+    // Refuse to start next to a live daemon. On Unix, binding would replace
+    // its socket; on Windows, creating the pipe would fail.
+    let endpoint = IpcEndpoint::daemon()?;
+    if let Some(pid) = daemon_pid()
+        && pid != std::process::id()
+        && endpoint.connect().await.is_ok()
+    {
+        tracing::error!("granaryd is already running (PID {})", pid);
+        anyhow::bail!("granaryd is already running (PID {})", pid);
+    }
+
+    // Write PID file, replacing any stale one
+    let pid_path = global_config_service::daemon_pid_path()?;
+    std::fs::write(&pid_path, std::process::id().to_string())?;
+
+    // Open global database
+    let global_pool = global_config_service::global_pool().await?;
+
+    // Create worker manager
+    let manager = Arc::new(WorkerManager::new(global_pool.clone()));
+
+    // Restore workers that were running before daemon stopped
+    if let Err(e) = manager.restore_workers().await {
+        tracing::warn!("Failed to restore workers: {}", e);
+    }
+
+    // Background services stop when this is set
+    let (background_shutdown_tx, background_shutdown_rx) = tokio::sync::watch::channel(false);
+
+    // Start webhook delivery, desktop notifications, daily backups, rules and
+    // due date reminders
+    let webhook_task = tokio::spawn(
+        WebhookDispatcher::new(global_pool.clone()).run(background_shutdown_rx.clone()),
+    );
+    let backup_task =
+        tokio::spawn(BackupScheduler::new(global_pool.clone()).run(background_shutdown_rx.clone()));
+    let rule_task =
+        tokio::spawn(RuleScheduler::new(global_pool.clone()).run(background_shutdown_rx.clone()));
+    let reminder_task = tokio::spawn(
+        ReminderScheduler::new(global_pool.clone()).run(background_shutdown_rx.clone()),
+    );
+    let notification_task =
+        tokio::spawn(NotificationDispatcher::new(global_pool).run(background_shutdown_rx.clone()));
+
+    // Start the gRPC API if requested
+    #[cfg(feature = "grpc")]
+    let grpc_task = match args.grpc {
+        Some(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            tracing::info!("granaryd serving gRPC API on http://{}", addr);
+            if !addr.ip().is_loopback() {
+                tracing::warn!(
+                    "gRPC API is bound to a non-loopback address; the auth token is sent in plain text"
+                );
+            }
+            let manager = Arc::clone(&manager);
+            let token = auth_token.clone();
+            let shutdown_rx = background_shutdown_rx.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = grpc::serve(listener, manager, token, shutdown_rx).await {
+                    tracing::error!("gRPC API error: {}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+
+    // Start the REST API if requested
+    let http_task = match args.http {
+        Some(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            tracing::info!("granaryd serving REST API on http://{}", addr);
+            if !addr.ip().is_loopback() {
+                tracing::warn!(
+                    "REST API is bound to a non-loopback address; the auth token is sent in plain text"
+                );
+            }
+            let manager = Arc::clone(&manager);
+            let shutdown_rx = background_shutdown_rx.clone();
+            Some(tokio::spawn(async move {
+                if let Err(e) = http::serve(listener, manager, auth_token, shutdown_rx).await {
+                    tracing::error!("REST API error: {}", e);
+                }
+            }))
+        }
+        None => None,
+    };
+
+    // Start IPC listener
+    let mut listener = IpcListener::bind(&endpoint).await?;
+    tracing::info!("granaryd listening on {}", listener.endpoint());
+
+    // Set up signal handlers
+    let shutdown = shutdown_signal()?;
+    tokio::pin!(shutdown);
+
+    // Flag to track shutdown request from IPC
+    let shutdown_flag = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    // Set up log cleanup interval (every hour)
+    let mut cleanup_interval = tokio::time::interval(Duration::from_secs(3600));
+    // Skip the first immediate tick
+    cleanup_interval.tick().await;
+
+    // Run initial log cleanup on startup
+    prune_logs(&manager, "Initial").await;
+
+    // Main loop
+    loop {
+        // Check if shutdown was requested via IPC
+        if shutdown_flag.load(std::sync::atomic::Ordering::SeqCst) {
+            tracing::info!("Shutdown requested via IPC");
+            break;
+        }
+
+        select! {
+            // Handle shutdown signals
+            signal = &mut shutdown => {
+                tracing::info!("Received {}, shutting down...", signal);
+                break;
+            }
+
+            // Periodic log cleanup
+            _ = cleanup_interval.tick() => {
+                prune_logs(&manager, "Periodic").await;
+            }
+
+            // Accept new connections
+            result = listener.accept() => {
+                match result {
+                    Ok(conn) => {
+                        let manager = Arc::clone(&manager);
+                        let shutdown_flag = Arc::clone(&shutdown_flag);
+                        tokio::spawn(async move {
+                            if let Err(e) = handle_connection(conn, &manager, &shutdown_flag).await {
+                                tracing::error!("Connection error: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        tracing::error!("Accept error: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Graceful shutdown
+    tracing::info!("Shutting down workers...");
+    manager.shutdown_all().await?;
+
+    let _ = background_shutdown_tx.send(true);
+    let _ = webhook_task.await;
+    let _ = notification_task.await;
+    let _ = backup_task.await;
+    let _ = rule_task.await;
+    let _ = reminder_task.await;
+    if let Some(task) = http_task {
+        let _ = task.await;
+    }
+    #[cfg(feature = "grpc")]
+    if let Some(task) = grpc_task {
+        let _ = task.await;
+    }
+
+    // Clean up PID file, unless another daemon has taken it over
+    if daemon_pid() == Some(std::process::id()) {
+        let _ = std::fs::remove_file(&pid_path);
+    }
+
+    tracing::info!("granaryd shutdown complete");
+    Ok(())
+}
+
+/// Enforce the log retention policy, logging what was removed
+async fn prune_logs(manager: &WorkerManager, kind: &str) {
+    match manager.prune_logs(false).await {
+        Ok(report) if !report.pruned.is_empty() => {
+            tracing::info!(
+                "{} log cleanup: deleted {} logs ({} bytes)",
+                kind,
+                report.pruned.len(),
+                report.freed
+            );
+        }
+        Err(e) => {
+            tracing::warn!("{} log cleanup failed: {}", kind, e);
+        }
+        _ => {}
+    }
+}
+
+/// Wait for the OS to ask the daemon to stop.
+///
+/// Resolves with the name of the signal: SIGTERM or SIGINT on Unix; Ctrl+C,
+/// console close or system shutdown on Windows.
+#[cfg(unix)]
+fn shutdown_signal() -> std::io::Result<impl Future<Output = &'static str>> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sigint = signal(SignalKind::interrupt())?;
+    Ok(async move {
+        select! {
+            _ = sigterm.recv() => "SIGTERM",
+            _ = sigint.recv() => "SIGINT",
+        }
+    })
+}
+
+/// Wait for the OS to ask the daemon to stop.
+///
+/// Resolves with the name of the signal: SIGTERM or SIGINT on Unix; Ctrl+C,
+/// console close or system shutdown on Windows.
+#[cfg(windows)]
+fn shutdown_signal() -> std::io::Result<impl Future<Output = &'static str>> {
+    use tokio::signal::windows::{ctrl_c, ctrl_close, ctrl_shutdown};
+
+    let mut ctrl_c = ctrl_c()?;
+    let mut close = ctrl_close()?;
+    let mut shutdown = ctrl_shutdown()?;
+    Ok(async move {
+        select! {
+            _ = ctrl_c.recv() => "Ctrl+C",
+            _ = close.recv() => "console close",
+            _ = shutdown.recv() => "system shutdown",
+        }
+    })
+}
+
+/// Handle a single client connection.
+///
+/// Processes requests in a loop until the connection is closed or
+/// a shutdown operation is received.
+///
+/// The first message must be an Auth operation with a valid token.
+/// Connections that fail authentication are rejected.
+async fn handle_connection(
+    mut conn: IpcConnection,
+    manager: &WorkerManager,
+    shutdown_flag: &std::sync::atomic::AtomicBool,
+) -> anyhow::Result<()> {
+    // First message must be authentication
+    let auth_request = match conn.recv_request().await {
+        Ok(req) => req,
+        Err(_) => return Ok(()), // Connection closed before auth
+    };
+
+    match &auth_request.op {
+        Operation::Auth(auth) => {
+            let expected = global_config_service::get_or_create_auth_token()?;
+            if auth.token != expected {
+                tracing::warn!("Authentication failed: invalid token");
+                conn.send_response(&Response::err(auth_request.id, "Authentication failed"))
+                    .await?;
+                return Ok(());
+            }
+            conn.send_response(&Response::ok_empty(auth_request.id))
+                .await?;
+            tracing::debug!("Client authenticated successfully");
+        }
+        _ => {
+            tracing::warn!("First message was not Auth, rejecting connection");
+            conn.send_response(&Response::err(
+                auth_request.id,
+                "First message must be Auth",
+            ))
+            .await?;
+            return Ok(());
+        }
+    }
+
+    // Continue with normal request loop after successful authentication
+    loop {
+        let request = match conn.recv_request().await {
+            Ok(req) => req,
+            Err(_) => break, // Connection closed
+        };
+
+        // Follow requests stream responses until the target finishes or the
+        // client disconnects, so they bypass the one-shot dispatcher
+        match &request.op {
+            Operation::WorkerLogs {
+                worker_id,
+                follow: true,
+                lines,
+            } => {
+                stream_logs(
+                    &mut conn,
+                    manager,
+                    request.id,
+                    worker_id,
+                    LogTarget::Worker,
+                    *lines,
+                    &LogFilter::default(),
+                )
+                .await?;
+                continue;
+            }
+            Operation::RunLogs {
+                run_id,
+                follow: true,
+                lines,
+                filter,
+            } => {
+                stream_logs(
+                    &mut conn,
+                    manager,
+                    request.id,
+                    run_id,
+                    LogTarget::Run,
+                    *lines,
+                    filter,
+                )
+                .await?;
+                continue;
+            }
+            _ => {}
+        }
+
+        let (response, should_shutdown) = dispatch_request(request, manager).await;
+        conn.send_response(&response).await?;
+
+        if should_shutdown {
+            // Signal the main loop to shutdown
+            shutdown_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Dispatch a request to the appropriate handler.
+///
+/// Returns the response and a flag indicating if the daemon should shutdown.
+async fn dispatch_request(request: Request, manager: &WorkerManager) -> (Response, bool) {
+    let id = request.id;
+
+    match request.op {
+        // Auth is only valid as the first message; reject if sent later
+        Operation::Auth(_) => {
+            let response = Response::err(id, "Auth is only valid as the first message");
+            (response, false)
+        }
+
+        Operation::Ping => {
+            let response = Response::ok(
+                id,
+                serde_json::json!({
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "status": "running"
+                }),
+            );
+            (response, false)
+        }
+
+        Operation::Shutdown => {
+            // Return acknowledgment, then daemon will shutdown
+            let response = Response::ok(id, "shutdown_ack");
+            (response, true)
+        }
+
+        Operation::StartWorker(req) => {
+            let create = granary::models::worker::CreateWorker {
+                runner_name: req.runner_name,
+                command: req.command,
+                args: req.args,
+                event_type: req.event_type,
+                filters: req.filters,
+                concurrency: req.concurrency,
+                instance_path: req.instance_path,
+                poll_cooldown_secs: req.poll_cooldown_secs.unwrap_or(300),
+                detached: !req.attach,
+                schedule: req.schedule,
+                retry_policy: req.retry_policy,
+                timeout_secs: req.timeout_secs,
+                debounce_secs: req.debounce_secs,
+                throttle_secs: req.throttle_secs,
+                stdin: req.stdin,
+                global: req.global,
+                on_pause: req.on_pause,
+            };
+
+            match manager.start_worker(create).await {
+                Ok(worker) => {
+                    let response = Response::ok(id, &worker);
+                    (response, false)
+                }
+                Err(e) => {
+                    let response = Response::err(id, e.to_string());
+                    (response, false)
+                }
+            }
+        }
+
+        Operation::StopWorker {
+            worker_id,
+            stop_runs,
+        } => match manager.stop_worker(&worker_id, stop_runs).await {
+            Ok(_) => (Response::ok_empty(id), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::PauseWorker { worker_id } => match manager.pause_worker(&worker_id).await {
+            Ok(worker) => (Response::ok(id, &worker), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::ResumeWorker { worker_id } => match manager.resume_worker(&worker_id).await {
+            Ok(worker) => (Response::ok(id, &worker), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::GetWorker { worker_id } => match manager.get_worker(&worker_id).await {
+            Ok(Some(worker)) => (Response::ok(id, &worker), false),
+            Ok(None) => (
+                Response::err(id, format!("Worker {} not found", worker_id)),
+                false,
+            ),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::ListWorkers { all } => match manager.list_workers(all).await {
+            Ok(workers) => (Response::ok(id, &workers), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::PruneWorkers => match manager.prune_workers().await {
+            Ok(pruned) => (
+                Response::ok(id, serde_json::json!({ "pruned": pruned })),
+                false,
+            ),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        // Follow mode is streamed by handle_connection
+        Operation::WorkerLogs {
+            worker_id, lines, ..
+        } => {
+            // Non-follow mode: get worker log path and read logs (simple string response)
+            match manager.get_worker_log_path(&worker_id) {
+                Ok(path) => {
+                    if path.exists() {
+                        match read_log_tail(&path, lines as usize) {
+                            Ok(logs) => {
+                                (Response::ok(id, serde_json::json!({ "logs": logs })), false)
+                            }
+                            Err(e) => (
+                                Response::err(id, format!("Failed to read logs: {}", e)),
+                                false,
+                            ),
+                        }
+                    } else {
+                        (
+                            Response::ok(
+                                id,
+                                serde_json::json!({ "logs": "", "message": "No log file found" }),
+                            ),
+                            false,
+                        )
+                    }
+                }
+                Err(e) => (Response::err(id, e.to_string()), false),
+            }
+        }
+
+        Operation::GetRun { run_id } => match manager.get_run(&run_id).await {
+            Ok(Some(run)) => (Response::ok(id, &run), false),
+            Ok(None) => (
+                Response::err(id, format!("Run {} not found", run_id)),
+                false,
+            ),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::ListRuns {
+            worker_id,
+            status,
+            all,
+        } => {
+            match manager
+                .list_runs(worker_id.as_deref(), status.as_deref(), all)
+                .await
+            {
+                Ok(runs) => (Response::ok(id, &runs), false),
+                Err(e) => (Response::err(id, e.to_string()), false),
+            }
+        }
+
+        Operation::StopRun { run_id } => match manager.stop_run(&run_id).await {
+            Ok(()) => (Response::ok_empty(id), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::PauseRun { run_id } => match manager.pause_run(&run_id).await {
+            Ok(()) => (Response::ok_empty(id), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::ResumeRun { run_id } => match manager.resume_run(&run_id).await {
+            Ok(()) => (Response::ok_empty(id), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        Operation::RetryRun { run_id } => match manager.retry_run(&run_id).await {
+            Ok(run) => (Response::ok(id, &run), false),
+            Err(e) => (Response::err(id, e.to_string()), false),
+        },
+
+        // Follow mode is streamed by handle_connection
+        Operation::RunLogs {
+            run_id,
+            lines,
+            filter,
+            ..
+        } if !filter.is_empty() => {
+            match manager
+                .get_logs(&run_id, LogTarget::Run, 0, u64::MAX, &filter)
+                .await
+            {
+                Ok(response) => {
+                    let skip = response.lines.len().saturating_sub(lines.max(0) as usize);
+                    let logs = response.lines[skip..].join("\n");
+                    (Response::ok(id, serde_json::json!({ "logs": logs })), false)
+                }
+                Err(e) => (Response::err(id, e.to_string()), false),
+            }
+        }
+
+        Operation::RunLogs { run_id, lines, .. } => {
+            // Non-follow mode: get log path and read logs (simple string response)
+            match manager.get_run_log_path(&run_id).await {
+                Ok(Some(path)) => {
+                    // Read last N lines from log file
+                    match read_log_tail(&path, lines as usize) {
+                        Ok(logs) => (Response::ok(id, serde_json::json!({ "logs": logs })), false),
+                        Err(e) => (
+                            Response::err(id, format!("Failed to read logs: {}", e)),
+                            false,
+                        ),
+                    }
+                }
+                Ok(None) => (
+                    Response::ok(
+                        id,
+                        serde_json::json!({ "logs": "", "message": "No log file found" }),
+                    ),
+                    false,
+                ),
+                Err(e) => (Response::err(id, e.to_string()), false),
+            }
+        }
+
+        Operation::GetLogs(req) => {
+            match manager
+                .get_logs(
+                    &req.target_id,
+                    req.target_type,
+                    req.since_line,
+                    req.limit,
+                    &req.filter,
+                )
+                .await
+            {
+                Ok(response) => (Response::ok(id, &response), false),
+                Err(e) => (Response::err(id, e.to_string()), false),
+            }
+        }
+    }
+}
+
+/// Interval between checks for new log lines while following
+const LOG_FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Send an empty chunk after this long without output, so that a client
+/// that has gone away is detected by the failed write
+const LOG_FOLLOW_KEEPALIVE: Duration = Duration::from_secs(5);
+
+/// Stream a worker or run log to the client, like `tail -f`.
+///
+/// Sends the last `lines` lines that pass `filter`, then pushes new lines as
+/// they are written.
+/// The stream ends when the target is no longer active and its log has been
+/// drained, or silently when the client disconnects.
+async fn stream_logs(
+    conn: &mut IpcConnection,
+    manager: &WorkerManager,
+    id: u64,
+    target_id: &str,
+    target_type: LogTarget,
+    lines: i32,
+    filter: &LogFilter,
+) -> anyhow::Result<()> {
+    let initial = match manager
+        .get_logs(target_id, target_type.clone(), 0, u64::MAX, filter)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            conn.send_response(&Response::err(id, e.to_string()))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let skip = initial.lines.len().saturating_sub(lines.max(0) as usize);
+    let mut since_line = initial.next_line;
+    let mut has_more = initial.has_more;
+    let first = LogsResponse {
+        lines: initial.lines[skip..].to_vec(),
+        ..initial
+    };
+    if conn.send_stream_chunk(id, &first).await.is_err() {
+        return Ok(());
+    }
+
+    let mut last_sent = std::time::Instant::now();
+    while has_more {
+        tokio::time::sleep(LOG_FOLLOW_POLL_INTERVAL).await;
+
+        let response = match manager
+            .get_logs(target_id, target_type.clone(), since_line, 1000, filter)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                conn.send_response(&Response::err(id, e.to_string()))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        since_line = response.next_line;
+        // Keep reading until the log is drained, even once the target stops
+        has_more = response.has_more || !response.lines.is_empty();
+
+        if !response.lines.is_empty() || last_sent.elapsed() >= LOG_FOLLOW_KEEPALIVE {
+            if conn.send_stream_chunk(id, &response).await.is_err() {
+                tracing::debug!("Log follower for {} disconnected", target_id);
+                return Ok(());
+            }
+            last_sent = std::time::Instant::now();
+        }
+    }
+
+    // Ignore write errors: the client may already have gone
+    let _ = conn.end_stream(id).await;
+    Ok(())
+}
+
+/// Read the last N lines from a log file
+fn read_log_tail(path: &std::path::Path, lines: usize) -> std::io::Result<String> {
+    use std::io::{BufRead, BufReader};
+
+    let file = std::fs::File::open(path)?;
+    let reader = BufReader::new(file);
+    let all_lines: Vec<String> = reader.lines().collect::<std::io::Result<_>>()?;
+
+    let start = if all_lines.len() > lines {
+        all_lines.len() - lines
+    } else {
+        0
+    };
+
+    Ok(all_lines[start..].join("\n"))
+}
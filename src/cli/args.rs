@@ -1,8 +1,14 @@
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
+use crate::models::worker::MAX_RETRY_DELAY_SECS;
+use crate::models::{LogFormat, LogStream, PauseMode};
 use crate::output::OutputFormat;
+use crate::services::batch_input::BatchFormat;
+use crate::services::github_import::IssueState;
+use crate::services::tokens::TokenModel;
 
 /// Granary - A CLI context hub for agentic work
 #[derive(Parser)]
@@ -35,15 +41,31 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub json: bool,
 
-    /// Workspace path override
-    #[arg(long, global = true, env = "GRANARY_HOME")]
-    pub workspace: Option<PathBuf>,
+    /// Columns for task tables, e.g. id,title,status,due,field.env (save per command with: granary config set columns.<command> ...)
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    pub columns: Option<String>,
+
+    /// Maximum table width in characters; the widest columns are truncated to fit
+    #[arg(long, global = true, value_name = "CHARS")]
+    pub max_width: Option<usize>,
+
+    /// Workspace to use: a directory holding .granary/, or a name from `granary workspaces`
+    #[arg(long, global = true, env = "GRANARY_HOME", value_name = "NAME|PATH")]
+    pub workspace: Option<String>,
 
     /// Session ID override
     #[arg(long, global = true, env = "GRANARY_SESSION")]
     pub session: Option<String>,
 
-    /// Watch mode - continuously poll and update output (works with: tasks, projects, workers, runs, sessions, initiatives, search, summary)
+    /// Who is making changes, recorded in the audit log (defaults to the session owner, then the login name)
+    #[arg(long, global = true, env = "GRANARY_ACTOR")]
+    pub actor: Option<String>,
+
+    /// Profile from ~/.granary/config.toml to apply, e.g. work
+    #[arg(long, global = true, env = "GRANARY_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Watch mode - continuously poll and update output (works with: tasks, projects, workers, runs, sessions, initiatives, search, summary, board)
     #[arg(long, global = true)]
     pub watch: bool,
 
@@ -68,6 +90,7 @@ pub enum CliOutputFormat {
     Table,
     Json,
     Yaml,
+    #[value(alias = "markdown")]
     Md,
     Prompt,
 }
@@ -87,10 +110,24 @@ impl From<CliOutputFormat> for OutputFormat {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize a new workspace
-    Init,
+    #[command(
+        after_help = "EXAMPLES:\n    granary init\n    granary init --encrypted                  # Key saved to the OS keychain\n    GRANARY_DB_KEY=... granary init --encrypted  # Key of your choice\n\nEncryption needs a build with the `encryption` feature (SQLCipher)."
+    )]
+    Init {
+        /// Encrypt the workspace database, keyed from GRANARY_DB_KEY or the OS keychain
+        #[arg(long)]
+        encrypted: bool,
+    },
 
     /// Check workspace health
-    Doctor,
+    #[command(
+        after_help = "EXAMPLES:\n    granary doctor          # Report problems\n    granary doctor --fix    # Repair them and report what changed\n\nREPAIRS:\n    Failed migrations       Re-run after clearing the failed record\n    Orphaned tasks          Recreate the deleted project as a placeholder\n    Integrity               Rebuild indexes\n    Full-text indexes       Rebuild from their content tables\n    Orphaned runs           Remove runs whose worker was deleted\n    Daemon files            Remove stale PID and socket files"
+    )]
+    Doctor {
+        /// Repair the problems found
+        #[arg(long)]
+        fix: bool,
+    },
 
     /// Plan a new feature - creates project and guides task creation
     #[command(after_help = "EXAMPLE:\n    granary plan \"Add Instagram OAuth2 provider\"")]
@@ -119,6 +156,10 @@ pub enum Commands {
     Show {
         /// Entity ID (auto-detected: project, task, session, checkpoint, comment, artifact)
         id: String,
+
+        /// Also show a task's or project's comments, nested into reply threads
+        #[arg(long)]
+        comments: bool,
     },
 
     /// List all projects or create a new one
@@ -130,8 +171,12 @@ pub enum Commands {
         action: Option<ProjectsAction>,
 
         /// Include archived projects (for list)
-        #[arg(long)]
+        #[arg(long, alias = "include-archived")]
         all: bool,
+
+        /// Sort by fields, e.g. name,-updated (prefix - for descending)
+        #[arg(long, value_name = "FIELDS", allow_hyphen_values = true)]
+        sort: Option<String>,
     },
 
     /// Work with a specific project or create a new one
@@ -151,6 +196,9 @@ pub enum Commands {
         after_help = "AGENTS: To work on a task with full context, use:\n    granary work start <task-id>"
     )]
     Tasks {
+        #[command(subcommand)]
+        action: Option<TasksAction>,
+
         /// Show all tasks (across all projects)
         #[arg(long)]
         all: bool,
@@ -163,9 +211,49 @@ pub enum Commands {
         #[arg(long)]
         priority: Option<String>,
 
-        /// Filter by owner
-        #[arg(long)]
+        /// Filter by owner (the assignee)
+        #[arg(long, visible_alias = "assignee")]
         owner: Option<String>,
+
+        /// Filter by who created the task
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Filter by label (repeat to require several labels)
+        #[arg(long)]
+        label: Vec<String>,
+
+        /// Filter by custom field (NAME=VALUE, or NAME= for unset; repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
+
+        /// Only open tasks with no status change, comment or checkpoint in this long (e.g. 14d)
+        #[arg(long, value_name = "DURATION")]
+        stale: Option<String>,
+
+        /// Show the tasks matched by a saved search
+        #[arg(long, conflicts_with_all = ["all", "status", "priority", "owner", "author", "label", "fields", "stale"])]
+        saved: Option<String>,
+
+        /// Maximum number of tasks to show
+        #[arg(long)]
+        limit: Option<u32>,
+
+        /// Number of tasks to skip
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Include archived tasks
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Sort by fields, e.g. priority,-updated (prefix - for descending)
+        #[arg(long, value_name = "FIELDS", allow_hyphen_values = true)]
+        sort: Option<String>,
+
+        /// Show open tasks from every workspace in `granary workspaces`, most urgent first
+        #[arg(long, conflicts_with_all = ["all", "saved", "sort"])]
+        global: bool,
     },
 
     /// Work with a specific task
@@ -189,6 +277,14 @@ pub enum Commands {
         /// Show all currently available tasks
         #[arg(long)]
         all: bool,
+
+        /// Show the scoring breakdown behind each recommendation
+        #[arg(long)]
+        explain: bool,
+
+        /// Start the recommended task and set it as the session focus
+        #[arg(long, conflicts_with = "all")]
+        start: bool,
     },
 
     /// Start a task (alias for task <id> start)
@@ -208,10 +304,17 @@ pub enum Commands {
         lease: Option<u32>,
     },
 
-    /// Set focus to a task
+    /// Set focus to a task, or push and pop the session's focus stack
+    #[command(
+        args_conflicts_with_subcommands = true,
+        after_help = "EXAMPLES:\n    granary focus my-proj-abc1-task-1        # Replace the focus task\n    granary focus push my-proj-abc1-task-7   # Set it aside for an interruption\n    granary focus pop                        # Back to the task set aside\n    granary focus                            # Show the focus stack"
+    )]
     Focus {
+        #[command(subcommand)]
+        action: Option<FocusAction>,
+
         /// Task ID
-        task_id: String,
+        task_id: Option<String>,
     },
 
     /// Pin a task for attention
@@ -226,8 +329,11 @@ pub enum Commands {
         task_id: String,
     },
 
-    /// List sessions
+    /// List sessions, or review one
     Sessions {
+        #[command(subcommand)]
+        action: Option<SessionsAction>,
+
         /// Include closed sessions
         #[arg(long)]
         all: bool,
@@ -241,20 +347,76 @@ pub enum Commands {
 
     /// Generate summary of current work
     Summary {
-        /// Approximate token budget
+        /// Token budget; lists are trimmed to fit
         #[arg(long)]
         token_budget: Option<usize>,
+
+        /// Model whose tokenizer measures the budget (gpt-4o, claude)
+        #[arg(long, default_value = "gpt-4o")]
+        model: TokenModel,
+
+        /// Template in .granary/templates/ for prompt output
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Count archived tasks too
+        #[arg(long)]
+        include_archived: bool,
+    },
+
+    /// Show tasks as a kanban board with a column per status
+    #[command(
+        after_help = "EXAMPLES:\n    granary board\n    granary board --project my-proj-abc1 --watch\n\nColumns: To Do, In Progress, Blocked, Done. Drafts are counted but not shown.\nP0 tasks are red and P1 yellow; set NO_COLOR to turn colors off."
+    )]
+    Board {
+        /// Only show tasks in this project
+        #[arg(long)]
+        project: Option<String>,
+    },
+
+    /// Reports on estimated work and agent spend
+    Report {
+        #[command(subcommand)]
+        action: ReportAction,
+    },
+
+    /// List and test rules that flag or escalate tasks
+    #[command(
+        after_help = "EXAMPLES:\n    granary config set rules.escalate.when \"status=blocked,priority=P2\"\n    granary config set rules.escalate.idle 3d\n    granary config set rules.escalate.then priority=P1\n    granary config set rules.escalate.apply true\n    granary rules test escalate\n\nFIELDS (rules.<name>.<field>):\n    when     Filters on the task, comma-separated (status=blocked,priority=P2)\n    idle     How long the task must have gone unchanged (3d, 12h)\n    then     flag (default) or priority=<P0-P4>\n    apply    true to apply the action; otherwise only the event is emitted\n\nThe daemon evaluates rules every 5 minutes and emits a rule.triggered event\nfor each match, once per task until the task changes again."
+    )]
+    Rules {
+        #[command(subcommand)]
+        action: RulesAction,
     },
 
     /// Export context pack for LLM consumption
+    #[command(
+        after_help = "EXAMPLES:\n    granary context --include tasks,decisions\n    granary context --include \"tasks:status=in_progress:limit=10,comments:type=decision\"\n    granary context --exclude \"attachments,tasks:status=done\"\n\nSECTIONS: projects, tasks, comments, decisions, blockers, artifacts, attachments, related\n\nA section can be followed by :-separated filters (field=value, field!=value,\nfield~=text) and :limit=<n>. Comments, blockers, artifacts, attachments and\nrelated tasks come from the tasks that were included. An excluded section\nwith filters only drops the items matching them."
+    )]
     Context {
-        /// What to include (comma-separated: projects,tasks,comments,decisions,blockers,artifacts)
+        /// Sections to include, with optional filters and limits (comma-separated)
         #[arg(long)]
         include: Option<String>,
 
+        /// Sections, or items matching filters, to leave out (comma-separated)
+        #[arg(long)]
+        exclude: Option<String>,
+
         /// Maximum items per category
         #[arg(long)]
         max_items: Option<usize>,
+
+        /// Token budget; sections are trimmed to fit
+        #[arg(long)]
+        token_budget: Option<usize>,
+
+        /// Model whose tokenizer measures the budget (gpt-4o, claude)
+        #[arg(long, default_value = "gpt-4o")]
+        model: TokenModel,
+
+        /// Template in .granary/templates/ for prompt output
+        #[arg(long)]
+        template: Option<String>,
     },
 
     /// Checkpoint management
@@ -280,20 +442,118 @@ pub enum Commands {
         /// Acceptance criteria
         #[arg(long)]
         acceptance_criteria: Option<String>,
+
+        /// Token budget; context comments are trimmed to fit
+        #[arg(long)]
+        token_budget: Option<usize>,
+
+        /// Model whose tokenizer measures the budget (gpt-4o, claude)
+        #[arg(long, default_value = "gpt-4o")]
+        model: TokenModel,
+
+        /// Template in .granary/templates/ for prompt output
+        #[arg(long)]
+        template: Option<String>,
     },
 
-    /// Apply a batch of operations from JSON
+    /// Apply a batch of operations from JSON or YAML
     Apply {
         /// Read from stdin
         #[arg(long)]
         stdin: bool,
+
+        /// Input format: json, jsonl or yaml (default: detected from the input)
+        #[arg(long, value_name = "FORMAT")]
+        input_format: Option<BatchFormat>,
     },
 
-    /// Process a batch of operations from JSONL
+    /// Process a batch of operations from JSONL or YAML, or bulk-update tasks
     Batch {
+        #[command(subcommand)]
+        action: Option<BatchAction>,
+
         /// Read from stdin
         #[arg(long)]
         stdin: bool,
+
+        /// Input format: json, jsonl or yaml (default: jsonl)
+        #[arg(long, value_name = "FORMAT")]
+        input_format: Option<BatchFormat>,
+    },
+
+    /// Reverse the most recent commands that changed the workspace
+    #[command(
+        after_help = "EXAMPLES:\n    granary undo\n    granary undo --last 3 --dry-run\n\nEvery command that creates, updates or deletes initiatives, projects, tasks,\ncomments, artifacts, labels, fields, steering or config is recorded."
+    )]
+    Undo {
+        /// Number of operations to undo
+        #[arg(long, default_value = "1")]
+        last: u32,
+
+        /// Show what would be undone without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show who changed what and when
+    #[command(
+        after_help = "EXAMPLES:\n    granary audit\n    granary audit --entity my-project-abc1-task-3 --since 2h\n    granary audit --by alice --since 2026-01-01\n\nChanges are attributed to --actor (or GRANARY_ACTOR), else the owner of the\ncurrent session, else the login name."
+    )]
+    Audit {
+        /// Only changes to this entity, or to rows that reference it
+        #[arg(long)]
+        entity: Option<String>,
+
+        /// Only changes since a time: a duration ago (30m, 2h, 7d, 1w) or a date
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only changes made by this actor
+        #[arg(long, value_name = "ACTOR")]
+        by: Option<String>,
+
+        /// Maximum number of changes to show
+        #[arg(long, default_value = "50")]
+        limit: u32,
+
+        /// Number of changes to skip
+        #[arg(long, default_value = "0")]
+        offset: u32,
+    },
+
+    /// Sync the workspace with an external tracker, or show remote sync status
+    Sync {
+        #[command(subcommand)]
+        action: SyncAction,
+    },
+
+    /// Manage remotes the workspace is synced with between machines
+    #[command(
+        after_help = "EXAMPLES:\n    granary remote add ~/Dropbox/granary\n    granary remote add https://granary.example.com/acme --name office\n    granary remote add s3://my-bucket/granary\n    granary remote list\n\nA remote is a directory, an HTTP(S) server that accepts GET and PUT, or an\nS3-compatible bucket. HTTP requests send GRANARY_REMOTE_TOKEN as a bearer\ntoken; S3 uses AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_REGION and\nAWS_ENDPOINT_URL."
+    )]
+    Remote {
+        #[command(subcommand)]
+        action: RemoteAction,
+    },
+
+    /// Upload local changes to a remote
+    #[command(
+        after_help = "EXAMPLES:\n    granary push\n    granary push --remote office\n\nThe first push uploads the whole workspace. Later pushes upload the changes\nmade since. Credentials in config are never pushed."
+    )]
+    Push {
+        /// Remote to push to (default: the only remote, else origin)
+        #[arg(long)]
+        remote: Option<String>,
+    },
+
+    /// Apply changes pushed to a remote from other machines
+    #[command(
+        after_help = "EXAMPLES:\n    granary pull\n    granary pull --remote office\n\nWhen a row changed on both machines, the later change wins. Pull before\nstarting work, so new tasks on two machines do not get the same number."
+    )]
+    Pull {
+        /// Remote to pull from (default: the only remote, else origin)
+        #[arg(long)]
+        remote: Option<String>,
     },
 
     /// Configuration management
@@ -308,11 +568,38 @@ pub enum Commands {
         action: SteeringAction,
     },
 
-    /// Search projects and tasks by title
-    #[command(after_help = "EXAMPLE:\n    granary search \"oauth\"")]
+    /// Search projects and tasks by title, or manage saved searches
+    #[command(
+        args_conflicts_with_subcommands = true,
+        after_help = "QUALIFIERS:\n    status:todo,in_progress  priority:p0  owner:NAME  author:NAME  project:ID  label:NAME  field.NAME:VALUE\n\nEXAMPLES:\n    granary search \"oauth\"\n    granary search \"status:todo priority:p0 label:backend\"\n    granary search save my-queue \"status:todo priority:p0 label:backend\"\n    granary search --saved my-queue\n    granary search --all-workspaces \"deploy\""
+    )]
     Search {
-        /// Search query
-        query: String,
+        #[command(subcommand)]
+        action: Option<SearchAction>,
+
+        /// Search query, optionally with qualifiers
+        #[arg(required_unless_present = "saved")]
+        query: Option<String>,
+
+        /// Run a saved search
+        #[arg(long, conflicts_with = "query")]
+        saved: Option<String>,
+
+        /// Maximum number of results to show
+        #[arg(long)]
+        limit: Option<u32>,
+
+        /// Number of results to skip
+        #[arg(long, default_value = "0")]
+        offset: u32,
+
+        /// Include archived initiatives, projects and tasks
+        #[arg(long)]
+        include_archived: bool,
+
+        /// Search every workspace in `granary workspaces`, not just this one
+        #[arg(long)]
+        all_workspaces: bool,
     },
 
     /// List all initiatives or create a new one
@@ -349,10 +636,43 @@ pub enum Commands {
         to: Option<String>,
     },
 
-    /// List all workers
+    /// List the workspaces on this machine, or rename or forget one
+    #[command(
+        after_help = "EXAMPLES:\n    granary workspaces\n    granary workspaces rename app billing-app\n    granary --workspace billing-app tasks\n\nWorkspaces are registered when created and whenever a command runs in them.\nEach is named after its directory until renamed."
+    )]
+    Workspaces {
+        #[command(subcommand)]
+        action: Option<WorkspacesAction>,
+    },
+
+    /// List external plugins: granary-<name> executables on PATH
+    #[command(
+        after_help = "EXAMPLES:\n    granary plugins\n    granary plugins list --json\n\nAn executable named granary-<name> on PATH runs as `granary <name> ...`.\nPlugins get the workspace root in GRANARY_HOME, the output format in\nGRANARY_FORMAT, GRANARY_SESSION, GRANARY_ACTOR and GRANARY_PROFILE when\ngiven, and the granary executable in GRANARY_BIN."
+    )]
+    Plugins {
+        #[command(subcommand)]
+        action: Option<PluginsAction>,
+    },
+
+    /// List the event types workers can be triggered by
+    #[command(
+        after_help = "EXAMPLES:\n    granary events types\n    granary events types --json\n    granary worker start --on comment.created --filter 'kind=blocker' -- ./notify.sh"
+    )]
+    Events {
+        #[command(subcommand)]
+        action: EventsAction,
+    },
+
+    /// List all workers, or test a worker filter
+    #[command(
+        after_help = "EXAMPLES:\n    granary workers\n    granary workers test-filter 'event.priority == \"P0\" && event.project_id == \"proj-1\"'\n    granary workers test-filter 'event.estimate >= 3' --payload '{\"estimate\": 5}'\n    granary workers test-filter 'status=blocked' --event 42"
+    )]
     Workers {
+        #[command(subcommand)]
+        action: Option<WorkersAction>,
+
         /// Include stopped/errored workers
-        #[arg(long)]
+        #[arg(long, global = true)]
         all: bool,
     },
 
@@ -362,23 +682,38 @@ pub enum Commands {
         command: WorkerCommand,
     },
 
-    /// List all runs
+    /// List all runs or retry a failed one
     Runs {
+        #[command(subcommand)]
+        action: Option<RunsAction>,
+
         /// Filter by worker ID
-        #[arg(long)]
+        #[arg(long, global = true)]
         worker: Option<String>,
 
-        /// Filter by status (pending, running, completed, failed, paused, cancelled)
-        #[arg(long)]
+        /// Filter by status (pending, running, completed, failed, paused, cancelled, killed, timed_out)
+        #[arg(long, global = true)]
         status: Option<String>,
 
         /// Include completed/failed/cancelled runs
-        #[arg(long)]
+        #[arg(long, global = true)]
         all: bool,
 
+        /// Only show runs waiting for a free slot under their worker's concurrency limit
+        #[arg(long, global = true, conflicts_with_all = ["status", "all"])]
+        queued: bool,
+
         /// Maximum number of runs to show
-        #[arg(long, default_value = "50")]
+        #[arg(long, default_value = "50", global = true)]
         limit: u32,
+
+        /// Number of runs to skip
+        #[arg(long, default_value = "0", global = true)]
+        offset: u32,
+
+        /// Sort by fields, e.g. status,-started (prefix - for descending)
+        #[arg(long, value_name = "FIELDS", global = true, allow_hyphen_values = true)]
+        sort: Option<String>,
     },
 
     /// Manage a specific run
@@ -392,6 +727,197 @@ pub enum Commands {
         #[command(subcommand)]
         command: DaemonCommand,
     },
+
+    /// Manage run and worker logs
+    Logs {
+        #[command(subcommand)]
+        command: LogsCommand,
+    },
+
+    /// Run a Model Context Protocol server over stdio
+    #[command(
+        after_help = "EXAMPLE:\n    granary mcp\n\nAGENTS: Register granary as an MCP server to use its tools natively, e.g.:\n    claude mcp add granary -- granary mcp"
+    )]
+    Mcp,
+
+    /// Export the workspace to a portable archive
+    #[command(
+        after_help = "EXAMPLES:\n    granary export > backup.json\n    granary export --archive tar --output backup.tar.gz\n    granary export -o backup.tgz\n\nRestore with: granary import <file>"
+    )]
+    Export {
+        /// Archive format (default: inferred from --output, otherwise json)
+        #[arg(long, value_enum)]
+        archive: Option<ExportFormat>,
+
+        /// Write to a file instead of stdout
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+
+    /// Import an export archive into a fresh workspace, or tasks from other tools
+    #[command(
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true,
+        after_help = "EXAMPLES:\n    granary import backup.json\n    granary import backup.tar.gz --path ../restored\n    granary import markdown TODO.md --project my-proj-abc1\n    granary import github --repo acme/app --label bug --project my-proj-abc1"
+    )]
+    Import {
+        #[command(subcommand)]
+        action: Option<ImportAction>,
+
+        /// Archive file created by `granary export` (json or tar), or - for stdin
+        #[arg(required = true)]
+        file: Option<PathBuf>,
+
+        /// Directory to create the workspace in (default: current directory)
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Snapshot the workspace and global databases
+    #[command(
+        after_help = "EXAMPLES:\n    granary backup                      # Snapshot into .granary/backups/\n    granary backup --to ~/granary-copy  # Snapshot into a directory of your choice\n    granary backup --list               # List rotated snapshots\n\nCONFIG:\n    backup.keep     Rotated snapshots to keep (default 7)\n    backup.daily    true to let the daemon take a snapshot every day\n\nRestore with: granary restore <snapshot>"
+    )]
+    Backup {
+        /// Write the snapshot to this directory instead of .granary/backups/
+        #[arg(long, conflicts_with = "list")]
+        to: Option<PathBuf>,
+
+        /// List rotated snapshots instead of taking one
+        #[arg(long)]
+        list: bool,
+    },
+
+    /// Replace the databases with a snapshot
+    #[command(
+        after_help = "EXAMPLES:\n    granary restore 20260214-093000\n    granary restore ~/granary-copy --global\n\nThe current state is snapshotted first, so a restore can be reversed by\nrestoring that snapshot. Stop the daemon before restoring."
+    )]
+    Restore {
+        /// Snapshot name from `granary backup --list`, or a snapshot directory
+        snapshot: String,
+
+        /// Also restore the global workers database
+        #[arg(long)]
+        global: bool,
+    },
+
+    /// Open an interactive dashboard of tasks, comments, workers and runs
+    #[command(
+        after_help = "KEYS:\n    j/k, up/down    Move selection\n    h/l, left/right Change column (board view)\n    v               Toggle list/board view\n    /               Filter tasks by title, ID or owner\n    q, esc          Quit\n\nData refreshes every --interval seconds. Worker and run status requires the daemon."
+    )]
+    Tui,
+
+    /// Serve a web dashboard of the board, runs and summary
+    #[command(
+        after_help = "EXAMPLES:\n    granary serve\n    granary serve --addr 0.0.0.0:8080\n    granary serve --graphql\n\nOpen the printed address in a browser. The page refreshes every few seconds and\nchanges nothing. Runs and their logs come from the daemon.\n\nWith --graphql, queries and mutations are POSTed to /graphql; GET it for the\nschema. Mutations need the daemon auth token (~/.granary/daemon/auth.token) as\n`Authorization: Bearer <token>`. Requests must address the server by the\naddress it listens on (or localhost), and the rest is not authenticated, so\nkeep the server on a loopback address unless the network is trusted."
+    )]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:7777")]
+        addr: SocketAddr,
+
+        /// Also serve the GraphQL API at /graphql (needs the `graphql` feature)
+        #[arg(long)]
+        graphql: bool,
+    },
+
+    /// Link tasks to git branches and commits
+    #[command(
+        after_help = "EXAMPLES:\n    granary git branch my-project-abc1-task-3\n    granary git link my-project-abc1-task-3\n    granary git link my-project-abc1-task-3 a1b2c3d HEAD~1\n    granary git links\n\nThe repository is found by walking up from the workspace root."
+    )]
+    Git {
+        #[command(subcommand)]
+        command: GitCommand,
+    },
+
+    /// Print a shell completion script
+    #[command(
+        after_help = "EXAMPLES:\n    source <(granary completions bash)           # in ~/.bashrc\n    source <(granary completions zsh)            # in ~/.zshrc\n    granary completions fish > ~/.config/fish/completions/granary.fish\n\nThe scripts complete commands and flags, and live task, project, initiative,\nworker and run IDs, label names and saved searches from the workspace."
+    )]
+    Completions {
+        /// Shell to print the script for
+        #[arg(value_enum)]
+        shell: CompletionShell,
+    },
+
+    /// Print completions for a partial command line (used by completion scripts)
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// Words after `granary`, the last being the word under the cursor
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        words: Vec<String>,
+    },
+
+    /// Run a granary-<name> plugin from PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+/// Shells `granary completions` can print a script for
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Subcommand)]
+pub enum GitCommand {
+    /// Create and check out a branch named after a task
+    Branch {
+        /// Task ID
+        task_id: String,
+
+        /// Branch name (default: <task-id>-<title-slug>)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Create the branch without checking it out
+        #[arg(long)]
+        no_checkout: bool,
+    },
+
+    /// Record commits against a task
+    Link {
+        /// Task ID
+        task_id: String,
+
+        /// Commits to link (default: HEAD)
+        revs: Vec<String>,
+    },
+
+    /// Show branches and commits linked to a task
+    Links {
+        /// Task ID (default: the task linked to the current branch)
+        task_id: Option<String>,
+    },
+
+    /// Remove a branch or commit link from a task
+    Unlink {
+        /// Task ID
+        task_id: String,
+
+        /// Branch name or commit SHA (abbreviated SHAs are accepted)
+        #[arg(name = "ref")]
+        ref_name: String,
+    },
+}
+
+/// Config files `granary config schema` describes
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConfigFile {
+    /// ~/.granary/config.toml
+    Global,
+    /// .granary/granary.toml
+    Workspace,
+}
+
+/// Archive format for `granary export`
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// Single JSON document
+    Json,
+    /// Gzip-compressed tarball with one file per table
+    Tar,
 }
 
 #[derive(Subcommand)]
@@ -463,15 +989,48 @@ pub enum ProjectsAction {
         #[arg(long)]
         tags: Option<String>,
     },
-}
 
-#[derive(Subcommand)]
-pub enum ProjectAction {
-    /// Update project
-    Update {
-        /// New name
-        #[arg(long)]
-        name: Option<String>,
+    /// Archive a project, hiding it from default listings and search
+    Archive {
+        /// Project ID
+        id: String,
+
+        /// Archive the project's tasks too
+        #[arg(long)]
+        cascade: bool,
+    },
+
+    /// Chart a project's tasks between their start and due dates, in dependency order
+    #[command(
+        after_help = "EXAMPLES:\n    granary projects timeline my-proj-abc1\n    granary projects timeline my-proj-abc1 --mermaid > timeline.mmd"
+    )]
+    Timeline {
+        /// Project ID
+        id: String,
+
+        /// Print a Mermaid gantt chart instead
+        #[arg(long)]
+        mermaid: bool,
+    },
+
+    /// Show health metrics: completion, blocked ratio, average age of open
+    /// tasks, tasks done and worker runs failed in the last 7 days
+    #[command(
+        after_help = "EXAMPLES:\n    granary projects health\n    granary projects health my-proj-abc1 --json"
+    )]
+    Health {
+        /// Project ID (default: every active project)
+        id: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProjectAction {
+    /// Update project
+    Update {
+        /// New name
+        #[arg(long)]
+        name: Option<String>,
 
         /// New description
         #[arg(long)]
@@ -484,15 +1043,27 @@ pub enum ProjectAction {
         /// Tags to add (+tag) or remove (-tag)
         #[arg(long)]
         tags: Option<String>,
+
+        /// Set a custom field (NAME=VALUE, or NAME= to clear; repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
     },
 
     /// Archive project
-    Archive,
+    Archive {
+        /// Archive the project's tasks too
+        #[arg(long)]
+        cascade: bool,
+    },
 
     /// List or create tasks in project
     Tasks {
         #[command(subcommand)]
         action: Option<ProjectTasksAction>,
+
+        /// Include archived tasks (for list)
+        #[arg(long)]
+        include_archived: bool,
     },
 
     /// Manage project dependencies
@@ -571,12 +1142,12 @@ pub enum ProjectTasksAction {
         #[arg(long)]
         description: Option<String>,
 
-        /// Priority (P0-P4)
-        #[arg(long, default_value = "P2")]
-        priority: String,
+        /// Priority (P0-P4). Defaults to defaults.priority in .granary/granary.toml, else P2
+        #[arg(long)]
+        priority: Option<String>,
 
         /// Owner
-        #[arg(long)]
+        #[arg(long, visible_alias = "assignee")]
         owner: Option<String>,
 
         /// Dependencies (comma-separated task IDs)
@@ -587,9 +1158,239 @@ pub enum ProjectTasksAction {
         #[arg(long)]
         tags: Option<String>,
 
-        /// Due date (ISO 8601)
+        /// Due date (ISO 8601, or relative: today, friday, next week, in 3 days)
         #[arg(long)]
         due: Option<String>,
+
+        /// Estimated effort, in points or hours
+        #[arg(long)]
+        estimate: Option<f64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImportAction {
+    /// Create tasks from the checklist items of a Markdown file
+    #[command(
+        after_help = "EXAMPLES:\n    granary import markdown TODO.md --project my-proj-abc1\n    cat notes.md | granary import markdown - --project my-proj-abc1\n\nEvery - [ ] item becomes a todo task and every - [x] item a done task. Items\nindented under another become its subtasks, and each task gets a label named\nafter the heading it is under (## Backend API -> backend-api)."
+    )]
+    Markdown {
+        /// Markdown file, or - for stdin
+        file: PathBuf,
+
+        /// Project to create the tasks in
+        #[arg(long)]
+        project: String,
+    },
+
+    /// Create tasks from a GitHub repository's issues, once
+    #[command(
+        after_help = "EXAMPLES:\n    granary import github --repo acme/app --project my-proj-abc1\n    granary import github --repo acme/app --label bug --state all --project my-proj-abc1 --dry-run\n\nEach issue becomes a task with its title, body, labels and comments. Open issues\nbecome todo tasks and closed ones done tasks. Issues imported before are skipped,\nand nothing is synced afterwards. Set GITHUB_TOKEN for private repositories;\nGITHUB_API_URL points at a GitHub Enterprise server."
+    )]
+    Github {
+        /// Repository as owner/name
+        #[arg(long)]
+        repo: String,
+
+        /// Only issues with this label (repeatable; issues need all of them)
+        #[arg(long = "label", value_name = "LABEL")]
+        labels: Vec<String>,
+
+        /// Issues to import: open, closed or all
+        #[arg(long, default_value = "open")]
+        state: IssueState,
+
+        /// Project to create the tasks in
+        #[arg(long)]
+        project: String,
+
+        /// Show what would be imported without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Create tasks from the rows of a CSV export
+    #[command(
+        after_help = "EXAMPLES:\n    granary import csv trello.csv --mapping trello.toml --project my-proj-abc1\n    granary import csv asana.csv --mapping asana.toml --project my-proj-abc1 --dry-run\n\nThe mapping file is TOML. [columns] names the CSV column for each field: title\n(required), description, status, priority, labels and due. [status] and\n[priority] translate the values of those columns:\n\n    [columns]\n    title = \"Card Name\"\n    status = \"List Name\"\n\n    [status]\n    \"Doing\" = \"in_progress\"\n    \"*\" = \"todo\"\n\nEvery row is checked before any task is created."
+    )]
+    Csv {
+        /// CSV file, or - for stdin
+        file: PathBuf,
+
+        /// TOML file mapping CSV columns to task fields
+        #[arg(long)]
+        mapping: PathBuf,
+
+        /// Project to create the tasks in
+        #[arg(long)]
+        project: String,
+
+        /// Show what would be imported without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BatchAction {
+    /// Apply the operations in a batch file
+    #[command(
+        after_help = "EXAMPLES:\n    granary batch apply plan.yaml\n    granary batch apply ops.jsonl --dry-run\n    granary batch apply plan.txt --input-format yaml\n    agent-plan | granary batch apply - --format json\n\nThe format comes from the extension: .json, .jsonl or .ndjson, .yaml or .yml.\nA YAML file may hold several documents (separated by ---), each a batch\n({ops: [...]}), a list of operations or a single operation. Anchors, aliases\nand << merge keys can share fields between operations.\n\nWith - as the file, JSON lines are read from stdin and each operation is\napplied as its line arrives. A result is written for every line straight away:\na JSON line with --format json, otherwise an [OK] or [ERR] line.\n\nWith --dry-run, every operation is checked against the workspace and the fields\nit would change are shown; nothing is written. The command fails if any\noperation would, for example because it names an unknown ID."
+    )]
+    Apply {
+        /// Batch file, or - to read JSON lines from stdin
+        file: PathBuf,
+
+        /// Input format: json, jsonl or yaml (default: from the file extension)
+        #[arg(long, value_name = "FORMAT")]
+        input_format: Option<BatchFormat>,
+
+        /// Show what each operation would change without applying any
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Apply the same changes to every task matching a filter
+    #[command(
+        after_help = "FILTERS use search qualifiers: status:, priority:, owner:, author:, project:, label:, field.NAME:\nCHANGES: status:VALUE  priority:VALUE  owner:NAME  label:+NAME  label:-NAME  field.NAME:VALUE\n\nEXAMPLE:\n    granary batch update --filter status:todo --filter label:backend --set priority:p1"
+    )]
+    Update {
+        /// Tasks to change, as a search query (repeatable)
+        #[arg(long = "filter", required = true, value_name = "QUERY")]
+        filters: Vec<String>,
+
+        /// Change to apply as KEY:VALUE (repeatable)
+        #[arg(long = "set", required = true, value_name = "KEY:VALUE")]
+        sets: Vec<String>,
+
+        /// Apply without asking for confirmation
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Only show the tasks that would change
+        #[arg(long, conflicts_with = "yes")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SearchAction {
+    /// Save a query under a name
+    Save {
+        /// Name of the saved search
+        name: String,
+
+        /// Search query, optionally with qualifiers
+        query: String,
+    },
+
+    /// List saved searches
+    List,
+
+    /// Delete a saved search
+    Delete {
+        /// Name of the saved search
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TasksAction {
+    /// Add or remove task labels
+    #[command(
+        after_help = "EXAMPLE:\n    granary tasks label my-proj-abc1-task-1 +urgent -frontend"
+    )]
+    Label {
+        /// Task ID
+        id: String,
+
+        /// Labels to add (+label or label) or remove (-label)
+        #[arg(required = true, allow_hyphen_values = true)]
+        changes: Vec<String>,
+    },
+
+    /// Attach a file to a task, copying it into .granary/attachments/
+    #[command(after_help = "EXAMPLE:\n    granary tasks attach my-proj-abc1-task-1 docs/spec.md")]
+    Attach {
+        /// Task ID
+        id: String,
+
+        /// File to attach
+        path: PathBuf,
+
+        /// Symlink the file instead of copying it
+        #[arg(long)]
+        symlink: bool,
+    },
+
+    /// Link a task to another: relates to, duplicates or caused by
+    #[command(
+        group(clap::ArgGroup::new("relation").required(true).args(["relates_to", "duplicates", "caused_by"])),
+        after_help = "EXAMPLES:\n    granary tasks link my-proj-abc1-task-7 --duplicates my-proj-abc1-task-3\n    granary tasks link my-proj-abc1-task-9 --caused-by my-proj-abc1-task-4"
+    )]
+    Link {
+        /// Task ID
+        id: String,
+
+        /// The task relates to this task
+        #[arg(long, value_name = "TASK")]
+        relates_to: Option<String>,
+
+        /// The task duplicates this task
+        #[arg(long, value_name = "TASK")]
+        duplicates: Option<String>,
+
+        /// The task was caused by this task
+        #[arg(long, value_name = "TASK")]
+        caused_by: Option<String>,
+    },
+
+    /// Remove the links between two tasks
+    Unlink {
+        /// Task ID
+        id: String,
+
+        /// The linked task
+        other: String,
+    },
+
+    /// Show the task dependency graph, or print it as Mermaid or DOT
+    #[command(
+        after_help = "EXAMPLES:\n    granary tasks graph --project my-proj-abc1 --mermaid\n    granary tasks graph --dot | dot -Tsvg > deps.svg"
+    )]
+    Graph {
+        /// Only show tasks of this project
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Print a Mermaid flowchart
+        #[arg(long, conflicts_with = "dot")]
+        mermaid: bool,
+
+        /// Print a Graphviz DOT digraph
+        #[arg(long)]
+        dot: bool,
+    },
+
+    /// Claim a task for a session, so other sessions can't change its status
+    #[command(
+        after_help = "EXAMPLES:\n    granary tasks claim my-proj-abc1-task-1\n    granary tasks claim my-proj-abc1-task-1 --session sess-20260112-xyz1 --ttl 120\n    granary tasks claim my-proj-abc1-task-1 --steal\n\nThe claim is advisory and lasts --ttl minutes; extend it with\n`granary task <id> heartbeat` and drop it with `granary task <id> release`.\nWhile it lasts, commands run in other sessions get a conflict when they try\nto change the task's status."
+    )]
+    Claim {
+        /// Task ID
+        id: String,
+
+        /// Session to claim the task for (uses current if not specified)
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Minutes the claim lasts
+        #[arg(long, default_value = "30")]
+        ttl: u32,
+
+        /// Take over a claim held by another session or owner
+        #[arg(long)]
+        steal: bool,
     },
 }
 
@@ -614,16 +1415,24 @@ pub enum TaskAction {
         priority: Option<String>,
 
         /// New owner
-        #[arg(long)]
+        #[arg(long, visible_alias = "assignee")]
         owner: Option<String>,
 
         /// Tags
         #[arg(long)]
         tags: Option<String>,
 
-        /// Due date
+        /// Due date (ISO 8601, or relative: today, friday, next week, in 3 days)
         #[arg(long)]
         due: Option<String>,
+
+        /// Estimated effort, in points or hours
+        #[arg(long)]
+        estimate: Option<f64>,
+
+        /// Set a custom field (NAME=VALUE, or NAME= to clear; repeatable)
+        #[arg(long = "field", value_name = "NAME=VALUE")]
+        fields: Vec<String>,
     },
 
     /// Mark a draft task as ready (transition Draft -> Todo)
@@ -660,6 +1469,12 @@ pub enum TaskAction {
     /// Unblock task
     Unblock,
 
+    /// Archive task, hiding it from default listings, summaries and search
+    Archive,
+
+    /// Restore an archived task
+    Unarchive,
+
     /// Claim task with a lease
     Claim {
         /// Owner
@@ -735,12 +1550,12 @@ pub enum SubtaskAction {
         #[arg(long)]
         description: Option<String>,
 
-        /// Priority
-        #[arg(long, default_value = "P2")]
-        priority: String,
+        /// Priority (P0-P4). Defaults to defaults.priority in .granary/granary.toml, else P2
+        #[arg(long)]
+        priority: Option<String>,
 
         /// Owner
-        #[arg(long)]
+        #[arg(long, visible_alias = "assignee")]
         owner: Option<String>,
     },
 }
@@ -748,6 +1563,7 @@ pub enum SubtaskAction {
 #[derive(Subcommand)]
 pub enum CommentAction {
     /// Create a comment
+    #[command(visible_alias = "add")]
     Create {
         /// Comment content (positional argument)
         content_positional: Option<String>,
@@ -763,6 +1579,10 @@ pub enum CommentAction {
         /// Author
         #[arg(long)]
         author: Option<String>,
+
+        /// Reply to a comment on the same task, threading under it
+        #[arg(long, value_name = "COMMENT_ID")]
+        reply_to: Option<String>,
     },
 }
 
@@ -799,7 +1619,7 @@ pub enum SessionAction {
         #[arg(long)]
         owner: Option<String>,
 
-        /// Session mode (plan, execute, review)
+        /// Session mode (execute, plan, review, or a mode defined in .granary/granary.toml)
         #[arg(long, default_value = "execute")]
         mode: String,
     },
@@ -807,6 +1627,15 @@ pub enum SessionAction {
     /// Show current session
     Current,
 
+    /// Switch the current session to another mode
+    #[command(
+        after_help = "EXAMPLES:\n    granary session mode review\n\nA mode's effects are set as [modes.<name>] in .granary/granary.toml: summary\nsections it emphasizes, statuses tasks can be moved to, and instructions for\nagents in prompt output."
+    )]
+    Mode {
+        /// Mode name
+        mode: String,
+    },
+
     /// Switch to a session
     Use {
         /// Session ID
@@ -846,6 +1675,33 @@ pub enum SessionAction {
     Env,
 }
 
+#[derive(Subcommand)]
+pub enum FocusAction {
+    /// Focus on a task, setting the current focus task aside
+    Push {
+        /// Task ID
+        task_id: String,
+    },
+
+    /// Drop the focus task and return to the one set aside before it
+    Pop,
+}
+
+#[derive(Subcommand)]
+pub enum SessionsAction {
+    /// List sessions (same as `granary sessions`)
+    List,
+
+    /// Summarize what happened during a session
+    #[command(
+        after_help = "EXAMPLES:\n    granary sessions review\n    granary sessions review sess-20260112-xyz1\n    granary sessions review sess-20260112-xyz1 --format prompt\n\nThe review covers the tasks the session's events were about and their status\nchanges while it was open, comments, checkpoints, the worker runs its events\ntriggered, and how long it lasted."
+    )]
+    Review {
+        /// Session ID (uses current if not specified)
+        session_id: Option<String>,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum CheckpointAction {
     /// Create a checkpoint
@@ -882,6 +1738,9 @@ pub enum ConfigAction {
     },
 
     /// Set a config value
+    #[command(
+        after_help = "EXAMPLES:\n    granary config set date.timezone Europe/London\n    granary config set date.week_start sunday"
+    )]
     Set {
         /// Config key
         key: String,
@@ -899,6 +1758,32 @@ pub enum ConfigAction {
         key: String,
     },
 
+    /// Show ~/.granary/config.toml and the workspace's .granary/granary.toml
+    #[command(
+        after_help = "EXAMPLES:\n    granary config show\n    granary config show --effective\n    granary --profile work config show --effective --json\n\nWith --effective, the settings that apply here: the global config with the\nactive profile applied, then .granary/granary.toml, plus the workspace's\nconfig keys."
+    )]
+    Show {
+        /// Show the merged result instead of each file
+        #[arg(long)]
+        effective: bool,
+    },
+
+    /// Check the global and workspace config for problems
+    #[command(
+        after_help = "EXAMPLES:\n    granary config validate\n    granary --profile work config validate --json\n\nChecks that config files parse, runner commands are on PATH, webhook and\nnotifier URLs are HTTP(S), filters and worker schedules parse, and workspace\nconfig keys have valid values. Exits with status 2 if there are errors."
+    )]
+    Validate,
+
+    /// Print a JSON Schema for a config file
+    #[command(
+        after_help = "EXAMPLES:\n    granary config schema global > ~/.granary/config.schema.json\n    granary config schema workspace > .granary/granary.schema.json\n\nEditors with a TOML language server (Taplo, Even Better TOML) use it for\ncompletion and checking when a file starts with a directive such as:\n    #:schema ./granary.schema.json"
+    )]
+    Schema {
+        /// Which config file
+        #[arg(value_enum)]
+        file: ConfigFile,
+    },
+
     /// Open global config file (~/.granary/config.toml) in $EDITOR
     Edit,
 
@@ -909,16 +1794,180 @@ pub enum ConfigAction {
     },
 }
 
+#[derive(Subcommand)]
+pub enum SyncAction {
+    /// Show changes waiting to be pushed to or pulled from each remote
+    #[command(after_help = "EXAMPLES:\n    granary sync status\n    granary sync status --json")]
+    Status,
+
+    /// Two-way sync of initiatives, projects and tasks with Linear
+    #[command(
+        after_help = "EXAMPLES:\n    granary config set sync.linear.team ENG\n    granary sync linear --dry-run\n    granary sync linear --conflict remote\n\nInitiatives sync to Linear initiatives, projects to Linear projects and tasks\nto issues. The API key is read from LINEAR_API_KEY unless sync.linear.api_key\nis set."
+    )]
+    Linear {
+        /// Show what would sync without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Side that wins when both changed: local, remote or newest.
+        /// Defaults to sync.linear.conflict, else newest
+        #[arg(long)]
+        conflict: Option<String>,
+
+        /// Compare everything instead of only what changed since the last sync
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Import Jira issues into a project and sync their status and comments
+    #[command(
+        after_help = "EXAMPLES:\n    granary config set sync.jira.url https://acme.atlassian.net\n    granary config set sync.jira.email me@example.com\n    granary sync jira --jql 'project = SUP AND status != Done' --project support-abc1\n    granary config set 'sync.jira.status.In Review' blocked\n    granary sync jira\n\nThe API token is read from JIRA_API_TOKEN unless sync.jira.token is set. A\n--jql or --project given here is saved for later syncs."
+    )]
+    Jira {
+        /// JQL query selecting the issues to sync. Defaults to sync.jira.jql
+        #[arg(long)]
+        jql: Option<String>,
+
+        /// Project to import issues into. Defaults to sync.jira.project
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Show what would sync without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Side that wins when both changed: local, remote or newest.
+        /// Defaults to sync.jira.conflict, else newest
+        #[arg(long)]
+        conflict: Option<String>,
+
+        /// Compare everything instead of only what changed since the last sync
+        #[arg(long)]
+        full: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ReportAction {
+    /// Chart a project's remaining estimate at each checkpoint and now
+    #[command(
+        after_help = "EXAMPLE:\n    granary task my-proj-abc1-task-1 update --estimate 3\n    granary checkpoint create sprint-start\n    granary report burndown --project my-proj-abc1\n\nEvery checkpoint records each project's total and remaining estimate."
+    )]
+    Burndown {
+        /// Project ID
+        #[arg(long)]
+        project: String,
+    },
+
+    /// Count tasks completed per week to show whether the pace is rising or falling
+    #[command(
+        after_help = "EXAMPLES:\n    granary report velocity\n    granary report velocity --window 8w --project my-proj-abc1\n\nCompletions come from the task status history. A task reopened and completed\nagain counts in the week it was last completed."
+    )]
+    Velocity {
+        /// How far back to report, rounded up to whole weeks (e.g. 4w, 30d)
+        #[arg(long, default_value = "4w")]
+        window: String,
+
+        /// Only count tasks in this project
+        #[arg(long)]
+        project: Option<String>,
+    },
+
+    /// Add up the tokens and cost agent runs reported, by worker, project and task
+    #[command(
+        after_help = "EXAMPLES:\n    granary report costs\n    granary report costs --since 30d --json\n\nRunners report usage by printing lines such as\n    GRANARY_USAGE: {\"input_tokens\":1200,\"output_tokens\":340,\"cost_usd\":0.012}\nEach line is added to the run's totals. Only runs of this workspace that\nreported usage are counted."
+    )]
+    Costs {
+        /// Only count runs created since this time (e.g. 7d, 24h, 2026-01-01)
+        #[arg(long, default_value = "7d")]
+        since: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RulesAction {
+    /// List the workspace's rules
+    List,
+
+    /// Show the tasks the rules match now, without changing anything
+    Test {
+        /// Only test this rule
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EventsAction {
+    /// List every event type granary emits, with a description
+    Types,
+}
+
+#[derive(Subcommand)]
+pub enum PluginsAction {
+    /// List plugins found on PATH
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum WorkspacesAction {
+    /// List registered workspaces with task counts and last activity
+    List,
+
+    /// Change the name used with --workspace
+    Rename {
+        /// Current name
+        name: String,
+
+        /// New name
+        new_name: String,
+    },
+
+    /// Forget a workspace; its files are left alone
+    Remove {
+        /// Workspace name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RemoteAction {
+    /// Add a remote
+    Add {
+        /// Directory, http(s):// URL or s3://bucket/prefix URL
+        url: String,
+
+        /// Name for the remote (default: origin)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// List remotes
+    List,
+
+    /// Remove a remote; nothing on the remote is deleted
+    Remove {
+        /// Remote name
+        name: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum RunnersAction {
     /// Add or update a runner configuration
+    #[command(
+        after_help = "EXAMPLES:\n    granary config runners add claude --command claude -a -p -a '{task.id}'\n    granary config runners add claude-opus --extends claude -a --model -a opus\n\nA runner that extends another gets its settings, with its own args appended\nand its own env and limits merged over them."
+    )]
     Add {
         /// Runner name
         name: String,
 
-        /// Command to execute
+        /// Command to execute (optional with --extends)
+        #[arg(long, required_unless_present = "extends")]
+        command: Option<String>,
+
+        /// Runner whose settings this one builds on
         #[arg(long)]
-        command: String,
+        extends: Option<String>,
 
         /// Arguments (can be specified multiple times)
         #[arg(long = "arg", short = 'a')]
@@ -935,6 +1984,26 @@ pub enum RunnersAction {
         /// Environment variables (KEY=VALUE format, can be specified multiple times)
         #[arg(long = "env", short = 'e')]
         env_vars: Vec<String>,
+
+        /// Dotenv file relative to the workspace root, read as each run starts
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<String>,
+
+        /// Kill runs that take longer than this many seconds
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+
+        /// Seconds a stopped run has to exit after SIGTERM before it is killed
+        #[arg(long, value_name = "SECS")]
+        grace_period: Option<u64>,
+
+        /// Send the triggering event and a context pack to each run as JSON on stdin
+        #[arg(long)]
+        stdin: bool,
+
+        /// Run log format: text, or jsonl for one JSON record per output line
+        #[arg(long, value_name = "FORMAT")]
+        log_format: Option<LogFormat>,
     },
 
     /// Update an existing runner
@@ -946,6 +2015,10 @@ pub enum RunnersAction {
         #[arg(long)]
         command: Option<String>,
 
+        /// Runner whose settings this one builds on
+        #[arg(long)]
+        extends: Option<String>,
+
         /// Arguments (replaces existing if provided)
         #[arg(long = "arg", short = 'a')]
         args: Option<Vec<String>>,
@@ -961,6 +2034,26 @@ pub enum RunnersAction {
         /// Environment variables (KEY=VALUE format, replaces existing if provided)
         #[arg(long = "env", short = 'e')]
         env_vars: Option<Vec<String>>,
+
+        /// Dotenv file relative to the workspace root, read as each run starts
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<String>,
+
+        /// Kill runs that take longer than this many seconds
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        timeout: Option<u64>,
+
+        /// Seconds a stopped run has to exit after SIGTERM before it is killed
+        #[arg(long, value_name = "SECS")]
+        grace_period: Option<u64>,
+
+        /// Whether to send the triggering event and a context pack on stdin
+        #[arg(long, value_name = "BOOL")]
+        stdin: Option<bool>,
+
+        /// Run log format: text, or jsonl for one JSON record per output line
+        #[arg(long, value_name = "FORMAT")]
+        log_format: Option<LogFormat>,
     },
 
     /// Remove a runner configuration
@@ -973,6 +2066,11 @@ pub enum RunnersAction {
     Show {
         /// Runner name
         name: String,
+
+        /// Show the runner with the runners it extends applied, as a worker
+        /// started here would run it
+        #[arg(long)]
+        resolved: bool,
     },
 }
 
@@ -1044,6 +2142,55 @@ pub enum InitiativesAction {
         #[arg(long)]
         tags: Option<String>,
     },
+
+    /// Manage an initiative's milestones
+    Milestone {
+        #[command(subcommand)]
+        action: MilestoneAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MilestoneAction {
+    /// Add a milestone to an initiative
+    #[command(
+        after_help = "EXAMPLES:\n    granary initiatives milestone add platform-5h18 \"Beta\" --target 2026-11-30 --project api-x2k1\n    granary initiatives milestone add platform-5h18 \"Launch\" --target \"end of week\" --task web-9ab2-task-4"
+    )]
+    Add {
+        /// Initiative ID
+        initiative_id: String,
+
+        /// Milestone name
+        name: String,
+
+        /// Target date (YYYY-MM-DD, "friday", "in 2 weeks", ...)
+        #[arg(long)]
+        target: Option<String>,
+
+        /// Milestone description
+        #[arg(long)]
+        description: Option<String>,
+
+        /// Attach a project of the initiative (repeatable)
+        #[arg(long = "project", value_name = "PROJECT")]
+        projects: Vec<String>,
+
+        /// Attach a task (repeatable)
+        #[arg(long = "task", value_name = "TASK")]
+        tasks: Vec<String>,
+    },
+
+    /// List an initiative's milestones with their progress
+    List {
+        /// Initiative ID
+        initiative_id: String,
+    },
+
+    /// Mark a milestone complete
+    Complete {
+        /// Milestone ID
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -1104,39 +2251,7 @@ pub enum InitiativeAction {
 #[derive(Subcommand)]
 pub enum WorkerCommand {
     /// Start a new worker
-    Start {
-        /// Runner name from config
-        #[arg(long)]
-        runner: Option<String>,
-
-        /// Inline command to execute
-        #[arg(long)]
-        command: Option<String>,
-
-        /// Command arguments (can be specified multiple times)
-        #[arg(long = "arg", short = 'a')]
-        args: Vec<String>,
-
-        /// Event type to subscribe to (uses runner's default if not specified)
-        #[arg(long)]
-        on: Option<String>,
-
-        /// Filter expressions (can be specified multiple times)
-        #[arg(long = "filter", short = 'f')]
-        filters: Vec<String>,
-
-        /// Run in background as daemon
-        #[arg(long, short = 'd')]
-        detached: bool,
-
-        /// Maximum concurrent runner instances
-        #[arg(long, default_value = "1")]
-        concurrency: u32,
-
-        /// Cooldown in seconds for polled events like task.next (default: 300 = 5 minutes)
-        #[arg(long, default_value = "300")]
-        poll_cooldown: i64,
-    },
+    Start(Box<WorkerStartArgs>),
 
     /// Show worker status
     Status {
@@ -1172,6 +2287,235 @@ pub enum WorkerCommand {
     Prune,
 }
 
+/// Options for `granary worker start`
+#[derive(Args)]
+pub struct WorkerStartArgs {
+    /// Runner name from config
+    #[arg(long)]
+    pub runner: Option<String>,
+
+    /// Inline command to execute
+    #[arg(long)]
+    pub command: Option<String>,
+
+    /// Command arguments (can be specified multiple times)
+    #[arg(long = "arg", short = 'a')]
+    pub args: Vec<String>,
+
+    /// Event type to subscribe to (uses runner's default if not specified)
+    #[arg(long)]
+    pub on: Option<String>,
+
+    /// Filter expressions (can be specified multiple times)
+    #[arg(long = "filter", short = 'f')]
+    pub filters: Vec<String>,
+
+    /// Run in background as daemon
+    #[arg(long, short = 'd')]
+    pub detached: bool,
+
+    /// Maximum concurrent runner instances
+    #[arg(long, default_value = "1")]
+    pub concurrency: u32,
+
+    /// Cooldown in seconds for polled events like task.next (default: 300 = 5 minutes)
+    #[arg(long, default_value = "300")]
+    pub poll_cooldown: i64,
+
+    /// Cron expression to fire runs on a timer, e.g. "0 9 * * MON-FRI"
+    /// (evaluated in the configured date.timezone)
+    #[arg(long)]
+    pub schedule: Option<String>,
+
+    /// Total attempts per run, including the first (1 disables retries)
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub max_attempts: Option<i32>,
+
+    /// How retry delays grow: fixed or exponential (default: exponential)
+    #[arg(long)]
+    pub retry_strategy: Option<String>,
+
+    /// Delay in seconds before the first retry (default: 5, at most 604800)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..=MAX_RETRY_DELAY_SECS))]
+    pub retry_delay: Option<u64>,
+
+    /// Maximum delay in seconds between retries (at most 604800, one week)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(..=MAX_RETRY_DELAY_SECS))]
+    pub retry_max_delay: Option<u64>,
+
+    /// Disable random jitter on retry delays
+    #[arg(long)]
+    pub no_retry_jitter: bool,
+
+    /// Also retry runs that exceed --timeout
+    #[arg(long)]
+    pub retry_on_timeout: bool,
+
+    /// Kill runs that take longer than this many seconds (default: the runner's timeout)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: Option<u64>,
+
+    /// Wait until no matching event has arrived for this many seconds, then
+    /// start one run for the whole burst
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub debounce: Option<u64>,
+
+    /// Start at most one run per this many seconds; events in between are
+    /// coalesced into the next run
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub throttle: Option<u64>,
+
+    /// Send the triggering event and a context pack to each run as JSON on
+    /// stdin (default: the runner's stdin setting)
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Receive events from every registered workspace, not only this one
+    #[arg(long)]
+    pub global: bool,
+
+    /// What to do with events that arrive while the worker is paused:
+    /// queue (handle them on resume) or drop
+    #[arg(long, value_name = "MODE", default_value = "queue")]
+    pub on_pause: PauseMode,
+}
+
+#[derive(Subcommand)]
+pub enum WorkersAction {
+    /// List workers (same as `granary workers`)
+    List,
+
+    /// Stop starting runs for new events, without stopping the worker
+    #[command(
+        after_help = "Active runs carry on. Events that arrive while paused are handled on resume,\nor skipped if the worker was started with --on-pause drop."
+    )]
+    Pause {
+        /// Worker ID
+        worker_id: String,
+    },
+
+    /// Resume a paused worker
+    Resume {
+        /// Worker ID
+        worker_id: String,
+    },
+
+    /// Check that a --filter parses, and optionally whether an event matches it
+    TestFilter {
+        /// Filter: field=value, or an expression like 'event.priority == "P0"'
+        filter: String,
+
+        /// Event payload to test against, as JSON
+        #[arg(long, conflicts_with = "event")]
+        payload: Option<String>,
+
+        /// ID of a workspace event to test against
+        #[arg(long)]
+        event: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RunsAction {
+    /// List runs (same as `granary runs`)
+    List,
+
+    /// Retry a failed, cancelled, killed or timed-out run
+    Retry {
+        /// Run ID
+        run_id: String,
+    },
+
+    /// Show runs waiting for a concurrency slot, in the order they will start
+    #[command(
+        after_help = "EXAMPLES:\n    granary runs queue\n    granary runs queue --worker worker-abc123\n    granary runs bump run-abc123\n    granary runs drop run-abc123\n\nEach worker has its own queue. Bumped runs start first, the most recently\nbumped one ahead of the others; the rest start oldest first."
+    )]
+    Queue,
+
+    /// Move a queued run to the front of its worker's queue
+    Bump {
+        /// Run ID
+        run_id: String,
+    },
+
+    /// Remove a queued run before it starts (it is marked cancelled)
+    Drop {
+        /// Run ID
+        run_id: String,
+    },
+
+    /// View run logs (same as `granary run logs`)
+    #[command(
+        after_help = "EXAMPLES:\n    granary runs logs run-abc123\n    granary runs logs run-abc123 -f\n    granary runs logs run-abc123 --stream stderr --since 10m\n    granary runs logs run-abc123 --strip-ansi > run.log\n\nLines a runner redrew with \\r, such as progress bars, are shown as they ended.\n\nWith -f, new lines are streamed from the daemon as the run writes them, and\nthe command exits once the run completes, fails or is cancelled.\n\n--stream, --since and --until need a runner with log_format = \"jsonl\"."
+    )]
+    Logs {
+        /// Run ID
+        run_id: String,
+
+        /// Follow log output until the run finishes
+        #[arg(long, short = 'f')]
+        follow: bool,
+
+        /// Number of lines to show from the end
+        #[arg(long, short = 'n', default_value = "100")]
+        lines: usize,
+
+        /// Only show lines from this stream: stdout or stderr (JSON-lines logs only)
+        #[arg(long, value_name = "STREAM")]
+        stream: Option<LogStream>,
+
+        /// Only show lines written since this time (e.g. 10m, 2h, 2026-01-15T10:00:00Z; JSON-lines logs only)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only show lines written before this time (JSON-lines logs only)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Remove ANSI color and cursor escape sequences
+        #[arg(long)]
+        strip_ansi: bool,
+    },
+
+    /// Search the logs of stored runs
+    #[command(
+        after_help = "EXAMPLES:\n    granary runs grep \"token expired\"\n    granary runs grep -i timeout --worker worker-abc123 --since 2h\n    granary runs grep -E 'exit code [1-9]' --since 7d --format json\n\nPrints the run ID, when the run started and each matching line with its\nline number. Runs whose logs were pruned are skipped."
+    )]
+    Grep {
+        /// Text to search for (a regular expression with -E)
+        pattern: String,
+
+        /// Treat the pattern as a regular expression
+        #[arg(long, short = 'E')]
+        regex: bool,
+
+        /// Match case-insensitively
+        #[arg(long, short = 'i')]
+        ignore_case: bool,
+
+        /// Only search runs created since this time (e.g. 2h, 7d, yesterday, 2026-01-15)
+        #[arg(long)]
+        since: Option<String>,
+    },
+
+    /// List the files a run left in its artifacts directory, or extract one
+    #[command(
+        after_help = "EXAMPLES:\n    granary runs artifacts run-abc123\n    granary runs artifacts run-abc123 --get fix.patch | git apply\n    granary runs artifacts run-abc123 --get reports/summary.md -o summary.md\n\nRunners write files into the directory in GRANARY_ARTIFACTS_DIR; they are\nrecorded when the run finishes."
+    )]
+    Artifacts {
+        /// Run ID
+        run_id: String,
+
+        /// Print the artifact with this name (or ID) instead of listing them
+        #[arg(long, value_name = "NAME")]
+        get: Option<String>,
+
+        /// With --get, write the artifact to this file instead of stdout
+        #[arg(long, short = 'o', requires = "get")]
+        output: Option<PathBuf>,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum RunCommand {
     /// Show run status and details
@@ -1192,6 +2536,22 @@ pub enum RunCommand {
         /// Number of lines to show from the end
         #[arg(long, short = 'n', default_value = "100")]
         lines: usize,
+
+        /// Only show lines from this stream: stdout or stderr (JSON-lines logs only)
+        #[arg(long, value_name = "STREAM")]
+        stream: Option<LogStream>,
+
+        /// Only show lines written since this time (e.g. 10m, 2h, 2026-01-15T10:00:00Z; JSON-lines logs only)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only show lines written before this time (JSON-lines logs only)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Remove ANSI color and cursor escape sequences
+        #[arg(long)]
+        strip_ansi: bool,
     },
 
     /// Stop a running run
@@ -1213,6 +2573,19 @@ pub enum RunCommand {
     },
 }
 
+#[derive(Subcommand)]
+pub enum LogsCommand {
+    /// Delete logs by the retention policy in the global config
+    #[command(
+        after_help = "EXAMPLES:\n    granary logs prune --dry-run\n    granary logs prune\n\nCONFIG (~/.granary/config.toml):\n    [logs]\n    max_age = \"7d\"                # Delete logs last written before this\n    max_total_size = \"100MB\"      # Then delete the oldest while larger than this\n    max_files_per_worker = 100     # Run logs kept per worker\n\nLogs of unfinished runs and running workers are kept. The daemon applies\nthe same policy every hour."
+    )]
+    Prune {
+        /// Show what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum DaemonCommand {
     /// Show daemon status
@@ -1237,4 +2610,17 @@ pub enum DaemonCommand {
         #[arg(short = 'n', long, default_value = "50")]
         lines: usize,
     },
+
+    /// Start the daemon on login with systemd, launchd or Task Scheduler
+    #[command(
+        after_help = "EXAMPLES:\n    granary daemon install           # Install, enable and start the unit\n    granary daemon install --print   # Show the unit without installing it\n\nUNITS:\n    Linux    ~/.config/systemd/user/granaryd.service\n    macOS    ~/Library/LaunchAgents/dev.granary.granaryd.plist\n    Windows  Task Scheduler task 'granaryd', run at logon"
+    )]
+    Install {
+        /// Print the unit instead of installing it
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Stop the daemon's service unit and remove it
+    Uninstall,
 }
@@ -0,0 +1,32 @@
+//! `granary audit`: who changed what and when, from the operation log.
+
+use crate::error::Result;
+use crate::models::{AuditFilter, Page};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{self, Workspace, datetime};
+
+/// Show recorded changes, newest first
+pub async fn audit(
+    entity: Option<String>,
+    since: Option<&str>,
+    actor: Option<String>,
+    page: Page,
+    format: OutputFormat,
+) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let since = since
+        .map(|s| datetime::parse_since(s, &datetime::settings()))
+        .transpose()?;
+    let filter = AuditFilter {
+        entity,
+        since,
+        actor,
+    };
+    let entries = services::audit(&pool, &filter, page).await?;
+
+    let formatter = Formatter::new(format);
+    println!("{}", formatter.format_audit_entries(&entries));
+    Ok(())
+}
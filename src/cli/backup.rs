@@ -0,0 +1,32 @@
+//! `granary backup` and `granary restore`: database snapshots.
+
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, backup_service};
+
+/// Take a snapshot, or list rotated snapshots
+pub async fn backup(to: Option<PathBuf>, list: bool, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let formatter = Formatter::new(format);
+
+    if list {
+        let snapshots = backup_service::list_snapshots(&workspace)?;
+        println!("{}", formatter.format_snapshots(&snapshots));
+        return Ok(());
+    }
+
+    let pool = workspace.pool().await?;
+    let snapshot = backup_service::backup(&workspace, &pool, to.as_deref()).await?;
+    println!("{}", formatter.format_snapshot(&snapshot));
+    Ok(())
+}
+
+/// Replace the databases with a snapshot
+pub async fn restore(snapshot: &str, global: bool, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let report = backup_service::restore(&workspace, snapshot, global).await?;
+    println!("{}", Formatter::new(format).format_restore_report(&report));
+    Ok(())
+}
@@ -1,68 +1,213 @@
-use std::io::{self, BufRead, Read};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::path::Path;
 
-use crate::error::Result;
-use crate::output::OutputFormat;
-use crate::services::{self, Workspace, batch_service::BatchRequest};
+use sqlx::SqlitePool;
 
-/// Apply a batch of operations from JSON
-pub async fn apply(stdin: bool, format: OutputFormat) -> Result<()> {
+use crate::cli::args::BatchAction;
+use crate::error::{GranaryError, Result};
+use crate::models::SearchQuery;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{
+    self, Workspace,
+    batch_input::{self, BatchFormat},
+    batch_service::{BatchOp, BatchRequest, BatchResult, TaskChangeSet},
+};
+
+/// Apply a batch of operations from JSON or YAML
+pub async fn apply(
+    stdin: bool,
+    input_format: Option<BatchFormat>,
+    format: OutputFormat,
+) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
+    let session_id = workspace.current_session_id();
 
     let input = if stdin {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer)?;
         buffer
     } else {
-        return Err(crate::error::GranaryError::InvalidArgument(
+        return Err(GranaryError::InvalidArgument(
             "Use --stdin to read from stdin".to_string(),
         ));
     };
 
-    let request: BatchRequest = serde_json::from_str(&input)?;
-    let results = services::apply_batch(&pool, &request).await?;
+    let input_format = input_format.unwrap_or_else(|| BatchFormat::detect(&input));
+    let request = batch_input::parse(&input, input_format)?;
+    let results = services::apply_batch(&pool, &request, session_id.as_deref()).await?;
+    print_results(&results, false, format)
+}
 
-    match format {
-        OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&results)?);
-        }
-        _ => {
-            let success_count = results.iter().filter(|r| r.success).count();
-            let fail_count = results.len() - success_count;
+/// Apply the operations in a batch file, or with `dry_run` show what they
+/// would change
+async fn apply_file(
+    file: &Path,
+    input_format: Option<BatchFormat>,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
 
-            println!(
-                "Batch complete: {} succeeded, {} failed",
-                success_count, fail_count
-            );
+    let input = std::fs::read_to_string(file).map_err(|e| {
+        GranaryError::InvalidArgument(format!("Cannot read {}: {}", file.display(), e))
+    })?;
+    let input_format = input_format
+        .or_else(|| BatchFormat::from_path(file))
+        .unwrap_or_else(|| BatchFormat::detect(&input));
+    let request = batch_input::parse(&input, input_format)?;
+    apply_request(
+        &pool,
+        &request,
+        workspace.current_session_id().as_deref(),
+        dry_run,
+        format,
+    )
+    .await
+}
 
-            for result in &results {
-                if result.success {
-                    if let Some(id) = &result.id {
-                        println!("  [OK] {} -> {}", result.op, id);
-                    } else {
-                        println!("  [OK] {}", result.op);
-                    }
-                } else {
-                    println!(
-                        "  [ERR] {}: {}",
-                        result.op,
-                        result.error.as_deref().unwrap_or("Unknown error")
-                    );
-                }
+/// Apply operations piped to `granary batch apply -`. JSON lines are applied
+/// as each line arrives, with a result written and flushed for every line,
+/// so a long plan can be streamed in. Other formats, and dry runs, need the
+/// whole input first.
+async fn apply_stdin(
+    input_format: Option<BatchFormat>,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let session_id = workspace.current_session_id();
+
+    let input_format = input_format.unwrap_or(BatchFormat::Jsonl);
+    if dry_run || input_format != BatchFormat::Jsonl {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        let request = batch_input::parse(&input, input_format)?;
+        return apply_request(&pool, &request, session_id.as_deref(), dry_run, format).await;
+    }
+
+    // `Stdin` locks for each read, so the lock isn't held while a line is applied
+    apply_lines(
+        &pool,
+        io::BufReader::new(io::stdin()),
+        &mut io::stdout(),
+        session_id.as_deref(),
+        format,
+    )
+    .await
+}
+
+/// Apply JSON lines from `input` one at a time, writing a result to `out` for
+/// each. A line that fails to parse or apply is reported and later lines
+/// still run, but the whole stream then fails.
+async fn apply_lines(
+    pool: &SqlitePool,
+    mut input: impl BufRead,
+    out: &mut impl Write,
+    session_id: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let mut index = 0;
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<BatchOp>(&line) {
+            Ok(op) => {
+                let request = BatchRequest { ops: vec![op] };
+                let mut results = services::apply_batch(pool, &request, session_id).await?;
+                let mut result = results.remove(0);
+                result.index = index;
+                result
             }
+            Err(e) => BatchResult {
+                index,
+                op: serde_json::from_str::<serde_json::Value>(&line)
+                    .ok()
+                    .and_then(|v| v["op"].as_str().map(str::to_string))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                success: false,
+                id: None,
+                error: Some(format!("Line {}: {}", line_number, e)),
+            },
+        };
+        index += 1;
+        if result.success {
+            succeeded += 1;
+        } else {
+            failed += 1;
         }
+        write_ack(out, &result, format)?;
     }
 
+    if !format.is_structured() {
+        writeln!(
+            out,
+            "Batch complete: {} succeeded, {} failed",
+            succeeded, failed
+        )?;
+    }
+    if failed > 0 {
+        return Err(GranaryError::InvalidArgument(format!(
+            "{} of {} operation(s) failed",
+            failed, index
+        )));
+    }
     Ok(())
 }
 
-/// Process a batch of operations from JSONL (one JSON object per line)
-pub async fn batch(stdin: bool, format: OutputFormat) -> Result<()> {
+/// Apply a parsed batch, or with `dry_run` print what it would change and
+/// fail if any operation would
+async fn apply_request(
+    pool: &SqlitePool,
+    request: &BatchRequest,
+    session_id: Option<&str>,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    if dry_run {
+        let plan = services::plan_batch(pool, request).await?;
+        println!("{}", Formatter::new(format).format_batch_plan(&plan));
+        let failing = plan.iter().filter(|c| c.error.is_some()).count();
+        if failing > 0 {
+            return Err(GranaryError::InvalidArgument(format!(
+                "{} of {} operation(s) would fail; nothing was applied",
+                failing,
+                plan.len()
+            )));
+        }
+        return Ok(());
+    }
+
+    let results = services::apply_batch(pool, request, session_id).await?;
+    print_results(&results, false, format)
+}
+
+/// Process a batch of operations from stdin: JSONL (one JSON object per
+/// line, applied as each line is read) unless another format is given
+pub async fn batch(
+    stdin: bool,
+    input_format: Option<BatchFormat>,
+    format: OutputFormat,
+) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
+    let session_id = workspace.current_session_id();
 
     if !stdin {
-        return Err(crate::error::GranaryError::InvalidArgument(
+        return Err(GranaryError::InvalidArgument(
             "Use --stdin to read from stdin".to_string(),
         ));
     }
@@ -70,39 +215,54 @@ pub async fn batch(stdin: bool, format: OutputFormat) -> Result<()> {
     let stdin = io::stdin();
     let mut all_results = Vec::new();
 
-    for line in stdin.lock().lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
+    match input_format.unwrap_or(BatchFormat::Jsonl) {
+        BatchFormat::Jsonl => {
+            for line in stdin.lock().lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
 
-        // Each line is a single operation
-        let op: services::batch_service::BatchOp = serde_json::from_str(&line)?;
-        let request = BatchRequest { ops: vec![op] };
-        let results = services::apply_batch(&pool, &request).await?;
-        all_results.extend(results);
+                // Each line is a single operation
+                let op: BatchOp = serde_json::from_str(&line)?;
+                let request = BatchRequest { ops: vec![op] };
+                let results = services::apply_batch(&pool, &request, session_id.as_deref()).await?;
+                all_results.extend(results);
+            }
+        }
+        input_format => {
+            let mut input = String::new();
+            stdin.lock().read_to_string(&mut input)?;
+            let request = batch_input::parse(&input, input_format)?;
+            all_results = services::apply_batch(&pool, &request, session_id.as_deref()).await?;
+        }
     }
 
+    print_results(&all_results, true, format)
+}
+
+/// Print batch results; in table output, `errors_only` leaves out the
+/// operations that succeeded
+fn print_results(results: &[BatchResult], errors_only: bool, format: OutputFormat) -> Result<()> {
     match format {
         OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&all_results)?);
+            println!("{}", serde_json::to_string_pretty(results)?);
+        }
+        OutputFormat::Yaml => {
+            print!("{}", serde_yaml::to_string(results)?);
         }
         _ => {
-            let success_count = all_results.iter().filter(|r| r.success).count();
-            let fail_count = all_results.len() - success_count;
+            let success_count = results.iter().filter(|r| r.success).count();
+            let fail_count = results.len() - success_count;
 
             println!(
                 "Batch complete: {} succeeded, {} failed",
                 success_count, fail_count
             );
 
-            for result in &all_results {
-                if !result.success {
-                    println!(
-                        "  [ERR] {}: {}",
-                        result.op,
-                        result.error.as_deref().unwrap_or("Unknown error")
-                    );
+            for result in results {
+                if !(errors_only && result.success) {
+                    println!("{}", result_line(result));
                 }
             }
         }
@@ -110,3 +270,204 @@ pub async fn batch(stdin: bool, format: OutputFormat) -> Result<()> {
 
     Ok(())
 }
+
+/// Write the result of one streamed operation as soon as it is known: a JSON
+/// line with `--format json`, a YAML document with `--format yaml`
+fn write_ack(out: &mut impl Write, result: &BatchResult, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(result)?)?,
+        OutputFormat::Yaml => write!(out, "---\n{}", serde_yaml::to_string(result)?)?,
+        _ => writeln!(out, "{}", result_line(result))?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// One line of table output for a batch result
+fn result_line(result: &BatchResult) -> String {
+    match (result.success, &result.id) {
+        (true, Some(id)) => format!("  [OK] {} -> {}", result.op, id),
+        (true, None) => format!("  [OK] {}", result.op),
+        (false, _) => format!(
+            "  [ERR] {}: {}",
+            result.op,
+            result.error.as_deref().unwrap_or("Unknown error")
+        ),
+    }
+}
+
+/// Handle `granary batch` subcommands
+pub async fn batch_action(action: BatchAction, format: OutputFormat) -> Result<()> {
+    match action {
+        BatchAction::Apply {
+            file,
+            input_format,
+            dry_run,
+        } => {
+            if file.as_os_str() == "-" {
+                apply_stdin(input_format, dry_run, format).await
+            } else {
+                apply_file(&file, input_format, dry_run, format).await
+            }
+        }
+        BatchAction::Update {
+            filters,
+            sets,
+            yes,
+            dry_run,
+        } => bulk_update(&filters, &sets, yes, dry_run, format).await,
+    }
+}
+
+/// Preview the tasks matching a filter, confirm, then update them together
+async fn bulk_update(
+    filters: &[String],
+    sets: &[String],
+    yes: bool,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    let query: SearchQuery = filters
+        .join(" ")
+        .parse()
+        .map_err(GranaryError::InvalidArgument)?;
+    let changes = TaskChangeSet::parse(sets)?;
+    if changes.is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "No changes given. Use --set KEY:VALUE".to_string(),
+        ));
+    }
+
+    let tasks = services::find_tasks(&pool, &query).await?;
+    if tasks.is_empty() {
+        eprintln!("No tasks match the filter");
+        if format.is_structured() {
+            println!("{}", formatter.format_tasks(&[]));
+        }
+        return Ok(());
+    }
+
+    // The preview goes to stderr so JSON and YAML output stay parseable
+    eprintln!(
+        "{} task(s) match. Changes: {}",
+        tasks.len(),
+        changes.describe()
+    );
+    if dry_run {
+        println!("{}", formatter.format_tasks(&tasks));
+        return Ok(());
+    }
+    if !format.is_structured() {
+        println!("{}", formatter.format_tasks(&tasks));
+    }
+
+    if !yes {
+        if !io::stdin().is_terminal() {
+            return Err(GranaryError::InvalidArgument(
+                "Refusing to update tasks without confirmation. Pass --yes to apply".to_string(),
+            ));
+        }
+        eprint!("Apply to {} task(s)? [y/N] ", tasks.len());
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let updated = services::batch_service::bulk_update_tasks(
+        &pool,
+        &tasks,
+        &changes,
+        workspace.current_session_id().as_deref(),
+    )
+    .await?;
+    if format.is_structured() {
+        println!("{}", formatter.format_tasks(&updated));
+    } else {
+        println!("Updated {} task(s)", updated.len());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CreateProject;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_apply_lines_acks_each_line_and_fails_if_any_did() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Stream".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let input = format!(
+            "{}\nnot json\n\n{}\n{}\n",
+            serde_json::json!({ "op": "task.create", "project_id": project.id, "title": "First" }),
+            serde_json::json!({ "op": "task.update", "id": "nope-0000-task-1", "title": "Missing" }),
+            serde_json::json!({ "op": "task.create", "project_id": project.id, "title": "Last" }),
+        );
+        let mut out = Vec::new();
+        let err = apply_lines(&pool, input.as_bytes(), &mut out, None, OutputFormat::Json)
+            .await
+            .unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::exit_codes::USER_ERROR);
+        assert_eq!(
+            err.to_string(),
+            "Invalid argument: 2 of 4 operation(s) failed"
+        );
+
+        let acks: Vec<BatchResult> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let summary: Vec<(usize, &str, bool)> = acks
+            .iter()
+            .map(|a| (a.index, a.op.as_str(), a.success))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, "task.create", true),
+                (1, "unknown", false),
+                (2, "task.update", false),
+                (3, "task.create", true),
+            ]
+        );
+        assert!(acks[1].error.as_deref().unwrap().starts_with("Line 2:"));
+        let tasks = services::list_tasks_by_project(&pool, &project.id, false)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        // A stream where every line applies succeeds
+        let input =
+            serde_json::json!({ "op": "task.create", "project_id": project.id, "title": "Ok" })
+                .to_string();
+        let mut out = Vec::new();
+        apply_lines(&pool, input.as_bytes(), &mut out, None, OutputFormat::Table)
+            .await
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("  [OK] task.create -> "));
+        assert!(out.ends_with("Batch complete: 1 succeeded, 0 failed\n"));
+    }
+}
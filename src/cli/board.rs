@@ -0,0 +1,48 @@
+//! `granary board`: tasks as a kanban board.
+
+use std::time::Duration;
+
+use crate::cli::watch::{watch_loop, watch_status_line};
+use crate::error::Result;
+use crate::models::Board;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{self, Workspace};
+
+/// Show the board, redrawing every `interval` seconds in watch mode
+pub async fn board(
+    project: Option<String>,
+    format: OutputFormat,
+    watch: bool,
+    interval: u64,
+) -> Result<()> {
+    if watch {
+        let interval_duration = Duration::from_secs(interval);
+        watch_loop(interval_duration, || async {
+            let output = render_board(project.as_deref(), format).await?;
+            Ok(format!(
+                "{}\n\n{}\n",
+                watch_status_line(interval_duration),
+                output
+            ))
+        })
+        .await?;
+    } else {
+        println!("{}", render_board(project.as_deref(), format).await?);
+    }
+    Ok(())
+}
+
+async fn render_board(project: Option<&str>, format: OutputFormat) -> Result<String> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let tasks = match project {
+        Some(id) => {
+            let project = services::get_project(&pool, id).await?;
+            services::list_tasks_by_project(&pool, &project.id, false).await?
+        }
+        None => services::list_all_tasks(&pool, false).await?,
+    };
+    let board = Board::new(project.map(str::to_string), tasks);
+    Ok(Formatter::new(format).format_board(&board))
+}
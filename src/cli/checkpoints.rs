@@ -1,6 +1,6 @@
 use crate::cli::args::CheckpointAction;
 use crate::error::{GranaryError, Result};
-use crate::output::{Formatter, OutputFormat, json};
+use crate::output::{Formatter, OutputFormat, json, yaml};
 use crate::services::{self, Workspace};
 
 /// Handle checkpoint subcommands
@@ -32,6 +32,9 @@ pub async fn checkpoint(action: CheckpointAction, format: OutputFormat) -> Resul
                 OutputFormat::Json => {
                     println!("{}", json::format_checkpoint_diff(&diff));
                 }
+                OutputFormat::Yaml => {
+                    print!("{}", yaml::format_checkpoint_diff(&diff));
+                }
                 _ => {
                     println!("Diff: {} -> {}", diff.from, diff.to);
                     println!();
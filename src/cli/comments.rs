@@ -2,7 +2,7 @@ use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::*;
 use crate::output::{Formatter, OutputFormat};
-use crate::services::Workspace;
+use crate::services::{self, Workspace};
 
 /// Show a comment by ID
 pub async fn show_comment(id: &str, format: OutputFormat) -> Result<()> {
@@ -29,30 +29,16 @@ pub async fn update_comment(
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let mut comment = db::comments::get(&pool, id)
-        .await?
-        .ok_or_else(|| GranaryError::CommentNotFound(id.to_string()))?;
-
-    if let Some(c) = content {
-        comment.content = c;
-    }
-    if let Some(k) = kind
-        && let Ok(ck) = k.parse::<CommentKind>()
-    {
-        comment.kind = ck.as_str().to_string();
-    }
-
-    let updated = db::comments::update(&pool, &comment).await?;
-    if !updated {
-        return Err(GranaryError::VersionMismatch {
-            expected: comment.version,
-            found: comment.version + 1,
-        });
-    }
-
-    let updated_comment = db::comments::get(&pool, id)
-        .await?
-        .ok_or_else(|| GranaryError::CommentNotFound(id.to_string()))?;
+    let updated_comment = services::update_comment(
+        &pool,
+        id,
+        UpdateComment {
+            content,
+            kind: kind.and_then(|k| k.parse::<CommentKind>().ok()),
+            meta: None,
+        },
+    )
+    .await?;
 
     let formatter = Formatter::new(format);
     println!("{}", formatter.format_comment(&updated_comment));
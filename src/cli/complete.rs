@@ -0,0 +1,562 @@
+//! Shell completions: the scripts printed by `granary completions` and the
+//! hidden `granary __complete` command they call on every <TAB>.
+//!
+//! The scripts pass the words typed so far; the last word is the one under
+//! the cursor. Commands and flags come from the clap definition, and IDs,
+//! labels and saved searches are read live from the workspace.
+
+use clap::{Arg, Command, CommandFactory};
+
+use crate::cli::args::{Cli, CompletionShell};
+use crate::db;
+use crate::error::Result;
+use crate::models::{RunStatus, TaskPriority, TaskStatus};
+use crate::services::{
+    Workspace, global_config_service, initiative_service, plugin_service, project_service,
+    remote_service, search_service, task_service,
+};
+
+const BASH_SCRIPT: &str = r#"# granary bash completion
+_granary() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    [[ "$cur" == "=" ]] && cur=""
+    local IFS=$'\n'
+    local values
+    values=$(granary __complete -- "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null | cut -f1)
+    COMPREPLY=($(compgen -W "$values" -- "$cur"))
+}
+complete -o default -F _granary granary
+"#;
+
+const ZSH_SCRIPT: &str = r#"#compdef granary
+# granary zsh completion
+_granary() {
+    local -a candidates
+    local line value desc
+    for line in "${(@f)$(granary __complete -- "${words[@]:1:$((CURRENT - 1))}" 2>/dev/null)}"; do
+        [[ -z "$line" ]] && continue
+        value="${line%%$'\t'*}"
+        desc="${line#*$'\t'}"
+        [[ "$desc" == "$line" ]] && desc=""
+        value="${value//:/\\:}"
+        if [[ -n "$desc" ]]; then
+            candidates+=("$value:$desc")
+        else
+            candidates+=("$value")
+        fi
+    done
+    _describe 'granary' candidates
+}
+if [[ "$funcstack[1]" == "_granary" ]]; then
+    _granary "$@"
+else
+    compdef _granary granary
+fi
+"#;
+
+const FISH_SCRIPT: &str = r#"# granary fish completion
+function __granary_complete
+    set -l tokens (commandline -opc)
+    granary __complete -- $tokens[2..-1] (commandline -ct) 2>/dev/null
+end
+complete -c granary -f -a '(__granary_complete)'
+"#;
+
+/// Print the completion script for a shell
+pub fn completions(shell: CompletionShell) {
+    let script = match shell {
+        CompletionShell::Bash => BASH_SCRIPT,
+        CompletionShell::Zsh => ZSH_SCRIPT,
+        CompletionShell::Fish => FISH_SCRIPT,
+    };
+    print!("{}", script);
+}
+
+/// Print completions for a partial command line, one `value\tdescription`
+/// per line. Lookups that fail print nothing rather than an error, so a
+/// missing workspace never breaks the shell.
+pub async fn complete(mut words: Vec<String>) -> Result<()> {
+    if words.first().is_some_and(|w| w == "--") {
+        words.remove(0);
+    }
+    let mut current = words.pop().unwrap_or_default();
+    // bash passes the `=` of `--flag=` as a word of its own
+    if current == "=" {
+        words.push(current);
+        current = String::new();
+    }
+
+    let mut cli = Cli::command();
+    cli.build();
+    let plan = plan(&cli, &words, &current);
+
+    let mut candidates = plan.candidates;
+    if let Some(kind) = plan.kind {
+        candidates.extend(values(kind).await);
+    }
+
+    let partial = current.strip_prefix(&plan.prefix).unwrap_or(&current);
+    for candidate in candidates {
+        if !candidate.value.starts_with(partial) {
+            continue;
+        }
+        if candidate.description.is_empty() {
+            println!("{}{}", plan.prefix, candidate.value);
+        } else {
+            println!(
+                "{}{}\t{}",
+                plan.prefix, candidate.value, candidate.description
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Values that have to be looked up rather than read from the clap definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Task,
+    Project,
+    Initiative,
+    Entity,
+    Label,
+    Worker,
+    Run,
+    Session,
+    SavedSearch,
+    Remote,
+    Workspace,
+    TaskStatus,
+    RunStatus,
+    Priority,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Candidate {
+    value: String,
+    description: String,
+}
+
+impl Candidate {
+    fn new(value: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            description: first_line(&description.into()),
+        }
+    }
+}
+
+/// What to offer for the word under the cursor
+#[derive(Debug, Default)]
+struct Plan {
+    /// Prepended to every value, e.g. `--label=` when completing inline
+    prefix: String,
+    candidates: Vec<Candidate>,
+    kind: Option<ValueKind>,
+}
+
+/// Walk the words before the cursor through the command tree and decide what
+/// the current word can be
+fn plan(root: &Command, previous: &[String], current: &str) -> Plan {
+    let mut cmd = root;
+    let mut path: Vec<&str> = Vec::new();
+    let mut pending: Option<&Arg> = None;
+    let mut positional = 0;
+    let mut only_positionals = false;
+
+    for word in previous {
+        if let Some(arg) = pending.take() {
+            // bash splits `--flag=value` into `--flag`, `=`, `value`
+            if word == "=" {
+                pending = Some(arg);
+            }
+            continue;
+        }
+        if !only_positionals && word == "--" {
+            only_positionals = true;
+            continue;
+        }
+        if !only_positionals && word.starts_with('-') && word.len() > 1 {
+            if let Some(arg) = find_flag(cmd, word)
+                && takes_value(arg)
+                && !has_inline_value(word)
+            {
+                pending = Some(arg);
+            }
+            continue;
+        }
+
+        let positionals: Vec<&Arg> = cmd.get_positionals().collect();
+        let awaiting_required = positionals
+            .get(positional)
+            .is_some_and(|arg| arg.is_required_set());
+        if !awaiting_required && let Some(sub) = find_subcommand(cmd, word) {
+            cmd = sub;
+            path.push(sub.get_name());
+            positional = 0;
+            continue;
+        }
+        if let Some(arg) = positionals.get(positional)
+            && !is_multiple(arg)
+        {
+            positional += 1;
+        }
+    }
+
+    if let Some(arg) = pending {
+        return arg_plan(&path, arg, String::new());
+    }
+
+    if !only_positionals && current.starts_with('-') {
+        if let Some((flag, _)) = current.split_once('=') {
+            return match find_flag(cmd, flag) {
+                Some(arg) if takes_value(arg) => arg_plan(&path, arg, format!("{}=", flag)),
+                _ => Plan::default(),
+            };
+        }
+        let candidates = cmd
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set() && !arg.is_positional())
+            .filter_map(|arg| {
+                let long = arg.get_long()?;
+                let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+                Some(Candidate::new(format!("--{}", long), help))
+            })
+            .collect();
+        return Plan {
+            candidates,
+            ..Plan::default()
+        };
+    }
+
+    let positionals: Vec<&Arg> = cmd.get_positionals().collect();
+    let next = positionals.get(positional).copied();
+    let mut plan = match next {
+        Some(arg) => arg_plan(&path, arg, String::new()),
+        None => Plan::default(),
+    };
+    if !next.is_some_and(|arg| arg.is_required_set()) {
+        plan.candidates.extend(
+            cmd.get_subcommands()
+                .filter(|sub| !sub.is_hide_set())
+                .map(|sub| {
+                    let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+                    Candidate::new(sub.get_name(), about)
+                }),
+        );
+        if path.is_empty() {
+            plan.candidates.extend(
+                plugin_service::discover_plugins()
+                    .into_iter()
+                    .map(|plugin| Candidate::new(plugin.name, "Plugin")),
+            );
+        }
+    }
+    plan
+}
+
+/// Values for one argument: its declared choices, or a lookup
+fn arg_plan(path: &[&str], arg: &Arg, prefix: String) -> Plan {
+    let candidates: Vec<Candidate> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| {
+            let help = value.get_help().map(|h| h.to_string()).unwrap_or_default();
+            Candidate::new(value.get_name(), help)
+        })
+        .collect();
+    let kind = if candidates.is_empty() {
+        value_kind(path, arg.get_id().as_str())
+    } else {
+        None
+    };
+    Plan {
+        prefix,
+        candidates,
+        kind,
+    }
+}
+
+/// Map an argument to the kind of value it takes, by name and by the
+/// command it belongs to
+fn value_kind(path: &[&str], arg_id: &str) -> Option<ValueKind> {
+    let in_runs = path.iter().any(|p| matches!(*p, "run" | "runs"));
+    match arg_id {
+        "task_id" | "task_ids" | "task" | "tasks" | "depends_on_id" => Some(ValueKind::Task),
+        "project" | "project_id" => Some(ValueKind::Project),
+        "initiative" | "initiative_id" => Some(ValueKind::Initiative),
+        "entity" => Some(ValueKind::Entity),
+        "label" => Some(ValueKind::Label),
+        "worker" | "worker_id" => Some(ValueKind::Worker),
+        "run_id" => Some(ValueKind::Run),
+        "session_id" => Some(ValueKind::Session),
+        "saved" => Some(ValueKind::SavedSearch),
+        "remote" => Some(ValueKind::Remote),
+        "name" if path.ends_with(&["remote", "remove"]) => Some(ValueKind::Remote),
+        "workspace" => Some(ValueKind::Workspace),
+        "name" if path.contains(&"workspaces") => Some(ValueKind::Workspace),
+        "status" if in_runs => Some(ValueKind::RunStatus),
+        "status" => Some(ValueKind::TaskStatus),
+        "priority" => Some(ValueKind::Priority),
+        // A bare `id` belongs to the closest noun in the command path
+        "id" => path.iter().rev().find_map(|p| match *p {
+            "task" | "tasks" => Some(ValueKind::Task),
+            "project" | "projects" => Some(ValueKind::Project),
+            "initiative" | "initiatives" => Some(ValueKind::Initiative),
+            "worker" | "workers" => Some(ValueKind::Worker),
+            "run" | "runs" => Some(ValueKind::Run),
+            "session" | "sessions" => Some(ValueKind::Session),
+            "show" => Some(ValueKind::Entity),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Look up the values for a kind, ignoring errors
+async fn values(kind: ValueKind) -> Vec<Candidate> {
+    match kind {
+        ValueKind::TaskStatus => [
+            TaskStatus::Draft,
+            TaskStatus::Todo,
+            TaskStatus::InProgress,
+            TaskStatus::Done,
+            TaskStatus::Blocked,
+        ]
+        .iter()
+        .map(|s| Candidate::new(s.as_str(), ""))
+        .collect(),
+        ValueKind::RunStatus => [
+            RunStatus::Pending,
+            RunStatus::Running,
+            RunStatus::Completed,
+            RunStatus::Failed,
+            RunStatus::Paused,
+            RunStatus::Cancelled,
+            RunStatus::Killed,
+            RunStatus::TimedOut,
+        ]
+        .iter()
+        .map(|s| Candidate::new(s.as_str(), ""))
+        .collect(),
+        ValueKind::Priority => [
+            TaskPriority::P0,
+            TaskPriority::P1,
+            TaskPriority::P2,
+            TaskPriority::P3,
+            TaskPriority::P4,
+        ]
+        .iter()
+        .map(|p| Candidate::new(p.as_str(), ""))
+        .collect(),
+        ValueKind::Worker | ValueKind::Run | ValueKind::Workspace => {
+            global_values(kind).await.unwrap_or_default()
+        }
+        _ => workspace_values(kind).await.unwrap_or_default(),
+    }
+}
+
+async fn workspace_values(kind: ValueKind) -> Result<Vec<Candidate>> {
+    let pool = Workspace::find()?.pool().await?;
+    let mut candidates = Vec::new();
+
+    if matches!(kind, ValueKind::Task | ValueKind::Entity) {
+        for task in task_service::list_all_tasks(&pool, false).await? {
+            candidates.push(Candidate::new(task.id, task.title));
+        }
+    }
+    if matches!(kind, ValueKind::Project | ValueKind::Entity) {
+        for project in project_service::list_projects(&pool, false).await? {
+            candidates.push(Candidate::new(project.id, project.name));
+        }
+    }
+    if matches!(kind, ValueKind::Initiative | ValueKind::Entity) {
+        for initiative in initiative_service::list_initiatives(&pool, false).await? {
+            candidates.push(Candidate::new(initiative.id, initiative.name));
+        }
+    }
+    match kind {
+        ValueKind::Label => {
+            for name in db::labels::list_names(&pool).await? {
+                candidates.push(Candidate::new(name, ""));
+            }
+        }
+        ValueKind::Session => {
+            for session in db::sessions::list(&pool, false).await? {
+                candidates.push(Candidate::new(session.id, session.name.unwrap_or_default()));
+            }
+        }
+        ValueKind::SavedSearch => {
+            for (name, query) in search_service::list_saved_searches(&pool).await? {
+                candidates.push(Candidate::new(name, query));
+            }
+        }
+        ValueKind::Remote => {
+            for remote in remote_service::list_remotes(&pool).await? {
+                candidates.push(Candidate::new(remote.name, remote.url));
+            }
+        }
+        _ => {}
+    }
+    Ok(candidates)
+}
+
+async fn global_values(kind: ValueKind) -> Result<Vec<Candidate>> {
+    let pool = global_config_service::global_pool().await?;
+    let candidates = match kind {
+        ValueKind::Worker => db::workers::list(&pool)
+            .await?
+            .into_iter()
+            .map(|worker| {
+                let description = worker.runner_name.unwrap_or(worker.command);
+                Candidate::new(worker.id, description)
+            })
+            .collect(),
+        ValueKind::Workspace => db::workspaces::list(&pool)
+            .await?
+            .into_iter()
+            .map(|workspace| Candidate::new(workspace.name, workspace.root))
+            .collect(),
+        _ => db::runs::list_all(&pool)
+            .await?
+            .into_iter()
+            .map(|run| Candidate::new(run.id, format!("{} {}", run.status, run.entity_id)))
+            .collect(),
+    };
+    Ok(candidates)
+}
+
+fn find_subcommand<'a>(cmd: &'a Command, word: &str) -> Option<&'a Command> {
+    cmd.get_subcommands()
+        .find(|sub| sub.get_name() == word || sub.get_all_aliases().any(|alias| alias == word))
+}
+
+/// Resolve `--long`, `--long=value`, `-s` or `-svalue` to its argument
+fn find_flag<'a>(cmd: &'a Command, word: &str) -> Option<&'a Arg> {
+    if let Some(long) = word.strip_prefix("--") {
+        let name = long.split_once('=').map_or(long, |(name, _)| name);
+        return cmd.get_arguments().find(|arg| {
+            arg.get_long() == Some(name)
+                || arg
+                    .get_all_aliases()
+                    .is_some_and(|aliases| aliases.contains(&name))
+        });
+    }
+    let short = word.strip_prefix('-')?.chars().next()?;
+    cmd.get_arguments()
+        .find(|arg| arg.get_short() == Some(short))
+}
+
+/// Whether a flag word already carries its value (`--flag=value`, `-svalue`)
+fn has_inline_value(word: &str) -> bool {
+    if word.starts_with("--") {
+        word.contains('=')
+    } else {
+        word.len() > 2
+    }
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn is_multiple(arg: &Arg) -> bool {
+    arg.get_num_args()
+        .is_some_and(|range| range.max_values() > 1)
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_for(line: &str) -> Plan {
+        let mut cli = Cli::command();
+        cli.build();
+        let mut words: Vec<String> = line.split(' ').map(str::to_string).collect();
+        let current = words.pop().unwrap();
+        plan(&cli, &words, &current)
+    }
+
+    fn names(plan: &Plan) -> Vec<&str> {
+        plan.candidates.iter().map(|c| c.value.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complete_subcommands() {
+        let plan = plan_for("");
+        let names = names(&plan);
+        assert!(names.contains(&"tasks"));
+        assert!(names.contains(&"completions"));
+        assert!(!names.contains(&"__complete"));
+        assert_eq!(plan.kind, None);
+
+        let plan = plan_for("task my-proj-abc1-task-1 ");
+        assert!(names_contains(&plan, "update"));
+        assert_eq!(plan.kind, None);
+    }
+
+    fn names_contains(plan: &Plan, name: &str) -> bool {
+        plan.candidates.iter().any(|c| c.value == name)
+    }
+
+    #[test]
+    fn test_complete_positional_ids() {
+        assert_eq!(plan_for("task ").kind, Some(ValueKind::Task));
+        assert!(plan_for("task ").candidates.is_empty());
+        assert_eq!(plan_for("project ").kind, Some(ValueKind::Project));
+        assert_eq!(plan_for("show ").kind, Some(ValueKind::Entity));
+        assert_eq!(plan_for("worker logs ").kind, Some(ValueKind::Worker));
+        assert_eq!(plan_for("run status ").kind, Some(ValueKind::Run));
+        assert_eq!(
+            plan_for("task my-proj-abc1-task-1 deps add a ").kind,
+            Some(ValueKind::Task)
+        );
+    }
+
+    #[test]
+    fn test_complete_flag_values() {
+        assert_eq!(plan_for("tasks --label ").kind, Some(ValueKind::Label));
+        assert_eq!(
+            plan_for("tasks --status ").kind,
+            Some(ValueKind::TaskStatus)
+        );
+        assert_eq!(plan_for("runs --status ").kind, Some(ValueKind::RunStatus));
+        assert_eq!(plan_for("push --remote ").kind, Some(ValueKind::Remote));
+        assert_eq!(plan_for("remote remove ").kind, Some(ValueKind::Remote));
+        assert_eq!(plan_for("remote add url --name ").kind, None);
+        assert_eq!(plan_for("--workspace ").kind, Some(ValueKind::Workspace));
+        assert_eq!(
+            plan_for("workspaces rename ").kind,
+            Some(ValueKind::Workspace)
+        );
+
+        let plan = plan_for("tasks --label=");
+        assert_eq!(plan.prefix, "--label=");
+        assert_eq!(plan.kind, Some(ValueKind::Label));
+
+        // bash splits `--label=` into separate words
+        assert_eq!(plan_for("tasks --label = ").kind, Some(ValueKind::Label));
+
+        // Global flags with declared choices
+        let plan = plan_for("tasks --format ");
+        assert!(names_contains(&plan, "json"));
+        assert_eq!(plan.kind, None);
+    }
+
+    #[test]
+    fn test_complete_flags() {
+        let plan = plan_for("tasks --");
+        assert!(names_contains(&plan, "--label"));
+        assert!(names_contains(&plan, "--json"));
+        assert_eq!(plan.kind, None);
+
+        // A flag's value is not mistaken for a subcommand or positional
+        let plan = plan_for("tasks --label task ");
+        assert!(names_contains(&plan, "label"));
+    }
+}
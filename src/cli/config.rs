@@ -1,13 +1,16 @@
-use crate::cli::args::{ConfigAction, RunnersAction, SteeringAction};
+use crate::cli::args::{ConfigAction, ConfigFile, RunnersAction, SteeringAction};
 use crate::db;
-use crate::error::Result;
-use crate::models::global_config::RunnerConfig;
+use crate::error::{GranaryError, Result};
+use crate::models::global_config::{GlobalConfig, RunnerConfig, RunnerLimits};
+use crate::models::workspace_config::WorkspaceConfig;
 use crate::output::OutputFormat;
-use crate::services::{Workspace, global_config_service};
+use crate::services::config_validation::{self, Diagnostic, Severity};
+use crate::services::{Workspace, global_config_service, workspace_config};
+use serde::Serialize;
 use std::collections::HashMap;
 
 /// Handle config subcommands
-pub async fn config(action: ConfigAction, _format: OutputFormat) -> Result<()> {
+pub async fn config(action: ConfigAction, format: OutputFormat) -> Result<()> {
     match action {
         // Workspace-level config commands need workspace
         ConfigAction::Get { key } => {
@@ -23,6 +26,7 @@ pub async fn config(action: ConfigAction, _format: OutputFormat) -> Result<()> {
         ConfigAction::Set { key, value } => {
             let workspace = Workspace::find()?;
             let pool = workspace.pool().await?;
+            config_validation::validate_config_value(&key, &value)?;
             db::config::set(&pool, &key, &value).await?;
             println!("Set {} = {}", key, value);
         }
@@ -51,6 +55,21 @@ pub async fn config(action: ConfigAction, _format: OutputFormat) -> Result<()> {
             }
         }
 
+        ConfigAction::Show { effective } => show(effective, format).await?,
+
+        ConfigAction::Validate => validate(format).await?,
+
+        ConfigAction::Schema { file } => {
+            let schema = match file {
+                ConfigFile::Global => schemars::schema_for!(GlobalConfig),
+                ConfigFile::Workspace => schemars::schema_for!(WorkspaceConfig),
+            };
+            match format {
+                OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&schema)?),
+                _ => println!("{}", serde_json::to_string_pretty(&schema)?),
+            }
+        }
+
         // Global config commands (don't need workspace)
         ConfigAction::Edit => {
             let path = global_config_service::config_path()?;
@@ -67,6 +86,115 @@ pub async fn config(action: ConfigAction, _format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+/// Config files for `granary config show`, as written
+#[derive(Serialize)]
+struct ConfigFiles {
+    global: GlobalConfig,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace: Option<WorkspaceConfig>,
+}
+
+/// Show the config files, or the merged settings with `effective`
+async fn show(effective: bool, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find().ok();
+    let workspace_file = workspace.as_ref().map(workspace_config::load).transpose()?;
+
+    if effective {
+        let values = match &workspace {
+            Some(workspace) => db::config::list(&workspace.pool().await?).await?,
+            None => Vec::new(),
+        };
+        let merged = workspace_config::effective(
+            global_config_service::load()?,
+            workspace_file.unwrap_or_default(),
+            workspace.as_ref(),
+            values,
+        );
+        print_config(&merged, format)
+    } else {
+        let files = ConfigFiles {
+            global: global_config_service::load_file()?,
+            workspace: workspace_file,
+        };
+        print_config(&files, format)
+    }
+}
+
+fn print_config<T: Serialize>(value: &T, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+        _ => print!(
+            "{}",
+            toml::to_string_pretty(value).map_err(|e| GranaryError::Other(e.to_string()))?
+        ),
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ValidationReport {
+    diagnostics: Vec<Diagnostic>,
+    errors: usize,
+    warnings: usize,
+}
+
+async fn validate(format: OutputFormat) -> Result<()> {
+    let mut diagnostics = Vec::new();
+    diagnostics.extend(config_validation::check_global_config(
+        &global_config_service::config_path()?,
+    ));
+    if let Ok(workspace) = Workspace::find() {
+        diagnostics.extend(config_validation::check_workspace_config(
+            &workspace_config::path(&workspace),
+        ));
+        diagnostics.extend(config_validation::check_config_keys(&workspace.pool().await?).await?);
+        let workers = db::workers::list_by_instance(
+            &global_config_service::global_pool().await?,
+            &workspace.root.to_string_lossy(),
+        )
+        .await?;
+        diagnostics.extend(config_validation::check_workers(&workers));
+    }
+
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let report = ValidationReport {
+        errors: count(Severity::Error),
+        warnings: count(Severity::Warning),
+        diagnostics,
+    };
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&report)?),
+        _ => {
+            for diagnostic in &report.diagnostics {
+                println!("{}", diagnostic);
+            }
+            if report.diagnostics.is_empty() {
+                println!("Config is valid");
+            } else {
+                println!(
+                    "\n{} error(s), {} warning(s)",
+                    report.errors, report.warnings
+                );
+            }
+        }
+    }
+
+    if report.errors > 0 {
+        return Err(GranaryError::InvalidArgument(format!(
+            "config has {} error(s)",
+            report.errors
+        )));
+    }
+    Ok(())
+}
+
 /// Handle runners subcommands
 async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
     match action {
@@ -80,7 +208,18 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
             } else {
                 println!("Configured runners:\n");
                 for (name, runner) in &config.runners {
-                    println!("  {} -> {} {}", name, runner.command, runner.args.join(" "));
+                    let command = std::iter::once(&runner.command)
+                        .chain(&runner.args)
+                        .filter(|part| !part.is_empty())
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    match runner.extends {
+                        Some(ref extends) => {
+                            println!("  {} (extends {}) -> {}", name, extends, command)
+                        }
+                        None => println!("  {} -> {}", name, command),
+                    }
                     if let Some(c) = runner.concurrency {
                         println!("    concurrency: {}", c);
                     }
@@ -105,18 +244,31 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
         Some(RunnersAction::Add {
             name,
             command,
+            extends,
             args,
             concurrency,
             on,
             env_vars,
+            env_file,
+            timeout,
+            grace_period,
+            stdin,
+            log_format,
         }) => {
             let env = parse_env_vars(&env_vars);
             let runner = RunnerConfig {
-                command,
+                extends,
+                command: command.unwrap_or_default(),
                 args,
                 concurrency,
                 on,
                 env,
+                env_file,
+                limits: RunnerLimits::default(),
+                timeout_secs: timeout,
+                grace_period_secs: grace_period,
+                stdin,
+                log_format: log_format.unwrap_or_default(),
             };
             global_config_service::set_runner(&name, runner)?;
             println!("Added runner: {}", name);
@@ -125,10 +277,16 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
         Some(RunnersAction::Update {
             name,
             command,
+            extends,
             args,
             concurrency,
             on,
             env_vars,
+            env_file,
+            timeout,
+            grace_period,
+            stdin,
+            log_format,
         }) => {
             let existing = global_config_service::get_runner(&name)?;
             match existing {
@@ -136,6 +294,9 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
                     if let Some(cmd) = command {
                         runner.command = cmd;
                     }
+                    if extends.is_some() {
+                        runner.extends = extends;
+                    }
                     if let Some(a) = args {
                         runner.args = a;
                     }
@@ -148,6 +309,21 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
                     if let Some(env_vec) = env_vars {
                         runner.env = parse_env_vars(&env_vec);
                     }
+                    if env_file.is_some() {
+                        runner.env_file = env_file;
+                    }
+                    if timeout.is_some() {
+                        runner.timeout_secs = timeout;
+                    }
+                    if grace_period.is_some() {
+                        runner.grace_period_secs = grace_period;
+                    }
+                    if let Some(stdin) = stdin {
+                        runner.stdin = stdin;
+                    }
+                    if let Some(log_format) = log_format {
+                        runner.log_format = log_format;
+                    }
                     global_config_service::set_runner(&name, runner)?;
                     println!("Updated runner: {}", name);
                 }
@@ -168,31 +344,73 @@ async fn handle_runners_action(action: Option<RunnersAction>) -> Result<()> {
             }
         }
 
-        Some(RunnersAction::Show { name }) => match global_config_service::get_runner(&name)? {
-            Some(runner) => {
-                println!("Runner: {}\n", name);
-                println!("  command: {}", runner.command);
-                if !runner.args.is_empty() {
-                    println!("  args: {:?}", runner.args);
-                }
-                if let Some(c) = runner.concurrency {
-                    println!("  concurrency: {}", c);
-                }
-                if let Some(ref on) = runner.on {
-                    println!("  on: {}", on);
-                }
-                if !runner.env.is_empty() {
-                    println!("  env:");
-                    for (k, v) in &runner.env {
-                        println!("    {}={}", k, v);
+        Some(RunnersAction::Show { name, resolved }) => {
+            let runner = if resolved {
+                workspace_config::get_runner(&name)?
+            } else {
+                global_config_service::get_runner(&name)?
+            };
+            match runner {
+                Some(runner) => {
+                    println!("Runner: {}\n", name);
+                    if let Some(ref extends) = runner.extends {
+                        println!("  extends: {}", extends);
+                    }
+                    if !runner.command.is_empty() {
+                        println!("  command: {}", runner.command);
+                    }
+                    if !runner.args.is_empty() {
+                        println!("  args: {:?}", runner.args);
+                    }
+                    if let Some(c) = runner.concurrency {
+                        println!("  concurrency: {}", c);
+                    }
+                    if let Some(ref on) = runner.on {
+                        println!("  on: {}", on);
+                    }
+                    if let Some(secs) = runner.timeout_secs {
+                        println!("  timeout_secs: {}", secs);
+                    }
+                    if let Some(secs) = runner.grace_period_secs {
+                        println!("  grace_period_secs: {}", secs);
+                    }
+                    if runner.stdin {
+                        println!("  stdin: true");
+                    }
+                    if !runner.log_format.is_text() {
+                        println!("  log_format: {}", runner.log_format.as_str());
+                    }
+                    if !runner.env.is_empty() {
+                        println!("  env:");
+                        for (k, v) in &runner.env {
+                            println!("    {}={}", k, v);
+                        }
+                    }
+                    if let Some(ref env_file) = runner.env_file {
+                        println!("  env_file: {}", env_file);
+                    }
+                    if !runner.limits.is_empty() {
+                        println!("  limits:");
+                        if let Some(mb) = runner.limits.max_memory_mb {
+                            println!("    max_memory_mb: {}", mb);
+                        }
+                        if let Some(secs) = runner.limits.max_cpu_secs {
+                            println!("    max_cpu_secs: {}", secs);
+                        }
+                        if let Some(nice) = runner.limits.nice {
+                            println!("    nice: {}", nice);
+                        }
+                        if let Some(ref cwd) = runner.limits.cwd {
+                            println!("    cwd: {}", cwd);
+                        }
                     }
                 }
+                None => {
+                    println!("Runner not found: {}", name);
+                    std::process::exit(3);
+                }
             }
-            None => {
-                println!("Runner not found: {}", name);
-                std::process::exit(3);
-            }
-        },
+        }
     }
 
     Ok(())
@@ -2,13 +2,14 @@
 //!
 //! The daemon is a long-running background process that manages workers and runs.
 //! These commands allow users to check its status, start/stop it manually,
-//! and view its logs.
+//! view its logs, and install it as a service that starts on login.
 
 use std::path::Path;
 
 use crate::cli::args::DaemonCommand;
-use crate::daemon::DaemonClient;
-use crate::daemon::auto_start::{daemon_pid, is_daemon_running};
+use crate::daemon::auto_start::{daemon_binary_path, daemon_pid, is_daemon_running};
+use crate::daemon::service_unit::{self, ServiceManager, ServiceUnit};
+use crate::daemon::{DaemonClient, IpcEndpoint};
 use crate::error::Result;
 use crate::services::global_config as global_config_service;
 
@@ -20,6 +21,8 @@ pub async fn daemon(command: DaemonCommand) -> Result<()> {
         DaemonCommand::Stop => daemon_stop().await,
         DaemonCommand::Restart => daemon_restart().await,
         DaemonCommand::Logs { follow, lines } => daemon_logs(follow, lines).await,
+        DaemonCommand::Install { print } => daemon_install(print).await,
+        DaemonCommand::Uninstall => daemon_uninstall(),
     }
 }
 
@@ -33,20 +36,17 @@ async fn daemon_status() -> Result<()> {
         println!("Daemon status: running");
         println!("  PID: {}", pid);
         println!("  Version: {}", version);
-        #[cfg(unix)]
-        {
-            let socket_path = global_config_service::daemon_socket_path()?;
-            println!("  Socket: {}", socket_path.display());
-        }
-        #[cfg(windows)]
-        {
-            let pipe_name = global_config_service::daemon_pipe_name();
-            println!("  Pipe: {}", pipe_name);
-        }
+        let endpoint = IpcEndpoint::daemon()?;
+        println!("  {}: {}", endpoint.kind(), endpoint);
     } else {
         println!("Daemon status: not running");
         println!("  Run 'granary daemon start' or any worker command to start it.");
     }
+    if let Ok(manager) = ServiceManager::current()
+        && let Some(path) = service_unit::installed_unit(manager)?
+    {
+        println!("  Service: {} ({})", manager.as_str(), path.display());
+    }
 
     Ok(())
 }
@@ -113,6 +113,48 @@ async fn daemon_restart() -> Result<()> {
     daemon_start().await
 }
 
+/// Install the daemon's service unit, or print it
+async fn daemon_install(print: bool) -> Result<()> {
+    let manager = ServiceManager::current()?;
+    let unit = ServiceUnit::new(manager, &daemon_binary_path()?)?;
+
+    if print {
+        print!("{}", unit.contents);
+        return Ok(());
+    }
+
+    // A daemon started by the CLI would keep the service's daemon from starting
+    if is_daemon_running().await {
+        println!(
+            "Stopping the running daemon so {} can start it...",
+            manager.as_str()
+        );
+        daemon_stop().await?;
+    }
+
+    service_unit::install(&unit)?;
+    println!(
+        "Installed {} unit: {}",
+        manager.as_str(),
+        unit.path.display()
+    );
+    println!("The daemon now starts on login. Remove it with 'granary daemon uninstall'.");
+    Ok(())
+}
+
+/// Remove the daemon's service unit
+fn daemon_uninstall() -> Result<()> {
+    let manager = ServiceManager::current()?;
+    match service_unit::uninstall(manager)? {
+        Some(path) => {
+            println!("Removed {} unit: {}", manager.as_str(), path.display());
+            println!("The daemon is stopped and will start on demand again.");
+        }
+        None => println!("No {} unit installed.", manager.as_str()),
+    }
+    Ok(())
+}
+
 /// Show daemon logs
 async fn daemon_logs(follow: bool, lines: usize) -> Result<()> {
     let log_path = global_config_service::daemon_log_path()?;
@@ -124,10 +166,58 @@ async fn daemon_logs(follow: bool, lines: usize) -> Result<()> {
     }
 
     if follow {
-        crate::cli::worker::follow_log(&log_path, lines).await
+        crate::cli::worker::follow_log_with(&log_path, lines, format_log_line).await
     } else {
-        crate::cli::worker::print_log_tail(&log_path, lines)
+        crate::cli::worker::print_log_tail_with(&log_path, lines, format_log_line)
+    }
+}
+
+/// Render a JSON log line from granaryd as `time LEVEL target: message key=value`.
+///
+/// Lines that are not JSON (e.g. from older daemons) are printed unchanged.
+fn format_log_line(line: &str) -> String {
+    let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_str(line) else {
+        return line.to_string();
+    };
+
+    let mut take = |key: &str| match fields.remove(key) {
+        Some(serde_json::Value::String(s)) => s,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+
+    let timestamp = take("timestamp");
+    let time = chrono::DateTime::parse_from_rfc3339(&timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or(timestamp);
+    let level = take("level");
+    let target = take("target");
+    let message = take("message");
+
+    let mut out = format!("{} {:>5} {}:", time, level, target);
+    if !message.is_empty() {
+        out.push(' ');
+        out.push_str(&message);
+    }
+    for (key, value) in fields {
+        match value {
+            serde_json::Value::String(s) => {
+                // Keep multi-line values (like SQL statements) on one line
+                let s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+                if s.contains(' ') {
+                    out.push_str(&format!(" {}={:?}", key, s));
+                } else {
+                    out.push_str(&format!(" {}={}", key, s));
+                }
+            }
+            other => out.push_str(&format!(" {}={}", key, other)),
+        }
     }
+    out
 }
 
 /// Helper to convert PathBuf to displayable path
@@ -140,6 +230,20 @@ fn display_path(path: &Path) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_log_line() {
+        let line = r#"{"timestamp":"2026-01-20T10:15:30.123456Z","level":"INFO","message":"Worker started","worker_id":"worker-1","concurrency":2,"target":"granary::daemon"}"#;
+        let formatted = format_log_line(line);
+        assert!(
+            formatted.ends_with(
+                " INFO granary::daemon: Worker started concurrency=2 worker_id=worker-1"
+            )
+        );
+        assert!(formatted.starts_with("2026-01-"));
+
+        assert_eq!(format_log_line("plain text line"), "plain text line");
+    }
+
     #[test]
     fn test_display_path() {
         use std::path::PathBuf;
@@ -0,0 +1,30 @@
+//! `granary events`: the event types workers can be triggered by.
+
+use crate::cli::args::EventsAction;
+use crate::error::Result;
+use crate::models::{EventType, EventTypeInfo};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::schedule::SCHEDULE_EVENT_TYPE;
+
+/// Handle events subcommands
+pub async fn events(action: EventsAction, format: OutputFormat) -> Result<()> {
+    match action {
+        EventsAction::Types => {
+            println!(
+                "{}",
+                Formatter::new(format).format_event_types(&event_types())
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Every event type a worker can use with `--on`
+pub fn event_types() -> Vec<EventTypeInfo> {
+    let mut types: Vec<EventTypeInfo> = EventType::ALL.iter().map(EventTypeInfo::from).collect();
+    types.push(EventTypeInfo {
+        name: SCHEDULE_EVENT_TYPE.to_string(),
+        description: "A scheduled worker's timer fired (not stored in the event log)".to_string(),
+    });
+    types
+}
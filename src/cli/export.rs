@@ -0,0 +1,118 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cli::args::ExportFormat;
+use crate::error::{GranaryError, Result};
+use crate::output::OutputFormat;
+use crate::services::export_service::{self, ArchiveFormat};
+use crate::services::{self, Workspace};
+
+/// Export the current workspace to stdout or a file
+pub async fn export(archive: Option<ExportFormat>, output: Option<PathBuf>) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let archive_format = match archive {
+        Some(ExportFormat::Json) => ArchiveFormat::Json,
+        Some(ExportFormat::Tar) => ArchiveFormat::Tar,
+        None => output
+            .as_deref()
+            .map(infer_format)
+            .unwrap_or(ArchiveFormat::Json),
+    };
+
+    let export = export_service::export_workspace(&pool).await?;
+
+    match output {
+        Some(path) => {
+            let file = std::fs::File::create(&path)?;
+            export_service::write_archive(&export, archive_format, io::BufWriter::new(file))?;
+            let total: usize = export.manifest.counts.values().sum();
+            eprintln!("Exported {} rows to {}", total, path.display());
+        }
+        None => {
+            if archive_format == ArchiveFormat::Tar && io::stdout().is_terminal() {
+                return Err(GranaryError::InvalidArgument(
+                    "Refusing to write a tar archive to a terminal. Use --output <file> or redirect stdout.".to_string(),
+                ));
+            }
+            let mut stdout = io::stdout().lock();
+            export_service::write_archive(&export, archive_format, &mut stdout)?;
+            if archive_format == ArchiveFormat::Json {
+                writeln!(stdout)?;
+            }
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the archive format from an output file name
+fn infer_format(path: &Path) -> ArchiveFormat {
+    let name = path.to_string_lossy().to_lowercase();
+    if [".tar", ".tar.gz", ".tgz"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+    {
+        ArchiveFormat::Tar
+    } else {
+        ArchiveFormat::Json
+    }
+}
+
+/// Import an archive into a new workspace
+pub async fn import(file: &Path, path: Option<PathBuf>, format: OutputFormat) -> Result<()> {
+    let data = if file == Path::new("-") {
+        let mut buffer = Vec::new();
+        io::stdin().read_to_end(&mut buffer)?;
+        buffer
+    } else {
+        std::fs::read(file)?
+    };
+
+    // Parse before touching the filesystem so a bad archive leaves nothing behind
+    let export = export_service::read_archive(&data)?;
+
+    let root = match path {
+        Some(path) => {
+            std::fs::create_dir_all(&path)?;
+            path
+        }
+        None => std::env::current_dir()?,
+    };
+
+    let workspace = Workspace::create(&root)?;
+    let pool = workspace.init_db().await?;
+
+    let summary = match export_service::import_workspace(&pool, &export).await {
+        Ok(summary) => summary,
+        Err(e) => {
+            pool.close().await;
+            let _ = std::fs::remove_dir_all(&workspace.granary_dir);
+            return Err(e);
+        }
+    };
+    services::try_register_workspace(&workspace).await;
+
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&summary)?);
+        }
+        OutputFormat::Yaml => {
+            print!("{}", serde_yaml::to_string(&summary)?);
+        }
+        _ => {
+            println!(
+                "Imported {} rows into {}",
+                summary.total,
+                workspace.granary_dir.display()
+            );
+            for (table, count) in summary.counts.iter().filter(|(_, count)| **count > 0) {
+                println!("  {}: {}", table, count);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Git CLI commands for linking tasks to branches and commits.
+
+use crate::cli::args::GitCommand;
+use crate::error::{GranaryError, Result};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, git_service};
+
+/// Handle git subcommands
+pub async fn git(command: GitCommand, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let repo = git_service::require_repo(&workspace.root)?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    match command {
+        GitCommand::Branch {
+            task_id,
+            name,
+            no_checkout,
+        } => {
+            let link =
+                git_service::create_branch(&pool, &repo, &task_id, name, !no_checkout).await?;
+            match format {
+                OutputFormat::Table => {
+                    let verb = if no_checkout {
+                        "Created"
+                    } else {
+                        "Switched to"
+                    };
+                    println!("{} branch {} for {}", verb, link.ref_name, link.task_id);
+                }
+                _ => println!("{}", formatter.format_vcs_links(&[link])),
+            }
+        }
+
+        GitCommand::Link { task_id, revs } => {
+            let links = git_service::link_commits(&pool, &repo, &task_id, &revs).await?;
+            match format {
+                OutputFormat::Table => {
+                    for link in &links {
+                        println!(
+                            "Linked {} {} to {}",
+                            link.short_ref(),
+                            link.summary.as_deref().unwrap_or(""),
+                            link.task_id
+                        );
+                    }
+                }
+                _ => println!("{}", formatter.format_vcs_links(&links)),
+            }
+        }
+
+        GitCommand::Links { task_id } => {
+            let task_id = match task_id {
+                Some(id) => id,
+                None => {
+                    let branch = git_service::current_branch(&repo).ok_or_else(|| {
+                        GranaryError::InvalidArgument(
+                            "Not on a branch. Specify a task ID.".to_string(),
+                        )
+                    })?;
+                    git_service::task_for_branch(&pool, &branch)
+                        .await?
+                        .ok_or_else(|| {
+                            GranaryError::InvalidArgument(format!(
+                                "Branch {} is not linked to a task. Specify a task ID.",
+                                branch
+                            ))
+                        })?
+                }
+            };
+            let links = git_service::list_links(&pool, &task_id).await?;
+            println!("{}", formatter.format_vcs_links(&links));
+        }
+
+        GitCommand::Unlink { task_id, ref_name } => {
+            git_service::unlink(&pool, &task_id, &ref_name).await?;
+            println!("Unlinked {} from {}", ref_name, task_id);
+        }
+    }
+
+    Ok(())
+}
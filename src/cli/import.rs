@@ -0,0 +1,101 @@
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::cli::args::ImportAction;
+use crate::error::{GranaryError, Result};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, csv_import, github_import, markdown_import};
+
+/// Handle `granary import` subcommands
+pub async fn import_action(action: ImportAction, format: OutputFormat) -> Result<()> {
+    match action {
+        ImportAction::Markdown { file, project } => import_markdown(&file, &project, format).await,
+        ImportAction::Github {
+            repo,
+            labels,
+            state,
+            project,
+            dry_run,
+        } => {
+            let workspace = Workspace::find()?;
+            let pool = workspace.pool().await?;
+            let options = github_import::GithubImport {
+                repo,
+                labels,
+                state,
+                project_id: project,
+                dry_run,
+            };
+            let report = github_import::import(&pool, &options).await?;
+            println!("{}", Formatter::new(format).format_import_report(&report));
+            Ok(())
+        }
+        ImportAction::Csv {
+            file,
+            mapping,
+            project,
+            dry_run,
+        } => import_csv(&file, &mapping, &project, dry_run, format).await,
+    }
+}
+
+/// Create tasks from the rows of a CSV export, mapped to task fields by a
+/// TOML mapping file
+async fn import_csv(
+    file: &Path,
+    mapping: &Path,
+    project_id: &str,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let mapping = csv_import::CsvMapping::parse(&read_input(mapping)?)?;
+    let tasks = csv_import::read_tasks(&read_input(file)?, &mapping)?;
+    let report = csv_import::import_tasks(
+        &pool,
+        project_id,
+        &file.display().to_string(),
+        &tasks,
+        dry_run,
+    )
+    .await?;
+    println!("{}", Formatter::new(format).format_import_report(&report));
+    Ok(())
+}
+
+/// Create tasks from the checklist items of a Markdown file
+async fn import_markdown(file: &Path, project_id: &str, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let input = read_input(file)?;
+    let items = markdown_import::parse_checklist(&input);
+    let tasks = markdown_import::import_checklist(&pool, project_id, &items).await?;
+
+    let formatter = Formatter::new(format);
+    println!("{}", formatter.format_tasks(&tasks));
+    if !format.is_structured() {
+        let done = tasks.iter().filter(|t| t.status == "done").count();
+        println!(
+            "Imported {} task(s) into {} ({} done)",
+            tasks.len(),
+            project_id,
+            done
+        );
+    }
+    Ok(())
+}
+
+/// Read a file, or stdin for `-`
+fn read_input(file: &Path) -> Result<String> {
+    if file == Path::new("-") {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        return Ok(buffer);
+    }
+    std::fs::read_to_string(file).map_err(|e| {
+        GranaryError::InvalidArgument(format!("Cannot read {}: {}", file.display(), e))
+    })
+}
@@ -1,17 +1,34 @@
+use crate::db::encryption;
 use crate::error::Result;
 use crate::services::{
     InjectionResult, Workspace, find_global_agent_dirs, find_workspace_agent_files,
     get_global_instruction_file_path, global_config_service, inject_granary_instruction,
-    inject_or_create_instruction,
+    inject_or_create_instruction, try_register_workspace,
 };
 
 /// Initialize a new workspace
-pub async fn init() -> Result<()> {
+pub async fn init(encrypted: bool) -> Result<()> {
     // Check if this is the first run BEFORE creating the ~/.granary directory
     let first_run = global_config_service::is_first_run()?;
 
     let workspace = Workspace::find_or_create(None)?;
-    let _pool = workspace.init_db().await?;
+    if encrypted && !encryption::is_encrypted(&workspace.db_path) {
+        let (key, in_keychain) = encryption::key_for_new(&workspace.db_path)?;
+        let _pool = workspace.init_encrypted_db(&key).await?;
+        if in_keychain {
+            println!(
+                "Encryption key saved to the OS keychain (service \"{}\")",
+                encryption::KEYCHAIN_SERVICE
+            );
+        } else {
+            println!(
+                "Database encrypted with the key from {}",
+                encryption::KEY_ENV
+            );
+        }
+    } else {
+        let _pool = workspace.init_db().await?;
+    }
 
     // Find all agent instruction files in workspace
     let agent_files = find_workspace_agent_files(&workspace.root)?;
@@ -47,6 +64,8 @@ pub async fn init() -> Result<()> {
         }
     }
 
+    try_register_workspace(&workspace).await;
+
     println!(
         "Initialized Granary workspace at {}",
         workspace.root.display()
@@ -57,9 +76,9 @@ pub async fn init() -> Result<()> {
 }
 
 /// Run diagnostic checks
-pub async fn doctor() -> Result<()> {
+pub async fn doctor(fix: bool) -> Result<()> {
     let workspace = Workspace::find()?;
-    let results = workspace.doctor().await?;
+    let results = workspace.doctor(fix).await?;
 
     println!("Granary Doctor");
     println!("==============");
@@ -1,12 +1,12 @@
 //! CLI handlers for initiative commands
 
-use crate::cli::args::{InitiativeAction, InitiativesAction};
+use crate::cli::args::{InitiativeAction, InitiativesAction, MilestoneAction};
 use crate::cli::watch::{watch_loop, watch_status_line};
 use crate::db;
 use crate::error::Result;
-use crate::models::initiative::{CreateInitiative, UpdateInitiative};
+use crate::models::initiative::{CreateInitiative, CreateMilestone, UpdateInitiative};
 use crate::output::{Formatter, OutputFormat};
-use crate::services::{self, Workspace};
+use crate::services::{self, Workspace, datetime};
 use std::time::Duration;
 
 /// Handle initiatives command (list or create)
@@ -43,9 +43,57 @@ pub async fn initiatives(
             owner,
             tags,
         }) => create_initiative(&name, description, owner, tags, format).await,
+        Some(InitiativesAction::Milestone { action }) => milestone(action, format).await,
     }
 }
 
+/// Handle initiative milestone commands
+async fn milestone(action: MilestoneAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    match action {
+        MilestoneAction::Add {
+            initiative_id,
+            name,
+            target,
+            description,
+            projects,
+            tasks,
+        } => {
+            let target_date = target
+                .map(|t| datetime::parse_due(&t, &datetime::settings()))
+                .transpose()?;
+            let milestone = services::add_milestone(
+                &pool,
+                CreateMilestone {
+                    initiative_id,
+                    name,
+                    description,
+                    target_date,
+                    project_ids: projects,
+                    task_ids: tasks,
+                },
+            )
+            .await?;
+            println!("{}", formatter.format_milestone(&milestone));
+        }
+
+        MilestoneAction::List { initiative_id } => {
+            let milestones = services::list_milestones(&pool, &initiative_id).await?;
+            println!("{}", formatter.format_milestones(&milestones));
+        }
+
+        MilestoneAction::Complete { id } => {
+            let milestone = services::complete_milestone(&pool, &id).await?;
+            println!("{}", formatter.format_milestone(&milestone));
+        }
+    }
+
+    Ok(())
+}
+
 /// Fetch and format all initiatives as a string
 async fn fetch_and_format_initiatives(
     include_archived: bool,
@@ -113,7 +161,11 @@ pub async fn initiative(
         None => {
             // Show initiative details
             let initiative = services::get_initiative_or_error(&pool, id).await?;
-            println!("{}", formatter.format_initiative(&initiative));
+            let milestones = services::list_milestones(&pool, id).await?;
+            println!(
+                "{}",
+                formatter.format_initiative_with_milestones(&initiative, &milestones)
+            );
         }
 
         Some(InitiativeAction::Update {
@@ -276,7 +328,7 @@ async fn print_initiative_dependency_graph(
 
 /// Sanitize a string to be a valid Mermaid node ID
 /// Mermaid IDs should be alphanumeric with underscores
-fn sanitize_mermaid_id(id: &str) -> String {
+pub(crate) fn sanitize_mermaid_id(id: &str) -> String {
     id.chars()
         .map(|c| {
             if c.is_alphanumeric() || c == '_' {
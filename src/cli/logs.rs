@@ -0,0 +1,25 @@
+//! `granary logs`: retention of run and worker logs.
+
+use crate::cli::args::LogsCommand;
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{global_config_service, log_retention};
+
+/// Handle logs subcommands
+pub async fn logs(command: LogsCommand, format: OutputFormat) -> Result<()> {
+    match command {
+        LogsCommand::Prune { dry_run } => prune(dry_run, format).await,
+    }
+}
+
+/// Delete logs by the `[logs]` policy in the global config
+async fn prune(dry_run: bool, format: OutputFormat) -> Result<()> {
+    let config = global_config_service::load()?;
+    let global_pool = global_config_service::global_pool().await?;
+    let report = log_retention::prune(&global_pool, &config.logs, dry_run).await?;
+    println!(
+        "{}",
+        Formatter::new(format).format_log_prune_report(&report)
+    );
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use tokio::io::BufReader;
+
+use crate::error::Result;
+use crate::services::mcp_server::McpServer;
+use crate::services::{self, Workspace};
+
+/// Run the MCP server over stdio until stdin is closed, attributing the
+/// changes it makes to `actor` (as with `--actor`)
+pub async fn mcp(actor: Option<&str>) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let actor = services::resolve_actor(actor).await;
+
+    let server = McpServer::new(pool, workspace).with_actor(actor);
+    server
+        .serve(BufReader::new(tokio::io::stdin()), tokio::io::stdout())
+        .await
+}
@@ -1,24 +1,43 @@
 pub mod args;
+pub mod audit;
+pub mod backup;
 pub mod batch;
+pub mod board;
 pub mod checkpoints;
 pub mod comments;
+pub mod complete;
 pub mod config;
 pub mod daemon;
 pub mod entrypoint;
+pub mod events;
+pub mod export;
+pub mod git;
+pub mod import;
 pub mod init;
 pub mod initiatives;
+pub mod logs;
+pub mod mcp;
 pub mod plan;
+pub mod plugins;
 pub mod projects;
+pub mod remote;
+pub mod report;
+pub mod rules;
 pub mod run;
 pub mod search;
+pub mod serve;
 pub mod sessions;
 pub mod show;
 pub mod summary;
+pub mod sync;
 pub mod tasks;
+pub mod tui;
+pub mod undo;
 pub mod update;
 pub mod watch;
 pub mod work;
 pub mod worker;
 pub mod workers;
+pub mod workspaces;
 
 pub use args::*;
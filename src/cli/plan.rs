@@ -36,13 +36,15 @@ pub async fn plan(name: &str, existing_project: Option<String>) -> Result<()> {
 
 /// Find prior art - projects with similar names or keywords
 async fn find_prior_art(pool: &sqlx::SqlitePool, query: &str) -> Result<Vec<ProjectWithProgress>> {
-    // Search for similar projects
-    let search_results = db::search::search_projects(pool, query).await?;
+    // Search for similar projects, archived ones included since they are
+    // often the most useful prior art
+    let search_results =
+        db::search::search_projects(pool, query, true, Page::new(Some(5), 0)).await?;
 
     let mut prior_art = Vec::new();
-    for project in search_results.into_iter().take(5) {
+    for project in search_results {
         // Get task counts for each project
-        let tasks = services::list_tasks_by_project(pool, &project.id).await?;
+        let tasks = services::list_tasks_by_project(pool, &project.id, true).await?;
         let done_count = tasks.iter().filter(|t| t.status == "done").count();
         let total_count = tasks.len();
 
@@ -0,0 +1,34 @@
+//! `granary plugins` and running `granary-<name>` plugins.
+
+use crate::cli::args::PluginsAction;
+use crate::error::{GranaryError, Result};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::plugin_service::{self, PluginInvocation};
+
+/// Handle plugins subcommands
+pub async fn plugins(action: Option<PluginsAction>, format: OutputFormat) -> Result<()> {
+    match action.unwrap_or(PluginsAction::List) {
+        PluginsAction::List => {
+            let plugins = plugin_service::discover_plugins();
+            println!("{}", Formatter::new(format).format_plugins(&plugins));
+        }
+    }
+    Ok(())
+}
+
+/// Run `granary <name> args...` as the `granary-<name>` plugin and return
+/// its exit code
+pub fn run_external(args: &[String], invocation: &PluginInvocation) -> Result<i32> {
+    let (name, args) = args
+        .split_first()
+        .ok_or_else(|| GranaryError::InvalidArgument("No command given".to_string()))?;
+    let plugin = plugin_service::find_plugin(name).ok_or_else(|| {
+        GranaryError::InvalidArgument(format!(
+            "Unknown command '{}'. See granary --help, or install a {}{} plugin on PATH",
+            name,
+            plugin_service::PLUGIN_PREFIX,
+            name
+        ))
+    })?;
+    plugin_service::run_plugin(&plugin, args, invocation)
+}
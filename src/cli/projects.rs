@@ -1,18 +1,20 @@
 use crate::cli::args::{
     ProjectAction, ProjectDepsAction, ProjectSteerAction, ProjectTasksAction, ProjectsAction,
 };
+use crate::cli::initiatives::sanitize_mermaid_id;
 use crate::cli::watch::{watch_loop, watch_status_line};
 use crate::db;
 use crate::error::Result;
 use crate::models::*;
 use crate::output::{Formatter, OutputFormat};
-use crate::services::{self, Workspace};
+use crate::services::{self, Workspace, datetime, workspace_config};
 use std::time::Duration;
 
 /// Handle projects command (list or create)
 pub async fn projects(
     action: Option<ProjectsAction>,
     include_archived: bool,
+    sort: Sort,
     format: OutputFormat,
     watch: bool,
     interval: u64,
@@ -23,7 +25,7 @@ pub async fn projects(
             if watch {
                 let interval_duration = Duration::from_secs(interval);
                 watch_loop(interval_duration, || async {
-                    let output = fetch_and_format_projects(include_archived, format)
+                    let output = fetch_and_format_projects(include_archived, &sort, format)
                         .await
                         .map_err(|e| anyhow::anyhow!("{}", e))?;
                     Ok(format!(
@@ -35,7 +37,7 @@ pub async fn projects(
                 .await?;
                 Ok(())
             } else {
-                let output = fetch_and_format_projects(include_archived, format).await?;
+                let output = fetch_and_format_projects(include_archived, &sort, format).await?;
                 println!("{}", output);
                 Ok(())
             }
@@ -46,17 +48,104 @@ pub async fn projects(
             owner,
             tags,
         }) => create_project(&name, description, owner, tags, format).await,
+        Some(ProjectsAction::Archive { id, cascade }) => {
+            let workspace = Workspace::find()?;
+            let pool = workspace.pool().await?;
+            let project = services::archive_project(&pool, &id, cascade).await?;
+            println!("Archived project: {}", project.id);
+            Ok(())
+        }
+        Some(ProjectsAction::Timeline { id, mermaid }) => {
+            let workspace = Workspace::find()?;
+            let pool = workspace.pool().await?;
+            let timeline = services::report_service::timeline(&pool, &id).await?;
+            if mermaid {
+                print_timeline_mermaid(&timeline);
+            } else {
+                println!("{}", Formatter::new(format).format_timeline(&timeline));
+            }
+            Ok(())
+        }
+        Some(ProjectsAction::Health { id }) => {
+            let workspace = Workspace::find()?;
+            let pool = workspace.pool().await?;
+            let projects = match id {
+                Some(id) => vec![services::get_project(&pool, &id).await?],
+                None => services::list_projects(&pool, false).await?,
+            };
+            let health =
+                services::report_service::project_health(&pool, &workspace, &projects).await?;
+            println!("{}", Formatter::new(format).format_project_health(&health));
+            Ok(())
+        }
+    }
+}
+
+/// Print a timeline as a Mermaid gantt chart. Bars run to the end of their
+/// last day; tasks with only a due date are milestones.
+fn print_timeline_mermaid(timeline: &Timeline) {
+    println!("gantt");
+    println!(
+        "    title {} ({})",
+        gantt_text(&timeline.project_name),
+        timeline.project_id
+    );
+    println!("    dateFormat YYYY-MM-DD");
+    println!("    section {}", gantt_text(&timeline.project_name));
+    for task in &timeline.tasks {
+        let id = sanitize_mermaid_id(&task.id);
+        let start = datetime::format_local_date(&task.start);
+        if task.milestone {
+            println!(
+                "    {} :milestone, {}, {}, 0d",
+                gantt_text(&task.title),
+                id,
+                start
+            );
+            continue;
+        }
+        let end =
+            chrono::NaiveDate::parse_from_str(&datetime::format_local_date(&task.end), "%Y-%m-%d")
+                .map(|d| (d + chrono::Days::new(1)).format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|_| start.clone());
+        let tag = match task.status.as_str() {
+            "done" => "done, ",
+            "in_progress" => "active, ",
+            "blocked" => "crit, ",
+            _ => "",
+        };
+        println!(
+            "    {} :{}{}, {}, {}",
+            gantt_text(&task.title),
+            tag,
+            id,
+            start,
+            end
+        );
     }
 }
 
+/// Gantt task names end at `:` and comments start at `%%`
+fn gantt_text(text: &str) -> String {
+    text.replace([':', ';', '#'], " ").replace("%%", "%")
+}
+
 /// Fetch and format all projects as a string
-async fn fetch_and_format_projects(include_archived: bool, format: OutputFormat) -> Result<String> {
+async fn fetch_and_format_projects(
+    include_archived: bool,
+    sort: &Sort,
+    format: OutputFormat,
+) -> Result<String> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let projects = services::list_projects(&pool, include_archived).await?;
+    let projects = services::list_projects_sorted(&pool, include_archived, sort).await?;
     let formatter = Formatter::new(format);
-    Ok(formatter.format_projects(&projects))
+    if format != OutputFormat::Table {
+        return Ok(formatter.format_projects(&projects));
+    }
+    let health = services::report_service::project_health(&pool, &workspace, &projects).await?;
+    Ok(formatter.format_projects_with_health(&projects, &health))
 }
 
 /// Show or manage a project
@@ -85,6 +174,7 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
             description,
             owner,
             tags,
+            fields,
         }) => {
             let parsed_tags = tags.map(|t| parse_tags(&t));
 
@@ -96,6 +186,7 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
                     description,
                     owner,
                     tags: parsed_tags,
+                    fields: services::parse_field_args(&fields)?,
                     ..Default::default()
                 },
             )
@@ -104,16 +195,20 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
             println!("{}", formatter.format_project(&project));
         }
 
-        Some(ProjectAction::Archive) => {
-            let project = services::archive_project(&pool, id).await?;
+        Some(ProjectAction::Archive { cascade }) => {
+            let project = services::archive_project(&pool, id, cascade).await?;
             println!("Archived project: {}", project.id);
         }
 
-        Some(ProjectAction::Tasks { action }) => {
+        Some(ProjectAction::Tasks {
+            action,
+            include_archived,
+        }) => {
             match action {
                 None => {
                     // List tasks with dependency info
-                    let tasks = services::list_tasks_by_project(&pool, id).await?;
+                    let tasks =
+                        services::list_tasks_by_project(&pool, id, include_archived).await?;
                     let tasks_with_deps = services::get_tasks_with_deps(&pool, tasks).await?;
                     println!("{}", formatter.format_tasks_with_deps(&tasks_with_deps));
                 }
@@ -125,8 +220,9 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
                     dependencies,
                     tags,
                     due,
+                    estimate,
                 }) => {
-                    let priority = priority.parse().unwrap_or_default();
+                    let priority = workspace_config::task_priority(priority)?;
                     let tags = tags
                         .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
                         .unwrap_or_default();
@@ -140,7 +236,10 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
                             priority,
                             owner,
                             tags,
-                            due_at: due,
+                            due_at: due
+                                .map(|d| datetime::parse_due(&d, &datetime::settings()))
+                                .transpose()?,
+                            estimate,
                             ..Default::default()
                         },
                     )
@@ -164,7 +263,7 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
 
         Some(ProjectAction::Summary) => {
             let project = services::get_project(&pool, id).await?;
-            let tasks = services::list_tasks_by_project(&pool, id).await?;
+            let tasks = services::list_tasks_by_project(&pool, id, false).await?;
 
             let done_count = tasks.iter().filter(|t| t.status == "done").count();
             let total = tasks.len();
@@ -186,7 +285,7 @@ pub async fn project(id: &str, action: Option<ProjectAction>, format: OutputForm
 
         Some(ProjectAction::Ready) => {
             // Validate project has tasks
-            let tasks = services::list_tasks_by_project(&pool, id).await?;
+            let tasks = services::list_tasks_by_project(&pool, id, false).await?;
 
             if tasks.is_empty() {
                 println!("Project has no tasks. Create tasks before marking ready.");
@@ -0,0 +1,59 @@
+//! `granary remote`, `granary push` and `granary pull`: workspace sync
+//! between machines.
+
+use crate::cli::args::RemoteAction;
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, remote_service};
+
+/// Handle remote subcommands
+pub async fn remote(action: RemoteAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    match action {
+        RemoteAction::Add { url, name } => {
+            let remote = remote_service::add_remote(&pool, &url, name.as_deref()).await?;
+            println!(
+                "{}",
+                formatter.format_remotes(std::slice::from_ref(&remote))
+            );
+        }
+        RemoteAction::List => {
+            let remotes = remote_service::list_remotes(&pool).await?;
+            println!("{}", formatter.format_remotes(&remotes));
+        }
+        RemoteAction::Remove { name } => {
+            remote_service::remove_remote(&pool, &name).await?;
+            println!("Removed remote {}", name);
+        }
+    }
+    Ok(())
+}
+
+/// Upload local changes to a remote
+pub async fn push(remote: Option<&str>, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let remote = remote_service::resolve_remote(&pool, remote).await?;
+    let report = remote_service::push(&pool, &remote).await?;
+    println!(
+        "{}",
+        Formatter::new(format).format_remote_sync_report(&report)
+    );
+    Ok(())
+}
+
+/// Apply changes from a remote
+pub async fn pull(remote: Option<&str>, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let remote = remote_service::resolve_remote(&pool, remote).await?;
+    let report = remote_service::pull(&pool, &remote).await?;
+    println!(
+        "{}",
+        Formatter::new(format).format_remote_sync_report(&report)
+    );
+    Ok(())
+}
@@ -0,0 +1,33 @@
+//! `granary report`: reports on estimated and completed work, and on what
+//! agent runs cost.
+
+use crate::cli::args::ReportAction;
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, datetime, report_service};
+
+/// Handle report subcommands
+pub async fn report(action: ReportAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    match action {
+        ReportAction::Burndown { project } => {
+            let burndown = report_service::burndown(&pool, &project).await?;
+            println!("{}", Formatter::new(format).format_burndown(&burndown));
+        }
+        ReportAction::Velocity { window, project } => {
+            let weeks = report_service::parse_velocity_window(&window)?;
+            let velocity =
+                report_service::velocity(&pool, project.as_deref(), weeks, &datetime::settings())
+                    .await?;
+            println!("{}", Formatter::new(format).format_velocity(&velocity));
+        }
+        ReportAction::Costs { since } => {
+            let since = datetime::parse_since(&since, &datetime::settings())?;
+            let report = report_service::costs(&pool, &workspace, &since).await?;
+            println!("{}", Formatter::new(format).format_cost_report(&report));
+        }
+    }
+    Ok(())
+}
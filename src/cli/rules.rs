@@ -0,0 +1,25 @@
+//! `granary rules`: rules the daemon evaluates against open tasks.
+
+use crate::cli::args::RulesAction;
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, rule_service};
+
+/// Handle rules subcommands
+pub async fn rules(action: RulesAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    match action {
+        RulesAction::List => {
+            let rules = rule_service::list_rules(&pool).await?;
+            println!("{}", formatter.format_rules(&rules));
+        }
+        RulesAction::Test { name } => {
+            let matches = rule_service::test_rules(&pool, name.as_deref()).await?;
+            println!("{}", formatter.format_rule_matches(&matches));
+        }
+    }
+    Ok(())
+}
@@ -1,25 +1,39 @@
 //! Run CLI commands for managing individual runner executions.
 //!
 //! Runs are individual executions spawned by workers. Users can monitor run status,
-//! view logs, and control run lifecycle (stop, pause, resume).
+//! view logs, and control run lifecycle (stop, pause, resume, retry).
 
+use std::path::Path;
 use std::time::Duration;
 
-use crate::cli::args::RunCommand;
+use crate::cli::args::{RunCommand, RunsAction};
 use crate::cli::watch::{watch_loop, watch_status_line};
+use crate::daemon::transport::is_process_alive;
 use crate::daemon::{LogTarget, ensure_daemon};
 use crate::db;
 use crate::error::{GranaryError, Result};
-use crate::models::run::{RunStatus, UpdateRunStatus};
+use crate::models::run::{QueuedRun, Run, RunStatus, UpdateRunStatus};
+use crate::models::{LogFilter, LogStream, Page, RunLogRecord, Sort};
 use crate::output::{Formatter, OutputFormat};
-use crate::services::global_config_service;
+use crate::services::run_log_search::{self, LogPattern};
+use crate::services::{datetime, global_config_service, log_sanitize, run_artifacts};
+
+/// Filters for `granary runs`
+#[derive(Debug, Clone, Default)]
+pub struct RunListFilter {
+    pub worker_id: Option<String>,
+    pub status: Option<String>,
+    /// Include finished runs
+    pub all: bool,
+    /// Only runs waiting for a concurrency slot
+    pub queued: bool,
+    pub page: Page,
+    pub sort: Sort,
+}
 
 /// List all runs with optional filters
 pub async fn list_runs(
-    worker_id: Option<String>,
-    status: Option<String>,
-    all: bool,
-    limit: u32,
+    filter: RunListFilter,
     format: OutputFormat,
     watch: bool,
     interval: u64,
@@ -27,18 +41,11 @@ pub async fn list_runs(
     if watch {
         let interval_duration = Duration::from_secs(interval);
         watch_loop(interval_duration, || {
-            let worker_id = worker_id.clone();
-            let status = status.clone();
+            let filter = filter.clone();
             async move {
-                let output = fetch_and_format_runs(
-                    worker_id.as_deref(),
-                    status.as_deref(),
-                    all,
-                    limit,
-                    format,
-                )
-                .await
-                .map_err(|e| anyhow::anyhow!("{}", e))?;
+                let output = fetch_and_format_runs(&filter, format)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("{}", e))?;
                 Ok(format!(
                     "{}\n{}",
                     watch_status_line(interval_duration),
@@ -48,66 +55,54 @@ pub async fn list_runs(
         })
         .await?;
     } else {
-        let output =
-            fetch_and_format_runs(worker_id.as_deref(), status.as_deref(), all, limit, format)
-                .await?;
+        let output = fetch_and_format_runs(&filter, format).await?;
         print!("{}", output);
     }
     Ok(())
 }
 
 /// Fetch and format runs for display
-async fn fetch_and_format_runs(
-    worker_id: Option<&str>,
-    status: Option<&str>,
-    all: bool,
-    limit: u32,
-    format: OutputFormat,
-) -> Result<String> {
+async fn fetch_and_format_runs(filter: &RunListFilter, format: OutputFormat) -> Result<String> {
+    let RunListFilter {
+        worker_id,
+        status,
+        all,
+        queued,
+        page,
+        sort,
+    } = filter;
+    let all = *all;
     let global_pool = global_config_service::global_pool().await?;
 
     // Parse status filter if provided
-    let status_filter: Option<RunStatus> = status.and_then(|s| s.parse().ok());
+    let status_filter: Option<RunStatus> = status.as_deref().and_then(|s| s.parse().ok());
 
-    // Get runs based on filters
-    let runs = if let Some(worker) = worker_id {
-        db::runs::list_by_worker(&global_pool, worker).await?
-    } else {
-        db::runs::list_all(&global_pool).await?
-    };
-
-    // Filter runs
-    let mut runs: Vec<_> = runs
+    // By default, only show pending/running/paused unless --all
+    let statuses: Vec<RunStatus> = RunStatus::ALL
         .into_iter()
-        .filter(|r| {
-            // Filter by status if specified
-            if let Some(ref status) = status_filter
-                && r.status_enum() != *status
-            {
-                return false;
-            }
-
-            // By default, only show pending/running/paused unless --all
-            if !all {
-                let run_status = r.status_enum();
-                if matches!(
-                    run_status,
-                    RunStatus::Completed | RunStatus::Failed | RunStatus::Cancelled
-                ) {
-                    return false;
-                }
-            }
-
-            true
-        })
-        .take(limit as usize)
+        .filter(|s| all || !s.is_finished())
+        .filter(|s| status_filter.as_ref().is_none_or(|wanted| s == wanted))
         .collect();
 
-    // Sort by created_at descending (most recent first)
-    runs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    // Filtering and paging happen in SQL so only the rows shown are loaded
+    let runs = if statuses.is_empty() {
+        Vec::new()
+    } else {
+        db::runs::list_filtered(
+            &global_pool,
+            worker_id.as_deref(),
+            &statuses,
+            *queued,
+            sort,
+            *page,
+        )
+        .await?
+    };
 
     if runs.is_empty() {
-        if all {
+        if *queued {
+            return Ok("No queued runs.\n".to_string());
+        } else if all {
             return Ok("No runs found.\n".to_string());
         } else {
             return Ok(
@@ -121,6 +116,153 @@ async fn fetch_and_format_runs(
     Ok(format!("{}\n", formatter.format_runs(&runs)))
 }
 
+/// Handle `granary runs` and its subcommands
+pub async fn runs_action(
+    action: Option<RunsAction>,
+    filter: RunListFilter,
+    format: OutputFormat,
+    watch: bool,
+    interval: u64,
+) -> Result<()> {
+    match action {
+        None | Some(RunsAction::List) => list_runs(filter, format, watch, interval).await,
+        Some(RunsAction::Retry { run_id }) => retry_run(&run_id, format).await,
+        Some(RunsAction::Queue) => show_queue(filter.worker_id.as_deref(), format).await,
+        Some(RunsAction::Bump { run_id }) => bump_run(&run_id, format).await,
+        Some(RunsAction::Drop { run_id }) => drop_run(&run_id, format).await,
+        Some(RunsAction::Logs {
+            run_id,
+            follow,
+            lines,
+            stream,
+            since,
+            until,
+            strip_ansi,
+        }) => {
+            let filter = log_filter(stream, since.as_deref(), until.as_deref())?;
+            show_logs(&run_id, follow, lines, &filter, strip_ansi, format).await
+        }
+        Some(RunsAction::Grep {
+            pattern,
+            regex,
+            ignore_case,
+            since,
+        }) => {
+            grep_logs(
+                &pattern,
+                regex,
+                ignore_case,
+                filter.worker_id.as_deref(),
+                since.as_deref(),
+                format,
+            )
+            .await
+        }
+        Some(RunsAction::Artifacts {
+            run_id,
+            get,
+            output,
+        }) => run_artifacts(&run_id, get.as_deref(), output.as_deref(), format).await,
+    }
+}
+
+/// Show queued runs in dispatch order, numbered within each worker's queue
+async fn show_queue(worker_id: Option<&str>, format: OutputFormat) -> Result<()> {
+    let global_pool = global_config_service::global_pool().await?;
+    let runs = db::runs::list_queued(&global_pool, worker_id).await?;
+
+    let mut queue: Vec<QueuedRun> = Vec::with_capacity(runs.len());
+    for run in runs {
+        let position = match queue.last() {
+            Some(prev) if prev.run.worker_id == run.worker_id => prev.position + 1,
+            _ => 1,
+        };
+        queue.push(QueuedRun { position, run });
+    }
+
+    let formatter = Formatter::new(format);
+    println!("{}", formatter.format_run_queue(&queue));
+    Ok(())
+}
+
+/// Load a run and check that it is still waiting in a queue
+async fn get_queued_run(pool: &sqlx::SqlitePool, run_id: &str) -> Result<Run> {
+    let run = db::runs::get(pool, run_id)
+        .await?
+        .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))?;
+    if !run.is_queued() {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Run {} is not queued: status is '{}'",
+            run_id, run.status
+        )));
+    }
+    Ok(run)
+}
+
+/// Move a queued run to the front of its worker's queue
+async fn bump_run(run_id: &str, format: OutputFormat) -> Result<()> {
+    let global_pool = global_config_service::global_pool().await?;
+    get_queued_run(&global_pool, run_id).await?;
+    if !db::runs::bump(&global_pool, run_id).await? {
+        return Err(GranaryError::Conflict(format!(
+            "Run {} started before it could be bumped",
+            run_id
+        )));
+    }
+
+    let run = db::runs::get(&global_pool, run_id)
+        .await?
+        .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))?;
+    let formatter = Formatter::new(format);
+    println!("Run moved to the front of the queue.");
+    println!("{}", formatter.format_run(&run));
+
+    Ok(())
+}
+
+/// Remove a queued run before it starts
+async fn drop_run(run_id: &str, format: OutputFormat) -> Result<()> {
+    let global_pool = global_config_service::global_pool().await?;
+    get_queued_run(&global_pool, run_id).await?;
+    if !db::runs::drop_queued(&global_pool, run_id).await? {
+        return Err(GranaryError::Conflict(format!(
+            "Run {} started before it could be dropped",
+            run_id
+        )));
+    }
+
+    let run = db::runs::get(&global_pool, run_id)
+        .await?
+        .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))?;
+    let formatter = Formatter::new(format);
+    println!("Run dropped from the queue.");
+    println!("{}", formatter.format_run(&run));
+
+    Ok(())
+}
+
+/// Search stored run logs
+async fn grep_logs(
+    pattern: &str,
+    regex: bool,
+    ignore_case: bool,
+    worker_id: Option<&str>,
+    since: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let pattern = LogPattern::new(pattern, regex, ignore_case)?;
+    let since = since
+        .map(|s| datetime::parse_since(s, &datetime::settings()))
+        .transpose()?;
+    let global_pool = global_config_service::global_pool().await?;
+    let matches =
+        run_log_search::search(&global_pool, worker_id, since.as_deref(), &pattern).await?;
+
+    let formatter = Formatter::new(format);
+    println!("{}", formatter.format_run_log_matches(&matches));
+    Ok(())
+}
+
 /// Handle run subcommands
 pub async fn run(command: RunCommand, format: OutputFormat) -> Result<()> {
     match command {
@@ -129,7 +271,14 @@ pub async fn run(command: RunCommand, format: OutputFormat) -> Result<()> {
             run_id,
             follow,
             lines,
-        } => show_logs(&run_id, follow, lines).await,
+            stream,
+            since,
+            until,
+            strip_ansi,
+        } => {
+            let filter = log_filter(stream, since.as_deref(), until.as_deref())?;
+            show_logs(&run_id, follow, lines, &filter, strip_ansi, format).await
+        }
         RunCommand::Stop { run_id } => stop_run(&run_id, format).await,
         RunCommand::Pause { run_id } => pause_run(&run_id, format).await,
         RunCommand::Resume { run_id } => resume_run(&run_id, format).await,
@@ -164,8 +313,54 @@ async fn show_status(run_id: &str, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+/// Build the filter for `--stream`, `--since` and `--until`
+fn log_filter(
+    stream: Option<LogStream>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<LogFilter> {
+    let settings = datetime::settings();
+    Ok(LogFilter {
+        stream,
+        since: since
+            .map(|s| datetime::parse_since(s, &settings))
+            .transpose()?,
+        until: until
+            .map(|s| datetime::parse_since(s, &settings))
+            .transpose()?,
+    })
+}
+
+/// Render a log line for display, collapsing `\r`-rewritten text and
+/// optionally stripping ANSI escapes. Records of JSON-lines logs are shown
+/// with their local time and stream, unless structured output was asked for.
+fn render_log_line(line: &str, strip_ansi: bool, format: OutputFormat) -> String {
+    let Ok(mut record) = serde_json::from_str::<RunLogRecord>(line) else {
+        return log_sanitize::sanitize(line, strip_ansi);
+    };
+    record.line = log_sanitize::sanitize(&record.line, strip_ansi);
+    if matches!(format, OutputFormat::Json | OutputFormat::Yaml) {
+        return serde_json::to_string(&record).unwrap_or_else(|_| line.to_string());
+    }
+    let time = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .map(|t| {
+            t.with_timezone(&datetime::settings().timezone)
+                .format("%H:%M:%S%.3f")
+                .to_string()
+        })
+        .unwrap_or(record.timestamp);
+    format!("{} {:<6} {}", time, record.stream.as_str(), record.line)
+}
+
 /// Show run logs
-async fn show_logs(run_id: &str, follow: bool, lines: usize) -> Result<()> {
+async fn show_logs(
+    run_id: &str,
+    follow: bool,
+    lines: usize,
+    filter: &LogFilter,
+    strip_ansi: bool,
+    format: OutputFormat,
+) -> Result<()> {
     // Connect to daemon (auto-starts if needed)
     let mut client = ensure_daemon().await?;
 
@@ -176,74 +371,40 @@ async fn show_logs(run_id: &str, follow: bool, lines: usize) -> Result<()> {
         // Use daemon-based log streaming for follow mode
         println!("--- Following run logs via daemon (Ctrl+C to stop) ---");
 
-        // Use Ctrl+C to break out of the follow loop
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
-        let _ctrlc_task = tokio::spawn(async move {
-            let _ = tokio::signal::ctrl_c().await;
-            let _ = tx.send(()).await;
-        });
-
-        // Stream logs via daemon with polling
-
-        // Get initial lines from the end
-        let initial_response = client.get_logs(run_id, LogTarget::Run, 0, u64::MAX).await?;
-        let total_lines = initial_response.next_line;
-        let mut since_line = total_lines.saturating_sub(lines as u64);
-
-        // Print initial lines
-        if since_line < total_lines {
-            let response = client
-                .get_logs(run_id, LogTarget::Run, since_line, 1000)
-                .await?;
-            for line in &response.lines {
-                println!("{}", line);
+        // The daemon pushes new lines until the run stops; Ctrl+C detaches
+        let follow = client.follow_logs(run_id, LogTarget::Run, lines as u64, filter, |batch| {
+            for line in batch {
+                println!("{}", render_log_line(line, strip_ansi, format));
             }
-            since_line = response.next_line;
-        }
+            true
+        });
 
-        // Poll for new lines
-        loop {
-            // Check for Ctrl+C
-            match rx.try_recv() {
-                Ok(()) | Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                    println!();
-                    println!("Stopped following logs.");
-                    break;
+        tokio::select! {
+            result = follow => {
+                result?;
+                let run = client.get_run(run_id).await?;
+                match run.exit_code {
+                    Some(code) => println!("--- Run {} (exit code {}) ---", run.status, code),
+                    None => println!("--- Run {} ---", run.status),
                 }
-                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {}
             }
-
-            let response = client
-                .get_logs(run_id, LogTarget::Run, since_line, 100)
-                .await?;
-
-            for line in &response.lines {
-                println!("{}", line);
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                println!("Stopped following logs.");
             }
-
-            since_line = response.next_line;
-
-            // If run is no longer active and no more lines, we're done
-            if !response.has_more && response.lines.is_empty() {
-                println!("--- Run is no longer active ---");
-                break;
-            }
-
-            tokio::time::sleep(Duration::from_millis(100)).await;
         }
     } else {
-        // Non-follow mode: get logs via daemon
-        let response = client.get_logs(run_id, LogTarget::Run, 0, u64::MAX).await?;
-
-        let total_lines = response.next_line;
-        let start_line = total_lines.saturating_sub(lines as u64);
-
-        // Get the last N lines
-        let response = client
-            .get_logs(run_id, LogTarget::Run, start_line, lines as u64)
+        // Non-follow mode: get logs via daemon and keep the last N lines.
+        // A filter may drop any line, so the tail is taken after filtering.
+        let mut response = client
+            .get_logs(run_id, LogTarget::Run, 0, u64::MAX, filter)
             .await?;
+        let skip = response.lines.len().saturating_sub(lines);
+        response.lines.drain(..skip);
 
-        if response.lines.is_empty() {
+        if response.lines.is_empty() && !filter.is_empty() {
+            println!("No log lines of run {} match the filter", run_id);
+        } else if response.lines.is_empty() {
             println!("No log lines found for run {}", run_id);
             if let Some(path) = response.log_path {
                 println!("Log path: {}", path.display());
@@ -253,7 +414,7 @@ async fn show_logs(run_id: &str, follow: bool, lines: usize) -> Result<()> {
             println!("or it may have been cleaned up.");
         } else {
             for line in &response.lines {
-                println!("{}", line);
+                println!("{}", render_log_line(line, strip_ansi, format));
             }
         }
     }
@@ -272,10 +433,7 @@ async fn stop_run(run_id: &str, format: OutputFormat) -> Result<()> {
     let run_status = run.status_enum();
 
     // Check if run can be stopped
-    if run_status == RunStatus::Completed
-        || run_status == RunStatus::Failed
-        || run_status == RunStatus::Cancelled
-    {
+    if run.is_finished() {
         println!(
             "Run {} is already finished (status: {})",
             run_id, run.status
@@ -418,6 +576,53 @@ async fn resume_run(run_id: &str, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+/// Requeue a failed, cancelled, killed or timed-out run via the daemon.
+///
+/// The run's worker must be running; it picks the run up on its next poll.
+async fn retry_run(run_id: &str, format: OutputFormat) -> Result<()> {
+    let mut client = ensure_daemon().await?;
+    let run = client.retry_run(run_id).await?;
+
+    let formatter = Formatter::new(format);
+    println!("Run queued for retry (attempt {}).", run.attempt);
+    println!("{}", formatter.format_run(&run));
+
+    Ok(())
+}
+
+/// List a run's artifacts, or copy one to stdout or a file
+async fn run_artifacts(
+    run_id: &str,
+    get: Option<&str>,
+    output: Option<&Path>,
+    format: OutputFormat,
+) -> Result<()> {
+    let global_pool = global_config_service::global_pool().await?;
+    db::runs::get(&global_pool, run_id)
+        .await?
+        .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))?;
+
+    let artifacts = run_artifacts::list(&global_pool, run_id).await?;
+    let Some(name) = get else {
+        println!("{}", Formatter::new(format).format_artifacts(&artifacts));
+        return Ok(());
+    };
+
+    let artifact = run_artifacts::find(&artifacts, name)?;
+    let path = Path::new(&artifact.path_or_url);
+    match output {
+        Some(output) => {
+            std::fs::copy(path, output)?;
+            println!("Wrote {} to {}", name, output.display());
+        }
+        None => {
+            let mut file = std::fs::File::open(path)?;
+            std::io::copy(&mut file, &mut std::io::stdout().lock())?;
+        }
+    }
+    Ok(())
+}
+
 /// Signal types for process control
 enum Signal {
     Term,
@@ -425,26 +630,6 @@ enum Signal {
     Cont,
 }
 
-/// Check if a process is alive by using kill -0
-#[cfg_attr(not(unix), allow(unused_variables))]
-fn is_process_alive(pid: u32) -> bool {
-    #[cfg(unix)]
-    {
-        // Use kill -0 to check if process exists
-        std::process::Command::new("kill")
-            .args(["-0", &pid.to_string()])
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false)
-    }
-
-    #[cfg(not(unix))]
-    {
-        // On non-unix platforms, assume it's alive if we can't check
-        true
-    }
-}
-
 /// Send a signal to a process
 #[cfg_attr(not(unix), allow(unused_variables))]
 fn send_signal(pid: u32, signal: Signal) {
@@ -1,18 +1,54 @@
 use std::time::Duration;
 
+use crate::cli::args::SearchAction;
 use crate::cli::watch::{watch_loop, watch_status_line};
 use crate::error::Result;
+use crate::models::Page;
 use crate::output::{Formatter, OutputFormat};
-use crate::services::{self, Workspace};
+use crate::services::{self, Workspace, global_config_service};
+
+/// Options for `granary search`
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub query: Option<String>,
+    /// Name of a saved search to run instead of `query`
+    pub saved: Option<String>,
+    pub page: Page,
+    pub include_archived: bool,
+    /// Search every registered workspace instead of the current one
+    pub all_workspaces: bool,
+}
 
 /// Handle search command
-pub async fn search(query: &str, format: OutputFormat, watch: bool, interval: u64) -> Result<()> {
+pub async fn search(
+    options: SearchOptions,
+    format: OutputFormat,
+    watch: bool,
+    interval: u64,
+) -> Result<()> {
+    let SearchOptions {
+        query,
+        saved,
+        page,
+        include_archived,
+        all_workspaces,
+    } = options;
+    let query = match saved {
+        Some(name) => {
+            let workspace = Workspace::find()?;
+            let pool = workspace.pool().await?;
+            services::get_saved_search(&pool, &name).await?
+        }
+        None => query.unwrap_or_default(),
+    };
+
     if watch {
         let interval_duration = Duration::from_secs(interval);
-        let query = query.to_string();
 
         watch_loop(interval_duration, || async {
-            let output = fetch_and_format_search(&query, format).await?;
+            let output =
+                fetch_and_format_search(&query, page, include_archived, all_workspaces, format)
+                    .await?;
             Ok(format!(
                 "{}\n\n{}",
                 watch_status_line(interval_duration),
@@ -21,19 +57,70 @@ pub async fn search(query: &str, format: OutputFormat, watch: bool, interval: u6
         })
         .await?;
     } else {
-        let output = fetch_and_format_search(query, format).await?;
+        let output =
+            fetch_and_format_search(&query, page, include_archived, all_workspaces, format).await?;
         println!("{}", output);
     }
 
     Ok(())
 }
 
-/// Fetch search results and format them for display
-async fn fetch_and_format_search(query: &str, format: OutputFormat) -> Result<String> {
+/// Handle saved search subcommands
+pub async fn search_action(action: SearchAction) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let results = services::search(&pool, query).await?;
+    match action {
+        SearchAction::Save { name, query } => {
+            services::save_search(&pool, &name, &query).await?;
+            println!("Saved search {} = {}", name, query);
+        }
+
+        SearchAction::List => {
+            let searches = services::list_saved_searches(&pool).await?;
+            if searches.is_empty() {
+                println!("No saved searches");
+            } else {
+                for (name, query) in searches {
+                    println!("{} = {}", name, query);
+                }
+            }
+        }
+
+        SearchAction::Delete { name } => {
+            if services::delete_saved_search(&pool, &name).await? {
+                println!("Deleted saved search {}", name);
+            } else {
+                println!("Saved search not found: {}", name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch search results and format them for display
+async fn fetch_and_format_search(
+    query: &str,
+    page: Page,
+    include_archived: bool,
+    all_workspaces: bool,
+    format: OutputFormat,
+) -> Result<String> {
     let formatter = Formatter::new(format);
+    if all_workspaces {
+        let global = global_config_service::global_pool().await?;
+        let (results, warnings) =
+            services::search_all_workspaces(&global, query, include_archived, page).await?;
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        return Ok(formatter.format_workspace_search_results(&results));
+    }
+
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let results = services::search(&pool, query, include_archived, page).await?;
     Ok(formatter.format_search_results(&results))
 }
@@ -0,0 +1,37 @@
+use std::net::SocketAddr;
+
+use crate::error::Result;
+use crate::services::{self, Workspace, global_config_service, web_ui};
+
+/// Serve the web dashboard until interrupted, attributing GraphQL changes to
+/// `actor` (as with `--actor`). GraphQL mutations need the daemon auth token.
+pub async fn serve(addr: SocketAddr, graphql: bool, actor: Option<&str>) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let actor = services::resolve_actor(actor).await;
+
+    let token = graphql
+        .then(global_config_service::get_or_create_auth_token)
+        .transpose()?;
+    let router = web_ui::router(pool, workspace, actor, token)?;
+    let listener = web_ui::bind(addr).await?;
+
+    if !addr.ip().is_loopback() {
+        eprintln!(
+            "Warning: {} is reachable from other machines, and only GraphQL mutations are authenticated",
+            addr
+        );
+    }
+    println!(
+        "Serving the granary dashboard on http://{} (Ctrl+C to stop)",
+        addr
+    );
+    if graphql {
+        println!("GraphQL API on http://{}/graphql", addr);
+        println!(
+            "Mutations need the token in {} as `Authorization: Bearer <token>`",
+            global_config_service::daemon_auth_token_path()?.display()
+        );
+    }
+    web_ui::serve_on(listener, router).await
+}
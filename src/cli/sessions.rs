@@ -38,6 +38,19 @@ async fn fetch_and_format_sessions(include_closed: bool, format: OutputFormat) -
     Ok(formatter.format_sessions(&sessions))
 }
 
+/// Review what happened during a session, the current one by default
+pub async fn review_session(session_id: Option<String>, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let session_id = session_id
+        .or_else(|| workspace.current_session_id())
+        .ok_or(GranaryError::NoActiveSession)?;
+
+    let review = services::review_session(&pool, &workspace, &session_id).await?;
+    println!("{}", Formatter::new(format).format_session_review(&review));
+    Ok(())
+}
+
 /// Handle session subcommands
 pub async fn session(action: SessionAction, format: OutputFormat) -> Result<()> {
     let workspace = Workspace::find()?;
@@ -46,7 +59,8 @@ pub async fn session(action: SessionAction, format: OutputFormat) -> Result<()>
 
     match action {
         SessionAction::Start { name, owner, mode } => {
-            let mode = mode.parse().unwrap_or_default();
+            let config = services::workspace_config::load(&workspace)?;
+            let mode = services::resolve_mode(&config, &mode)?;
 
             let session = services::create_session(
                 &pool,
@@ -88,6 +102,25 @@ pub async fn session(action: SessionAction, format: OutputFormat) -> Result<()>
             }
         }
 
+        SessionAction::Mode { mode } => {
+            let session_id = workspace
+                .current_session_id()
+                .ok_or(GranaryError::NoActiveSession)?;
+            let config = services::workspace_config::load(&workspace)?;
+            let mode = services::resolve_mode(&config, &mode)?;
+
+            let session = services::update_session(
+                &pool,
+                &session_id,
+                UpdateSession {
+                    mode: Some(mode),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            println!("{}", formatter.format_session(&session));
+        }
+
         SessionAction::Use { session_id } => {
             // Verify session exists
             let session = services::get_session(&pool, &session_id).await?;
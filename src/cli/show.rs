@@ -1,5 +1,6 @@
 use crate::db;
 use crate::error::{GranaryError, Result};
+use crate::models::CommentThread;
 use crate::output::{Formatter, OutputFormat};
 use crate::services::{self, Workspace};
 
@@ -13,6 +14,7 @@ pub enum EntityKind {
     Checkpoint,
     Comment,
     Artifact,
+    Milestone,
 }
 
 /// Detect the entity type from an ID based on naming patterns
@@ -23,6 +25,7 @@ pub enum EntityKind {
 /// - Task: `<project_id>-task-<n>` e.g., `my-project-5h18-task-42`
 /// - Comment: `<parent_id>-comment-<n>` e.g., `my-project-5h18-task-42-comment-3`
 /// - Artifact: `<parent_id>-artifact-<n>` e.g., `my-project-5h18-task-42-artifact-1`
+/// - Milestone: `<initiative_id>-milestone-<n>` e.g., `my-initiative-5h18-milestone-2`
 /// - Initiative: `<slug>-<4char suffix>` e.g., `my-initiative-5h18` (same pattern as Project)
 /// - Project: `<slug>-<4char suffix>` e.g., `my-project-5h18`
 ///
@@ -39,6 +42,8 @@ pub fn detect_entity_kind(id: &str) -> EntityKind {
         EntityKind::Artifact
     } else if id.contains("-task-") {
         EntityKind::Task
+    } else if id.contains("-milestone-") {
+        EntityKind::Milestone
     } else {
         // Could be initiative or project - they share the same ID pattern
         // Return Project as default, but show() will try Initiative first
@@ -46,8 +51,9 @@ pub fn detect_entity_kind(id: &str) -> EntityKind {
     }
 }
 
-/// Show an entity by ID, auto-detecting its type
-pub async fn show(id: &str, format: OutputFormat) -> Result<()> {
+/// Show an entity by ID, auto-detecting its type. With `comments`, a task's
+/// or project's comments follow, nested into reply threads.
+pub async fn show(id: &str, comments: bool, format: OutputFormat) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
     let formatter = Formatter::new(format);
@@ -58,23 +64,43 @@ pub async fn show(id: &str, format: OutputFormat) -> Result<()> {
         EntityKind::Initiative => {
             // This case is used when explicitly looking up initiatives
             let initiative = services::get_initiative_or_error(&pool, id).await?;
-            println!("{}", formatter.format_initiative(&initiative));
+            let milestones = services::list_milestones(&pool, id).await?;
+            println!(
+                "{}",
+                formatter.format_initiative_with_milestones(&initiative, &milestones)
+            );
         }
 
         EntityKind::Project => {
             // Since Initiative and Project share the same ID pattern,
             // try Initiative first, then fall back to Project
             if let Some(initiative) = services::get_initiative(&pool, id).await? {
-                println!("{}", formatter.format_initiative(&initiative));
+                let milestones = services::list_milestones(&pool, id).await?;
+                println!(
+                    "{}",
+                    formatter.format_initiative_with_milestones(&initiative, &milestones)
+                );
             } else {
                 let project = services::get_project(&pool, id).await?;
                 println!("{}", formatter.format_project(&project));
+                if comments {
+                    print_comment_threads(&pool, &formatter, &project.id).await?;
+                }
             }
         }
 
         EntityKind::Task => {
             let (task, blocked_by) = services::get_task_with_deps(&pool, id).await?;
-            println!("{}", formatter.format_task_with_deps(&task, blocked_by));
+            let links = db::vcs_links::list_by_task(&pool, &task.id).await?;
+            let attachments = db::attachments::list_by_task(&pool, &task.id).await?;
+            let related = services::related_tasks(&pool, &task.id).await?;
+            println!(
+                "{}",
+                formatter.format_task_with_links(&task, blocked_by, &links, &attachments, &related)
+            );
+            if comments {
+                print_comment_threads(&pool, &formatter, &task.id).await?;
+            }
         }
 
         EntityKind::Session => {
@@ -109,11 +135,29 @@ pub async fn show(id: &str, format: OutputFormat) -> Result<()> {
                 .ok_or_else(|| GranaryError::ArtifactNotFound(id.to_string()))?;
             println!("{}", formatter.format_artifact(&artifact));
         }
+
+        EntityKind::Milestone => {
+            let milestone = services::get_milestone(&pool, id).await?;
+            println!("{}", formatter.format_milestone(&milestone));
+        }
     }
 
     Ok(())
 }
 
+async fn print_comment_threads(
+    pool: &sqlx::SqlitePool,
+    formatter: &Formatter,
+    parent_id: &str,
+) -> Result<()> {
+    let comments = db::comments::list_by_parent(pool, parent_id).await?;
+    println!(
+        "{}",
+        formatter.format_comment_threads(&CommentThread::build(comments))
+    );
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -137,6 +181,10 @@ mod tests {
             detect_entity_kind("my-project-5h18-task-42-artifact-1"),
             EntityKind::Artifact
         );
+        assert_eq!(
+            detect_entity_kind("my-initiative-5h18-milestone-2"),
+            EntityKind::Milestone
+        );
         // Initiative and Project share the same ID pattern (slug-suffix)
         // detect_entity_kind returns Project as default, show() handles the distinction
         assert_eq!(detect_entity_kind("my-project-5h18"), EntityKind::Project);
@@ -2,12 +2,18 @@ use std::time::Duration;
 
 use crate::cli::watch::watch_loop;
 use crate::error::Result;
-use crate::output::{OutputFormat, json, prompt};
-use crate::services::{self, Workspace};
+use crate::output::{OutputFormat, json, markdown, prompt, yaml};
+use crate::services::context_spec::ContextSelection;
+use crate::services::prompt_templates::{self, PromptKind};
+use crate::services::tokens::{TokenBudget, TokenModel};
+use crate::services::{self, Workspace, datetime};
 
 /// Generate summary
 pub async fn summary(
     token_budget: Option<usize>,
+    model: TokenModel,
+    template: Option<String>,
+    include_archived: bool,
     format: OutputFormat,
     watch: bool,
     interval: u64,
@@ -15,11 +21,25 @@ pub async fn summary(
     if watch {
         let interval_duration = Duration::from_secs(interval);
         watch_loop(interval_duration, || async {
-            render_summary(token_budget, format).await
+            render_summary(
+                token_budget,
+                model,
+                template.as_deref(),
+                include_archived,
+                format,
+            )
+            .await
         })
         .await?;
     } else {
-        let output = render_summary(token_budget, format).await?;
+        let output = render_summary(
+            token_budget,
+            model,
+            template.as_deref(),
+            include_archived,
+            format,
+        )
+        .await?;
         print!("{}", output);
     }
 
@@ -27,15 +47,28 @@ pub async fn summary(
 }
 
 /// Render summary output as a string (for both regular and watch mode)
-async fn render_summary(token_budget: Option<usize>, format: OutputFormat) -> Result<String> {
+async fn render_summary(
+    token_budget: Option<usize>,
+    model: TokenModel,
+    template: Option<&str>,
+    include_archived: bool,
+    format: OutputFormat,
+) -> Result<String> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let summary = services::generate_summary(&pool, &workspace, token_budget).await?;
+    let budget = TokenBudget::new(token_budget, model);
+    let summary = services::generate_summary(&pool, &workspace, budget, include_archived).await?;
 
     let output = match format {
         OutputFormat::Json => json::format_summary(&summary),
-        OutputFormat::Prompt => prompt::format_summary(&summary),
+        OutputFormat::Yaml => yaml::format_summary(&summary),
+        OutputFormat::Md => markdown::format_summary(&summary),
+        OutputFormat::Prompt => {
+            prompt_templates::render(&pool, &workspace, PromptKind::Summary, template, &summary)
+                .await?
+                .unwrap_or_else(|| prompt::format_summary(&summary))
+        }
         _ => format_summary_table(&summary),
     };
 
@@ -79,6 +112,44 @@ fn format_summary_table(summary: &json::SummaryOutput) -> String {
         summary.state.by_priority.p3,
         summary.state.by_priority.p4
     ));
+    if !summary.state.by_label.is_empty() {
+        let labels: Vec<String> = summary
+            .state
+            .by_label
+            .iter()
+            .map(|(label, count)| format!("{} {}", count, label))
+            .collect();
+        output.push_str(&format!("  By label: {}\n", labels.join(", ")));
+    }
+    if !summary.state.by_assignee.is_empty() {
+        let assignees: Vec<String> = summary
+            .state
+            .by_assignee
+            .iter()
+            .map(|(assignee, count)| format!("{} {}", count, assignee))
+            .collect();
+        output.push_str(&format!("  By assignee: {}\n", assignees.join(", ")));
+    }
+    if !summary.state.by_author.is_empty() {
+        let authors: Vec<String> = summary
+            .state
+            .by_author
+            .iter()
+            .map(|(author, counts)| {
+                format!(
+                    "{} ({} tasks, {} comments)",
+                    author, counts.tasks, counts.comments
+                )
+            })
+            .collect();
+        output.push_str(&format!("  By author: {}\n", authors.join(", ")));
+    }
+    for rollup in &summary.state.estimates {
+        output.push_str(&format!(
+            "  Estimate ({} {}): {} of {} remaining\n",
+            rollup.kind, rollup.name, rollup.remaining, rollup.total
+        ));
+    }
     output.push('\n');
 
     if let Some(focus) = &summary.focus_task {
@@ -94,6 +165,17 @@ fn format_summary_table(summary: &json::SummaryOutput) -> String {
         output.push('\n');
     }
 
+    if !summary.interrupted.is_empty() {
+        output.push_str(&format!("Set Aside ({}):\n", summary.interrupted.len()));
+        for task in &summary.interrupted {
+            output.push_str(&format!(
+                "  - {} ({}) [{}]\n",
+                task.title, task.id, task.status
+            ));
+        }
+        output.push('\n');
+    }
+
     if !summary.blockers.is_empty() {
         output.push_str(&format!("Blockers ({}):\n", summary.blockers.len()));
         for task in &summary.blockers {
@@ -106,6 +188,35 @@ fn format_summary_table(summary: &json::SummaryOutput) -> String {
         output.push('\n');
     }
 
+    if !summary.due_soon.is_empty() {
+        output.push_str(&format!("Due Soon ({}):\n", summary.due_soon.len()));
+        for task in &summary.due_soon {
+            output.push_str(&format!(
+                "  - [{}] {} ({}): due {}",
+                task.priority,
+                task.title,
+                task.id,
+                datetime::format_local_date(task.due_at.as_deref().unwrap_or_default())
+            ));
+            if task.is_overdue() {
+                output.push_str(" (overdue)");
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    if !summary.stale.is_empty() {
+        output.push_str(&format!("Stale ({}):\n", summary.stale.len()));
+        for task in &summary.stale {
+            output.push_str(&format!(
+                "  - [{}] {} ({}): no activity in {}d\n",
+                task.priority, task.title, task.id, task.idle_days
+            ));
+        }
+        output.push('\n');
+    }
+
     if !summary.next_actions.is_empty() {
         output.push_str("Next Actions:\n");
         for task in &summary.next_actions {
@@ -119,9 +230,12 @@ fn format_summary_table(summary: &json::SummaryOutput) -> String {
 
     if !summary.recent_decisions.is_empty() {
         output.push_str("Recent Decisions:\n");
-        for comment in &summary.recent_decisions {
-            let author = comment.author.as_deref().unwrap_or("unknown");
-            output.push_str(&format!("  - {}: {}\n", author, comment.content));
+        for decision in &summary.recent_decisions {
+            let author = decision.comment.author.as_deref().unwrap_or("unknown");
+            output.push_str(&format!("  - {}: {}\n", author, decision.comment.content));
+            if let Some(context) = decision.thread_context() {
+                output.push_str(&format!("    ({})\n", context));
+            }
         }
         output.push('\n');
     }
@@ -132,26 +246,38 @@ fn format_summary_table(summary: &json::SummaryOutput) -> String {
 /// Generate context pack
 pub async fn context(
     include: Option<String>,
+    exclude: Option<String>,
     max_items: Option<usize>,
+    token_budget: Option<usize>,
+    model: TokenModel,
+    template: Option<String>,
     format: OutputFormat,
 ) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let include_vec = include.map(|s| s.split(',').map(|s| s.trim().to_string()).collect());
+    let selection = ContextSelection::parse(include.as_deref(), exclude.as_deref())?;
 
-    let context = services::generate_context(&pool, &workspace, include_vec, max_items).await?;
+    let budget = TokenBudget::new(token_budget, model);
+    let context =
+        services::generate_context(&pool, &workspace, &selection, max_items, budget).await?;
 
     match format {
         OutputFormat::Json => {
             println!("{}", json::format_context(&context));
         }
-        OutputFormat::Prompt => {
-            println!("{}", prompt::format_context(&context));
+        OutputFormat::Yaml => {
+            print!("{}", yaml::format_context(&context));
         }
         _ => {
             // Default to prompt format for context
-            println!("{}", prompt::format_context(&context));
+            let kind = PromptKind::Context;
+            match prompt_templates::render(&pool, &workspace, kind, template.as_deref(), &context)
+                .await?
+            {
+                Some(output) => print!("{}", output),
+                None => println!("{}", prompt::format_context(&context)),
+            }
         }
     }
 
@@ -159,11 +285,15 @@ pub async fn context(
 }
 
 /// Generate handoff document
+#[allow(clippy::too_many_arguments)]
 pub async fn handoff(
     to: &str,
     tasks: &str,
     constraints: Option<String>,
     acceptance_criteria: Option<String>,
+    token_budget: Option<usize>,
+    model: TokenModel,
+    template: Option<String>,
     format: OutputFormat,
 ) -> Result<()> {
     let workspace = Workspace::find()?;
@@ -178,6 +308,7 @@ pub async fn handoff(
         constraints.as_deref(),
         acceptance_criteria.as_deref(),
         None,
+        TokenBudget::new(token_budget, model),
     )
     .await?;
 
@@ -185,11 +316,20 @@ pub async fn handoff(
         OutputFormat::Json => {
             println!("{}", json::format_handoff(&handoff));
         }
-        OutputFormat::Prompt => {
-            println!("{}", prompt::format_handoff(&handoff));
+        OutputFormat::Yaml => {
+            print!("{}", yaml::format_handoff(&handoff));
+        }
+        OutputFormat::Md => {
+            print!("{}", markdown::format_handoff(&handoff));
         }
         _ => {
-            println!("{}", prompt::format_handoff(&handoff));
+            let kind = PromptKind::Handoff;
+            match prompt_templates::render(&pool, &workspace, kind, template.as_deref(), &handoff)
+                .await?
+            {
+                Some(output) => print!("{}", output),
+                None => println!("{}", prompt::format_handoff(&handoff)),
+            }
         }
     }
 
@@ -0,0 +1,60 @@
+//! `granary sync`: two-way sync with external trackers, and the status of
+//! remotes.
+
+use crate::cli::args::SyncAction;
+use crate::error::{GranaryError, Result};
+use crate::models::{ConflictStrategy, SyncOptions};
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{Workspace, jira_service, linear_service, remote_service};
+
+/// Handle sync subcommands
+pub async fn sync(action: SyncAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let formatter = Formatter::new(format);
+    let report = match action {
+        SyncAction::Status => {
+            let statuses = remote_service::status(&pool).await?;
+            println!("{}", formatter.format_remote_statuses(&statuses));
+            return Ok(());
+        }
+        SyncAction::Linear {
+            dry_run,
+            conflict,
+            full,
+        } => {
+            let options = SyncOptions {
+                dry_run,
+                full,
+                conflict: parse_conflict(conflict)?,
+            };
+            linear_service::sync(&pool, &options).await?
+        }
+        SyncAction::Jira {
+            jql,
+            project,
+            dry_run,
+            conflict,
+            full,
+        } => {
+            let options = SyncOptions {
+                dry_run,
+                full,
+                conflict: parse_conflict(conflict)?,
+            };
+            let target = jira_service::JiraTarget { jql, project };
+            jira_service::sync(&pool, &options, &target).await?
+        }
+    };
+
+    println!("{}", formatter.format_sync_report(&report));
+    Ok(())
+}
+
+fn parse_conflict(conflict: Option<String>) -> Result<Option<ConflictStrategy>> {
+    conflict
+        .map(|c| c.parse::<ConflictStrategy>())
+        .transpose()
+        .map_err(GranaryError::InvalidArgument)
+}
@@ -1,18 +1,75 @@
-use crate::cli::args::{ArtifactAction, CommentAction, DepsAction, SubtaskAction, TaskAction};
+use crate::cli::args::{
+    ArtifactAction, CommentAction, DepsAction, SubtaskAction, TaskAction, TasksAction,
+};
+use crate::cli::initiatives::sanitize_mermaid_id;
 use crate::cli::watch::{watch_loop, watch_status_line};
 use crate::db;
-use crate::error::Result;
+use crate::error::{GranaryError, Result};
 use crate::models::*;
 use crate::output::{Formatter, OutputFormat};
-use crate::services::{self, Workspace};
+use crate::services::{
+    self, Workspace, attachment_service, datetime, global_config_service, recommendation,
+    workspace_config,
+};
 use std::time::Duration;
 
+/// Filters for `granary tasks`
+#[derive(Debug, Clone, Default)]
+pub struct TaskListFilter {
+    /// Show tasks across all projects instead of the session scope
+    pub all: bool,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub owner: Option<String>,
+    pub author: Option<String>,
+    /// Tasks must have every one of these labels
+    pub labels: Vec<String>,
+    /// Custom field filters as NAME=VALUE
+    pub fields: Vec<String>,
+    /// Only open tasks idle for this long, e.g. 14d
+    pub stale: Option<String>,
+    /// Name of a saved search to show instead of the filters above
+    pub saved: Option<String>,
+    pub page: Page,
+    pub include_archived: bool,
+    pub sort: Sort,
+    /// List open tasks from every registered workspace
+    pub global: bool,
+}
+
+impl TaskListFilter {
+    fn is_filtered(&self) -> bool {
+        self.status.is_some()
+            || self.priority.is_some()
+            || self.owner.is_some()
+            || self.author.is_some()
+            || !self.labels.is_empty()
+            || !self.fields.is_empty()
+            || self.stale.is_some()
+    }
+
+    fn search_query(&self) -> Result<SearchQuery> {
+        Ok(SearchQuery {
+            statuses: self.status.iter().cloned().collect(),
+            priorities: self.priority.iter().cloned().collect(),
+            owner: self.owner.clone(),
+            author: self.author.clone(),
+            labels: self.labels.clone(),
+            fields: services::parse_field_args(&self.fields)?,
+            stale_since: self
+                .stale
+                .as_deref()
+                .map(|stale| datetime::parse_since(stale, &datetime::settings()))
+                .transpose()?,
+            include_archived: self.include_archived,
+            ..Default::default()
+        })
+    }
+}
+
 /// List tasks
 pub async fn list_tasks(
-    all: bool,
-    status: Option<String>,
-    priority: Option<String>,
-    owner: Option<String>,
+    filter: TaskListFilter,
     format: OutputFormat,
     watch: bool,
     interval: u64,
@@ -20,14 +77,7 @@ pub async fn list_tasks(
     if watch {
         let interval_duration = Duration::from_secs(interval);
         watch_loop(interval_duration, || async {
-            let output = fetch_and_format_tasks(
-                all,
-                status.clone(),
-                priority.clone(),
-                owner.clone(),
-                format,
-            )
-            .await?;
+            let output = fetch_and_format_tasks(&filter, format).await?;
             Ok(format!(
                 "{}\n\n{}",
                 watch_status_line(interval_duration),
@@ -36,7 +86,7 @@ pub async fn list_tasks(
         })
         .await?;
     } else {
-        let output = fetch_and_format_tasks(all, status, priority, owner, format).await?;
+        let output = fetch_and_format_tasks(&filter, format).await?;
         println!("{}", output);
     }
 
@@ -44,42 +94,38 @@ pub async fn list_tasks(
 }
 
 /// Fetch tasks and format them for display
-async fn fetch_and_format_tasks(
-    all: bool,
-    status: Option<String>,
-    priority: Option<String>,
-    owner: Option<String>,
-    format: OutputFormat,
-) -> Result<String> {
+async fn fetch_and_format_tasks(filter: &TaskListFilter, format: OutputFormat) -> Result<String> {
+    if filter.global {
+        let global = global_config_service::global_pool().await?;
+        let (tasks, warnings) =
+            services::global_tasks(&global, &filter.search_query()?, filter.page).await?;
+        for warning in warnings {
+            eprintln!("Warning: {}", warning);
+        }
+        return Ok(Formatter::new(format).format_workspace_tasks(&tasks));
+    }
+
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let tasks = if all || status.is_some() || priority.is_some() || owner.is_some() {
-        services::list_tasks_filtered(
-            &pool,
-            status.as_deref(),
-            priority.as_deref(),
-            owner.as_deref(),
-        )
-        .await?
-    } else {
-        // Default: show tasks in current session scope, or all if no session
-        if let Some(session_id) = workspace.current_session_id() {
-            let project_ids =
-                services::get_scope_by_type(&pool, &session_id, ScopeItemType::Project).await?;
-            if project_ids.is_empty() {
-                services::list_all_tasks(&pool).await?
-            } else {
-                let mut all_tasks = Vec::new();
-                for project_id in project_ids {
-                    let tasks = services::list_tasks_by_project(&pool, &project_id).await?;
-                    all_tasks.extend(tasks);
-                }
-                all_tasks
-            }
-        } else {
-            services::list_all_tasks(&pool).await?
+    let saved = match &filter.saved {
+        Some(name) => Some(name.clone()),
+        // A configured default view replaces the session scope
+        None if !filter.all && !filter.is_filtered() => services::default_task_view(&pool).await?,
+        None => None,
+    };
+    let tasks = match saved {
+        Some(name) => {
+            services::find_saved_tasks(
+                &pool,
+                &name,
+                filter.include_archived,
+                &filter.sort,
+                filter.page,
+            )
+            .await?
         }
+        None => fetch_filtered_tasks(&workspace, &pool, filter).await?,
     };
 
     // Enrich tasks with dependency information
@@ -89,10 +135,178 @@ async fn fetch_and_format_tasks(
     Ok(formatter.format_tasks_with_deps(&tasks_with_deps))
 }
 
+/// Fetch tasks matching the list flags, or the session scope when none are given
+async fn fetch_filtered_tasks(
+    workspace: &Workspace,
+    pool: &sqlx::SqlitePool,
+    filter: &TaskListFilter,
+) -> Result<Vec<Task>> {
+    let query = filter.search_query()?;
+
+    // Default: show tasks in current session scope, or all if no session
+    let scope = match workspace.current_session_id() {
+        Some(session_id) if !filter.all && !filter.is_filtered() => {
+            let project_ids =
+                services::get_scope_by_type(pool, &session_id, ScopeItemType::Project).await?;
+            (!project_ids.is_empty()).then_some(project_ids)
+        }
+        _ => None,
+    };
+
+    services::find_tasks_page(pool, &query, scope.as_deref(), &filter.sort, filter.page).await
+}
+
+/// Handle `granary tasks` subcommands
+pub async fn tasks_action(action: TasksAction, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+    let formatter = Formatter::new(format);
+
+    match action {
+        TasksAction::Label { id, changes } => {
+            let changes = services::parse_label_changes(&changes)?;
+            let task = services::label_task(&pool, &id, &changes).await?;
+            println!("{}", formatter.format_task(&task));
+        }
+        TasksAction::Attach { id, path, symlink } => {
+            let mode = if symlink {
+                AttachmentMode::Symlink
+            } else {
+                AttachmentMode::Copy
+            };
+            let attachment =
+                attachment_service::attach(&pool, &workspace, &id, &path, mode).await?;
+            match format {
+                OutputFormat::Table => {
+                    println!("Attached {} to {}", attachment.path, attachment.task_id)
+                }
+                _ => println!("{}", formatter.format_attachments(&[attachment])),
+            }
+        }
+        TasksAction::Link {
+            id,
+            relates_to,
+            duplicates,
+            caused_by,
+        } => {
+            let (kind, other) = match (relates_to, duplicates, caused_by) {
+                (Some(other), _, _) => (TaskLinkKind::RelatesTo, other),
+                (_, Some(other), _) => (TaskLinkKind::Duplicates, other),
+                (_, _, Some(other)) => (TaskLinkKind::CausedBy, other),
+                _ => {
+                    return Err(GranaryError::InvalidArgument(
+                        "Give one of --relates-to, --duplicates or --caused-by".to_string(),
+                    ));
+                }
+            };
+            let related = services::link_tasks(&pool, &id, &other, kind).await?;
+            println!("{}", formatter.format_related_tasks(&related));
+        }
+        TasksAction::Unlink { id, other } => {
+            if services::unlink_tasks(&pool, &id, &other).await? {
+                println!("Unlinked {} and {}", id, other);
+            } else {
+                println!("{} and {} are not linked", id, other);
+            }
+        }
+        TasksAction::Graph {
+            project,
+            mermaid,
+            dot,
+        } => {
+            let graph = services::dependency_graph(&pool, project.as_deref()).await?;
+            if mermaid {
+                print_graph_mermaid(&graph);
+            } else if dot {
+                print_graph_dot(&graph);
+            } else {
+                println!("{}", formatter.format_task_graph(&graph));
+            }
+        }
+        TasksAction::Claim {
+            id,
+            session,
+            ttl,
+            steal,
+        } => {
+            let session_id = session
+                .or_else(|| workspace.current_session_id())
+                .ok_or(GranaryError::NoActiveSession)?;
+            let task =
+                services::claim_task_for_session(&pool, &id, &session_id, ttl, steal).await?;
+            println!("{}", formatter.format_task(&task));
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a dependency graph as a Mermaid flowchart. An arrow points from a
+/// task to the task it depends on.
+fn print_graph_mermaid(graph: &TaskGraph) {
+    println!("flowchart TD");
+    for task in &graph.tasks {
+        println!(
+            "    {}[\"{}\"]:::{}",
+            sanitize_mermaid_id(&task.id),
+            task.title.replace('"', "'").replace('\n', " "),
+            task.status
+        );
+    }
+    for dep in &graph.dependencies {
+        println!(
+            "    {} --> {}",
+            sanitize_mermaid_id(&dep.task_id),
+            sanitize_mermaid_id(&dep.depends_on_task_id)
+        );
+    }
+    println!("    classDef done fill:#d4edda,stroke:#28a745");
+    println!("    classDef in_progress fill:#fff3cd,stroke:#ffc107");
+    println!("    classDef blocked fill:#f8d7da,stroke:#dc3545");
+}
+
+/// Print a dependency graph as a Graphviz DOT digraph. An edge points from a
+/// task to the task it depends on.
+fn print_graph_dot(graph: &TaskGraph) {
+    let quote = |s: &str| {
+        let escaped = s
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    };
+    println!("digraph dependencies {{");
+    println!("    rankdir=LR;");
+    println!("    node [shape=box, style=\"rounded,filled\", fillcolor=white];");
+    for task in &graph.tasks {
+        let fill = match task.status.as_str() {
+            "done" => ", fillcolor=\"#d4edda\"",
+            "in_progress" => ", fillcolor=\"#fff3cd\"",
+            "blocked" => ", fillcolor=\"#f8d7da\"",
+            _ => "",
+        };
+        println!(
+            "    {} [label={}{}];",
+            quote(&task.id),
+            quote(&format!("{}\n{}", task.title, task.id)),
+            fill
+        );
+    }
+    for dep in &graph.dependencies {
+        println!(
+            "    {} -> {};",
+            quote(&dep.task_id),
+            quote(&dep.depends_on_task_id)
+        );
+    }
+    println!("}}");
+}
+
 /// Show or manage a task
 pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
+    let session_id = workspace.current_session_id();
     let formatter = Formatter::new(format);
 
     match action {
@@ -109,6 +323,8 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
             owner,
             tags,
             due,
+            estimate,
+            fields,
         }) => {
             let status = status.as_ref().and_then(|s| s.parse().ok());
             let priority = priority.as_ref().and_then(|p| p.parse().ok());
@@ -124,9 +340,14 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
                     priority,
                     owner,
                     tags,
-                    due_at: due,
+                    due_at: due
+                        .map(|d| datetime::parse_due(&d, &datetime::settings()))
+                        .transpose()?,
+                    estimate,
+                    fields: services::parse_field_args(&fields)?,
                     ..Default::default()
                 },
+                session_id.as_deref(),
             )
             .await?;
 
@@ -134,12 +355,13 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
         }
 
         Some(TaskAction::Ready) => {
-            let task = services::ready_task(&pool, id).await?;
+            let task = services::ready_task(&pool, id, session_id.as_deref()).await?;
             println!("{}", formatter.format_task(&task));
         }
 
         Some(TaskAction::Start { owner, lease }) => {
-            let task = services::start_task(&pool, id, owner.clone()).await?;
+            let task =
+                services::start_task(&pool, id, owner.clone(), session_id.as_deref()).await?;
             if let Some(minutes) = lease {
                 let owner_name = owner.unwrap_or_else(|| "unknown".to_string());
                 services::claim_task(&pool, id, &owner_name, Some(minutes)).await?;
@@ -148,17 +370,29 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
         }
 
         Some(TaskAction::Done { comment }) => {
-            let task = services::complete_task(&pool, id, comment.as_deref()).await?;
+            let task =
+                services::complete_task(&pool, id, comment.as_deref(), session_id.as_deref())
+                    .await?;
             println!("{}", formatter.format_task(&task));
         }
 
         Some(TaskAction::Block { reason }) => {
-            let task = services::block_task(&pool, id, &reason).await?;
+            let task = services::block_task(&pool, id, &reason, session_id.as_deref()).await?;
             println!("{}", formatter.format_task(&task));
         }
 
         Some(TaskAction::Unblock) => {
-            let task = services::unblock_task(&pool, id).await?;
+            let task = services::unblock_task(&pool, id, session_id.as_deref()).await?;
+            println!("{}", formatter.format_task(&task));
+        }
+
+        Some(TaskAction::Archive) => {
+            let task = services::archive_task(&pool, id).await?;
+            println!("{}", formatter.format_task(&task));
+        }
+
+        Some(TaskAction::Unarchive) => {
+            let task = services::unarchive_task(&pool, id).await?;
             println!("{}", formatter.format_task(&task));
         }
 
@@ -193,7 +427,7 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
                 owner,
             }) => {
                 let task = services::get_task(&pool, id).await?;
-                let priority = priority.parse().unwrap_or_default();
+                let priority = workspace_config::task_priority(priority)?;
 
                 let subtask = services::create_task(
                     &pool,
@@ -223,13 +457,26 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
                 content_flag,
                 kind,
                 author,
+                reply_to,
             }) => {
                 let content = content_positional
                     .or(content_flag)
                     .ok_or_else(|| crate::error::GranaryError::InvalidArgument(
                         "content is required (provide as positional argument or with --content flag)".to_string()
                     ))?;
-                let comment = create_comment(&pool, id, &content, &kind, author).await?;
+                let comment = services::create_comment(
+                    &pool,
+                    CreateComment {
+                        parent_type: ParentType::Task,
+                        parent_id: id.to_string(),
+                        parent_comment_id: reply_to,
+                        kind: kind.parse().unwrap_or_default(),
+                        content,
+                        author,
+                        meta: None,
+                    },
+                )
+                .await?;
                 println!("{}", formatter.format_comment(&comment));
             }
         },
@@ -245,11 +492,11 @@ pub async fn task(id: &str, action: Option<TaskAction>, format: OutputFormat) ->
                 description,
             }) => {
                 let artifact =
-                    create_artifact(&pool, id, &artifact_type, &path, description).await?;
+                    services::add_artifact(&pool, id, &artifact_type, &path, description).await?;
                 println!("{}", formatter.format_artifact(&artifact));
             }
             Some(ArtifactAction::Rm { artifact_id }) => {
-                db::artifacts::delete(&pool, &artifact_id).await?;
+                services::remove_artifact(&pool, &artifact_id).await?;
                 println!("Removed artifact {}", artifact_id);
             }
         },
@@ -299,35 +546,47 @@ async fn handle_deps(
 }
 
 /// Get next actionable task
-pub async fn next_task(include_reason: bool, all: bool, format: OutputFormat) -> Result<()> {
+pub async fn next_task(
+    include_reason: bool,
+    all: bool,
+    explain: bool,
+    start: bool,
+    format: OutputFormat,
+) -> Result<()> {
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    // Get project scope from current session if any
-    let project_ids = if let Some(session_id) = workspace.current_session_id() {
-        let ids = services::get_scope_by_type(&pool, &session_id, ScopeItemType::Project).await?;
-        if ids.is_empty() { None } else { Some(ids) }
-    } else {
-        None
-    };
+    // Ready tasks in the session's project scope, best first
+    let mut recommendations = recommendation::recommend(&pool, &workspace).await?;
 
     let formatter = Formatter::new(format);
 
-    if all {
-        // Get all available tasks
-        let tasks = services::get_all_next_tasks(&pool, project_ids.as_deref()).await?;
+    if start {
+        let Some(top) = recommendations.first() else {
+            println!("{}", formatter.format_next_task(None, None));
+            return Ok(());
+        };
+        let session_id = workspace.current_session_id();
+        let task = services::start_task(&pool, &top.task.id, None, session_id.as_deref()).await?;
+        if let Some(session_id) = session_id {
+            services::set_focus_task(&pool, &session_id, &task.id).await?;
+        }
+        println!("{}", formatter.format_task(&task));
+    } else if explain {
+        if !all {
+            recommendations.truncate(1);
+        }
+        println!("{}", formatter.format_recommendations(&recommendations));
+    } else if all {
+        let tasks: Vec<Task> = recommendations.into_iter().map(|r| r.task).collect();
         println!("{}", formatter.format_tasks(&tasks));
     } else {
-        // Get single next task
-        let task = services::get_next_task(&pool, project_ids.as_deref()).await?;
-
-        let reason = if include_reason {
-            Some("Selected based on: priority, due date, creation time; all dependencies satisfied")
-        } else {
-            None
-        };
-
-        println!("{}", formatter.format_next_task(task.as_ref(), reason));
+        let top = recommendations.first();
+        let reason = top.filter(|_| include_reason).map(recommendation::reason);
+        println!(
+            "{}",
+            formatter.format_next_task(top.map(|r| &r.task), reason.as_deref())
+        );
     }
 
     Ok(())
@@ -343,7 +602,13 @@ pub async fn start_task(
     let workspace = Workspace::find()?;
     let pool = workspace.pool().await?;
 
-    let task = services::start_task(&pool, task_id, owner.clone()).await?;
+    let task = services::start_task(
+        &pool,
+        task_id,
+        owner.clone(),
+        workspace.current_session_id().as_deref(),
+    )
+    .await?;
 
     if let Some(minutes) = lease {
         let owner_name = owner.unwrap_or_else(|| "unknown".to_string());
@@ -380,6 +645,79 @@ pub async fn focus_task(task_id: &str, format: OutputFormat) -> Result<()> {
     Ok(())
 }
 
+/// Focus on a task, setting the current focus task aside
+pub async fn push_focus(task_id: &str, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let session_id = workspace
+        .current_session_id()
+        .ok_or(crate::error::GranaryError::NoActiveSession)?;
+
+    let session = services::push_focus_task(&pool, &session_id, task_id).await?;
+
+    let task = services::get_task(&pool, task_id).await?;
+    let formatter = Formatter::new(format);
+    println!(
+        "Focus set to: {} ({} set aside)",
+        task.title,
+        session.focus_stack_vec().len() - 1
+    );
+    println!("{}", formatter.format_task(&task));
+
+    Ok(())
+}
+
+/// Drop the focus task and return to the one set aside before it
+pub async fn pop_focus(format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let session_id = workspace
+        .current_session_id()
+        .ok_or(crate::error::GranaryError::NoActiveSession)?;
+
+    let (session, popped) = services::pop_focus_task(&pool, &session_id).await?;
+    println!("Dropped focus on {}", popped);
+    match &session.focus_task_id {
+        Some(focus_id) => {
+            let task = services::get_task(&pool, focus_id).await?;
+            let formatter = Formatter::new(format);
+            println!("Focus returned to: {}", task.title);
+            println!("{}", formatter.format_task(&task));
+        }
+        None => println!("No focus task"),
+    }
+
+    Ok(())
+}
+
+/// Show the focus stack of the current session, the focus task first
+pub async fn show_focus(format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let session = services::get_current_session(&pool, &workspace)
+        .await?
+        .ok_or(crate::error::GranaryError::NoActiveSession)?;
+
+    let mut tasks = Vec::new();
+    for id in session.focus_stack_vec().iter().rev() {
+        // Tasks deleted since they were pushed are skipped
+        if let Ok(task) = services::get_task(&pool, id).await {
+            tasks.push(task);
+        }
+    }
+    if tasks.is_empty() && format == OutputFormat::Table {
+        println!("No focus task");
+        return Ok(());
+    }
+    let formatter = Formatter::new(format);
+    println!("{}", formatter.format_tasks(&tasks));
+
+    Ok(())
+}
+
 /// Pin a task
 pub async fn pin_task(task_id: &str) -> Result<()> {
     let workspace = Workspace::find()?;
@@ -406,84 +744,3 @@ pub async fn unpin_task(task_id: &str) -> Result<()> {
 
     Ok(())
 }
-
-async fn create_comment(
-    pool: &sqlx::SqlitePool,
-    parent_id: &str,
-    content: &str,
-    kind: &str,
-    author: Option<String>,
-) -> Result<Comment> {
-    let scope = format!("task:{}:comment", parent_id);
-    let comment_number = db::counters::next(pool, &scope).await?;
-    let id = generate_comment_id(parent_id, comment_number);
-    let now = chrono::Utc::now().to_rfc3339();
-
-    let comment_kind: CommentKind = kind.parse().unwrap_or_default();
-
-    let comment = Comment {
-        id: id.clone(),
-        parent_type: "task".to_string(),
-        parent_id: parent_id.to_string(),
-        comment_number,
-        kind: comment_kind.as_str().to_string(),
-        content: content.to_string(),
-        author,
-        meta: None,
-        created_at: now.clone(),
-        updated_at: now,
-        version: 1,
-    };
-
-    db::comments::create(pool, &comment).await?;
-
-    // Log event
-    db::events::create(
-        pool,
-        &CreateEvent {
-            event_type: EventType::CommentCreated,
-            entity_type: EntityType::Comment,
-            entity_id: comment.id.clone(),
-            actor: comment.author.clone(),
-            session_id: None,
-            payload: serde_json::json!({
-                "kind": comment.kind,
-                "parent_id": comment.parent_id,
-            }),
-        },
-    )
-    .await?;
-
-    Ok(comment)
-}
-
-async fn create_artifact(
-    pool: &sqlx::SqlitePool,
-    parent_id: &str,
-    artifact_type: &str,
-    path: &str,
-    description: Option<String>,
-) -> Result<Artifact> {
-    let scope = format!("task:{}:artifact", parent_id);
-    let artifact_number = db::counters::next(pool, &scope).await?;
-    let id = generate_artifact_id(parent_id, artifact_number);
-    let now = chrono::Utc::now().to_rfc3339();
-
-    let art_type: ArtifactType = artifact_type.parse().unwrap_or_default();
-
-    let artifact = Artifact {
-        id: id.clone(),
-        parent_type: "task".to_string(),
-        parent_id: parent_id.to_string(),
-        artifact_number,
-        artifact_type: art_type.as_str().to_string(),
-        path_or_url: path.to_string(),
-        description,
-        meta: None,
-        created_at: now,
-    };
-
-    db::artifacts::create(pool, &artifact).await?;
-
-    Ok(artifact)
-}
@@ -0,0 +1,680 @@
+//! Interactive terminal dashboard.
+//!
+//! `granary tui` shows the workspace's tasks (as a filterable list or a kanban
+//! board), the selected task's detail and recent comments, and live worker and
+//! run status fetched from the daemon over IPC. It replaces running several
+//! `--watch` commands side by side.
+//!
+//! The daemon is never started by the dashboard: if it isn't running, the
+//! worker and run panes say so and the task panes keep working.
+
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use sqlx::SqlitePool;
+
+use crate::daemon::DaemonClient;
+use crate::db;
+use crate::error::Result;
+use crate::models::run::Run;
+use crate::models::worker::Worker;
+use crate::models::*;
+use crate::services::{self, Workspace};
+
+/// How long to wait for a key press before redrawing
+const INPUT_POLL: Duration = Duration::from_millis(200);
+
+/// Number of comments shown for the selected task
+const RECENT_COMMENTS: usize = 10;
+
+/// Kanban columns, in board order
+const KANBAN_COLUMNS: [TaskStatus; 5] = [
+    TaskStatus::Draft,
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::Blocked,
+    TaskStatus::Done,
+];
+
+/// Layout of the task pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TaskView {
+    List,
+    Kanban,
+}
+
+/// Whether key presses navigate or edit the filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    Normal,
+    Filter,
+}
+
+/// Detail for the selected task
+struct TaskDetail {
+    task: Task,
+    blocked_by: Vec<String>,
+    comments: Vec<Comment>,
+}
+
+/// Worker and run status from the daemon
+enum DaemonStatus {
+    Connected {
+        workers: Vec<Worker>,
+        runs: Vec<Run>,
+    },
+    Unavailable(String),
+}
+
+/// Dashboard state
+struct App {
+    tasks: Vec<Task>,
+    view: TaskView,
+    input_mode: InputMode,
+    filter: String,
+    /// Index of the selected task within the current list or kanban column
+    selected: usize,
+    /// Selected kanban column
+    column: usize,
+    detail: Option<TaskDetail>,
+    daemon: DaemonStatus,
+    should_quit: bool,
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            tasks: Vec::new(),
+            view: TaskView::List,
+            input_mode: InputMode::Normal,
+            filter: String::new(),
+            selected: 0,
+            column: 0,
+            detail: None,
+            daemon: DaemonStatus::Unavailable("Connecting to daemon...".to_string()),
+            should_quit: false,
+        }
+    }
+
+    /// Tasks matching the filter (case-insensitive on title, ID and owner)
+    fn filtered_tasks(&self) -> Vec<&Task> {
+        let needle = self.filter.to_lowercase();
+        self.tasks
+            .iter()
+            .filter(|t| {
+                needle.is_empty()
+                    || t.title.to_lowercase().contains(&needle)
+                    || t.id.to_lowercase().contains(&needle)
+                    || t.owner
+                        .as_deref()
+                        .is_some_and(|o| o.to_lowercase().contains(&needle))
+            })
+            .collect()
+    }
+
+    /// Filtered tasks in a kanban column
+    fn column_tasks(&self, column: usize) -> Vec<&Task> {
+        let status = KANBAN_COLUMNS[column].as_str();
+        self.filtered_tasks()
+            .into_iter()
+            .filter(|t| t.status == status)
+            .collect()
+    }
+
+    /// Tasks the selection currently moves through
+    fn visible_tasks(&self) -> Vec<&Task> {
+        match self.view {
+            TaskView::List => self.filtered_tasks(),
+            TaskView::Kanban => self.column_tasks(self.column),
+        }
+    }
+
+    fn selected_task(&self) -> Option<&Task> {
+        self.visible_tasks().get(self.selected).copied()
+    }
+
+    /// Keep the selection within the visible tasks after data or filter changes
+    fn clamp_selection(&mut self) {
+        let len = self.visible_tasks().len();
+        self.selected = self.selected.min(len.saturating_sub(1));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.should_quit = true;
+            return;
+        }
+
+        match self.input_mode {
+            InputMode::Filter => match key.code {
+                KeyCode::Enter => self.input_mode = InputMode::Normal,
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.input_mode = InputMode::Normal;
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => {}
+            },
+            InputMode::Normal => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+                KeyCode::Char('j') | KeyCode::Down
+                    if self.selected + 1 < self.visible_tasks().len() =>
+                {
+                    self.selected += 1;
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                KeyCode::Char('h') | KeyCode::Left if self.view == TaskView::Kanban => {
+                    self.column = self.column.saturating_sub(1);
+                }
+                KeyCode::Char('l') | KeyCode::Right if self.view == TaskView::Kanban => {
+                    self.column = (self.column + 1).min(KANBAN_COLUMNS.len() - 1);
+                }
+                KeyCode::Char('v') => {
+                    self.view = match self.view {
+                        TaskView::List => TaskView::Kanban,
+                        TaskView::Kanban => TaskView::List,
+                    };
+                    self.selected = 0;
+                }
+                KeyCode::Char('/') => self.input_mode = InputMode::Filter,
+                _ => {}
+            },
+        }
+
+        self.clamp_selection();
+    }
+
+    /// Reload tasks from the workspace database
+    async fn refresh_tasks(&mut self, pool: &SqlitePool) -> Result<()> {
+        self.tasks = db::tasks::list_all(pool, false).await?;
+        self.clamp_selection();
+        Ok(())
+    }
+
+    /// Reload detail and comments for the selected task
+    async fn refresh_detail(&mut self, pool: &SqlitePool) -> Result<()> {
+        let Some(id) = self.selected_task().map(|t| t.id.clone()) else {
+            self.detail = None;
+            return Ok(());
+        };
+
+        let (task, blocked_by) = services::get_task_with_deps(pool, &id).await?;
+        let mut comments = db::comments::list_by_parent(pool, &id).await?;
+        let skip = comments.len().saturating_sub(RECENT_COMMENTS);
+        comments.drain(..skip);
+
+        self.detail = Some(TaskDetail {
+            task,
+            blocked_by,
+            comments,
+        });
+        Ok(())
+    }
+
+    /// Fetch worker and run status from the daemon, if it is running
+    async fn refresh_daemon(&mut self) {
+        self.daemon = match fetch_daemon_status().await {
+            Ok((workers, runs)) => DaemonStatus::Connected { workers, runs },
+            Err(e) => DaemonStatus::Unavailable(format!(
+                "Daemon not available: {}\nStart it with `granary daemon start`.",
+                e
+            )),
+        };
+    }
+}
+
+async fn fetch_daemon_status() -> Result<(Vec<Worker>, Vec<Run>)> {
+    let mut client = DaemonClient::connect().await?;
+    let workers = client.list_workers(false).await?;
+    let runs = client.list_runs(None, None, false).await?;
+    Ok((workers, runs))
+}
+
+/// Run the dashboard until the user quits
+pub async fn tui(interval: u64) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, &pool, Duration::from_secs(interval.max(1))).await;
+    ratatui::restore();
+    result
+}
+
+async fn run_app(
+    terminal: &mut DefaultTerminal,
+    pool: &SqlitePool,
+    interval: Duration,
+) -> Result<()> {
+    let mut app = App::new();
+    let mut last_refresh: Option<Instant> = None;
+    let mut detail_for: Option<String> = None;
+
+    loop {
+        if last_refresh.is_none_or(|t| t.elapsed() >= interval) {
+            app.refresh_tasks(pool).await?;
+            app.refresh_daemon().await;
+            app.refresh_detail(pool).await?;
+            last_refresh = Some(Instant::now());
+        } else {
+            // Selection moved since the last draw
+            let selected = app.selected_task().map(|t| t.id.clone());
+            if selected != detail_for {
+                app.refresh_detail(pool).await?;
+            }
+        }
+        detail_for = app.detail.as_ref().map(|d| d.task.id.clone());
+
+        terminal.draw(|frame| draw(frame, &app))?;
+
+        if event::poll(INPUT_POLL)?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key);
+        }
+
+        if app.should_quit {
+            return Ok(());
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [main, bottom, footer] = Layout::vertical([
+        Constraint::Min(10),
+        Constraint::Length(8),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let [tasks_area, side] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(main);
+    let [detail_area, comments_area] =
+        Layout::vertical([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(side);
+    let [workers_area, runs_area] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(bottom);
+
+    match app.view {
+        TaskView::List => draw_task_list(frame, app, tasks_area),
+        TaskView::Kanban => draw_kanban(frame, app, tasks_area),
+    }
+    draw_detail(frame, app, detail_area);
+    draw_comments(frame, app, comments_area);
+    draw_daemon(frame, app, workers_area, runs_area);
+    draw_footer(frame, app, footer);
+}
+
+fn status_color(status: &str) -> Color {
+    match status {
+        "todo" => Color::Cyan,
+        "in_progress" => Color::Yellow,
+        "blocked" => Color::Red,
+        "done" => Color::Green,
+        "running" => Color::Yellow,
+        "paused" => Color::Magenta,
+        "completed" => Color::Green,
+        "failed" | "error" => Color::Red,
+        _ => Color::Gray,
+    }
+}
+
+fn highlight_style() -> Style {
+    Style::default()
+        .add_modifier(Modifier::REVERSED)
+        .add_modifier(Modifier::BOLD)
+}
+
+fn tasks_title(app: &App, label: &str) -> String {
+    if app.filter.is_empty() && app.input_mode == InputMode::Normal {
+        format!(" {} ", label)
+    } else {
+        format!(" {} (filter: {}) ", label, app.filter)
+    }
+}
+
+fn draw_task_list(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .filtered_tasks()
+        .into_iter()
+        .map(|t| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12}", t.status),
+                    Style::default().fg(status_color(&t.status)),
+                ),
+                Span::raw(format!("{} ", t.priority)),
+                Span::raw(t.title.clone()),
+                Span::styled(format!("  {}", t.id), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tasks_title(app, "Tasks")),
+        )
+        .highlight_style(highlight_style());
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_kanban(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(tasks_title(app, "Board"));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Ratio(1, KANBAN_COLUMNS.len() as u32);
+            KANBAN_COLUMNS.len()
+        ])
+        .split(inner);
+
+    for (i, status) in KANBAN_COLUMNS.iter().enumerate() {
+        let tasks = app.column_tasks(i);
+        let items: Vec<ListItem> = tasks
+            .iter()
+            .map(|t| ListItem::new(format!("{} {}", t.priority, t.title)))
+            .collect();
+        let mut title_style = Style::default().fg(status_color(status.as_str()));
+        if i == app.column {
+            title_style = title_style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        }
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::TOP).title(Span::styled(
+                format!("{} ({})", status.as_str(), tasks.len()),
+                title_style,
+            )))
+            .highlight_style(highlight_style());
+        let selected = (i == app.column).then_some(app.selected);
+        let mut state = ListState::default().with_selected(selected);
+        frame.render_stateful_widget(list, columns[i], &mut state);
+    }
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(" Task ");
+    let Some(detail) = &app.detail else {
+        frame.render_widget(Paragraph::new("No task selected").block(block), area);
+        return;
+    };
+
+    let task = &detail.task;
+    let label = |name: &str| {
+        Span::styled(
+            format!("{:<10}", name),
+            Style::default().fg(Color::DarkGray),
+        )
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(
+            task.title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(vec![label("ID"), Span::raw(task.id.clone())]),
+        Line::from(vec![
+            label("Status"),
+            Span::styled(
+                task.status.clone(),
+                Style::default().fg(status_color(&task.status)),
+            ),
+        ]),
+        Line::from(vec![label("Priority"), Span::raw(task.priority.clone())]),
+        Line::from(vec![
+            label("Owner"),
+            Span::raw(task.owner.clone().unwrap_or_else(|| "-".to_string())),
+        ]),
+    ];
+    if let Some(due) = &task.due_at {
+        lines.push(Line::from(vec![label("Due"), Span::raw(due.clone())]));
+    }
+    if let Some(reason) = &task.blocked_reason {
+        lines.push(Line::from(vec![
+            label("Blocked"),
+            Span::raw(reason.clone()),
+        ]));
+    }
+    if !detail.blocked_by.is_empty() {
+        lines.push(Line::from(vec![
+            label("Waits on"),
+            Span::raw(detail.blocked_by.join(", ")),
+        ]));
+    }
+    if let Some(description) = &task.description {
+        lines.push(Line::default());
+        lines.extend(description.lines().map(|l| Line::from(l.to_string())));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn draw_comments(frame: &mut Frame, app: &App, area: Rect) {
+    let comments = app
+        .detail
+        .as_ref()
+        .map(|d| d.comments.as_slice())
+        .unwrap_or_default();
+    let items: Vec<ListItem> = comments
+        .iter()
+        .rev()
+        .map(|c| {
+            let author = c.author.as_deref().unwrap_or("-");
+            let first_line = c.content.lines().next().unwrap_or("");
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("[{}] {}: ", c.kind, author),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(first_line.to_string()),
+            ]))
+        })
+        .collect();
+
+    frame.render_widget(
+        List::new(items).block(Block::default().borders(Borders::ALL).title(" Comments ")),
+        area,
+    );
+}
+
+fn draw_daemon(frame: &mut Frame, app: &App, workers_area: Rect, runs_area: Rect) {
+    let workers_block = Block::default().borders(Borders::ALL).title(" Workers ");
+    let runs_block = Block::default().borders(Borders::ALL).title(" Runs ");
+
+    let (workers, runs) = match &app.daemon {
+        DaemonStatus::Connected { workers, runs } => (workers, runs),
+        DaemonStatus::Unavailable(message) => {
+            frame.render_widget(
+                Paragraph::new(message.as_str())
+                    .block(workers_block)
+                    .wrap(Wrap { trim: true }),
+                workers_area,
+            );
+            frame.render_widget(runs_block, runs_area);
+            return;
+        }
+    };
+
+    let worker_items: Vec<ListItem> = workers
+        .iter()
+        .map(|w| {
+            let name = w.runner_name.as_deref().unwrap_or(&w.command);
+            let trigger = w.schedule.as_deref().unwrap_or(&w.event_type);
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<9}", w.status),
+                    Style::default().fg(status_color(&w.status)),
+                ),
+                Span::raw(format!("{} {} on {}", w.id, name, trigger)),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(worker_items).block(workers_block), workers_area);
+
+    let run_items: Vec<ListItem> = runs
+        .iter()
+        .map(|r| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<9}", r.status),
+                    Style::default().fg(status_color(&r.status)),
+                ),
+                Span::raw(format!("{} {} {}", r.id, r.event_type, r.entity_id)),
+                Span::styled(
+                    r.progress_display()
+                        .map(|p| format!(" [{}]", p))
+                        .unwrap_or_default(),
+                    Style::default().fg(Color::Cyan),
+                ),
+            ]))
+        })
+        .collect();
+    frame.render_widget(List::new(run_items).block(runs_block), runs_area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let help = match (app.input_mode, app.view) {
+        (InputMode::Filter, _) => "type to filter  enter: apply  esc: clear",
+        (InputMode::Normal, TaskView::List) => "j/k: move  /: filter  v: board view  q: quit",
+        (InputMode::Normal, TaskView::Kanban) => {
+            "j/k: move  h/l: column  /: filter  v: list view  q: quit"
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, title: &str, status: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "proj-abc1".to_string(),
+            task_number: 1,
+            parent_task_id: None,
+            title: title.to_string(),
+            description: None,
+            status: status.to_string(),
+            priority: "P2".to_string(),
+            owner: None,
+            author: None,
+            tags: None,
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
+            due_at: None,
+            estimate: None,
+            claim_owner: None,
+            claim_claimed_at: None,
+            claim_lease_expires_at: None,
+            claim_session_id: None,
+            pinned: 0,
+            focus_weight: 0,
+            archived_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            version: 1,
+            labels: vec![],
+            fields: Default::default(),
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.tasks = vec![
+            task("proj-abc1-task-1", "Write docs", "todo"),
+            task("proj-abc1-task-2", "Fix login", "in_progress"),
+            task("proj-abc1-task-3", "Fix logout", "todo"),
+        ];
+        app
+    }
+
+    #[test]
+    fn test_filter_narrows_tasks() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('/')));
+        for c in "fix".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Enter));
+
+        let titles: Vec<&str> = app
+            .visible_tasks()
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Fix login", "Fix logout"]);
+        assert_eq!(app.input_mode, InputMode::Normal);
+    }
+
+    #[test]
+    fn test_selection_is_clamped() {
+        let mut app = app();
+        for _ in 0..5 {
+            app.handle_key(key(KeyCode::Down));
+        }
+        assert_eq!(app.selected, 2);
+
+        // Narrowing the filter pulls the selection back into range
+        app.filter = "docs".to_string();
+        app.clamp_selection();
+        assert_eq!(app.selected_task().unwrap().id, "proj-abc1-task-1");
+    }
+
+    #[test]
+    fn test_kanban_columns() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('v')));
+        assert_eq!(app.view, TaskView::Kanban);
+
+        // Draft column is empty
+        assert!(app.selected_task().is_none());
+
+        app.handle_key(key(KeyCode::Right));
+        assert_eq!(app.visible_tasks().len(), 2);
+        app.handle_key(key(KeyCode::Right));
+        assert_eq!(app.selected_task().unwrap().title, "Fix login");
+    }
+
+    #[test]
+    fn test_quit_keys() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('/')));
+        app.handle_key(key(KeyCode::Char('q')));
+        assert!(!app.should_quit, "q while filtering is input");
+        assert_eq!(app.filter, "q");
+
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.filter.is_empty());
+        app.handle_key(key(KeyCode::Char('q')));
+        assert!(app.should_quit);
+    }
+}
@@ -0,0 +1,42 @@
+//! `granary undo`: reverse recent mutating commands using the operation log.
+
+use crate::error::Result;
+use crate::models::Operation;
+use crate::output::{OutputFormat, yaml};
+use crate::services::{self, Workspace};
+
+/// Undo the most recent operations, or show what would be undone
+pub async fn undo(last: u32, dry_run: bool, format: OutputFormat) -> Result<()> {
+    let workspace = Workspace::find()?;
+    let pool = workspace.pool().await?;
+
+    let operations = if dry_run {
+        services::list_undoable(&pool, last).await?
+    } else {
+        services::undo(&pool, last).await?
+    };
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&operations)?),
+        OutputFormat::Yaml => print!("{}", yaml::to_yaml(&operations)),
+        _ if operations.is_empty() => println!("Nothing to undo"),
+        _ => {
+            let verb = if dry_run { "Would undo" } else { "Undid" };
+            for operation in &operations {
+                println!("{} {}", verb, describe(operation));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn describe(operation: &Operation) -> String {
+    format!(
+        "{}: granary {} ({} change{})",
+        operation.id,
+        operation.command,
+        operation.changes,
+        if operation.changes == 1 { "" } else { "s" }
+    )
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use chrono::{DateTime, Utc};
@@ -6,8 +6,12 @@ use semver::Version;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{GranaryError, Result};
+use crate::models::global_config::{UpdateChannel, UpdateConfig};
+use crate::services::global_config as global_config_service;
 
 const GITHUB_REPO: &str = "speakeasy-api/granary";
+const INSTALL_SCRIPT_BASE: &str =
+    "https://raw.githubusercontent.com/speakeasy-api/granary/main/scripts";
 const CACHE_TTL_HOURS: i64 = 24;
 
 #[derive(Deserialize, Clone)]
@@ -24,15 +28,19 @@ struct UpdateCache {
     latest_prerelease: Option<String>,
 }
 
-/// Get cache file path (~/.granary/update-check.json)
-fn cache_path() -> Option<PathBuf> {
+fn home_dir() -> Option<PathBuf> {
     #[cfg(unix)]
     let home = std::env::var("HOME").ok();
 
     #[cfg(windows)]
     let home = std::env::var("USERPROFILE").ok();
 
-    home.map(|h| PathBuf::from(h).join(".granary").join("update-check.json"))
+    home.map(PathBuf::from)
+}
+
+/// Get cache file path (~/.granary/update-check.json)
+fn cache_path() -> Option<PathBuf> {
+    home_dir().map(|h| h.join(".granary").join("update-check.json"))
 }
 
 /// Read cached update info (if fresh, <24h old)
@@ -78,9 +86,26 @@ struct VersionInfo {
     latest_prerelease: Option<String>,
 }
 
-/// Fetch all releases from GitHub API
-async fn fetch_releases() -> Result<Vec<GitHubRelease>> {
-    let url = format!("https://api.github.com/repos/{}/releases", GITHUB_REPO);
+impl VersionInfo {
+    /// The newest release on a channel
+    fn latest(&self, channel: UpdateChannel) -> &str {
+        match (channel, &self.latest_prerelease) {
+            (UpdateChannel::Prerelease, Some(pre))
+                if is_newer_version(&self.latest_stable, pre) =>
+            {
+                pre
+            }
+            _ => &self.latest_stable,
+        }
+    }
+}
+
+/// Fetch all releases from the GitHub API, or the configured mirror
+async fn fetch_releases(config: &UpdateConfig) -> Result<Vec<GitHubRelease>> {
+    let url = match &config.release_url {
+        Some(url) => url.clone(),
+        None => format!("https://api.github.com/repos/{}/releases", GITHUB_REPO),
+    };
 
     let client = reqwest::Client::new();
     let response = client
@@ -92,7 +117,8 @@ async fn fetch_releases() -> Result<Vec<GitHubRelease>> {
 
     if !response.status().is_success() {
         return Err(GranaryError::Network(format!(
-            "GitHub API returned status {}",
+            "{} returned status {}",
+            url,
             response.status()
         )));
     }
@@ -111,8 +137,8 @@ fn strip_v_prefix(version: &str) -> &str {
 }
 
 /// Fetch version info from GitHub (latest stable and optionally latest prerelease)
-async fn fetch_version_info() -> Result<VersionInfo> {
-    let releases = fetch_releases().await?;
+async fn fetch_version_info(config: &UpdateConfig) -> Result<VersionInfo> {
+    let releases = fetch_releases(config).await?;
 
     // Find latest stable (first non-prerelease)
     let latest_stable = releases
@@ -143,29 +169,40 @@ fn is_newer_version(current: &str, latest: &str) -> bool {
 
 /// Check for update (fetches from GitHub and updates cache)
 pub async fn check_for_update() -> Result<Option<String>> {
+    let config = global_config_service::load()?.update;
+    if config.channel == UpdateChannel::None {
+        return Ok(None);
+    }
+
     let current = env!("CARGO_PKG_VERSION");
-    let info = fetch_version_info().await?;
+    let info = fetch_version_info(&config).await?;
 
     // Update cache
     let _ = write_cache(&info.latest_stable, info.latest_prerelease.as_deref());
 
-    if is_newer_version(current, &info.latest_stable) {
-        Ok(Some(info.latest_stable))
-    } else {
-        Ok(None)
-    }
+    let latest = info.latest(config.channel);
+    Ok(is_newer_version(current, latest).then(|| latest.to_string()))
 }
 
 /// Check for update using cache only (for version display)
 pub fn check_for_update_cached() -> Option<String> {
+    // A broken config shouldn't break `--version`
+    let config = global_config_service::load()
+        .map(|c| c.update)
+        .unwrap_or_default();
+    if !config.notify || config.channel == UpdateChannel::None {
+        return None;
+    }
+
     let current = env!("CARGO_PKG_VERSION");
     let cache = read_cache()?;
+    let info = VersionInfo {
+        latest_stable: cache.latest_version,
+        latest_prerelease: cache.latest_prerelease,
+    };
 
-    if is_newer_version(current, &cache.latest_version) {
-        Some(cache.latest_version)
-    } else {
-        None
-    }
+    let latest = info.latest(config.channel);
+    is_newer_version(current, latest).then(|| latest.to_string())
 }
 
 /// Get version string with update notice for clap
@@ -183,13 +220,181 @@ pub fn version_with_update_notice() -> &'static str {
     }
 }
 
+/// How the running binary was installed, which decides how it is updated
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InstallMethod {
+    /// The install script, into ~/.granary/bin or GRANARY_INSTALL_DIR
+    Script,
+    Homebrew,
+    /// `cargo install`, from crates.io or a git repository, with the
+    /// features it was built with
+    Cargo {
+        git: Option<String>,
+        features: Vec<String>,
+    },
+    CargoBinstall,
+    /// Anywhere else, e.g. a build from a checkout or a distro package
+    Unknown(PathBuf),
+}
+
+impl InstallMethod {
+    /// Detect how the running binary was installed
+    fn detect() -> Self {
+        let Ok(exe) = std::env::current_exe() else {
+            return InstallMethod::Script;
+        };
+        let cargo_home = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".cargo")));
+        let script_dir = std::env::var_os("GRANARY_INSTALL_DIR")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".granary").join("bin")));
+        Self::detect_from(&exe, cargo_home.as_deref(), script_dir.as_deref())
+    }
+
+    fn detect_from(exe: &Path, cargo_home: Option<&Path>, script_dir: Option<&Path>) -> Self {
+        let exe = exe.canonicalize().unwrap_or_else(|_| exe.to_path_buf());
+        let in_dir = |dir: &Path| {
+            let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+            exe.parent() == Some(dir.as_path())
+        };
+
+        let path = exe.to_string_lossy();
+        if path.contains("/Cellar/granary/") || path.contains("/.linuxbrew/") {
+            return InstallMethod::Homebrew;
+        }
+
+        if let Some(cargo_home) = cargo_home
+            && in_dir(&cargo_home.join("bin"))
+        {
+            let binstall = std::fs::read_to_string(cargo_home.join("binstall/crates-v1.json"))
+                .unwrap_or_default();
+            if binstall.contains("\"name\":\"granary\"") {
+                return InstallMethod::CargoBinstall;
+            }
+            return match cargo_install_record(cargo_home) {
+                Some(method) => method,
+                None => InstallMethod::Cargo {
+                    git: None,
+                    features: Vec::new(),
+                },
+            };
+        }
+
+        if script_dir.is_some_and(in_dir) {
+            return InstallMethod::Script;
+        }
+        InstallMethod::Unknown(exe)
+    }
+
+    /// The package manager command that installs `version`, or `None` to
+    /// run the install script. `requested` is set for `--to`, which
+    /// Homebrew can't honor.
+    fn command(&self, version: &str, requested: bool) -> Result<Option<Vec<String>>> {
+        let args: Vec<String> = match self {
+            InstallMethod::Script => return Ok(None),
+            InstallMethod::Homebrew if requested => {
+                return Err(GranaryError::Update(
+                    "granary was installed with Homebrew, which only installs the formula's version. Run `brew upgrade granary`, or uninstall it and use the install script to pick a version".to_string(),
+                ));
+            }
+            InstallMethod::Homebrew => vec!["brew".into(), "upgrade".into(), "granary".into()],
+            InstallMethod::Cargo { git, features } => {
+                let mut args = vec!["cargo".to_string(), "install".to_string()];
+                match git {
+                    Some(url) => {
+                        args.extend(["--git".to_string(), url.clone()]);
+                        args.extend(["--tag".to_string(), format!("v{}", version)]);
+                    }
+                    None => {
+                        args.push("granary".to_string());
+                        args.extend(["--version".to_string(), version.to_string()]);
+                    }
+                }
+                if !features.is_empty() {
+                    args.extend(["--features".to_string(), features.join(",")]);
+                }
+                args.push("--locked".to_string());
+                args
+            }
+            InstallMethod::CargoBinstall => vec![
+                "cargo".into(),
+                "binstall".into(),
+                "-y".into(),
+                format!("granary@{}", version),
+            ],
+            InstallMethod::Unknown(path) => {
+                return Err(GranaryError::Update(format!(
+                    "granary at {} wasn't installed by the install script, Homebrew or cargo. Update it the way it was installed, or reinstall with the install script",
+                    path.display()
+                )));
+            }
+        };
+        Ok(Some(args))
+    }
+}
+
+/// Find granary's `cargo install` record in `.crates2.json`
+fn cargo_install_record(cargo_home: &Path) -> Option<InstallMethod> {
+    let content = std::fs::read_to_string(cargo_home.join(".crates2.json")).ok()?;
+    let records: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let (key, record) = records["installs"]
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.starts_with("granary "))?;
+
+    // Keys look like "granary 0.9.1 (git+https://github.com/org/granary.git#<sha>)"
+    let source = key.split_once('(')?.1.trim_end_matches(')');
+    let git = match source.split_once('+') {
+        Some(("git", url)) => Some(url.split(['#', '?']).next().unwrap_or(url).to_string()),
+        Some(("registry" | "sparse", _)) => None,
+        _ => return None,
+    };
+    let features = record["features"]
+        .as_array()
+        .map(|f| {
+            f.iter()
+                .filter_map(|f| f.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(InstallMethod::Cargo { git, features })
+}
+
+/// Install `version` the way granary was installed
+fn install(version: &str, requested: bool, config: &UpdateConfig) -> Result<()> {
+    let Some(args) = InstallMethod::detect().command(version, requested)? else {
+        return run_install_script(Some(version), config);
+    };
+    let command_line = args.join(" ");
+    println!("Running `{}`", command_line);
+    println!();
+
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(|e| {
+            GranaryError::Update(format!(
+                "Failed to run `{}`: {}. Run it yourself to update",
+                command_line, e
+            ))
+        })?;
+    if !status.success() {
+        return Err(GranaryError::Update(format!("`{}` failed", command_line)));
+    }
+    Ok(())
+}
+
 /// Run the install script to perform the update
-fn run_install_script(version: Option<&str>) -> Result<()> {
+fn run_install_script(version: Option<&str>, config: &UpdateConfig) -> Result<()> {
     #[cfg(unix)]
     {
+        let script = config
+            .install_script
+            .clone()
+            .unwrap_or_else(|| format!("{}/install.sh", INSTALL_SCRIPT_BASE));
         let mut cmd = Command::new("sh");
-        cmd.arg("-c")
-            .arg("curl -sSfL https://raw.githubusercontent.com/speakeasy-api/granary/main/scripts/install.sh | sh");
+        cmd.arg("-c").arg(format!("curl -sSfL '{}' | sh", script));
 
         // Set GRANARY_VERSION env var if a specific version is requested
         if let Some(v) = version {
@@ -207,14 +412,14 @@ fn run_install_script(version: Option<&str>) -> Result<()> {
 
     #[cfg(windows)]
     {
+        let url = config
+            .install_script
+            .clone()
+            .unwrap_or_else(|| format!("{}/install.ps1", INSTALL_SCRIPT_BASE));
         let script = if let Some(v) = version {
-            format!(
-                "$env:GRANARY_VERSION='{}'; irm https://raw.githubusercontent.com/speakeasy-api/granary/main/scripts/install.ps1 | iex",
-                v
-            )
+            format!("$env:GRANARY_VERSION='{}'; irm '{}' | iex", v, url)
         } else {
-            "irm https://raw.githubusercontent.com/speakeasy-api/granary/main/scripts/install.ps1 | iex"
-                .to_string()
+            format!("irm '{}' | iex", url)
         };
 
         let status = Command::new("powershell")
@@ -234,22 +439,29 @@ fn run_install_script(version: Option<&str>) -> Result<()> {
 /// Main update command handler
 pub async fn update(check_only: bool, target_version: Option<String>) -> Result<()> {
     let current = env!("CARGO_PKG_VERSION");
+    let config = global_config_service::load()?.update;
 
     // If a specific version is requested, install it directly
     if let Some(ref version) = target_version {
         println!("Installing granary {}...", version);
         println!();
 
-        run_install_script(Some(version))?;
+        install(version, true, &config)?;
 
         println!();
         println!("Successfully installed granary {}!", version);
         return Ok(());
     }
 
+    if config.channel == UpdateChannel::None {
+        return Err(GranaryError::Update(
+            "Update checks are disabled (channel = \"none\" in [update] in ~/.granary/config.toml). Use --to=<version> to install a specific version".to_string(),
+        ));
+    }
+
     println!("Checking for updates...");
 
-    let info = match fetch_version_info().await {
+    let info = match fetch_version_info(&config).await {
         Ok(v) => v,
         Err(e) => {
             return Err(GranaryError::Update(format!(
@@ -262,19 +474,24 @@ pub async fn update(check_only: bool, target_version: Option<String>) -> Result<
     // Update cache
     let _ = write_cache(&info.latest_stable, info.latest_prerelease.as_deref());
 
-    let has_stable_update = is_newer_version(current, &info.latest_stable);
+    let latest = info.latest(config.channel).to_string();
+    let has_update = is_newer_version(current, &latest);
+    let channel_name = match config.channel {
+        UpdateChannel::Prerelease => "version",
+        _ => "stable version",
+    };
 
-    // Check if there's a prerelease newer than the latest stable
+    // On the stable channel, mention a prerelease newer than the latest stable
     let newer_prerelease = info.latest_prerelease.as_ref().and_then(|pre| {
-        if is_newer_version(&info.latest_stable, pre) {
+        if config.channel == UpdateChannel::Stable && is_newer_version(&info.latest_stable, pre) {
             Some(pre.clone())
         } else {
             None
         }
     });
 
-    if !has_stable_update {
-        println!("granary {} is the latest stable version", current);
+    if !has_update {
+        println!("granary {} is the latest {}", current, channel_name);
 
         // Show prerelease info if available and newer
         if let Some(pre) = &newer_prerelease {
@@ -288,7 +505,7 @@ pub async fn update(check_only: bool, target_version: Option<String>) -> Result<
 
     if check_only {
         println!("Current version: {}", current);
-        println!("Latest version:  {}", info.latest_stable);
+        println!("Latest version:  {}", latest);
 
         // Show prerelease info if available and newer
         if let Some(pre) = &newer_prerelease {
@@ -298,17 +515,20 @@ pub async fn update(check_only: bool, target_version: Option<String>) -> Result<
         }
 
         println!();
-        println!("Run `granary update` to install the latest stable version.");
+        println!(
+            "Run `granary update` to install the latest {}.",
+            channel_name
+        );
         return Ok(());
     }
 
-    println!("Updating granary {} → {}...", current, info.latest_stable);
+    println!("Updating granary {} → {}...", current, latest);
     println!();
 
-    run_install_script(None)?;
+    install(&latest, false, &config)?;
 
     println!();
-    println!("Successfully updated to granary {}!", info.latest_stable);
+    println!("Successfully updated to granary {}!", latest);
 
     // Inform about prerelease if available
     if let Some(pre) = &newer_prerelease {
@@ -319,3 +539,75 @@ pub async fn update(check_only: bool, target_version: Option<String>) -> Result<
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_install_method() {
+        let temp = tempfile::tempdir().unwrap();
+        let cargo_home = temp.path().join("cargo");
+        let script_dir = temp.path().join("granary-bin");
+        std::fs::create_dir_all(cargo_home.join("bin")).unwrap();
+        std::fs::create_dir_all(&script_dir).unwrap();
+        let detect =
+            |exe: &Path| InstallMethod::detect_from(exe, Some(&cargo_home), Some(&script_dir));
+
+        assert_eq!(detect(&script_dir.join("granary")), InstallMethod::Script);
+        assert_eq!(
+            detect(Path::new("/opt/homebrew/Cellar/granary/0.9.1/bin/granary")),
+            InstallMethod::Homebrew
+        );
+        assert!(matches!(
+            detect(Path::new("/usr/bin/granary")),
+            InstallMethod::Unknown(_)
+        ));
+
+        let cargo_exe = cargo_home.join("bin").join("granary");
+        std::fs::write(
+            cargo_home.join(".crates2.json"),
+            r#"{"installs":{"granary 0.9.1 (git+https://github.com/speakeasy-api/granary.git#1a2b3c)":{"features":["encryption"]}}}"#,
+        )
+        .unwrap();
+        let method = detect(&cargo_exe);
+        assert_eq!(
+            method,
+            InstallMethod::Cargo {
+                git: Some("https://github.com/speakeasy-api/granary.git".to_string()),
+                features: vec!["encryption".to_string()],
+            }
+        );
+        assert_eq!(
+            method.command("0.9.2", false).unwrap().unwrap().join(" "),
+            "cargo install --git https://github.com/speakeasy-api/granary.git --tag v0.9.2 --features encryption --locked"
+        );
+
+        std::fs::create_dir_all(cargo_home.join("binstall")).unwrap();
+        std::fs::write(
+            cargo_home.join("binstall/crates-v1.json"),
+            r#"{"name":"granary","version_req":"*","current_version":"0.9.1"}"#,
+        )
+        .unwrap();
+        assert_eq!(detect(&cargo_exe), InstallMethod::CargoBinstall);
+
+        assert!(InstallMethod::Homebrew.command("0.9.2", true).is_err());
+    }
+
+    #[test]
+    fn test_latest_for_channel() {
+        let info = VersionInfo {
+            latest_stable: "0.9.1".to_string(),
+            latest_prerelease: Some("0.10.0-pre.1".to_string()),
+        };
+        assert_eq!(info.latest(UpdateChannel::Stable), "0.9.1");
+        assert_eq!(info.latest(UpdateChannel::Prerelease), "0.10.0-pre.1");
+
+        // A prerelease older than the latest stable is ignored
+        let info = VersionInfo {
+            latest_stable: "0.9.1".to_string(),
+            latest_prerelease: Some("0.9.0-pre.2".to_string()),
+        };
+        assert_eq!(info.latest(UpdateChannel::Prerelease), "0.9.1");
+    }
+}
@@ -89,7 +89,13 @@ async fn work_start(task_id: &str, owner: Option<String>) -> Result<()> {
 
     // 6. Start the task (claims it)
     let owner_name = owner.clone().unwrap_or_else(|| "agent".to_string());
-    services::start_task(&pool, task_id, Some(owner_name.clone())).await?;
+    services::start_task(
+        &pool,
+        task_id,
+        Some(owner_name.clone()),
+        workspace.current_session_id().as_deref(),
+    )
+    .await?;
     services::claim_task(&pool, task_id, &owner_name, Some(30)).await?;
 
     // 7. Get the project
@@ -115,7 +121,13 @@ async fn work_done(task_id: &str, summary: &str) -> Result<()> {
     })?;
 
     // Complete the task with a comment
-    services::complete_task(&pool, task_id, Some(summary)).await?;
+    services::complete_task(
+        &pool,
+        task_id,
+        Some(summary),
+        workspace.current_session_id().as_deref(),
+    )
+    .await?;
 
     println!("Done.");
     Ok(())
@@ -132,7 +144,13 @@ async fn work_block(task_id: &str, reason: &str) -> Result<()> {
     })?;
 
     // Block the task
-    services::block_task(&pool, task_id, reason).await?;
+    services::block_task(
+        &pool,
+        task_id,
+        reason,
+        workspace.current_session_id().as_deref(),
+    )
+    .await?;
 
     println!("Blocked.");
     Ok(())
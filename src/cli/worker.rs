@@ -8,25 +8,50 @@ use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::time::Duration;
 
-use crate::cli::args::WorkerCommand;
+use crate::cli::args::{WorkerCommand, WorkerStartArgs};
 use crate::daemon::{LogTarget, StartWorkerRequest, ensure_daemon};
 use crate::error::{GranaryError, Result};
+use crate::models::{LogFilter, PauseMode, RetryPolicy, Run};
 use crate::output::{Formatter, OutputFormat};
-use crate::services::{Workspace, global_config_service};
+use crate::services::filter_expr::parse_event_filters;
+use crate::services::schedule::{SCHEDULE_EVENT_TYPE, Schedule};
+use crate::services::{Workspace, global_config_service, workspace_config};
 
 /// Handle worker commands
 pub async fn worker(command: WorkerCommand, format: OutputFormat) -> Result<()> {
     match command {
-        WorkerCommand::Start {
-            runner,
-            command,
-            args,
-            on,
-            filters,
-            detached,
-            concurrency,
-            poll_cooldown,
-        } => {
+        WorkerCommand::Start(start) => {
+            let WorkerStartArgs {
+                runner,
+                command,
+                args,
+                on,
+                filters,
+                detached,
+                concurrency,
+                poll_cooldown,
+                schedule,
+                max_attempts,
+                retry_strategy,
+                retry_delay,
+                retry_max_delay,
+                no_retry_jitter,
+                retry_on_timeout,
+                timeout,
+                debounce,
+                throttle,
+                stdin,
+                global,
+                on_pause,
+            } = *start;
+            let retry_policy = build_retry_policy(
+                max_attempts,
+                retry_strategy,
+                retry_delay,
+                retry_max_delay,
+                no_retry_jitter,
+                retry_on_timeout,
+            )?;
             start_worker(StartWorkerArgs {
                 runner_name: runner,
                 inline_command: command,
@@ -36,6 +61,14 @@ pub async fn worker(command: WorkerCommand, format: OutputFormat) -> Result<()>
                 detached,
                 concurrency,
                 poll_cooldown_secs: poll_cooldown,
+                schedule,
+                retry_policy,
+                timeout_secs: timeout,
+                debounce_secs: debounce,
+                throttle_secs: throttle,
+                stdin,
+                global,
+                on_pause,
                 format,
             })
             .await
@@ -60,9 +93,79 @@ struct StartWorkerArgs {
     detached: bool,
     concurrency: u32,
     poll_cooldown_secs: i64,
+    schedule: Option<String>,
+    retry_policy: Option<RetryPolicy>,
+    timeout_secs: Option<u64>,
+    debounce_secs: Option<u64>,
+    throttle_secs: Option<u64>,
+    stdin: bool,
+    global: bool,
+    on_pause: PauseMode,
     format: OutputFormat,
 }
 
+/// Reject settings a global worker cannot honour. Polled events are computed
+/// from one workspace, and debounce and throttle coalesce events by ID,
+/// which is only unique within a workspace.
+fn check_global_worker(
+    event_type: &str,
+    debounce_secs: Option<u64>,
+    throttle_secs: Option<u64>,
+) -> Result<()> {
+    if matches!(event_type, "task.next" | "project.next") {
+        return Err(GranaryError::InvalidArgument(format!(
+            "--global cannot be used with {} workers",
+            event_type
+        )));
+    }
+    if debounce_secs.is_some() || throttle_secs.is_some() {
+        return Err(GranaryError::InvalidArgument(
+            "--global cannot be combined with --debounce or --throttle".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Build a retry policy from CLI flags, or None if no retry flag was given
+fn build_retry_policy(
+    max_attempts: Option<i32>,
+    strategy: Option<String>,
+    base_delay_secs: Option<u64>,
+    max_delay_secs: Option<u64>,
+    no_jitter: bool,
+    retry_timeouts: bool,
+) -> Result<Option<RetryPolicy>> {
+    if max_attempts.is_none()
+        && strategy.is_none()
+        && base_delay_secs.is_none()
+        && max_delay_secs.is_none()
+        && !no_jitter
+        && !retry_timeouts
+    {
+        return Ok(None);
+    }
+
+    let defaults = RetryPolicy::default();
+    let strategy = match strategy {
+        Some(s) => s.parse().map_err(|_| {
+            GranaryError::InvalidArgument(format!(
+                "Invalid retry strategy '{}': expected fixed or exponential",
+                s
+            ))
+        })?,
+        None => defaults.strategy,
+    };
+
+    Ok(Some(RetryPolicy {
+        strategy,
+        max_attempts: max_attempts.unwrap_or(defaults.max_attempts),
+        base_delay_secs: base_delay_secs.unwrap_or(defaults.base_delay_secs),
+        max_delay_secs,
+        jitter: !no_jitter,
+        retry_timeouts,
+    }))
+}
+
 /// Start a new worker via the daemon
 async fn start_worker(args: StartWorkerArgs) -> Result<()> {
     let StartWorkerArgs {
@@ -74,15 +177,31 @@ async fn start_worker(args: StartWorkerArgs) -> Result<()> {
         detached,
         concurrency,
         poll_cooldown_secs,
+        schedule,
+        retry_policy,
+        timeout_secs,
+        debounce_secs,
+        throttle_secs,
+        stdin,
+        global,
+        on_pause,
         format,
     } = args;
 
+    // A scheduled worker without --on only fires on its timer
+    if let Some(ref expression) = schedule {
+        Schedule::parse(expression)?;
+    }
+    parse_event_filters(&filters)?;
+    let event_type =
+        event_type.or_else(|| schedule.as_ref().map(|_| SCHEDULE_EVENT_TYPE.to_string()));
+
     // Validate we have either a runner or an inline command
-    let (command, final_args, final_concurrency, final_event_type) =
+    let (command, final_args, final_concurrency, final_event_type, final_timeout, final_stdin) =
         match (&runner_name, &inline_command) {
             (Some(name), None) => {
                 // Load runner from config
-                let runner = global_config_service::get_runner(name)?
+                let runner = workspace_config::get_runner(name)?
                     .ok_or_else(|| GranaryError::RunnerNotFound(name.clone()))?;
 
                 let concurrency = if concurrency == 1 {
@@ -108,16 +227,25 @@ async fn start_worker(args: StartWorkerArgs) -> Result<()> {
                     merged_args,
                     concurrency,
                     resolved_event_type,
+                    timeout_secs.or(runner.timeout_secs),
+                    stdin || runner.stdin,
                 )
             }
             (None, Some(cmd)) => {
                 // Inline command requires --on
                 let resolved_event_type = event_type.ok_or_else(|| {
                     GranaryError::InvalidArgument(
-                        "Must specify --on event type when using inline --command".to_string(),
+                        "Must specify --on or --schedule when using inline --command".to_string(),
                     )
                 })?;
-                (cmd.clone(), cli_args, concurrency, resolved_event_type)
+                (
+                    cmd.clone(),
+                    cli_args,
+                    concurrency,
+                    resolved_event_type,
+                    timeout_secs,
+                    stdin,
+                )
             }
             (Some(_), Some(_)) => {
                 return Err(GranaryError::InvalidArgument(
@@ -131,6 +259,20 @@ async fn start_worker(args: StartWorkerArgs) -> Result<()> {
             }
         };
 
+    if global {
+        check_global_worker(&final_event_type, debounce_secs, throttle_secs)?;
+    }
+
+    if !super::events::event_types()
+        .iter()
+        .any(|t| t.name == final_event_type)
+    {
+        eprintln!(
+            "Warning: '{}' is not an event type granary emits; see granary events types",
+            final_event_type
+        );
+    }
+
     // Get workspace path
     let workspace = Workspace::find()?;
     let instance_path = workspace.root.to_string_lossy().to_string();
@@ -149,6 +291,14 @@ async fn start_worker(args: StartWorkerArgs) -> Result<()> {
         instance_path,
         attach: !detached,
         poll_cooldown_secs: Some(poll_cooldown_secs),
+        schedule,
+        retry_policy,
+        timeout_secs: final_timeout.map(|secs| secs as i64),
+        debounce_secs: debounce_secs.map(|secs| secs as i64),
+        throttle_secs: throttle_secs.map(|secs| secs as i64),
+        stdin: final_stdin,
+        global,
+        on_pause,
     };
 
     let worker = client.start_worker(req).await?;
@@ -226,6 +376,48 @@ async fn show_status(worker_id: &str, format: OutputFormat) -> Result<()> {
     println!("  Completed: {}", completed_count);
     println!("  Failed:    {}", failed_count);
 
+    // Resource usage of finished runs, to spot runaway runners
+    let measured: Vec<_> = runs.iter().filter(|r| r.duration_ms.is_some()).collect();
+    if !measured.is_empty() {
+        let cpu_ms = |r: &Run| r.user_cpu_ms.unwrap_or(0) + r.system_cpu_ms.unwrap_or(0);
+        let total_cpu_ms: i64 = measured.iter().map(|r| cpu_ms(r)).sum();
+        let total_wall_ms: i64 = measured.iter().filter_map(|r| r.duration_ms).sum();
+        println!(
+            "  CPU Time:  {:.1}s over {} runs",
+            total_cpu_ms as f64 / 1000.0,
+            measured.len()
+        );
+        println!(
+            "  Avg Time:  {:.1}s",
+            total_wall_ms as f64 / 1000.0 / measured.len() as f64
+        );
+        if let Some(run) = measured.iter().max_by_key(|r| cpu_ms(r)) {
+            println!(
+                "  Most CPU:  {:.1}s ({})",
+                cpu_ms(run) as f64 / 1000.0,
+                run.id
+            );
+        }
+        if let Some(run) = measured.iter().max_by_key(|r| r.max_rss_kb)
+            && let Some(kb) = run.max_rss_kb
+        {
+            println!("  Peak RSS:  {:.1} MB ({})", kb as f64 / 1024.0, run.id);
+        }
+    }
+
+    // Tokens and cost the runs reported on GRANARY_USAGE lines
+    let reported: Vec<_> = runs
+        .iter()
+        .filter(|r| r.input_tokens.is_some() || r.output_tokens.is_some() || r.cost_usd.is_some())
+        .collect();
+    if !reported.is_empty() {
+        let input: i64 = reported.iter().filter_map(|r| r.input_tokens).sum();
+        let output: i64 = reported.iter().filter_map(|r| r.output_tokens).sum();
+        let cost: f64 = reported.iter().filter_map(|r| r.cost_usd).sum();
+        println!("  Tokens:    {} in, {} out", input, output);
+        println!("  Cost:      ${:.4} over {} runs", cost, reported.len());
+    }
+
     Ok(())
 }
 
@@ -237,71 +429,41 @@ async fn show_logs(worker_id: &str, follow: bool, lines: usize) -> Result<()> {
     // Verify worker exists by getting it from daemon
     let worker = client.get_worker(worker_id).await?;
 
+    // Worker logs are plain text, so they are never filtered
+    let filter = LogFilter::default();
+
     if follow {
         // Use daemon-based log streaming for follow mode
         println!("--- Following worker logs via daemon (Ctrl+C to stop) ---");
 
-        // Use Ctrl+C to break out of the follow loop
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(1);
-        let _ctrlc_task = tokio::spawn(async move {
-            let _ = tokio::signal::ctrl_c().await;
-            let _ = tx.send(()).await;
-        });
-
-        // Stream logs via daemon with polling
-
-        // Get initial lines from the end
-        let initial_response = client
-            .get_logs(&worker.id, LogTarget::Worker, 0, u64::MAX)
-            .await?;
-        let total_lines = initial_response.next_line;
-        let mut since_line = total_lines.saturating_sub(lines as u64);
-
-        // Print initial lines
-        if since_line < total_lines {
-            let response = client
-                .get_logs(&worker.id, LogTarget::Worker, since_line, 1000)
-                .await?;
-            for line in &response.lines {
-                println!("{}", line);
-            }
-            since_line = response.next_line;
-        }
-
-        // Poll for new lines
-        loop {
-            // Check for Ctrl+C
-            match rx.try_recv() {
-                Ok(()) | Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
-                    println!();
-                    println!("Stopped following logs.");
-                    break;
+        // The daemon pushes new lines until the worker stops; Ctrl+C detaches
+        let follow = client.follow_logs(
+            &worker.id,
+            LogTarget::Worker,
+            lines as u64,
+            &filter,
+            |batch| {
+                for line in batch {
+                    println!("{}", line);
                 }
-                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {}
-            }
-
-            let response = client
-                .get_logs(&worker.id, LogTarget::Worker, since_line, 100)
-                .await?;
+                true
+            },
+        );
 
-            for line in &response.lines {
-                println!("{}", line);
-            }
-
-            since_line = response.next_line;
-
-            // If worker is no longer active and no more lines, we're done
-            if !response.has_more && response.lines.is_empty() {
+        tokio::select! {
+            result = follow => {
+                result?;
                 println!("--- Worker is no longer active ---");
-                break;
             }
-
-            tokio::time::sleep(Duration::from_millis(100)).await;
+            _ = tokio::signal::ctrl_c() => {
+                println!();
+                println!("Stopped following logs.");
+            }
         }
     } else {
         // Non-follow mode: get logs via daemon
         let response = client
-            .get_logs(&worker.id, LogTarget::Worker, 0, u64::MAX)
+            .get_logs(&worker.id, LogTarget::Worker, 0, u64::MAX, &filter)
             .await?;
 
         let total_lines = response.next_line;
@@ -309,7 +471,13 @@ async fn show_logs(worker_id: &str, follow: bool, lines: usize) -> Result<()> {
 
         // Get the last N lines
         let response = client
-            .get_logs(&worker.id, LogTarget::Worker, start_line, lines as u64)
+            .get_logs(
+                &worker.id,
+                LogTarget::Worker,
+                start_line,
+                lines as u64,
+                &filter,
+            )
             .await?;
 
         if response.lines.is_empty() {
@@ -329,6 +497,15 @@ async fn show_logs(worker_id: &str, follow: bool, lines: usize) -> Result<()> {
 
 /// Print the last N lines of a log file
 pub fn print_log_tail(path: &PathBuf, lines: usize) -> Result<()> {
+    print_log_tail_with(path, lines, str::to_string)
+}
+
+/// Print the last N lines of a log file, passing each through `format_line`
+pub fn print_log_tail_with(
+    path: &PathBuf,
+    lines: usize,
+    format_line: fn(&str) -> String,
+) -> Result<()> {
     let file = std::fs::File::open(path)?;
     let reader = BufReader::new(file);
     let all_lines: Vec<String> = reader.lines().collect::<std::io::Result<_>>()?;
@@ -340,7 +517,7 @@ pub fn print_log_tail(path: &PathBuf, lines: usize) -> Result<()> {
     };
 
     for line in &all_lines[start..] {
-        println!("{}", line);
+        println!("{}", format_line(line));
     }
 
     Ok(())
@@ -348,12 +525,24 @@ pub fn print_log_tail(path: &PathBuf, lines: usize) -> Result<()> {
 
 /// Follow a log file like tail -f
 pub async fn follow_log(path: &PathBuf, initial_lines: usize) -> Result<()> {
+    follow_log_with(path, initial_lines, str::to_string).await
+}
+
+/// Follow a log file like tail -f, passing each line through `format_line`.
+///
+/// If the file is rotated or truncated while following, the new file is
+/// read from the start.
+pub async fn follow_log_with(
+    path: &PathBuf,
+    initial_lines: usize,
+    format_line: fn(&str) -> String,
+) -> Result<()> {
     // Print initial lines
-    print_log_tail(path, initial_lines)?;
+    print_log_tail_with(path, initial_lines, format_line)?;
 
     // Open file for following
     let mut file = std::fs::File::open(path)?;
-    file.seek(SeekFrom::End(0))?;
+    let mut position = file.seek(SeekFrom::End(0))?;
 
     let mut reader = BufReader::new(file);
     let mut line = String::new();
@@ -363,12 +552,23 @@ pub async fn follow_log(path: &PathBuf, initial_lines: usize) -> Result<()> {
     loop {
         match reader.read_line(&mut line) {
             Ok(0) => {
+                // The file was rotated away or truncated; start over on the new one
+                if std::fs::metadata(path).is_ok_and(|m| m.len() < position)
+                    && let Ok(file) = std::fs::File::open(path)
+                {
+                    reader = BufReader::new(file);
+                    position = 0;
+                    continue;
+                }
                 // No new data, wait a bit
                 tokio::time::sleep(Duration::from_millis(100)).await;
             }
-            Ok(_) => {
-                print!("{}", line);
-                line.clear();
+            Ok(read) => {
+                position += read as u64;
+                if line.ends_with('\n') {
+                    println!("{}", format_line(line.trim_end_matches(['\r', '\n'])));
+                    line.clear();
+                }
             }
             Err(e) => {
                 eprintln!("Error reading log: {}", e);
@@ -1,19 +1,111 @@
 //! Workers list CLI command.
 //!
-//! Lists all workers across all workspaces from the global database.
+//! Lists workers from the global database, across all workspaces or for one,
+//! and tests worker filters.
 
 use std::time::Duration;
 
+use crate::cli::args::WorkersAction;
 use crate::cli::watch::{watch_loop, watch_status_line};
+use crate::daemon::ensure_daemon;
 use crate::db;
-use crate::error::Result;
-use crate::models::worker::WorkerStatus;
+use crate::error::{GranaryError, Result};
+use crate::models::worker::{FilterTest, WorkerStatus};
 use crate::output::{Formatter, OutputFormat};
-use crate::services::global_config_service;
+use crate::services::filter_expr::EventFilter;
+use crate::services::{Workspace, global_config_service};
 
-/// List all workers with optional watch mode
+/// Handle workers subcommands
+pub async fn workers_action(
+    action: Option<WorkersAction>,
+    all: bool,
+    workspace: Option<String>,
+    format: OutputFormat,
+    watch: bool,
+    interval: u64,
+) -> Result<()> {
+    match action {
+        None | Some(WorkersAction::List) => {
+            list_workers(all, workspace, format, watch, interval).await
+        }
+        Some(WorkersAction::Pause { worker_id }) => pause_worker(&worker_id, format).await,
+        Some(WorkersAction::Resume { worker_id }) => resume_worker(&worker_id, format).await,
+        Some(WorkersAction::TestFilter {
+            filter,
+            payload,
+            event,
+        }) => test_filter(filter, payload, event, format).await,
+    }
+}
+
+/// Pause a worker through the daemon
+async fn pause_worker(worker_id: &str, format: OutputFormat) -> Result<()> {
+    let mut client = ensure_daemon().await?;
+    let worker = client.pause_worker(worker_id).await?;
+
+    let formatter = Formatter::new(format);
+    println!("Worker paused.");
+    println!("{}", formatter.format_worker(&worker));
+
+    Ok(())
+}
+
+/// Resume a paused worker through the daemon
+async fn resume_worker(worker_id: &str, format: OutputFormat) -> Result<()> {
+    let mut client = ensure_daemon().await?;
+    let worker = client.resume_worker(worker_id).await?;
+
+    let formatter = Formatter::new(format);
+    println!("Worker resumed.");
+    println!("{}", formatter.format_worker(&worker));
+
+    Ok(())
+}
+
+/// Parse a worker filter and, given a payload or event, evaluate it the way
+/// the daemon does before creating a run
+async fn test_filter(
+    filter: String,
+    payload: Option<String>,
+    event_id: Option<i64>,
+    format: OutputFormat,
+) -> Result<()> {
+    let parsed = EventFilter::parse(&filter)?;
+
+    let payload = match (payload, event_id) {
+        (Some(json), _) => Some(serde_json::from_str(&json).map_err(|e| {
+            GranaryError::InvalidArgument(format!("Invalid --payload JSON: {}", e))
+        })?),
+        (None, Some(id)) => {
+            let pool = Workspace::find()?.pool().await?;
+            let event = db::events::get(&pool, id)
+                .await?
+                .ok_or_else(|| GranaryError::InvalidArgument(format!("Event not found: {}", id)))?;
+            Some(event.payload_json())
+        }
+        (None, None) => None,
+    };
+
+    let test = FilterTest {
+        kind: match parsed {
+            EventFilter::Field(_) => "field",
+            EventFilter::Expr(_) => "expression",
+        }
+        .to_string(),
+        matches: payload.map(|payload| parsed.matches(&payload)),
+        filter,
+    };
+    println!("{}", Formatter::new(format).format_filter_test(&test));
+    Ok(())
+}
+
+/// List workers with optional watch mode.
+///
+/// With `workspace`, only workers that receive events from that workspace
+/// are listed: the ones started in it, and global workers.
 pub async fn list_workers(
     all: bool,
+    workspace: Option<String>,
     format: OutputFormat,
     watch: bool,
     interval: u64,
@@ -21,7 +113,7 @@ pub async fn list_workers(
     if watch {
         let interval_duration = Duration::from_secs(interval);
         watch_loop(interval_duration, || async {
-            let output = fetch_and_format_workers(all, format).await?;
+            let output = fetch_and_format_workers(all, workspace.as_deref(), format).await?;
             Ok(format!(
                 "{}\n{}",
                 watch_status_line(interval_duration),
@@ -30,7 +122,7 @@ pub async fn list_workers(
         })
         .await?;
     } else {
-        let output = fetch_and_format_workers(all, format).await?;
+        let output = fetch_and_format_workers(all, workspace.as_deref(), format).await?;
         println!("{}", output);
     }
 
@@ -38,10 +130,18 @@ pub async fn list_workers(
 }
 
 /// Fetch workers and format them for display
-async fn fetch_and_format_workers(all: bool, format: OutputFormat) -> anyhow::Result<String> {
+async fn fetch_and_format_workers(
+    all: bool,
+    workspace: Option<&str>,
+    format: OutputFormat,
+) -> anyhow::Result<String> {
     let global_pool = global_config_service::global_pool().await?;
 
-    let workers = db::workers::list(&global_pool).await?;
+    let workers: Vec<_> = db::workers::list(&global_pool)
+        .await?
+        .into_iter()
+        .filter(|w| workspace.is_none_or(|root| w.serves_workspace(root)))
+        .collect();
 
     // Filter out stopped/errored workers unless --all is specified
     let workers: Vec<_> = if all {
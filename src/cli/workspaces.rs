@@ -0,0 +1,27 @@
+//! `granary workspaces`: the registry of workspaces on this machine.
+
+use crate::cli::args::WorkspacesAction;
+use crate::error::Result;
+use crate::output::{Formatter, OutputFormat};
+use crate::services::{self, global_config_service};
+
+/// Handle workspaces subcommands
+pub async fn workspaces(action: Option<WorkspacesAction>, format: OutputFormat) -> Result<()> {
+    let global = global_config_service::global_pool().await?;
+
+    match action.unwrap_or(WorkspacesAction::List) {
+        WorkspacesAction::List => {
+            let workspaces = services::list_workspaces(&global).await?;
+            println!("{}", Formatter::new(format).format_workspaces(&workspaces));
+        }
+        WorkspacesAction::Rename { name, new_name } => {
+            let workspace = services::rename_workspace(&global, &name, &new_name).await?;
+            println!("Renamed workspace {} to {}", name, workspace.name);
+        }
+        WorkspacesAction::Remove { name } => {
+            services::forget_workspace(&global, &name).await?;
+            println!("Forgot workspace {}", name);
+        }
+    }
+    Ok(())
+}
@@ -2,21 +2,30 @@
 //!
 //! This module provides utilities to ensure the daemon is running when CLI
 //! commands need it. It handles automatic daemon startup with exponential
-//! backoff retry logic.
+//! backoff retry logic, and uses the daemon's PID file to avoid spawning a
+//! second daemon while one is still starting up.
 
+use std::path::PathBuf;
 use std::process::Command;
 use std::time::Duration;
 use tokio::time::sleep;
 
 use crate::daemon::client::DaemonClient;
+use crate::daemon::transport::is_process_alive;
 use crate::error::{GranaryError, Result};
 use crate::services::global_config as global_config_service;
 
+/// Process name of the daemon binary
+#[cfg(unix)]
+const DAEMON_PROCESS_NAME: &str = "granaryd";
+
 /// Ensure the daemon is running, starting it if necessary.
 ///
 /// This function first attempts to connect to an already-running daemon.
 /// If the connection fails, it spawns a new daemon process and retries
-/// the connection with exponential backoff.
+/// the connection with exponential backoff. If the PID file points at a live
+/// granaryd, that daemon is still starting up and is given the same time to
+/// answer before it is replaced.
 ///
 /// # Returns
 ///
@@ -44,22 +53,58 @@ pub async fn ensure_daemon() -> Result<DaemonClient> {
         return Ok(client);
     }
 
-    // Daemon not running, spawn it
-    spawn_daemon()?;
+    // Daemon not running (or not accepting connections yet), spawn it
+    let starting = daemon_pid().is_some();
+    if !starting {
+        spawn_daemon()?;
+    }
+    if let Some(client) = connect_with_retries().await {
+        return Ok(client);
+    }
+
+    // The daemon in the PID file never answered, so it is hung: start
+    // another, which takes over its socket
+    if starting {
+        if let Ok(pid_path) = global_config_service::daemon_pid_path() {
+            let _ = std::fs::remove_file(pid_path);
+        }
+        spawn_daemon()?;
+        if let Some(client) = connect_with_retries().await {
+            return Ok(client);
+        }
+    }
+
+    Err(GranaryError::DaemonConnection(
+        "Failed to start daemon. Check ~/.granary/daemon/daemon.log for details.".to_string(),
+    ))
+}
 
-    // Retry with exponential backoff: 50ms, 100ms, 150ms, ...
+/// Connect to the daemon, retrying with backoff: 50ms, 100ms, 150ms, ...
+async fn connect_with_retries() -> Option<DaemonClient> {
     for attempt in 0..10 {
         let delay = Duration::from_millis(50 * (attempt + 1));
         sleep(delay).await;
 
         if let Ok(client) = DaemonClient::connect().await {
-            return Ok(client);
+            return Some(client);
         }
     }
+    None
+}
 
-    Err(GranaryError::DaemonConnection(
-        "Failed to start daemon. Check ~/.granary/daemon/daemon.log for details.".to_string(),
-    ))
+/// Path of the granaryd binary, which is installed next to the granary binary.
+pub fn daemon_binary_path() -> Result<PathBuf> {
+    let current_exe = std::env::current_exe()?;
+    let daemon_path =
+        current_exe.with_file_name(format!("granaryd{}", std::env::consts::EXE_SUFFIX));
+
+    if !daemon_path.exists() {
+        return Err(GranaryError::DaemonConnection(format!(
+            "Daemon binary not found at {:?}",
+            daemon_path
+        )));
+    }
+    Ok(daemon_path)
 }
 
 /// Spawn the daemon process in the background (Unix implementation).
@@ -71,16 +116,7 @@ pub async fn ensure_daemon() -> Result<DaemonClient> {
 fn spawn_daemon() -> Result<()> {
     use std::process::Stdio;
 
-    // Find the granaryd binary - it should be next to the granary binary
-    let current_exe = std::env::current_exe()?;
-    let daemon_path = current_exe.with_file_name("granaryd");
-
-    if !daemon_path.exists() {
-        return Err(GranaryError::DaemonConnection(format!(
-            "Daemon binary not found at {:?}",
-            daemon_path
-        )));
-    }
+    let daemon_path = daemon_binary_path()?;
 
     // Ensure the daemon directory exists for socket/pid/log files
     let daemon_dir = global_config_service::daemon_dir()?;
@@ -108,15 +144,7 @@ fn spawn_daemon() -> Result<()> {
     const DETACHED_PROCESS: u32 = 0x00000008;
     const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-    let current_exe = std::env::current_exe()?;
-    let daemon_path = current_exe.with_file_name("granaryd.exe");
-
-    if !daemon_path.exists() {
-        return Err(GranaryError::DaemonConnection(format!(
-            "Daemon binary not found at {:?}",
-            daemon_path
-        )));
-    }
+    let daemon_path = daemon_binary_path()?;
 
     // Ensure the daemon directory exists for socket/pid/log files
     let daemon_dir = global_config_service::daemon_dir()?;
@@ -131,8 +159,9 @@ fn spawn_daemon() -> Result<()> {
 
 /// Check if the daemon is currently running.
 ///
-/// This is a convenience function that attempts to connect to the daemon
-/// and returns true if the connection succeeds.
+/// Returns true if the PID file points at a live process and a connection
+/// to the daemon succeeds. The PID check avoids a connection attempt when
+/// the daemon has clearly exited.
 ///
 /// # Example
 ///
@@ -146,16 +175,18 @@ fn spawn_daemon() -> Result<()> {
 /// }
 /// ```
 pub async fn is_daemon_running() -> bool {
-    DaemonClient::connect().await.is_ok()
+    daemon_pid().is_some() && DaemonClient::connect().await.is_ok()
 }
 
 /// Get the daemon PID if running.
 ///
 /// Reads the PID from the daemon's PID file at `~/.granary/daemon/granaryd.pid`.
-/// Returns `None` if the PID file doesn't exist or cannot be parsed.
+/// Returns `None` if the PID file doesn't exist, cannot be parsed, or points
+/// at a process that is no longer running or is not granaryd (a stale file
+/// left by a crash, whose PID may since have been reused).
 ///
-/// Note: This does not verify if the process with that PID is still running.
-/// Use `is_daemon_running()` for a connection-based check.
+/// Note: This does not verify that the process is accepting connections.
+/// Use `is_daemon_running()` for that.
 ///
 /// # Example
 ///
@@ -169,7 +200,40 @@ pub async fn is_daemon_running() -> bool {
 pub fn daemon_pid() -> Option<u32> {
     let pid_path = global_config_service::daemon_pid_path().ok()?;
     let pid_str = std::fs::read_to_string(&pid_path).ok()?;
-    pid_str.trim().parse().ok()
+    let pid = pid_str.trim().parse().ok()?;
+    (is_process_alive(pid) && is_daemon_process(pid)).then_some(pid)
+}
+
+/// Whether the process `pid` is granaryd. Where its name can't be read, any
+/// live process is taken to be the daemon.
+#[cfg(target_os = "linux")]
+fn is_daemon_process(pid: u32) -> bool {
+    match std::fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        Ok(name) => name.trim() == DAEMON_PROCESS_NAME,
+        Err(_) => true,
+    }
+}
+
+/// Whether the process `pid` is granaryd. Where its name can't be read, any
+/// live process is taken to be the daemon.
+#[cfg(all(unix, not(target_os = "linux")))]
+fn is_daemon_process(pid: u32) -> bool {
+    let Ok(output) = Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+    else {
+        return true;
+    };
+    let name = String::from_utf8_lossy(&output.stdout);
+    !output.status.success()
+        || std::path::Path::new(name.trim())
+            .file_name()
+            .is_some_and(|name| name == DAEMON_PROCESS_NAME)
+}
+
+#[cfg(windows)]
+fn is_daemon_process(_pid: u32) -> bool {
+    true
 }
 
 #[cfg(test)]
@@ -186,6 +250,13 @@ mod tests {
         let _ = result;
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_pid_of_another_process_is_not_the_daemon() {
+        // A PID file left by a crash may point at a reused PID
+        assert!(!is_daemon_process(std::process::id()));
+    }
+
     #[tokio::test]
     async fn test_is_daemon_running_returns_bool() {
         // This test verifies that is_daemon_running returns a bool without panicking
@@ -0,0 +1,126 @@
+//! Daily database snapshots.
+//!
+//! The scheduler checks every workspace the daemon serves (any workspace with
+//! a worker record) and takes a snapshot, like `granary backup` does, in
+//! those that set `backup.daily` to true and have no snapshot from the last
+//! day. Daily snapshots are rotated along with the others.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::Snapshot;
+use crate::services::{Workspace, backup_service};
+
+/// Interval between checks for workspaces due a snapshot
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Age of the newest snapshot after which a new one is taken
+const SNAPSHOT_EVERY: chrono::Duration = chrono::Duration::days(1);
+
+/// Takes daily snapshots of workspaces that ask for them.
+pub struct BackupScheduler {
+    global_pool: SqlitePool,
+}
+
+impl BackupScheduler {
+    pub fn new(global_pool: SqlitePool) -> Self {
+        Self { global_pool }
+    }
+
+    /// Check for due snapshots until a shutdown signal is received
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        tracing::warn!("Scheduled backup failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Snapshot every served workspace that is due one
+    pub async fn tick(&self) -> Result<()> {
+        let paths: BTreeSet<String> = db::workers::list(&self.global_pool)
+            .await?
+            .into_iter()
+            .map(|w| w.instance_path)
+            .collect();
+
+        for path in paths {
+            // Workspaces that no longer exist are skipped
+            let Ok(workspace) = Workspace::open(&path) else {
+                continue;
+            };
+            if let Err(e) = self.backup_if_due(&workspace).await {
+                tracing::warn!("Scheduled backup of {} failed: {}", path, e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn backup_if_due(&self, workspace: &Workspace) -> Result<()> {
+        let pool = workspace.pool().await?;
+        if !backup_service::daily_enabled(&pool).await? {
+            return Ok(());
+        }
+        if !is_due(
+            &backup_service::list_snapshots(workspace)?,
+            chrono::Utc::now(),
+        ) {
+            return Ok(());
+        }
+        let snapshot =
+            backup_service::take_snapshot(workspace, &pool, Some(&self.global_pool), None).await?;
+        tracing::info!("Took daily snapshot {}", snapshot.path);
+        Ok(())
+    }
+}
+
+/// Whether the newest snapshot is older than a day
+fn is_due(snapshots: &[Snapshot], now: chrono::DateTime<chrono::Utc>) -> bool {
+    let Some(newest) = snapshots.first() else {
+        return true;
+    };
+    match chrono::DateTime::parse_from_rfc3339(&newest.created_at) {
+        Ok(created_at) => now.signed_duration_since(created_at) >= SNAPSHOT_EVERY,
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(created_at: &str) -> Snapshot {
+        Snapshot {
+            name: "20260214-093000".to_string(),
+            path: "/tmp/backups/20260214-093000".to_string(),
+            created_at: created_at.to_string(),
+            files: vec!["granary.db".to_string()],
+            size: 4096,
+        }
+    }
+
+    #[test]
+    fn test_backup_is_due() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-02-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert!(is_due(&[], now));
+        assert!(!is_due(&[snapshot("2026-02-15T09:00:00+00:00")], now));
+        assert!(is_due(&[snapshot("2026-02-14T09:00:00+00:00")], now));
+    }
+}
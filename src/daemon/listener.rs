@@ -2,7 +2,8 @@
 //!
 //! This module provides the IPC listener that the daemon uses to accept
 //! connections from CLI clients. On Unix, this uses Unix domain sockets.
-//! On Windows, this uses named pipes.
+//! On Windows, this uses named pipes. Both produce the same
+//! [`IpcConnection`], so request handling is platform independent.
 //!
 //! ## Security
 //!
@@ -10,16 +11,16 @@
 //! unauthorized access. The socket file is automatically cleaned up
 //! when the listener is dropped.
 //!
-//! On Windows, the named pipe includes the username for per-user isolation.
+//! On Windows, the named pipe includes the username for per-user isolation
+//! and rejects remote clients.
 //!
 //! ## Usage
 //!
 //! ```ignore
 //! use granary::daemon::listener::IpcListener;
+//! use granary::daemon::transport::IpcEndpoint;
 //!
-//! // Unix: pass socket path
-//! // Windows: pass pipe name
-//! let listener = IpcListener::bind(socket_path).await?;
+//! let mut listener = IpcListener::bind(&IpcEndpoint::daemon()?).await?;
 //! loop {
 //!     let mut conn = listener.accept().await?;
 //!     let request = conn.recv_request().await?;
@@ -29,12 +30,75 @@
 //! ```
 
 use crate::daemon::protocol::{Request, Response, read_request, write_response};
+use crate::daemon::transport::{IpcEndpoint, IpcStream};
 use crate::error::Result;
+use serde::Serialize;
+
+/// A connection to a CLI client.
+///
+/// Each connection represents a single CLI invocation and supports
+/// request/response communication using the IPC protocol. It works the same
+/// over a Unix socket or a named pipe.
+pub struct IpcConnection {
+    stream: Box<dyn IpcStream>,
+}
 
-#[cfg(unix)]
-use std::path::{Path, PathBuf};
-#[cfg(unix)]
-use tokio::net::{UnixListener, UnixStream};
+impl IpcConnection {
+    /// Create a new connection from an accepted stream.
+    pub fn new(stream: impl IpcStream + 'static) -> Self {
+        Self {
+            stream: Box::new(stream),
+        }
+    }
+
+    /// Receive a request from the client.
+    ///
+    /// Reads a length-delimited JSON frame from the connection and
+    /// deserializes it as a Request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Reading from the connection fails
+    /// - The frame cannot be deserialized as a Request
+    pub async fn recv_request(&mut self) -> Result<Request> {
+        let request = read_request(&mut self.stream).await?;
+        Ok(request)
+    }
+
+    /// Send a response to the client.
+    ///
+    /// Serializes the response as JSON and writes it as a length-delimited
+    /// frame to the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The response cannot be serialized
+    /// - Writing to the connection fails
+    pub async fn send_response(&mut self, response: &Response) -> Result<()> {
+        write_response(&mut self.stream, response).await?;
+        Ok(())
+    }
+
+    /// Send one frame of a streaming response.
+    ///
+    /// The client keeps reading frames for the request until it receives
+    /// the frame sent by `end_stream`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails, which usually means the client
+    /// has disconnected and the stream should be abandoned.
+    pub async fn send_stream_chunk(&mut self, id: u64, body: impl Serialize) -> Result<()> {
+        self.send_response(&Response::stream_chunk(id, body)).await
+    }
+
+    /// Send the final frame of a streaming response.
+    pub async fn end_stream(&mut self, id: u64) -> Result<()> {
+        self.send_response(&Response::stream_end(id)).await
+    }
+}
 
 // ============================================================================
 // Unix Implementation
@@ -43,6 +107,7 @@ use tokio::net::{UnixListener, UnixStream};
 #[cfg(unix)]
 mod unix_impl {
     use super::*;
+    use tokio::net::UnixListener;
 
     /// Unix socket listener for accepting IPC connections from CLI clients.
     ///
@@ -51,11 +116,11 @@ mod unix_impl {
     /// send and receive messages.
     pub struct IpcListener {
         listener: UnixListener,
-        socket_path: PathBuf,
+        endpoint: IpcEndpoint,
     }
 
     impl IpcListener {
-        /// Bind to a Unix domain socket at the given path.
+        /// Bind to the Unix domain socket of the given endpoint.
         ///
         /// This will:
         /// 1. Create the parent directory if it doesn't exist
@@ -63,6 +128,9 @@ mod unix_impl {
         /// 3. Bind to the socket
         /// 4. Set socket permissions to 0600 (owner only)
         ///
+        /// Callers must make sure no other daemon is serving the socket, since
+        /// an existing socket file is replaced.
+        ///
         /// # Errors
         ///
         /// Returns an error if:
@@ -70,8 +138,8 @@ mod unix_impl {
         /// - The existing socket file cannot be removed
         /// - The socket cannot be bound
         /// - Permissions cannot be set
-        pub async fn bind(socket_path: impl AsRef<Path>) -> Result<Self> {
-            let socket_path = socket_path.as_ref().to_path_buf();
+        pub async fn bind(endpoint: &IpcEndpoint) -> Result<Self> {
+            let socket_path = endpoint.path();
 
             // Ensure parent directory exists
             if let Some(parent) = socket_path.parent() {
@@ -80,20 +148,20 @@ mod unix_impl {
 
             // Remove existing socket file if present (stale from previous run)
             if socket_path.exists() {
-                std::fs::remove_file(&socket_path)?;
+                std::fs::remove_file(socket_path)?;
             }
 
-            let listener = UnixListener::bind(&socket_path)?;
+            let listener = UnixListener::bind(socket_path)?;
 
             // Set socket permissions to 0600 for security (owner only)
             {
                 use std::os::unix::fs::PermissionsExt;
-                std::fs::set_permissions(&socket_path, std::fs::Permissions::from_mode(0o600))?;
+                std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))?;
             }
 
             Ok(Self {
                 listener,
-                socket_path,
+                endpoint: endpoint.clone(),
             })
         }
 
@@ -104,14 +172,14 @@ mod unix_impl {
         /// # Errors
         ///
         /// Returns an error if accepting the connection fails.
-        pub async fn accept(&self) -> Result<IpcConnection> {
+        pub async fn accept(&mut self) -> Result<IpcConnection> {
             let (stream, _addr) = self.listener.accept().await?;
             Ok(IpcConnection::new(stream))
         }
 
-        /// Get the path to the socket file.
-        pub fn socket_path(&self) -> &Path {
-            &self.socket_path
+        /// Get the endpoint this listener is bound to.
+        pub fn endpoint(&self) -> &IpcEndpoint {
+            &self.endpoint
         }
     }
 
@@ -119,65 +187,7 @@ mod unix_impl {
         fn drop(&mut self) {
             // Clean up socket file on shutdown
             // Ignore errors since we're in drop
-            let _ = std::fs::remove_file(&self.socket_path);
-        }
-    }
-
-    /// A connection to a CLI client over the Unix socket.
-    ///
-    /// Each connection represents a single CLI invocation and supports
-    /// request/response communication using the IPC protocol.
-    pub struct IpcConnection {
-        stream: UnixStream,
-    }
-
-    impl IpcConnection {
-        /// Create a new connection from a Unix stream.
-        pub fn new(stream: UnixStream) -> Self {
-            Self { stream }
-        }
-
-        /// Receive a request from the client.
-        ///
-        /// Reads a length-delimited JSON frame from the socket and deserializes
-        /// it as a Request.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - Reading from the socket fails
-        /// - The frame cannot be deserialized as a Request
-        pub async fn recv_request(&mut self) -> Result<Request> {
-            let request = read_request(&mut self.stream).await?;
-            Ok(request)
-        }
-
-        /// Send a response to the client.
-        ///
-        /// Serializes the response as JSON and writes it as a length-delimited
-        /// frame to the socket.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - The response cannot be serialized
-        /// - Writing to the socket fails
-        pub async fn send_response(&mut self, response: &Response) -> Result<()> {
-            write_response(&mut self.stream, response).await?;
-            Ok(())
-        }
-
-        /// Get a reference to the underlying Unix stream.
-        ///
-        /// This is useful for advanced operations like setting socket options
-        /// or implementing custom protocols.
-        pub fn stream(&self) -> &UnixStream {
-            &self.stream
-        }
-
-        /// Get a mutable reference to the underlying Unix stream.
-        pub fn stream_mut(&mut self) -> &mut UnixStream {
-            &mut self.stream
+            let _ = std::fs::remove_file(self.endpoint.path());
         }
     }
 }
@@ -201,31 +211,34 @@ mod windows_impl {
     /// Each connection is represented by an `IpcConnection` that can be used to
     /// send and receive messages.
     pub struct IpcListener {
-        pipe_name: String,
+        endpoint: IpcEndpoint,
         /// The current pipe server instance waiting for a connection
         server: NamedPipeServer,
     }
 
     impl IpcListener {
-        /// Bind to a named pipe with the given name.
+        /// Bind to the named pipe of the given endpoint.
         ///
-        /// The pipe name should be in the format `\\.\pipe\{name}`.
+        /// The pipe name should be in the format `\\.\pipe\{name}`. Remote
+        /// clients are rejected so the pipe is only reachable locally.
         ///
         /// # Errors
         ///
         /// Returns an error if:
         /// - The pipe cannot be created
         /// - Another process already owns a pipe with this name
-        pub async fn bind(pipe_name: impl Into<String>) -> Result<Self> {
-            let pipe_name = pipe_name.into();
-
+        pub async fn bind(endpoint: &IpcEndpoint) -> Result<Self> {
             // Create the first pipe instance
             let server = ServerOptions::new()
                 .first_pipe_instance(true)
-                .create(&pipe_name)
+                .reject_remote_clients(true)
+                .create(endpoint.name())
                 .map_err(|e| io::Error::new(io::ErrorKind::AddrInUse, e))?;
 
-            Ok(Self { pipe_name, server })
+            Ok(Self {
+                endpoint: endpoint.clone(),
+                server,
+            })
         }
 
         /// Accept a new incoming connection.
@@ -242,74 +255,17 @@ mod windows_impl {
             self.server.connect().await?;
 
             // Take the connected pipe and create a new one for the next client
-            let connected_pipe = std::mem::replace(
-                &mut self.server,
-                ServerOptions::new()
-                    .create(&self.pipe_name)
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?,
-            );
+            let next = ServerOptions::new()
+                .reject_remote_clients(true)
+                .create(self.endpoint.name())?;
+            let connected_pipe = std::mem::replace(&mut self.server, next);
 
             Ok(IpcConnection::new(connected_pipe))
         }
 
-        /// Get the pipe name.
-        pub fn pipe_name(&self) -> &str {
-            &self.pipe_name
-        }
-    }
-
-    /// A connection to a CLI client over a Windows named pipe.
-    ///
-    /// Each connection represents a single CLI invocation and supports
-    /// request/response communication using the IPC protocol.
-    pub struct IpcConnection {
-        pipe: NamedPipeServer,
-    }
-
-    impl IpcConnection {
-        /// Create a new connection from a named pipe server.
-        pub fn new(pipe: NamedPipeServer) -> Self {
-            Self { pipe }
-        }
-
-        /// Receive a request from the client.
-        ///
-        /// Reads a length-delimited JSON frame from the pipe and deserializes
-        /// it as a Request.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - Reading from the pipe fails
-        /// - The frame cannot be deserialized as a Request
-        pub async fn recv_request(&mut self) -> Result<Request> {
-            let request = read_request(&mut self.pipe).await?;
-            Ok(request)
-        }
-
-        /// Send a response to the client.
-        ///
-        /// Serializes the response as JSON and writes it as a length-delimited
-        /// frame to the pipe.
-        ///
-        /// # Errors
-        ///
-        /// Returns an error if:
-        /// - The response cannot be serialized
-        /// - Writing to the pipe fails
-        pub async fn send_response(&mut self, response: &Response) -> Result<()> {
-            write_response(&mut self.pipe, response).await?;
-            Ok(())
-        }
-
-        /// Get a reference to the underlying named pipe.
-        pub fn pipe(&self) -> &NamedPipeServer {
-            &self.pipe
-        }
-
-        /// Get a mutable reference to the underlying named pipe.
-        pub fn pipe_mut(&mut self) -> &mut NamedPipeServer {
-            &mut self.pipe
+        /// Get the endpoint this listener is bound to.
+        pub fn endpoint(&self) -> &IpcEndpoint {
+            &self.endpoint
         }
     }
 }
@@ -338,10 +294,12 @@ mod tests {
     async fn test_listener_bind_creates_socket() {
         let (_dir, socket_path) = temp_socket_path();
 
-        let listener = IpcListener::bind(&socket_path).await.unwrap();
+        let listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         assert!(socket_path.exists());
-        assert_eq!(listener.socket_path(), socket_path);
+        assert_eq!(listener.endpoint().path(), socket_path);
     }
 
     #[tokio::test]
@@ -349,7 +307,9 @@ mod tests {
         let dir = TempDir::new().unwrap();
         let socket_path = dir.path().join("nested").join("dir").join("test.sock");
 
-        let _listener = IpcListener::bind(&socket_path).await.unwrap();
+        let _listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         assert!(socket_path.exists());
     }
@@ -359,7 +319,9 @@ mod tests {
         let (_dir, socket_path) = temp_socket_path();
 
         // Create first listener
-        let listener1 = IpcListener::bind(&socket_path).await.unwrap();
+        let listener1 = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
         drop(listener1); // This removes the socket
 
         // Socket should be gone
@@ -370,7 +332,9 @@ mod tests {
         assert!(socket_path.exists());
 
         // Second listener should succeed by removing the stale file
-        let _listener2 = IpcListener::bind(&socket_path).await.unwrap();
+        let _listener2 = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
         assert!(socket_path.exists());
     }
 
@@ -379,7 +343,9 @@ mod tests {
         let (_dir, socket_path) = temp_socket_path();
 
         {
-            let _listener = IpcListener::bind(&socket_path).await.unwrap();
+            let _listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+                .await
+                .unwrap();
             assert!(socket_path.exists());
         }
         // Listener dropped here
@@ -394,7 +360,9 @@ mod tests {
 
         let (_dir, socket_path) = temp_socket_path();
 
-        let _listener = IpcListener::bind(&socket_path).await.unwrap();
+        let _listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         let metadata = std::fs::metadata(&socket_path).unwrap();
         let mode = metadata.permissions().mode();
@@ -408,7 +376,9 @@ mod tests {
         let (_dir, socket_path) = temp_socket_path();
         let socket_path_clone = socket_path.clone();
 
-        let listener = IpcListener::bind(&socket_path).await.unwrap();
+        let mut listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         // Spawn a client that connects
         let client_handle =
@@ -420,7 +390,7 @@ mod tests {
             .unwrap()
             .unwrap();
 
-        assert!(conn.stream().peer_addr().is_ok());
+        drop(conn);
         client_handle.await.unwrap();
     }
 
@@ -429,7 +399,9 @@ mod tests {
         let (_dir, socket_path) = temp_socket_path();
         let socket_path_clone = socket_path.clone();
 
-        let listener = IpcListener::bind(&socket_path).await.unwrap();
+        let mut listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         // Spawn server handler
         let server_handle = tokio::spawn(async move {
@@ -469,12 +441,70 @@ mod tests {
         .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_streaming_response() {
+        let (_dir, socket_path) = temp_socket_path();
+        let socket_path_clone = socket_path.clone();
+
+        let mut listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
+
+        let server_handle = tokio::spawn(async move {
+            let mut conn = listener.accept().await.unwrap();
+            let request = conn.recv_request().await.unwrap();
+
+            conn.send_stream_chunk(request.id, vec!["first"])
+                .await
+                .unwrap();
+            conn.send_stream_chunk(request.id, vec!["second"])
+                .await
+                .unwrap();
+            conn.end_stream(request.id).await.unwrap();
+        });
+
+        let client_handle = tokio::spawn(async move {
+            let mut stream = UnixStream::connect(&socket_path_clone).await.unwrap();
+
+            let request = Request::new(7, Operation::Ping);
+            crate::daemon::protocol::write_request(&mut stream, &request)
+                .await
+                .unwrap();
+
+            // Read frames until the stream ends
+            let mut chunks = Vec::new();
+            loop {
+                let response = crate::daemon::protocol::read_response(&mut stream)
+                    .await
+                    .unwrap();
+                assert_eq!(response.id, 7);
+                if let Some(body) = response.body {
+                    chunks.push(body);
+                }
+                if !response.stream {
+                    break;
+                }
+            }
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(chunks[1][0], "second");
+        });
+
+        timeout(Duration::from_secs(5), async {
+            server_handle.await.unwrap();
+            client_handle.await.unwrap();
+        })
+        .await
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn test_multiple_requests_on_same_connection() {
         let (_dir, socket_path) = temp_socket_path();
         let socket_path_clone = socket_path.clone();
 
-        let listener = IpcListener::bind(&socket_path).await.unwrap();
+        let mut listener = IpcListener::bind(&IpcEndpoint::socket(&socket_path))
+            .await
+            .unwrap();
 
         // Spawn server handler
         let server_handle = tokio::spawn(async move {
@@ -6,19 +6,34 @@
 //! ## Components
 //!
 //! - [`protocol`]: Request/Response types and length-delimited JSON framing
-//! - [`listener`]: Unix socket listener for accepting CLI connections
+//! - [`transport`]: Platform IPC endpoints (Unix socket or named pipe) and PID liveness checks
+//! - [`listener`]: IPC listener for accepting CLI connections
 //! - [`worker_manager`]: Worker lifecycle management (start/stop/query workers)
 //! - [`client`]: DaemonClient for CLI-to-daemon communication
 //! - [`auto_start`]: Auto-start logic to ensure daemon is running
+//! - [`service_unit`]: systemd, launchd and Task Scheduler units that start the daemon on login
+//! - [`webhooks`]: Webhook delivery for workspace events
+//! - [`notifications`]: Desktop notifications for key workspace events
+//! - [`backups`]: Daily database snapshots
+//! - [`rules`]: Periodic evaluation of workspace rules
+//! - [`reminders`]: Due date reminders
 
 pub mod auto_start;
+pub mod backups;
 pub mod client;
 pub mod listener;
+pub mod notifications;
 pub mod protocol;
+pub mod reminders;
+pub mod rules;
+pub mod service_unit;
+pub mod transport;
+pub mod webhooks;
 pub mod worker_manager;
 
 pub use auto_start::ensure_daemon;
 pub use client::DaemonClient;
 pub use listener::{IpcConnection, IpcListener};
 pub use protocol::*;
+pub use transport::{IpcEndpoint, IpcStream};
 pub use worker_manager::WorkerManager;
@@ -0,0 +1,616 @@
+//! Notifications for workspace events.
+//!
+//! The dispatcher tails the event log of every workspace the daemon serves,
+//! like the webhook dispatcher, and hands matching events to [`Notifier`]s:
+//!
+//! - [`DesktopNotifier`], enabled by `[notifications]` in
+//!   `~/.granary/config.toml`. It uses the platform's own tools, so no extra
+//!   libraries are needed: `osascript` on macOS, a PowerShell toast on
+//!   Windows and `notify-send` elsewhere.
+//! - [`SlackNotifier`] and [`DiscordNotifier`], one per `[[notifiers]]`
+//!   table, posting to the channel's incoming webhook for events that match
+//!   the notifier's rules.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use sqlx::SqlitePool;
+use tokio::process::Command;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::event::Event;
+use crate::models::global_config::{
+    NotificationConfig, NotifierConfig, NotifierKind, matches_event_patterns,
+};
+use crate::models::run::Run;
+use crate::services::Workspace;
+use crate::services::filter;
+use crate::services::global_config as global_config_service;
+
+/// Interval between event log polls
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long delivering a notification may take before it is abandoned
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A notification ready to show
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+/// Delivers notifications somewhere a person will see them
+pub trait Notifier: Send + Sync {
+    /// Short description for logs
+    fn name(&self) -> String;
+
+    /// Whether this notifier wants the event. `run` is the run a
+    /// `run.*` event is about, when it could be found.
+    fn wants(&self, event: &Event, run: Option<&Run>) -> bool;
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Pops a desktop notification
+pub struct DesktopNotifier {
+    config: NotificationConfig,
+}
+
+impl DesktopNotifier {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn name(&self) -> String {
+        "Desktop".to_string()
+    }
+
+    /// Completed runs only notify when they ran for at least the configured
+    /// minimum, so quick runs do not interrupt anyone
+    fn wants(&self, event: &Event, run: Option<&Run>) -> bool {
+        if !self.config.matches(&event.event_type) {
+            return false;
+        }
+        if event.event_type == "run.completed" {
+            return run
+                .and_then(run_seconds)
+                .is_some_and(|s| s >= self.config.min_run_seconds() as i64);
+        }
+        true
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        Box::pin(send_desktop(notification))
+    }
+}
+
+/// Posts to a chat channel's incoming webhook for events matching its rules
+struct ChatNotifier {
+    client: reqwest::Client,
+    config: NotifierConfig,
+    /// Event patterns and their parsed filters
+    rules: Vec<(String, Vec<filter::Filter>)>,
+}
+
+impl ChatNotifier {
+    fn new(client: reqwest::Client, config: NotifierConfig) -> Self {
+        let rules = config
+            .effective_rules()
+            .into_iter()
+            .filter_map(|rule| match filter::parse_filters(&rule.filters) {
+                Ok(filters) => Some((rule.event, filters)),
+                Err(e) => {
+                    tracing::warn!("Ignoring notifier rule for {}: {}", rule.event, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            client,
+            config,
+            rules,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self.config.kind {
+            NotifierKind::Slack => "Slack".to_string(),
+            NotifierKind::Discord => "Discord".to_string(),
+        }
+    }
+
+    fn wants(&self, event: &Event) -> bool {
+        let payload: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or_default();
+        self.rules.iter().any(|(pattern, filters)| {
+            matches_event_patterns(std::slice::from_ref(pattern), &event.event_type)
+                && filter::matches_all(filters, &payload)
+        })
+    }
+
+    async fn post(&self, body: serde_json::Value) -> Result<()> {
+        let response = self
+            .client
+            .post(self.config.expanded_url())
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| GranaryError::Other(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(GranaryError::Other(format!(
+                "webhook responded with {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Posts to a Slack channel
+pub struct SlackNotifier(ChatNotifier);
+
+impl SlackNotifier {
+    pub fn new(client: reqwest::Client, config: NotifierConfig) -> Self {
+        Self(ChatNotifier::new(client, config))
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn wants(&self, event: &Event, _run: Option<&Run>) -> bool {
+        self.0.wants(event)
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.0.post(slack_body(notification)))
+    }
+}
+
+/// Posts to a Discord channel
+pub struct DiscordNotifier(ChatNotifier);
+
+impl DiscordNotifier {
+    pub fn new(client: reqwest::Client, config: NotifierConfig) -> Self {
+        Self(ChatNotifier::new(client, config))
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn wants(&self, event: &Event, _run: Option<&Run>) -> bool {
+        self.0.wants(event)
+    }
+
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        Box::pin(self.0.post(discord_body(notification)))
+    }
+}
+
+/// Slack incoming webhook message
+pub fn slack_body(notification: &Notification) -> serde_json::Value {
+    serde_json::json!({
+        "text": format!("*{}*\n{}", notification.title, notification.body),
+    })
+}
+
+/// Discord webhook message
+pub fn discord_body(notification: &Notification) -> serde_json::Value {
+    serde_json::json!({
+        "username": "granary",
+        "content": format!("**{}**\n{}", notification.title, notification.body),
+    })
+}
+
+/// Tails workspace event logs and sends notifications.
+pub struct NotificationDispatcher {
+    global_pool: SqlitePool,
+    client: reqwest::Client,
+    /// Workspace path to its pool and the last event seen
+    workspaces: HashMap<String, (SqlitePool, i64)>,
+}
+
+impl NotificationDispatcher {
+    pub fn new(global_pool: SqlitePool) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .user_agent(concat!("granary/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            global_pool,
+            client,
+            workspaces: HashMap::new(),
+        }
+    }
+
+    /// The notifiers enabled in the global config
+    fn notifiers(&self) -> Vec<Arc<dyn Notifier>> {
+        let config = match global_config_service::load() {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!("Failed to load global config for notifications: {}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut notifiers: Vec<Arc<dyn Notifier>> = Vec::new();
+        if config.notifications.enabled {
+            notifiers.push(Arc::new(DesktopNotifier::new(config.notifications)));
+        }
+        for notifier in config.notifiers {
+            let client = self.client.clone();
+            notifiers.push(match notifier.kind {
+                NotifierKind::Slack => Arc::new(SlackNotifier::new(client, notifier)),
+                NotifierKind::Discord => Arc::new(DiscordNotifier::new(client, notifier)),
+            });
+        }
+        notifiers
+    }
+
+    /// Poll for events until a shutdown signal is received
+    pub async fn run(mut self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        tracing::warn!("Notification dispatch failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send notifications for events recorded since the previous tick
+    pub async fn tick(&mut self) -> Result<()> {
+        let notifiers = self.notifiers();
+        // Forget cursors while disabled so enabling does not replay old events
+        if notifiers.is_empty() {
+            self.workspaces.clear();
+            return Ok(());
+        }
+
+        let paths: BTreeSet<String> = db::workers::list(&self.global_pool)
+            .await?
+            .into_iter()
+            .map(|w| w.instance_path)
+            .collect();
+        self.workspaces.retain(|path, _| paths.contains(path));
+
+        for path in paths {
+            for event in self.poll_workspace(&path).await? {
+                let run = match event.event_type.as_str() {
+                    "run.completed" | "run.failed" => {
+                        db::runs::get(&self.global_pool, &event.entity_id).await?
+                    }
+                    _ => None,
+                };
+                let notification = notification_for(&event, &path, run.as_ref());
+                for notifier in &notifiers {
+                    if !notifier.wants(&event, run.as_ref()) {
+                        continue;
+                    }
+                    let notifier = Arc::clone(notifier);
+                    let notification = notification.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = notifier.notify(&notification).await {
+                            tracing::warn!("{} notification failed: {}", notifier.name(), e);
+                        }
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// New events in a workspace. A workspace is picked up from its latest
+    /// event, so nothing that happened before is shown.
+    async fn poll_workspace(&mut self, path: &str) -> Result<Vec<Event>> {
+        let Some((pool, last_event_id)) = self.workspaces.get_mut(path) else {
+            let Ok(workspace) = Workspace::open(path) else {
+                return Ok(Vec::new());
+            };
+            let pool = workspace.pool().await?;
+            let last_event_id = db::events::latest_id(&pool).await?;
+            self.workspaces
+                .insert(path.to_string(), (pool, last_event_id));
+            return Ok(Vec::new());
+        };
+
+        let events = db::events::list_since_id(pool, *last_event_id).await?;
+        if let Some(last) = events.last() {
+            *last_event_id = last.id;
+        }
+        Ok(events)
+    }
+}
+
+/// Describe an event as a notification
+pub fn notification_for(event: &Event, workspace: &str, run: Option<&Run>) -> Notification {
+    let payload: serde_json::Value = serde_json::from_str(&event.payload).unwrap_or_default();
+    let workspace = Path::new(workspace)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| workspace.to_string());
+    let trigger = payload["trigger_entity_id"]
+        .as_str()
+        .unwrap_or(&event.entity_id);
+
+    let (title, body) = match event.event_type.as_str() {
+        "run.failed" => {
+            let mut reason = match payload["error"].as_str() {
+                Some(error) => error.to_string(),
+                None => format!("exit code {}", payload["exit_code"]),
+            };
+            if let Some(attempt) = payload["attempt"].as_i64().filter(|a| *a > 1) {
+                reason.push_str(&format!(" after {} attempts", attempt));
+            }
+            ("Run failed".to_string(), format!("{}: {}", trigger, reason))
+        }
+        "run.completed" => {
+            let body = match run.and_then(run_seconds) {
+                Some(seconds) => format!("{} finished after {}", trigger, format_duration(seconds)),
+                None => format!("{} finished", trigger),
+            };
+            ("Run completed".to_string(), body)
+        }
+        "comment.created" if payload["kind"] == "handoff" => {
+            let parent = payload["parent_id"].as_str().unwrap_or(&event.entity_id);
+            let body = match &event.actor {
+                Some(actor) => format!("{} handed off {}", actor, parent),
+                None => format!("Handoff on {}", parent),
+            };
+            ("Handoff".to_string(), body)
+        }
+        "task.blocked" => {
+            let body = match payload["reason"].as_str() {
+                Some(reason) => format!("{}: {}", event.entity_id, reason),
+                None => event.entity_id.clone(),
+            };
+            ("Task blocked".to_string(), body)
+        }
+        "task.due_soon" | "task.overdue" => {
+            let title = if event.event_type == "task.overdue" {
+                "Task overdue"
+            } else {
+                "Task due soon"
+            };
+            let body = match payload["due_at"].as_str() {
+                Some(due) => format!(
+                    "{} is due {}",
+                    event.entity_id,
+                    crate::services::datetime::format_local_datetime(due)
+                ),
+                None => event.entity_id.clone(),
+            };
+            (title.to_string(), body)
+        }
+        other => (other.to_string(), event.entity_id.clone()),
+    };
+
+    Notification {
+        title: format!("granary: {}", title),
+        body: format!("{} ({})", body, workspace),
+    }
+}
+
+/// How long a run took, from its start to its completion
+fn run_seconds(run: &Run) -> Option<i64> {
+    let parse = |s: &Option<String>| {
+        s.as_deref()
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+    };
+    let (started, completed) = (parse(&run.started_at)?, parse(&run.completed_at)?);
+    Some((completed - started).num_seconds())
+}
+
+fn format_duration(seconds: i64) -> String {
+    match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+/// Show a notification with the platform's notification tool
+async fn send_desktop(notification: &Notification) -> Result<()> {
+    let mut command = notification_command(notification);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(SEND_TIMEOUT, command.output())
+        .await
+        .map_err(|_| GranaryError::Other("notification command timed out".to_string()))??;
+    if !output.status.success() {
+        return Err(GranaryError::Other(format!(
+            "notification command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn notification_command(notification: &Notification) -> Command {
+    let mut command = Command::new("osascript");
+    command.arg("-e").arg(format!(
+        "display notification {} with title {}",
+        applescript_string(&notification.body),
+        applescript_string(&notification.title)
+    ));
+    command
+}
+
+#[cfg(windows)]
+fn notification_command(notification: &Notification) -> Command {
+    let script = format!(
+        "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null; \
+         $template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+         $text = $template.GetElementsByTagName('text'); \
+         $text.Item(0).AppendChild($template.CreateTextNode({})) | Out-Null; \
+         $text.Item(1).AppendChild($template.CreateTextNode({})) | Out-Null; \
+         [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('granary').Show([Windows.UI.Notifications.ToastNotification]::new($template))",
+        powershell_string(&notification.title),
+        powershell_string(&notification.body)
+    );
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    command
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn notification_command(notification: &Notification) -> Command {
+    let mut command = Command::new("notify-send");
+    command
+        .arg("--app-name=granary")
+        .arg(&notification.title)
+        .arg(&notification.body);
+    command
+}
+
+/// Quote a string as an AppleScript literal
+#[cfg_attr(not(any(target_os = "macos", test)), allow(dead_code))]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quote a string as a PowerShell single-quoted literal
+#[cfg_attr(not(any(windows, test)), allow(dead_code))]
+fn powershell_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(event_type: &str, entity_id: &str, payload: serde_json::Value) -> Event {
+        Event {
+            id: 1,
+            event_type: event_type.to_string(),
+            entity_type: "run".to_string(),
+            entity_id: entity_id.to_string(),
+            actor: None,
+            session_id: None,
+            payload: payload.to_string(),
+            created_at: "2026-01-15T10:00:00Z".to_string(),
+        }
+    }
+
+    fn enabled() -> NotificationConfig {
+        NotificationConfig {
+            enabled: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_failed_notification() {
+        let event = event(
+            "run.failed",
+            "run-1",
+            serde_json::json!({ "trigger_entity_id": "proj-task-1", "exit_code": 2, "error": null }),
+        );
+        let notification = notification_for(&event, "/repos/app", None);
+        assert_eq!(notification.title, "granary: Run failed");
+        assert_eq!(notification.body, "proj-task-1: exit code 2 (app)");
+    }
+
+    #[test]
+    fn test_chat_notifier_rules() {
+        let notifier = SlackNotifier::new(
+            reqwest::Client::new(),
+            NotifierConfig {
+                kind: NotifierKind::Slack,
+                url: "https://hooks.slack.com/services/x".to_string(),
+                rules: Vec::new(),
+            },
+        );
+        let mut handoff = event(
+            "comment.created",
+            "proj-task-1-comment-1",
+            serde_json::json!({ "kind": "handoff", "parent_id": "proj-task-1" }),
+        );
+        handoff.actor = Some("planner".to_string());
+        let note = event(
+            "comment.created",
+            "proj-task-1-comment-2",
+            serde_json::json!({ "kind": "note", "parent_id": "proj-task-1" }),
+        );
+        let retried = event("run.failed", "run-1", serde_json::json!({ "attempt": 3 }));
+        let first = event("run.failed", "run-2", serde_json::json!({ "attempt": 1 }));
+
+        assert!(notifier.wants(&handoff, None));
+        assert!(!notifier.wants(&note, None));
+        assert!(notifier.wants(&retried, None));
+        assert!(!notifier.wants(&first, None));
+
+        let notification = notification_for(&handoff, "/repos/app", None);
+        assert_eq!(notification.body, "planner handed off proj-task-1 (app)");
+        assert_eq!(
+            slack_body(&notification)["text"],
+            "*granary: Handoff*\nplanner handed off proj-task-1 (app)"
+        );
+        assert_eq!(
+            discord_body(&notification)["content"],
+            "**granary: Handoff**\nplanner handed off proj-task-1 (app)"
+        );
+    }
+
+    #[test]
+    fn test_completed_runs_notify_only_when_long() {
+        let event = event(
+            "run.completed",
+            "run-1",
+            serde_json::json!({ "trigger_entity_id": "proj-task-1" }),
+        );
+        let mut run: Run = serde_json::from_value(serde_json::json!({
+            "id": "run-1", "worker_id": "w", "event_id": 1, "event_type": "task.unblocked",
+            "entity_id": "proj-task-1", "command": "claude", "args": "[]", "status": "completed",
+            "attempt": 1, "max_attempts": 3,
+            "started_at": "2026-01-15T10:00:00Z", "completed_at": "2026-01-15T10:00:30Z",
+            "created_at": "2026-01-15T10:00:00Z", "updated_at": "2026-01-15T10:00:30Z"
+        }))
+        .unwrap();
+        let desktop = DesktopNotifier::new(enabled());
+        assert!(!desktop.wants(&event, Some(&run)));
+
+        run.completed_at = Some("2026-01-15T10:05:30Z".to_string());
+        assert!(desktop.wants(&event, Some(&run)));
+        let notification = notification_for(&event, "/repos/app", Some(&run));
+        assert_eq!(notification.body, "proj-task-1 finished after 5m 30s (app)");
+    }
+
+    #[test]
+    fn test_quoting() {
+        assert_eq!(
+            applescript_string(r#"say "hi" \o/"#),
+            r#""say \"hi\" \\o/""#
+        );
+        assert_eq!(powershell_string("it's"), "'it''s'");
+    }
+}
@@ -10,12 +10,22 @@
 //! - N bytes: JSON-encoded message
 //!
 //! This allows for efficient parsing and streaming of messages.
+//!
+//! ## Streaming Responses
+//!
+//! Most operations produce exactly one response. Streaming operations (log
+//! following) produce a sequence of responses sharing the request ID: every
+//! frame except the last has `stream: true`, and the final frame has
+//! `stream: false`. The stream also ends if either side closes the connection.
 
 use serde::{Deserialize, Serialize};
 use std::io;
 use std::path::PathBuf;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
+use crate::models::LogFilter;
+use crate::models::worker::{PauseMode, RetryPolicy};
+
 /// Maximum message size (16 MB) to prevent memory exhaustion attacks
 pub const MAX_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
 
@@ -48,6 +58,9 @@ pub struct Response {
     /// Error message if ok is false
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// True if more responses will follow for this request (streaming mode)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stream: bool,
 }
 
 impl Response {
@@ -58,6 +71,7 @@ impl Response {
             ok: true,
             body: Some(serde_json::to_value(body).unwrap_or(serde_json::Value::Null)),
             error: None,
+            stream: false,
         }
     }
 
@@ -68,6 +82,7 @@ impl Response {
             ok: true,
             body: None,
             error: None,
+            stream: false,
         }
     }
 
@@ -78,8 +93,22 @@ impl Response {
             ok: false,
             body: None,
             error: Some(error.into()),
+            stream: false,
         }
     }
+
+    /// Create an intermediate streaming response; more frames will follow
+    pub fn stream_chunk(id: u64, body: impl Serialize) -> Self {
+        Self {
+            stream: true,
+            ..Self::ok(id, body)
+        }
+    }
+
+    /// Create the final response that ends a stream
+    pub fn stream_end(id: u64) -> Self {
+        Self::ok_empty(id)
+    }
 }
 
 /// Authentication request sent as the first message on connection.
@@ -92,6 +121,7 @@ pub struct AuthRequest {
 /// Operations supported by the daemon
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
+#[allow(clippy::large_enum_variant)]
 pub enum Operation {
     // Authentication
     /// Authenticate the connection (must be first message)
@@ -112,6 +142,10 @@ pub enum Operation {
         /// Whether to also stop running runs
         stop_runs: bool,
     },
+    /// Stop starting runs for new events, without stopping the worker
+    PauseWorker { worker_id: String },
+    /// Resume a paused worker
+    ResumeWorker { worker_id: String },
     /// Get worker details
     GetWorker { worker_id: String },
     /// List all workers
@@ -122,6 +156,9 @@ pub enum Operation {
     /// Remove stopped workers
     PruneWorkers,
     /// Get worker logs
+    ///
+    /// With `follow`, the daemon streams `LogsResponse` chunks until the
+    /// worker stops or the client disconnects.
     WorkerLogs {
         worker_id: String,
         /// Follow log output
@@ -148,13 +185,21 @@ pub enum Operation {
     PauseRun { run_id: String },
     /// Resume a paused run
     ResumeRun { run_id: String },
+    /// Requeue a failed, cancelled, killed or timed-out run for another attempt
+    RetryRun { run_id: String },
     /// Get run logs
+    ///
+    /// With `follow`, the daemon streams `LogsResponse` chunks until the
+    /// run finishes or the client disconnects.
     RunLogs {
         run_id: String,
         /// Follow log output
         follow: bool,
         /// Number of lines to show
         lines: i32,
+        /// Stream and time filter, for runs with JSON-lines logs
+        #[serde(default)]
+        filter: LogFilter,
     },
 
     /// Get logs with offset-based pagination (for streaming support)
@@ -181,6 +226,9 @@ pub struct LogsRequest {
     pub since_line: u64,
     /// Maximum number of lines to return
     pub limit: u64,
+    /// Stream and time filter, for runs with JSON-lines logs
+    #[serde(default)]
+    pub filter: LogFilter,
 }
 
 /// Response payload for log requests with streaming support
@@ -218,6 +266,25 @@ pub struct StartWorkerRequest {
     pub attach: bool,
     /// Cooldown in seconds for polled events (default: 300 = 5 minutes)
     pub poll_cooldown_secs: Option<i64>,
+    /// Optional cron expression to fire runs on a timer
+    pub schedule: Option<String>,
+    /// Retry policy for failed runs (None uses the default policy)
+    pub retry_policy: Option<RetryPolicy>,
+    /// Wall-clock limit per run in seconds (None means no timeout)
+    pub timeout_secs: Option<i64>,
+    /// Quiet period in seconds before a burst of events starts one run
+    pub debounce_secs: Option<i64>,
+    /// Minimum interval in seconds between runs started by events
+    pub throttle_secs: Option<i64>,
+    /// Send the triggering event and a context pack to runners on stdin
+    #[serde(default)]
+    pub stdin: bool,
+    /// Receive events from every registered workspace
+    #[serde(default)]
+    pub global: bool,
+    /// What to do with events while the worker is paused
+    #[serde(default)]
+    pub on_pause: PauseMode,
 }
 
 impl Default for StartWorkerRequest {
@@ -232,6 +299,14 @@ impl Default for StartWorkerRequest {
             instance_path: String::new(),
             attach: false,
             poll_cooldown_secs: None,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         }
     }
 }
@@ -332,6 +407,7 @@ pub async fn read_response<R: AsyncReadExt + Unpin>(reader: &mut R) -> io::Resul
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::LogStream;
     use std::io::Cursor;
 
     #[test]
@@ -378,6 +454,23 @@ mod tests {
         assert!(deserialized.error.is_none());
     }
 
+    #[test]
+    fn test_response_stream_serialization() {
+        let chunk = Response::stream_chunk(4, vec!["line 1", "line 2"]);
+        let json = serde_json::to_string(&chunk).unwrap();
+        assert!(json.contains(r#""stream":true"#));
+        let deserialized: Response = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.ok);
+        assert!(deserialized.stream);
+
+        // The final frame omits the stream flag entirely
+        let end = Response::stream_end(4);
+        let json = serde_json::to_string(&end).unwrap();
+        assert!(!json.contains("stream"));
+        let deserialized: Response = serde_json::from_str(&json).unwrap();
+        assert!(!deserialized.stream);
+    }
+
     #[test]
     fn test_operation_tagged_serialization() {
         // Test that operations serialize with type tags
@@ -478,6 +571,12 @@ mod tests {
                 worker_id: "w1".to_string(),
                 stop_runs: false,
             },
+            Operation::PauseWorker {
+                worker_id: "w1".to_string(),
+            },
+            Operation::ResumeWorker {
+                worker_id: "w1".to_string(),
+            },
             Operation::GetWorker {
                 worker_id: "w1".to_string(),
             },
@@ -505,16 +604,25 @@ mod tests {
             Operation::ResumeRun {
                 run_id: "r1".to_string(),
             },
+            Operation::RetryRun {
+                run_id: "r1".to_string(),
+            },
             Operation::RunLogs {
                 run_id: "r1".to_string(),
                 follow: false,
                 lines: 50,
+                filter: LogFilter {
+                    stream: Some(LogStream::Stderr),
+                    since: Some("2026-01-01T00:00:00Z".to_string()),
+                    until: None,
+                },
             },
             Operation::GetLogs(LogsRequest {
                 target_id: "w1".to_string(),
                 target_type: LogTarget::Worker,
                 since_line: 0,
                 limit: 100,
+                filter: LogFilter::default(),
             }),
         ];
 
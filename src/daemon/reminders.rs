@@ -0,0 +1,77 @@
+//! Periodic due date reminders.
+//!
+//! The scheduler checks the open tasks of every workspace the daemon serves
+//! (any workspace with a worker record) and emits `task.due_soon` and
+//! `task.overdue` events, which workers, webhooks and notifications can
+//! react to.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::error::Result;
+use crate::services::{Workspace, reminder_service};
+
+/// Interval between reminder checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Sends due date reminders on an interval.
+pub struct ReminderScheduler {
+    global_pool: SqlitePool,
+}
+
+impl ReminderScheduler {
+    pub fn new(global_pool: SqlitePool) -> Self {
+        Self { global_pool }
+    }
+
+    /// Send reminders until a shutdown signal is received
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        tracing::warn!("Due date reminders failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Send the reminders of every served workspace
+    pub async fn tick(&self) -> Result<()> {
+        let paths: BTreeSet<String> = db::workers::list(&self.global_pool)
+            .await?
+            .into_iter()
+            .map(|w| w.instance_path)
+            .collect();
+
+        for path in paths {
+            // Workspaces that no longer exist are skipped
+            let Ok(workspace) = Workspace::open(&path) else {
+                continue;
+            };
+            if let Err(e) = run_workspace(&workspace).await {
+                tracing::warn!("Reminders in {} failed: {}", path, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn run_workspace(workspace: &Workspace) -> Result<()> {
+    let pool = workspace.pool().await?;
+    for (event_type, task) in reminder_service::run_reminders(&pool).await? {
+        tracing::info!("Sent {} for {}", event_type.as_str(), task.id);
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+//! Periodic rule evaluation.
+//!
+//! The scheduler runs the rules (`rules.<name>.*` config keys) of every
+//! workspace the daemon serves (any workspace with a worker record). Each
+//! new match emits a `rule.triggered` event, which workers, webhooks and
+//! notifications can react to, and rules with `apply` set change the task.
+
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::Actor;
+use crate::services::{self, Workspace, rule_service};
+
+/// Interval between rule evaluations
+const CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Who the changes rules make are attributed to
+const RULES_ACTOR: &str = "granary rules";
+
+/// Evaluates workspace rules on an interval.
+pub struct RuleScheduler {
+    global_pool: SqlitePool,
+}
+
+impl RuleScheduler {
+    pub fn new(global_pool: SqlitePool) -> Self {
+        Self { global_pool }
+    }
+
+    /// Evaluate rules until a shutdown signal is received
+    pub async fn run(self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        tracing::warn!("Rule evaluation failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run the rules of every served workspace
+    pub async fn tick(&self) -> Result<()> {
+        let paths: BTreeSet<String> = db::workers::list(&self.global_pool)
+            .await?
+            .into_iter()
+            .map(|w| w.instance_path)
+            .collect();
+
+        for path in paths {
+            // Workspaces that no longer exist are skipped
+            let Ok(workspace) = Workspace::open(&path) else {
+                continue;
+            };
+            if let Err(e) = run_workspace(&workspace).await {
+                tracing::warn!("Rules in {} failed: {}", path, e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run a workspace's rules, recording the changes rules with `apply` make as
+/// one operation for `granary audit` and `granary undo`
+async fn run_workspace(workspace: &Workspace) -> Result<()> {
+    let actor = Actor {
+        name: Some(RULES_ACTOR.to_string()),
+        session_id: None,
+    };
+    let fired = services::record_operation(workspace, "rules", actor, |pool| async move {
+        rule_service::run_rules(&pool).await
+    })
+    .await?;
+    for fired in fired {
+        tracing::info!(
+            "Rule {} matched {} ({})",
+            fired.rule,
+            fired.task_id,
+            fired.action
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+//! Service-manager units for the granary daemon.
+//!
+//! By default the CLI starts granaryd lazily, the first time a command needs
+//! it. `granary daemon install` instead registers granaryd with the
+//! platform's service manager, so it starts on login and is restarted if it
+//! crashes:
+//!
+//! - Linux: a systemd user unit, `~/.config/systemd/user/granaryd.service`
+//! - macOS: a launchd agent, `~/Library/LaunchAgents/dev.granary.granaryd.plist`
+//! - Windows: a Task Scheduler task named `granaryd` that runs at logon
+//!
+//! granaryd is not a Windows service (it does not talk to the service
+//! control manager), so a logon task is used there instead.
+//!
+//! The unit runs granaryd in the foreground with the `PATH` of the shell that
+//! installed it, so runners resolve the same commands they do when the CLI
+//! starts the daemon. `granary daemon stop` exits cleanly, which the units
+//! treat as intentional: they restart the daemon only after a failure.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{GranaryError, Result};
+use crate::services::global_config as global_config_service;
+
+/// systemd unit name
+const SYSTEMD_UNIT: &str = "granaryd.service";
+
+/// launchd agent label
+const LAUNCHD_LABEL: &str = "dev.granary.granaryd";
+
+/// Task Scheduler task name
+const TASK_NAME: &str = "granaryd";
+
+/// A platform service manager
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceManager {
+    Systemd,
+    Launchd,
+    TaskScheduler,
+}
+
+impl ServiceManager {
+    /// The service manager of the current platform
+    pub fn current() -> Result<Self> {
+        if cfg!(target_os = "macos") {
+            Ok(ServiceManager::Launchd)
+        } else if cfg!(windows) {
+            Ok(ServiceManager::TaskScheduler)
+        } else if cfg!(target_os = "linux") {
+            Ok(ServiceManager::Systemd)
+        } else {
+            Err(GranaryError::DaemonError(
+                "No supported service manager on this platform".to_string(),
+            ))
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceManager::Systemd => "systemd",
+            ServiceManager::Launchd => "launchd",
+            ServiceManager::TaskScheduler => "Task Scheduler",
+        }
+    }
+
+    /// Where the unit is written
+    pub fn unit_path(&self) -> Result<PathBuf> {
+        let home = || {
+            dirs::home_dir().ok_or_else(|| {
+                GranaryError::GlobalConfig("Could not determine home directory".into())
+            })
+        };
+        Ok(match self {
+            ServiceManager::Systemd => match dirs::config_dir() {
+                Some(dir) => dir,
+                None => home()?.join(".config"),
+            }
+            .join("systemd")
+            .join("user")
+            .join(SYSTEMD_UNIT),
+            ServiceManager::Launchd => home()?
+                .join("Library")
+                .join("LaunchAgents")
+                .join(format!("{}.plist", LAUNCHD_LABEL)),
+            ServiceManager::TaskScheduler => {
+                global_config_service::daemon_dir()?.join("granaryd-task.xml")
+            }
+        })
+    }
+}
+
+/// A rendered unit for the daemon
+#[derive(Debug)]
+pub struct ServiceUnit {
+    pub manager: ServiceManager,
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+impl ServiceUnit {
+    /// Unit that runs `daemon_path` with the current `PATH`
+    pub fn new(manager: ServiceManager, daemon_path: &Path) -> Result<Self> {
+        let path_env = std::env::var("PATH").ok();
+        let contents = match manager {
+            ServiceManager::Systemd => render_systemd(daemon_path, path_env.as_deref()),
+            ServiceManager::Launchd => render_launchd(
+                daemon_path,
+                path_env.as_deref(),
+                &global_config_service::daemon_dir()?.join("service.log"),
+            ),
+            ServiceManager::TaskScheduler => render_task(daemon_path, current_windows_user()),
+        };
+        Ok(Self {
+            manager,
+            path: manager.unit_path()?,
+            contents,
+        })
+    }
+}
+
+/// Write a unit, enable it and start the daemon through it
+pub fn install(unit: &ServiceUnit) -> Result<()> {
+    if let Some(parent) = unit.path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let path = unit.path.to_string_lossy();
+    match unit.manager {
+        ServiceManager::Systemd => {
+            std::fs::write(&unit.path, &unit.contents)?;
+            run("systemctl", &["--user", "daemon-reload"])?;
+            run("systemctl", &["--user", "enable", "--now", SYSTEMD_UNIT])
+        }
+        ServiceManager::Launchd => {
+            // Reloading an agent that is already loaded fails, so unload first
+            if unit.path.exists() {
+                let _ = run("launchctl", &["unload", "-w", &path]);
+            }
+            std::fs::write(&unit.path, &unit.contents)?;
+            run("launchctl", &["load", "-w", &path])
+        }
+        ServiceManager::TaskScheduler => {
+            // schtasks reads task definitions as UTF-16
+            let bytes: Vec<u8> = std::iter::once('\u{feff}')
+                .chain(unit.contents.chars())
+                .collect::<String>()
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect();
+            std::fs::write(&unit.path, bytes)?;
+            run(
+                "schtasks",
+                &["/Create", "/TN", TASK_NAME, "/XML", &path, "/F"],
+            )?;
+            run("schtasks", &["/Run", "/TN", TASK_NAME])
+        }
+    }
+}
+
+/// Stop and disable the installed unit and remove it.
+///
+/// Returns the removed unit's path, or `None` when no unit was installed.
+pub fn uninstall(manager: ServiceManager) -> Result<Option<PathBuf>> {
+    let Some(path) = installed_unit(manager)? else {
+        return Ok(None);
+    };
+    let path_str = path.to_string_lossy();
+    match manager {
+        ServiceManager::Systemd => {
+            run("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT])?;
+            std::fs::remove_file(&path)?;
+            run("systemctl", &["--user", "daemon-reload"])?;
+        }
+        ServiceManager::Launchd => {
+            run("launchctl", &["unload", "-w", &path_str])?;
+            std::fs::remove_file(&path)?;
+        }
+        ServiceManager::TaskScheduler => {
+            // End the running instance before deleting the task; it may not be running
+            let _ = run("schtasks", &["/End", "/TN", TASK_NAME]);
+            run("schtasks", &["/Delete", "/TN", TASK_NAME, "/F"])?;
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(Some(path))
+}
+
+/// Path of the installed unit, if there is one
+pub fn installed_unit(manager: ServiceManager) -> Result<Option<PathBuf>> {
+    let path = manager.unit_path()?;
+    Ok(path.exists().then_some(path))
+}
+
+/// Run a service-manager command, failing with its stderr
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| GranaryError::DaemonError(format!("Failed to run {}: {}", program, e)))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout.trim()
+    } else {
+        stderr.trim()
+    };
+    Err(GranaryError::DaemonError(format!(
+        "`{} {}` failed: {}",
+        program,
+        args.join(" "),
+        message
+    )))
+}
+
+/// `DOMAIN\user` of the current Windows user, for the logon trigger
+fn current_windows_user() -> Option<String> {
+    let user = std::env::var("USERNAME").ok()?;
+    Some(match std::env::var("USERDOMAIN") {
+        Ok(domain) => format!("{}\\{}", domain, user),
+        Err(_) => user,
+    })
+}
+
+/// systemd user unit
+pub fn render_systemd(daemon_path: &Path, path_env: Option<&str>) -> String {
+    let mut unit = String::from(
+        "[Unit]\n\
+         Description=Granary daemon\n\
+         Documentation=https://github.com/danielkov/granary\n\
+         \n\
+         [Service]\n\
+         Type=simple\n",
+    );
+    unit.push_str(&format!(
+        "ExecStart={}\n",
+        systemd_quote(&daemon_path.to_string_lossy())
+    ));
+    if let Some(path_env) = path_env {
+        unit.push_str(&format!(
+            "Environment={}\n",
+            systemd_quote(&format!("PATH={}", path_env))
+        ));
+    }
+    unit.push_str(
+        "Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+    );
+    unit
+}
+
+/// launchd agent property list
+pub fn render_launchd(daemon_path: &Path, path_env: Option<&str>, log_path: &Path) -> String {
+    let mut plist = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+"#,
+        LAUNCHD_LABEL,
+        xml_escape(&daemon_path.to_string_lossy()),
+    );
+    if let Some(path_env) = path_env {
+        plist.push_str(&format!(
+            "    <key>EnvironmentVariables</key>\n    <dict>\n        <key>PATH</key>\n        <string>{}</string>\n    </dict>\n",
+            xml_escape(path_env)
+        ));
+    }
+    let log_path = xml_escape(&log_path.to_string_lossy());
+    plist.push_str(&format!(
+        "    <key>StandardOutPath</key>\n    <string>{}</string>\n    <key>StandardErrorPath</key>\n    <string>{}</string>\n</dict>\n</plist>\n",
+        log_path, log_path
+    ));
+    plist
+}
+
+/// Task Scheduler task definition that starts the daemon at logon
+pub fn render_task(daemon_path: &Path, user: Option<String>) -> String {
+    let user_id = user
+        .map(|u| format!("\n      <UserId>{}</UserId>", xml_escape(&u)))
+        .unwrap_or_default();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Description>Granary daemon</Description>
+  </RegistrationInfo>
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>{}
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      <LogonType>InteractiveToken</LogonType>
+      <RunLevel>LeastPrivilege</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <RestartOnFailure>
+      <Interval>PT1M</Interval>
+      <Count>3</Count>
+    </RestartOnFailure>
+    <Hidden>true</Hidden>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      <Command>{}</Command>
+    </Exec>
+  </Actions>
+</Task>
+"#,
+        user_id,
+        xml_escape(&daemon_path.to_string_lossy()),
+    )
+}
+
+/// Quote a systemd directive value, escaping specifiers and quotes
+fn systemd_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_systemd() {
+        let unit = render_systemd(
+            Path::new("/opt/granary 2/granaryd"),
+            Some("/usr/bin:/home/me/100%/bin"),
+        );
+        assert!(unit.contains("ExecStart=\"/opt/granary 2/granaryd\"\n"));
+        assert!(unit.contains("Environment=\"PATH=/usr/bin:/home/me/100%%/bin\"\n"));
+        assert!(unit.contains("Restart=on-failure\n"));
+        assert!(unit.ends_with("WantedBy=default.target\n"));
+    }
+
+    #[test]
+    fn test_render_launchd_escapes_paths() {
+        let plist = render_launchd(
+            Path::new("/Users/me/R&D/granaryd"),
+            None,
+            Path::new("/Users/me/.granary/daemon/service.log"),
+        );
+        assert!(plist.contains("<string>/Users/me/R&amp;D/granaryd</string>"));
+        assert!(plist.contains("<string>dev.granary.granaryd</string>"));
+        assert!(!plist.contains("EnvironmentVariables"));
+        assert!(plist.contains("<key>SuccessfulExit</key>\n        <false/>"));
+    }
+
+    #[test]
+    fn test_render_task() {
+        let task = render_task(
+            Path::new(r"C:\Tools\granaryd.exe"),
+            Some(r"HOME\me".to_string()),
+        );
+        assert!(task.contains(r"<Command>C:\Tools\granaryd.exe</Command>"));
+        assert!(task.contains(r"<UserId>HOME\me</UserId>"));
+        assert!(task.contains("<ExecutionTimeLimit>PT0S</ExecutionTimeLimit>"));
+    }
+}
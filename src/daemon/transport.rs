@@ -0,0 +1,202 @@
+//! Platform transport for daemon IPC.
+//!
+//! The daemon is reached over a Unix domain socket on Unix and a named pipe
+//! on Windows. Framing, authentication and request handling only need a
+//! bidirectional byte stream, so they are written once against [`IpcStream`]
+//! and the platform differences are confined to [`IpcEndpoint`] (where to
+//! connect) and [`IpcListener`](crate::daemon::listener::IpcListener) (how to
+//! accept).
+//!
+//! This module also provides PID liveness checks, which the CLI and daemon
+//! use to tell a running daemon apart from a stale PID file.
+
+use std::fmt;
+use std::io;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+
+use crate::error::Result;
+use crate::services::global_config as global_config_service;
+
+/// A bidirectional byte stream that the IPC protocol can run over.
+///
+/// Implemented for every async stream, including `UnixStream` and the
+/// named pipe client and server types.
+pub trait IpcStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> IpcStream for T {}
+
+/// The address the daemon listens on.
+///
+/// On Unix this is a socket path; on Windows it is a pipe name in the format
+/// `\\.\pipe\{name}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcEndpoint {
+    #[cfg(unix)]
+    path: PathBuf,
+    #[cfg(windows)]
+    name: String,
+}
+
+impl IpcEndpoint {
+    /// The endpoint of the global daemon for the current user
+    #[cfg(unix)]
+    pub fn daemon() -> Result<Self> {
+        Ok(Self::socket(global_config_service::daemon_socket_path()?))
+    }
+
+    /// The endpoint of the global daemon for the current user
+    #[cfg(windows)]
+    pub fn daemon() -> Result<Self> {
+        Ok(Self::pipe(global_config_service::daemon_pipe_name()))
+    }
+
+    /// An endpoint for a Unix domain socket at `path`
+    #[cfg(unix)]
+    pub fn socket(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// An endpoint for a named pipe
+    #[cfg(windows)]
+    pub fn pipe(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+
+    /// Path of the socket file
+    #[cfg(unix)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Name of the pipe
+    #[cfg(windows)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Human-readable kind of endpoint, for status output
+    pub fn kind(&self) -> &'static str {
+        if cfg!(windows) { "Pipe" } else { "Socket" }
+    }
+
+    /// Open a client connection to this endpoint.
+    #[cfg(unix)]
+    pub async fn connect(&self) -> io::Result<Box<dyn IpcStream>> {
+        let stream = tokio::net::UnixStream::connect(&self.path).await?;
+        Ok(Box::new(stream))
+    }
+
+    /// Open a client connection to this endpoint.
+    ///
+    /// If every pipe instance is busy, this retries with a short delay until
+    /// the server creates a new instance.
+    #[cfg(windows)]
+    pub async fn connect(&self) -> io::Result<Box<dyn IpcStream>> {
+        use std::time::Duration;
+        use tokio::net::windows::named_pipe::ClientOptions;
+        use windows_sys::Win32::Foundation::ERROR_PIPE_BUSY;
+
+        loop {
+            match ClientOptions::new().open(&self.name) {
+                Ok(pipe) => return Ok(Box::new(pipe)),
+                Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl fmt::Display for IpcEndpoint {
+    #[cfg(unix)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+
+    #[cfg(windows)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Check whether a process with the given PID is still running.
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 performs the permission and existence checks without sending
+    // anything. EPERM means the process exists but belongs to another user.
+    // SAFETY: kill with signal 0 has no side effects
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Check whether a process with the given PID is still running.
+#[cfg(windows)]
+pub fn is_process_alive(pid: u32) -> bool {
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, ERROR_ACCESS_DENIED, GetLastError, STILL_ACTIVE,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    if pid == 0 {
+        return false;
+    }
+
+    // SAFETY: the handle is checked for null and closed before returning
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            // The process exists but we are not allowed to query it
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut exit_code = 0u32;
+        let ok = GetExitCodeProcess(handle, &mut exit_code);
+        CloseHandle(handle);
+        ok != 0 && exit_code == STILL_ACTIVE as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_process_is_alive() {
+        assert!(is_process_alive(std::process::id()));
+    }
+
+    #[test]
+    fn test_exited_process_is_not_alive() {
+        let mut child = if cfg!(windows) {
+            std::process::Command::new("cmd")
+                .args(["/C", "exit"])
+                .spawn()
+                .unwrap()
+        } else {
+            std::process::Command::new("true").spawn().unwrap()
+        };
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(!is_process_alive(pid));
+        assert!(!is_process_alive(0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_endpoint_display() {
+        let endpoint = IpcEndpoint::socket("/tmp/granaryd.sock");
+        assert_eq!(endpoint.to_string(), "/tmp/granaryd.sock");
+        assert_eq!(endpoint.kind(), "Socket");
+    }
+}
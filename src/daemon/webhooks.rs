@@ -0,0 +1,438 @@
+//! Webhook delivery for workspace events.
+//!
+//! The dispatcher tails the event log of every workspace the daemon serves
+//! (any workspace with a worker record) and POSTs matching events as JSON to
+//! the configured webhook URLs. Webhooks come from two places:
+//!
+//! - `[[webhooks]]` tables in `~/.granary/config.toml`, applied to all workspaces
+//! - `webhooks.<name>.url` / `.events` / `.secret` / `.max_attempts` workspace
+//!   config keys, applied to that workspace only
+//!
+//! Deliveries that fail with a network error, a 429 or a 5xx response are
+//! retried with exponential backoff. Events are only delivered while the daemon
+//! is running: on startup each workspace is picked up from its latest event.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use tokio::sync::watch;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::event::Event;
+use crate::models::global_config::WebhookConfig;
+use crate::services::Workspace;
+use crate::services::global_config as global_config_service;
+use crate::services::worker_runtime::calculate_backoff;
+
+/// Interval between event log polls
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Base delay for delivery retries, in seconds
+const RETRY_BASE_DELAY_SECS: u64 = 2;
+
+/// Timeout for a single delivery attempt
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prefix of workspace config keys that define webhooks
+const WORKSPACE_KEY_PREFIX: &str = "webhooks.";
+
+/// Delivery state for one workspace
+struct WorkspaceCursor {
+    pool: SqlitePool,
+    last_event_id: i64,
+}
+
+/// Tails workspace event logs and delivers events to webhooks.
+pub struct WebhookDispatcher {
+    global_pool: SqlitePool,
+    client: reqwest::Client,
+    workspaces: HashMap<String, WorkspaceCursor>,
+    retry_base_delay_secs: u64,
+}
+
+impl WebhookDispatcher {
+    pub fn new(global_pool: SqlitePool) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("granary/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            global_pool,
+            client,
+            workspaces: HashMap::new(),
+            retry_base_delay_secs: RETRY_BASE_DELAY_SECS,
+        }
+    }
+
+    /// Poll for events until a shutdown signal is received
+    pub async fn run(mut self, mut shutdown_rx: watch::Receiver<bool>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => {
+                    if *shutdown_rx.borrow() {
+                        break;
+                    }
+                }
+                _ = interval.tick() => {
+                    if let Err(e) = self.tick().await {
+                        tracing::warn!("Webhook dispatch failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deliver events recorded since the previous tick.
+    ///
+    /// Deliveries run in the background; this returns once they are spawned.
+    pub async fn tick(&mut self) -> Result<()> {
+        let global_webhooks = match global_config_service::load() {
+            Ok(config) => config.webhooks,
+            Err(e) => {
+                tracing::warn!("Failed to load global config for webhooks: {}", e);
+                Vec::new()
+            }
+        };
+
+        let paths: BTreeSet<String> = db::workers::list(&self.global_pool)
+            .await?
+            .into_iter()
+            .map(|w| w.instance_path)
+            .collect();
+        self.workspaces.retain(|path, _| paths.contains(path));
+
+        for path in paths {
+            for (webhook, body) in self.poll_workspace(&path, &global_webhooks).await? {
+                let client = self.client.clone();
+                let base_delay = self.retry_base_delay_secs;
+                tokio::spawn(async move {
+                    if let Err(e) = deliver(&client, &webhook, &body, base_delay).await {
+                        tracing::warn!("Webhook delivery to {} failed: {}", webhook.url, e);
+                    }
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Collect new events in a workspace and the webhooks they should go to
+    async fn poll_workspace(
+        &mut self,
+        path: &str,
+        global_webhooks: &[WebhookConfig],
+    ) -> Result<Vec<(WebhookConfig, serde_json::Value)>> {
+        if !self.workspaces.contains_key(path) {
+            // Workspaces that no longer exist are skipped until they reappear
+            let Ok(workspace) = Workspace::open(path) else {
+                return Ok(Vec::new());
+            };
+            let pool = workspace.pool().await?;
+            let last_event_id = db::events::latest_id(&pool).await?;
+            self.workspaces.insert(
+                path.to_string(),
+                WorkspaceCursor {
+                    pool,
+                    last_event_id,
+                },
+            );
+            return Ok(Vec::new());
+        }
+
+        let cursor = self.workspaces.get_mut(path).expect("cursor exists");
+        let events = db::events::list_since_id(&cursor.pool, cursor.last_event_id).await?;
+        let Some(last) = events.last() else {
+            return Ok(Vec::new());
+        };
+        cursor.last_event_id = last.id;
+
+        let mut webhooks = global_webhooks.to_vec();
+        webhooks.extend(workspace_webhooks(&db::config::list(&cursor.pool).await?));
+
+        let mut deliveries = Vec::new();
+        for event in &events {
+            for webhook in webhooks.iter().filter(|w| w.matches(&event.event_type)) {
+                deliveries.push((webhook.clone(), event_payload(event, path)));
+            }
+        }
+        Ok(deliveries)
+    }
+}
+
+/// Parse webhooks from workspace config entries.
+///
+/// Keys take the form `webhooks.<name>.<field>`, where field is `url`,
+/// `events` (comma-separated), `secret` or `max_attempts`. Entries without a
+/// URL are ignored.
+pub fn workspace_webhooks(entries: &[(String, String)]) -> Vec<WebhookConfig> {
+    let mut webhooks: BTreeMap<&str, WebhookConfig> = BTreeMap::new();
+
+    for (key, value) in entries {
+        let Some((name, field)) = key
+            .strip_prefix(WORKSPACE_KEY_PREFIX)
+            .and_then(|rest| rest.rsplit_once('.'))
+        else {
+            continue;
+        };
+        let webhook = webhooks.entry(name).or_default();
+        match field {
+            "url" => webhook.url = value.clone(),
+            "events" => {
+                webhook.events = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            }
+            "secret" => webhook.secret = Some(value.clone()),
+            "max_attempts" => webhook.max_attempts = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    webhooks
+        .into_values()
+        .filter(|w| !w.url.is_empty())
+        .collect()
+}
+
+/// Build the JSON body sent for an event
+pub fn event_payload(event: &Event, workspace: &str) -> serde_json::Value {
+    let payload = serde_json::from_str::<serde_json::Value>(&event.payload)
+        .unwrap_or_else(|_| serde_json::Value::String(event.payload.clone()));
+
+    serde_json::json!({
+        "id": event.id,
+        "event_type": event.event_type,
+        "entity_type": event.entity_type,
+        "entity_id": event.entity_id,
+        "actor": event.actor,
+        "session_id": event.session_id,
+        "payload": payload,
+        "created_at": event.created_at,
+        "workspace": workspace,
+    })
+}
+
+/// Compute the `X-Granary-Signature` header value for a body
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// POST a payload to a webhook, retrying transient failures with backoff
+pub async fn deliver(
+    client: &reqwest::Client,
+    webhook: &WebhookConfig,
+    body: &serde_json::Value,
+    retry_base_delay_secs: u64,
+) -> Result<()> {
+    let bytes = serde_json::to_vec(body)?;
+    let event_type = body["event_type"].as_str().unwrap_or_default();
+    let max_attempts = webhook.max_attempts();
+
+    for attempt in 1..=max_attempts {
+        let mut request = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header("X-Granary-Event", event_type)
+            .header("X-Granary-Delivery-Attempt", attempt.to_string());
+        if let Some(secret) = webhook.expanded_secret() {
+            request = request.header("X-Granary-Signature", sign(&secret, &bytes));
+        }
+        for (name, value) in webhook.expanded_headers() {
+            request = request.header(name, value);
+        }
+
+        let retryable = match request.body(bytes.clone()).send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                if !(status.is_server_error() || status.as_u16() == 429) {
+                    return Err(GranaryError::Other(format!(
+                        "webhook responded with {}",
+                        status
+                    )));
+                }
+                format!("webhook responded with {}", status)
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt == max_attempts {
+            return Err(GranaryError::Other(format!(
+                "{} (gave up after {} attempts)",
+                retryable, max_attempts
+            )));
+        }
+        tracing::debug!(
+            "Webhook delivery to {} failed (attempt {}/{}): {}",
+            webhook.url,
+            attempt,
+            max_attempts,
+            retryable
+        );
+        tokio::time::sleep(calculate_backoff(attempt as i32, retry_base_delay_secs)).await;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve HTTP responses with the given status codes, one per connection
+    async fn serve(statuses: Vec<u16>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let _ = socket.read(&mut buf).await;
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, hits)
+    }
+
+    fn body() -> serde_json::Value {
+        serde_json::json!({ "event_type": "run.failed" })
+    }
+
+    #[test]
+    fn test_workspace_webhooks() {
+        let entries = vec![
+            ("date.timezone".to_string(), "UTC".to_string()),
+            (
+                "webhooks.ci.url".to_string(),
+                "https://ci.example.com".to_string(),
+            ),
+            (
+                "webhooks.ci.events".to_string(),
+                "run.failed, task.completed".to_string(),
+            ),
+            ("webhooks.ci.max_attempts".to_string(), "2".to_string()),
+            ("webhooks.broken.events".to_string(), "run.*".to_string()),
+        ];
+
+        let webhooks = workspace_webhooks(&entries);
+        assert_eq!(webhooks.len(), 1);
+        assert_eq!(webhooks[0].url, "https://ci.example.com");
+        assert_eq!(webhooks[0].events, vec!["run.failed", "task.completed"]);
+        assert_eq!(webhooks[0].max_attempts(), 2);
+    }
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_event_payload_parses_json() {
+        let event = Event {
+            id: 7,
+            event_type: "task.completed".to_string(),
+            entity_type: "task".to_string(),
+            entity_id: "proj-abc1-task-1".to_string(),
+            actor: None,
+            session_id: None,
+            payload: r#"{"title":"Ship it"}"#.to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+        let body = event_payload(&event, "/work/proj");
+        assert_eq!(body["payload"]["title"], "Ship it");
+        assert_eq!(body["workspace"], "/work/proj");
+    }
+
+    #[tokio::test]
+    async fn test_poll_workspace_delivers_new_events() {
+        use crate::models::CreateProject;
+        use crate::services::create_project;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let path = temp_dir.path().to_string_lossy().to_string();
+
+        let create = |name: &str| CreateProject {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        create_project(&pool, create("Before")).await.unwrap();
+
+        let mut dispatcher = WebhookDispatcher::new(pool.clone());
+        let mut webhook = WebhookConfig::new("http://127.0.0.1:9/hook");
+        webhook.events = vec!["project.*".to_string()];
+        let webhooks = vec![webhook];
+
+        // The first poll only records the cursor; history is not replayed
+        let deliveries = dispatcher.poll_workspace(&path, &webhooks).await.unwrap();
+        assert!(deliveries.is_empty());
+
+        create_project(&pool, create("After")).await.unwrap();
+        let deliveries = dispatcher.poll_workspace(&path, &webhooks).await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].1["event_type"], "project.created");
+
+        let deliveries = dispatcher.poll_workspace(&path, &webhooks).await.unwrap();
+        assert!(deliveries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_server_errors() {
+        let (url, hits) = serve(vec![503, 200]).await;
+        let webhook = WebhookConfig::new(url);
+        deliver(&reqwest::Client::new(), &webhook, &body(), 0)
+            .await
+            .unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_does_not_retry_client_errors() {
+        let (url, hits) = serve(vec![404, 200]).await;
+        let webhook = WebhookConfig::new(url);
+        let result = deliver(&reqwest::Client::new(), &webhook, &body(), 0).await;
+        assert!(result.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up() {
+        let (url, hits) = serve(vec![500, 500, 500]).await;
+        let mut webhook = WebhookConfig::new(url);
+        webhook.max_attempts = Some(2);
+        let result = deliver(&reqwest::Client::new(), &webhook, &body(), 0).await;
+        assert!(result.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+}
@@ -9,7 +9,7 @@
 
 use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use sqlx::SqlitePool;
@@ -21,8 +21,12 @@ use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::run::{Run, RunStatus, UpdateRunStatus};
 use crate::models::worker::{CreateWorker, UpdateWorkerStatus, Worker, WorkerStatus};
+use crate::models::{LogFilter, LogPruneReport, RunLogRecord};
 use crate::services::Workspace;
 use crate::services::global_config as global_config_service;
+use crate::services::log_retention;
+use crate::services::runner::check_command_allowed;
+use crate::services::schedule::Schedule;
 use crate::services::worker_runtime::{WorkerRuntime, WorkerRuntimeConfig};
 
 /// Handle to a running worker, containing the task handle and shutdown channel.
@@ -87,10 +91,23 @@ impl WorkerManager {
     /// # Errors
     ///
     /// Returns an error if:
+    /// - The schedule is not a valid cron expression
+    /// - The command is not allowed by the `[security]` policy
     /// - Database operations fail
     /// - The workspace cannot be opened
     /// - The log directory cannot be created
     pub async fn start_worker(&self, create: CreateWorker) -> Result<Worker> {
+        // Reject malformed schedules and disallowed commands before anything
+        // is persisted
+        if let Some(ref expression) = create.schedule {
+            Schedule::parse(expression)?;
+        }
+        check_command_allowed(
+            &global_config_service::load_file()?.security,
+            &create.command,
+            Path::new(&create.instance_path),
+        )?;
+
         // 1. Create DB record
         let worker = db::workers::create(&self.global_pool, &create).await?;
 
@@ -177,6 +194,71 @@ impl WorkerManager {
         Ok(())
     }
 
+    /// Pause a running worker.
+    ///
+    /// The worker keeps running and finishes its active runs, but starts no
+    /// runs for new events until it is resumed. What happens to events that
+    /// arrive meanwhile depends on the worker's `on_pause` mode. The runtime
+    /// picks up the change on its next poll.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The worker is not found
+    /// - The worker is not in 'running' status
+    pub async fn pause_worker(&self, worker_id: &str) -> Result<Worker> {
+        self.set_worker_paused(worker_id, WorkerStatus::Running, WorkerStatus::Paused)
+            .await
+    }
+
+    /// Resume a paused worker.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The worker is not found
+    /// - The worker is not in 'paused' status
+    pub async fn resume_worker(&self, worker_id: &str) -> Result<Worker> {
+        self.set_worker_paused(worker_id, WorkerStatus::Paused, WorkerStatus::Running)
+            .await
+    }
+
+    /// Move a worker between running and paused, keeping its pid
+    async fn set_worker_paused(
+        &self,
+        worker_id: &str,
+        from: WorkerStatus,
+        to: WorkerStatus,
+    ) -> Result<Worker> {
+        let worker = db::workers::get(&self.global_pool, worker_id)
+            .await?
+            .ok_or_else(|| GranaryError::WorkerNotFound(worker_id.to_string()))?;
+
+        if worker.status_enum() != from {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Cannot {} worker: status is '{}', must be '{}'",
+                if to == WorkerStatus::Paused {
+                    "pause"
+                } else {
+                    "resume"
+                },
+                worker.status,
+                from.as_str()
+            )));
+        }
+
+        let update = UpdateWorkerStatus {
+            status: to,
+            error_message: None,
+            pid: worker.pid,
+        };
+        db::workers::update_status(&self.global_pool, worker_id, &update).await?;
+
+        db::workers::get(&self.global_pool, worker_id)
+            .await?
+            .ok_or_else(|| GranaryError::WorkerNotFound(worker_id.to_string()))
+    }
+
     /// Get a worker by ID from the database.
     ///
     /// # Arguments
@@ -290,13 +372,19 @@ impl WorkerManager {
     /// 3. If present, attempt to restart the worker using `start_existing_worker`
     /// 4. Log restoration results
     ///
+    /// Scheduled workers resume from their persisted `next_fire_at`. If that
+    /// time passed while the daemon was down, the runtime fires once to catch
+    /// up and then schedules the next occurrence from the current time.
+    ///
     /// # Errors
     ///
     /// Returns an error if database operations fail. Individual worker
     /// restoration failures are logged but don't cause the method to fail.
     pub async fn restore_workers(&self) -> Result<()> {
-        // Find workers that were running when daemon last stopped
-        let workers = db::workers::list_by_status(&self.global_pool, WorkerStatus::Running).await?;
+        // Find workers that were running or paused when daemon last stopped
+        let mut workers =
+            db::workers::list_by_status(&self.global_pool, WorkerStatus::Running).await?;
+        workers.extend(db::workers::list_by_status(&self.global_pool, WorkerStatus::Paused).await?);
 
         if workers.is_empty() {
             return Ok(());
@@ -365,6 +453,13 @@ impl WorkerManager {
     /// - The log directory cannot be created
     /// - The worker runtime cannot be created
     async fn start_existing_worker(&self, worker: Worker) -> Result<()> {
+        // The policy may have been tightened since the worker was started
+        check_command_allowed(
+            &global_config_service::load_file()?.security,
+            &worker.command,
+            Path::new(&worker.instance_path),
+        )?;
+
         // Get workspace pool
         let workspace = Workspace::open(&worker.instance_path)?;
         let workspace_pool = workspace.pool().await?;
@@ -484,9 +579,13 @@ impl WorkerManager {
     ///
     /// This method:
     /// 1. Finds the run in the database
-    /// 2. If the run has a PID, sends SIGTERM to the process
+    /// 2. If the run has a PID, sends SIGTERM to the process (and SIGCONT
+    ///    if it is paused, so it can act on it)
     /// 3. Updates the run status to cancelled
     ///
+    /// The worker's runtime then gives the run its runner's grace period to
+    /// exit before killing it, and records whether it exited gracefully.
+    ///
     /// # Arguments
     ///
     /// * `run_id` - The ID of the run to stop
@@ -509,7 +608,10 @@ impl WorkerManager {
 
         // If run has a PID, try to kill the process
         if let Some(pid) = run.pid {
-            kill_process(pid as u32, ProcessSignal::Term);
+            kill_process(pid as u32, ProcessSignal::Term)?;
+            if run.status_enum() == RunStatus::Paused {
+                kill_process(pid as u32, ProcessSignal::Cont)?;
+            }
         }
 
         // Update status to cancelled
@@ -557,7 +659,7 @@ impl WorkerManager {
         })?;
 
         // Send SIGSTOP
-        kill_process(pid as u32, ProcessSignal::Stop);
+        kill_process(pid as u32, ProcessSignal::Stop)?;
 
         // Update status to paused
         let update = UpdateRunStatus {
@@ -604,7 +706,7 @@ impl WorkerManager {
         })?;
 
         // Send SIGCONT
-        kill_process(pid as u32, ProcessSignal::Cont);
+        kill_process(pid as u32, ProcessSignal::Cont)?;
 
         // Update status to running
         let update = UpdateRunStatus {
@@ -618,6 +720,46 @@ impl WorkerManager {
         Ok(())
     }
 
+    /// Retry a failed, cancelled, killed or timed-out run.
+    ///
+    /// The run is requeued as pending with the next attempt number and is
+    /// picked up by its worker on the next poll. The run's attempt limit is
+    /// raised if needed so a manual retry is always allowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The run is not found
+    /// - The run is not in 'failed', 'cancelled', 'killed' or 'timed_out' status
+    /// - The run's worker is not running in this daemon
+    pub async fn retry_run(&self, run_id: &str) -> Result<Run> {
+        let run = db::runs::get(&self.global_pool, run_id)
+            .await?
+            .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))?;
+
+        if !matches!(
+            run.status_enum(),
+            RunStatus::Failed | RunStatus::Cancelled | RunStatus::Killed | RunStatus::TimedOut
+        ) {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Cannot retry run: status is '{}', must be 'failed', 'cancelled', 'killed' or 'timed_out'",
+                run.status
+            )));
+        }
+
+        if !self.is_worker_running(&run.worker_id).await {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Cannot retry run: worker {} is not running",
+                run.worker_id
+            )));
+        }
+
+        db::runs::requeue(&self.global_pool, run_id).await?;
+        db::runs::get(&self.global_pool, run_id)
+            .await?
+            .ok_or_else(|| GranaryError::RunNotFound(run_id.to_string()))
+    }
+
     /// Get the log path for a run.
     ///
     /// # Arguments
@@ -636,8 +778,7 @@ impl WorkerManager {
             Ok(Some(std::path::PathBuf::from(path)))
         } else {
             // Fallback: try to find log in worker's log directory
-            let log_dir = global_config_service::worker_logs_dir(&run.worker_id)?;
-            let log_path = log_dir.join(format!("{}.log", run_id));
+            let log_path = fallback_run_log_path(&run)?;
             if log_path.exists() {
                 Ok(Some(log_path))
             } else {
@@ -662,17 +803,21 @@ impl WorkerManager {
     /// * `target_type` - Whether this is a worker or run log request
     /// * `since_line` - Return lines after this line number (0-indexed)
     /// * `limit` - Maximum number of lines to return
+    /// * `filter` - Stream and time filter; only JSON-lines run logs can be
+    ///   filtered
     ///
     /// # Returns
     ///
     /// A `LogsResponse` containing the requested log lines and metadata for
-    /// subsequent polling requests.
+    /// subsequent polling requests. `next_line` counts every line read, so
+    /// it stays a valid offset when lines are filtered out.
     pub async fn get_logs(
         &self,
         target_id: &str,
         target_type: LogTarget,
         since_line: u64,
         limit: u64,
+        filter: &LogFilter,
     ) -> Result<LogsResponse> {
         let log_path = match target_type {
             LogTarget::Worker => {
@@ -689,36 +834,34 @@ impl WorkerManager {
                     PathBuf::from(path)
                 } else {
                     // Fallback: try worker's log directory
-                    let log_dir = global_config_service::worker_logs_dir(&run.worker_id)?;
-                    log_dir.join(format!("{}.log", target_id))
+                    fallback_run_log_path(&run)?
                 }
             }
         };
 
+        if !filter.is_empty() && !is_json_lines(&log_path) {
+            return Err(GranaryError::InvalidArgument(format!(
+                "{} is a plain text log; stream and time filters need a runner with log_format = \"jsonl\"",
+                log_path.display()
+            )));
+        }
+
+        // Check if target is still active (more logs might come). This is
+        // checked before reading, so a target that stops in between gets
+        // one more read with has_more set.
+        let has_more = self.is_target_active(target_id, &target_type).await;
+
         // If log file doesn't exist, return empty response
         if !log_path.exists() {
             return Ok(LogsResponse {
                 lines: vec![],
                 next_line: 0,
-                has_more: self.is_target_active(target_id, &target_type).await,
+                has_more,
                 log_path: Some(log_path),
             });
         }
 
-        // Read lines from file starting at since_line
-        let file = std::fs::File::open(&log_path)?;
-        let reader = BufReader::new(file);
-
-        let lines: Vec<String> = reader
-            .lines()
-            .skip(since_line as usize)
-            .take(limit as usize)
-            .collect::<std::io::Result<_>>()?;
-
-        let next_line = since_line + lines.len() as u64;
-
-        // Check if target is still active (more logs might come)
-        let has_more = self.is_target_active(target_id, &target_type).await;
+        let (lines, next_line) = read_log_lines(&log_path, since_line, limit, !has_more, filter)?;
 
         Ok(LogsResponse {
             lines,
@@ -738,7 +881,10 @@ impl WorkerManager {
                 }
                 // Also check database status
                 if let Ok(Some(worker)) = db::workers::get(&self.global_pool, target_id).await {
-                    matches!(worker.status.as_str(), "running" | "pending" | "starting")
+                    matches!(
+                        worker.status.as_str(),
+                        "running" | "paused" | "pending" | "starting"
+                    )
                 } else {
                     false
                 }
@@ -773,8 +919,8 @@ impl WorkerManager {
     ///
     /// This method cleans up workers that are no longer active by:
     /// 1. Finding all workers with "stopped" or "error" status
-    /// 2. Deleting their associated run records from the database
-    /// 3. Removing their log directories from disk
+    /// 2. Deleting their associated run and run artifact records from the database
+    /// 3. Removing their log directories, which hold run artifacts, from disk
     /// 4. Deleting the worker records from the database
     ///
     /// # Returns
@@ -797,7 +943,8 @@ impl WorkerManager {
 
         let mut pruned = 0;
         for worker in all_workers {
-            // Delete runs for this worker
+            // Delete runs for this worker, and the artifacts they recorded
+            db::artifacts::delete_by_worker_runs(&self.global_pool, &worker.id).await?;
             db::runs::delete_by_worker(&self.global_pool, &worker.id).await?;
 
             // Delete log directory
@@ -816,121 +963,14 @@ impl WorkerManager {
     }
 
     // ========================================================================
-    // Log retention and cleanup methods
+    // Log retention
     // ========================================================================
 
-    /// Clean up old log files based on retention policy.
-    ///
-    /// This method enforces the log retention policy by:
-    /// 1. Iterating through all worker log directories
-    /// 2. Deleting log files older than `max_age_days`
-    /// 3. Keeping only the most recent `max_files_per_worker` files per worker
-    ///
-    /// # Arguments
-    ///
-    /// * `config` - The log retention configuration specifying cleanup thresholds
-    ///
-    /// # Returns
-    ///
-    /// The number of log files that were deleted.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the logs directory cannot be read. Individual file
-    /// deletion failures are silently ignored to ensure cleanup continues.
-    pub fn cleanup_old_logs(
-        &self,
-        config: &crate::models::global_config::LogRetentionConfig,
-    ) -> Result<u64> {
-        let logs_base_dir = global_config_service::logs_dir()?;
-
-        // If logs directory doesn't exist, nothing to clean
-        if !logs_base_dir.exists() {
-            return Ok(0);
-        }
-
-        let max_age_secs = config.max_age_days * 86400;
-        let mut deleted = 0u64;
-
-        // Iterate through worker directories
-        let entries = match std::fs::read_dir(&logs_base_dir) {
-            Ok(entries) => entries,
-            Err(_) => return Ok(0),
-        };
-
-        for entry in entries.flatten() {
-            if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                continue;
-            }
-
-            let worker_dir = entry.path();
-            deleted +=
-                self.cleanup_worker_logs(&worker_dir, max_age_secs, config.max_files_per_worker);
-        }
-
-        Ok(deleted)
-    }
-
-    /// Clean up log files in a single worker's log directory.
-    ///
-    /// Deletes files that are either:
-    /// - Older than the maximum age threshold
-    /// - Exceeding the maximum file count (oldest files first)
-    ///
-    /// # Arguments
-    ///
-    /// * `worker_dir` - Path to the worker's log directory
-    /// * `max_age_secs` - Maximum age in seconds for log files
-    /// * `max_files` - Maximum number of log files to keep
-    ///
-    /// # Returns
-    ///
-    /// The number of files deleted from this worker directory.
-    fn cleanup_worker_logs(
-        &self,
-        worker_dir: &std::path::Path,
-        max_age_secs: u64,
-        max_files: usize,
-    ) -> u64 {
-        let entries = match std::fs::read_dir(worker_dir) {
-            Ok(entries) => entries,
-            Err(_) => return 0,
-        };
-
-        // Collect all log files with their modification times
-        let mut log_files: Vec<(std::path::PathBuf, std::time::SystemTime)> = entries
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
-            .filter_map(|e| {
-                let path = e.path();
-                let modified = e.metadata().ok()?.modified().ok()?;
-                Some((path, modified))
-            })
-            .collect();
-
-        // Sort by modification time (oldest first)
-        log_files.sort_by_key(|(_, modified)| *modified);
-
-        let now = std::time::SystemTime::now();
-        let mut deleted = 0u64;
-        let total_files = log_files.len();
-
-        for (i, (path, modified)) in log_files.iter().enumerate() {
-            // Check if file is too old
-            let is_too_old = now
-                .duration_since(*modified)
-                .map(|d| d.as_secs() > max_age_secs)
-                .unwrap_or(false);
-
-            // Check if we have too many files (keep the newest max_files)
-            let exceeds_max_count = total_files > max_files && i < (total_files - max_files);
-
-            if (is_too_old || exceeds_max_count) && std::fs::remove_file(path).is_ok() {
-                deleted += 1;
-            }
-        }
-
-        deleted
+    /// Prune `~/.granary/logs/` by the `[logs]` policy in the global config,
+    /// which is read afresh so edits apply without restarting the daemon.
+    pub async fn prune_logs(&self, dry_run: bool) -> Result<LogPruneReport> {
+        let config = global_config_service::load()?;
+        log_retention::prune(&self.global_pool, &config.logs, dry_run).await
     }
 }
 
@@ -948,7 +988,9 @@ enum ProcessSignal {
 /// the signal when the run is stopped/paused/resumed.
 ///
 /// On Windows, for TERM signals, taskkill /T is used to kill the process tree.
-fn kill_process(pid: u32, signal: ProcessSignal) {
+/// Windows has no equivalent of SIGSTOP/SIGCONT, so pausing and resuming
+/// fails there rather than recording a paused run that is still running.
+fn kill_process(pid: u32, signal: ProcessSignal) -> Result<()> {
     #[cfg(unix)]
     {
         let sig = match signal {
@@ -963,24 +1005,95 @@ fn kill_process(pid: u32, signal: ProcessSignal) {
         let _ = std::process::Command::new("kill")
             .args([sig, &pgid])
             .output();
+        Ok(())
     }
 
     #[cfg(not(unix))]
     {
-        // On Windows, use taskkill /T to kill the entire process tree
-        if matches!(signal, ProcessSignal::Term) {
-            let _ = std::process::Command::new("taskkill")
-                .args(["/PID", &pid.to_string(), "/T", "/F"])
-                .output();
+        match signal {
+            ProcessSignal::Term => {
+                // On Windows, use taskkill /T to kill the entire process tree
+                let _ = std::process::Command::new("taskkill")
+                    .args(["/PID", &pid.to_string(), "/T", "/F"])
+                    .output();
+                Ok(())
+            }
+            ProcessSignal::Stop | ProcessSignal::Cont => Err(GranaryError::InvalidArgument(
+                "Pausing and resuming runs is not supported on Windows".to_string(),
+            )),
+        }
+    }
+}
+
+/// Where a run's log is expected when the run has no recorded log path: the
+/// JSON-lines log if there is one, otherwise the plain text log.
+fn fallback_run_log_path(run: &Run) -> Result<PathBuf> {
+    let log_dir = global_config_service::worker_logs_dir(&run.worker_id)?;
+    let jsonl = log_dir.join(format!("{}.jsonl", run.id));
+    if jsonl.exists() {
+        Ok(jsonl)
+    } else {
+        Ok(log_dir.join(format!("{}.log", run.id)))
+    }
+}
+
+/// Whether a log file holds JSON-lines records
+fn is_json_lines(path: &std::path::Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "jsonl")
+}
+
+/// Read up to `limit` lines of a log file, starting at line `since_line`,
+/// keeping only JSON-lines records that pass `filter` when it is set.
+/// Returns the lines and the line number to continue reading from.
+///
+/// A last line without a newline is still being written, so it is left for
+/// the next read unless `include_partial` is set (the writer has finished).
+/// Invalid UTF-8 is replaced rather than failing the read.
+fn read_log_lines(
+    path: &std::path::Path,
+    since_line: u64,
+    limit: u64,
+    include_partial: bool,
+    filter: &LogFilter,
+) -> std::io::Result<(Vec<String>, u64)> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    let mut index = 0u64;
+    while (lines.len() as u64) < limit {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
         }
-        // STOP and CONT are not easily supported on Windows
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+        } else if !include_partial {
+            break;
+        }
+        if index >= since_line {
+            let line = String::from_utf8_lossy(&buf).into_owned();
+            let keep = filter.is_empty()
+                || serde_json::from_str::<RunLogRecord>(&line)
+                    .is_ok_and(|record| filter.matches(&record));
+            if keep {
+                lines.push(line);
+            }
+        }
+        index += 1;
     }
+    Ok((lines, index.max(since_line)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db::connection::{create_pool, run_migrations};
+    use crate::models::LogStream;
+    use crate::models::run::{CreateRun, ScheduleRetry};
+    use crate::models::worker::{PauseMode, RetryPolicy, RetryStrategy};
     use tempfile::tempdir;
 
     async fn setup_test_db() -> (SqlitePool, tempfile::TempDir) {
@@ -1036,6 +1149,547 @@ mod tests {
         assert_eq!(manager.active_worker_count().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_start_worker_rejects_invalid_schedule() {
+        let (pool, _temp) = setup_test_db().await;
+        let manager = WorkerManager::new(pool.clone());
+
+        let create = CreateWorker {
+            command: "echo".to_string(),
+            event_type: "schedule.fired".to_string(),
+            instance_path: "/nonexistent/workspace/path".to_string(),
+            schedule: Some("every tuesday".to_string()),
+            ..Default::default()
+        };
+
+        let result = manager.start_worker(create).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+
+        // Nothing should have been persisted
+        let workers = db::workers::list(&pool).await.unwrap();
+        assert!(workers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_worker_next_fire_persisted() {
+        let (pool, _temp) = setup_test_db().await;
+
+        let create = CreateWorker {
+            command: "echo".to_string(),
+            event_type: "schedule.fired".to_string(),
+            instance_path: "/tmp/workspace".to_string(),
+            schedule: Some("0 9 * * *".to_string()),
+            ..Default::default()
+        };
+        let worker = db::workers::create(&pool, &create).await.unwrap();
+        assert_eq!(worker.schedule.as_deref(), Some("0 9 * * *"));
+        assert!(worker.next_fire_at.is_none());
+
+        db::workers::update_next_fire(&pool, &worker.id, Some("2026-03-02T09:00:00+00:00"))
+            .await
+            .unwrap();
+        let worker = db::workers::get(&pool, &worker.id).await.unwrap().unwrap();
+        assert_eq!(
+            worker.next_fire_at.as_deref(),
+            Some("2026-03-02T09:00:00+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_worker() {
+        let (pool, _temp) = setup_test_db().await;
+        let manager = WorkerManager::new(pool.clone());
+
+        let create = CreateWorker {
+            command: "echo".to_string(),
+            event_type: "task.unblocked".to_string(),
+            instance_path: "/tmp/workspace".to_string(),
+            on_pause: PauseMode::Drop,
+            ..Default::default()
+        };
+        let worker = db::workers::create(&pool, &create).await.unwrap();
+        assert_eq!(worker.pause_mode(), PauseMode::Drop);
+
+        // A pending worker cannot be paused
+        let result = manager.pause_worker(&worker.id).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+
+        let update = UpdateWorkerStatus {
+            status: WorkerStatus::Running,
+            error_message: None,
+            pid: Some(4242),
+        };
+        db::workers::update_status(&pool, &worker.id, &update)
+            .await
+            .unwrap();
+
+        let paused = manager.pause_worker(&worker.id).await.unwrap();
+        assert!(paused.is_paused());
+        assert_eq!(paused.pid, Some(4242));
+        assert_eq!(manager.list_workers(false).await.unwrap().len(), 1);
+        assert!(
+            manager
+                .is_target_active(&worker.id, &LogTarget::Worker)
+                .await
+        );
+
+        // Pausing twice is an error, as is resuming a running worker
+        assert!(manager.pause_worker(&worker.id).await.is_err());
+        let resumed = manager.resume_worker(&worker.id).await.unwrap();
+        assert!(resumed.is_running());
+        assert!(manager.resume_worker(&worker.id).await.is_err());
+
+        let result = manager.pause_worker("nonexistent").await;
+        assert!(matches!(result, Err(GranaryError::WorkerNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_run_queue_bump_and_drop() {
+        let (pool, _temp) = setup_test_db().await;
+
+        let create = CreateWorker {
+            command: "echo".to_string(),
+            event_type: "task.unblocked".to_string(),
+            instance_path: "/tmp/workspace".to_string(),
+            ..Default::default()
+        };
+        let worker = db::workers::create(&pool, &create).await.unwrap();
+
+        let mut runs = Vec::new();
+        for event_id in 1..=3 {
+            let create = CreateRun {
+                worker_id: worker.id.clone(),
+                event_id,
+                ..Default::default()
+            };
+            runs.push(db::runs::create(&pool, &create).await.unwrap().id);
+            // created_at breaks ties between runs of the same priority
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let queue = |pool: SqlitePool, worker_id: String| async move {
+            db::runs::get_pending(&pool, &worker_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|run| run.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(queue(pool.clone(), worker.id.clone()).await, runs);
+
+        // Bumping moves a run ahead of the others, including earlier bumps
+        assert!(db::runs::bump(&pool, &runs[2]).await.unwrap());
+        assert!(db::runs::bump(&pool, &runs[1]).await.unwrap());
+        assert_eq!(
+            queue(pool.clone(), worker.id.clone()).await,
+            vec![runs[1].clone(), runs[2].clone(), runs[0].clone()]
+        );
+        let listed = db::runs::list_queued(&pool, Some(&worker.id))
+            .await
+            .unwrap();
+        assert_eq!(listed[0].id, runs[1]);
+
+        assert!(db::runs::drop_queued(&pool, &runs[2]).await.unwrap());
+        let dropped = db::runs::get(&pool, &runs[2]).await.unwrap().unwrap();
+        assert_eq!(dropped.status_enum(), RunStatus::Cancelled);
+        assert_eq!(
+            queue(pool.clone(), worker.id.clone()).await,
+            vec![runs[1].clone(), runs[0].clone()]
+        );
+
+        // Runs that are no longer queued are left alone
+        assert!(!db::runs::drop_queued(&pool, &runs[2]).await.unwrap());
+        assert!(!db::runs::bump(&pool, &runs[2]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_worker_retry_policy_persisted() {
+        let (pool, _temp) = setup_test_db().await;
+
+        let policy = RetryPolicy {
+            strategy: RetryStrategy::Fixed,
+            max_attempts: 5,
+            base_delay_secs: 30,
+            max_delay_secs: None,
+            jitter: false,
+            retry_timeouts: true,
+        };
+        let create = CreateWorker {
+            command: "echo".to_string(),
+            event_type: "task.unblocked".to_string(),
+            instance_path: "/tmp/workspace".to_string(),
+            retry_policy: Some(policy.clone()),
+            timeout_secs: Some(600),
+            throttle_secs: Some(30),
+            ..Default::default()
+        };
+        let worker = db::workers::create(&pool, &create).await.unwrap();
+        assert_eq!(worker.retry_policy_config(), policy);
+        assert_eq!(worker.timeout_secs, Some(600));
+        assert_eq!(worker.throttle_secs, Some(30));
+        assert!(worker.batches_events());
+
+        let create = CreateWorker {
+            retry_policy: None,
+            timeout_secs: None,
+            ..create
+        };
+        let worker = db::workers::create(&pool, &create).await.unwrap();
+        assert!(worker.retry_policy.is_none());
+        assert!(worker.timeout_secs.is_none());
+        assert_eq!(worker.retry_policy_config(), RetryPolicy::default());
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_holds_back_partial_line_while_active() {
+        let (pool, temp) = setup_test_db().await;
+        let manager = WorkerManager::new(pool.clone());
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "echo".to_string(),
+                event_type: "task.created".to_string(),
+                instance_path: "/tmp/workspace".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let log_path = temp.path().join("run.log");
+        std::fs::write(&log_path, b"first\r\nsecond\n\xffthi").unwrap();
+        let run = db::runs::create(
+            &pool,
+            &CreateRun {
+                worker_id: worker.id.clone(),
+                command: "echo".to_string(),
+                log_path: Some(log_path.display().to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Pending: the unterminated line may still grow
+        let logs = manager
+            .get_logs(&run.id, LogTarget::Run, 0, 100, &LogFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(logs.lines, ["first", "second"]);
+        assert_eq!(logs.next_line, 2);
+        assert!(logs.has_more);
+
+        let done = UpdateRunStatus {
+            status: RunStatus::Completed,
+            exit_code: Some(0),
+            error_message: None,
+            pid: None,
+        };
+        db::runs::update_status(&pool, &run.id, &done)
+            .await
+            .unwrap();
+
+        let logs = manager
+            .get_logs(&run.id, LogTarget::Run, 2, 100, &LogFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(logs.lines, ["\u{fffd}thi"]);
+        assert!(!logs.has_more);
+
+        // Plain text logs cannot be filtered
+        let stderr = LogFilter {
+            stream: Some(LogStream::Stderr),
+            ..Default::default()
+        };
+        assert!(
+            manager
+                .get_logs(&run.id, LogTarget::Run, 0, 100, &stderr)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_logs_filters_json_lines() {
+        let (pool, temp) = setup_test_db().await;
+        let manager = WorkerManager::new(pool.clone());
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "echo".to_string(),
+                event_type: "task.created".to_string(),
+                instance_path: "/tmp/workspace".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let log_path = temp.path().join("run.jsonl");
+        let records = [
+            ("2026-01-15T10:00:00.000Z", LogStream::Stdout, "starting"),
+            (
+                "2026-01-15T10:00:01.000Z",
+                LogStream::Stderr,
+                "warning: slow",
+            ),
+            (
+                "2026-01-15T10:05:00.000Z",
+                LogStream::Stderr,
+                "error: failed",
+            ),
+            ("2026-01-15T10:05:01.000Z", LogStream::Stdout, "done"),
+        ];
+        let content: String = records
+            .iter()
+            .map(|(timestamp, stream, line)| {
+                let record = RunLogRecord {
+                    timestamp: timestamp.to_string(),
+                    stream: *stream,
+                    line: line.to_string(),
+                };
+                format!("{}\n", serde_json::to_string(&record).unwrap())
+            })
+            .collect();
+        std::fs::write(&log_path, content).unwrap();
+        let run = db::runs::create(
+            &pool,
+            &CreateRun {
+                worker_id: worker.id.clone(),
+                command: "echo".to_string(),
+                log_path: Some(log_path.display().to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let line_of = |raw: &String| serde_json::from_str::<RunLogRecord>(raw).unwrap().line;
+
+        let stderr = LogFilter {
+            stream: Some(LogStream::Stderr),
+            ..Default::default()
+        };
+        let logs = manager
+            .get_logs(&run.id, LogTarget::Run, 0, 1, &stderr)
+            .await
+            .unwrap();
+        assert_eq!(
+            logs.lines.iter().map(line_of).collect::<Vec<_>>(),
+            ["warning: slow"]
+        );
+        // The offset counts every line read, not only the matching ones
+        assert_eq!(logs.next_line, 2);
+        let logs = manager
+            .get_logs(&run.id, LogTarget::Run, logs.next_line, 100, &stderr)
+            .await
+            .unwrap();
+        assert_eq!(
+            logs.lines.iter().map(line_of).collect::<Vec<_>>(),
+            ["error: failed"]
+        );
+        assert_eq!(logs.next_line, 4);
+
+        let window = LogFilter {
+            stream: None,
+            since: Some("2026-01-15T10:00:01Z".to_string()),
+            until: Some("2026-01-15T10:05:01Z".to_string()),
+        };
+        let logs = manager
+            .get_logs(&run.id, LogTarget::Run, 0, 100, &window)
+            .await
+            .unwrap();
+        assert_eq!(
+            logs.lines.iter().map(line_of).collect::<Vec<_>>(),
+            ["warning: slow", "error: failed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_cursors() {
+        let (pool, _temp) = setup_test_db().await;
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "echo".to_string(),
+                event_type: "task.created".to_string(),
+                instance_path: "/tmp/workspace".to_string(),
+                global: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(worker.global);
+
+        let cursor = |path: &'static str| {
+            let pool = pool.clone();
+            let id = worker.id.clone();
+            async move {
+                db::workers::get_workspace_cursor(&pool, &id, path)
+                    .await
+                    .unwrap()
+            }
+        };
+        assert_eq!(cursor("/tmp/other").await, None);
+
+        db::workers::update_workspace_cursor(&pool, &worker.id, "/tmp/other", 7)
+            .await
+            .unwrap();
+        db::workers::update_workspace_cursor(&pool, &worker.id, "/tmp/other", 12)
+            .await
+            .unwrap();
+        assert_eq!(cursor("/tmp/other").await, Some(12));
+        assert_eq!(cursor("/tmp/third").await, None);
+
+        // Cursors go with the worker
+        db::workers::delete(&pool, &worker.id).await.unwrap();
+        assert_eq!(cursor("/tmp/other").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_retry_run() {
+        let (pool, _temp) = setup_test_db().await;
+        let manager = WorkerManager::new(pool.clone());
+
+        let result = manager.retry_run("run-missing").await;
+        assert!(matches!(result, Err(GranaryError::RunNotFound(_))));
+
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "false".to_string(),
+                event_type: "task.unblocked".to_string(),
+                instance_path: "/tmp/workspace".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let run = db::runs::create(
+            &pool,
+            &CreateRun {
+                worker_id: worker.id.clone(),
+                command: "false".to_string(),
+                max_attempts: 2,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // Pending runs cannot be retried
+        let result = manager.retry_run(&run.id).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+
+        let failed = UpdateRunStatus {
+            status: RunStatus::Failed,
+            exit_code: Some(1),
+            error_message: Some("boom".to_string()),
+            pid: None,
+        };
+        db::runs::update_status(&pool, &run.id, &failed)
+            .await
+            .unwrap();
+
+        // The worker isn't running in this daemon
+        let result = manager.retry_run(&run.id).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+
+        // Requeueing clears the outcome and raises the attempt limit
+        db::runs::update_for_retry(
+            &pool,
+            &run.id,
+            &ScheduleRetry {
+                next_retry_at: chrono::Utc::now().to_rfc3339(),
+                attempt: 2,
+                error_message: None,
+            },
+        )
+        .await
+        .unwrap();
+        db::runs::update_status(&pool, &run.id, &failed)
+            .await
+            .unwrap();
+        db::runs::requeue(&pool, &run.id).await.unwrap();
+
+        let run = db::runs::get(&pool, &run.id).await.unwrap().unwrap();
+        assert_eq!(run.status_enum(), RunStatus::Pending);
+        assert_eq!(run.attempt, 3);
+        assert_eq!(run.max_attempts, 3);
+        assert!(run.exit_code.is_none());
+        assert!(run.error_message.is_none());
+        assert!(run.completed_at.is_none());
+
+        let due = db::runs::list_pending_retries(&pool, &chrono::Utc::now().to_rfc3339())
+            .await
+            .unwrap();
+        assert!(due.iter().any(|r| r.id == run.id));
+    }
+
+    #[tokio::test]
+    async fn test_queued_runs_oldest_first() {
+        let (pool, _temp) = setup_test_db().await;
+
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "echo".to_string(),
+                event_type: "task.unblocked".to_string(),
+                instance_path: "/tmp/workspace".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut runs = Vec::new();
+        for event_id in 1..=4 {
+            let run = db::runs::create(
+                &pool,
+                &CreateRun {
+                    worker_id: worker.id.clone(),
+                    event_id,
+                    command: "echo".to_string(),
+                    max_attempts: 3,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            runs.push(run);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // One run is active and one is waiting for its retry backoff
+        let running = UpdateRunStatus {
+            status: RunStatus::Running,
+            exit_code: None,
+            error_message: None,
+            pid: Some(1),
+        };
+        db::runs::update_status(&pool, &runs[0].id, &running)
+            .await
+            .unwrap();
+        db::runs::update_for_retry(
+            &pool,
+            &runs[1].id,
+            &ScheduleRetry {
+                next_retry_at: chrono::Utc::now().to_rfc3339(),
+                attempt: 2,
+                error_message: Some("boom".to_string()),
+            },
+        )
+        .await
+        .unwrap();
+
+        let queued = db::runs::get_pending(&pool, &worker.id).await.unwrap();
+        let ids: Vec<&str> = queued.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, [runs[2].id.as_str(), runs[3].id.as_str()]);
+        assert!(queued.iter().all(|r| r.is_queued()));
+
+        let retry = db::runs::get(&pool, &runs[1].id).await.unwrap().unwrap();
+        assert!(!retry.is_queued());
+        assert_eq!(retry.error_message.as_deref(), Some("boom"));
+    }
+
     #[tokio::test]
     async fn test_restore_workers_missing_workspace() {
         let (pool, _temp) = setup_test_db().await;
@@ -1085,97 +1739,4 @@ mod tests {
                 .contains("Workspace directory missing")
         );
     }
-
-    #[tokio::test]
-    async fn test_cleanup_worker_logs_by_count() {
-        let (pool, temp_dir) = setup_test_db().await;
-        let manager = WorkerManager::new(pool);
-
-        // Create a fake worker log directory
-        let worker_dir = temp_dir.path().join("worker-test");
-        std::fs::create_dir_all(&worker_dir).unwrap();
-
-        // Create multiple log files
-        for i in 0..5 {
-            let log_path = worker_dir.join(format!("run-{}.log", i));
-            std::fs::write(&log_path, format!("Log content {}", i)).unwrap();
-            // Add small delay to ensure different modification times
-            std::thread::sleep(std::time::Duration::from_millis(10));
-        }
-
-        // Verify all files were created
-        let files_before: Vec<_> = std::fs::read_dir(&worker_dir)
-            .unwrap()
-            .filter_map(|e| e.ok())
-            .collect();
-        assert_eq!(files_before.len(), 5);
-
-        // Cleanup with max 3 files
-        let deleted = manager.cleanup_worker_logs(&worker_dir, u64::MAX, 3);
-        assert_eq!(deleted, 2); // Should delete 2 oldest files
-
-        // Verify only 3 newest files remain
-        let files_after: Vec<_> = std::fs::read_dir(&worker_dir)
-            .unwrap()
-            .filter_map(|e| e.ok())
-            .collect();
-        assert_eq!(files_after.len(), 3);
-    }
-
-    #[tokio::test]
-    async fn test_cleanup_worker_logs_empty_directory() {
-        let (pool, temp_dir) = setup_test_db().await;
-        let manager = WorkerManager::new(pool);
-
-        // Create an empty worker log directory
-        let worker_dir = temp_dir.path().join("worker-empty");
-        std::fs::create_dir_all(&worker_dir).unwrap();
-
-        // Cleanup should return 0
-        let deleted = manager.cleanup_worker_logs(&worker_dir, u64::MAX, 100);
-        assert_eq!(deleted, 0);
-    }
-
-    #[tokio::test]
-    async fn test_cleanup_worker_logs_nonexistent_directory() {
-        let (pool, temp_dir) = setup_test_db().await;
-        let manager = WorkerManager::new(pool);
-
-        // Try to cleanup a nonexistent directory
-        let worker_dir = temp_dir.path().join("nonexistent");
-        let deleted = manager.cleanup_worker_logs(&worker_dir, u64::MAX, 100);
-        assert_eq!(deleted, 0);
-    }
-
-    #[tokio::test]
-    async fn test_cleanup_worker_logs_ignores_non_log_files() {
-        let (pool, temp_dir) = setup_test_db().await;
-        let manager = WorkerManager::new(pool);
-
-        // Create a fake worker log directory
-        let worker_dir = temp_dir.path().join("worker-mixed");
-        std::fs::create_dir_all(&worker_dir).unwrap();
-
-        // Create some log files and some non-log files
-        std::fs::write(worker_dir.join("run-1.log"), "log1").unwrap();
-        std::fs::write(worker_dir.join("run-2.log"), "log2").unwrap();
-        std::fs::write(worker_dir.join("config.json"), "{}").unwrap();
-        std::fs::write(worker_dir.join("data.txt"), "data").unwrap();
-
-        // Cleanup with max 1 log file
-        let deleted = manager.cleanup_worker_logs(&worker_dir, u64::MAX, 1);
-        assert_eq!(deleted, 1); // Should delete 1 oldest log file
-
-        // Non-log files should still exist
-        assert!(worker_dir.join("config.json").exists());
-        assert!(worker_dir.join("data.txt").exists());
-
-        // One log file should remain
-        let log_count = std::fs::read_dir(&worker_dir)
-            .unwrap()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
-            .count();
-        assert_eq!(log_count, 1);
-    }
 }
@@ -1,25 +1,84 @@
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::{ConnectOptions, Connection};
 use std::path::Path;
 use std::str::FromStr;
 
+use crate::db::{encryption, operations};
 use crate::error::Result;
 
 /// Create a connection pool for the SQLite database
 pub async fn create_pool(db_path: &Path) -> Result<SqlitePool> {
+    let pool = pool_options()
+        .connect_with(connect_options(db_path)?)
+        .await?;
+    Ok(pool)
+}
+
+/// Create a connection pool for a database encrypted with `key`, creating
+/// it if missing. Existing encrypted databases are keyed automatically by
+/// [`create_pool`]; this is for creating one.
+pub async fn create_encrypted_pool(db_path: &Path, key: &str) -> Result<SqlitePool> {
+    let pool = pool_options()
+        .connect_with(keyed_options(db_path, Some(key))?)
+        .await?;
+    Ok(pool)
+}
+
+/// Create a connection pool whose changes are recorded in the operation log
+/// under `operation_id`, so they can be undone
+pub async fn create_recorded_pool(
+    db_path: &Path,
+    operation_id: impl Into<String>,
+) -> Result<SqlitePool> {
+    let operation_id: String = operation_id.into();
+    let pool = pool_options()
+        .after_connect(move |conn, _meta| {
+            let operation_id = operation_id.clone();
+            Box::pin(async move { operations::install_triggers(conn, &operation_id).await })
+        })
+        .connect_with(connect_options(db_path)?)
+        .await?;
+    Ok(pool)
+}
+
+/// Open an existing database with `key` and run `PRAGMA quick_check`,
+/// returning the problems found (empty when it is sound)
+pub async fn quick_check(db_path: &Path, key: Option<&str>) -> Result<Vec<String>> {
+    let mut options = SqliteConnectOptions::new().filename(db_path);
+    if let Some(key) = key {
+        options = options.pragma("key", encryption::key_pragma(key));
+    }
+    let mut conn = options.connect().await?;
+    let rows: Vec<String> = sqlx::query_scalar("PRAGMA quick_check")
+        .fetch_all(&mut conn)
+        .await?;
+    conn.close().await?;
+    Ok(rows.into_iter().filter(|row| row != "ok").collect())
+}
+
+fn connect_options(db_path: &Path) -> Result<SqliteConnectOptions> {
+    let key = encryption::key_for(db_path)?;
+    keyed_options(db_path, key.as_deref())
+}
+
+fn keyed_options(db_path: &Path, key: Option<&str>) -> Result<SqliteConnectOptions> {
     let url = format!("sqlite:{}?mode=rwc", db_path.display());
 
-    let options = SqliteConnectOptions::from_str(&url)?
+    let mut options = SqliteConnectOptions::from_str(&url)?;
+    // sqlx sends the key before any other pragma, as SQLCipher requires
+    if let Some(key) = key {
+        options = options.pragma("key", encryption::key_pragma(key));
+    }
+    let options = options
         .create_if_missing(true)
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
         .foreign_keys(true)
         .busy_timeout(std::time::Duration::from_secs(30));
+    Ok(options)
+}
 
-    let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect_with(options)
-        .await?;
-
-    Ok(pool)
+fn pool_options() -> SqlitePoolOptions {
+    SqlitePoolOptions::new().max_connections(5)
 }
 
 /// Run database migrations using sqlx's migration system
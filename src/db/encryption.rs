@@ -0,0 +1,265 @@
+//! Encryption at rest with SQLCipher.
+//!
+//! Builds with the `encryption` feature link SQLCipher in place of SQLite.
+//! `granary init --encrypted` creates an encrypted workspace database; after
+//! that every connection is keyed automatically. A database counts as
+//! encrypted when its file does not start with the SQLite header, so no
+//! marker file is needed.
+//!
+//! The key comes from `GRANARY_DB_KEY`, else from the OS keychain under the
+//! service "granary" and the database path as the account. The keychain is
+//! reached through the platform's own tools: `security` on macOS and
+//! `secret-tool` (libsecret) on Linux. Elsewhere only the variable is used.
+
+use std::path::Path;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+
+use crate::error::{GranaryError, Result};
+
+/// Environment variable holding the database key
+pub const KEY_ENV: &str = "GRANARY_DB_KEY";
+
+/// Keychain service the key is stored under
+pub const KEYCHAIN_SERVICE: &str = "granary";
+
+/// The first bytes of every unencrypted SQLite database file
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Whether this build can open encrypted databases
+pub fn is_supported() -> bool {
+    cfg!(feature = "encryption")
+}
+
+/// Whether the database file exists and is encrypted
+pub fn is_encrypted(db_path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match std::fs::File::open(db_path) {
+        Ok(mut file) => {
+            std::io::Read::read_exact(&mut file, &mut header).is_ok() && header != SQLITE_HEADER
+        }
+        Err(_) => false,
+    }
+}
+
+/// The key to open a database with, or None when it is not encrypted
+pub fn key_for(db_path: &Path) -> Result<Option<String>> {
+    if !is_encrypted(db_path) {
+        return Ok(None);
+    }
+    if !is_supported() {
+        return Err(GranaryError::Encryption(format!(
+            "{} is encrypted, but this granary was built without the `encryption` feature",
+            db_path.display()
+        )));
+    }
+    match lookup_key(db_path) {
+        Some(key) => Ok(Some(key)),
+        None => Err(GranaryError::Encryption(format!(
+            "{} is encrypted. Set {} or store the key in the OS keychain (service \"{}\", account \"{}\")",
+            db_path.display(),
+            KEY_ENV,
+            KEYCHAIN_SERVICE,
+            account(db_path)
+        ))),
+    }
+}
+
+/// Choose the key for a new encrypted database: `GRANARY_DB_KEY` when set,
+/// otherwise a random key saved to the OS keychain. Returns the key and
+/// whether it was saved to the keychain.
+pub fn key_for_new(db_path: &Path) -> Result<(String, bool)> {
+    if !is_supported() {
+        return Err(GranaryError::Encryption(
+            "This granary was built without the `encryption` feature. Rebuild with `cargo install granary --features encryption`".to_string(),
+        ));
+    }
+    if let Some(key) = env_key() {
+        return Ok((key, false));
+    }
+    let key = generate_key();
+    store_in_keychain(&account(db_path), &key).map_err(|e| {
+        GranaryError::Encryption(format!(
+            "Could not save the key to the OS keychain ({}). Set {} to choose a key instead",
+            e, KEY_ENV
+        ))
+    })?;
+    Ok((key, true))
+}
+
+/// Value for `PRAGMA key`. A 64-digit hex key is used as the raw key, which
+/// skips key derivation on every connection; anything else is a passphrase.
+pub fn key_pragma(key: &str) -> String {
+    if key.len() == 64 && key.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("\"x'{}'\"", key)
+    } else {
+        format!("'{}'", key.replace('\'', "''"))
+    }
+}
+
+fn env_key() -> Option<String> {
+    std::env::var(KEY_ENV).ok().filter(|key| !key.is_empty())
+}
+
+fn lookup_key(db_path: &Path) -> Option<String> {
+    env_key().or_else(|| read_from_keychain(&account(db_path)))
+}
+
+/// Keychain account for a database: its absolute path
+fn account(db_path: &Path) -> String {
+    db_path
+        .canonicalize()
+        .unwrap_or_else(|_| db_path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// 256 random bits as hex
+fn generate_key() -> String {
+    let bytes: [u8; 32] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(target_os = "macos")]
+fn read_from_keychain(account: &str) -> Option<String> {
+    let output = Command::new("security")
+        .args([
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output_key(output)
+}
+
+#[cfg(target_os = "macos")]
+fn store_in_keychain(account: &str, key: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    // With `-w` last and no value, security prompts for the password (and
+    // again to confirm) on stdin, so the key never shows in `ps`
+    let mut child = Command::new("security")
+        .args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        writeln!(stdin, "{}", key)?;
+        writeln!(stdin, "{}", key)?;
+    }
+    check_status(child.wait()?, "security")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn read_from_keychain(account: &str) -> Option<String> {
+    let output = Command::new("secret-tool")
+        .args(["lookup", "service", KEYCHAIN_SERVICE, "account", account])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    output_key(output)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn store_in_keychain(account: &str, key: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    // secret-tool reads the secret from stdin so it never shows in `ps`
+    let mut child = Command::new("secret-tool")
+        .args([
+            "store",
+            "--label=granary database key",
+            "service",
+            KEYCHAIN_SERVICE,
+            "account",
+            account,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(key.as_bytes())?;
+    }
+    check_status(child.wait()?, "secret-tool")
+}
+
+#[cfg(not(unix))]
+fn read_from_keychain(_account: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+fn store_in_keychain(_account: &str, _key: &str) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "no supported keychain on this platform",
+    ))
+}
+
+#[cfg(unix)]
+fn output_key(output: std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let key = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!key.is_empty()).then_some(key)
+}
+
+#[cfg(unix)]
+fn check_status(status: std::process::ExitStatus, tool: &str) -> std::io::Result<()> {
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "{} exited with {}",
+            tool, status
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_encrypted_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("granary.db");
+        assert!(!is_encrypted(&path));
+        assert!(key_for(&path).unwrap().is_none());
+
+        let mut plain = SQLITE_HEADER.to_vec();
+        plain.extend_from_slice(&[0; 100]);
+        std::fs::write(&path, &plain).unwrap();
+        assert!(!is_encrypted(&path));
+
+        std::fs::write(&path, [0x5a; 116]).unwrap();
+        assert!(is_encrypted(&path));
+        if !is_supported() {
+            assert!(matches!(key_for(&path), Err(GranaryError::Encryption(_))));
+        }
+    }
+
+    #[test]
+    fn test_key_pragma() {
+        let raw = "ab".repeat(32);
+        assert_eq!(key_pragma(&raw), format!("\"x'{}'\"", raw));
+        assert_eq!(key_pragma("it's secret"), "'it''s secret'");
+        assert_eq!(generate_key().len(), 64);
+    }
+}
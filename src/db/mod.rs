@@ -1,6 +1,7 @@
 pub mod connection;
+pub mod encryption;
 
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
 
 use crate::error::Result;
 use crate::models::*;
@@ -43,17 +44,22 @@ pub mod projects {
     }
 
     pub async fn list(pool: &SqlitePool, include_archived: bool) -> Result<Vec<Project>> {
-        let projects = if include_archived {
-            sqlx::query_as::<_, Project>("SELECT * FROM projects ORDER BY created_at DESC")
-                .fetch_all(pool)
-                .await?
-        } else {
-            sqlx::query_as::<_, Project>(
-                "SELECT * FROM projects WHERE status = 'active' ORDER BY created_at DESC",
-            )
-            .fetch_all(pool)
-            .await?
-        };
+        list_sorted(pool, include_archived, &Sort::default()).await
+    }
+
+    /// List projects in the given order, newest first by default
+    pub async fn list_sorted(
+        pool: &SqlitePool,
+        include_archived: bool,
+        sort: &Sort,
+    ) -> Result<Vec<Project>> {
+        let mut query = String::from("SELECT * FROM projects");
+        if !include_archived {
+            query.push_str(" WHERE status = 'active'");
+        }
+        query.push_str(&sort.order_by("created_at DESC"));
+
+        let projects = sqlx::query_as::<_, Project>(&query).fetch_all(pool).await?;
         Ok(projects)
     }
 
@@ -81,12 +87,12 @@ pub mod projects {
         Ok(result.rows_affected() > 0)
     }
 
-    pub async fn archive(pool: &SqlitePool, id: &str) -> Result<bool> {
+    pub async fn archive<'e>(executor: impl SqliteExecutor<'e>, id: &str) -> Result<bool> {
         let result =
             sqlx::query("UPDATE projects SET status = 'archived', updated_at = ? WHERE id = ?")
                 .bind(chrono::Utc::now().to_rfc3339())
                 .bind(id)
-                .execute(pool)
+                .execute(executor)
                 .await?;
         Ok(result.rows_affected() > 0)
     }
@@ -95,6 +101,7 @@ pub mod projects {
     /// A project is 'available' if it has tasks that are:
     /// - Status is 'todo' or 'in_progress'
     /// - Not blocked (blocked_reason IS NULL)
+    /// - Not archived
     /// - All dependencies are done
     pub async fn list_with_available_tasks(pool: &SqlitePool) -> Result<Vec<Project>> {
         let projects = sqlx::query_as::<_, Project>(
@@ -104,6 +111,7 @@ pub mod projects {
             WHERE p.status = 'active'
               AND t.status IN ('todo', 'in_progress')
               AND t.blocked_reason IS NULL
+              AND t.archived_at IS NULL
               AND NOT EXISTS (
                   SELECT 1 FROM task_dependencies td
                   JOIN tasks dep ON dep.id = td.depends_on_task_id
@@ -359,6 +367,144 @@ pub mod initiative_projects {
 }
 
 /// Database operations for tasks
+/// Database operations for initiative milestones
+pub mod milestones {
+    use super::*;
+    use crate::models::initiative::Milestone;
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        pool: &SqlitePool,
+        id: &str,
+        initiative_id: &str,
+        milestone_number: i64,
+        name: &str,
+        description: Option<&str>,
+        target_date: Option<&str>,
+    ) -> Result<Milestone> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let milestone = sqlx::query_as::<_, Milestone>(
+            r#"
+            INSERT INTO initiative_milestones (id, initiative_id, milestone_number, name, description, target_date, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(initiative_id)
+        .bind(milestone_number)
+        .bind(name)
+        .bind(description)
+        .bind(target_date)
+        .bind(&now)
+        .bind(&now)
+        .fetch_one(pool)
+        .await?;
+        Ok(milestone)
+    }
+
+    pub async fn get(pool: &SqlitePool, id: &str) -> Result<Option<Milestone>> {
+        let milestone =
+            sqlx::query_as::<_, Milestone>("SELECT * FROM initiative_milestones WHERE id = ?")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(milestone)
+    }
+
+    /// Milestones of an initiative, by target date with undated ones last
+    pub async fn list(pool: &SqlitePool, initiative_id: &str) -> Result<Vec<Milestone>> {
+        let milestones = sqlx::query_as::<_, Milestone>(
+            r#"
+            SELECT * FROM initiative_milestones
+            WHERE initiative_id = ?
+            ORDER BY target_date IS NULL, target_date, milestone_number
+            "#,
+        )
+        .bind(initiative_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(milestones)
+    }
+
+    /// Mark a milestone complete; returns false if it already was
+    pub async fn complete(pool: &SqlitePool, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            "UPDATE initiative_milestones SET completed_at = ?, updated_at = ? WHERE id = ? AND completed_at IS NULL",
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn add_project(
+        pool: &SqlitePool,
+        milestone_id: &str,
+        project_id: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO milestone_projects (milestone_id, project_id) VALUES (?, ?)",
+        )
+        .bind(milestone_id)
+        .bind(project_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn add_task(pool: &SqlitePool, milestone_id: &str, task_id: &str) -> Result<()> {
+        sqlx::query("INSERT OR IGNORE INTO milestone_tasks (milestone_id, task_id) VALUES (?, ?)")
+            .bind(milestone_id)
+            .bind(task_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list_projects(pool: &SqlitePool, milestone_id: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar::<_, String>(
+            "SELECT project_id FROM milestone_projects WHERE milestone_id = ? ORDER BY project_id",
+        )
+        .bind(milestone_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(ids)
+    }
+
+    pub async fn list_tasks(pool: &SqlitePool, milestone_id: &str) -> Result<Vec<String>> {
+        let ids = sqlx::query_scalar::<_, String>(
+            "SELECT task_id FROM milestone_tasks WHERE milestone_id = ? ORDER BY task_id",
+        )
+        .bind(milestone_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(ids)
+    }
+
+    /// Total and done task counts over the attached tasks and every
+    /// unarchived task of the attached projects, each task counted once
+    pub async fn task_counts(pool: &SqlitePool, milestone_id: &str) -> Result<(i64, i64)> {
+        let counts = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(status = 'done'), 0) FROM tasks
+            WHERE archived_at IS NULL AND (
+                id IN (SELECT task_id FROM milestone_tasks WHERE milestone_id = ?)
+                OR project_id IN (SELECT project_id FROM milestone_projects WHERE milestone_id = ?)
+            )
+            "#,
+        )
+        .bind(milestone_id)
+        .bind(milestone_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(counts)
+    }
+}
+
 pub mod tasks {
     use super::*;
 
@@ -366,10 +512,10 @@ pub mod tasks {
         sqlx::query(
             r#"
             INSERT INTO tasks (id, project_id, task_number, parent_task_id, title, description,
-                status, priority, owner, tags, blocked_reason, started_at, completed_at, due_at,
-                claim_owner, claim_claimed_at, claim_lease_expires_at, pinned, focus_weight,
-                created_at, updated_at, version)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                status, priority, owner, author, tags, blocked_reason, started_at, completed_at,
+                due_at, estimate, claim_owner, claim_claimed_at, claim_lease_expires_at,
+                claim_session_id, pinned, focus_weight, created_at, updated_at, version)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&task.id)
@@ -381,14 +527,17 @@ pub mod tasks {
         .bind(&task.status)
         .bind(&task.priority)
         .bind(&task.owner)
+        .bind(&task.author)
         .bind(&task.tags)
         .bind(&task.blocked_reason)
         .bind(&task.started_at)
         .bind(&task.completed_at)
         .bind(&task.due_at)
+        .bind(task.estimate)
         .bind(&task.claim_owner)
         .bind(&task.claim_claimed_at)
         .bind(&task.claim_lease_expires_at)
+        .bind(&task.claim_session_id)
         .bind(task.pinned)
         .bind(task.focus_weight)
         .bind(&task.created_at)
@@ -407,31 +556,47 @@ pub mod tasks {
         Ok(task)
     }
 
-    pub async fn list_by_project(pool: &SqlitePool, project_id: &str) -> Result<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>(
-            "SELECT * FROM tasks WHERE project_id = ? ORDER BY task_number ASC",
-        )
-        .bind(project_id)
-        .fetch_all(pool)
-        .await?;
-        Ok(tasks)
-    }
+    pub async fn list_by_project(
+        pool: &SqlitePool,
+        project_id: &str,
+        include_archived: bool,
+    ) -> Result<Vec<Task>> {
+        let mut query = String::from("SELECT * FROM tasks WHERE project_id = ?");
+        if !include_archived {
+            query.push_str(" AND archived_at IS NULL");
+        }
+        query.push_str(" ORDER BY task_number ASC");
 
-    pub async fn list_all(pool: &SqlitePool) -> Result<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>("SELECT * FROM tasks ORDER BY created_at DESC")
+        let tasks = sqlx::query_as::<_, Task>(&query)
+            .bind(project_id)
             .fetch_all(pool)
             .await?;
         Ok(tasks)
     }
 
+    pub async fn list_all(pool: &SqlitePool, include_archived: bool) -> Result<Vec<Task>> {
+        let mut query = String::from("SELECT * FROM tasks");
+        if !include_archived {
+            query.push_str(" WHERE archived_at IS NULL");
+        }
+        query.push_str(" ORDER BY created_at DESC");
+
+        let tasks = sqlx::query_as::<_, Task>(&query).fetch_all(pool).await?;
+        Ok(tasks)
+    }
+
     pub async fn list_filtered(
         pool: &SqlitePool,
         status: Option<&str>,
         priority: Option<&str>,
         owner: Option<&str>,
+        include_archived: bool,
     ) -> Result<Vec<Task>> {
         let mut query = String::from("SELECT * FROM tasks WHERE 1=1");
 
+        if !include_archived {
+            query.push_str(" AND archived_at IS NULL");
+        }
         if status.is_some() {
             query.push_str(" AND status = ?");
         }
@@ -469,14 +634,35 @@ pub mod tasks {
         Ok(tasks)
     }
 
-    pub async fn update(pool: &SqlitePool, task: &Task) -> Result<bool> {
+    /// Total and remaining estimate per project, over unarchived tasks with
+    /// an estimate
+    pub async fn estimate_totals(pool: &SqlitePool) -> Result<Vec<ProjectEstimate>> {
+        let totals = sqlx::query_as::<_, ProjectEstimate>(
+            r#"
+            SELECT project_id,
+                TOTAL(estimate) AS total,
+                TOTAL(CASE WHEN status != 'done' THEN estimate END) AS remaining
+            FROM tasks
+            WHERE estimate IS NOT NULL AND archived_at IS NULL
+            GROUP BY project_id
+            ORDER BY project_id
+            "#,
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(totals)
+    }
+
+    /// Save a task if its version is unchanged. Accepts a pool or a transaction.
+    pub async fn update<'e>(executor: impl SqliteExecutor<'e>, task: &Task) -> Result<bool> {
         let result = sqlx::query(
             r#"
             UPDATE tasks
             SET title = ?, description = ?, status = ?, priority = ?, owner = ?, tags = ?,
-                blocked_reason = ?, started_at = ?, completed_at = ?, due_at = ?,
+                blocked_reason = ?, started_at = ?, completed_at = ?, due_at = ?, estimate = ?,
                 claim_owner = ?, claim_claimed_at = ?, claim_lease_expires_at = ?,
-                pinned = ?, focus_weight = ?, updated_at = ?, version = version + 1
+                claim_session_id = ?, pinned = ?, focus_weight = ?, archived_at = ?, updated_at = ?,
+                version = version + 1
             WHERE id = ? AND version = ?
             "#,
         )
@@ -490,19 +676,44 @@ pub mod tasks {
         .bind(&task.started_at)
         .bind(&task.completed_at)
         .bind(&task.due_at)
+        .bind(task.estimate)
         .bind(&task.claim_owner)
         .bind(&task.claim_claimed_at)
         .bind(&task.claim_lease_expires_at)
+        .bind(&task.claim_session_id)
         .bind(task.pinned)
         .bind(task.focus_weight)
+        .bind(&task.archived_at)
         .bind(chrono::Utc::now().to_rfc3339())
         .bind(&task.id)
         .bind(task.version)
-        .execute(pool)
+        .execute(executor)
         .await?;
         Ok(result.rows_affected() > 0)
     }
 
+    /// Archive every task in a project that is not archived yet, returning
+    /// the IDs of the tasks archived
+    pub async fn archive_by_project<'e>(
+        executor: impl SqliteExecutor<'e>,
+        project_id: &str,
+    ) -> Result<Vec<String>> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let ids = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE tasks SET archived_at = ?, updated_at = ?, version = version + 1
+            WHERE project_id = ? AND archived_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(project_id)
+        .fetch_all(executor)
+        .await?;
+        Ok(ids)
+    }
+
     /// Get the next actionable task based on the spec algorithm
     pub async fn get_next(
         pool: &SqlitePool,
@@ -519,6 +730,7 @@ pub mod tasks {
             FROM tasks t
             WHERE t.status IS 'todo'
               AND t.blocked_reason IS NULL
+              AND t.archived_at IS NULL
               AND NOT EXISTS (
                   SELECT 1 FROM task_dependencies td
                   JOIN tasks dep ON dep.id = td.depends_on_task_id
@@ -581,6 +793,7 @@ pub mod tasks {
             FROM tasks t
             WHERE t.status IS 'todo'
               AND t.blocked_reason IS NULL
+              AND t.archived_at IS NULL
               AND NOT EXISTS (
                   SELECT 1 FROM task_dependencies td
                   JOIN tasks dep ON dep.id = td.depends_on_task_id
@@ -624,6 +837,65 @@ pub mod tasks {
 
         Ok(tasks)
     }
+
+    /// SQL expression for a task's last activity: its creation, latest
+    /// status change, latest comment, or latest checkpoint that mentions it.
+    /// Evaluated against a `tasks` row.
+    pub const LAST_ACTIVITY_SQL: &str = r#"MAX(
+        tasks.created_at,
+        COALESCE((
+            SELECT MAX(e.created_at) FROM events e
+            WHERE e.entity_type = 'task' AND e.entity_id = tasks.id
+              AND e.event_type IN ('task.status_changed', 'task.started', 'task.completed',
+                                   'task.blocked', 'task.unblocked')
+        ), ''),
+        COALESCE((
+            SELECT MAX(c.created_at) FROM comments c
+            WHERE c.parent_type = 'task' AND c.parent_id = tasks.id
+        ), ''),
+        COALESCE((
+            SELECT MAX(k.created_at) FROM checkpoints k
+            WHERE instr(k.snapshot, '"' || tasks.id || '"') > 0
+        ), '')
+    )"#;
+
+    /// Open, unarchived tasks with no activity since `cutoff`, as
+    /// (task id, last activity) pairs, least recently active first
+    pub async fn list_stale(pool: &SqlitePool, cutoff: &str) -> Result<Vec<(String, String)>> {
+        let sql = format!(
+            r#"
+            SELECT id, last_activity FROM (
+                SELECT tasks.id, {} AS last_activity FROM tasks
+                WHERE tasks.status != 'done' AND tasks.archived_at IS NULL
+            )
+            WHERE last_activity < ?
+            ORDER BY last_activity
+            "#,
+            LAST_ACTIVITY_SQL
+        );
+        let rows = sqlx::query_as::<_, (String, String)>(&sql)
+            .bind(cutoff)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Last activity of each of the given tasks, as (task id, last
+    /// activity) pairs
+    pub async fn list_last_activity(
+        pool: &SqlitePool,
+        task_ids: &[String],
+    ) -> Result<Vec<(String, String)>> {
+        let sql = format!(
+            "SELECT tasks.id, {} FROM tasks WHERE tasks.id IN (SELECT value FROM json_each(?))",
+            LAST_ACTIVITY_SQL
+        );
+        let rows = sqlx::query_as::<_, (String, String)>(&sql)
+            .bind(serde_json::to_string(task_ids)?)
+            .fetch_all(pool)
+            .await?;
+        Ok(rows)
+    }
 }
 
 /// Database operations for task dependencies
@@ -674,32 +946,49 @@ pub mod dependencies {
         Ok(deps)
     }
 
-    /// Check if adding a dependency would create a cycle
-    pub async fn would_create_cycle(
-        pool: &SqlitePool,
-        task_id: &str,
-        depends_on: &str,
-    ) -> Result<bool> {
-        // Check if depends_on transitively depends on task_id
-        // This would create a cycle
-        let result = sqlx::query_scalar::<_, i64>(
+    /// Number of open, unarchived tasks waiting on each task that has any,
+    /// as (task id, count) pairs
+    pub async fn count_open_dependents(pool: &SqlitePool) -> Result<Vec<(String, i64)>> {
+        let counts = sqlx::query_as::<_, (String, i64)>(
             r#"
-            WITH RECURSIVE dep_chain(id) AS (
-                SELECT depends_on_task_id FROM task_dependencies WHERE task_id = ?
-                UNION
-                SELECT td.depends_on_task_id
-                FROM task_dependencies td
-                JOIN dep_chain dc ON td.task_id = dc.id
-            )
-            SELECT COUNT(*) FROM dep_chain WHERE id = ?
+            SELECT td.depends_on_task_id, COUNT(*)
+            FROM task_dependencies td
+            JOIN tasks t ON t.id = td.task_id
+            WHERE t.status != 'done' AND t.archived_at IS NULL
+            GROUP BY td.depends_on_task_id
             "#,
         )
-        .bind(depends_on)
-        .bind(task_id)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await?;
+        Ok(counts)
+    }
 
-        Ok(result > 0)
+    pub async fn list_all(pool: &SqlitePool) -> Result<Vec<TaskDependency>> {
+        let deps = sqlx::query_as::<_, TaskDependency>(
+            "SELECT * FROM task_dependencies ORDER BY task_id, depends_on_task_id",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(deps)
+    }
+
+    /// Dependencies between tasks of one project
+    pub async fn list_by_project(
+        pool: &SqlitePool,
+        project_id: &str,
+    ) -> Result<Vec<TaskDependency>> {
+        let deps = sqlx::query_as::<_, TaskDependency>(
+            r#"
+            SELECT td.* FROM task_dependencies td
+            JOIN tasks t ON t.id = td.task_id
+            JOIN tasks d ON d.id = td.depends_on_task_id
+            WHERE t.project_id = ? AND d.project_id = t.project_id
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(deps)
     }
 
     /// Get all unmet dependencies for a task (dependencies that aren't done)
@@ -862,14 +1151,15 @@ pub mod comments {
     pub async fn create(pool: &SqlitePool, comment: &Comment) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO comments (id, parent_type, parent_id, comment_number, kind, content,
-                author, meta, created_at, updated_at, version)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO comments (id, parent_type, parent_id, parent_comment_id, comment_number,
+                kind, content, author, meta, created_at, updated_at, version)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&comment.id)
         .bind(&comment.parent_type)
         .bind(&comment.parent_id)
+        .bind(&comment.parent_comment_id)
         .bind(comment.comment_number)
         .bind(&comment.kind)
         .bind(&comment.content)
@@ -901,6 +1191,27 @@ pub mod comments {
         Ok(comments)
     }
 
+    /// The comment that starts the thread a reply is in, or None when the
+    /// comment is not a reply
+    pub async fn thread_root(pool: &SqlitePool, id: &str) -> Result<Option<Comment>> {
+        let root = sqlx::query_as::<_, Comment>(
+            r#"
+            WITH RECURSIVE ancestors(id, parent_comment_id, depth) AS (
+                SELECT id, parent_comment_id, 0 FROM comments WHERE id = ?
+                UNION ALL
+                SELECT c.id, c.parent_comment_id, a.depth + 1
+                FROM comments c JOIN ancestors a ON c.id = a.parent_comment_id
+            )
+            SELECT c.* FROM comments c JOIN ancestors a ON c.id = a.id
+            WHERE a.parent_comment_id IS NULL AND a.depth > 0
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(root)
+    }
+
     pub async fn list_by_kind(pool: &SqlitePool, kind: &str) -> Result<Vec<Comment>> {
         let comments = sqlx::query_as::<_, Comment>(
             "SELECT * FROM comments WHERE kind = ? ORDER BY created_at DESC",
@@ -911,6 +1222,16 @@ pub mod comments {
         Ok(comments)
     }
 
+    /// Task ID and author of every task comment that has an author
+    pub async fn list_task_authors(pool: &SqlitePool) -> Result<Vec<(String, String)>> {
+        let authors = sqlx::query_as::<_, (String, String)>(
+            "SELECT parent_id, author FROM comments WHERE parent_type = 'task' AND author IS NOT NULL",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(authors)
+    }
+
     pub async fn update(pool: &SqlitePool, comment: &Comment) -> Result<bool> {
         let result = sqlx::query(
             r#"
@@ -938,9 +1259,9 @@ pub mod sessions {
     pub async fn create(pool: &SqlitePool, session: &Session) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO sessions (id, name, owner, mode, focus_task_id, variables,
+            INSERT INTO sessions (id, name, owner, mode, focus_task_id, focus_stack, variables,
                 created_at, updated_at, closed_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&session.id)
@@ -948,6 +1269,7 @@ pub mod sessions {
         .bind(&session.owner)
         .bind(&session.mode)
         .bind(&session.focus_task_id)
+        .bind(&session.focus_stack)
         .bind(&session.variables)
         .bind(&session.created_at)
         .bind(&session.updated_at)
@@ -984,8 +1306,8 @@ pub mod sessions {
         let result = sqlx::query(
             r#"
             UPDATE sessions
-            SET name = ?, owner = ?, mode = ?, focus_task_id = ?, variables = ?,
-                updated_at = ?, closed_at = ?
+            SET name = ?, owner = ?, mode = ?, focus_task_id = ?, focus_stack = ?,
+                variables = ?, updated_at = ?, closed_at = ?
             WHERE id = ?
             "#,
         )
@@ -993,6 +1315,7 @@ pub mod sessions {
         .bind(&session.owner)
         .bind(&session.mode)
         .bind(&session.focus_task_id)
+        .bind(&session.focus_stack)
         .bind(&session.variables)
         .bind(chrono::Utc::now().to_rfc3339())
         .bind(&session.closed_at)
@@ -1082,8 +1405,13 @@ pub mod sessions {
 pub mod events {
     use super::*;
 
-    pub async fn create(pool: &SqlitePool, event: &CreateEvent) -> Result<i64> {
+    /// Record an event. Accepts a pool or a transaction.
+    ///
+    /// Events without an actor or session are attributed to whoever the
+    /// current command is recorded for.
+    pub async fn create<'e>(executor: impl SqliteExecutor<'e>, event: &CreateEvent) -> Result<i64> {
         let now = chrono::Utc::now().to_rfc3339();
+        let recorded = super::operations::recording_actor().unwrap_or_default();
         let payload = serde_json::to_string(&event.payload)?;
 
         let id = sqlx::query_scalar::<_, i64>(
@@ -1096,11 +1424,11 @@ pub mod events {
         .bind(event.event_type.as_str())
         .bind(event.entity_type.as_str())
         .bind(&event.entity_id)
-        .bind(&event.actor)
-        .bind(&event.session_id)
+        .bind(event.actor.as_ref().or(recorded.name.as_ref()))
+        .bind(event.session_id.as_ref().or(recorded.session_id.as_ref()))
         .bind(&payload)
         .bind(&now)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(id)
@@ -1141,6 +1469,23 @@ pub mod events {
         Ok(events)
     }
 
+    /// Get an event by ID
+    pub async fn get(pool: &SqlitePool, id: i64) -> Result<Option<Event>> {
+        let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(event)
+    }
+
+    /// ID of the most recent event, or 0 if there are none
+    pub async fn latest_id(pool: &SqlitePool) -> Result<i64> {
+        let id = sqlx::query_scalar::<_, i64>("SELECT COALESCE(MAX(id), 0) FROM events")
+            .fetch_one(pool)
+            .await?;
+        Ok(id)
+    }
+
     /// List events since a specific event ID (exclusive)
     ///
     /// This is used for cursor-based event polling. Returns events with IDs
@@ -1224,6 +1569,30 @@ pub mod artifacts {
             .await?;
         Ok(result.rows_affected() > 0)
     }
+
+    /// Delete all artifacts of a task or run
+    pub async fn delete_by_parent(pool: &SqlitePool, parent_id: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM artifacts WHERE parent_id = ?")
+            .bind(parent_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete the artifacts recorded for a worker's runs
+    pub async fn delete_by_worker_runs(pool: &SqlitePool, worker_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM artifacts
+            WHERE parent_type = 'run'
+              AND parent_id IN (SELECT id FROM runs WHERE worker_id = ?)
+            "#,
+        )
+        .bind(worker_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
 }
 
 /// Database operations for checkpoints
@@ -1233,14 +1602,15 @@ pub mod checkpoints {
     pub async fn create(pool: &SqlitePool, checkpoint: &Checkpoint) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO checkpoints (id, session_id, name, snapshot, created_at)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO checkpoints (id, session_id, name, snapshot, author, created_at)
+            VALUES (?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&checkpoint.id)
         .bind(&checkpoint.session_id)
         .bind(&checkpoint.name)
         .bind(&checkpoint.snapshot)
+        .bind(&checkpoint.author)
         .bind(&checkpoint.created_at)
         .execute(pool)
         .await?;
@@ -1279,6 +1649,16 @@ pub mod checkpoints {
         .await?;
         Ok(checkpoints)
     }
+
+    /// Checkpoints of every session, oldest first
+    pub async fn list_all(pool: &SqlitePool) -> Result<Vec<Checkpoint>> {
+        let checkpoints = sqlx::query_as::<_, Checkpoint>(
+            "SELECT * FROM checkpoints ORDER BY created_at ASC, id ASC",
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(checkpoints)
+    }
 }
 
 /// Database operations for counters (monotonic ID generation)
@@ -1299,6 +1679,25 @@ pub mod counters {
         Ok(value)
     }
 
+    /// Raise task, comment and artifact counters to the highest numbers in
+    /// use, so rows copied in from elsewhere don't have their IDs reused
+    pub async fn catch_up(conn: &mut SqliteConnection) -> Result<()> {
+        let sources = [
+            "SELECT 'project:' || project_id || ':task', MAX(task_number) FROM tasks WHERE true GROUP BY project_id",
+            "SELECT parent_type || ':' || parent_id || ':comment', MAX(comment_number) FROM comments WHERE true GROUP BY parent_type, parent_id",
+            "SELECT parent_type || ':' || parent_id || ':artifact', MAX(artifact_number) FROM artifacts WHERE true GROUP BY parent_type, parent_id",
+        ];
+        for source in sources {
+            let sql = format!(
+                "INSERT INTO counters (scope, value) {} \
+                 ON CONFLICT(scope) DO UPDATE SET value = MAX(value, excluded.value)",
+                source
+            );
+            sqlx::query(&sql).execute(&mut *conn).await?;
+        }
+        Ok(())
+    }
+
     pub async fn current(pool: &SqlitePool, scope: &str) -> Result<i64> {
         let value = sqlx::query_scalar::<_, i64>("SELECT value FROM counters WHERE scope = ?")
             .bind(scope)
@@ -1564,161 +1963,1764 @@ pub mod search {
 
     /// Search projects by name (case-insensitive)
     /// TODO: need to migrate this to FTS5
-    pub async fn search_projects(pool: &SqlitePool, query: &str) -> Result<Vec<Project>> {
-        let projects = sqlx::query_as::<_, Project>(
-            r#"
-            SELECT * FROM projects
-            WHERE name LIKE ? COLLATE NOCASE
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(format!("%{}%", query))
-        .fetch_all(pool)
-        .await?;
+    pub async fn search_projects(
+        pool: &SqlitePool,
+        query: &str,
+        include_archived: bool,
+        page: Page,
+    ) -> Result<Vec<Project>> {
+        let mut sql = String::from("SELECT * FROM projects WHERE name LIKE ? COLLATE NOCASE");
+        if !include_archived {
+            sql.push_str(" AND status != 'archived'");
+        }
+        sql.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
+
+        let projects = sqlx::query_as::<_, Project>(&sql)
+            .bind(format!("%{}%", query))
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(pool)
+            .await?;
         Ok(projects)
     }
 
-    /// Search tasks by title (case-insensitive)
-    pub async fn search_tasks(pool: &SqlitePool, query: &str) -> Result<Vec<Task>> {
-        let tasks = sqlx::query_as::<_, Task>(
-            r#"
-            SELECT * FROM tasks
-            WHERE title LIKE ? COLLATE NOCASE
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(format!("%{}%", query))
-        .fetch_all(pool)
-        .await?;
-        Ok(tasks)
-    }
+    /// Search tasks by title or label name (case-insensitive)
+    pub async fn search_tasks(
+        pool: &SqlitePool,
+        query: &str,
+        include_archived: bool,
+        page: Page,
+    ) -> Result<Vec<Task>> {
+        let query = SearchQuery {
+            text: query.to_string(),
+            include_archived,
+            ..Default::default()
+        };
+        find_tasks(pool, &query, None, &Sort::default(), page).await
+    }
+
+    /// Tasks matching a parsed query.
+    ///
+    /// `project_ids` restricts results to those projects. Without a sort,
+    /// scoped tasks are listed in task number order and others newest first.
+    /// Field filters must already be normalized against the workspace schema.
+    pub async fn find_tasks(
+        pool: &SqlitePool,
+        query: &SearchQuery,
+        project_ids: Option<&[String]>,
+        sort: &Sort,
+        page: Page,
+    ) -> Result<Vec<Task>> {
+        let mut sql = String::from("SELECT * FROM tasks WHERE 1=1");
+        let mut binds: Vec<String> = Vec::new();
+
+        if !query.include_archived {
+            sql.push_str(" AND archived_at IS NULL");
+        }
+        if !query.text.is_empty() {
+            let pattern = format!("%{}%", query.text);
+            sql.push_str(
+                r#"
+                AND (title LIKE ? COLLATE NOCASE
+                     OR id IN (
+                         SELECT tl.task_id FROM task_labels tl
+                         JOIN labels l ON l.id = tl.label_id
+                         WHERE l.name LIKE ? COLLATE NOCASE
+                     ))"#,
+            );
+            binds.push(pattern.clone());
+            binds.push(pattern);
+        }
+        if !query.statuses.is_empty() {
+            sql.push_str(" AND status IN (SELECT value FROM json_each(?))");
+            binds.push(serde_json::to_string(&query.statuses)?);
+        }
+        if !query.priorities.is_empty() {
+            sql.push_str(" AND priority IN (SELECT value FROM json_each(?))");
+            binds.push(serde_json::to_string(&query.priorities)?);
+        }
+        if let Some(owner) = &query.owner {
+            sql.push_str(" AND owner = ?");
+            binds.push(owner.clone());
+        }
+        if let Some(author) = &query.author {
+            sql.push_str(" AND author = ?");
+            binds.push(author.clone());
+        }
+        if let Some(project) = &query.project {
+            sql.push_str(" AND project_id = ?");
+            binds.push(project.clone());
+        }
+        if let Some(ids) = project_ids {
+            sql.push_str(" AND project_id IN (SELECT value FROM json_each(?))");
+            binds.push(serde_json::to_string(ids)?);
+        }
+        for label in &query.labels {
+            // labels.name is COLLATE NOCASE
+            sql.push_str(
+                r#"
+                AND EXISTS (
+                    SELECT 1 FROM task_labels tl
+                    JOIN labels l ON l.id = tl.label_id
+                    WHERE tl.task_id = tasks.id AND l.name = ?
+                )"#,
+            );
+            binds.push(label.clone());
+        }
+        if let Some(cutoff) = &query.stale_since {
+            sql.push_str(&format!(
+                " AND status != 'done' AND {} < ?",
+                super::tasks::LAST_ACTIVITY_SQL
+            ));
+            binds.push(cutoff.clone());
+        }
+        for field in &query.fields {
+            match &field.value {
+                Some(value) => {
+                    sql.push_str(
+                        r#"
+                        AND EXISTS (
+                            SELECT 1 FROM custom_fields cf
+                            WHERE cf.entity_type = 'task' AND cf.entity_id = tasks.id
+                              AND cf.name = ? AND cf.value = ? COLLATE NOCASE
+                        )"#,
+                    );
+                    binds.push(field.name.clone());
+                    binds.push(value.clone());
+                }
+                None => {
+                    sql.push_str(
+                        r#"
+                        AND NOT EXISTS (
+                            SELECT 1 FROM custom_fields cf
+                            WHERE cf.entity_type = 'task' AND cf.entity_id = tasks.id
+                              AND cf.name = ?
+                        )"#,
+                    );
+                    binds.push(field.name.clone());
+                }
+            }
+        }
+
+        sql.push_str(&sort.order_by(if project_ids.is_some() {
+            "project_id, task_number ASC"
+        } else {
+            "created_at DESC"
+        }));
+        sql.push_str(" LIMIT ? OFFSET ?");
+
+        let mut q = sqlx::query_as::<_, Task>(&sql);
+        for value in binds {
+            q = q.bind(value);
+        }
+        let tasks = q
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(pool)
+            .await?;
+        Ok(tasks)
+    }
+
+    /// Search initiatives by name (case-insensitive)
+    pub async fn search_initiatives(
+        pool: &SqlitePool,
+        query: &str,
+        include_archived: bool,
+        page: Page,
+    ) -> Result<Vec<crate::models::initiative::Initiative>> {
+        let mut sql = String::from("SELECT * FROM initiatives WHERE name LIKE ? COLLATE NOCASE");
+        if !include_archived {
+            sql.push_str(" AND status != 'archived'");
+        }
+        sql.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
+
+        let initiatives = sqlx::query_as::<_, crate::models::initiative::Initiative>(&sql)
+            .bind(format!("%{}%", query))
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(pool)
+            .await?;
+        Ok(initiatives)
+    }
+}
+
+/// Database operations for getting next tasks across an initiative
+/// This respects both project-to-project dependencies and task-to-task dependencies
+pub mod initiative_tasks {
+    use super::*;
+
+    /// Get all unblocked tasks across an initiative.
+    ///
+    /// A task is actionable only if:
+    /// 1. Its project has no unmet project dependencies (all dependency projects have all tasks done)
+    /// 2. The task itself has no unmet task dependencies (all dependency tasks are done)
+    /// 3. The task is not blocked (status != blocked, no blocked_reason)
+    /// 4. The task is todo or in_progress
+    ///
+    /// Results are sorted by priority (P0 first), due_at (earliest first), created_at, and id (for determinism).
+    pub async fn get_next(pool: &SqlitePool, initiative_id: &str, all: bool) -> Result<Vec<Task>> {
+        // Step 1: Get all project IDs in the initiative
+        let project_rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT p.id FROM projects p
+            JOIN initiative_projects ip ON p.id = ip.project_id
+            WHERE ip.initiative_id = ?
+            "#,
+        )
+        .bind(initiative_id)
+        .fetch_all(pool)
+        .await?;
+
+        if project_rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let all_project_ids: Vec<String> = project_rows.into_iter().map(|(id,)| id).collect();
+
+        // Step 2: Find projects with unmet dependencies
+        // A project has unmet dependencies if any of its dependencies has incomplete tasks
+        let mut blocked_project_ids: Vec<String> = Vec::new();
+
+        for project_id in &all_project_ids {
+            // Check if this project has any unmet project dependencies
+            let unmet_count: (i64,) = sqlx::query_as(
+                r#"
+                SELECT COUNT(*) FROM project_dependencies pd
+                WHERE pd.project_id = ?
+                AND EXISTS (
+                    SELECT 1 FROM tasks t
+                    WHERE t.project_id = pd.depends_on_project_id
+                    AND t.status != 'done'
+                )
+                "#,
+            )
+            .bind(project_id)
+            .fetch_one(pool)
+            .await?;
+
+            if unmet_count.0 > 0 {
+                blocked_project_ids.push(project_id.clone());
+            }
+        }
+
+        // Step 3: Get unblocked project IDs
+        let unblocked_project_ids: Vec<&String> = all_project_ids
+            .iter()
+            .filter(|id| !blocked_project_ids.contains(id))
+            .collect();
+
+        if unblocked_project_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Step 4: Query for unblocked tasks in unblocked projects
+        let json_project_ids = serde_json::to_string(&unblocked_project_ids)?;
+
+        let limit = if all { 1000i32 } else { 1i32 };
+
+        let tasks = sqlx::query_as::<_, Task>(
+            r#"
+            SELECT t.*
+            FROM tasks t
+            WHERE t.project_id IN (SELECT value FROM json_each(?))
+              AND t.status IN ('todo', 'in_progress')
+              AND t.blocked_reason IS NULL
+              AND t.archived_at IS NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM task_dependencies td
+                  JOIN tasks dep ON dep.id = td.depends_on_task_id
+                  WHERE td.task_id = t.id
+                    AND dep.status != 'done'
+              )
+            ORDER BY
+                CASE t.priority
+                    WHEN 'P0' THEN 0
+                    WHEN 'P1' THEN 1
+                    WHEN 'P2' THEN 2
+                    WHEN 'P3' THEN 3
+                    WHEN 'P4' THEN 4
+                END,
+                t.due_at NULLS LAST,
+                t.created_at,
+                t.id
+            LIMIT ?
+            "#,
+        )
+        .bind(&json_project_ids)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tasks)
+    }
+}
+
+/// Database operations for task attachments
+pub mod attachments {
+    use super::*;
+
+    /// Record an attachment, replacing any with the same name on the task
+    pub async fn upsert(
+        pool: &SqlitePool,
+        task_id: &str,
+        name: &str,
+        path: &str,
+        source: &str,
+        mode: AttachmentMode,
+        size_bytes: i64,
+    ) -> Result<Attachment> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let attachment = sqlx::query_as::<_, Attachment>(
+            r#"
+            INSERT INTO attachments (task_id, name, path, source, mode, size_bytes, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(task_id, name) DO UPDATE SET
+                path = excluded.path,
+                source = excluded.source,
+                mode = excluded.mode,
+                size_bytes = excluded.size_bytes,
+                created_at = excluded.created_at
+            RETURNING *
+            "#,
+        )
+        .bind(task_id)
+        .bind(name)
+        .bind(path)
+        .bind(source)
+        .bind(mode.as_str())
+        .bind(size_bytes)
+        .bind(&now)
+        .fetch_one(pool)
+        .await?;
+        Ok(attachment)
+    }
+
+    pub async fn list_by_task(pool: &SqlitePool, task_id: &str) -> Result<Vec<Attachment>> {
+        let attachments = sqlx::query_as::<_, Attachment>(
+            "SELECT * FROM attachments WHERE task_id = ? ORDER BY name",
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(attachments)
+    }
+}
+
+/// Database operations for rule firings
+pub mod rule_firings {
+    use super::*;
+
+    /// The task version a rule last fired at, if it has fired for the task
+    pub async fn fired_version(
+        pool: &SqlitePool,
+        rule: &str,
+        task_id: &str,
+    ) -> Result<Option<i64>> {
+        let version = sqlx::query_scalar::<_, i64>(
+            "SELECT task_version FROM rule_firings WHERE rule = ? AND task_id = ?",
+        )
+        .bind(rule)
+        .bind(task_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(version)
+    }
+
+    pub async fn record(
+        pool: &SqlitePool,
+        rule: &str,
+        task_id: &str,
+        task_version: i64,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO rule_firings (rule, task_id, task_version, fired_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(rule, task_id) DO UPDATE SET
+                task_version = excluded.task_version,
+                fired_at = excluded.fired_at
+            "#,
+        )
+        .bind(rule)
+        .bind(task_id)
+        .bind(task_version)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Database operations for due date reminders
+pub mod task_reminders {
+    use super::*;
+
+    /// The due date a reminder of this kind was last sent for, if any
+    pub async fn sent_for(pool: &SqlitePool, task_id: &str, kind: &str) -> Result<Option<String>> {
+        let due_at = sqlx::query_scalar::<_, String>(
+            "SELECT due_at FROM task_reminders WHERE task_id = ? AND kind = ?",
+        )
+        .bind(task_id)
+        .bind(kind)
+        .fetch_optional(pool)
+        .await?;
+        Ok(due_at)
+    }
+
+    pub async fn record(pool: &SqlitePool, task_id: &str, kind: &str, due_at: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO task_reminders (task_id, kind, due_at, sent_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(task_id, kind) DO UPDATE SET
+                due_at = excluded.due_at,
+                sent_at = excluded.sent_at
+            "#,
+        )
+        .bind(task_id)
+        .bind(kind)
+        .bind(due_at)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Database operations for typed task links
+pub mod task_links {
+    use super::*;
+
+    /// Record a link; returns false when it already exists
+    pub async fn add(
+        pool: &SqlitePool,
+        task_id: &str,
+        linked_task_id: &str,
+        kind: &str,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO task_links (task_id, linked_task_id, kind, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(task_id)
+        .bind(linked_task_id)
+        .bind(kind)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove every link between two tasks, in either direction
+    pub async fn remove_between(pool: &SqlitePool, a: &str, b: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM task_links
+            WHERE (task_id = ? AND linked_task_id = ?) OR (task_id = ? AND linked_task_id = ?)
+            "#,
+        )
+        .bind(a)
+        .bind(b)
+        .bind(b)
+        .bind(a)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Links recorded on or pointing at a task, oldest first
+    pub async fn list_for_task(pool: &SqlitePool, task_id: &str) -> Result<Vec<TaskLink>> {
+        let links = sqlx::query_as::<_, TaskLink>(
+            r#"
+            SELECT * FROM task_links
+            WHERE task_id = ? OR linked_task_id = ?
+            ORDER BY created_at, rowid
+            "#,
+        )
+        .bind(task_id)
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(links)
+    }
+}
+
+/// Database operations for task status history
+pub mod status_history {
+    use super::*;
+
+    /// Tasks currently done whose last transition to done was at or after
+    /// `since`, as (task id, completed at, estimate)
+    pub async fn completions_since(
+        pool: &SqlitePool,
+        project_id: Option<&str>,
+        since: &str,
+    ) -> Result<Vec<(String, String, Option<f64>)>> {
+        let rows = sqlx::query_as::<_, (String, String, Option<f64>)>(
+            r#"
+            SELECT t.id, MAX(h.changed_at) AS done_at, t.estimate
+            FROM task_status_history h
+            JOIN tasks t ON t.id = h.task_id
+            WHERE h.to_status = 'done' AND t.status = 'done'
+              AND (? IS NULL OR t.project_id = ?)
+            GROUP BY t.id
+            HAVING done_at >= ?
+            ORDER BY done_at
+            "#,
+        )
+        .bind(project_id)
+        .bind(project_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Status changes of `task_ids` made between `since` and `until`,
+    /// oldest first
+    pub async fn list_for_tasks(
+        pool: &SqlitePool,
+        task_ids: &[String],
+        since: &str,
+        until: &str,
+    ) -> Result<Vec<StatusTransition>> {
+        let ids = serde_json::to_string(task_ids)?;
+        let transitions = sqlx::query_as::<_, StatusTransition>(
+            r#"
+            SELECT task_id, from_status, to_status, changed_at
+            FROM task_status_history
+            WHERE task_id IN (SELECT value FROM json_each(?))
+              AND changed_at >= ? AND changed_at <= ?
+            ORDER BY changed_at, id
+            "#,
+        )
+        .bind(ids)
+        .bind(since)
+        .bind(until)
+        .fetch_all(pool)
+        .await?;
+        Ok(transitions)
+    }
+}
+
+/// Database operations for task VCS links
+pub mod vcs_links {
+    use super::*;
+
+    /// Record a link. Returns false if the task is already linked to this ref.
+    pub async fn add(
+        pool: &SqlitePool,
+        task_id: &str,
+        kind: VcsLinkKind,
+        ref_name: &str,
+        summary: Option<&str>,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO task_vcs_links (task_id, kind, ref_name, summary, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(task_id)
+        .bind(kind.as_str())
+        .bind(ref_name)
+        .bind(summary)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_by_task(pool: &SqlitePool, task_id: &str) -> Result<Vec<TaskVcsLink>> {
+        let links = sqlx::query_as::<_, TaskVcsLink>(
+            "SELECT * FROM task_vcs_links WHERE task_id = ? ORDER BY kind, created_at, id",
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(links)
+    }
+
+    /// Find links by ref, matching commit SHAs by prefix
+    pub async fn list_by_ref(pool: &SqlitePool, ref_name: &str) -> Result<Vec<TaskVcsLink>> {
+        let links = sqlx::query_as::<_, TaskVcsLink>(
+            r#"
+            SELECT * FROM task_vcs_links
+            WHERE ref_name = ? OR (kind = 'commit' AND ref_name LIKE ? || '%')
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(ref_name)
+        .bind(ref_name)
+        .fetch_all(pool)
+        .await?;
+        Ok(links)
+    }
+
+    /// Remove a link by ref, matching commit SHAs by prefix
+    pub async fn remove(pool: &SqlitePool, task_id: &str, ref_name: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM task_vcs_links
+            WHERE task_id = ? AND (ref_name = ? OR (kind = 'commit' AND ref_name LIKE ? || '%'))
+            "#,
+        )
+        .bind(task_id)
+        .bind(ref_name)
+        .bind(ref_name)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Database operations for task labels
+pub mod labels {
+    use super::*;
+
+    /// Add and remove labels on a task in a single transaction.
+    ///
+    /// Labels are created on first use, and labels no longer attached to any
+    /// task are deleted.
+    pub async fn apply(
+        pool: &SqlitePool,
+        task_id: &str,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        apply_in(&mut tx, task_id, add, remove).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Like [`apply`], but within a caller's transaction
+    pub async fn apply_in(
+        conn: &mut SqliteConnection,
+        task_id: &str,
+        add: &[String],
+        remove: &[String],
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        for name in add {
+            sqlx::query("INSERT OR IGNORE INTO labels (name, created_at) VALUES (?, ?)")
+                .bind(name)
+                .bind(&now)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO task_labels (task_id, label_id, created_at)
+                SELECT ?, id, ? FROM labels WHERE name = ?
+                "#,
+            )
+            .bind(task_id)
+            .bind(&now)
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        for name in remove {
+            sqlx::query(
+                r#"
+                DELETE FROM task_labels
+                WHERE task_id = ? AND label_id IN (SELECT id FROM labels WHERE name = ?)
+                "#,
+            )
+            .bind(task_id)
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        if !remove.is_empty() {
+            sqlx::query(
+                "DELETE FROM labels WHERE id NOT IN (SELECT DISTINCT label_id FROM task_labels)",
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// All label names, sorted by name
+    pub async fn list_names(pool: &SqlitePool) -> Result<Vec<String>> {
+        let names =
+            sqlx::query_scalar::<_, String>("SELECT name FROM labels ORDER BY name COLLATE NOCASE")
+                .fetch_all(pool)
+                .await?;
+        Ok(names)
+    }
+
+    /// Label names attached to a task, sorted by name
+    pub async fn list_by_task(pool: &SqlitePool, task_id: &str) -> Result<Vec<String>> {
+        let names = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT l.name FROM labels l
+            JOIN task_labels tl ON tl.label_id = l.id
+            WHERE tl.task_id = ?
+            ORDER BY l.name COLLATE NOCASE
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(names)
+    }
+
+    /// Labels of several tasks at once, sorted by task and name
+    pub async fn list_for_tasks(pool: &SqlitePool, task_ids: &[&str]) -> Result<Vec<TaskLabel>> {
+        let ids_json = serde_json::to_string(task_ids)?;
+        let labels = sqlx::query_as::<_, TaskLabel>(
+            r#"
+            SELECT tl.task_id, l.name FROM task_labels tl
+            JOIN labels l ON l.id = tl.label_id
+            WHERE tl.task_id IN (SELECT value FROM json_each(?))
+            ORDER BY tl.task_id, l.name COLLATE NOCASE
+            "#,
+        )
+        .bind(&ids_json)
+        .fetch_all(pool)
+        .await?;
+        Ok(labels)
+    }
+}
+
+pub mod custom_fields {
+    use super::*;
+
+    /// Set and clear custom fields on an entity in a single transaction
+    pub async fn apply(
+        pool: &SqlitePool,
+        entity_type: &str,
+        entity_id: &str,
+        set: &[(String, String)],
+        clear: &[String],
+    ) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        apply_in(&mut tx, entity_type, entity_id, set, clear).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Like [`apply`], but within a caller's transaction
+    pub async fn apply_in(
+        conn: &mut SqliteConnection,
+        entity_type: &str,
+        entity_id: &str,
+        set: &[(String, String)],
+        clear: &[String],
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        for (name, value) in set {
+            sqlx::query(
+                r#"
+                INSERT INTO custom_fields (entity_type, entity_id, name, value, updated_at)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(entity_type, entity_id, name)
+                DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(entity_type)
+            .bind(entity_id)
+            .bind(name)
+            .bind(value)
+            .bind(&now)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        for name in clear {
+            sqlx::query(
+                "DELETE FROM custom_fields WHERE entity_type = ? AND entity_id = ? AND name = ?",
+            )
+            .bind(entity_type)
+            .bind(entity_id)
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Field values of several entities at once, sorted by entity and name
+    pub async fn list_for(
+        pool: &SqlitePool,
+        entity_type: &str,
+        entity_ids: &[&str],
+    ) -> Result<Vec<CustomField>> {
+        let ids_json = serde_json::to_string(entity_ids)?;
+        let fields = sqlx::query_as::<_, CustomField>(
+            r#"
+            SELECT entity_id, name, value FROM custom_fields
+            WHERE entity_type = ? AND entity_id IN (SELECT value FROM json_each(?))
+            ORDER BY entity_id, name
+            "#,
+        )
+        .bind(entity_type)
+        .bind(&ids_json)
+        .fetch_all(pool)
+        .await?;
+        Ok(fields)
+    }
+}
+
+/// Raw table access for workspace export and import.
+///
+/// Rows are read and written as JSON objects keyed by column name, so an
+/// export preserves every column (including IDs) without a typed model per table.
+pub mod export {
+    use super::*;
+    use serde_json::{Map, Value};
+    use sqlx::sqlite::SqliteRow;
+    use sqlx::{Column, Row, TypeInfo, ValueRef};
+
+    /// A table row keyed by column name
+    pub type RawRow = Map<String, Value>;
+
+    /// Highest applied migration version, used to detect exports from newer schemas
+    pub async fn schema_version(pool: &SqlitePool) -> Result<i64> {
+        let version =
+            sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations")
+                .fetch_one(pool)
+                .await?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// Column names of a table, in declaration order
+    pub async fn table_columns(pool: &SqlitePool, table: &str) -> Result<Vec<String>> {
+        let columns = sqlx::query_scalar::<_, String>("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(pool)
+            .await?;
+        Ok(columns)
+    }
+
+    /// Read every row of a table in insertion order.
+    ///
+    /// `table` must be a trusted identifier; it is interpolated into the query.
+    pub async fn dump_table(pool: &SqlitePool, table: &str) -> Result<Vec<RawRow>> {
+        let query = format!("SELECT * FROM {} ORDER BY rowid", table);
+        let rows = sqlx::query(&query).fetch_all(pool).await?;
+        rows.iter().map(row_to_json).collect()
+    }
+
+    pub(crate) fn row_to_json(row: &SqliteRow) -> Result<RawRow> {
+        let mut map = Map::new();
+        for column in row.columns() {
+            let raw = row.try_get_raw(column.ordinal())?;
+            let value = if raw.is_null() {
+                Value::Null
+            } else {
+                match raw.type_info().name() {
+                    "INTEGER" => Value::from(row.try_get::<i64, _>(column.ordinal())?),
+                    "REAL" => Value::from(row.try_get::<f64, _>(column.ordinal())?),
+                    "TEXT" => Value::from(row.try_get::<String, _>(column.ordinal())?),
+                    other => {
+                        return Err(crate::error::GranaryError::Conflict(format!(
+                            "Cannot export column {} of type {}",
+                            column.name(),
+                            other
+                        )));
+                    }
+                }
+            };
+            map.insert(column.name().to_string(), value);
+        }
+        Ok(map)
+    }
+
+    /// Insert rows into their tables in a single transaction.
+    ///
+    /// Foreign keys are checked at commit, so tables may be given in any order.
+    /// Column names must already be validated against the target schema.
+    pub async fn insert_rows(pool: &SqlitePool, tables: &[(&str, &[RawRow])]) -> Result<u64> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+
+        let mut inserted = 0;
+        for (table, rows) in tables {
+            for row in rows.iter() {
+                let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+                let placeholders = vec!["?"; columns.len()].join(", ");
+                let query = format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table,
+                    columns.join(", "),
+                    placeholders
+                );
+
+                let mut q = sqlx::query(&query);
+                for value in row.values() {
+                    q = bind_json(q, value);
+                }
+                q.execute(&mut *tx).await?;
+                inserted += 1;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Bind a JSON value as the SQLite value it was read from
+    pub(crate) fn bind_json<'q>(
+        q: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+        value: &Value,
+    ) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+        match value {
+            Value::Null => q.bind(None::<String>),
+            Value::Bool(b) => q.bind(*b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => q.bind(i),
+                None => q.bind(n.as_f64()),
+            },
+            Value::String(s) => q.bind(s.clone()),
+            other => q.bind(other.to_string()),
+        }
+    }
+}
+
+/// Operation log for `granary undo`.
+///
+/// While a command is being recorded, every connection in its pool carries
+/// temporary triggers that copy row changes in the tracked tables into
+/// `operation_changes`. Temporary triggers are local to the connection, so
+/// writes from other processes, such as the daemon, are never attributed to
+/// the command.
+pub mod operations {
+    use super::export::{RawRow, bind_json, row_to_json};
+    use super::*;
+    use crate::error::GranaryError;
+    use serde_json::Value;
+    use sqlx::FromRow;
+    use std::sync::OnceLock;
+
+    /// Tables whose changes are recorded and can be undone
+    pub const TRACKED_TABLES: &[&str] = &[
+        "initiatives",
+        "projects",
+        "initiative_projects",
+        "project_dependencies",
+        "tasks",
+        "task_dependencies",
+        "task_vcs_links",
+        "labels",
+        "task_labels",
+        "custom_fields",
+        "comments",
+        "artifacts",
+        "steering",
+        "config",
+        "sync_links",
+        "sync_cursors",
+    ];
+
+    struct Recording {
+        operation_id: String,
+        actor: Actor,
+    }
+
+    static RECORDING: OnceLock<Recording> = OnceLock::new();
+
+    tokio::task_local! {
+        /// Who the request being served is recorded for, in long-running
+        /// servers that record an operation per request
+        static REQUEST_ACTOR: Actor;
+    }
+
+    /// Record changes made through workspace pools opened from now on,
+    /// attributing them and any events this process emits to `actor`
+    pub fn start_recording(operation_id: String, actor: Actor) {
+        let _ = RECORDING.set(Recording {
+            operation_id,
+            actor,
+        });
+    }
+
+    /// The operation this process is recording, if any
+    pub fn recording() -> Option<&'static str> {
+        RECORDING.get().map(|r| r.operation_id.as_str())
+    }
+
+    /// Run `future` with the events and records it creates attributed to
+    /// `actor`, in place of whoever the process is recording for
+    pub async fn with_actor<F: std::future::Future>(actor: Actor, future: F) -> F::Output {
+        REQUEST_ACTOR.scope(actor, future).await
+    }
+
+    /// Who the current request, or else this process, is recording changes
+    /// for, if either is recording
+    pub fn recording_actor() -> Option<Actor> {
+        REQUEST_ACTOR
+            .try_with(Actor::clone)
+            .ok()
+            .or_else(|| RECORDING.get().map(|r| r.actor.clone()))
+    }
+
+    /// Install the recording triggers on a connection.
+    ///
+    /// Does nothing until the migration creating the log has run, so the
+    /// connection that applies it is not recorded.
+    pub async fn install_triggers(
+        conn: &mut SqliteConnection,
+        operation_id: &str,
+    ) -> sqlx::Result<()> {
+        let has_log: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'operation_changes')",
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        if !has_log {
+            return Ok(());
+        }
+
+        let operation_id = operation_id.replace('\'', "''");
+        for table in TRACKED_TABLES {
+            let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+                .bind(table)
+                .fetch_all(&mut *conn)
+                .await?;
+            if columns.is_empty() {
+                continue;
+            }
+
+            let snapshot = |row: &str| {
+                let pairs: Vec<String> = columns
+                    .iter()
+                    .map(|c| format!("'{}', {}.\"{}\"", c, row, c))
+                    .collect();
+                format!("json_object({})", pairs.join(", "))
+            };
+            let (old, new) = (snapshot("OLD"), snapshot("NEW"));
+            let triggers = [
+                ("insert", String::new(), "NULL".to_string(), new.clone()),
+                (
+                    "update",
+                    format!(" WHEN {} IS NOT {}", old, new),
+                    old.clone(),
+                    new,
+                ),
+                ("delete", String::new(), old, "NULL".to_string()),
+            ];
+            for (action, when, before, after) in triggers {
+                let sql = format!(
+                    "CREATE TEMP TRIGGER IF NOT EXISTS granary_undo_{table}_{action} \
+                     AFTER {event} ON main.{table}{when} BEGIN \
+                     INSERT INTO operation_changes (operation_id, table_name, action, before, after) \
+                     VALUES ('{operation_id}', '{table}', '{action}', {before}, {after}); END",
+                    event = action.to_uppercase(),
+                );
+                sqlx::query(&sql).execute(&mut *conn).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Save a recorded operation if the command changed anything.
+    /// Returns whether an operation was saved.
+    pub async fn finish(
+        pool: &SqlitePool,
+        operation_id: &str,
+        command: &str,
+        actor: &Actor,
+        undoable: bool,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO operations (id, command, actor, session_id, undoable, created_at)
+            SELECT ?, ?, ?, ?, ?, ?
+            WHERE EXISTS (SELECT 1 FROM operation_changes WHERE operation_id = ?)
+            "#,
+        )
+        .bind(operation_id)
+        .bind(command)
+        .bind(&actor.name)
+        .bind(&actor.session_id)
+        .bind(undoable)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(operation_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The most recent operations that have not been undone, newest first
+    pub async fn list_undoable(pool: &SqlitePool, limit: u32) -> Result<Vec<Operation>> {
+        let operations = sqlx::query_as::<_, Operation>(
+            r#"
+            SELECT o.*,
+                   (SELECT COUNT(*) FROM operation_changes c WHERE c.operation_id = o.id) AS changes
+            FROM operations o
+            WHERE o.undone_at IS NULL AND o.undoable = 1
+            ORDER BY o.created_at DESC, o.rowid DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+        Ok(operations)
+    }
+
+    /// Reverse operations in the order given, all in one transaction.
+    ///
+    /// Each change is checked against the current row first, so an operation
+    /// whose rows were modified since, for example by another process, is
+    /// refused instead of overwriting the newer data.
+    pub async fn undo(pool: &SqlitePool, operations: &[Operation]) -> Result<()> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        for operation in operations {
+            let changes = sqlx::query_as::<_, OperationChange>(
+                "SELECT * FROM operation_changes WHERE operation_id = ? ORDER BY id DESC",
+            )
+            .bind(&operation.id)
+            .fetch_all(&mut *tx)
+            .await?;
+            for change in &changes {
+                revert(&mut tx, change).await?;
+            }
+
+            sqlx::query("UPDATE operations SET undone_at = ? WHERE id = ?")
+                .bind(&now)
+                .bind(&operation.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revert(conn: &mut SqliteConnection, change: &OperationChange) -> Result<()> {
+        let table = change.table_name.as_str();
+        if !TRACKED_TABLES.contains(&table) {
+            return Err(GranaryError::Conflict(format!(
+                "Cannot undo changes to table {}",
+                table
+            )));
+        }
+        let before = parse_snapshot(change.before.as_deref())?;
+        let after = parse_snapshot(change.after.as_deref())?;
+
+        let mut keys: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM pragma_table_info(?) WHERE pk > 0 ORDER BY pk")
+                .bind(table)
+                .fetch_all(&mut *conn)
+                .await?;
+        let Some(reference) = after.as_ref().or(before.as_ref()) else {
+            return Ok(());
+        };
+        if keys.is_empty() {
+            keys = reference.keys().cloned().collect();
+        }
+        let key_values: Vec<&Value> = keys
+            .iter()
+            .map(|k| reference.get(k).unwrap_or(&Value::Null))
+            .collect();
+        let condition = keys
+            .iter()
+            .map(|k| format!("\"{}\" = ?", k))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        // The row must still look the way the operation left it
+        let select = format!("SELECT * FROM {} WHERE {}", table, condition);
+        let mut q = sqlx::query(&select);
+        for value in &key_values {
+            q = bind_json(q, value);
+        }
+        let current = q
+            .fetch_optional(&mut *conn)
+            .await?
+            .map(|row| row_to_json(&row))
+            .transpose()?;
+        if current != after {
+            let key = key_values
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| v.to_string())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(GranaryError::Conflict(format!(
+                "Cannot undo: {} row {} has changed since",
+                table, key
+            )));
+        }
+
+        let (sql, values): (String, Vec<&Value>) = match &before {
+            // Undo an insert
+            None => (
+                format!("DELETE FROM {} WHERE {}", table, condition),
+                key_values,
+            ),
+            // Undo a delete
+            Some(before) if after.is_none() => {
+                let columns: Vec<String> = before.keys().map(|c| format!("\"{}\"", c)).collect();
+                (
+                    format!(
+                        "INSERT INTO {} ({}) VALUES ({})",
+                        table,
+                        columns.join(", "),
+                        vec!["?"; columns.len()].join(", ")
+                    ),
+                    before.values().collect(),
+                )
+            }
+            // Undo an update
+            Some(before) => {
+                let assignments: Vec<String> =
+                    before.keys().map(|c| format!("\"{}\" = ?", c)).collect();
+                let mut values: Vec<&Value> = before.values().collect();
+                values.extend(key_values);
+                (
+                    format!(
+                        "UPDATE {} SET {} WHERE {}",
+                        table,
+                        assignments.join(", "),
+                        condition
+                    ),
+                    values,
+                )
+            }
+        };
+        let mut q = sqlx::query(&sql);
+        for value in values {
+            q = bind_json(q, value);
+        }
+        q.execute(&mut *conn).await?;
+        Ok(())
+    }
+
+    #[derive(FromRow)]
+    struct AuditRow {
+        id: i64,
+        operation_id: String,
+        command: String,
+        actor: Option<String>,
+        session_id: Option<String>,
+        created_at: String,
+        undone_at: Option<String>,
+        table_name: String,
+        action: String,
+        entity_id: Option<String>,
+        before: Option<String>,
+        after: Option<String>,
+    }
+
+    /// Recorded changes matching a filter, newest first
+    pub async fn audit(
+        pool: &SqlitePool,
+        filter: &AuditFilter,
+        page: Page,
+    ) -> Result<Vec<AuditEntry>> {
+        // The row's own key: its ID, or the owning entity for join tables
+        let snapshot = "COALESCE(c.after, c.before)";
+        let mut sql = format!(
+            r#"
+            SELECT c.id, c.operation_id, o.command, o.actor, o.session_id, o.created_at,
+                   o.undone_at, c.table_name, c.action,
+                   CAST(COALESCE(json_extract({s}, '$.id'), json_extract({s}, '$.task_id'),
+                        json_extract({s}, '$.project_id'), json_extract({s}, '$.key'),
+                        json_extract({s}, '$.name')) AS TEXT) AS entity_id,
+                   c.before, c.after
+            FROM operation_changes c
+            JOIN operations o ON o.id = c.operation_id
+            WHERE 1 = 1
+            "#,
+            s = snapshot
+        );
+        if filter.entity.is_some() {
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM json_each({}) \
+                 WHERE (key = 'id' OR key = 'key' OR key LIKE '%\\_id' ESCAPE '\\') AND value = ?)",
+                snapshot
+            ));
+        }
+        if filter.since.is_some() {
+            sql.push_str(" AND o.created_at >= ?");
+        }
+        if filter.actor.is_some() {
+            sql.push_str(" AND o.actor = ? COLLATE NOCASE");
+        }
+        sql.push_str(" ORDER BY c.id DESC LIMIT ? OFFSET ?");
+
+        let mut query = sqlx::query_as::<_, AuditRow>(&sql);
+        for value in [&filter.entity, &filter.since, &filter.actor]
+            .into_iter()
+            .flatten()
+        {
+            query = query.bind(value);
+        }
+        let rows = query
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(pool)
+            .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let parse = |snapshot: Option<String>| {
+                    snapshot
+                        .map(|s| serde_json::from_str(&s).map_err(GranaryError::from))
+                        .transpose()
+                };
+                Ok(AuditEntry {
+                    id: row.id,
+                    operation_id: row.operation_id,
+                    command: row.command,
+                    actor: row.actor,
+                    session_id: row.session_id,
+                    created_at: row.created_at,
+                    undone_at: row.undone_at,
+                    table_name: row.table_name,
+                    action: row.action,
+                    entity_id: row.entity_id,
+                    before: parse(row.before)?,
+                    after: parse(row.after)?,
+                })
+            })
+            .collect()
+    }
 
-    /// Search initiatives by name (case-insensitive)
-    pub async fn search_initiatives(
-        pool: &SqlitePool,
-        query: &str,
-    ) -> Result<Vec<crate::models::initiative::Initiative>> {
-        let initiatives = sqlx::query_as::<_, crate::models::initiative::Initiative>(
-            r#"
-            SELECT * FROM initiatives
-            WHERE name LIKE ? COLLATE NOCASE
-            ORDER BY created_at DESC
-            "#,
-        )
-        .bind(format!("%{}%", query))
-        .fetch_all(pool)
-        .await?;
-        Ok(initiatives)
+    fn parse_snapshot(snapshot: Option<&str>) -> Result<Option<RawRow>> {
+        snapshot
+            .map(|s| serde_json::from_str(s).map_err(GranaryError::from))
+            .transpose()
     }
 }
 
-/// Database operations for getting next tasks across an initiative
-/// This respects both project-to-project dependencies and task-to-task dependencies
-pub mod initiative_tasks {
+/// Database operations for remotes synchronised by `granary push` and
+/// `granary pull`
+pub mod remotes {
+    use super::export::{RawRow, bind_json};
     use super::*;
+    use serde_json::Value;
+    use sqlx::FromRow;
+    use std::collections::HashSet;
 
-    /// Get all unblocked tasks across an initiative.
-    ///
-    /// A task is actionable only if:
-    /// 1. Its project has no unmet project dependencies (all dependency projects have all tasks done)
-    /// 2. The task itself has no unmet task dependencies (all dependency tasks are done)
-    /// 3. The task is not blocked (status != blocked, no blocked_reason)
-    /// 4. The task is todo or in_progress
-    ///
-    /// Results are sorted by priority (P0 first), due_at (earliest first), created_at, and id (for determinism).
-    pub async fn get_next(pool: &SqlitePool, initiative_id: &str, all: bool) -> Result<Vec<Task>> {
-        // Step 1: Get all project IDs in the initiative
-        let project_rows: Vec<(String,)> = sqlx::query_as(
+    /// A change from the operation log, with the time its operation ran
+    #[derive(Debug, Clone, FromRow)]
+    pub struct LocalChange {
+        pub id: i64,
+        pub table_name: String,
+        pub before: Option<String>,
+        pub after: Option<String>,
+        pub changed_at: String,
+    }
+
+    impl LocalChange {
+        /// The row after the change, or before it for deletes
+        pub fn snapshot(&self) -> Result<Option<RawRow>> {
+            self.after
+                .as_deref()
+                .or(self.before.as_deref())
+                .map(|s| serde_json::from_str(s).map_err(crate::error::GranaryError::from))
+                .transpose()
+        }
+
+        pub fn is_delete(&self) -> bool {
+            self.after.is_none()
+        }
+    }
+
+    pub async fn create(pool: &SqlitePool, name: &str, url: &str) -> Result<Remote> {
+        let remote = sqlx::query_as::<_, Remote>(
             r#"
-            SELECT p.id FROM projects p
-            JOIN initiative_projects ip ON p.id = ip.project_id
-            WHERE ip.initiative_id = ?
+            INSERT INTO remotes (name, url, created_at)
+            VALUES (?, ?, ?)
+            RETURNING *
             "#,
         )
-        .bind(initiative_id)
-        .fetch_all(pool)
+        .bind(name)
+        .bind(url)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .fetch_one(pool)
         .await?;
+        Ok(remote)
+    }
 
-        if project_rows.is_empty() {
-            return Ok(Vec::new());
-        }
+    pub async fn get(pool: &SqlitePool, name: &str) -> Result<Option<Remote>> {
+        let remote = sqlx::query_as::<_, Remote>("SELECT * FROM remotes WHERE name = ?")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?;
+        Ok(remote)
+    }
 
-        let all_project_ids: Vec<String> = project_rows.into_iter().map(|(id,)| id).collect();
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<Remote>> {
+        let remotes = sqlx::query_as::<_, Remote>("SELECT * FROM remotes ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+        Ok(remotes)
+    }
 
-        // Step 2: Find projects with unmet dependencies
-        // A project has unmet dependencies if any of its dependencies has incomplete tasks
-        let mut blocked_project_ids: Vec<String> = Vec::new();
+    /// Delete a remote and what is known of its batches
+    pub async fn delete(pool: &SqlitePool, name: &str) -> Result<bool> {
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM remote_batches WHERE remote = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM remotes WHERE name = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
 
-        for project_id in &all_project_ids {
-            // Check if this project has any unmet project dependencies
-            let unmet_count: (i64,) = sqlx::query_as(
-                r#"
-                SELECT COUNT(*) FROM project_dependencies pd
-                WHERE pd.project_id = ?
-                AND EXISTS (
-                    SELECT 1 FROM tasks t
-                    WHERE t.project_id = pd.depends_on_project_id
-                    AND t.status != 'done'
-                )
-                "#,
-            )
-            .bind(project_id)
+    pub async fn set_pushed<'e>(
+        executor: impl SqliteExecutor<'e>,
+        name: &str,
+        change_id: i64,
+    ) -> Result<()> {
+        sqlx::query("UPDATE remotes SET pushed_change_id = ?, pushed_at = ? WHERE name = ?")
+            .bind(change_id)
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(name)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_pulled<'e>(executor: impl SqliteExecutor<'e>, name: &str) -> Result<()> {
+        sqlx::query("UPDATE remotes SET pulled_at = ? WHERE name = ?")
+            .bind(chrono::Utc::now().to_rfc3339())
+            .bind(name)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    /// Names of the batches on a remote that were pushed from or applied here
+    pub async fn known_batches(pool: &SqlitePool, remote: &str) -> Result<HashSet<String>> {
+        let batches =
+            sqlx::query_scalar::<_, String>("SELECT batch FROM remote_batches WHERE remote = ?")
+                .bind(remote)
+                .fetch_all(pool)
+                .await?;
+        Ok(batches.into_iter().collect())
+    }
+
+    pub async fn add_batch<'e>(
+        executor: impl SqliteExecutor<'e>,
+        remote: &str,
+        batch: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO remote_batches (remote, batch, synced_at) VALUES (?, ?, ?)",
+        )
+        .bind(remote)
+        .bind(batch)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// ID of the newest change in the operation log
+    pub async fn last_change_id(pool: &SqlitePool) -> Result<i64> {
+        let id = sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(id) FROM operation_changes")
             .fetch_one(pool)
             .await?;
+        Ok(id.unwrap_or(0))
+    }
 
-            if unmet_count.0 > 0 {
-                blocked_project_ids.push(project_id.clone());
-            }
-        }
+    /// Changes recorded after `after_id`, oldest first. `granary pull` is not
+    /// recorded, so these are all changes made here.
+    pub async fn changes_since(pool: &SqlitePool, after_id: i64) -> Result<Vec<LocalChange>> {
+        let changes = sqlx::query_as::<_, LocalChange>(
+            r#"
+            SELECT c.id, c.table_name, c.before, c.after, o.created_at AS changed_at
+            FROM operation_changes c
+            JOIN operations o ON o.id = c.operation_id
+            WHERE c.id > ?
+            ORDER BY c.id
+            "#,
+        )
+        .bind(after_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(changes)
+    }
 
-        // Step 3: Get unblocked project IDs
-        let unblocked_project_ids: Vec<&String> = all_project_ids
+    /// Columns identifying a row: the primary key, or every column for
+    /// tables without one.
+    ///
+    /// `table` must be a trusted identifier; it is interpolated into queries
+    /// built from the result.
+    pub async fn key_columns<'e>(
+        executor: impl SqliteExecutor<'e>,
+        table: &str,
+    ) -> Result<Vec<String>> {
+        let columns = sqlx::query_as::<_, (String, i64)>(
+            "SELECT name, pk FROM pragma_table_info(?) ORDER BY pk, cid",
+        )
+        .bind(table)
+        .fetch_all(executor)
+        .await?;
+        let keys: Vec<String> = columns
             .iter()
-            .filter(|id| !blocked_project_ids.contains(id))
+            .filter(|(_, pk)| *pk > 0)
+            .map(|(name, _)| name.clone())
             .collect();
+        if keys.is_empty() {
+            Ok(columns.into_iter().map(|(name, _)| name).collect())
+        } else {
+            Ok(keys)
+        }
+    }
 
-        if unblocked_project_ids.is_empty() {
-            return Ok(Vec::new());
+    /// When a row last changed, as of the last push or pull
+    pub async fn clock(
+        conn: &mut SqliteConnection,
+        table: &str,
+        row_key: &str,
+    ) -> Result<Option<String>> {
+        let changed_at = sqlx::query_scalar::<_, String>(
+            "SELECT changed_at FROM remote_row_clocks WHERE table_name = ? AND row_key = ?",
+        )
+        .bind(table)
+        .bind(row_key)
+        .fetch_optional(&mut *conn)
+        .await?;
+        Ok(changed_at)
+    }
+
+    pub async fn set_clock(
+        conn: &mut SqliteConnection,
+        table: &str,
+        row_key: &str,
+        changed_at: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO remote_row_clocks (table_name, row_key, changed_at) VALUES (?, ?, ?)
+            ON CONFLICT(table_name, row_key) DO UPDATE SET changed_at = excluded.changed_at
+            "#,
+        )
+        .bind(table)
+        .bind(row_key)
+        .bind(changed_at)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Make a row match a pulled change: delete it, or update or insert it
+    /// with the pulled values. Columns this schema lacks are ignored.
+    ///
+    /// `change.table` must be a trusted identifier.
+    pub async fn apply(conn: &mut SqliteConnection, change: &RowChange) -> Result<()> {
+        let table = change.table.as_str();
+        let columns: HashSet<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+        if change.key.is_empty() || change.key.keys().any(|k| !columns.contains(k)) {
+            return Err(crate::error::GranaryError::Conflict(format!(
+                "Cannot apply change to {}: unknown key {}",
+                table,
+                change.row_key()
+            )));
         }
+        let condition = change
+            .key
+            .keys()
+            .map(|k| format!("\"{}\" = ?", k))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let key_values: Vec<&Value> = change.key.values().collect();
+
+        let Some(row) = &change.row else {
+            let sql = format!("DELETE FROM {} WHERE {}", table, condition);
+            let mut q = sqlx::query(&sql);
+            for value in key_values {
+                q = bind_json(q, value);
+            }
+            q.execute(&mut *conn).await?;
+            return Ok(());
+        };
 
-        // Step 4: Query for unblocked tasks in unblocked projects
-        let json_project_ids = serde_json::to_string(&unblocked_project_ids)?;
+        let row: Vec<(&String, &Value)> =
+            row.iter().filter(|(c, _)| columns.contains(*c)).collect();
+        let exists_sql = format!("SELECT 1 FROM {} WHERE {} LIMIT 1", table, condition);
+        let mut exists = sqlx::query(&exists_sql);
+        for value in &key_values {
+            exists = bind_json(exists, value);
+        }
 
-        let limit = if all { 1000i32 } else { 1i32 };
+        let (sql, values): (String, Vec<&Value>) =
+            if exists.fetch_optional(&mut *conn).await?.is_some() {
+                let assignments: Vec<String> =
+                    row.iter().map(|(c, _)| format!("\"{}\" = ?", c)).collect();
+                let mut values: Vec<&Value> = row.iter().map(|(_, v)| *v).collect();
+                values.extend(key_values);
+                (
+                    format!(
+                        "UPDATE {} SET {} WHERE {}",
+                        table,
+                        assignments.join(", "),
+                        condition
+                    ),
+                    values,
+                )
+            } else {
+                let names: Vec<String> = row.iter().map(|(c, _)| format!("\"{}\"", c)).collect();
+                (
+                    format!(
+                        "INSERT INTO {} ({}) VALUES ({})",
+                        table,
+                        names.join(", "),
+                        vec!["?"; names.len()].join(", ")
+                    ),
+                    row.iter().map(|(_, v)| *v).collect(),
+                )
+            };
+        let mut q = sqlx::query(&sql);
+        for value in values {
+            q = bind_json(q, value);
+        }
+        q.execute(&mut *conn).await?;
+        Ok(())
+    }
+}
 
-        let tasks = sqlx::query_as::<_, Task>(
+/// Database operations for links to external trackers and their sync cursors
+pub mod sync {
+    use super::*;
+
+    pub async fn get_link(
+        pool: &SqlitePool,
+        provider: &str,
+        entity_type: &str,
+        local_id: &str,
+    ) -> Result<Option<SyncLink>> {
+        let link = sqlx::query_as::<_, SyncLink>(
+            "SELECT * FROM sync_links WHERE provider = ? AND entity_type = ? AND local_id = ?",
+        )
+        .bind(provider)
+        .bind(entity_type)
+        .bind(local_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(link)
+    }
+
+    pub async fn get_link_by_remote(
+        pool: &SqlitePool,
+        provider: &str,
+        entity_type: &str,
+        remote_id: &str,
+    ) -> Result<Option<SyncLink>> {
+        let link = sqlx::query_as::<_, SyncLink>(
+            "SELECT * FROM sync_links WHERE provider = ? AND entity_type = ? AND remote_id = ?",
+        )
+        .bind(provider)
+        .bind(entity_type)
+        .bind(remote_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(link)
+    }
+
+    pub async fn list_links(pool: &SqlitePool, provider: &str) -> Result<Vec<SyncLink>> {
+        let links = sqlx::query_as::<_, SyncLink>(
+            "SELECT * FROM sync_links WHERE provider = ? ORDER BY entity_type, local_id",
+        )
+        .bind(provider)
+        .fetch_all(pool)
+        .await?;
+        Ok(links)
+    }
+
+    /// Insert a link, or replace the link of the same local entity
+    pub async fn upsert_link(pool: &SqlitePool, link: &SyncLink) -> Result<()> {
+        sqlx::query(
             r#"
-            SELECT t.*
-            FROM tasks t
-            WHERE t.project_id IN (SELECT value FROM json_each(?))
-              AND t.status IN ('todo', 'in_progress')
-              AND t.blocked_reason IS NULL
-              AND NOT EXISTS (
-                  SELECT 1 FROM task_dependencies td
-                  JOIN tasks dep ON dep.id = td.depends_on_task_id
-                  WHERE td.task_id = t.id
-                    AND dep.status != 'done'
-              )
-            ORDER BY
-                CASE t.priority
-                    WHEN 'P0' THEN 0
-                    WHEN 'P1' THEN 1
-                    WHEN 'P2' THEN 2
-                    WHEN 'P3' THEN 3
-                    WHEN 'P4' THEN 4
-                END,
-                t.due_at NULLS LAST,
-                t.created_at,
-                t.id
-            LIMIT ?
+            INSERT INTO sync_links (provider, entity_type, local_id, remote_id, remote_key,
+                local_updated_at, remote_updated_at, synced_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(provider, entity_type, local_id) DO UPDATE SET
+                remote_id = excluded.remote_id,
+                remote_key = excluded.remote_key,
+                local_updated_at = excluded.local_updated_at,
+                remote_updated_at = excluded.remote_updated_at,
+                synced_at = excluded.synced_at
             "#,
         )
-        .bind(&json_project_ids)
-        .bind(limit)
-        .fetch_all(pool)
+        .bind(&link.provider)
+        .bind(&link.entity_type)
+        .bind(&link.local_id)
+        .bind(&link.remote_id)
+        .bind(&link.remote_key)
+        .bind(&link.local_updated_at)
+        .bind(&link.remote_updated_at)
+        .bind(&link.synced_at)
+        .execute(pool)
         .await?;
+        Ok(())
+    }
 
-        Ok(tasks)
+    pub async fn get_cursor(
+        pool: &SqlitePool,
+        provider: &str,
+        entity_type: &str,
+    ) -> Result<Option<String>> {
+        let cursor = sqlx::query_scalar::<_, String>(
+            "SELECT cursor FROM sync_cursors WHERE provider = ? AND entity_type = ?",
+        )
+        .bind(provider)
+        .bind(entity_type)
+        .fetch_optional(pool)
+        .await?;
+        Ok(cursor)
+    }
+
+    pub async fn set_cursor(
+        pool: &SqlitePool,
+        provider: &str,
+        entity_type: &str,
+        cursor: &str,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO sync_cursors (provider, entity_type, cursor, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(provider, entity_type) DO UPDATE SET
+                cursor = excluded.cursor,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(provider)
+        .bind(entity_type)
+        .bind(cursor)
+        .bind(&now)
+        .execute(pool)
+        .await?;
+        Ok(())
     }
 }
 
@@ -1735,12 +3737,19 @@ pub mod workers {
         let now = chrono::Utc::now().to_rfc3339();
         let args_json = serde_json::to_string(&input.args)?;
         let filters_json = serde_json::to_string(&input.filters)?;
+        let retry_policy_json = input
+            .retry_policy
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
 
         sqlx::query(
             r#"
             INSERT INTO workers (id, runner_name, command, args, event_type, filters,
-                concurrency, instance_path, status, poll_cooldown_secs, detached, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'pending', ?, ?, ?, ?)
+                concurrency, instance_path, status, poll_cooldown_secs, detached, schedule,
+                retry_policy, timeout_secs, debounce_secs, throttle_secs, stdin, global,
+                on_pause, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'pending', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
@@ -1753,6 +3762,14 @@ pub mod workers {
         .bind(&input.instance_path)
         .bind(input.poll_cooldown_secs)
         .bind(input.detached)
+        .bind(&input.schedule)
+        .bind(&retry_policy_json)
+        .bind(input.timeout_secs)
+        .bind(input.debounce_secs)
+        .bind(input.throttle_secs)
+        .bind(input.stdin)
+        .bind(input.global)
+        .bind(input.on_pause.as_str())
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -1770,7 +3787,9 @@ pub mod workers {
     const WORKER_COLUMNS: &str = r#"
         id, runner_name, command, args, event_type, filters, concurrency,
         instance_path, status, error_message, pid, detached, created_at,
-        updated_at, stopped_at, poll_cooldown_secs, last_event_id
+        updated_at, stopped_at, poll_cooldown_secs, last_event_id, schedule,
+        next_fire_at, retry_policy, timeout_secs, debounce_secs, throttle_secs, stdin,
+        global, on_pause
     "#;
 
     /// Get a worker by ID
@@ -1906,12 +3925,12 @@ pub mod workers {
         Ok(workers)
     }
 
-    /// List active workers (running or pending)
+    /// List active workers (running, paused or pending)
     ///
     /// This is used by WorkerManager to filter out stopped/errored workers.
     pub async fn list_active(pool: &SqlitePool) -> Result<Vec<Worker>> {
         let query = format!(
-            "SELECT {} FROM workers WHERE status IN ('running', 'pending') ORDER BY created_at DESC",
+            "SELECT {} FROM workers WHERE status IN ('running', 'paused', 'pending') ORDER BY created_at DESC",
             WORKER_COLUMNS
         );
         let workers = sqlx::query_as::<_, Worker>(&query).fetch_all(pool).await?;
@@ -1927,14 +3946,69 @@ pub mod workers {
         Ok(count)
     }
 
-    /// Update the last_event_id cursor for a worker
-    ///
-    /// This is used for event polling to track which events have been processed.
-    pub async fn update_cursor(pool: &SqlitePool, id: &str, last_event_id: i64) -> Result<bool> {
+    /// Update the last_event_id cursor for a worker
+    ///
+    /// This is used for event polling to track which events have been processed.
+    pub async fn update_cursor(pool: &SqlitePool, id: &str, last_event_id: i64) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result =
+            sqlx::query("UPDATE workers SET last_event_id = ?, updated_at = ? WHERE id = ?")
+                .bind(last_event_id)
+                .bind(&now)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Event cursor of a global worker in one workspace, if it has one
+    pub async fn get_workspace_cursor(
+        pool: &SqlitePool,
+        id: &str,
+        instance_path: &str,
+    ) -> Result<Option<i64>> {
+        let cursor = sqlx::query_scalar::<_, i64>(
+            "SELECT last_event_id FROM worker_cursors WHERE worker_id = ? AND instance_path = ?",
+        )
+        .bind(id)
+        .bind(instance_path)
+        .fetch_optional(pool)
+        .await?;
+        Ok(cursor)
+    }
+
+    /// Set the event cursor of a global worker in one workspace
+    pub async fn update_workspace_cursor(
+        pool: &SqlitePool,
+        id: &str,
+        instance_path: &str,
+        last_event_id: i64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_cursors (worker_id, instance_path, last_event_id)
+            VALUES (?, ?, ?)
+            ON CONFLICT (worker_id, instance_path) DO UPDATE SET last_event_id = excluded.last_event_id
+            "#,
+        )
+        .bind(id)
+        .bind(instance_path)
+        .bind(last_event_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Update the persisted next fire time for a scheduled worker
+    pub async fn update_next_fire(
+        pool: &SqlitePool,
+        id: &str,
+        next_fire_at: Option<&str>,
+    ) -> Result<bool> {
         let now = chrono::Utc::now().to_rfc3339();
         let result =
-            sqlx::query("UPDATE workers SET last_event_id = ?, updated_at = ? WHERE id = ?")
-                .bind(last_event_id)
+            sqlx::query("UPDATE workers SET next_fire_at = ?, updated_at = ? WHERE id = ?")
+                .bind(next_fire_at)
                 .bind(&now)
                 .bind(id)
                 .execute(pool)
@@ -1948,7 +4022,10 @@ pub mod workers {
 pub mod runs {
     use super::*;
     use crate::models::ids::generate_run_id;
-    use crate::models::run::{CreateRun, Run, RunStatus, ScheduleRetry, UpdateRunStatus};
+    use crate::models::run::{
+        CreateRun, Run, RunProgress, RunStatus, RunUsage, ScheduleRetry, TokenUsage,
+        UpdateRunStatus,
+    };
 
     /// Create a new run record
     pub async fn create(pool: &SqlitePool, input: &CreateRun) -> Result<Run> {
@@ -1959,8 +4036,8 @@ pub mod runs {
         sqlx::query(
             r#"
             INSERT INTO runs (id, worker_id, event_id, event_type, entity_id, command, args,
-                status, attempt, max_attempts, log_path, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, 'pending', 1, ?, ?, ?, ?)
+                status, attempt, max_attempts, log_path, instance_path, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, 'pending', 1, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(&id)
@@ -1972,6 +4049,7 @@ pub mod runs {
         .bind(&args_json)
         .bind(input.max_attempts)
         .bind(&input.log_path)
+        .bind(&input.instance_path)
         .bind(&now)
         .bind(&now)
         .execute(pool)
@@ -2005,6 +4083,123 @@ pub mod runs {
         Ok(runs)
     }
 
+    /// Runs triggered by events on an entity, such as a task, newest first
+    pub async fn list_by_entity(pool: &SqlitePool, entity_id: &str) -> Result<Vec<Run>> {
+        let runs = sqlx::query_as::<_, Run>(
+            "SELECT * FROM runs WHERE entity_id = ? ORDER BY created_at DESC",
+        )
+        .bind(entity_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    /// Runs with a log file, oldest first, optionally limited to one worker
+    /// and to runs created at or after `since`
+    pub async fn list_with_logs(
+        pool: &SqlitePool,
+        worker_id: Option<&str>,
+        since: Option<&str>,
+    ) -> Result<Vec<Run>> {
+        let runs = sqlx::query_as::<_, Run>(
+            r#"
+            SELECT * FROM runs
+            WHERE log_path IS NOT NULL
+              AND (?1 IS NULL OR worker_id = ?1)
+              AND (?2 IS NULL OR created_at >= ?2)
+            ORDER BY created_at ASC, id ASC
+            "#,
+        )
+        .bind(worker_id)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    /// Forget a run's log file once log retention has deleted it
+    pub async fn clear_log_path(pool: &SqlitePool, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query("UPDATE runs SET log_path = NULL, updated_at = ? WHERE id = ?")
+            .bind(&now)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Failed, killed and timed out runs created since `since` by the
+    /// workers of one workspace
+    pub async fn list_failing_for_instance(
+        pool: &SqlitePool,
+        instance_path: &str,
+        since: &str,
+    ) -> Result<Vec<Run>> {
+        let runs = sqlx::query_as::<_, Run>(
+            r#"
+            SELECT r.* FROM runs r
+            JOIN workers w ON w.id = r.worker_id
+            WHERE w.instance_path = ?
+              AND r.status IN ('failed', 'killed', 'timed_out')
+              AND r.created_at >= ?
+            ORDER BY r.created_at DESC
+            "#,
+        )
+        .bind(instance_path)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    /// Runs created since `since` in one workspace that reported token
+    /// usage or cost, including the runs of global workers
+    pub async fn list_with_token_usage_for_instance(
+        pool: &SqlitePool,
+        instance_path: &str,
+        since: &str,
+    ) -> Result<Vec<Run>> {
+        let runs = sqlx::query_as::<_, Run>(
+            r#"
+            SELECT r.* FROM runs r
+            JOIN workers w ON w.id = r.worker_id
+            WHERE COALESCE(r.instance_path, w.instance_path) = ?
+              AND (r.input_tokens IS NOT NULL OR r.output_tokens IS NOT NULL
+                   OR r.cost_usd IS NOT NULL)
+              AND r.created_at >= ?
+            ORDER BY r.created_at DESC
+            "#,
+        )
+        .bind(instance_path)
+        .bind(since)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    /// Runs of one workspace triggered by any of `event_ids`, oldest first
+    pub async fn list_for_events(
+        pool: &SqlitePool,
+        instance_path: &str,
+        event_ids: &[i64],
+    ) -> Result<Vec<Run>> {
+        let ids = serde_json::to_string(event_ids)?;
+        let runs = sqlx::query_as::<_, Run>(
+            r#"
+            SELECT r.* FROM runs r
+            JOIN workers w ON w.id = r.worker_id
+            WHERE COALESCE(r.instance_path, w.instance_path) = ?
+              AND r.event_id IN (SELECT value FROM json_each(?))
+            ORDER BY r.created_at
+            "#,
+        )
+        .bind(instance_path)
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
     /// List all runs (global list)
     pub async fn list_all(pool: &SqlitePool) -> Result<Vec<Run>> {
         let runs = sqlx::query_as::<_, Run>("SELECT * FROM runs ORDER BY created_at DESC")
@@ -2013,6 +4208,42 @@ pub mod runs {
         Ok(runs)
     }
 
+    /// List one page of runs, most recent first unless sorted otherwise.
+    ///
+    /// Only runs with one of `statuses` are returned. `queued` further limits
+    /// them to first attempts still waiting to start.
+    pub async fn list_filtered(
+        pool: &SqlitePool,
+        worker_id: Option<&str>,
+        statuses: &[RunStatus],
+        queued: bool,
+        sort: &Sort,
+        page: Page,
+    ) -> Result<Vec<Run>> {
+        let mut query =
+            String::from("SELECT * FROM runs WHERE status IN (SELECT value FROM json_each(?))");
+        if worker_id.is_some() {
+            query.push_str(" AND worker_id = ?");
+        }
+        if queued {
+            query.push_str(" AND status = 'pending' AND attempt = 1");
+        }
+        query.push_str(&sort.order_by("created_at DESC"));
+        query.push_str(" LIMIT ? OFFSET ?");
+
+        let statuses: Vec<&str> = statuses.iter().map(RunStatus::as_str).collect();
+        let mut q = sqlx::query_as::<_, Run>(&query).bind(serde_json::to_string(&statuses)?);
+        if let Some(worker_id) = worker_id {
+            q = q.bind(worker_id);
+        }
+        let runs = q
+            .bind(page.sql_limit())
+            .bind(page.sql_offset())
+            .fetch_all(pool)
+            .await?;
+        Ok(runs)
+    }
+
     /// List runs pending retry (where next_retry_at is before the given time)
     pub async fn list_pending_retries(pool: &SqlitePool, before_time: &str) -> Result<Vec<Run>> {
         let runs = sqlx::query_as::<_, Run>(
@@ -2053,9 +4284,11 @@ pub mod runs {
         // Determine started_at and completed_at based on status
         let (started_at, completed_at) = match update.status {
             RunStatus::Running => (Some(now.clone()), None),
-            RunStatus::Completed | RunStatus::Failed | RunStatus::Cancelled => {
-                (None, Some(now.clone()))
-            }
+            RunStatus::Completed
+            | RunStatus::Failed
+            | RunStatus::Cancelled
+            | RunStatus::Killed
+            | RunStatus::TimedOut => (None, Some(now.clone())),
             _ => (None, None),
         };
 
@@ -2083,6 +4316,94 @@ pub mod runs {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Record whether a stopped or timed-out run exited after SIGTERM, or
+    /// had to be killed
+    pub async fn set_exited_gracefully(
+        pool: &SqlitePool,
+        id: &str,
+        exited_gracefully: bool,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result =
+            sqlx::query("UPDATE runs SET exited_gracefully = ?, updated_at = ? WHERE id = ?")
+                .bind(exited_gracefully)
+                .bind(&now)
+                .bind(id)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record the resources a run's process used, once it has exited
+    pub async fn set_usage(pool: &SqlitePool, id: &str, usage: &RunUsage) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET max_rss_kb = ?, user_cpu_ms = ?, system_cpu_ms = ?, duration_ms = ?,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(usage.max_rss_kb)
+        .bind(usage.user_cpu_ms)
+        .bind(usage.system_cpu_ms)
+        .bind(usage.duration_ms)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Record a runner's progress report. A report without a percentage
+    /// keeps the previous one.
+    pub async fn update_progress(
+        pool: &SqlitePool,
+        id: &str,
+        progress: &RunProgress,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET progress = COALESCE(?, progress), progress_message = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(progress.percent)
+        .bind(&progress.message)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add a runner's usage report to the run's totals. Fields the report
+    /// leaves out are kept.
+    pub async fn add_token_usage(pool: &SqlitePool, id: &str, usage: &TokenUsage) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET input_tokens = COALESCE(input_tokens + ?1, input_tokens, ?1),
+                output_tokens = COALESCE(output_tokens + ?2, output_tokens, ?2),
+                cost_usd = COALESCE(cost_usd + ?3, cost_usd, ?3),
+                updated_at = ?4
+            WHERE id = ?5
+            "#,
+        )
+        .bind(usage.input_tokens)
+        .bind(usage.output_tokens)
+        .bind(usage.cost_usd)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Schedule a retry for a run
     pub async fn update_for_retry(
         pool: &SqlitePool,
@@ -2094,12 +4415,41 @@ pub mod runs {
         let result = sqlx::query(
             r#"
             UPDATE runs
-            SET status = 'pending', next_retry_at = ?, attempt = ?, updated_at = ?
+            SET status = 'pending', next_retry_at = ?, attempt = ?, error_message = ?,
+                progress = NULL, progress_message = NULL, updated_at = ?
             WHERE id = ?
             "#,
         )
         .bind(&retry.next_retry_at)
         .bind(retry.attempt)
+        .bind(&retry.error_message)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Requeue a finished run for an immediate retry.
+    ///
+    /// Clears the previous outcome, bumps the attempt number and raises
+    /// `max_attempts` if needed so the new attempt is within the limit.
+    pub async fn requeue(pool: &SqlitePool, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET status = 'pending', attempt = attempt + 1,
+                max_attempts = MAX(max_attempts, attempt + 1),
+                next_retry_at = ?, exit_code = NULL, error_message = NULL,
+                pid = NULL, completed_at = NULL, progress = NULL, progress_message = NULL,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(&now)
         .bind(&now)
         .bind(id)
         .execute(pool)
@@ -2157,21 +4507,83 @@ pub mod runs {
         Ok(result.rows_affected())
     }
 
-    /// Get pending runs (not yet started, not retries)
+    /// Get pending runs (not yet started, not retries) in dispatch order:
+    /// highest priority first, then oldest first.
+    ///
+    /// These are the runs queued behind the worker's concurrency limit.
     pub async fn get_pending(pool: &SqlitePool, worker_id: &str) -> Result<Vec<Run>> {
         let runs = sqlx::query_as::<_, Run>(
             r#"
             SELECT * FROM runs
             WHERE worker_id = ? AND status = 'pending' AND attempt = 1
-            ORDER BY created_at ASC
+            ORDER BY priority DESC, created_at ASC
+            "#,
+        )
+        .bind(worker_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(runs)
+    }
+
+    /// List queued runs, grouped by worker and in dispatch order within each
+    pub async fn list_queued(pool: &SqlitePool, worker_id: Option<&str>) -> Result<Vec<Run>> {
+        let runs = sqlx::query_as::<_, Run>(
+            r#"
+            SELECT * FROM runs
+            WHERE status = 'pending' AND attempt = 1 AND (? IS NULL OR worker_id = ?)
+            ORDER BY worker_id, priority DESC, created_at ASC
             "#,
         )
         .bind(worker_id)
+        .bind(worker_id)
         .fetch_all(pool)
         .await?;
         Ok(runs)
     }
 
+    /// Move a queued run to the front of its worker's queue. Returns false if
+    /// the run is not queued (it may have started in the meantime).
+    pub async fn bump(pool: &SqlitePool, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET priority = (
+                    SELECT MAX(queued.priority) + 1 FROM runs AS queued
+                    WHERE queued.worker_id = runs.worker_id
+                      AND queued.status = 'pending' AND queued.attempt = 1
+                ),
+                updated_at = ?
+            WHERE id = ? AND status = 'pending' AND attempt = 1
+            "#,
+        )
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Cancel a queued run before it starts. Returns false if the run is not
+    /// queued (it may have started in the meantime).
+    pub async fn drop_queued(pool: &SqlitePool, id: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE runs
+            SET status = 'cancelled', error_message = 'Dropped from the queue',
+                completed_at = ?, updated_at = ?
+            WHERE id = ? AND status = 'pending' AND attempt = 1
+            "#,
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Count runs by status for a worker
     pub async fn count_by_status_for_worker(
         pool: &SqlitePool,
@@ -2216,3 +4628,93 @@ pub mod runs {
         Ok(runs)
     }
 }
+
+/// Database operations for the workspace registry
+/// The registry is stored in the GLOBAL database (~/.granary/workers.db)
+pub mod workspaces {
+    use super::*;
+
+    /// Add a workspace, or mark it used if it is already registered
+    pub async fn register(
+        pool: &SqlitePool,
+        name: &str,
+        root: &str,
+    ) -> Result<RegisteredWorkspace> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let workspace = sqlx::query_as::<_, RegisteredWorkspace>(
+            r#"
+            INSERT INTO workspaces (name, root, created_at, last_used_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(root) DO UPDATE SET last_used_at = excluded.last_used_at
+            RETURNING *
+            "#,
+        )
+        .bind(name)
+        .bind(root)
+        .bind(&now)
+        .bind(&now)
+        .fetch_one(pool)
+        .await?;
+        Ok(workspace)
+    }
+
+    pub async fn get(pool: &SqlitePool, name: &str) -> Result<Option<RegisteredWorkspace>> {
+        let workspace =
+            sqlx::query_as::<_, RegisteredWorkspace>("SELECT * FROM workspaces WHERE name = ?")
+                .bind(name)
+                .fetch_optional(pool)
+                .await?;
+        Ok(workspace)
+    }
+
+    pub async fn get_by_root(pool: &SqlitePool, root: &str) -> Result<Option<RegisteredWorkspace>> {
+        let workspace =
+            sqlx::query_as::<_, RegisteredWorkspace>("SELECT * FROM workspaces WHERE root = ?")
+                .bind(root)
+                .fetch_optional(pool)
+                .await?;
+        Ok(workspace)
+    }
+
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<RegisteredWorkspace>> {
+        let workspaces =
+            sqlx::query_as::<_, RegisteredWorkspace>("SELECT * FROM workspaces ORDER BY name")
+                .fetch_all(pool)
+                .await?;
+        Ok(workspaces)
+    }
+
+    pub async fn rename(pool: &SqlitePool, name: &str, new_name: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE workspaces SET name = ? WHERE name = ?")
+            .bind(new_name)
+            .bind(name)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete(pool: &SqlitePool, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM workspaces WHERE name = ?")
+            .bind(name)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Task counts and the last task update in a workspace database:
+    /// (tasks, tasks not done, last update)
+    pub async fn task_activity(pool: &SqlitePool) -> Result<(i64, i64, Option<String>)> {
+        let activity = sqlx::query_as::<_, (i64, i64, Option<String>)>(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(SUM(CASE WHEN status != 'done' THEN 1 ELSE 0 END), 0),
+                   MAX(updated_at)
+            FROM tasks
+            WHERE archived_at IS NULL
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(activity)
+    }
+}
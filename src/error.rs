@@ -39,6 +39,9 @@ pub enum GranaryError {
     #[error("Initiative not found: {0}")]
     InitiativeNotFound(String),
 
+    #[error("Milestone not found: {0}")]
+    MilestoneNotFound(String),
+
     #[error("Worker not found: {0}")]
     WorkerNotFound(String),
 
@@ -111,6 +114,12 @@ pub enum GranaryError {
     #[error("Daemon error: {0}")]
     DaemonError(String),
 
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Command not allowed: {0}")]
+    CommandNotAllowed(String),
+
     #[error("{0}")]
     Other(String),
 }
@@ -125,7 +134,10 @@ impl GranaryError {
     pub fn exit_code(&self) -> i32 {
         match self {
             // User errors (bad arguments, invalid input)
-            GranaryError::InvalidArgument(_) | GranaryError::InvalidId(_) => exit_codes::USER_ERROR,
+            GranaryError::InvalidArgument(_)
+            | GranaryError::InvalidId(_)
+            | GranaryError::Encryption(_)
+            | GranaryError::CommandNotAllowed(_) => exit_codes::USER_ERROR,
 
             // Not found errors
             GranaryError::WorkspaceNotFound
@@ -136,6 +148,7 @@ impl GranaryError {
             | GranaryError::CheckpointNotFound(_)
             | GranaryError::ArtifactNotFound(_)
             | GranaryError::InitiativeNotFound(_)
+            | GranaryError::MilestoneNotFound(_)
             | GranaryError::WorkerNotFound(_)
             | GranaryError::RunNotFound(_)
             | GranaryError::RunnerNotFound(_)
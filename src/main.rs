@@ -1,18 +1,128 @@
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, ValueEnum};
 use std::process::ExitCode;
 
-use granary::cli::args::{Cli, Commands};
+use granary::cli::args::{
+    Cli, CliOutputFormat, Commands, ConfigAction, FocusAction, SessionsAction,
+};
 use granary::cli::{
-    batch, checkpoints, config, daemon, entrypoint, init, initiatives, plan, projects, run, search,
-    sessions, show, summary, tasks, update, work, worker, workers,
+    audit, backup, batch, board, checkpoints, complete, config, daemon, entrypoint, events, export,
+    git, import, init, initiatives, logs, mcp, plan, plugins, projects, remote, report, rules, run,
+    search, serve, sessions, show, summary, sync, tasks, tui, undo, update, work, worker, workers,
+    workspaces,
 };
 use granary::error::{GranaryError, exit_codes};
+use granary::models::{
+    Actor, PROJECT_SORT_FIELDS, Page, RUN_SORT_FIELDS, Sort, SortField, TASK_SORT_FIELDS,
+};
+use granary::output::columns;
+use granary::services::plugin_service::PluginInvocation;
+use granary::services::workspace_config::PendingHooks;
+use granary::services::{self, NON_UNDOABLE_COMMANDS, UNRECORDED_COMMANDS, Workspace, datetime};
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let command_name = matches.subcommand_name().map(str::to_string);
+    let mut cli = match Cli::from_arg_matches(&matches) {
+        Ok(cli) => cli,
+        Err(e) => e.exit(),
+    };
+
+    // `--workspace` names a workspace directory or a registered workspace.
+    // GRANARY_HOME only applies when it points at a workspace, as before.
+    if let Some(value) = cli.workspace.as_deref() {
+        match services::workspace_root(value).await {
+            Ok(root) => Workspace::set_root_override(root),
+            Err(e) if matches.value_source("workspace") == Some(ValueSource::CommandLine) => {
+                eprintln!("Error: {}", e);
+                return ExitCode::from(e.exit_code() as u8);
+            }
+            Err(_) => {}
+        }
+    }
+
+    // `--profile` (or GRANARY_PROFILE) selects a profile from the global config
+    if let Some(profile) = cli.profile.as_deref()
+        && let Err(e) = services::global_config::set_profile(profile)
+    {
+        eprintln!("Error: {}", e);
+        return ExitCode::from(e.exit_code() as u8);
+    }
+
+    // .granary/granary.toml fills in output settings not given on the command line.
+    // `config validate` reports an invalid file itself, and `config schema`
+    // helps fix one.
+    let validating = matches!(
+        &cli.command,
+        Some(Commands::Config {
+            action: ConfigAction::Validate | ConfigAction::Schema { .. }
+        })
+    );
+    let workspace_config = match services::workspace_config::load_current() {
+        Ok(config) => config,
+        Err(_) if validating => Default::default(),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::from(e.exit_code() as u8);
+        }
+    };
+    if let Some(format) = workspace_config.output.format.as_deref()
+        && !cli.json
+        && matches.value_source("format") != Some(ValueSource::CommandLine)
+        && let Ok(format) = CliOutputFormat::from_str(format, true)
+    {
+        cli.format = format;
+    }
+    if cli.max_width.is_none() {
+        cli.max_width = workspace_config.output.max_width;
+    }
+
+    // `granary <name>` for an unknown name runs the granary-<name> plugin,
+    // which records its own changes through the commands it runs
+    if let Some(Commands::External(args)) = &cli.command {
+        let invocation = PluginInvocation {
+            format: cli.output_format(),
+            session: cli.session.clone(),
+            actor: cli.actor.clone(),
+        };
+        return match plugins::run_external(args, &invocation) {
+            Ok(code) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                ExitCode::from(e.exit_code() as u8)
+            }
+        };
+    }
 
-    let result = run(cli).await;
+    // Record what the command changes, and who by, for `granary undo` and
+    // `granary audit`
+    let recording = match command_name.as_deref() {
+        Some(name) if !UNRECORDED_COMMANDS.contains(&name) => {
+            let actor = services::resolve_actor(cli.actor.as_deref()).await;
+            let undoable = !NON_UNDOABLE_COMMANDS.contains(&name);
+            Some((services::start_recording(actor.clone()), actor, undoable))
+        }
+        _ => None,
+    };
+
+    // Hooks in .granary/granary.toml run for the events the command emits
+    let hooks = match &recording {
+        Some(_) => PendingHooks::watch(&workspace_config).await,
+        None => None,
+    };
+
+    let result = run(cli, command_name.as_deref()).await;
+
+    if let Some((operation_id, actor, undoable)) = recording {
+        finish_recording(&operation_id, &actor, undoable).await;
+        register_workspace().await;
+    }
+    if let Some(hooks) = hooks {
+        for warning in hooks.run().await {
+            eprintln!("Warning: {}", warning);
+        }
+    }
 
     match result {
         Ok(()) => ExitCode::from(exit_codes::SUCCESS as u8),
@@ -23,9 +133,23 @@ async fn main() -> ExitCode {
     }
 }
 
-async fn run(cli: Cli) -> granary::Result<()> {
+async fn run(cli: Cli, command_name: Option<&str>) -> granary::Result<()> {
     let format = cli.output_format();
 
+    // Resolve timezone and week start for date parsing and rendering
+    let workspace_pool = match Workspace::find() {
+        Ok(workspace) => workspace.pool().await.ok(),
+        Err(_) => None,
+    };
+    datetime::init(workspace_pool.as_ref()).await;
+    columns::init(
+        workspace_pool.as_ref(),
+        command_name,
+        cli.columns.as_deref(),
+        cli.max_width,
+    )
+    .await?;
+
     let command = match cli.command {
         Some(cmd) => cmd,
         None => {
@@ -35,12 +159,12 @@ async fn run(cli: Cli) -> granary::Result<()> {
     };
 
     match command {
-        Commands::Init => {
-            init::init().await?;
+        Commands::Init { encrypted } => {
+            init::init(encrypted).await?;
         }
 
-        Commands::Doctor => {
-            init::doctor().await?;
+        Commands::Doctor { fix } => {
+            init::doctor(fix).await?;
         }
 
         Commands::Plan { name, project } => {
@@ -51,12 +175,13 @@ async fn run(cli: Cli) -> granary::Result<()> {
             work::work(command).await?;
         }
 
-        Commands::Show { id } => {
-            show::show(&id, format).await?;
+        Commands::Show { id, comments } => {
+            show::show(&id, comments, format).await?;
         }
 
-        Commands::Projects { action, all } => {
-            projects::projects(action, all, format, cli.watch, cli.interval).await?;
+        Commands::Projects { action, all, sort } => {
+            let sort = parse_sort(sort.as_deref(), PROJECT_SORT_FIELDS)?;
+            projects::projects(action, all, sort, format, cli.watch, cli.interval).await?;
         }
 
         Commands::Project { id, action } => {
@@ -71,21 +196,45 @@ async fn run(cli: Cli) -> granary::Result<()> {
         }
 
         Commands::Tasks {
+            action: Some(action),
+            ..
+        } => {
+            tasks::tasks_action(action, format).await?;
+        }
+
+        Commands::Tasks {
+            action: None,
             all,
             status,
             priority,
             owner,
+            author,
+            label,
+            fields,
+            stale,
+            saved,
+            limit,
+            offset,
+            include_archived,
+            sort,
+            global,
         } => {
-            tasks::list_tasks(
+            let filter = tasks::TaskListFilter {
                 all,
                 status,
                 priority,
                 owner,
-                format,
-                cli.watch,
-                cli.interval,
-            )
-            .await?;
+                author,
+                labels: label,
+                fields,
+                stale,
+                saved,
+                page: Page::new(limit, offset),
+                include_archived,
+                sort: parse_sort(sort.as_deref(), TASK_SORT_FIELDS)?,
+                global,
+            };
+            tasks::list_tasks(filter, format, cli.watch, cli.interval).await?;
         }
 
         Commands::Task { id, action } => {
@@ -95,8 +244,10 @@ async fn run(cli: Cli) -> granary::Result<()> {
         Commands::Next {
             include_reason,
             all,
+            explain,
+            start,
         } => {
-            tasks::next_task(include_reason, all, format).await?;
+            tasks::next_task(include_reason, all, explain, start, format).await?;
         }
 
         Commands::Start {
@@ -107,9 +258,20 @@ async fn run(cli: Cli) -> granary::Result<()> {
             tasks::start_task(&task_id, owner, lease, format).await?;
         }
 
-        Commands::Focus { task_id } => {
-            tasks::focus_task(&task_id, format).await?;
-        }
+        Commands::Focus { action, task_id } => match (action, task_id) {
+            (Some(FocusAction::Push { task_id }), _) => {
+                tasks::push_focus(&task_id, format).await?;
+            }
+            (Some(FocusAction::Pop), _) => {
+                tasks::pop_focus(format).await?;
+            }
+            (None, Some(task_id)) => {
+                tasks::focus_task(&task_id, format).await?;
+            }
+            (None, None) => {
+                tasks::show_focus(format).await?;
+            }
+        },
 
         Commands::Pin { task_id } => {
             tasks::pin_task(&task_id).await?;
@@ -119,20 +281,67 @@ async fn run(cli: Cli) -> granary::Result<()> {
             tasks::unpin_task(&task_id).await?;
         }
 
-        Commands::Sessions { all } => {
-            sessions::list_sessions(all, format, cli.watch, cli.interval).await?;
-        }
+        Commands::Sessions { action, all } => match action {
+            None | Some(SessionsAction::List) => {
+                sessions::list_sessions(all, format, cli.watch, cli.interval).await?;
+            }
+            Some(SessionsAction::Review { session_id }) => {
+                sessions::review_session(session_id, format).await?;
+            }
+        },
 
         Commands::Session { action } => {
             sessions::session(action, format).await?;
         }
 
-        Commands::Summary { token_budget } => {
-            summary::summary(token_budget, format, cli.watch, cli.interval).await?;
+        Commands::Summary {
+            token_budget,
+            model,
+            template,
+            include_archived,
+        } => {
+            summary::summary(
+                token_budget,
+                model,
+                template,
+                include_archived,
+                format,
+                cli.watch,
+                cli.interval,
+            )
+            .await?;
         }
 
-        Commands::Context { include, max_items } => {
-            summary::context(include, max_items, format).await?;
+        Commands::Board { project } => {
+            board::board(project, format, cli.watch, cli.interval).await?;
+        }
+
+        Commands::Report { action } => {
+            report::report(action, format).await?;
+        }
+
+        Commands::Rules { action } => {
+            rules::rules(action, format).await?;
+        }
+
+        Commands::Context {
+            include,
+            exclude,
+            max_items,
+            token_budget,
+            model,
+            template,
+        } => {
+            summary::context(
+                include,
+                exclude,
+                max_items,
+                token_budget,
+                model,
+                template,
+                format,
+            )
+            .await?;
         }
 
         Commands::Checkpoint { action } => {
@@ -144,16 +353,88 @@ async fn run(cli: Cli) -> granary::Result<()> {
             tasks,
             constraints,
             acceptance_criteria,
+            token_budget,
+            model,
+            template,
         } => {
-            summary::handoff(&to, &tasks, constraints, acceptance_criteria, format).await?;
+            summary::handoff(
+                &to,
+                &tasks,
+                constraints,
+                acceptance_criteria,
+                token_budget,
+                model,
+                template,
+                format,
+            )
+            .await?;
         }
 
-        Commands::Apply { stdin } => {
-            batch::apply(stdin, format).await?;
+        Commands::Apply {
+            stdin,
+            input_format,
+        } => {
+            batch::apply(stdin, input_format, format).await?;
         }
 
-        Commands::Batch { stdin } => {
-            batch::batch(stdin, format).await?;
+        Commands::Batch {
+            action: Some(action),
+            ..
+        } => {
+            batch::batch_action(action, format).await?;
+        }
+
+        Commands::Batch {
+            action: None,
+            stdin,
+            input_format,
+        } => {
+            batch::batch(stdin, input_format, format).await?;
+        }
+
+        Commands::Undo { last, dry_run } => {
+            undo::undo(last, dry_run, format).await?;
+        }
+
+        Commands::Audit {
+            entity,
+            since,
+            by,
+            limit,
+            offset,
+        } => {
+            audit::audit(
+                entity,
+                since.as_deref(),
+                by,
+                Page::new(Some(limit), offset),
+                format,
+            )
+            .await?;
+        }
+
+        Commands::Completions { shell } => {
+            complete::completions(shell);
+        }
+
+        Commands::Complete { words } => {
+            complete::complete(words).await?;
+        }
+
+        Commands::Sync { action } => {
+            sync::sync(action, format).await?;
+        }
+
+        Commands::Remote { action } => {
+            remote::remote(action, format).await?;
+        }
+
+        Commands::Push { remote } => {
+            remote::push(remote.as_deref(), format).await?;
+        }
+
+        Commands::Pull { remote } => {
+            remote::pull(remote.as_deref(), format).await?;
         }
 
         Commands::Config { action } => {
@@ -164,8 +445,30 @@ async fn run(cli: Cli) -> granary::Result<()> {
             config::steering(action, format).await?;
         }
 
-        Commands::Search { query } => {
-            search::search(&query, format, cli.watch, cli.interval).await?;
+        Commands::Search {
+            action: Some(action),
+            ..
+        } => {
+            search::search_action(action).await?;
+        }
+
+        Commands::Search {
+            action: None,
+            query,
+            saved,
+            limit,
+            offset,
+            include_archived,
+            all_workspaces,
+        } => {
+            let options = search::SearchOptions {
+                query,
+                saved,
+                page: Page::new(limit, offset),
+                include_archived,
+                all_workspaces,
+            };
+            search::search(options, format, cli.watch, cli.interval).await?;
         }
 
         Commands::Initiatives { action, all } => {
@@ -180,8 +483,29 @@ async fn run(cli: Cli) -> granary::Result<()> {
             update::update(check, to).await?;
         }
 
-        Commands::Workers { all } => {
-            workers::list_workers(all, format, cli.watch, cli.interval).await?;
+        Commands::Workspaces { action } => {
+            workspaces::workspaces(action, format).await?;
+        }
+
+        Commands::Events { action } => {
+            events::events(action, format).await?;
+        }
+
+        Commands::Plugins { action } => {
+            plugins::plugins(action, format).await?;
+        }
+
+        // Plugins run in main, before anything is recorded
+        Commands::External(_) => {}
+
+        Commands::Workers { action, all } => {
+            // --workspace (or GRANARY_HOME) limits the list to one workspace
+            let workspace = match cli.workspace {
+                Some(_) => Some(Workspace::find()?.root.to_string_lossy().to_string()),
+                None => None,
+            };
+            workers::workers_action(action, all, workspace, format, cli.watch, cli.interval)
+                .await?;
         }
 
         Commands::Worker { command } => {
@@ -189,12 +513,24 @@ async fn run(cli: Cli) -> granary::Result<()> {
         }
 
         Commands::Runs {
+            action,
             worker,
             status,
             all,
+            queued,
             limit,
+            offset,
+            sort,
         } => {
-            run::list_runs(worker, status, all, limit, format, cli.watch, cli.interval).await?;
+            let filter = run::RunListFilter {
+                worker_id: worker,
+                status,
+                all,
+                queued,
+                page: Page::new(Some(limit), offset),
+                sort: parse_sort(sort.as_deref(), RUN_SORT_FIELDS)?,
+            };
+            run::runs_action(action, filter, format, cli.watch, cli.interval).await?;
         }
 
         Commands::Run { command } => {
@@ -204,7 +540,97 @@ async fn run(cli: Cli) -> granary::Result<()> {
         Commands::Daemon { command } => {
             daemon::daemon(command).await?;
         }
+
+        Commands::Logs { command } => {
+            logs::logs(command, format).await?;
+        }
+
+        Commands::Mcp => {
+            mcp::mcp(cli.actor.as_deref()).await?;
+        }
+
+        Commands::Export { archive, output } => {
+            export::export(archive, output).await?;
+        }
+
+        Commands::Import {
+            action: Some(action),
+            ..
+        } => {
+            import::import_action(action, format).await?;
+        }
+
+        Commands::Import {
+            action: None,
+            file,
+            path,
+        } => {
+            let file = file.expect("clap requires a file without a subcommand");
+            export::import(&file, path, format).await?;
+        }
+
+        Commands::Backup { to, list } => {
+            backup::backup(to, list, format).await?;
+        }
+
+        Commands::Restore { snapshot, global } => {
+            backup::restore(&snapshot, global, format).await?;
+        }
+
+        Commands::Tui => {
+            tui::tui(cli.interval).await?;
+        }
+
+        Commands::Serve { addr, graphql } => {
+            serve::serve(addr, graphql, cli.actor.as_deref()).await?;
+        }
+
+        Commands::Git { command } => {
+            git::git(command, format).await?;
+        }
     }
 
     Ok(())
 }
+
+/// Parse a `--sort` flag against the fields a listing accepts
+fn parse_sort(spec: Option<&str>, fields: &[SortField]) -> granary::Result<Sort> {
+    match spec {
+        Some(spec) => Sort::parse(spec, fields).map_err(GranaryError::InvalidArgument),
+        None => Ok(Sort::default()),
+    }
+}
+
+/// Register the workspace the command ran in, so that workspaces created
+/// before the registry existed are picked up on first use
+async fn register_workspace() {
+    if let Ok(workspace) = Workspace::find() {
+        services::try_register_workspace(&workspace).await;
+    }
+}
+
+/// Save the operation recorded for this command, even if the command failed
+/// partway, so that whatever it did change can be undone
+async fn finish_recording(operation_id: &str, actor: &Actor, undoable: bool) {
+    let Ok(workspace) = Workspace::find() else {
+        return;
+    };
+    let Ok(pool) = workspace.pool().await else {
+        return;
+    };
+    let command = std::env::args()
+        .skip(1)
+        .map(|arg| {
+            if arg.contains(char::is_whitespace) {
+                format!("{:?}", arg)
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    if let Err(e) = services::finish_recording(&pool, operation_id, &command, actor, undoable).await
+    {
+        eprintln!("Warning: could not record this command for undo: {}", e);
+    }
+}
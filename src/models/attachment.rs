@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// How an attached file is stored under `.granary/attachments/`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentMode {
+    Copy,
+    Symlink,
+}
+
+impl AttachmentMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttachmentMode::Copy => "copy",
+            AttachmentMode::Symlink => "symlink",
+        }
+    }
+}
+
+impl std::str::FromStr for AttachmentMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "copy" => Ok(AttachmentMode::Copy),
+            "symlink" => Ok(AttachmentMode::Symlink),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A file attached to a task with `granary tasks attach`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Attachment {
+    pub id: i64,
+    pub task_id: String,
+    /// File name, unique per task
+    pub name: String,
+    /// Stored file, relative to the workspace root
+    pub path: String,
+    /// The file that was attached
+    pub source: String,
+    pub mode: String,
+    pub size_bytes: i64,
+    pub created_at: String,
+}
+
+impl Attachment {
+    pub fn mode_enum(&self) -> Option<AttachmentMode> {
+        self.mode.parse().ok()
+    }
+
+    /// Where the stored file is, given the workspace root
+    pub fn absolute_path(&self, root: &Path) -> PathBuf {
+        root.join(&self.path)
+    }
+}
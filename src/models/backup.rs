@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// A copy of the workspace database, plus the global workers database when
+/// there is one, taken by `granary backup` or the daemon
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Directory name, e.g. "20260214-093000"
+    pub name: String,
+    /// Directory holding the database files
+    pub path: String,
+    pub created_at: String,
+    /// Database files in the snapshot, e.g. ["granary.db", "workers.db"]
+    pub files: Vec<String>,
+    /// Total size of the database files in bytes
+    pub size: u64,
+}
+
+/// Result of `granary restore`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    /// The snapshot that was restored
+    pub restored: Snapshot,
+    /// Snapshot of the state the restore replaced
+    pub saved: Snapshot,
+    /// Whether the global workers database was restored too
+    pub global: bool,
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// What a batch operation does to its entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeAction {
+    Create,
+    Update,
+    Delete,
+}
+
+impl ChangeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeAction::Create => "create",
+            ChangeAction::Update => "update",
+            ChangeAction::Delete => "delete",
+        }
+    }
+}
+
+/// A field a batch operation would set, with its value before and after
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// What one operation of a batch would do, worked out by
+/// `granary batch apply --dry-run` without writing anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub index: usize,
+    pub op: String,
+    pub action: ChangeAction,
+    /// "project", "task", "dependency", "comment", "session" or "scope"
+    pub entity_type: String,
+    /// The entity changed; `None` for one the operation would create
+    pub id: Option<String>,
+    pub changes: Vec<FieldChange>,
+    /// Why the operation would fail, such as an unknown ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Task, TaskStatus};
+
+/// Board columns, in order. Drafts are not ready for work and get no column.
+pub const BOARD_COLUMNS: [TaskStatus; 4] = [
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::Blocked,
+    TaskStatus::Done,
+];
+
+/// Tasks grouped into columns by status, from `granary board`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Board {
+    /// The project shown, or None for every project
+    pub project_id: Option<String>,
+    pub columns: Vec<BoardColumn>,
+    /// Draft tasks, left off the board
+    pub drafts: usize,
+}
+
+/// One status column of a board
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardColumn {
+    pub status: String,
+    pub tasks: Vec<Task>,
+}
+
+impl BoardColumn {
+    pub fn title(&self) -> &'static str {
+        match self.status.parse().unwrap_or_default() {
+            TaskStatus::Draft => "Draft",
+            TaskStatus::Todo => "To Do",
+            TaskStatus::InProgress => "In Progress",
+            TaskStatus::Blocked => "Blocked",
+            TaskStatus::Done => "Done",
+        }
+    }
+}
+
+impl Board {
+    /// Group tasks into columns. Open columns list the highest priority
+    /// first; Done lists the most recently completed first.
+    pub fn new(project_id: Option<String>, tasks: Vec<Task>) -> Self {
+        let mut columns: Vec<BoardColumn> = BOARD_COLUMNS
+            .iter()
+            .map(|status| BoardColumn {
+                status: status.as_str().to_string(),
+                tasks: Vec::new(),
+            })
+            .collect();
+        let mut drafts = 0;
+        for task in tasks {
+            match columns.iter_mut().find(|c| c.status == task.status) {
+                Some(column) => column.tasks.push(task),
+                None => drafts += 1,
+            }
+        }
+        for column in &mut columns {
+            if column.status == TaskStatus::Done.as_str() {
+                column
+                    .tasks
+                    .sort_by(|a, b| b.completed_at.cmp(&a.completed_at));
+            } else {
+                column.tasks.sort_by(|a, b| {
+                    (a.priority_enum().order(), &a.created_at)
+                        .cmp(&(b.priority_enum().order(), &b.created_at))
+                });
+            }
+        }
+        Board {
+            project_id,
+            columns,
+            drafts,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.iter().all(|c| c.tasks.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, status: &str, priority: &str, completed_at: Option<&str>) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "proj".to_string(),
+            task_number: 1,
+            parent_task_id: None,
+            title: id.to_string(),
+            description: None,
+            status: status.to_string(),
+            priority: priority.to_string(),
+            owner: None,
+            author: None,
+            tags: None,
+            blocked_reason: None,
+            started_at: None,
+            completed_at: completed_at.map(str::to_string),
+            due_at: None,
+            estimate: None,
+            claim_owner: None,
+            claim_claimed_at: None,
+            claim_lease_expires_at: None,
+            claim_session_id: None,
+            pinned: 0,
+            focus_weight: 0,
+            archived_at: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            version: 1,
+            labels: vec![],
+            fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_board_groups_by_status() {
+        let board = Board::new(
+            None,
+            vec![
+                task("low", "todo", "P3", None),
+                task("urgent", "todo", "P0", None),
+                task("sketch", "draft", "P1", None),
+                task("old", "done", "P2", Some("2024-01-02T00:00:00Z")),
+                task("new", "done", "P2", Some("2024-01-05T00:00:00Z")),
+                task("stuck", "blocked", "P2", None),
+            ],
+        );
+        let ids: Vec<Vec<&str>> = board
+            .columns
+            .iter()
+            .map(|c| c.tasks.iter().map(|t| t.id.as_str()).collect())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                vec!["urgent", "low"],
+                vec![],
+                vec!["stuck"],
+                vec!["new", "old"]
+            ]
+        );
+        assert_eq!(board.drafts, 1);
+        assert_eq!(board.columns[1].title(), "In Progress");
+    }
+}
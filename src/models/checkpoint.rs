@@ -7,6 +7,9 @@ pub struct Checkpoint {
     pub session_id: String,
     pub name: String,
     pub snapshot: String, // JSON snapshot of session state
+    /// Who created the checkpoint
+    #[serde(default)]
+    pub author: Option<String>,
     pub created_at: String,
 }
 
@@ -23,6 +26,10 @@ pub struct SessionSnapshot {
     pub scope: Vec<ScopeItem>,
     pub tasks: Vec<TaskSnapshot>,
     pub variables: std::collections::HashMap<String, String>,
+    /// Estimate totals of every project with estimated tasks, charted by
+    /// `granary report burndown`
+    #[serde(default)]
+    pub estimates: Vec<ProjectEstimate>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +39,10 @@ pub struct SessionSnapshotData {
     pub owner: Option<String>,
     pub mode: Option<String>,
     pub focus_task_id: Option<String>,
+    /// Focus stack, bottom first. Empty in snapshots taken before focus
+    /// stacks, where `focus_task_id` is the whole stack.
+    #[serde(default)]
+    pub focus_stack: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +62,14 @@ pub struct TaskSnapshot {
     pub focus_weight: i64,
 }
 
+/// Total and remaining (not done) estimate of a project's estimated tasks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ProjectEstimate {
+    pub project_id: String,
+    pub total: f64,
+    pub remaining: f64,
+}
+
 #[derive(Debug)]
 pub struct CreateCheckpoint {
     pub session_id: String,
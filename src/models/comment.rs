@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -94,6 +96,8 @@ pub struct Comment {
     pub id: String,
     pub parent_type: String,
     pub parent_id: String,
+    /// The comment this one replies to, on the same parent
+    pub parent_comment_id: Option<String>,
     pub comment_number: i64,
     pub kind: String,
     pub content: String,
@@ -120,10 +124,50 @@ impl Comment {
     }
 }
 
+/// A comment with its replies, nested by `parent_comment_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: Comment,
+    pub replies: Vec<CommentThread>,
+}
+
+impl CommentThread {
+    /// Nest comments into threads, keeping their order. A reply whose parent
+    /// is not among the comments starts its own thread.
+    pub fn build(comments: Vec<Comment>) -> Vec<CommentThread> {
+        let ids: HashSet<String> = comments.iter().map(|c| c.id.clone()).collect();
+        let mut replies: HashMap<String, Vec<Comment>> = HashMap::new();
+        let mut roots = Vec::new();
+        for comment in comments {
+            match comment
+                .parent_comment_id
+                .clone()
+                .filter(|parent| ids.contains(parent))
+            {
+                Some(parent) => replies.entry(parent).or_default().push(comment),
+                None => roots.push(comment),
+            }
+        }
+
+        fn nest(comment: Comment, replies: &mut HashMap<String, Vec<Comment>>) -> CommentThread {
+            let children = replies.remove(&comment.id).unwrap_or_default();
+            CommentThread {
+                replies: children.into_iter().map(|c| nest(c, replies)).collect(),
+                comment,
+            }
+        }
+
+        roots.into_iter().map(|c| nest(c, &mut replies)).collect()
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct CreateComment {
     pub parent_type: ParentType,
     pub parent_id: String,
+    /// Comment to reply to, starting or continuing its thread
+    pub parent_comment_id: Option<String>,
     pub kind: CommentKind,
     pub content: String,
     pub author: Option<String>,
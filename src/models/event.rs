@@ -20,6 +20,15 @@ pub enum EventType {
     TaskUnblocked,
     TaskClaimed,
     TaskReleased,
+    TaskVcsLinked,
+    TaskVcsUnlinked,
+    TaskAttached,
+    TaskArchived,
+    TaskUnarchived,
+    TaskDueSoon,
+    TaskOverdue,
+    TaskLinked,
+    TaskUnlinked,
 
     // Polled events (not persisted, generated on-demand)
     TaskNext,    // Emitted when a task becomes available for work
@@ -49,11 +58,115 @@ pub enum EventType {
     ArtifactAdded,
     ArtifactRemoved,
 
+    // Run events (emitted by the daemon as a worker's runs start and finish)
+    RunStarted,
+    RunCompleted,
+    RunFailed,
+    RunCancelled,
+
+    // Rule events (emitted by the daemon when a rule matches a task)
+    RuleTriggered,
+
     // Other
     Custom(String),
 }
 
 impl EventType {
+    /// Every event type granary emits, as listed by `granary events types`
+    pub const ALL: &'static [EventType] = &[
+        EventType::ProjectCreated,
+        EventType::ProjectUpdated,
+        EventType::ProjectArchived,
+        EventType::ProjectNext,
+        EventType::TaskCreated,
+        EventType::TaskUpdated,
+        EventType::TaskStatusChanged,
+        EventType::TaskStarted,
+        EventType::TaskCompleted,
+        EventType::TaskBlocked,
+        EventType::TaskUnblocked,
+        EventType::TaskClaimed,
+        EventType::TaskReleased,
+        EventType::TaskVcsLinked,
+        EventType::TaskVcsUnlinked,
+        EventType::TaskAttached,
+        EventType::TaskArchived,
+        EventType::TaskUnarchived,
+        EventType::TaskDueSoon,
+        EventType::TaskOverdue,
+        EventType::TaskLinked,
+        EventType::TaskUnlinked,
+        EventType::TaskNext,
+        EventType::DependencyAdded,
+        EventType::DependencyRemoved,
+        EventType::CommentCreated,
+        EventType::CommentUpdated,
+        EventType::SessionStarted,
+        EventType::SessionUpdated,
+        EventType::SessionClosed,
+        EventType::SessionScopeAdded,
+        EventType::SessionScopeRemoved,
+        EventType::SessionFocusChanged,
+        EventType::CheckpointCreated,
+        EventType::CheckpointRestored,
+        EventType::ArtifactAdded,
+        EventType::ArtifactRemoved,
+        EventType::RunStarted,
+        EventType::RunCompleted,
+        EventType::RunFailed,
+        EventType::RunCancelled,
+        EventType::RuleTriggered,
+    ];
+
+    /// What happened, for `granary events types`
+    pub fn description(&self) -> &'static str {
+        match self {
+            EventType::ProjectCreated => "A project was created",
+            EventType::ProjectUpdated => "A project was updated",
+            EventType::ProjectArchived => "A project was archived",
+            EventType::ProjectNext => "A project has work ready (polled, not stored)",
+            EventType::TaskCreated => "A task was created",
+            EventType::TaskUpdated => "A task's fields changed",
+            EventType::TaskStatusChanged => "A task moved to another status",
+            EventType::TaskStarted => "Work started on a task",
+            EventType::TaskCompleted => "A task was marked done",
+            EventType::TaskBlocked => "A task was blocked",
+            EventType::TaskUnblocked => "A task's blocker or last unmet dependency cleared",
+            EventType::TaskClaimed => "A task was claimed by an owner",
+            EventType::TaskReleased => "A task's claim was released",
+            EventType::TaskVcsLinked => "A git branch or commit was linked to a task",
+            EventType::TaskVcsUnlinked => "A git branch or commit was unlinked from a task",
+            EventType::TaskAttached => "A file was attached to a task",
+            EventType::TaskArchived => "A task was archived",
+            EventType::TaskUnarchived => "A task was restored from the archive",
+            EventType::TaskDueSoon => "A task is due within reminders.due_soon (daemon)",
+            EventType::TaskOverdue => "A task passed its due date (daemon)",
+            EventType::TaskLinked => "Two tasks were related",
+            EventType::TaskUnlinked => "A relation between two tasks was removed",
+            EventType::TaskNext => "A task is ready to work on (polled, not stored)",
+            EventType::DependencyAdded => "A task now depends on another",
+            EventType::DependencyRemoved => "A task dependency was removed",
+            EventType::CommentCreated => "A comment or reply was added",
+            EventType::CommentUpdated => "A comment was edited",
+            EventType::SessionStarted => "A session started",
+            EventType::SessionUpdated => "A session's name, owner, mode or variables changed",
+            EventType::SessionClosed => "A session ended",
+            EventType::SessionScopeAdded => "An item was added to a session's scope",
+            EventType::SessionScopeRemoved => "An item was removed from a session's scope",
+            EventType::SessionFocusChanged => "A session's focus task changed",
+            EventType::CheckpointCreated => "A checkpoint was created",
+            EventType::CheckpointRestored => "A checkpoint was restored",
+            EventType::ArtifactAdded => "An artifact was recorded on a task",
+            EventType::ArtifactRemoved => "An artifact was removed from a task",
+            EventType::RunStarted => "A worker's run started (daemon)",
+            EventType::RunCompleted => "A worker's run exited successfully (daemon)",
+            EventType::RunFailed => "A worker's run failed, timed out or was killed (daemon)",
+            EventType::RunCancelled => "A worker's run was stopped (daemon)",
+            EventType::RuleTriggered => "A rule matched a task (daemon)",
+            EventType::Custom(_) => "Custom event",
+        }
+    }
+
     pub fn as_str(&self) -> String {
         match self {
             EventType::ProjectCreated => "project.created".to_string(),
@@ -68,6 +181,15 @@ impl EventType {
             EventType::TaskUnblocked => "task.unblocked".to_string(),
             EventType::TaskClaimed => "task.claimed".to_string(),
             EventType::TaskReleased => "task.released".to_string(),
+            EventType::TaskVcsLinked => "task.vcs_linked".to_string(),
+            EventType::TaskVcsUnlinked => "task.vcs_unlinked".to_string(),
+            EventType::TaskAttached => "task.attached".to_string(),
+            EventType::TaskArchived => "task.archived".to_string(),
+            EventType::TaskUnarchived => "task.unarchived".to_string(),
+            EventType::TaskDueSoon => "task.due_soon".to_string(),
+            EventType::TaskOverdue => "task.overdue".to_string(),
+            EventType::TaskLinked => "task.linked".to_string(),
+            EventType::TaskUnlinked => "task.unlinked".to_string(),
             EventType::TaskNext => "task.next".to_string(),
             EventType::ProjectNext => "project.next".to_string(),
             EventType::DependencyAdded => "dependency.added".to_string(),
@@ -84,6 +206,11 @@ impl EventType {
             EventType::CheckpointRestored => "checkpoint.restored".to_string(),
             EventType::ArtifactAdded => "artifact.added".to_string(),
             EventType::ArtifactRemoved => "artifact.removed".to_string(),
+            EventType::RunStarted => "run.started".to_string(),
+            EventType::RunCompleted => "run.completed".to_string(),
+            EventType::RunFailed => "run.failed".to_string(),
+            EventType::RunCancelled => "run.cancelled".to_string(),
+            EventType::RuleTriggered => "rule.triggered".to_string(),
             EventType::Custom(s) => s.clone(),
         }
     }
@@ -106,6 +233,15 @@ impl std::str::FromStr for EventType {
             "task.unblocked" => EventType::TaskUnblocked,
             "task.claimed" => EventType::TaskClaimed,
             "task.released" => EventType::TaskReleased,
+            "task.vcs_linked" => EventType::TaskVcsLinked,
+            "task.vcs_unlinked" => EventType::TaskVcsUnlinked,
+            "task.attached" => EventType::TaskAttached,
+            "task.archived" => EventType::TaskArchived,
+            "task.unarchived" => EventType::TaskUnarchived,
+            "task.due_soon" => EventType::TaskDueSoon,
+            "task.overdue" => EventType::TaskOverdue,
+            "task.linked" => EventType::TaskLinked,
+            "task.unlinked" => EventType::TaskUnlinked,
             "task.next" => EventType::TaskNext,
             "project.next" => EventType::ProjectNext,
             "dependency.added" => EventType::DependencyAdded,
@@ -122,6 +258,11 @@ impl std::str::FromStr for EventType {
             "checkpoint.restored" => EventType::CheckpointRestored,
             "artifact.added" => EventType::ArtifactAdded,
             "artifact.removed" => EventType::ArtifactRemoved,
+            "run.started" => EventType::RunStarted,
+            "run.completed" => EventType::RunCompleted,
+            "run.failed" => EventType::RunFailed,
+            "run.cancelled" => EventType::RunCancelled,
+            "rule.triggered" => EventType::RuleTriggered,
             other => EventType::Custom(other.to_string()),
         })
     }
@@ -136,6 +277,7 @@ pub enum EntityType {
     Session,
     Checkpoint,
     Artifact,
+    Run,
 }
 
 impl EntityType {
@@ -147,6 +289,7 @@ impl EntityType {
             EntityType::Session => "session",
             EntityType::Checkpoint => "checkpoint",
             EntityType::Artifact => "artifact",
+            EntityType::Run => "run",
         }
     }
 }
@@ -162,6 +305,7 @@ impl std::str::FromStr for EntityType {
             "session" => Ok(EntityType::Session),
             "checkpoint" => Ok(EntityType::Checkpoint),
             "artifact" => Ok(EntityType::Artifact),
+            "run" => Ok(EntityType::Run),
             _ => Err(()),
         }
     }
@@ -189,6 +333,22 @@ impl Event {
     }
 }
 
+/// An event type with its description, for `granary events types`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventTypeInfo {
+    pub name: String,
+    pub description: String,
+}
+
+impl From<&EventType> for EventTypeInfo {
+    fn from(event_type: &EventType) -> Self {
+        Self {
+            name: event_type.as_str(),
+            description: event_type.description().to_string(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CreateEvent {
     pub event_type: EventType,
@@ -198,3 +358,17 @@ pub struct CreateEvent {
     pub session_id: Option<String>,
     pub payload: serde_json::Value,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_types_round_trip() {
+        for event_type in EventType::ALL {
+            let name = event_type.as_str();
+            assert!(!matches!(event_type, EventType::Custom(_)));
+            assert_eq!(name.parse::<EventType>().unwrap().as_str(), name);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Custom fields on tasks and projects.
+//!
+//! The fields a workspace accepts are declared in workspace config as
+//! `field.<name> = <type>`, for example `field.env = enum:dev,staging,prod`.
+//! Values are stored in the `custom_fields` table.
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The type of a custom field, as written in workspace config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    Number,
+    Bool,
+    Enum(Vec<String>),
+}
+
+impl FieldType {
+    /// Check a value against this type and return it in canonical form
+    pub fn normalize(&self, value: &str) -> Result<String, String> {
+        let value = value.trim();
+        match self {
+            FieldType::Text => Ok(value.to_string()),
+            FieldType::Number => value
+                .parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(|_| value.to_string())
+                .ok_or_else(|| format!("'{}' is not a number", value)),
+            FieldType::Bool => match value.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok("true".to_string()),
+                "false" | "no" | "0" => Ok("false".to_string()),
+                _ => Err(format!("'{}' is not a boolean (true/false)", value)),
+            },
+            FieldType::Enum(options) => options
+                .iter()
+                .find(|o| o.eq_ignore_ascii_case(value))
+                .cloned()
+                .ok_or_else(|| format!("'{}' is not one of: {}", value, options.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Text => write!(f, "text"),
+            FieldType::Number => write!(f, "number"),
+            FieldType::Bool => write!(f, "bool"),
+            FieldType::Enum(options) => write!(f, "enum:{}", options.join(",")),
+        }
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(options) = s.strip_prefix("enum:") {
+            let options: Vec<String> = options
+                .split(',')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+            if options.is_empty() {
+                return Err("Enum fields need at least one option, e.g. enum:dev,prod".to_string());
+            }
+            return Ok(FieldType::Enum(options));
+        }
+        match s.to_lowercase().as_str() {
+            "text" | "string" => Ok(FieldType::Text),
+            "number" => Ok(FieldType::Number),
+            "bool" | "boolean" => Ok(FieldType::Bool),
+            _ => Err(format!(
+                "Invalid field type: {}. Expected text, number, bool or enum:a,b,c",
+                s
+            )),
+        }
+    }
+}
+
+/// A custom field declared in workspace config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+}
+
+/// A stored field value, used when loading fields for many entities
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CustomField {
+    pub entity_id: String,
+    pub name: String,
+    pub value: String,
+}
+
+/// A `name=value` argument. An empty value clears the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldAssignment {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl FromStr for FieldAssignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid field '{}'. Expected NAME=VALUE", s))?;
+        let name = name.trim();
+        if !is_valid_field_name(name) {
+            return Err(format!(
+                "Invalid field name '{}'. Use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+        let value = value.trim();
+        Ok(FieldAssignment {
+            name: name.to_lowercase(),
+            value: (!value.is_empty()).then(|| value.to_string()),
+        })
+    }
+}
+
+/// Field names are lowercase identifiers so they can be used as config keys
+pub fn is_valid_field_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Format field values as `name=value` pairs
+pub fn format_fields(fields: &BTreeMap<String, String>) -> String {
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_type_parse_and_display() {
+        assert_eq!("text".parse::<FieldType>().unwrap(), FieldType::Text);
+        assert_eq!("Number".parse::<FieldType>().unwrap(), FieldType::Number);
+        let env: FieldType = "enum: dev, staging ,prod".parse().unwrap();
+        assert_eq!(env.to_string(), "enum:dev,staging,prod");
+        assert!("enum:".parse::<FieldType>().is_err());
+        assert!("date".parse::<FieldType>().is_err());
+    }
+
+    #[test]
+    fn test_field_type_normalize() {
+        let env: FieldType = "enum:dev,prod".parse().unwrap();
+        assert_eq!(env.normalize("PROD").unwrap(), "prod");
+        assert!(env.normalize("qa").is_err());
+        assert_eq!(FieldType::Bool.normalize("yes").unwrap(), "true");
+        assert!(FieldType::Bool.normalize("maybe").is_err());
+        assert_eq!(FieldType::Number.normalize(" 2.5 ").unwrap(), "2.5");
+        assert!(FieldType::Number.normalize("NaN").is_err());
+    }
+
+    #[test]
+    fn test_field_assignment_parse() {
+        let set: FieldAssignment = "Env=prod".parse().unwrap();
+        assert_eq!(set.name, "env");
+        assert_eq!(set.value.as_deref(), Some("prod"));
+
+        let clear: FieldAssignment = "env=".parse().unwrap();
+        assert_eq!(clear.value, None);
+
+        assert!("env".parse::<FieldAssignment>().is_err());
+        assert!("=prod".parse::<FieldAssignment>().is_err());
+        assert!("my env=prod".parse::<FieldAssignment>().is_err());
+    }
+}
@@ -3,50 +3,466 @@
 //! The global config lives at `~/.granary/config.toml` and contains
 //! user-level settings like runner definitions that persist across workspaces.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
-/// Configuration for log retention and rotation policies.
+/// Retention policy for `~/.granary/logs/`, set as `[logs]` in
+/// `config.toml`.
 ///
-/// Controls automatic cleanup of log files to prevent unbounded disk usage.
-/// Used by the daemon for periodic log maintenance.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The daemon enforces it every hour, and `granary logs prune` on demand.
+/// Logs of runs that have not finished, and of running workers, are kept
+/// whatever their age or size.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
 pub struct LogRetentionConfig {
-    /// Maximum age of log files to keep in days.
-    /// Files older than this will be automatically deleted.
-    pub max_age_days: u64,
+    /// Logs last written longer ago than this are deleted (e.g. "7d", "12h")
+    pub max_age: String,
 
-    /// Maximum total size of logs directory in megabytes.
-    /// When exceeded, oldest logs are deleted first.
-    pub max_total_size_mb: u64,
+    /// While the logs directory is larger than this, the oldest logs are
+    /// deleted (e.g. "100MB", "1GB")
+    pub max_total_size: String,
 
-    /// Maximum number of log files per worker.
-    /// Excess files (oldest first) are deleted.
+    /// Run logs kept per worker; the oldest beyond this are deleted
     pub max_files_per_worker: usize,
 }
 
 impl Default for LogRetentionConfig {
     fn default() -> Self {
         Self {
-            max_age_days: 7,
-            max_total_size_mb: 100,
+            max_age: "7d".to_string(),
+            max_total_size: "100MB".to_string(),
             max_files_per_worker: 100,
         }
     }
 }
 
+/// Which releases `granary update` and the update notice follow
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// Stable releases only
+    #[default]
+    Stable,
+    /// Pre-releases as well as stable releases
+    Prerelease,
+    /// Never check for updates
+    None,
+}
+
+/// Update checks, set as `[update]` in `config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Release channel to follow
+    pub channel: UpdateChannel,
+
+    /// Releases endpoint to check instead of the GitHub API, for mirrors.
+    /// It must return the GitHub releases JSON (`tag_name`, `prerelease`).
+    pub release_url: Option<String>,
+
+    /// Install script to run instead of the one on GitHub
+    pub install_script: Option<String>,
+
+    /// Show "Update available" in `granary --version`
+    pub notify: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            channel: UpdateChannel::Stable,
+            release_url: None,
+            install_script: None,
+            notify: true,
+        }
+    }
+}
+
+/// Restrictions on what workers may run, set as `[security]` in
+/// `config.toml`.
+///
+/// The daemon checks them when a worker is started or restored, so a worker
+/// defined by an agent can't run anything else.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Commands workers may run: names as given to `--command` or in a
+    /// runner (e.g., "claude"), absolute paths, or glob patterns over
+    /// absolute paths (e.g., "/opt/agents/*"). Unset allows any command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_commands: Option<Vec<String>>,
+}
+
+impl SecurityConfig {
+    /// Check whether a command may be run. `resolved` is the canonical path
+    /// it runs from, if it could be found.
+    ///
+    /// A bare name matches a pattern as written; a command with a path only
+    /// matches through where it resolves to, so `..` can't escape a pattern.
+    /// In patterns `*` matches within one path segment and `?` one character.
+    pub fn allows_command(&self, command: &str, resolved: Option<&Path>) -> bool {
+        let Some(patterns) = &self.allowed_commands else {
+            return true;
+        };
+        let bare_name = Path::new(command).components().count() == 1;
+        patterns.iter().any(|pattern| {
+            (bare_name && glob_matches(pattern, command))
+                || resolved.is_some_and(|path| glob_matches(pattern, &path.to_string_lossy()))
+        })
+    }
+}
+
+/// Match `text` against a glob pattern where `*` matches any characters
+/// except `/` and `?` any one character except `/`
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // matches[j]: whether the pattern so far matches text[..j]
+    let mut matches = vec![false; text.len() + 1];
+    matches[0] = true;
+    for p in &pattern {
+        let mut next = vec![false; text.len() + 1];
+        for j in 0..=text.len() {
+            next[j] = match p {
+                '*' => matches[j] || (j > 0 && next[j - 1] && text[j - 1] != '/'),
+                '?' => j > 0 && matches[j - 1] && text[j - 1] != '/',
+                c => j > 0 && matches[j - 1] && text[j - 1] == *c,
+            };
+        }
+        matches = next;
+    }
+    matches[text.len()]
+}
+
 /// Global configuration structure stored at ~/.granary/config.toml
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
 pub struct GlobalConfig {
+    /// Who you are, for attributing tasks, comments and checkpoints
+    #[serde(default)]
+    pub user: UserConfig,
+
     /// Runner definitions that can be referenced by name
     #[serde(default)]
     pub runners: HashMap<String, RunnerConfig>,
+
+    /// Date handling settings (timezone, first day of week)
+    #[serde(default)]
+    pub date: DateConfig,
+
+    /// Webhook endpoints that receive events from every workspace
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Desktop notifications for key events
+    #[serde(default)]
+    pub notifications: NotificationConfig,
+
+    /// Chat notifiers (Slack, Discord) that post messages for key events
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+
+    /// Retention policy for run and worker logs
+    #[serde(default)]
+    pub logs: LogRetentionConfig,
+
+    /// Release channel and mirror for `granary update`
+    #[serde(default)]
+    pub update: UpdateConfig,
+
+    /// Named sets of settings applied over the rest with `--profile` or
+    /// `GRANARY_PROFILE`
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Restrictions on what workers may run. Profiles can't change them.
+    #[serde(default)]
+    pub security: SecurityConfig,
+}
+
+impl GlobalConfig {
+    /// This config with a profile applied over it, or `None` if there is no
+    /// such profile. The profile's runners are added to the others, replacing
+    /// any of the same name; its other sections replace the top-level ones.
+    pub fn with_profile(mut self, name: &str) -> Option<Self> {
+        let profile = self.profiles.get(name)?.clone();
+        self.runners.extend(profile.runners);
+        if let Some(user) = profile.user {
+            self.user = user;
+        }
+        if let Some(date) = profile.date {
+            self.date = date;
+        }
+        if let Some(webhooks) = profile.webhooks {
+            self.webhooks = webhooks;
+        }
+        if let Some(notifications) = profile.notifications {
+            self.notifications = notifications;
+        }
+        if let Some(notifiers) = profile.notifiers {
+            self.notifiers = notifiers;
+        }
+        Some(self)
+    }
+}
+
+/// A named profile, set as `[profiles.<name>]` in `config.toml`, for keeping
+/// contexts such as work and personal apart. Anything it leaves out comes
+/// from the top level of the config.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct ProfileConfig {
+    /// Who you are in this profile
+    #[serde(default)]
+    pub user: Option<UserConfig>,
+
+    /// Runners added to (or replacing) the top-level runners
+    #[serde(default)]
+    pub runners: HashMap<String, RunnerConfig>,
+
+    /// Date handling settings
+    #[serde(default)]
+    pub date: Option<DateConfig>,
+
+    /// Webhook endpoints, instead of the top-level ones
+    #[serde(default)]
+    pub webhooks: Option<Vec<WebhookConfig>>,
+
+    /// Desktop notifications, instead of the top-level settings
+    #[serde(default)]
+    pub notifications: Option<NotificationConfig>,
+
+    /// Chat notifiers, instead of the top-level ones
+    #[serde(default)]
+    pub notifiers: Option<Vec<NotifierConfig>>,
+}
+
+/// The user's identity, set as `[user]` in `config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct UserConfig {
+    /// Name recorded as the author of what you create. `--actor` and
+    /// `GRANARY_ACTOR` take precedence; unset falls back to the login name.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Chat services a notifier can post to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifierKind {
+    Slack,
+    Discord,
+}
+
+/// A chat channel that receives messages for matching events.
+///
+/// Defined as `[[notifiers]]` tables in `config.toml`, each with an incoming
+/// webhook URL and the rules that select events.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct NotifierConfig {
+    pub kind: NotifierKind,
+
+    /// Incoming webhook URL; supports `${VAR}` expansion
+    pub url: String,
+
+    /// Events to post. Empty means the defaults: handoff comments and runs
+    /// that failed after retrying.
+    #[serde(default)]
+    pub rules: Vec<NotifierRule>,
+}
+
+/// An event type and filters over its payload that select events to post
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct NotifierRule {
+    /// Event type, matched exactly or by prefix when it ends in `*`
+    pub event: String,
+
+    /// Filters over the event payload, all of which must match
+    /// (e.g., "kind=handoff")
+    #[serde(default)]
+    pub filters: Vec<String>,
+}
+
+impl NotifierRule {
+    pub fn new(event: &str, filters: &[&str]) -> Self {
+        Self {
+            event: event.to_string(),
+            filters: filters.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+}
+
+impl NotifierConfig {
+    /// Rules used when none are configured: a handoff comment, or a run that
+    /// failed on a retry (runs only report failure once retries are used up)
+    pub fn default_rules() -> Vec<NotifierRule> {
+        vec![
+            NotifierRule::new("comment.created", &["kind=handoff"]),
+            NotifierRule::new("run.failed", &["attempt!=1"]),
+        ]
+    }
+
+    /// The configured rules, or the defaults
+    pub fn effective_rules(&self) -> Vec<NotifierRule> {
+        if self.rules.is_empty() {
+            Self::default_rules()
+        } else {
+            self.rules.clone()
+        }
+    }
+
+    /// Webhook URL with environment variables expanded
+    pub fn expanded_url(&self) -> String {
+        expand_env_vars(&self.url)
+    }
+}
+
+/// Desktop notifications sent by the daemon for events in any workspace
+/// that has a worker. Defined as `[notifications]` in `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
+pub struct NotificationConfig {
+    /// Notifications are off unless this is set
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Event types that notify (e.g., "run.failed", "task.*"). Empty means
+    /// the defaults: failed runs, blocked tasks and long completed runs.
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// Only notify for completed runs that took at least this many seconds
+    /// (default: 60)
+    #[serde(default)]
+    pub min_run_seconds: Option<u64>,
+}
+
+impl NotificationConfig {
+    /// Events that notify when none are configured
+    pub const DEFAULT_EVENTS: &[&str] = &["run.failed", "task.blocked", "run.completed"];
+
+    /// Default minimum duration of a completed run worth notifying about
+    pub const DEFAULT_MIN_RUN_SECONDS: u64 = 60;
+
+    /// Check whether an event type should pop a notification
+    pub fn matches(&self, event_type: &str) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.events.is_empty() {
+            return Self::DEFAULT_EVENTS.contains(&event_type);
+        }
+        matches_event_patterns(&self.events, event_type)
+    }
+
+    pub fn min_run_seconds(&self) -> u64 {
+        self.min_run_seconds
+            .unwrap_or(Self::DEFAULT_MIN_RUN_SECONDS)
+    }
+}
+
+/// Check an event type against patterns that match exactly, or by prefix
+/// when they end in `*` (`run.*`)
+pub fn matches_event_patterns(patterns: &[String], event_type: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => event_type.starts_with(prefix),
+            None => pattern == event_type,
+        })
+}
+
+/// A webhook endpoint that receives workspace events.
+///
+/// Webhooks are delivered by the daemon. Global webhooks are defined as
+/// `[[webhooks]]` tables in `config.toml`; workspace webhooks use the
+/// `webhooks.<name>.*` workspace config keys.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Default)]
+pub struct WebhookConfig {
+    /// URL to POST event payloads to
+    pub url: String,
+
+    /// Event types to deliver (e.g., "task.completed", "run.*"). Empty means all events.
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// Shared secret for signing payloads (sent as `X-Granary-Signature`)
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// Extra HTTP headers to send with each delivery
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+
+    /// Maximum delivery attempts, including the first (default: 5)
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+}
+
+impl WebhookConfig {
+    /// Default maximum delivery attempts
+    pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+    /// Create a webhook that receives all events
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Check whether an event type passes this webhook's filter.
+    ///
+    /// Patterns match exactly, or by prefix when they end in `*` (`run.*`).
+    pub fn matches(&self, event_type: &str) -> bool {
+        self.events.is_empty() || matches_event_patterns(&self.events, event_type)
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+            .unwrap_or(Self::DEFAULT_MAX_ATTEMPTS)
+            .max(1)
+    }
+
+    /// Signing secret with environment variables expanded
+    pub fn expanded_secret(&self) -> Option<String> {
+        self.secret.as_deref().map(expand_env_vars)
+    }
+
+    /// Headers with environment variables expanded in their values
+    pub fn expanded_headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.clone(), expand_env_vars(value)))
+            .collect()
+    }
+}
+
+/// Date handling settings.
+///
+/// Used for parsing relative due dates ("friday", "next week") and for
+/// rendering timestamps. Workspace config keys `date.timezone` and
+/// `date.week_start` override these values.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+pub struct DateConfig {
+    /// IANA timezone name (e.g., "Europe/London"). Defaults to UTC.
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// First day of the week (e.g., "monday", "sunday"). Defaults to Monday.
+    #[serde(default)]
+    pub week_start: Option<String>,
 }
 
 /// Configuration for a runner that executes tasks
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RunnerConfig {
-    /// Command to execute (e.g., "claude", "python")
+    /// Runner whose settings this one builds on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+
+    /// Command to execute (e.g., "claude", "python"); may be left out when
+    /// the runner extends another
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
 
     /// Arguments to pass to the command
@@ -64,17 +480,127 @@ pub struct RunnerConfig {
     /// Environment variables to set when running
     #[serde(default)]
     pub env: HashMap<String, String>,
+
+    /// Dotenv file, relative to the workspace root, read when each run
+    /// starts. Its variables take precedence over `env`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+
+    /// Resource limits applied to each run
+    #[serde(default, skip_serializing_if = "RunnerLimits::is_empty")]
+    pub limits: RunnerLimits,
+
+    /// Wall-clock limit per run in seconds; workers inherit it unless they set their own
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+
+    /// Seconds a run is given to exit after SIGTERM when it is stopped or
+    /// times out, before it is killed (default: 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period_secs: Option<u64>,
+
+    /// Send the triggering event and a context pack to each run as JSON on stdin
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stdin: bool,
+
+    /// How run output is written to the log file
+    #[serde(default, skip_serializing_if = "LogFormat::is_text")]
+    pub log_format: LogFormat,
+}
+
+/// How a runner's output is written to its run log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// stdout and stderr as written, interleaved, in `<run-id>.log`
+    #[default]
+    Text,
+    /// One JSON record per line (timestamp, stream, line) in `<run-id>.jsonl`
+    Jsonl,
+}
+
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        *self == LogFormat::Text
+    }
+
+    /// Extension of run log files in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "log",
+            LogFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "jsonl" | "json-lines" => Ok(LogFormat::Jsonl),
+            _ => Err(format!(
+                "Invalid log format: {} (expected text or jsonl)",
+                s
+            )),
+        }
+    }
+}
+
+/// Resource limits for runner processes.
+///
+/// Enforced with rlimits on Unix and a Job Object on Windows. Memory and CPU
+/// limits apply to each process the runner starts, not to its whole tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RunnerLimits {
+    /// Maximum memory per process in megabytes (address space on Unix)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+
+    /// Maximum CPU time per process in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_secs: Option<u64>,
+
+    /// Scheduling priority, from -20 (highest) to 19 (lowest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+
+    /// Working directory relative to the workspace root; must stay inside it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+impl RunnerLimits {
+    /// Check if no limits are set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 impl RunnerConfig {
     /// Create a new runner configuration with just a command
     pub fn new(command: impl Into<String>) -> Self {
         Self {
+            extends: None,
             command: command.into(),
             args: Vec::new(),
             concurrency: None,
             on: None,
             env: HashMap::new(),
+            env_file: None,
+            limits: RunnerLimits::default(),
+            timeout_secs: None,
+            grace_period_secs: None,
+            stdin: false,
+            log_format: LogFormat::Text,
         }
     }
 
@@ -83,11 +609,87 @@ impl RunnerConfig {
     pub fn expand_env_in_args(&self) -> Vec<String> {
         self.args.iter().map(|arg| expand_env_vars(arg)).collect()
     }
+
+    /// Apply this runner's settings over the runner it extends: settings it
+    /// sets replace the parent's, `args` are appended to the parent's, and
+    /// `env` and `limits` are merged key by key.
+    fn merged_over(self, parent: RunnerConfig) -> RunnerConfig {
+        let mut args = parent.args;
+        args.extend(self.args);
+        let mut env = parent.env;
+        env.extend(self.env);
+        RunnerConfig {
+            extends: parent.extends,
+            command: if self.command.is_empty() {
+                parent.command
+            } else {
+                self.command
+            },
+            args,
+            concurrency: self.concurrency.or(parent.concurrency),
+            on: self.on.or(parent.on),
+            env,
+            env_file: self.env_file.or(parent.env_file),
+            limits: RunnerLimits {
+                max_memory_mb: self.limits.max_memory_mb.or(parent.limits.max_memory_mb),
+                max_cpu_secs: self.limits.max_cpu_secs.or(parent.limits.max_cpu_secs),
+                nice: self.limits.nice.or(parent.limits.nice),
+                cwd: self.limits.cwd.or(parent.limits.cwd),
+            },
+            timeout_secs: self.timeout_secs.or(parent.timeout_secs),
+            grace_period_secs: self.grace_period_secs.or(parent.grace_period_secs),
+            stdin: self.stdin || parent.stdin,
+            log_format: if self.log_format.is_text() {
+                parent.log_format
+            } else {
+                self.log_format
+            },
+        }
+    }
+}
+
+/// Look up a runner and apply the runners it extends, in order.
+///
+/// Returns `Ok(None)` if there is no runner of that name, and an error if
+/// it extends a runner that doesn't exist, extends itself, or ends up
+/// without a command.
+pub fn resolve_runner(
+    runners: &HashMap<String, RunnerConfig>,
+    name: &str,
+) -> Result<Option<RunnerConfig>, String> {
+    let Some(runner) = runners.get(name) else {
+        return Ok(None);
+    };
+    let mut chain = vec![name.to_string()];
+    let mut resolved = runner.clone();
+    while let Some(parent_name) = resolved.extends.take() {
+        if chain.contains(&parent_name) {
+            return Err(format!(
+                "runner '{}' extends itself: {} -> {}",
+                name,
+                chain.join(" -> "),
+                parent_name
+            ));
+        }
+        let parent = runners.get(&parent_name).ok_or_else(|| {
+            format!(
+                "runner '{}' extends '{}', which is not defined",
+                chain[chain.len() - 1],
+                parent_name
+            )
+        })?;
+        resolved = resolved.merged_over(parent.clone());
+        chain.push(parent_name);
+    }
+    if resolved.command.trim().is_empty() {
+        return Err(format!("runner '{}' has no command", name));
+    }
+    Ok(Some(resolved))
 }
 
 /// Expand environment variables in a string.
 /// Supports ${VAR} and $VAR syntax.
-fn expand_env_vars(input: &str) -> String {
+pub(crate) fn expand_env_vars(input: &str) -> String {
     let mut result = input.to_string();
 
     // Handle ${VAR} syntax
@@ -117,6 +719,159 @@ mod tests {
     fn test_default_global_config() {
         let config = GlobalConfig::default();
         assert!(config.runners.is_empty());
+        assert!(config.date.timezone.is_none());
+        assert!(config.date.week_start.is_none());
+    }
+
+    #[test]
+    fn test_webhook_matches() {
+        let mut webhook = WebhookConfig::new("https://example.com/hook");
+        assert!(webhook.matches("task.created"));
+
+        webhook.events = vec!["task.completed".to_string(), "run.*".to_string()];
+        assert!(webhook.matches("task.completed"));
+        assert!(webhook.matches("run.failed"));
+        assert!(!webhook.matches("task.created"));
+        assert!(!webhook.matches("task.completed.extra"));
+    }
+
+    #[test]
+    fn test_notification_matches() {
+        let mut notifications = NotificationConfig::default();
+        assert!(!notifications.matches("run.failed"));
+
+        notifications.enabled = true;
+        assert!(notifications.matches("run.failed"));
+        assert!(notifications.matches("task.blocked"));
+        assert!(!notifications.matches("task.created"));
+
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [notifications]
+            enabled = true
+            events = ["task.*"]
+            min_run_seconds = 300
+            "#,
+        )
+        .unwrap();
+        assert!(config.notifications.matches("task.created"));
+        assert!(!config.notifications.matches("run.failed"));
+        assert_eq!(config.notifications.min_run_seconds(), 300);
+    }
+
+    #[test]
+    fn test_notifiers_from_toml() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [[notifiers]]
+            kind = "slack"
+            url = "https://hooks.slack.com/services/T/B/X"
+
+            [[notifiers]]
+            kind = "discord"
+            url = "https://discord.com/api/webhooks/1/abc"
+
+            [[notifiers.rules]]
+            event = "task.*"
+            filters = ["priority=P0"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.notifiers[0].kind, NotifierKind::Slack);
+        assert_eq!(
+            config.notifiers[0].effective_rules(),
+            NotifierConfig::default_rules()
+        );
+        assert_eq!(config.notifiers[1].kind, NotifierKind::Discord);
+        assert_eq!(
+            config.notifiers[1].effective_rules(),
+            vec![NotifierRule::new("task.*", &["priority=P0"])]
+        );
+    }
+
+    #[test]
+    fn test_webhooks_from_toml() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [[webhooks]]
+            url = "https://example.com/hook"
+            events = ["run.failed"]
+            secret = "s3cret"
+
+            [[webhooks]]
+            url = "https://example.com/all"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.webhooks.len(), 2);
+        assert_eq!(config.webhooks[0].events, vec!["run.failed"]);
+        assert_eq!(config.webhooks[0].max_attempts(), 5);
+        assert!(config.webhooks[1].events.is_empty());
+    }
+
+    #[test]
+    fn test_update_config() {
+        let config: GlobalConfig = toml::from_str("").unwrap();
+        assert_eq!(config.update, UpdateConfig::default());
+        assert!(config.update.notify);
+
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [update]
+            channel = "prerelease"
+            release_url = "https://mirror.example.com/granary/releases"
+            notify = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.update.channel, UpdateChannel::Prerelease);
+        assert!(config.update.release_url.is_some());
+        assert!(config.update.install_script.is_none());
+        assert!(!config.update.notify);
+
+        assert!(toml::from_str::<GlobalConfig>("[update]\nchannel = \"nightly\"").is_err());
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [user]
+            name = "alice"
+
+            [runners.claude]
+            command = "claude"
+
+            [runners.lint]
+            command = "eslint"
+
+            [profiles.work.user]
+            name = "alice.smith"
+
+            [profiles.work.runners.claude]
+            command = "claude"
+            args = ["--model", "opus"]
+
+            [profiles.work.notifications]
+            enabled = true
+
+            [profiles.personal]
+            "#,
+        )
+        .unwrap();
+
+        let work = config.clone().with_profile("work").unwrap();
+        assert_eq!(work.user.name.as_deref(), Some("alice.smith"));
+        assert_eq!(work.runners.len(), 2);
+        assert_eq!(work.runners["claude"].args, vec!["--model", "opus"]);
+        assert!(work.notifications.enabled);
+
+        let personal = config.clone().with_profile("personal").unwrap();
+        assert_eq!(personal.user.name.as_deref(), Some("alice"));
+        assert!(personal.runners["claude"].args.is_empty());
+        assert!(!personal.notifications.enabled);
+
+        assert!(config.with_profile("missing").is_none());
     }
 
     #[test]
@@ -162,4 +917,82 @@ mod tests {
             std::env::remove_var("TOKEN");
         }
     }
+
+    #[test]
+    fn test_resolve_runner_extends() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+            [runners.base-claude]
+            command = "claude"
+            args = ["-p"]
+            env = { MODEL = "sonnet", LOG = "1" }
+            timeout_secs = 600
+
+            [runners.base-claude.limits]
+            max_memory_mb = 2048
+
+            [runners.opus]
+            extends = "base-claude"
+            args = ["--model", "opus"]
+            env = { MODEL = "opus" }
+
+            [runners.opus.limits]
+            nice = 10
+
+            [runners.opus-review]
+            extends = "opus"
+            on = "task.completed"
+
+            [runners.loop-a]
+            extends = "loop-b"
+
+            [runners.loop-b]
+            extends = "loop-a"
+
+            [runners.orphan]
+            extends = "missing"
+            "#,
+        )
+        .unwrap();
+
+        let runner = resolve_runner(&config.runners, "opus-review")
+            .unwrap()
+            .unwrap();
+        assert_eq!(runner.extends, None);
+        assert_eq!(runner.command, "claude");
+        assert_eq!(runner.args, vec!["-p", "--model", "opus"]);
+        assert_eq!(runner.env["MODEL"], "opus");
+        assert_eq!(runner.env["LOG"], "1");
+        assert_eq!(runner.timeout_secs, Some(600));
+        assert_eq!(runner.limits.max_memory_mb, Some(2048));
+        assert_eq!(runner.limits.nice, Some(10));
+        assert_eq!(runner.on.as_deref(), Some("task.completed"));
+
+        assert!(resolve_runner(&config.runners, "nope").unwrap().is_none());
+        let err = resolve_runner(&config.runners, "loop-a").unwrap_err();
+        assert!(err.contains("loop-a -> loop-b -> loop-a"), "{err}");
+        let err = resolve_runner(&config.runners, "orphan").unwrap_err();
+        assert!(err.contains("'missing'"), "{err}");
+    }
+
+    #[test]
+    fn test_security_allows_command() {
+        assert!(SecurityConfig::default().allows_command("anything", None));
+
+        let security: SecurityConfig =
+            toml::from_str(r#"allowed_commands = ["claude", "/usr/bin/python3", "/opt/agents/*"]"#)
+                .unwrap();
+        assert!(security.allows_command("claude", None));
+        assert!(security.allows_command("python3", Some(Path::new("/usr/bin/python3"))));
+        assert!(security.allows_command("./agents/run.sh", Some(Path::new("/opt/agents/run.sh"))));
+        assert!(!security.allows_command("bash", Some(Path::new("/usr/bin/bash"))));
+        // `*` stays within one directory
+        assert!(!security.allows_command(
+            "/opt/agents/sub/run.sh",
+            Some(Path::new("/opt/agents/sub/run.sh"))
+        ));
+        // A path is only matched where it resolves to
+        assert!(!security.allows_command("/opt/agents/../../bin/sh", None));
+        assert!(!security.allows_command("./claude", None));
+    }
 }
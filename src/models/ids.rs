@@ -71,6 +71,13 @@ pub fn generate_comment_id(parent_id: &str, comment_number: i64) -> String {
     format!("{}-comment-{}", parent_id, comment_number)
 }
 
+/// Generate a milestone ID from an initiative ID and milestone number
+/// Format: <initiative_id>-milestone-<n>
+/// Example: "platform-revamp-5h18-milestone-2"
+pub fn generate_milestone_id(initiative_id: &str, milestone_number: i64) -> String {
+    format!("{}-milestone-{}", initiative_id, milestone_number)
+}
+
 /// Generate an artifact ID from a parent ID and artifact number
 /// Format: <parent_id>-artifact-<n>
 pub fn generate_artifact_id(parent_id: &str, artifact_number: i64) -> String {
@@ -93,6 +100,13 @@ pub fn generate_checkpoint_id() -> String {
     format!("chkpt-{}", suffix)
 }
 
+/// Generate an operation ID for the undo log
+/// Format: op-<suffix>
+pub fn generate_operation_id() -> String {
+    let suffix = generate_suffix(8);
+    format!("op-{}", suffix)
+}
+
 /// Generate a worker ID
 /// Format: worker-<suffix>
 /// Example: "worker-a3f8k2m1"
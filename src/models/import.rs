@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// One item brought in by `granary import` from another tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedItem {
+    /// Where the item came from, e.g. "owner/repo#12"
+    pub source: String,
+    pub title: String,
+    /// The task created for the item; `None` in a dry run or when skipped
+    pub task_id: Option<String>,
+    /// Status the task has, or would have
+    pub status: String,
+    pub labels: Vec<String>,
+    /// Comments copied onto the task
+    pub comments: usize,
+    /// The item was imported before and was left alone
+    pub skipped: bool,
+}
+
+/// The outcome of `granary import` from another tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// What was imported from, e.g. "github:owner/repo"
+    pub source: String,
+    pub project_id: String,
+    pub dry_run: bool,
+    pub items: Vec<ImportedItem>,
+}
+
+impl ImportReport {
+    /// Number of items imported, or that would be in a dry run
+    pub fn imported(&self) -> usize {
+        self.items.iter().filter(|i| !i.skipped).count()
+    }
+}
@@ -82,6 +82,59 @@ pub struct InitiativeProject {
     pub added_at: String,
 }
 
+/// A named checkpoint within an initiative, with an optional target date
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Milestone {
+    pub id: String,
+    pub initiative_id: String,
+    pub milestone_number: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<String>,
+    pub completed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Milestone {
+    pub fn is_completed(&self) -> bool {
+        self.completed_at.is_some()
+    }
+
+    /// Whether the milestone is open and past its target date
+    pub fn is_overdue(&self) -> bool {
+        !self.is_completed()
+            && self
+                .target_date
+                .as_deref()
+                .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok())
+                .is_some_and(|date| date < chrono::Utc::now())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CreateMilestone {
+    pub initiative_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_date: Option<String>,
+    pub project_ids: Vec<String>,
+    pub task_ids: Vec<String>,
+}
+
+/// A milestone with the progress of the projects and tasks attached to it.
+/// Attached projects count all of their tasks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MilestoneProgress {
+    #[serde(flatten)]
+    pub milestone: Milestone,
+    pub projects: Vec<String>,
+    pub tasks: Vec<String>,
+    pub total_tasks: usize,
+    pub done_tasks: usize,
+    pub percent_complete: f32,
+}
+
 // === Initiative Summary Models ===
 
 /// High-level summary of an initiative for orchestration scenarios
@@ -92,6 +145,8 @@ pub struct InitiativeSummary {
     pub projects: Vec<ProjectSummary>,
     pub blockers: Vec<InitiativeBlockerInfo>,
     pub next_actions: Vec<NextAction>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<MilestoneProgress>,
 }
 
 /// Basic initiative identification info
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a log was pruned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneReason {
+    /// Older than `logs.max_age`
+    Age,
+    /// Beyond `logs.max_files_per_worker` for its worker
+    Count,
+    /// Removed to bring the logs directory under `logs.max_total_size`
+    Size,
+    /// Log directory of a worker that no longer exists
+    Orphaned,
+}
+
+impl PruneReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PruneReason::Age => "age",
+            PruneReason::Count => "count",
+            PruneReason::Size => "size",
+            PruneReason::Orphaned => "orphaned",
+        }
+    }
+}
+
+/// A run log, worker log or orphaned log directory removed by log retention
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrunedLog {
+    pub path: String,
+    pub worker_id: String,
+    /// Set for run logs; the run's artifacts are removed with its log
+    pub run_id: Option<String>,
+    /// Bytes freed, including the run's artifacts
+    pub size: u64,
+    pub reason: PruneReason,
+}
+
+/// Result of `granary logs prune` or a periodic cleanup by the daemon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogPruneReport {
+    /// Nothing was deleted; `pruned` is what would have been
+    pub dry_run: bool,
+    pub pruned: Vec<PrunedLog>,
+    /// Size of the logs directory before pruning, in bytes
+    pub total_size: u64,
+    /// Bytes freed (or that would be freed)
+    pub freed: u64,
+}
+
+/// Output stream of a runner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+impl std::str::FromStr for LogStream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stdout" => Ok(LogStream::Stdout),
+            "stderr" => Ok(LogStream::Stderr),
+            _ => Err(format!("Invalid stream: {} (expected stdout or stderr)", s)),
+        }
+    }
+}
+
+/// One line of output in a JSON-lines run log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunLogRecord {
+    /// When the line was read from the runner, RFC 3339 in UTC
+    pub timestamp: String,
+    pub stream: LogStream,
+    pub line: String,
+}
+
+/// Selects lines of a JSON-lines run log by stream and time
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<LogStream>,
+    /// Only lines written at or after this RFC 3339 time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Only lines written before this RFC 3339 time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl LogFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether a record passes the filter. Records with a timestamp that
+    /// cannot be parsed only pass filters without a time range.
+    pub fn matches(&self, record: &RunLogRecord) -> bool {
+        if self.stream.is_some_and(|stream| stream != record.stream) {
+            return false;
+        }
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        let parse = |s: &str| DateTime::parse_from_rfc3339(s).map(|t| t.with_timezone(&Utc));
+        let Ok(at) = parse(&record.timestamp) else {
+            return false;
+        };
+        let after_since = self
+            .since
+            .as_deref()
+            .is_none_or(|since| parse(since).is_ok_and(|since| at >= since));
+        let before_until = self
+            .until
+            .as_deref()
+            .is_none_or(|until| parse(until).is_ok_and(|until| at < until));
+        after_since && before_until
+    }
+}
@@ -1,27 +1,65 @@
 pub mod artifact;
+pub mod attachment;
+pub mod backup;
+pub mod batch;
+pub mod board;
 pub mod checkpoint;
 pub mod comment;
 pub mod event;
+pub mod field;
 pub mod global_config;
 pub mod ids;
+pub mod import;
 pub mod initiative;
+pub mod logs;
+pub mod operation;
+pub mod page;
+pub mod plugin;
 pub mod project;
+pub mod registry;
+pub mod remote;
+pub mod report;
+pub mod rule;
 pub mod run;
 pub mod search;
 pub mod session;
+pub mod sort;
+pub mod sync;
 pub mod task;
+pub mod task_link;
+pub mod vcs;
 pub mod worker;
+pub mod workspace_config;
 
 pub use artifact::*;
+pub use attachment::*;
+pub use backup::*;
+pub use batch::*;
+pub use board::*;
 pub use checkpoint::*;
 pub use comment::*;
 pub use event::*;
+pub use field::*;
 pub use global_config::*;
 pub use ids::*;
+pub use import::*;
 pub use initiative::*;
+pub use logs::*;
+pub use operation::*;
+pub use page::*;
+pub use plugin::*;
 pub use project::*;
+pub use registry::*;
+pub use remote::*;
+pub use report::*;
+pub use rule::*;
 pub use run::*;
 pub use search::*;
 pub use session::*;
+pub use sort::*;
+pub use sync::*;
 pub use task::*;
+pub use task_link::*;
+pub use vcs::*;
 pub use worker::*;
+pub use workspace_config::*;
@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// One recorded CLI command and the row changes it made
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Operation {
+    pub id: String,
+    /// The command line, without the program name
+    pub command: String,
+    /// Who ran the command
+    pub actor: Option<String>,
+    /// The session that was active when the command ran
+    pub session_id: Option<String>,
+    pub created_at: String,
+    pub undone_at: Option<String>,
+    /// Number of row changes recorded for the operation
+    pub changes: i64,
+}
+
+/// A single row change: an insert, update or delete with JSON snapshots
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OperationChange {
+    pub id: i64,
+    pub operation_id: String,
+    pub table_name: String,
+    pub action: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Who is making changes: an agent or user name and the active session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub name: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// A row change with the operation that made it, as shown by `granary audit`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// ID of the change, increasing in the order changes were made
+    pub id: i64,
+    pub operation_id: String,
+    pub command: String,
+    pub actor: Option<String>,
+    pub session_id: Option<String>,
+    pub created_at: String,
+    pub undone_at: Option<String>,
+    pub table_name: String,
+    pub action: String,
+    /// ID (or other key) of the changed row
+    pub entity_id: Option<String>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+}
+
+/// Filters for `granary audit`
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    /// Only changes to this entity, or rows that reference it
+    pub entity: Option<String>,
+    /// Only changes made at or after this RFC 3339 timestamp
+    pub since: Option<String>,
+    /// Only changes made by this actor
+    pub actor: Option<String>,
+}
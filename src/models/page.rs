@@ -0,0 +1,61 @@
+//! Pagination for list queries.
+
+/// A window of rows to fetch, from `--limit` and `--offset`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Page {
+    /// Maximum number of rows, or `None` for all of them
+    pub limit: Option<u32>,
+    /// Number of rows to skip
+    pub offset: u32,
+}
+
+impl Page {
+    pub fn new(limit: Option<u32>, offset: u32) -> Self {
+        Self { limit, offset }
+    }
+
+    /// Value for SQLite's `LIMIT`, where -1 means no limit
+    pub fn sql_limit(&self) -> i64 {
+        self.limit.map(i64::from).unwrap_or(-1)
+    }
+
+    pub fn sql_offset(&self) -> i64 {
+        i64::from(self.offset)
+    }
+
+    /// The rows each source must return when several sources are merged
+    /// before this page is cut from the result
+    pub fn prefix(&self) -> Page {
+        Page {
+            limit: self.limit.map(|limit| limit.saturating_add(self.offset)),
+            offset: 0,
+        }
+    }
+
+    /// Cut this page from rows that are already in memory
+    pub fn apply<T>(&self, items: Vec<T>) -> Vec<T> {
+        let items = items.into_iter().skip(self.offset as usize);
+        match self.limit {
+            Some(limit) => items.take(limit as usize).collect(),
+            None => items.collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_window() {
+        let page = Page::new(Some(2), 3);
+        assert_eq!(page.apply((0..10).collect()), vec![3, 4]);
+        assert_eq!(page.prefix(), Page::new(Some(5), 0));
+        assert_eq!(page.sql_limit(), 2);
+
+        let all = Page::default();
+        assert_eq!(all.sql_limit(), -1);
+        assert_eq!(all.apply(vec![1, 2]), vec![1, 2]);
+        assert_eq!(all.prefix(), all);
+    }
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// An external `granary-<name>` executable, run as `granary <name>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plugin {
+    /// Subcommand name, the executable name without the `granary-` prefix
+    pub name: String,
+    /// Executable found first on PATH
+    pub path: String,
+}
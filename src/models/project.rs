@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeMap;
+
+use super::FieldAssignment;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -44,6 +47,11 @@ pub struct Project {
     pub created_at: String,
     pub updated_at: String,
     pub version: i64,
+
+    /// Custom field values, filled in by the project service
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 impl Project {
@@ -85,6 +93,7 @@ pub struct UpdateProject {
     pub tags: Option<Vec<String>>,
     pub default_session_policy: Option<serde_json::Value>,
     pub steering_refs: Option<Vec<String>>,
+    pub fields: Vec<FieldAssignment>,
 }
 
 /// Represents a dependency relationship between two projects
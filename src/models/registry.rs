@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// A workspace known to the global registry
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RegisteredWorkspace {
+    /// Name for `--workspace`, the directory name unless renamed
+    pub name: String,
+    /// Directory holding `.granary/`
+    pub root: String,
+    pub created_at: String,
+    pub last_used_at: String,
+}
+
+/// One workspace in `granary workspaces list`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceOverview {
+    pub name: String,
+    pub root: String,
+    /// Tasks, or `None` when the workspace could not be opened
+    pub tasks: Option<i64>,
+    /// Tasks not done yet
+    pub open_tasks: Option<i64>,
+    /// The later of the last command run there and the last task update
+    pub last_activity: String,
+    /// Why the workspace could not be opened, e.g. it was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
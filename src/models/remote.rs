@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::FromRow;
+
+use super::SyncDirection;
+
+/// A remote the workspace is synchronised with, added by `granary remote add`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Remote {
+    pub name: String,
+    /// A directory, an HTTP(S) URL or an `s3://bucket/prefix` URL
+    pub url: String,
+    /// ID of the last operation log change pushed
+    pub pushed_change_id: i64,
+    /// When the remote was last pushed to; the first push sends every row
+    pub pushed_at: Option<String>,
+    pub pulled_at: Option<String>,
+    pub created_at: String,
+}
+
+/// The list of batches on a remote, stored as `index.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteIndex {
+    /// Batch names in the order they were pushed
+    pub batches: Vec<String>,
+}
+
+/// Row changes pushed together, stored as `batches/<name>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    pub created_at: String,
+    pub changes: Vec<RowChange>,
+}
+
+/// The new state of one row: its values, or `None` when it was deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowChange {
+    pub table: String,
+    /// Primary key columns and their values
+    pub key: Map<String, Value>,
+    pub row: Option<Map<String, Value>>,
+    /// When the change was made, compared for last-writer-wins
+    pub changed_at: String,
+}
+
+impl RowChange {
+    /// The key as a string, identifying the row within its table
+    pub fn row_key(&self) -> String {
+        Value::Object(self.key.clone()).to_string()
+    }
+}
+
+/// Result of `granary push` or `granary pull`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteSyncReport {
+    pub remote: String,
+    pub direction: SyncDirection,
+    /// Batches pushed or applied
+    pub batches: Vec<String>,
+    /// Row changes pushed or applied
+    pub changes: usize,
+    /// Pulled changes dropped because the local row changed later
+    pub skipped: usize,
+}
+
+/// One remote in `granary sync status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteStatus {
+    pub name: String,
+    pub url: String,
+    /// Local changes not pushed yet, or `None` before the first push, which
+    /// sends the whole workspace
+    pub unpushed: Option<usize>,
+    /// Batches on the remote not pulled yet, or `None` when the remote
+    /// could not be read
+    pub unpulled: Option<usize>,
+    pub pushed_at: Option<String>,
+    pub pulled_at: Option<String>,
+    /// Why the remote could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::run::Run;
+
+/// A project's remaining estimate over time, from `granary report burndown`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Burndown {
+    pub project_id: String,
+    pub project_name: String,
+    /// Oldest first; the last point is the current state
+    pub points: Vec<BurndownPoint>,
+}
+
+/// Estimate totals at one checkpoint, or now
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BurndownPoint {
+    pub at: String,
+    /// Checkpoint name, or None for the current state
+    pub checkpoint: Option<String>,
+    pub total: f64,
+    pub remaining: f64,
+}
+
+/// Spend on agent runs that reported usage, from `granary report costs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Start of the period reported on (RFC 3339)
+    pub since: String,
+    pub total: CostTotals,
+    /// Most expensive first, as are the projects and tasks
+    pub by_worker: Vec<CostGroup>,
+    pub by_project: Vec<CostGroup>,
+    pub by_task: Vec<CostGroup>,
+}
+
+/// Usage summed over a set of runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostTotals {
+    pub runs: usize,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost_usd: f64,
+}
+
+impl CostTotals {
+    /// Count a run and add its reported usage
+    pub fn add_run(&mut self, run: &Run) {
+        self.runs += 1;
+        self.input_tokens += run.input_tokens.unwrap_or(0);
+        self.output_tokens += run.output_tokens.unwrap_or(0);
+        self.cost_usd += run.cost_usd.unwrap_or(0.0);
+    }
+}
+
+/// Usage of the runs of one worker, project or task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostGroup {
+    pub id: String,
+    /// Runner or command of a worker, name of a project, title of a task
+    pub name: String,
+    #[serde(flatten)]
+    pub totals: CostTotals,
+}
+
+/// A project's dated tasks in dependency order, from `granary projects timeline`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timeline {
+    pub project_id: String,
+    pub project_name: String,
+    /// Tasks after the tasks they depend on, earliest start first
+    pub tasks: Vec<TimelineTask>,
+    /// Tasks with neither a start nor a due date, left off the chart
+    pub undated: usize,
+}
+
+/// One bar of a timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineTask {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    /// When work started, or the due date for tasks not started yet
+    pub start: String,
+    /// When the task was completed, else its due date, else now
+    pub end: String,
+    /// A due date with no start: drawn as a single point
+    pub milestone: bool,
+    /// Tasks in the same project this one depends on
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+/// Health metrics of a project, from `granary projects health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectHealth {
+    pub project_id: String,
+    pub project_name: String,
+    pub total_tasks: usize,
+    pub done_tasks: usize,
+    pub percent_complete: f32,
+    /// Open tasks that are blocked, and their share of all open tasks
+    pub blocked_tasks: usize,
+    pub blocked_ratio: f32,
+    /// Average days since open tasks were created, or None with no open tasks
+    pub avg_open_task_age_days: Option<f64>,
+    /// Tasks completed within the last `window_days`
+    pub throughput: usize,
+    /// Failed, killed and timed out runs of this workspace's workers on the
+    /// project or its tasks within the last `window_days`
+    pub failing_runs: usize,
+    pub window_days: i64,
+}
+
+impl ProjectHealth {
+    /// One-line summary, e.g. `60% done, 1 blocked, 3 done/7d, 2 failing runs`
+    pub fn badge(&self) -> String {
+        let mut parts = vec![format!("{:.0}% done", self.percent_complete)];
+        if self.blocked_tasks > 0 {
+            parts.push(format!("{} blocked", self.blocked_tasks));
+        }
+        parts.push(format!("{} done/{}d", self.throughput, self.window_days));
+        if self.failing_runs > 0 {
+            parts.push(format!(
+                "{} failing run{}",
+                self.failing_runs,
+                if self.failing_runs == 1 { "" } else { "s" }
+            ));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Tasks completed per week, from `granary report velocity`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Velocity {
+    /// The project reported on, or None for every project
+    pub project_id: Option<String>,
+    /// Oldest first; the last week is the current one
+    pub weeks: Vec<VelocityWeek>,
+    /// Pace of the later complete weeks against the earlier ones, or None
+    /// with fewer than two complete weeks
+    pub trend: Option<VelocityTrend>,
+}
+
+/// Completions in one week
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VelocityWeek {
+    /// First day of the week (`YYYY-MM-DD`)
+    pub week_start: String,
+    pub completed: usize,
+    /// Total estimate of the tasks completed
+    pub estimate: f64,
+    /// The current week, still in progress
+    pub partial: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VelocityTrend {
+    Increasing,
+    Steady,
+    Dropping,
+}
+
+impl VelocityTrend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VelocityTrend::Increasing => "increasing",
+            VelocityTrend::Steady => "steady",
+            VelocityTrend::Dropping => "dropping",
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use super::TaskPriority;
+
+/// What a rule does to the tasks it matches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    /// Only emit a `rule.triggered` event
+    #[default]
+    Flag,
+    /// Change the task's priority
+    Priority(TaskPriority),
+}
+
+impl RuleAction {
+    pub fn as_string(&self) -> String {
+        match self {
+            RuleAction::Flag => "flag".to_string(),
+            RuleAction::Priority(priority) => format!("priority={}", priority.as_str()),
+        }
+    }
+}
+
+impl std::str::FromStr for RuleAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().split_once('=') {
+            None if s.trim().eq_ignore_ascii_case("flag") => Ok(RuleAction::Flag),
+            Some((field, value)) if field.trim() == "priority" => {
+                value.trim().parse().map(RuleAction::Priority)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+/// A rule the daemon evaluates against open tasks, defined by
+/// `rules.<name>.*` workspace config keys
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    /// Filters the task must match, e.g. `status=blocked`
+    pub when: Vec<String>,
+    /// How long the task must have gone unchanged, e.g. `3d`
+    pub idle: Option<String>,
+    pub action: RuleAction,
+    /// Apply the action; otherwise only the event is emitted
+    pub apply: bool,
+}
+
+/// A task a rule matched, from `granary rules test` or a daemon pass
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub task_id: String,
+    pub title: String,
+    pub action: String,
+    /// Whether the action was applied to the task
+    pub applied: bool,
+}
@@ -12,9 +12,35 @@ pub enum RunStatus {
     Failed,    // finished with error (exit code != 0)
     Paused,    // manually paused
     Cancelled, // manually cancelled
+    Killed,    // terminated for exceeding a resource limit
+    TimedOut,  // terminated for exceeding the worker's timeout
 }
 
 impl RunStatus {
+    /// Every status, in lifecycle order
+    pub const ALL: [RunStatus; 8] = [
+        RunStatus::Pending,
+        RunStatus::Running,
+        RunStatus::Paused,
+        RunStatus::Completed,
+        RunStatus::Failed,
+        RunStatus::Cancelled,
+        RunStatus::Killed,
+        RunStatus::TimedOut,
+    ];
+
+    /// Whether a run in this status has finished (completed, failed, cancelled, killed, or timed out)
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Completed
+                | RunStatus::Failed
+                | RunStatus::Cancelled
+                | RunStatus::Killed
+                | RunStatus::TimedOut
+        )
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Pending => "pending",
@@ -23,6 +49,8 @@ impl RunStatus {
             Self::Failed => "failed",
             Self::Paused => "paused",
             Self::Cancelled => "cancelled",
+            Self::Killed => "killed",
+            Self::TimedOut => "timed_out",
         }
     }
 }
@@ -38,6 +66,8 @@ impl std::str::FromStr for RunStatus {
             "failed" => Ok(RunStatus::Failed),
             "paused" => Ok(RunStatus::Paused),
             "cancelled" => Ok(RunStatus::Cancelled),
+            "killed" => Ok(RunStatus::Killed),
+            "timed_out" => Ok(RunStatus::TimedOut),
             _ => Err(()),
         }
     }
@@ -72,7 +102,7 @@ pub struct Run {
     pub command: String,
     /// Resolved arguments (stored as JSON array)
     pub args: String,
-    /// Current run status: pending, running, completed, failed, paused, cancelled
+    /// Current run status: pending, running, completed, failed, paused, cancelled, killed, timed_out
     pub status: String,
     /// Exit code when completed or failed
     pub exit_code: Option<i32>,
@@ -96,6 +126,44 @@ pub struct Run {
     pub created_at: String,
     /// Timestamp when the run was last updated
     pub updated_at: String,
+    /// Workspace the run belongs to, when it is not its worker's (runs of
+    /// global workers)
+    #[serde(default)]
+    pub instance_path: Option<String>,
+    /// Last percentage (0-100) the runner reported on a `GRANARY_PROGRESS` line
+    #[serde(default)]
+    pub progress: Option<i64>,
+    /// Text of the runner's last progress report
+    #[serde(default)]
+    pub progress_message: Option<String>,
+    /// Queued runs with a higher priority start first
+    #[serde(default)]
+    pub priority: i64,
+    /// Whether a run that was stopped or timed out exited on its own after
+    /// SIGTERM (`false` if it had to be killed); unset for other runs
+    #[serde(default)]
+    pub exited_gracefully: Option<bool>,
+    /// Peak resident set size of the runner process, in KiB
+    #[serde(default)]
+    pub max_rss_kb: Option<i64>,
+    /// CPU time the runner spent in user mode, in milliseconds
+    #[serde(default)]
+    pub user_cpu_ms: Option<i64>,
+    /// CPU time the runner spent in the kernel, in milliseconds
+    #[serde(default)]
+    pub system_cpu_ms: Option<i64>,
+    /// Wall time from spawning the runner to its exit, in milliseconds
+    #[serde(default)]
+    pub duration_ms: Option<i64>,
+    /// Model input tokens the runner reported on `GRANARY_USAGE` lines
+    #[serde(default)]
+    pub input_tokens: Option<i64>,
+    /// Model output tokens the runner reported on `GRANARY_USAGE` lines
+    #[serde(default)]
+    pub output_tokens: Option<i64>,
+    /// Cost in US dollars the runner reported on `GRANARY_USAGE` lines
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
 }
 
 impl Run {
@@ -114,12 +182,9 @@ impl Run {
         self.status_enum() == RunStatus::Running
     }
 
-    /// Check if the run has finished (completed, failed, or cancelled)
+    /// Check if the run has finished (completed, failed, cancelled, killed, or timed out)
     pub fn is_finished(&self) -> bool {
-        matches!(
-            self.status_enum(),
-            RunStatus::Completed | RunStatus::Failed | RunStatus::Cancelled
-        )
+        self.status_enum().is_finished()
     }
 
     /// Check if the run can be retried
@@ -127,10 +192,102 @@ impl Run {
         self.status_enum() == RunStatus::Failed && self.attempt < self.max_attempts
     }
 
+    /// Check if the run has attempts remaining, regardless of status
+    pub fn has_attempts_left(&self) -> bool {
+        self.attempt < self.max_attempts
+    }
+
     /// Check if the run is waiting for retry
     pub fn is_pending_retry(&self) -> bool {
         self.status_enum() == RunStatus::Pending && self.attempt > 1
     }
+
+    /// Check if the run is queued behind its worker's concurrency limit
+    pub fn is_queued(&self) -> bool {
+        self.status_enum() == RunStatus::Pending && self.attempt == 1
+    }
+
+    /// The last progress report as `42% building`, if there was one
+    pub fn progress_display(&self) -> Option<String> {
+        match (self.progress, self.progress_message.as_deref()) {
+            (Some(percent), Some(message)) => Some(format!("{}% {}", percent, message)),
+            (Some(percent), None) => Some(format!("{}%", percent)),
+            (None, Some(message)) => Some(message.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// A progress report parsed from a `GRANARY_PROGRESS` line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunProgress {
+    /// Percentage complete, 0-100
+    pub percent: Option<i64>,
+    pub message: Option<String>,
+}
+
+/// Tokens and cost reported by an AI runner on a `GRANARY_USAGE` line, or
+/// the sum of several reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    /// Add another report, keeping fields neither report has unset
+    pub fn add(&mut self, other: &TokenUsage) {
+        fn sum<T: std::ops::Add<Output = T>>(a: Option<T>, b: Option<T>) -> Option<T> {
+            match (a, b) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            }
+        }
+        self.input_tokens = sum(self.input_tokens, other.input_tokens);
+        self.output_tokens = sum(self.output_tokens, other.output_tokens);
+        self.cost_usd = sum(self.cost_usd, other.cost_usd);
+    }
+}
+
+/// Resources a runner process used, measured when it exits.
+///
+/// CPU time and peak memory cover the runner and the descendants it waited
+/// for; they are only measured on Unix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunUsage {
+    /// Peak resident set size, in KiB
+    pub max_rss_kb: Option<i64>,
+    /// User CPU time, in milliseconds
+    pub user_cpu_ms: Option<i64>,
+    /// System CPU time, in milliseconds
+    pub system_cpu_ms: Option<i64>,
+    /// Wall time from spawn to exit, in milliseconds
+    pub duration_ms: i64,
+}
+
+/// A run waiting for a concurrency slot, for `granary runs queue`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedRun {
+    /// 1-based place in its worker's queue
+    pub position: usize,
+    #[serde(flatten)]
+    pub run: Run,
+}
+
+/// A log line matching `granary runs grep`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunLogMatch {
+    pub run_id: String,
+    pub worker_id: String,
+    /// When the run started, or was created if it never started
+    pub run_at: String,
+    /// 1-based line number in the run's log
+    pub line_number: u64,
+    pub line: String,
 }
 
 /// Input for creating a new run
@@ -144,6 +301,7 @@ pub struct CreateRun {
     pub args: Vec<String>,
     pub max_attempts: i32,
     pub log_path: Option<String>,
+    pub instance_path: Option<String>,
 }
 
 impl Default for CreateRun {
@@ -157,6 +315,7 @@ impl Default for CreateRun {
             args: Vec::new(),
             max_attempts: 3,
             log_path: None,
+            instance_path: None,
         }
     }
 }
@@ -175,4 +334,6 @@ pub struct UpdateRunStatus {
 pub struct ScheduleRetry {
     pub next_retry_at: String,
     pub attempt: i32,
+    /// Why the previous attempt failed
+    pub error_message: Option<String>,
 }
@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use super::{FieldAssignment, TaskPriority, TaskStatus};
 
 /// Search result item (can be an initiative, project, or task)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +26,8 @@ pub enum SearchResult {
         status: String,
         priority: String,
         project_id: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        labels: Vec<String>,
     },
 }
 
@@ -51,3 +56,134 @@ impl SearchResult {
         }
     }
 }
+
+/// A search result from `granary search --all-workspaces`, with the name of
+/// the workspace it was found in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSearchResult {
+    pub workspace: String,
+    #[serde(flatten)]
+    pub result: SearchResult,
+}
+
+/// A parsed search query: free text plus `key:value` qualifiers.
+///
+/// Qualifiers filter tasks: `status:todo,in_progress`, `priority:p0`,
+/// `owner:alice` (or `assignee:alice`), `author:bob`, `project:<id>`,
+/// `label:backend` (repeatable) and
+/// `field.<name>:<value>` (an empty value matches tasks without the field).
+/// Other words are matched against titles and labels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub text: String,
+    pub statuses: Vec<String>,
+    pub priorities: Vec<String>,
+    pub owner: Option<String>,
+    pub author: Option<String>,
+    pub project: Option<String>,
+    pub labels: Vec<String>,
+    pub fields: Vec<FieldAssignment>,
+    /// Only open tasks with no activity since this time (RFC 3339), set by
+    /// `granary tasks --stale`
+    pub stale_since: Option<String>,
+    /// Also match archived tasks, set by `--include-archived`
+    pub include_archived: bool,
+}
+
+impl SearchQuery {
+    /// Whether the query has any qualifiers, which restrict results to tasks
+    pub fn has_qualifiers(&self) -> bool {
+        !self.statuses.is_empty()
+            || !self.priorities.is_empty()
+            || self.owner.is_some()
+            || self.author.is_some()
+            || self.project.is_some()
+            || !self.labels.is_empty()
+            || !self.fields.is_empty()
+            || self.stale_since.is_some()
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut query = SearchQuery::default();
+        let mut words = Vec::new();
+
+        for word in s.split_whitespace() {
+            let Some((key, value)) = word.split_once(':') else {
+                words.push(word);
+                continue;
+            };
+            let values = || value.split(',').filter(|v| !v.is_empty());
+            match key.to_lowercase().as_str() {
+                "status" => {
+                    for v in values() {
+                        let status: TaskStatus = v
+                            .parse()
+                            .map_err(|_| format!("Invalid status in query: {}", v))?;
+                        query.statuses.push(status.as_str().to_string());
+                    }
+                }
+                "priority" => {
+                    for v in values() {
+                        let priority: TaskPriority = v
+                            .parse()
+                            .map_err(|_| format!("Invalid priority in query: {}", v))?;
+                        query.priorities.push(priority.as_str().to_string());
+                    }
+                }
+                "owner" | "assignee" => query.owner = Some(value.to_string()),
+                "author" => query.author = Some(value.to_string()),
+                "project" => query.project = Some(value.to_string()),
+                "label" => query.labels.extend(values().map(str::to_string)),
+                other => match other.strip_prefix("field.") {
+                    Some(name) => query
+                        .fields
+                        .push(format!("{}={}", name, value).parse::<FieldAssignment>()?),
+                    // Not a qualifier, e.g. a URL or "note:" in a title
+                    None => words.push(word),
+                },
+            }
+        }
+
+        query.text = words.join(" ");
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_search_query() {
+        let query: SearchQuery =
+            "login status:todo,in-progress priority:p0 label:backend label:api bug"
+                .parse()
+                .unwrap();
+        assert_eq!(query.text, "login bug");
+        assert_eq!(query.statuses, vec!["todo", "in_progress"]);
+        assert_eq!(query.priorities, vec!["P0"]);
+        assert_eq!(query.labels, vec!["backend", "api"]);
+        assert!(query.has_qualifiers());
+
+        let query: SearchQuery = "field.env:prod owner:alice see http://x".parse().unwrap();
+        assert_eq!(query.fields[0].name, "env");
+        assert_eq!(query.fields[0].value.as_deref(), Some("prod"));
+        assert_eq!(query.owner.as_deref(), Some("alice"));
+        assert_eq!(query.text, "see http://x");
+
+        let query: SearchQuery = "assignee:alice author:bob".parse().unwrap();
+        assert_eq!(query.owner.as_deref(), Some("alice"));
+        assert_eq!(query.author.as_deref(), Some("bob"));
+        assert!(query.has_qualifiers());
+
+        let plain: SearchQuery = "api".parse().unwrap();
+        assert!(!plain.has_qualifiers());
+
+        assert!("status:later".parse::<SearchQuery>().is_err());
+        assert!("priority:p9".parse::<SearchQuery>().is_err());
+    }
+}
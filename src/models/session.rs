@@ -8,14 +8,21 @@ pub enum SessionMode {
     Execute,
     Plan,
     Review,
+    /// A mode defined as `[modes.<name>]` in the workspace config
+    Custom(String),
 }
 
 impl SessionMode {
-    pub fn as_str(&self) -> &'static str {
+    /// Modes available without config
+    pub const BUILT_IN: [SessionMode; 3] =
+        [SessionMode::Execute, SessionMode::Plan, SessionMode::Review];
+
+    pub fn as_str(&self) -> &str {
         match self {
             SessionMode::Plan => "plan",
             SessionMode::Execute => "execute",
             SessionMode::Review => "review",
+            SessionMode::Custom(name) => name,
         }
     }
 }
@@ -40,6 +47,9 @@ pub struct Session {
     pub owner: Option<String>,
     pub mode: Option<String>,
     pub focus_task_id: Option<String>,
+    /// JSON array of task IDs, bottom first, topped by the focus task
+    #[serde(default)]
+    pub focus_stack: Option<String>,
     pub variables: Option<String>, // JSON key/value
     pub created_at: String,
     pub updated_at: String,
@@ -50,10 +60,18 @@ impl Session {
     pub fn mode_enum(&self) -> SessionMode {
         self.mode
             .as_ref()
-            .and_then(|m| m.parse().ok())
+            .map(|m| m.parse().unwrap_or_else(|_| SessionMode::Custom(m.clone())))
             .unwrap_or_default()
     }
 
+    /// Tasks on the focus stack, bottom first. The last is the focus task.
+    pub fn focus_stack_vec(&self) -> Vec<String> {
+        self.focus_stack
+            .as_ref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_else(|| self.focus_task_id.iter().cloned().collect())
+    }
+
     pub fn variables_map(&self) -> std::collections::HashMap<String, String> {
         self.variables
             .as_ref()
@@ -129,3 +147,53 @@ pub struct UpdateSession {
     pub focus_task_id: Option<String>,
     pub variables: Option<std::collections::HashMap<String, String>>,
 }
+
+/// What happened during a session, from `granary sessions review`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReview {
+    pub session: Session,
+    /// Seconds from the start of the session to its close, or to now while
+    /// it is open
+    pub elapsed_secs: i64,
+    /// Events recorded in the session
+    pub event_count: usize,
+    /// Tasks the session's events were about, in the order first touched
+    pub tasks: Vec<ReviewedTask>,
+    /// Status changes of those tasks while the session was open, oldest first
+    pub transitions: Vec<StatusTransition>,
+    /// Comments written in the session, oldest first
+    pub comments: Vec<crate::models::Comment>,
+    /// Checkpoints created in the session, oldest first
+    pub checkpoints: Vec<ReviewedCheckpoint>,
+    /// Runs of this workspace's workers triggered by the session's events
+    pub runs: Vec<crate::models::run::Run>,
+}
+
+/// A task touched during a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewedTask {
+    pub id: String,
+    pub title: String,
+    /// Status now, which may have changed since the session
+    pub status: String,
+    /// Events about the task recorded in the session
+    pub events: usize,
+}
+
+/// One change of a task's status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct StatusTransition {
+    pub task_id: String,
+    /// None for backfilled history
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub changed_at: String,
+}
+
+/// A checkpoint created during a session, without its snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewedCheckpoint {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
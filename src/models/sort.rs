@@ -0,0 +1,149 @@
+//! Sorting for list queries.
+//!
+//! A sort spec such as `priority,-updated_at` is a comma-separated list of
+//! fields, each descending when prefixed with `-`. Fields are checked against
+//! a per-entity whitelist and turned into an SQL `ORDER BY` clause.
+
+/// A sortable field: the names accepted on the command line and the SQL
+/// expression it sorts by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortField {
+    pub names: &'static [&'static str],
+    pub expr: &'static str,
+}
+
+const fn field(names: &'static [&'static str], expr: &'static str) -> SortField {
+    SortField { names, expr }
+}
+
+/// Fields `granary tasks --sort` accepts
+pub const TASK_SORT_FIELDS: &[SortField] = &[
+    field(&["id"], "id"),
+    field(&["title"], "title COLLATE NOCASE"),
+    field(
+        &["status"],
+        "CASE status WHEN 'draft' THEN 0 WHEN 'todo' THEN 1 WHEN 'in_progress' THEN 2 \
+         WHEN 'done' THEN 3 ELSE 4 END",
+    ),
+    field(&["priority"], "priority"),
+    field(&["owner"], "owner"),
+    field(&["project", "project_id"], "project_id"),
+    field(&["number", "task_number"], "task_number"),
+    field(&["due", "due_at"], "due_at"),
+    field(&["created", "created_at"], "created_at"),
+    field(&["updated", "updated_at"], "updated_at"),
+];
+
+/// Fields `granary projects --sort` accepts
+pub const PROJECT_SORT_FIELDS: &[SortField] = &[
+    field(&["id"], "id"),
+    field(&["name"], "name COLLATE NOCASE"),
+    field(&["status"], "status"),
+    field(&["owner"], "owner"),
+    field(&["created", "created_at"], "created_at"),
+    field(&["updated", "updated_at"], "updated_at"),
+];
+
+/// Fields `granary runs --sort` accepts
+pub const RUN_SORT_FIELDS: &[SortField] = &[
+    field(&["id"], "id"),
+    field(&["worker", "worker_id"], "worker_id"),
+    field(&["status"], "status"),
+    field(&["event_type"], "event_type"),
+    field(&["attempt"], "attempt"),
+    field(&["started", "started_at"], "started_at"),
+    field(&["completed", "completed_at"], "completed_at"),
+    field(&["created", "created_at"], "created_at"),
+    field(&["updated", "updated_at"], "updated_at"),
+];
+
+/// One key of a sort spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub expr: &'static str,
+    pub descending: bool,
+}
+
+/// A parsed sort spec. An empty sort leaves the query's default order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sort {
+    pub keys: Vec<SortKey>,
+}
+
+impl Sort {
+    /// Parse a spec like `priority,-updated_at` against the allowed fields
+    pub fn parse(spec: &str, fields: &[SortField]) -> Result<Sort, String> {
+        let keys = spec
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                let (name, descending) = match key.strip_prefix('-') {
+                    Some(name) => (name, true),
+                    None => (key.strip_prefix('+').unwrap_or(key), false),
+                };
+                let name = name.to_lowercase();
+                fields
+                    .iter()
+                    .find(|f| f.names.contains(&name.as_str()))
+                    .map(|f| SortKey {
+                        expr: f.expr,
+                        descending,
+                    })
+                    .ok_or_else(|| {
+                        let known: Vec<&str> = fields.iter().map(|f| f.names[0]).collect();
+                        format!(
+                            "Unknown sort field: {}. Expected one of: {}",
+                            name,
+                            known.join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Sort { keys })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The `ORDER BY` clause for this sort, falling back to `default` when
+    /// empty. Missing values sort last in either direction, and `default`
+    /// breaks ties.
+    pub fn order_by(&self, default: &str) -> String {
+        if self.keys.is_empty() {
+            return format!(" ORDER BY {}", default);
+        }
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| {
+                let direction = if key.descending { "DESC" } else { "ASC" };
+                format!("{} {} NULLS LAST", key.expr, direction)
+            })
+            .collect();
+        format!(" ORDER BY {}, {}", keys.join(", "), default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sort() {
+        let sort = Sort::parse("priority, -Updated", TASK_SORT_FIELDS).unwrap();
+        assert_eq!(
+            sort.order_by("created_at DESC"),
+            " ORDER BY priority ASC NULLS LAST, updated_at DESC NULLS LAST, created_at DESC"
+        );
+        assert_eq!(
+            Sort::default().order_by("created_at DESC"),
+            " ORDER BY created_at DESC"
+        );
+
+        assert!(Sort::parse("name", TASK_SORT_FIELDS).is_err());
+        assert!(Sort::parse("-due", PROJECT_SORT_FIELDS).is_err());
+        assert!(Sort::parse("priority; DROP TABLE tasks", TASK_SORT_FIELDS).is_err());
+    }
+}
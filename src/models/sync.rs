@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+
+/// A local entity linked to its copy in an external tracker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct SyncLink {
+    pub provider: String,
+    /// "initiative", "project" or "task"
+    pub entity_type: String,
+    pub local_id: String,
+    pub remote_id: String,
+    /// Human-readable remote ID, e.g. an issue identifier like "ENG-42"
+    pub remote_key: Option<String>,
+    /// The local entity's `updated_at` as of the last sync
+    pub local_updated_at: String,
+    /// The remote entity's last update as of the last sync
+    pub remote_updated_at: String,
+    pub synced_at: String,
+}
+
+/// Which side wins when an entity changed both locally and remotely
+/// since the last sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Local changes overwrite remote ones
+    Local,
+    /// Remote changes overwrite local ones
+    Remote,
+    /// The side updated most recently wins
+    #[default]
+    Newest,
+}
+
+impl ConflictStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictStrategy::Local => "local",
+            ConflictStrategy::Remote => "remote",
+            ConflictStrategy::Newest => "newest",
+        }
+    }
+
+    /// Which side wins for an entity that changed on both sides since the
+    /// last sync, given both sides' RFC 3339 update times
+    pub fn resolve(&self, local_updated_at: &str, remote_updated_at: &str) -> SyncDirection {
+        match self {
+            ConflictStrategy::Local => SyncDirection::Push,
+            ConflictStrategy::Remote => SyncDirection::Pull,
+            ConflictStrategy::Newest => {
+                let parse = |s: &str| chrono::DateTime::parse_from_rfc3339(s).ok();
+                let remote_is_newer = match (parse(local_updated_at), parse(remote_updated_at)) {
+                    (Some(local), Some(remote)) => remote > local,
+                    _ => remote_updated_at > local_updated_at,
+                };
+                if remote_is_newer {
+                    SyncDirection::Pull
+                } else {
+                    SyncDirection::Push
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConflictStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "local" | "local-wins" => Ok(ConflictStrategy::Local),
+            "remote" | "remote-wins" => Ok(ConflictStrategy::Remote),
+            "newest" | "newest-wins" => Ok(ConflictStrategy::Newest),
+            other => Err(format!(
+                "Unknown conflict strategy: {}. Expected local, remote or newest",
+                other
+            )),
+        }
+    }
+}
+
+/// Options for one sync
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    /// Report what would change without changing either side
+    pub dry_run: bool,
+    /// Ignore the cursors and compare everything
+    pub full: bool,
+    /// Conflict strategy for this sync, overriding the configured one
+    pub conflict: Option<ConflictStrategy>,
+}
+
+/// Whether a change was sent to the tracker or applied locally
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncDirection {
+    Push,
+    Pull,
+}
+
+impl SyncDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncDirection::Push => "push",
+            SyncDirection::Pull => "pull",
+        }
+    }
+}
+
+/// One entity copied by a sync
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncChange {
+    pub entity_type: String,
+    /// Local ID, or `None` for a remote entity not yet created locally
+    /// during a dry run
+    pub local_id: Option<String>,
+    /// Remote identifier, or `None` for a local entity not yet created
+    /// remotely during a dry run
+    pub remote_key: Option<String>,
+    pub title: String,
+    pub direction: SyncDirection,
+    /// The entity did not exist on the receiving side before
+    pub created: bool,
+    /// Both sides had changed and the conflict strategy picked the winner
+    pub conflict: bool,
+}
+
+/// The outcome of `granary sync`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub provider: String,
+    pub dry_run: bool,
+    pub conflict_strategy: ConflictStrategy,
+    pub changes: Vec<SyncChange>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conflict_strategy() {
+        assert_eq!("local".parse(), Ok(ConflictStrategy::Local));
+        assert_eq!("Remote_Wins".parse(), Ok(ConflictStrategy::Remote));
+        assert_eq!("newest-wins".parse(), Ok(ConflictStrategy::Newest));
+        assert!("oldest".parse::<ConflictStrategy>().is_err());
+    }
+
+    #[test]
+    fn test_resolve_conflict() {
+        let older = "2026-01-01T00:00:00Z";
+        let newer = "2026-01-01T00:00:01.000Z";
+        assert_eq!(
+            ConflictStrategy::Newest.resolve(older, newer),
+            SyncDirection::Pull
+        );
+        assert_eq!(
+            ConflictStrategy::Newest.resolve(newer, older),
+            SyncDirection::Push
+        );
+        assert_eq!(
+            ConflictStrategy::Local.resolve(older, newer),
+            SyncDirection::Push
+        );
+        assert_eq!(
+            ConflictStrategy::Remote.resolve(newer, older),
+            SyncDirection::Pull
+        );
+    }
+}
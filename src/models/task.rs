@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::BTreeMap;
+
+use super::FieldAssignment;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
@@ -105,24 +108,49 @@ pub struct Task {
     pub status: String,
     pub priority: String,
     pub owner: Option<String>,
+    /// Who created the task; NULL for tasks created before authors were recorded
+    #[serde(default)]
+    pub author: Option<String>,
     pub tags: Option<String>, // JSON array
     pub blocked_reason: Option<String>,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub due_at: Option<String>,
+    /// Estimated effort, in points or hours
+    #[serde(default)]
+    pub estimate: Option<f64>,
 
     // Claim/lease fields
     pub claim_owner: Option<String>,
     pub claim_claimed_at: Option<String>,
     pub claim_lease_expires_at: Option<String>,
+    /// Session holding the claim. Other sessions can't change the task's
+    /// status while the lease lasts.
+    #[serde(default)]
+    pub claim_session_id: Option<String>,
 
     // Attention/focus fields
     pub pinned: i64,
     pub focus_weight: i64,
 
+    /// When the task was archived; archived tasks are hidden from default
+    /// listings, summaries and search
+    #[serde(default)]
+    pub archived_at: Option<String>,
+
     pub created_at: String,
     pub updated_at: String,
     pub version: i64,
+
+    /// Labels from the `task_labels` table, filled in by the task service
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+
+    /// Custom field values, filled in by the task service
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
 impl Task {
@@ -141,10 +169,29 @@ impl Task {
             .unwrap_or_default()
     }
 
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
     pub fn is_blocked(&self) -> bool {
         self.status_enum() == TaskStatus::Blocked || self.blocked_reason.is_some()
     }
 
+    /// Whether the task is open and past its due date
+    pub fn is_overdue(&self) -> bool {
+        self.is_due_before(chrono::Utc::now())
+    }
+
+    /// Whether the task is open and due before `time`
+    pub fn is_due_before(&self, time: chrono::DateTime<chrono::Utc>) -> bool {
+        !self.status_enum().is_terminal()
+            && self
+                .due_at
+                .as_deref()
+                .and_then(|due| chrono::DateTime::parse_from_rfc3339(due).ok())
+                .is_some_and(|due| due < time)
+    }
+
     pub fn is_claimed(&self) -> bool {
         if let (Some(expires_at), Some(_)) = (&self.claim_lease_expires_at, &self.claim_owner) {
             // Check if lease is still valid
@@ -161,6 +208,7 @@ impl Task {
                 owner: owner.clone(),
                 claimed_at: claimed_at.clone(),
                 lease_expires_at: self.claim_lease_expires_at.clone(),
+                session_id: self.claim_session_id.clone(),
             })
         } else {
             None
@@ -168,11 +216,63 @@ impl Task {
     }
 }
 
+/// A task from `granary tasks --global`, with the name of the workspace it
+/// belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTask {
+    pub workspace: String,
+    #[serde(flatten)]
+    pub task: Task,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClaimInfo {
     pub owner: String,
     pub claimed_at: String,
     pub lease_expires_at: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// A label attached to a task
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskLabel {
+    pub task_id: String,
+    pub name: String,
+}
+
+/// A change to a task's labels: `+name` or `name` adds, `-name` removes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelChange {
+    Add(String),
+    Remove(String),
+}
+
+impl std::str::FromStr for LabelChange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (change, name): (fn(String) -> LabelChange, &str) = match s.strip_prefix('-') {
+            Some(name) => (LabelChange::Remove, name),
+            None => (LabelChange::Add, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if name.is_empty() {
+            return Err(format!("Invalid label '{}': name is empty", s));
+        }
+        if name.contains(|c: char| c.is_whitespace() || c == ',') {
+            return Err(format!(
+                "Invalid label '{}': names cannot contain spaces or commas",
+                s
+            ));
+        }
+        if name.starts_with(['+', '-']) {
+            return Err(format!(
+                "Invalid label '{}': name cannot start with + or -",
+                s
+            ));
+        }
+        Ok(change(name.to_string()))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -182,6 +282,15 @@ pub struct TaskDependency {
     pub created_at: String,
 }
 
+/// Tasks and the dependencies between them, from `granary tasks graph`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskGraph {
+    /// The project shown, or None for the whole workspace
+    pub project_id: Option<String>,
+    pub tasks: Vec<Task>,
+    pub dependencies: Vec<TaskDependency>,
+}
+
 #[derive(Debug, Default)]
 pub struct CreateTask {
     pub project_id: String,
@@ -192,6 +301,7 @@ pub struct CreateTask {
     pub owner: Option<String>,
     pub tags: Vec<String>,
     pub due_at: Option<String>,
+    pub estimate: Option<f64>,
 }
 
 #[derive(Debug, Default)]
@@ -204,6 +314,30 @@ pub struct UpdateTask {
     pub tags: Option<Vec<String>>,
     pub blocked_reason: Option<String>,
     pub due_at: Option<String>,
+    pub estimate: Option<f64>,
     pub pinned: Option<bool>,
     pub focus_weight: Option<i64>,
+    pub fields: Vec<FieldAssignment>,
+}
+
+/// A ready task scored by `granary next`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRecommendation {
+    pub task: Task,
+    /// Sum of the factors' points
+    pub score: f64,
+    pub factors: Vec<ScoreFactor>,
+}
+
+/// One part of a recommendation's score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreFactor {
+    /// priority, due, stale or unblocks
+    pub name: String,
+    /// Points, with the weight applied
+    pub points: f64,
+    /// Weight from the session mode's `next_weights`, 1 unless set
+    pub weight: f64,
+    /// Why the task got the points, e.g. "due in 2 days"
+    pub detail: String,
 }
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Kind of relationship between two tasks. Each kind reads one way from the
+/// task it was recorded on and the other way from the linked task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskLinkKind {
+    RelatesTo,
+    Duplicates,
+    CausedBy,
+}
+
+impl TaskLinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskLinkKind::RelatesTo => "relates_to",
+            TaskLinkKind::Duplicates => "duplicates",
+            TaskLinkKind::CausedBy => "caused_by",
+        }
+    }
+
+    /// How the relationship reads from the linked task
+    pub fn inverse(&self) -> &'static str {
+        match self {
+            TaskLinkKind::RelatesTo => "relates_to",
+            TaskLinkKind::Duplicates => "duplicated_by",
+            TaskLinkKind::CausedBy => "causes",
+        }
+    }
+
+    /// Whether the relationship reads the same from both tasks
+    pub fn is_symmetric(&self) -> bool {
+        matches!(self, TaskLinkKind::RelatesTo)
+    }
+}
+
+impl std::str::FromStr for TaskLinkKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "relates_to" => Ok(TaskLinkKind::RelatesTo),
+            "duplicates" => Ok(TaskLinkKind::Duplicates),
+            "caused_by" => Ok(TaskLinkKind::CausedBy),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A typed relationship recorded from one task to another
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskLink {
+    pub task_id: String,
+    pub linked_task_id: String,
+    pub kind: String,
+    pub created_at: String,
+}
+
+impl TaskLink {
+    pub fn kind_enum(&self) -> Option<TaskLinkKind> {
+        self.kind.parse().ok()
+    }
+}
+
+/// A task related to another, described from that other task's side
+/// (`duplicated_by` rather than `duplicates` for the original)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedTask {
+    /// The task the relationship is described from
+    pub task_id: String,
+    /// `relates_to`, `duplicates`, `duplicated_by`, `caused_by` or `causes`
+    pub relation: String,
+    pub related_task_id: String,
+    pub title: String,
+    pub status: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_kind_reads_both_ways() {
+        let kind: TaskLinkKind = "caused-by".parse().unwrap();
+        assert_eq!(kind, TaskLinkKind::CausedBy);
+        assert_eq!(kind.inverse(), "causes");
+        assert_eq!(TaskLinkKind::Duplicates.inverse(), "duplicated_by");
+        assert!(TaskLinkKind::RelatesTo.is_symmetric());
+        assert!("blocks".parse::<TaskLinkKind>().is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Kind of version control reference linked to a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VcsLinkKind {
+    Branch,
+    Commit,
+}
+
+impl VcsLinkKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VcsLinkKind::Branch => "branch",
+            VcsLinkKind::Commit => "commit",
+        }
+    }
+}
+
+impl std::str::FromStr for VcsLinkKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "branch" => Ok(VcsLinkKind::Branch),
+            "commit" => Ok(VcsLinkKind::Commit),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A git branch or commit recorded against a task
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskVcsLink {
+    pub id: i64,
+    pub task_id: String,
+    pub kind: String,
+    /// Branch name or full commit SHA
+    pub ref_name: String,
+    /// Commit subject line (commits only)
+    pub summary: Option<String>,
+    pub created_at: String,
+}
+
+impl TaskVcsLink {
+    pub fn kind_enum(&self) -> Option<VcsLinkKind> {
+        self.kind.parse().ok()
+    }
+
+    /// Abbreviated reference for display (short SHA for commits)
+    pub fn short_ref(&self) -> &str {
+        match self.kind_enum() {
+            Some(VcsLinkKind::Commit) if self.ref_name.len() > 12 => &self.ref_name[..12],
+            _ => &self.ref_name,
+        }
+    }
+}
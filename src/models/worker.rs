@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -8,6 +10,8 @@ pub enum WorkerStatus {
     #[default]
     Pending,
     Running,
+    /// Running, but not starting runs for new events
+    Paused,
     Stopped,
     Error,
 }
@@ -17,6 +21,7 @@ impl WorkerStatus {
         match self {
             Self::Pending => "pending",
             Self::Running => "running",
+            Self::Paused => "paused",
             Self::Stopped => "stopped",
             Self::Error => "error",
         }
@@ -30,6 +35,7 @@ impl std::str::FromStr for WorkerStatus {
         match s.to_lowercase().as_str() {
             "pending" => Ok(WorkerStatus::Pending),
             "running" => Ok(WorkerStatus::Running),
+            "paused" => Ok(WorkerStatus::Paused),
             "stopped" => Ok(WorkerStatus::Stopped),
             "error" => Ok(WorkerStatus::Error),
             _ => Err(()),
@@ -43,6 +49,158 @@ impl std::fmt::Display for WorkerStatus {
     }
 }
 
+/// What a paused worker does with events that arrive while it is paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseMode {
+    /// Leave them to be handled when the worker resumes
+    #[default]
+    Queue,
+    /// Skip them
+    Drop,
+}
+
+impl PauseMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Queue => "queue",
+            Self::Drop => "drop",
+        }
+    }
+}
+
+impl std::str::FromStr for PauseMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "queue" => Ok(PauseMode::Queue),
+            "drop" => Ok(PauseMode::Drop),
+            _ => Err(format!(
+                "Invalid pause mode: {} (expected queue or drop)",
+                s
+            )),
+        }
+    }
+}
+
+/// How the delay between retry attempts grows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryStrategy {
+    /// Wait `base_delay_secs` before every retry
+    Fixed,
+    /// Double the delay after every attempt: base, 2*base, 4*base...
+    #[default]
+    Exponential,
+}
+
+impl RetryStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Exponential => "exponential",
+        }
+    }
+}
+
+impl std::str::FromStr for RetryStrategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fixed" => Ok(RetryStrategy::Fixed),
+            "exponential" => Ok(RetryStrategy::Exponential),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for RetryStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Longest delay allowed between retries, in seconds (one week)
+pub const MAX_RETRY_DELAY_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Retry policy applied to a worker's failed runs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub strategy: RetryStrategy,
+    /// Total attempts per run, including the first (1 disables retries)
+    pub max_attempts: i32,
+    /// Delay before the first retry, in seconds
+    pub base_delay_secs: u64,
+    /// Upper bound on the delay before jitter is added, in seconds
+    pub max_delay_secs: Option<u64>,
+    /// Add 0-25% random jitter to each delay
+    pub jitter: bool,
+    /// Also retry runs killed for exceeding the worker's timeout
+    pub retry_timeouts: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            strategy: RetryStrategy::Exponential,
+            max_attempts: 3,
+            base_delay_secs: 5,
+            max_delay_secs: None,
+            jitter: true,
+            retry_timeouts: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after the given (1-based) attempt failed, at
+    /// most [`MAX_RETRY_DELAY_SECS`] before jitter
+    pub fn delay(&self, attempt: i32) -> Duration {
+        let delay = match self.strategy {
+            RetryStrategy::Fixed => self.base_delay_secs,
+            RetryStrategy::Exponential => {
+                // Cap at 2^10 to prevent overflow
+                let exp = (attempt - 1).clamp(0, 10) as u32;
+                self.base_delay_secs.saturating_mul(2u64.pow(exp))
+            }
+        };
+        let delay = self
+            .max_delay_secs
+            .map_or(delay, |max| delay.min(max))
+            .min(MAX_RETRY_DELAY_SECS);
+
+        let jitter_range = delay / 4;
+        let jitter = if self.jitter && jitter_range > 0 {
+            rand::random::<u64>() % jitter_range
+        } else {
+            0
+        };
+
+        Duration::from_secs(delay.saturating_add(jitter))
+    }
+
+    /// Short human-readable description, e.g. "exponential, 3 attempts, 5s base"
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}, {} attempts, {}s base",
+            self.strategy, self.max_attempts, self.base_delay_secs
+        );
+        if let Some(max) = self.max_delay_secs {
+            summary.push_str(&format!(", {}s max", max));
+        }
+        if !self.jitter {
+            summary.push_str(", no jitter");
+        }
+        if self.retry_timeouts {
+            summary.push_str(", retries timeouts");
+        }
+        summary
+    }
+}
+
 /// Worker model representing a long-running process that subscribes to granary events
 /// and spawns runners to execute commands.
 ///
@@ -66,7 +224,7 @@ pub struct Worker {
     pub concurrency: i32,
     /// Workspace root path this worker is attached to
     pub instance_path: String,
-    /// Current worker status: pending, running, stopped, error
+    /// Current worker status: pending, running, paused, stopped, error
     pub status: String,
     /// Error message if status is error
     pub error_message: Option<String>,
@@ -85,6 +243,27 @@ pub struct Worker {
     pub poll_cooldown_secs: i64,
     /// ID of the last processed event for cursor-based polling
     pub last_event_id: i64,
+    /// Optional cron expression for timer-driven runs
+    pub schedule: Option<String>,
+    /// Next time the schedule is due to fire (RFC3339)
+    pub next_fire_at: Option<String>,
+    /// Retry policy for failed runs (stored as JSON), or None for the default
+    pub retry_policy: Option<String>,
+    /// Wall-clock limit per run in seconds, or None for no timeout
+    pub timeout_secs: Option<i64>,
+    /// Quiet period in seconds before a burst of events starts one run
+    pub debounce_secs: Option<i64>,
+    /// Minimum interval in seconds between runs started by events
+    pub throttle_secs: Option<i64>,
+    /// Send the triggering event and a context pack to runners as JSON on stdin
+    #[serde(default)]
+    pub stdin: bool,
+    /// Receive events from every registered workspace, not only `instance_path`
+    #[serde(default)]
+    pub global: bool,
+    /// What to do with events while paused: queue or drop
+    #[serde(default)]
+    pub on_pause: String,
 }
 
 impl Worker {
@@ -103,11 +282,44 @@ impl Worker {
         serde_json::from_str(&self.filters).unwrap_or_default()
     }
 
+    /// Parse the retry policy JSON, falling back to the default policy
+    pub fn retry_policy_config(&self) -> RetryPolicy {
+        self.retry_policy
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Check if the worker coalesces events into fewer runs
+    pub fn batches_events(&self) -> bool {
+        self.debounce_secs.is_some() || self.throttle_secs.is_some()
+    }
+
+    /// Check if the worker receives events from the workspace at `root`
+    pub fn serves_workspace(&self, root: &str) -> bool {
+        self.global || self.instance_path == root
+    }
+
+    /// Check if the worker fires on a cron schedule
+    pub fn is_scheduled(&self) -> bool {
+        self.schedule.is_some()
+    }
+
+    /// Parse the pause mode, falling back to queueing events
+    pub fn pause_mode(&self) -> PauseMode {
+        self.on_pause.parse().unwrap_or_default()
+    }
+
     /// Check if the worker is currently running
     pub fn is_running(&self) -> bool {
         self.status_enum() == WorkerStatus::Running
     }
 
+    /// Check if the worker is paused
+    pub fn is_paused(&self) -> bool {
+        self.status_enum() == WorkerStatus::Paused
+    }
+
     /// Check if the worker has stopped (either normally or with error)
     pub fn is_stopped(&self) -> bool {
         matches!(
@@ -129,6 +341,14 @@ pub struct CreateWorker {
     pub instance_path: String,
     pub poll_cooldown_secs: i64,
     pub detached: bool,
+    pub schedule: Option<String>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub timeout_secs: Option<i64>,
+    pub debounce_secs: Option<i64>,
+    pub throttle_secs: Option<i64>,
+    pub stdin: bool,
+    pub global: bool,
+    pub on_pause: PauseMode,
 }
 
 impl Default for CreateWorker {
@@ -143,6 +363,14 @@ impl Default for CreateWorker {
             instance_path: String::new(),
             poll_cooldown_secs: 300, // 5 minutes default
             detached: false,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         }
     }
 }
@@ -154,3 +382,76 @@ pub struct UpdateWorkerStatus {
     pub error_message: Option<String>,
     pub pid: Option<i64>,
 }
+
+/// Result of `granary workers test-filter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilterTest {
+    pub filter: String,
+    /// `expression` or `field`, for `field=value` filters
+    pub kind: String,
+    /// Whether the payload matched, when one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_fixed_delay() {
+        let policy = RetryPolicy {
+            strategy: RetryStrategy::Fixed,
+            base_delay_secs: 10,
+            jitter: false,
+            ..Default::default()
+        };
+        for attempt in 1..=5 {
+            assert_eq!(policy.delay(attempt), Duration::from_secs(10));
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_exponential_delay_with_cap() {
+        let policy = RetryPolicy {
+            base_delay_secs: 5,
+            max_delay_secs: Some(30),
+            jitter: false,
+            ..Default::default()
+        };
+        let delays: Vec<u64> = (1..=5).map(|a| policy.delay(a).as_secs()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+    }
+
+    #[test]
+    fn test_retry_policy_delay_is_bounded() {
+        let policy = RetryPolicy {
+            base_delay_secs: u64::MAX,
+            ..Default::default()
+        };
+        for attempt in [1, 5, i32::MAX] {
+            let delay = policy.delay(attempt).as_secs();
+            assert!((MAX_RETRY_DELAY_SECS..MAX_RETRY_DELAY_SECS * 5 / 4).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_jitter_bounds() {
+        let policy = RetryPolicy {
+            base_delay_secs: 100,
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let delay = policy.delay(1).as_secs();
+            assert!((100..125).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_retry_policy_partial_json_uses_defaults() {
+        let policy: RetryPolicy = serde_json::from_str(r#"{"strategy":"fixed"}"#).unwrap();
+        assert_eq!(policy.strategy, RetryStrategy::Fixed);
+        assert_eq!(policy.max_attempts, 3);
+        assert!(policy.jitter);
+    }
+}
@@ -0,0 +1,190 @@
+//! Workspace configuration file model.
+//!
+//! The file lives at `.granary/granary.toml` and holds settings for one
+//! workspace, merged over the global config: runners, defaults for new
+//! tasks, output preferences, hooks and session modes.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::TaskStatus;
+use crate::models::global_config::{RunnerConfig, matches_event_patterns};
+
+/// Workspace configuration stored at `.granary/granary.toml`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    /// Runners for this workspace, replacing global runners of the same name
+    #[serde(default)]
+    pub runners: HashMap<String, RunnerConfig>,
+
+    /// Defaults for new tasks
+    #[serde(default)]
+    pub defaults: WorkspaceDefaults,
+
+    /// Output preferences, used when not given on the command line
+    #[serde(default)]
+    pub output: OutputPreferences,
+
+    /// Commands run after a granary command emits matching events
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+
+    /// Session modes, giving a built-in mode effects or defining a new one
+    #[serde(default)]
+    pub modes: HashMap<String, ModeConfig>,
+}
+
+/// Defaults for new tasks, set as `[defaults]`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceDefaults {
+    /// Priority of new tasks created without `--priority` (P0-P4)
+    #[serde(default)]
+    pub priority: Option<String>,
+}
+
+/// Output preferences, set as `[output]`
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OutputPreferences {
+    /// Output format used without `--format` or `--json`
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// Maximum table width used without `--max-width`
+    #[serde(default)]
+    pub max_width: Option<usize>,
+}
+
+/// A command run for events emitted by a granary command in this
+/// workspace, set as `[[hooks]]`.
+///
+/// The command runs through the shell from the workspace root, with the
+/// event as JSON on stdin and `GRANARY_EVENT_ID`, `GRANARY_EVENT_TYPE` and
+/// `GRANARY_ENTITY_ID` in its environment.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    /// Event types that run the hook (e.g., "task.completed", "task.*")
+    pub on: Vec<String>,
+
+    /// Shell command to run
+    pub command: String,
+}
+
+impl HookConfig {
+    /// Check whether an event type runs this hook
+    pub fn matches(&self, event_type: &str) -> bool {
+        matches_event_patterns(&self.on, event_type)
+    }
+}
+
+/// What a session mode changes, set as `[modes.<name>]`.
+///
+/// The built-in modes (execute, plan, review) change nothing until
+/// configured here. Any other name defines a mode for
+/// `granary session start --mode`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ModeConfig {
+    /// What the mode is for
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Summary sections built first, so they are the last trimmed by a
+    /// token budget, and listed as emphasized in prompt output
+    #[serde(default)]
+    pub emphasize: Vec<String>,
+
+    /// Statuses tasks can be moved to while a session is in this mode.
+    /// Any status when unset.
+    #[serde(default)]
+    pub statuses: Option<Vec<String>>,
+
+    /// Instructions for agents, included in summary and context prompts
+    #[serde(default)]
+    pub prompt: Option<String>,
+
+    /// Weights `granary next` gives its scoring factors (priority, due,
+    /// stale, unblocks) in this mode. Factors not listed weigh 1.
+    #[serde(default)]
+    pub next_weights: HashMap<String, f64>,
+}
+
+impl ModeConfig {
+    /// Check whether tasks can be moved to a status in this mode
+    pub fn allows_status(&self, status: &TaskStatus) -> bool {
+        self.statuses.as_ref().is_none_or(|statuses| {
+            statuses
+                .iter()
+                .any(|s| s.parse::<TaskStatus>().is_ok_and(|s| s == *status))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workspace_config() {
+        let config: WorkspaceConfig = toml::from_str(
+            r#"
+            [runners.test]
+            command = "cargo"
+            args = ["test"]
+
+            [defaults]
+            priority = "P1"
+
+            [output]
+            format = "json"
+            max_width = 100
+
+            [[hooks]]
+            on = ["task.completed", "run.*"]
+            command = "echo done"
+
+            [modes.review]
+            emphasize = ["blockers", "decisions"]
+            statuses = ["blocked", "done"]
+            prompt = "Review finished work; don't start new tasks."
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.runners["test"].args, vec!["test"]);
+        assert_eq!(config.defaults.priority.as_deref(), Some("P1"));
+        assert_eq!(config.output.format.as_deref(), Some("json"));
+        assert_eq!(config.output.max_width, Some(100));
+        assert!(config.hooks[0].matches("task.completed"));
+        assert!(config.hooks[0].matches("run.failed"));
+        assert!(!config.hooks[0].matches("task.created"));
+        let review = &config.modes["review"];
+        assert_eq!(review.emphasize, vec!["blockers", "decisions"]);
+        assert!(review.allows_status(&TaskStatus::Done));
+        assert!(!review.allows_status(&TaskStatus::InProgress));
+        assert!(ModeConfig::default().allows_status(&TaskStatus::InProgress));
+
+        assert!(toml::from_str::<WorkspaceConfig>("[output]\ncolour = true").is_err());
+    }
+
+    #[test]
+    fn test_schema_matches_fields() {
+        let schema = serde_json::to_value(schemars::schema_for!(WorkspaceConfig)).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        let mut keys: Vec<_> = properties.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec!["defaults", "hooks", "modes", "output", "runners"]
+        );
+        // Unknown keys are rejected when parsing, so the schema says so too
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(
+            schema["$defs"]["HookConfig"]["required"],
+            serde_json::json!(["on", "command"])
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Column selection and width control for table output.
+//!
+//! Task tables show the columns given with `--columns`, or the ones saved for
+//! the command in workspace config (`granary config set columns.tasks
+//! id,title,due`). `--max-width` truncates any table to fit.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::is_valid_field_name;
+
+/// Prefix of the config keys that hold preferred columns per command
+pub const COLUMNS_KEY_PREFIX: &str = "columns.";
+
+/// A column of a task table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskColumn {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Owner,
+    Author,
+    Labels,
+    Project,
+    Due,
+    Estimate,
+    Tags,
+    BlockedBy,
+    Created,
+    Updated,
+    /// A custom field, written `field.<name>`
+    Field(String),
+}
+
+impl TaskColumn {
+    /// The columns shown when none are configured
+    pub fn defaults() -> Vec<TaskColumn> {
+        vec![
+            TaskColumn::Id,
+            TaskColumn::Title,
+            TaskColumn::Status,
+            TaskColumn::Priority,
+            TaskColumn::Owner,
+            TaskColumn::Labels,
+        ]
+    }
+
+    pub fn header(&self) -> String {
+        match self {
+            TaskColumn::Id => "ID".to_string(),
+            TaskColumn::Title => "Title".to_string(),
+            TaskColumn::Status => "Status".to_string(),
+            TaskColumn::Priority => "Priority".to_string(),
+            TaskColumn::Owner => "Owner".to_string(),
+            TaskColumn::Author => "Author".to_string(),
+            TaskColumn::Labels => "Labels".to_string(),
+            TaskColumn::Project => "Project".to_string(),
+            TaskColumn::Due => "Due".to_string(),
+            TaskColumn::Estimate => "Estimate".to_string(),
+            TaskColumn::Tags => "Tags".to_string(),
+            TaskColumn::BlockedBy => "Blocked By".to_string(),
+            TaskColumn::Created => "Created".to_string(),
+            TaskColumn::Updated => "Updated".to_string(),
+            TaskColumn::Field(name) => name.clone(),
+        }
+    }
+}
+
+impl fmt::Display for TaskColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskColumn::Id => write!(f, "id"),
+            TaskColumn::Title => write!(f, "title"),
+            TaskColumn::Status => write!(f, "status"),
+            TaskColumn::Priority => write!(f, "priority"),
+            TaskColumn::Owner => write!(f, "owner"),
+            TaskColumn::Author => write!(f, "author"),
+            TaskColumn::Labels => write!(f, "labels"),
+            TaskColumn::Project => write!(f, "project"),
+            TaskColumn::Due => write!(f, "due"),
+            TaskColumn::Estimate => write!(f, "estimate"),
+            TaskColumn::Tags => write!(f, "tags"),
+            TaskColumn::BlockedBy => write!(f, "blocked_by"),
+            TaskColumn::Created => write!(f, "created"),
+            TaskColumn::Updated => write!(f, "updated"),
+            TaskColumn::Field(name) => write!(f, "field.{}", name),
+        }
+    }
+}
+
+impl FromStr for TaskColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if let Some(name) = s.strip_prefix("field.") {
+            if !is_valid_field_name(name) {
+                return Err(format!("Invalid field column: {}", s));
+            }
+            return Ok(TaskColumn::Field(name.to_string()));
+        }
+        match s.as_str() {
+            "id" => Ok(TaskColumn::Id),
+            "title" => Ok(TaskColumn::Title),
+            "status" => Ok(TaskColumn::Status),
+            "priority" => Ok(TaskColumn::Priority),
+            "owner" | "assignee" => Ok(TaskColumn::Owner),
+            "author" => Ok(TaskColumn::Author),
+            "labels" | "label" => Ok(TaskColumn::Labels),
+            "project" => Ok(TaskColumn::Project),
+            "due" => Ok(TaskColumn::Due),
+            "estimate" => Ok(TaskColumn::Estimate),
+            "tags" => Ok(TaskColumn::Tags),
+            "blocked_by" | "deps" => Ok(TaskColumn::BlockedBy),
+            "created" => Ok(TaskColumn::Created),
+            "updated" => Ok(TaskColumn::Updated),
+            _ => Err(format!(
+                "Unknown column: {}. Expected id, title, status, priority, owner, author, \
+                 labels, project, due, estimate, tags, blocked_by, created, updated or field.<name>",
+                s
+            )),
+        }
+    }
+}
+
+/// Parse a comma-separated column list
+pub fn parse_columns(s: &str) -> std::result::Result<Vec<TaskColumn>, String> {
+    let columns = s
+        .split(',')
+        .filter(|c| !c.trim().is_empty())
+        .map(str::parse)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if columns.is_empty() {
+        return Err("No columns given".to_string());
+    }
+    Ok(columns)
+}
+
+/// Table settings for the current invocation
+#[derive(Debug, Clone, Default)]
+pub struct TableOptions {
+    /// Task table columns, or `None` for the defaults
+    pub columns: Option<Vec<TaskColumn>>,
+    /// Maximum table width in characters
+    pub max_width: Option<usize>,
+}
+
+static TABLE_OPTIONS: OnceLock<TableOptions> = OnceLock::new();
+
+/// Resolve and install the process-wide table options.
+///
+/// `--columns` wins over the columns saved for `command`. An invalid flag is
+/// an error, while an invalid saved value falls back to the defaults like
+/// other display settings.
+pub async fn init(
+    pool: Option<&SqlitePool>,
+    command: Option<&str>,
+    columns: Option<&str>,
+    max_width: Option<usize>,
+) -> Result<TableOptions> {
+    let columns = match columns {
+        Some(columns) => Some(parse_columns(columns).map_err(GranaryError::InvalidArgument)?),
+        None => match (pool, command) {
+            (Some(pool), Some(command)) => {
+                let key = format!("{}{}", COLUMNS_KEY_PREFIX, command);
+                db::config::get(pool, &key)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|value| parse_columns(&value).ok())
+            }
+            _ => None,
+        },
+    };
+
+    let options = TableOptions { columns, max_width };
+    let _ = TABLE_OPTIONS.set(options.clone());
+    Ok(options)
+}
+
+/// The process-wide options, or defaults if `init` has not been called
+pub fn options() -> TableOptions {
+    TABLE_OPTIONS.get().cloned().unwrap_or_default()
+}
+
+/// Validate a value for a `columns.<command>` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(command) = key.strip_prefix(COLUMNS_KEY_PREFIX) else {
+        return Ok(());
+    };
+    if command.is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "Name the command the columns are for, e.g. columns.tasks".to_string(),
+        ));
+    }
+    parse_columns(value)
+        .map(|_| ())
+        .map_err(GranaryError::InvalidArgument)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(
+            parse_columns("id, Title,field.env").unwrap(),
+            vec![
+                TaskColumn::Id,
+                TaskColumn::Title,
+                TaskColumn::Field("env".to_string())
+            ]
+        );
+        assert!(parse_columns("id,colour").is_err());
+        assert!(parse_columns("field.").is_err());
+        assert!(parse_columns(" , ").is_err());
+    }
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value("columns.tasks", "id,title,due").is_ok());
+        assert!(validate_config_value("columns.tasks", "id,bogus").is_err());
+        assert!(validate_config_value("columns.", "id").is_err());
+        assert!(validate_config_value("other.key", "anything").is_ok());
+    }
+}
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use serde_json::json;
 
-use crate::models::initiative::Initiative;
+use crate::models::initiative::{Initiative, MilestoneProgress};
 use crate::models::*;
 
 /// Task output with dependency information
@@ -12,19 +12,46 @@ pub struct TaskOutput {
     pub task: Task,
     /// List of task IDs that block this task (unmet dependencies)
     pub blocked_by: Vec<String>,
+    /// Git branches and commits linked to this task
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vcs_links: Vec<TaskVcsLink>,
+    /// Files attached to this task
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Tasks linked to this task
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related: Vec<RelatedTask>,
 }
 
 impl TaskOutput {
     pub fn new(task: Task, blocked_by: Vec<String>) -> Self {
-        Self { task, blocked_by }
-    }
-
-    pub fn from_task(task: Task) -> Self {
         Self {
             task,
-            blocked_by: vec![],
+            blocked_by,
+            vcs_links: vec![],
+            attachments: vec![],
+            related: vec![],
         }
     }
+
+    pub fn from_task(task: Task) -> Self {
+        Self::new(task, vec![])
+    }
+
+    pub fn with_vcs_links(mut self, links: Vec<TaskVcsLink>) -> Self {
+        self.vcs_links = links;
+        self
+    }
+
+    pub fn with_attachments(mut self, attachments: Vec<Attachment>) -> Self {
+        self.attachments = attachments;
+        self
+    }
+
+    pub fn with_related(mut self, related: Vec<RelatedTask>) -> Self {
+        self.related = related;
+        self
+    }
 }
 
 pub fn format_project(project: &Project) -> String {
@@ -45,6 +72,32 @@ pub fn format_task_with_deps(task: &Task, blocked_by: Vec<String>) -> String {
     serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
 }
 
+pub fn format_task_with_links(
+    task: &Task,
+    blocked_by: Vec<String>,
+    links: &[TaskVcsLink],
+    attachments: &[Attachment],
+    related: &[RelatedTask],
+) -> String {
+    let output = TaskOutput::new(task.clone(), blocked_by)
+        .with_vcs_links(links.to_vec())
+        .with_attachments(attachments.to_vec())
+        .with_related(related.to_vec());
+    serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_vcs_links(links: &[TaskVcsLink]) -> String {
+    serde_json::to_string_pretty(links).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_attachments(attachments: &[Attachment]) -> String {
+    serde_json::to_string_pretty(attachments).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_related_tasks(related: &[RelatedTask]) -> String {
+    serde_json::to_string_pretty(related).unwrap_or_else(|_| "[]".to_string())
+}
+
 pub fn format_tasks(tasks: &[Task]) -> String {
     let outputs: Vec<TaskOutput> = tasks
         .iter()
@@ -61,6 +114,10 @@ pub fn format_tasks_with_deps(tasks_with_deps: &[(Task, Vec<String>)]) -> String
     serde_json::to_string_pretty(&outputs).unwrap_or_else(|_| "[]".to_string())
 }
 
+pub fn format_workspace_tasks(tasks: &[WorkspaceTask]) -> String {
+    serde_json::to_string_pretty(tasks).unwrap_or_else(|_| "[]".to_string())
+}
+
 pub fn format_comment(comment: &Comment) -> String {
     serde_json::to_string_pretty(comment).unwrap_or_else(|_| "{}".to_string())
 }
@@ -69,6 +126,10 @@ pub fn format_comments(comments: &[Comment]) -> String {
     serde_json::to_string_pretty(comments).unwrap_or_else(|_| "[]".to_string())
 }
 
+pub fn format_comment_threads(threads: &[CommentThread]) -> String {
+    serde_json::to_string_pretty(threads).unwrap_or_else(|_| "[]".to_string())
+}
+
 pub fn format_session(session: &Session) -> String {
     serde_json::to_string_pretty(session).unwrap_or_else(|_| "{}".to_string())
 }
@@ -93,8 +154,9 @@ pub fn format_artifacts(artifacts: &[Artifact]) -> String {
     serde_json::to_string_pretty(artifacts).unwrap_or_else(|_| "[]".to_string())
 }
 
-pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
-    let output = if let Some(t) = task {
+/// The `{task, reason}` object shared by the JSON and YAML next-task output
+pub fn next_task_output(task: Option<&Task>, reason: Option<&str>) -> serde_json::Value {
+    if let Some(t) = task {
         json!({
             "task": t,
             "reason": reason
@@ -104,22 +166,76 @@ pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
             "task": null,
             "reason": "No actionable tasks found"
         })
-    };
-    serde_json::to_string_pretty(&output).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
+    serde_json::to_string_pretty(&next_task_output(task, reason))
+        .unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_recommendations(recommendations: &[TaskRecommendation]) -> String {
+    serde_json::to_string_pretty(recommendations).unwrap_or_else(|_| "[]".to_string())
 }
 
 /// Format a summary as JSON
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct SummaryOutput {
     pub session: Option<SessionSummary>,
     pub state: StateSummary,
     pub focus_task: Option<Task>,
+    /// Tasks below the focus task on the session's focus stack, most
+    /// recently set aside first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub interrupted: Vec<Task>,
     pub blockers: Vec<Task>,
+    /// Open tasks due within `reminders.due_soon` or overdue, soonest first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub due_soon: Vec<Task>,
+    /// Open tasks with no activity within `summary.stale_after`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stale: Vec<StaleTask>,
     pub next_actions: Vec<Task>,
-    pub recent_decisions: Vec<Comment>,
+    pub recent_decisions: Vec<RecentDecision>,
     pub recent_artifacts: Vec<Artifact>,
 }
 
+/// An open task with no status change, comment or checkpoint mentioning it
+/// for a while
+#[derive(Serialize)]
+pub struct StaleTask {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub priority: String,
+    pub last_activity: String,
+    /// Whole days since the last activity
+    pub idle_days: i64,
+}
+
+/// A decision comment, with the comment that started its thread when it is
+/// a reply
+#[derive(Serialize)]
+pub struct RecentDecision {
+    #[serde(flatten)]
+    pub comment: Comment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_root: Option<Comment>,
+}
+
+impl RecentDecision {
+    /// "in reply to <author>: <first line>" when the decision is a reply
+    pub fn thread_context(&self) -> Option<String> {
+        self.thread_root.as_ref().map(|root| {
+            format!(
+                "in reply to {}: {}",
+                root.author.as_deref().unwrap_or("unknown"),
+                root.content.lines().next().unwrap_or_default()
+            )
+        })
+    }
+}
+
 #[derive(Serialize)]
 pub struct SessionSummary {
     pub id: String,
@@ -127,13 +243,52 @@ pub struct SessionSummary {
     pub mode: Option<String>,
     pub owner: Option<String>,
     pub focus_task_id: Option<String>,
+    /// The session's focus stack, bottom first, topped by the focus task
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub focus_stack: Vec<String>,
+    /// Summary sections the session's mode emphasizes
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub emphasize: Vec<String>,
+    /// Instructions for agents working in the session's mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct StateSummary {
     pub total_tasks: usize,
     pub by_status: StatusCounts,
     pub by_priority: PriorityCounts,
+    /// Open (not done) tasks per label
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub by_label: std::collections::BTreeMap<String, usize>,
+    /// Open (not done) tasks per assignee
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub by_assignee: std::collections::BTreeMap<String, usize>,
+    /// Tasks created and comments written per author
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub by_author: std::collections::BTreeMap<String, AuthorCounts>,
+    /// Estimated effort per project, then per initiative
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub estimates: Vec<EstimateRollup>,
+}
+
+/// Total and remaining (not done) estimate of a project's or initiative's
+/// estimated tasks
+#[derive(Serialize)]
+pub struct EstimateRollup {
+    /// "project" or "initiative"
+    pub kind: String,
+    pub id: String,
+    pub name: String,
+    pub total: f64,
+    pub remaining: f64,
+}
+
+#[derive(Serialize, Default)]
+pub struct AuthorCounts {
+    pub tasks: usize,
+    pub comments: usize,
 }
 
 #[derive(Serialize, Default)]
@@ -176,11 +331,22 @@ pub struct ContextOutput {
     pub tasks: Vec<Task>,
     pub comments: Vec<Comment>,
     pub artifacts: Vec<Artifact>,
+    pub attachments: Vec<AttachmentInfo>,
+    /// Links between the tasks in context and other tasks
+    pub related: Vec<RelatedTask>,
     pub decisions: Vec<Comment>,
     pub blockers: Vec<BlockerInfo>,
     pub steering: Vec<SteeringInfo>,
 }
 
+/// A task's attached file, with its absolute path so agents can open it
+#[derive(Serialize)]
+pub struct AttachmentInfo {
+    pub task_id: String,
+    pub name: String,
+    pub path: String,
+}
+
 #[derive(Serialize)]
 pub struct BlockerInfo {
     pub task_id: String,
@@ -234,6 +400,10 @@ pub fn format_search_results(results: &[SearchResult]) -> String {
     serde_json::to_string_pretty(results).unwrap_or_else(|_| "[]".to_string())
 }
 
+pub fn format_workspace_search_results(results: &[WorkspaceSearchResult]) -> String {
+    serde_json::to_string_pretty(results).unwrap_or_else(|_| "[]".to_string())
+}
+
 pub fn format_initiative(initiative: &Initiative) -> String {
     serde_json::to_string_pretty(initiative).unwrap_or_else(|_| "{}".to_string())
 }
@@ -242,6 +412,34 @@ pub fn format_initiatives(initiatives: &[Initiative]) -> String {
     serde_json::to_string_pretty(initiatives).unwrap_or_else(|_| "[]".to_string())
 }
 
+/// Initiative output with the initiative's milestones
+#[derive(Serialize)]
+pub struct InitiativeOutput {
+    #[serde(flatten)]
+    pub initiative: Initiative,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub milestones: Vec<MilestoneProgress>,
+}
+
+pub fn format_initiative_with_milestones(
+    initiative: &Initiative,
+    milestones: &[MilestoneProgress],
+) -> String {
+    serde_json::to_string_pretty(&InitiativeOutput {
+        initiative: initiative.clone(),
+        milestones: milestones.to_vec(),
+    })
+    .unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_milestone(milestone: &MilestoneProgress) -> String {
+    serde_json::to_string_pretty(milestone).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_milestones(milestones: &[MilestoneProgress]) -> String {
+    serde_json::to_string_pretty(milestones).unwrap_or_else(|_| "[]".to_string())
+}
+
 // === Initiative Summary ===
 
 use crate::models::initiative::InitiativeSummary;
@@ -252,7 +450,7 @@ pub fn format_initiative_summary(summary: &InitiativeSummary) -> String {
 
 // === Worker formatting ===
 
-use crate::models::worker::Worker;
+use crate::models::worker::{FilterTest, Worker};
 
 pub fn format_worker(worker: &Worker) -> String {
     serde_json::to_string_pretty(worker).unwrap_or_else(|_| "{}".to_string())
@@ -262,6 +460,10 @@ pub fn format_workers(workers: &[Worker]) -> String {
     serde_json::to_string_pretty(workers).unwrap_or_else(|_| "[]".to_string())
 }
 
+pub fn format_filter_test(test: &FilterTest) -> String {
+    serde_json::to_string_pretty(test).unwrap_or_else(|_| "{}".to_string())
+}
+
 // === Run formatting ===
 
 use crate::models::run::Run;
@@ -270,10 +472,114 @@ pub fn format_run(run: &Run) -> String {
     serde_json::to_string_pretty(run).unwrap_or_else(|_| "{}".to_string())
 }
 
+pub fn format_run_queue(queue: &[QueuedRun]) -> String {
+    serde_json::to_string_pretty(queue).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_import_report(report: &ImportReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_batch_plan(plan: &[PlannedChange]) -> String {
+    serde_json::to_string_pretty(plan).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_run_log_matches(matches: &[RunLogMatch]) -> String {
+    serde_json::to_string_pretty(matches).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_log_prune_report(report: &LogPruneReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
 pub fn format_runs(runs: &[Run]) -> String {
     serde_json::to_string_pretty(runs).unwrap_or_else(|_| "[]".to_string())
 }
 
+pub fn format_audit_entries(entries: &[AuditEntry]) -> String {
+    serde_json::to_string_pretty(entries).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_sync_report(report: &SyncReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_snapshot(snapshot: &Snapshot) -> String {
+    serde_json::to_string_pretty(snapshot).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_snapshots(snapshots: &[Snapshot]) -> String {
+    serde_json::to_string_pretty(snapshots).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_restore_report(report: &RestoreReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_remotes(remotes: &[Remote]) -> String {
+    serde_json::to_string_pretty(remotes).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_remote_sync_report(report: &RemoteSyncReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_remote_statuses(statuses: &[RemoteStatus]) -> String {
+    serde_json::to_string_pretty(statuses).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_workspaces(workspaces: &[WorkspaceOverview]) -> String {
+    serde_json::to_string_pretty(workspaces).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_plugins(plugins: &[Plugin]) -> String {
+    serde_json::to_string_pretty(plugins).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_event_types(types: &[EventTypeInfo]) -> String {
+    serde_json::to_string_pretty(types).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_burndown(burndown: &Burndown) -> String {
+    serde_json::to_string_pretty(burndown).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_rules(rules: &[Rule]) -> String {
+    serde_json::to_string_pretty(rules).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_rule_matches(matches: &[RuleMatch]) -> String {
+    serde_json::to_string_pretty(matches).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_board(board: &Board) -> String {
+    serde_json::to_string_pretty(board).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_timeline(timeline: &Timeline) -> String {
+    serde_json::to_string_pretty(timeline).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_velocity(velocity: &Velocity) -> String {
+    serde_json::to_string_pretty(velocity).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_session_review(review: &SessionReview) -> String {
+    serde_json::to_string_pretty(review).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_cost_report(report: &CostReport) -> String {
+    serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
+}
+
+pub fn format_project_health(health: &[ProjectHealth]) -> String {
+    serde_json::to_string_pretty(health).unwrap_or_else(|_| "[]".to_string())
+}
+
+pub fn format_task_graph(graph: &TaskGraph) -> String {
+    serde_json::to_string_pretty(graph).unwrap_or_else(|_| "{}".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -289,19 +595,25 @@ mod tests {
             status: "todo".to_string(),
             priority: "P1".to_string(),
             owner: Some("test-user".to_string()),
+            author: None,
             tags: None,
             blocked_reason: None,
             started_at: None,
             completed_at: None,
             due_at: None,
+            estimate: None,
             claim_owner: None,
             claim_claimed_at: None,
             claim_lease_expires_at: None,
+            claim_session_id: None,
             pinned: 0,
             focus_weight: 0,
+            archived_at: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             version: 1,
+            labels: vec![],
+            fields: Default::default(),
         }
     }
 
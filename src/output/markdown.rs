@@ -0,0 +1,381 @@
+//! Markdown reports for summaries and handoffs.
+//!
+//! These render the same structures as the JSON formatters as headings,
+//! tables and task checklists, ready to paste into a PR description or doc.
+
+use crate::models::*;
+use crate::output::json::{HandoffOutput, SummaryOutput};
+use crate::services::datetime;
+
+/// Escape text for use inside a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// A checklist item for a task, checked when it is done
+fn task_item(task: &Task) -> String {
+    let checkbox = if task.status == "done" { "[x]" } else { "[ ]" };
+    format!(
+        "- {} **{}** (`{}`) · {} · {}",
+        checkbox, task.title, task.id, task.priority, task.status
+    )
+}
+
+/// Format a work summary as a Markdown report
+pub fn format_summary(summary: &SummaryOutput) -> String {
+    let mut md = String::from("# Work Summary\n\n");
+
+    if let Some(session) = &summary.session {
+        md.push_str(&format!(
+            "**Session:** {} (`{}`)\n",
+            session.name.as_deref().unwrap_or("-"),
+            session.id
+        ));
+        if let Some(mode) = &session.mode {
+            md.push_str(&format!("**Mode:** {}\n", mode));
+        }
+        if let Some(owner) = &session.owner {
+            md.push_str(&format!("**Owner:** {}\n", owner));
+        }
+        if !session.emphasize.is_empty() {
+            md.push_str(&format!(
+                "**Emphasize:** {}\n",
+                session.emphasize.join(", ")
+            ));
+        }
+        if let Some(instructions) = &session.instructions {
+            md.push_str(&format!(
+                "\n> {}\n",
+                instructions.trim().replace('\n', "\n> ")
+            ));
+        }
+        md.push('\n');
+    }
+
+    let state = &summary.state;
+    md.push_str("## State of Work\n\n");
+    md.push_str(&format!("**Total tasks:** {}\n\n", state.total_tasks));
+    md.push_str("| Todo | In progress | Blocked | Done |\n");
+    md.push_str("| ---: | ---: | ---: | ---: |\n");
+    md.push_str(&format!(
+        "| {} | {} | {} | {} |\n\n",
+        state.by_status.todo,
+        state.by_status.in_progress,
+        state.by_status.blocked,
+        state.by_status.done
+    ));
+    md.push_str("| P0 | P1 | P2 | P3 | P4 |\n");
+    md.push_str("| ---: | ---: | ---: | ---: | ---: |\n");
+    md.push_str(&format!(
+        "| {} | {} | {} | {} | {} |\n\n",
+        state.by_priority.p0,
+        state.by_priority.p1,
+        state.by_priority.p2,
+        state.by_priority.p3,
+        state.by_priority.p4
+    ));
+    if !state.by_label.is_empty() {
+        md.push_str("| Label | Open tasks |\n");
+        md.push_str("| --- | ---: |\n");
+        for (label, count) in &state.by_label {
+            md.push_str(&format!("| {} | {} |\n", cell(label), count));
+        }
+        md.push('\n');
+    }
+    if !state.by_assignee.is_empty() {
+        md.push_str("| Assignee | Open tasks |\n");
+        md.push_str("| --- | ---: |\n");
+        for (assignee, count) in &state.by_assignee {
+            md.push_str(&format!("| {} | {} |\n", cell(assignee), count));
+        }
+        md.push('\n');
+    }
+    if !state.by_author.is_empty() {
+        md.push_str("| Author | Tasks | Comments |\n");
+        md.push_str("| --- | ---: | ---: |\n");
+        for (author, counts) in &state.by_author {
+            md.push_str(&format!(
+                "| {} | {} | {} |\n",
+                cell(author),
+                counts.tasks,
+                counts.comments
+            ));
+        }
+        md.push('\n');
+    }
+    if !state.estimates.is_empty() {
+        md.push_str("| Estimate | Remaining | Total |\n");
+        md.push_str("| --- | ---: | ---: |\n");
+        for rollup in &state.estimates {
+            md.push_str(&format!(
+                "| {} {} | {} | {} |\n",
+                rollup.kind,
+                cell(&rollup.name),
+                rollup.remaining,
+                rollup.total
+            ));
+        }
+        md.push('\n');
+    }
+
+    if let Some(focus) = &summary.focus_task {
+        md.push_str("## Focus\n\n");
+        md.push_str(&format!("{}\n", task_item(focus)));
+        if let Some(desc) = &focus.description {
+            md.push_str(&format!("\n{}\n", desc));
+        }
+        md.push('\n');
+    }
+
+    if !summary.interrupted.is_empty() {
+        md.push_str("## Set Aside\n\n");
+        for task in &summary.interrupted {
+            md.push_str(&format!("{}\n", task_item(task)));
+        }
+        md.push('\n');
+    }
+
+    if !summary.blockers.is_empty() {
+        md.push_str(&format!("## Blockers ({})\n\n", summary.blockers.len()));
+        for task in &summary.blockers {
+            md.push_str(&task_item(task));
+            if let Some(reason) = &task.blocked_reason {
+                md.push_str(&format!(": {}", reason));
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+
+    if !summary.due_soon.is_empty() {
+        md.push_str(&format!("## Due Soon ({})\n\n", summary.due_soon.len()));
+        for task in &summary.due_soon {
+            md.push_str(&format!(
+                "{} · due {}",
+                task_item(task),
+                datetime::format_local_date(task.due_at.as_deref().unwrap_or_default())
+            ));
+            if task.is_overdue() {
+                md.push_str(" · **overdue**");
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+
+    if !summary.stale.is_empty() {
+        md.push_str(&format!("## Stale ({})\n\n", summary.stale.len()));
+        for task in &summary.stale {
+            md.push_str(&format!(
+                "- [ ] **{}** (`{}`) · {} · {} · no activity in {}d\n",
+                task.title, task.id, task.priority, task.status, task.idle_days
+            ));
+        }
+        md.push('\n');
+    }
+
+    if !summary.next_actions.is_empty() {
+        md.push_str("## Next Actions\n\n");
+        for task in &summary.next_actions {
+            md.push_str(&format!("{}\n", task_item(task)));
+        }
+        md.push('\n');
+    }
+
+    if !summary.recent_decisions.is_empty() {
+        md.push_str("## Recent Decisions\n\n");
+        for decision in &summary.recent_decisions {
+            let author = decision.comment.author.as_deref().unwrap_or("unknown");
+            md.push_str(&format!("- **{}:** {}", author, decision.comment.content));
+            if let Some(context) = decision.thread_context() {
+                md.push_str(&format!(" _({})_", context));
+            }
+            md.push('\n');
+        }
+        md.push('\n');
+    }
+
+    if !summary.recent_artifacts.is_empty() {
+        md.push_str("## Recent Artifacts\n\n");
+        md.push_str("| Type | Path | Description |\n");
+        md.push_str("| --- | --- | --- |\n");
+        for artifact in &summary.recent_artifacts {
+            md.push_str(&format!(
+                "| {} | `{}` | {} |\n",
+                artifact.artifact_type,
+                cell(&artifact.path_or_url),
+                cell(artifact.description.as_deref().unwrap_or("-"))
+            ));
+        }
+        md.push('\n');
+    }
+
+    md
+}
+
+/// Format a handoff as a Markdown brief
+pub fn format_handoff(handoff: &HandoffOutput) -> String {
+    let mut md = format!("# Handoff to {}\n\n", handoff.to);
+
+    md.push_str("## Tasks\n\n");
+    for task in &handoff.tasks {
+        md.push_str(&format!("{}\n", task_item(task)));
+        if let Some(desc) = &task.description {
+            for line in desc.lines() {
+                md.push_str(&format!("  {}\n", line));
+            }
+        }
+    }
+    md.push('\n');
+
+    if !handoff.context.is_empty() {
+        md.push_str("## Context\n\n");
+        for comment in &handoff.context {
+            let author = comment.author.as_deref().unwrap_or("unknown");
+            md.push_str(&format!(
+                "- *{}* **{}:** {}\n",
+                comment.kind, author, comment.content
+            ));
+        }
+        md.push('\n');
+    }
+
+    if let Some(constraints) = &handoff.constraints {
+        md.push_str(&format!("## Constraints\n\n{}\n\n", constraints));
+    }
+
+    if let Some(criteria) = &handoff.acceptance_criteria {
+        md.push_str(&format!("## Acceptance Criteria\n\n{}\n\n", criteria));
+    }
+
+    if let Some(schema) = &handoff.output_schema {
+        md.push_str("## Output Schema\n\n```json\n");
+        md.push_str(&serde_json::to_string_pretty(schema).unwrap_or_else(|_| "{}".to_string()));
+        md.push_str("\n```\n\n");
+    }
+
+    if !handoff.steering.is_empty() {
+        md.push_str("## Steering\n\n");
+        for steering in &handoff.steering {
+            md.push_str(&format!("### `{}`\n\n", steering.path));
+            match &steering.content {
+                Some(content) => {
+                    md.push_str(content.trim_end());
+                    md.push_str("\n\n");
+                }
+                None => md.push_str("(reference to external document)\n\n"),
+            }
+        }
+    }
+
+    md
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::json::{AuthorCounts, PriorityCounts, StateSummary, StatusCounts};
+
+    fn create_test_task(id: &str, status: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "test-proj".to_string(),
+            task_number: 1,
+            parent_task_id: None,
+            title: format!("Task {}", id),
+            description: Some("Line one\nLine two".to_string()),
+            status: status.to_string(),
+            priority: "P1".to_string(),
+            owner: None,
+            author: None,
+            tags: None,
+            blocked_reason: Some("Waiting on API".to_string()),
+            started_at: None,
+            completed_at: None,
+            due_at: None,
+            estimate: None,
+            claim_owner: None,
+            claim_claimed_at: None,
+            claim_lease_expires_at: None,
+            claim_session_id: None,
+            pinned: 0,
+            focus_weight: 0,
+            archived_at: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            version: 1,
+            labels: vec![],
+            fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let summary = SummaryOutput {
+            session: None,
+            state: StateSummary {
+                total_tasks: 3,
+                by_status: StatusCounts {
+                    todo: 1,
+                    done: 1,
+                    blocked: 1,
+                    ..Default::default()
+                },
+                by_priority: PriorityCounts {
+                    p1: 3,
+                    ..Default::default()
+                },
+                by_label: [("a|b".to_string(), 2)].into_iter().collect(),
+                by_assignee: [("alice".to_string(), 2)].into_iter().collect(),
+                by_author: [(
+                    "bob".to_string(),
+                    AuthorCounts {
+                        tasks: 3,
+                        comments: 1,
+                    },
+                )]
+                .into_iter()
+                .collect(),
+                estimates: vec![],
+            },
+            focus_task: None,
+            interrupted: vec![],
+            blockers: vec![create_test_task("t-2", "blocked")],
+            due_soon: vec![],
+            stale: vec![],
+            next_actions: vec![create_test_task("t-1", "todo")],
+            recent_decisions: vec![],
+            recent_artifacts: vec![],
+        };
+        let md = format_summary(&summary);
+
+        assert!(md.starts_with("# Work Summary\n"));
+        assert!(md.contains("| 1 | 0 | 1 | 1 |"));
+        assert!(md.contains("| a\\|b | 2 |"));
+        assert!(md.contains("| Assignee | Open tasks |\n| --- | ---: |\n| alice | 2 |"));
+        assert!(md.contains("| bob | 3 | 1 |"));
+        assert!(md.contains("- [ ] **Task t-2** (`t-2`) · P1 · blocked: Waiting on API"));
+        assert!(md.contains("## Next Actions\n\n- [ ] **Task t-1**"));
+        assert!(!md.contains("## Recent Decisions"));
+    }
+
+    #[test]
+    fn test_format_handoff() {
+        let handoff = HandoffOutput {
+            to: "Code Review Agent".to_string(),
+            tasks: vec![create_test_task("t-1", "done")],
+            context: vec![],
+            constraints: Some("No new dependencies".to_string()),
+            acceptance_criteria: None,
+            output_schema: Some(serde_json::json!({ "type": "object" })),
+            steering: vec![],
+        };
+        let md = format_handoff(&handoff);
+
+        assert!(md.starts_with("# Handoff to Code Review Agent\n"));
+        assert!(md.contains("- [x] **Task t-1** (`t-1`) · P1 · done\n  Line one\n  Line two\n"));
+        assert!(md.contains("## Constraints\n\nNo new dependencies"));
+        assert!(md.contains("```json\n{\n  \"type\": \"object\"\n}\n```"));
+        assert!(!md.contains("## Acceptance Criteria"));
+    }
+}
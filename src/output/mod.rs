@@ -1,6 +1,9 @@
+pub mod columns;
 pub mod json;
+pub mod markdown;
 pub mod prompt;
 pub mod table;
+pub mod yaml;
 
 use crate::models::run::Run;
 use crate::models::worker;
@@ -32,6 +35,13 @@ impl std::str::FromStr for OutputFormat {
     }
 }
 
+impl OutputFormat {
+    /// Whether this format is meant for parsing (JSON or YAML) rather than reading
+    pub fn is_structured(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Yaml)
+    }
+}
+
 /// Format output based on the selected format
 pub struct Formatter {
     pub format: OutputFormat,
@@ -45,7 +55,7 @@ impl Formatter {
     pub fn format_project(&self, project: &Project) -> String {
         match self.format {
             OutputFormat::Json => json::format_project(project),
-            OutputFormat::Yaml => yaml_format_project(project),
+            OutputFormat::Yaml => yaml::format_project(project),
             OutputFormat::Md => md_format_project(project),
             OutputFormat::Prompt => prompt::format_project(project),
             OutputFormat::Table => table::format_project(project),
@@ -55,17 +65,29 @@ impl Formatter {
     pub fn format_projects(&self, projects: &[Project]) -> String {
         match self.format {
             OutputFormat::Json => json::format_projects(projects),
-            OutputFormat::Yaml => yaml_format_projects(projects),
+            OutputFormat::Yaml => yaml::format_projects(projects),
             OutputFormat::Md => md_format_projects(projects),
             OutputFormat::Prompt => prompt::format_projects(projects),
             OutputFormat::Table => table::format_projects(projects),
         }
     }
 
+    /// Project listing with a health badge per project in table output
+    pub fn format_projects_with_health(
+        &self,
+        projects: &[Project],
+        health: &[ProjectHealth],
+    ) -> String {
+        match self.format {
+            OutputFormat::Table => table::format_projects_with_health(projects, health),
+            _ => self.format_projects(projects),
+        }
+    }
+
     pub fn format_task(&self, task: &Task) -> String {
         match self.format {
             OutputFormat::Json => json::format_task(task),
-            OutputFormat::Yaml => yaml_format_task(task),
+            OutputFormat::Yaml => yaml::format_task(task),
             OutputFormat::Md => md_format_task(task),
             OutputFormat::Prompt => prompt::format_task(task),
             OutputFormat::Table => table::format_task(task),
@@ -75,7 +97,7 @@ impl Formatter {
     pub fn format_task_with_deps(&self, task: &Task, blocked_by: Vec<String>) -> String {
         match self.format {
             OutputFormat::Json => json::format_task_with_deps(task, blocked_by),
-            OutputFormat::Yaml => yaml_format_task_with_deps(task, &blocked_by),
+            OutputFormat::Yaml => yaml::format_task_with_deps(task, &blocked_by),
             OutputFormat::Md => md_format_task_with_deps(task, &blocked_by),
             OutputFormat::Prompt => prompt::format_task_with_deps(task, &blocked_by),
             OutputFormat::Table => table::format_task_with_deps(task, &blocked_by),
@@ -85,17 +107,25 @@ impl Formatter {
     pub fn format_tasks(&self, tasks: &[Task]) -> String {
         match self.format {
             OutputFormat::Json => json::format_tasks(tasks),
-            OutputFormat::Yaml => yaml_format_tasks(tasks),
+            OutputFormat::Yaml => yaml::format_tasks(tasks),
             OutputFormat::Md => md_format_tasks(tasks),
             OutputFormat::Prompt => prompt::format_tasks(tasks),
             OutputFormat::Table => table::format_tasks(tasks),
         }
     }
 
+    pub fn format_workspace_tasks(&self, tasks: &[WorkspaceTask]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_workspace_tasks(tasks),
+            OutputFormat::Yaml => yaml::format_workspace_tasks(tasks),
+            _ => table::format_workspace_tasks(tasks),
+        }
+    }
+
     pub fn format_tasks_with_deps(&self, tasks_with_deps: &[(Task, Vec<String>)]) -> String {
         match self.format {
             OutputFormat::Json => json::format_tasks_with_deps(tasks_with_deps),
-            OutputFormat::Yaml => yaml_format_tasks_with_deps(tasks_with_deps),
+            OutputFormat::Yaml => yaml::format_tasks_with_deps(tasks_with_deps),
             OutputFormat::Md => md_format_tasks_with_deps(tasks_with_deps),
             OutputFormat::Prompt => {
                 let refs: Vec<(&Task, &[String])> = tasks_with_deps
@@ -111,7 +141,7 @@ impl Formatter {
     pub fn format_comment(&self, comment: &Comment) -> String {
         match self.format {
             OutputFormat::Json => json::format_comment(comment),
-            OutputFormat::Yaml => yaml_format_comment(comment),
+            OutputFormat::Yaml => yaml::format_comment(comment),
             OutputFormat::Md => md_format_comment(comment),
             OutputFormat::Prompt => prompt::format_comment(comment),
             OutputFormat::Table => table::format_comment(comment),
@@ -121,17 +151,27 @@ impl Formatter {
     pub fn format_comments(&self, comments: &[Comment]) -> String {
         match self.format {
             OutputFormat::Json => json::format_comments(comments),
-            OutputFormat::Yaml => yaml_format_comments(comments),
+            OutputFormat::Yaml => yaml::format_comments(comments),
             OutputFormat::Md => md_format_comments(comments),
             OutputFormat::Prompt => prompt::format_comments(comments),
             OutputFormat::Table => table::format_comments(comments),
         }
     }
 
+    pub fn format_comment_threads(&self, threads: &[CommentThread]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_comment_threads(threads),
+            OutputFormat::Yaml => yaml::format_comment_threads(threads),
+            OutputFormat::Md => md_format_comment_threads(threads),
+            OutputFormat::Prompt => prompt::format_comment_threads(threads),
+            OutputFormat::Table => table::format_comment_threads(threads),
+        }
+    }
+
     pub fn format_session(&self, session: &Session) -> String {
         match self.format {
             OutputFormat::Json => json::format_session(session),
-            OutputFormat::Yaml => yaml_format_session(session),
+            OutputFormat::Yaml => yaml::format_session(session),
             OutputFormat::Md => md_format_session(session),
             OutputFormat::Prompt => prompt::format_session(session),
             OutputFormat::Table => table::format_session(session),
@@ -141,17 +181,26 @@ impl Formatter {
     pub fn format_sessions(&self, sessions: &[Session]) -> String {
         match self.format {
             OutputFormat::Json => json::format_sessions(sessions),
-            OutputFormat::Yaml => yaml_format_sessions(sessions),
+            OutputFormat::Yaml => yaml::format_sessions(sessions),
             OutputFormat::Md => md_format_sessions(sessions),
             OutputFormat::Prompt => prompt::format_sessions(sessions),
             OutputFormat::Table => table::format_sessions(sessions),
         }
     }
 
+    pub fn format_session_review(&self, review: &SessionReview) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_session_review(review),
+            OutputFormat::Yaml => yaml::format_session_review(review),
+            OutputFormat::Prompt => prompt::format_session_review(review),
+            _ => table::format_session_review(review),
+        }
+    }
+
     pub fn format_checkpoint(&self, checkpoint: &Checkpoint) -> String {
         match self.format {
             OutputFormat::Json => json::format_checkpoint(checkpoint),
-            OutputFormat::Yaml => yaml_format_checkpoint(checkpoint),
+            OutputFormat::Yaml => yaml::format_checkpoint(checkpoint),
             OutputFormat::Md => md_format_checkpoint(checkpoint),
             OutputFormat::Prompt => prompt::format_checkpoint(checkpoint),
             OutputFormat::Table => table::format_checkpoint(checkpoint),
@@ -161,7 +210,7 @@ impl Formatter {
     pub fn format_checkpoints(&self, checkpoints: &[Checkpoint]) -> String {
         match self.format {
             OutputFormat::Json => json::format_checkpoints(checkpoints),
-            OutputFormat::Yaml => yaml_format_checkpoints(checkpoints),
+            OutputFormat::Yaml => yaml::format_checkpoints(checkpoints),
             OutputFormat::Md => md_format_checkpoints(checkpoints),
             OutputFormat::Prompt => prompt::format_checkpoints(checkpoints),
             OutputFormat::Table => table::format_checkpoints(checkpoints),
@@ -171,7 +220,7 @@ impl Formatter {
     pub fn format_artifact(&self, artifact: &Artifact) -> String {
         match self.format {
             OutputFormat::Json => json::format_artifact(artifact),
-            OutputFormat::Yaml => yaml_format_artifact(artifact),
+            OutputFormat::Yaml => yaml::format_artifact(artifact),
             _ => table::format_artifact(artifact),
         }
     }
@@ -179,7 +228,7 @@ impl Formatter {
     pub fn format_artifacts(&self, artifacts: &[Artifact]) -> String {
         match self.format {
             OutputFormat::Json => json::format_artifacts(artifacts),
-            OutputFormat::Yaml => yaml_format_artifacts(artifacts),
+            OutputFormat::Yaml => yaml::format_artifacts(artifacts),
             _ => table::format_artifacts(artifacts),
         }
     }
@@ -187,35 +236,89 @@ impl Formatter {
     pub fn format_next_task(&self, task: Option<&Task>, reason: Option<&str>) -> String {
         match self.format {
             OutputFormat::Json => json::format_next_task(task, reason),
+            OutputFormat::Yaml => yaml::format_next_task(task, reason),
             OutputFormat::Prompt => prompt::format_next_task(task, reason),
             _ => table::format_next_task(task, reason),
         }
     }
 
+    pub fn format_recommendations(&self, recommendations: &[TaskRecommendation]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_recommendations(recommendations),
+            OutputFormat::Yaml => yaml::format_recommendations(recommendations),
+            OutputFormat::Prompt => prompt::format_recommendations(recommendations),
+            _ => table::format_recommendations(recommendations),
+        }
+    }
+
     pub fn format_search_results(&self, results: &[SearchResult]) -> String {
         match self.format {
             OutputFormat::Json => json::format_search_results(results),
-            OutputFormat::Yaml => yaml_format_search_results(results),
+            OutputFormat::Yaml => yaml::format_search_results(results),
             OutputFormat::Md => md_format_search_results(results),
             OutputFormat::Prompt => prompt::format_search_results(results),
             OutputFormat::Table => table::format_search_results(results),
         }
     }
 
+    pub fn format_workspace_search_results(&self, results: &[WorkspaceSearchResult]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_workspace_search_results(results),
+            OutputFormat::Yaml => yaml::format_workspace_search_results(results),
+            _ => table::format_workspace_search_results(results),
+        }
+    }
+
     pub fn format_initiative(&self, initiative: &initiative::Initiative) -> String {
         match self.format {
             OutputFormat::Json => json::format_initiative(initiative),
-            OutputFormat::Yaml => yaml_format_initiative(initiative),
+            OutputFormat::Yaml => yaml::format_initiative(initiative),
             OutputFormat::Md => md_format_initiative(initiative),
             OutputFormat::Prompt => prompt::format_initiative(initiative),
             OutputFormat::Table => table::format_initiative(initiative),
         }
     }
 
+    pub fn format_initiative_with_milestones(
+        &self,
+        initiative: &initiative::Initiative,
+        milestones: &[initiative::MilestoneProgress],
+    ) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_initiative_with_milestones(initiative, milestones),
+            OutputFormat::Yaml => yaml::format_initiative_with_milestones(initiative, milestones),
+            OutputFormat::Md => md_format_initiative_with_milestones(initiative, milestones),
+            OutputFormat::Prompt => {
+                prompt::format_initiative_with_milestones(initiative, milestones)
+            }
+            OutputFormat::Table => table::format_initiative_with_milestones(initiative, milestones),
+        }
+    }
+
+    pub fn format_milestone(&self, milestone: &initiative::MilestoneProgress) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_milestone(milestone),
+            OutputFormat::Yaml => yaml::format_milestone(milestone),
+            OutputFormat::Md => md_format_milestones(std::slice::from_ref(milestone)),
+            OutputFormat::Prompt => prompt::format_milestone(milestone),
+            OutputFormat::Table => table::format_milestone(milestone),
+        }
+    }
+
+    pub fn format_milestones(&self, milestones: &[initiative::MilestoneProgress]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_milestones(milestones),
+            OutputFormat::Yaml => yaml::format_milestones(milestones),
+            OutputFormat::Md => md_format_milestones(milestones),
+            OutputFormat::Prompt => prompt::format_milestones(milestones),
+            OutputFormat::Table => table::format_milestones(milestones),
+        }
+    }
+
     pub fn format_initiatives(&self, initiatives: &[initiative::Initiative]) -> String {
         match self.format {
             OutputFormat::Json => json::format_initiatives(initiatives),
-            OutputFormat::Yaml => yaml_format_initiatives(initiatives),
+            OutputFormat::Yaml => yaml::format_initiatives(initiatives),
             OutputFormat::Md => md_format_initiatives(initiatives),
             OutputFormat::Prompt => prompt::format_initiatives(initiatives),
             OutputFormat::Table => table::format_initiatives(initiatives),
@@ -225,7 +328,7 @@ impl Formatter {
     pub fn format_initiative_summary(&self, summary: &initiative::InitiativeSummary) -> String {
         match self.format {
             OutputFormat::Json => json::format_initiative_summary(summary),
-            OutputFormat::Yaml => yaml_format_initiative_summary(summary),
+            OutputFormat::Yaml => yaml::format_initiative_summary(summary),
             OutputFormat::Md => md_format_initiative_summary(summary),
             OutputFormat::Prompt => prompt::format_initiative_summary(summary),
             OutputFormat::Table => table::format_initiative_summary(summary),
@@ -235,23 +338,88 @@ impl Formatter {
     pub fn format_worker(&self, worker: &worker::Worker) -> String {
         match self.format {
             OutputFormat::Json => json::format_worker(worker),
-            OutputFormat::Yaml => yaml_format_worker(worker),
+            OutputFormat::Yaml => yaml::format_worker(worker),
             _ => table::format_worker(worker),
         }
     }
 
+    pub fn format_task_with_links(
+        &self,
+        task: &Task,
+        blocked_by: Vec<String>,
+        links: &[TaskVcsLink],
+        attachments: &[Attachment],
+        related: &[RelatedTask],
+    ) -> String {
+        match self.format {
+            OutputFormat::Json => {
+                json::format_task_with_links(task, blocked_by, links, attachments, related)
+            }
+            OutputFormat::Yaml => {
+                yaml::format_task_with_links(task, &blocked_by, links, attachments, related)
+            }
+            OutputFormat::Md => {
+                md_format_task_with_links(task, &blocked_by, links, attachments, related)
+            }
+            OutputFormat::Prompt => {
+                prompt::format_task_with_links(task, &blocked_by, links, attachments, related)
+            }
+            OutputFormat::Table => {
+                table::format_task_with_links(task, &blocked_by, links, attachments, related)
+            }
+        }
+    }
+
+    pub fn format_attachments(&self, attachments: &[Attachment]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_attachments(attachments),
+            OutputFormat::Yaml => yaml::format_attachments(attachments),
+            OutputFormat::Md => md_format_attachments(attachments),
+            OutputFormat::Prompt => prompt::format_attachments(attachments),
+            OutputFormat::Table => table::format_attachments(attachments),
+        }
+    }
+
+    pub fn format_related_tasks(&self, related: &[RelatedTask]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_related_tasks(related),
+            OutputFormat::Yaml => yaml::format_related_tasks(related),
+            OutputFormat::Md => md_format_related_tasks(related),
+            OutputFormat::Prompt => prompt::format_related_tasks(related),
+            OutputFormat::Table => table::format_related_tasks(related),
+        }
+    }
+
+    pub fn format_vcs_links(&self, links: &[TaskVcsLink]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_vcs_links(links),
+            OutputFormat::Yaml => yaml::format_vcs_links(links),
+            OutputFormat::Md => md_format_vcs_links(links),
+            OutputFormat::Prompt => prompt::format_vcs_links(links),
+            OutputFormat::Table => table::format_vcs_links(links),
+        }
+    }
+
     pub fn format_workers(&self, workers: &[worker::Worker]) -> String {
         match self.format {
             OutputFormat::Json => json::format_workers(workers),
-            OutputFormat::Yaml => yaml_format_workers(workers),
+            OutputFormat::Yaml => yaml::format_workers(workers),
             _ => table::format_workers(workers),
         }
     }
 
+    pub fn format_filter_test(&self, test: &worker::FilterTest) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_filter_test(test),
+            OutputFormat::Yaml => yaml::format_filter_test(test),
+            _ => table::format_filter_test(test),
+        }
+    }
+
     pub fn format_run(&self, run: &Run) -> String {
         match self.format {
             OutputFormat::Json => json::format_run(run),
-            OutputFormat::Yaml => yaml_format_run(run),
+            OutputFormat::Yaml => yaml::format_run(run),
             _ => table::format_run(run),
         }
     }
@@ -259,88 +427,221 @@ impl Formatter {
     pub fn format_runs(&self, runs: &[Run]) -> String {
         match self.format {
             OutputFormat::Json => json::format_runs(runs),
-            OutputFormat::Yaml => yaml_format_runs(runs),
+            OutputFormat::Yaml => yaml::format_runs(runs),
             _ => table::format_runs(runs),
         }
     }
 
-    /// Format task creation confirmation
-    /// For table/text formats: single line "Task created: <task-id>"
-    /// For JSON: full task object for scripting compatibility
-    pub fn format_task_created(&self, task: &Task) -> String {
+    pub fn format_log_prune_report(&self, report: &LogPruneReport) -> String {
         match self.format {
-            OutputFormat::Json => json::format_task(task),
-            OutputFormat::Yaml => yaml_format_task(task),
-            _ => format!("Task created: {}", task.id),
+            OutputFormat::Json => json::format_log_prune_report(report),
+            OutputFormat::Yaml => yaml::format_log_prune_report(report),
+            _ => table::format_log_prune_report(report),
         }
     }
-}
 
-// YAML formatters (using serde_yaml)
-fn yaml_format_project(project: &Project) -> String {
-    serde_yaml::to_string(project).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_run_queue(&self, queue: &[QueuedRun]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_run_queue(queue),
+            OutputFormat::Yaml => yaml::format_run_queue(queue),
+            _ => table::format_run_queue(queue),
+        }
+    }
 
-fn yaml_format_projects(projects: &[Project]) -> String {
-    serde_yaml::to_string(projects).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_import_report(&self, report: &ImportReport) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_import_report(report),
+            OutputFormat::Yaml => yaml::format_import_report(report),
+            _ => table::format_import_report(report),
+        }
+    }
 
-fn yaml_format_task(task: &Task) -> String {
-    let output = json::TaskOutput::from_task(task.clone());
-    serde_yaml::to_string(&output).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_batch_plan(&self, plan: &[PlannedChange]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_batch_plan(plan),
+            OutputFormat::Yaml => yaml::format_batch_plan(plan),
+            _ => table::format_batch_plan(plan),
+        }
+    }
 
-fn yaml_format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
-    let output = json::TaskOutput::new(task.clone(), blocked_by.to_vec());
-    serde_yaml::to_string(&output).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_run_log_matches(&self, matches: &[RunLogMatch]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_run_log_matches(matches),
+            OutputFormat::Yaml => yaml::format_run_log_matches(matches),
+            _ => table::format_run_log_matches(matches),
+        }
+    }
 
-fn yaml_format_tasks(tasks: &[Task]) -> String {
-    let outputs: Vec<json::TaskOutput> = tasks
-        .iter()
-        .map(|t| json::TaskOutput::from_task(t.clone()))
-        .collect();
-    serde_yaml::to_string(&outputs).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_audit_entries(&self, entries: &[AuditEntry]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_audit_entries(entries),
+            OutputFormat::Yaml => yaml::format_audit_entries(entries),
+            _ => table::format_audit_entries(entries),
+        }
+    }
 
-fn yaml_format_tasks_with_deps(tasks_with_deps: &[(Task, Vec<String>)]) -> String {
-    let outputs: Vec<json::TaskOutput> = tasks_with_deps
-        .iter()
-        .map(|(t, deps)| json::TaskOutput::new(t.clone(), deps.clone()))
-        .collect();
-    serde_yaml::to_string(&outputs).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_sync_report(&self, report: &SyncReport) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_sync_report(report),
+            OutputFormat::Yaml => yaml::format_sync_report(report),
+            _ => table::format_sync_report(report),
+        }
+    }
 
-fn yaml_format_comment(comment: &Comment) -> String {
-    serde_yaml::to_string(comment).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_snapshot(&self, snapshot: &Snapshot) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_snapshot(snapshot),
+            OutputFormat::Yaml => yaml::format_snapshot(snapshot),
+            _ => table::format_snapshot(snapshot),
+        }
+    }
 
-fn yaml_format_comments(comments: &[Comment]) -> String {
-    serde_yaml::to_string(comments).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_snapshots(&self, snapshots: &[Snapshot]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_snapshots(snapshots),
+            OutputFormat::Yaml => yaml::format_snapshots(snapshots),
+            _ => table::format_snapshots(snapshots),
+        }
+    }
 
-fn yaml_format_session(session: &Session) -> String {
-    serde_yaml::to_string(session).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_restore_report(&self, report: &RestoreReport) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_restore_report(report),
+            OutputFormat::Yaml => yaml::format_restore_report(report),
+            _ => table::format_restore_report(report),
+        }
+    }
 
-fn yaml_format_sessions(sessions: &[Session]) -> String {
-    serde_yaml::to_string(sessions).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_remotes(&self, remotes: &[Remote]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_remotes(remotes),
+            OutputFormat::Yaml => yaml::format_remotes(remotes),
+            _ => table::format_remotes(remotes),
+        }
+    }
 
-fn yaml_format_checkpoint(checkpoint: &Checkpoint) -> String {
-    serde_yaml::to_string(checkpoint).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_remote_sync_report(&self, report: &RemoteSyncReport) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_remote_sync_report(report),
+            OutputFormat::Yaml => yaml::format_remote_sync_report(report),
+            _ => table::format_remote_sync_report(report),
+        }
+    }
 
-fn yaml_format_checkpoints(checkpoints: &[Checkpoint]) -> String {
-    serde_yaml::to_string(checkpoints).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_remote_statuses(&self, statuses: &[RemoteStatus]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_remote_statuses(statuses),
+            OutputFormat::Yaml => yaml::format_remote_statuses(statuses),
+            _ => table::format_remote_statuses(statuses),
+        }
+    }
 
-fn yaml_format_artifact(artifact: &Artifact) -> String {
-    serde_yaml::to_string(artifact).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
+    pub fn format_workspaces(&self, workspaces: &[WorkspaceOverview]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_workspaces(workspaces),
+            OutputFormat::Yaml => yaml::format_workspaces(workspaces),
+            _ => table::format_workspaces(workspaces),
+        }
+    }
+
+    pub fn format_plugins(&self, plugins: &[Plugin]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_plugins(plugins),
+            OutputFormat::Yaml => yaml::format_plugins(plugins),
+            _ => table::format_plugins(plugins),
+        }
+    }
+
+    pub fn format_event_types(&self, types: &[EventTypeInfo]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_event_types(types),
+            OutputFormat::Yaml => yaml::format_event_types(types),
+            _ => table::format_event_types(types),
+        }
+    }
+
+    pub fn format_burndown(&self, burndown: &Burndown) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_burndown(burndown),
+            OutputFormat::Yaml => yaml::format_burndown(burndown),
+            _ => table::format_burndown(burndown),
+        }
+    }
+
+    pub fn format_rules(&self, rules: &[Rule]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_rules(rules),
+            OutputFormat::Yaml => yaml::format_rules(rules),
+            _ => table::format_rules(rules),
+        }
+    }
+
+    pub fn format_rule_matches(&self, matches: &[RuleMatch]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_rule_matches(matches),
+            OutputFormat::Yaml => yaml::format_rule_matches(matches),
+            _ => table::format_rule_matches(matches),
+        }
+    }
+
+    pub fn format_board(&self, board: &Board) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_board(board),
+            OutputFormat::Yaml => yaml::format_board(board),
+            _ => table::format_board(board),
+        }
+    }
+
+    pub fn format_timeline(&self, timeline: &Timeline) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_timeline(timeline),
+            OutputFormat::Yaml => yaml::format_timeline(timeline),
+            _ => table::format_timeline(timeline),
+        }
+    }
+
+    pub fn format_velocity(&self, velocity: &Velocity) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_velocity(velocity),
+            OutputFormat::Yaml => yaml::format_velocity(velocity),
+            _ => table::format_velocity(velocity),
+        }
+    }
+
+    pub fn format_cost_report(&self, report: &CostReport) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_cost_report(report),
+            OutputFormat::Yaml => yaml::format_cost_report(report),
+            _ => table::format_cost_report(report),
+        }
+    }
+
+    pub fn format_project_health(&self, health: &[ProjectHealth]) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_project_health(health),
+            OutputFormat::Yaml => yaml::format_project_health(health),
+            _ => table::format_project_health(health),
+        }
+    }
+
+    pub fn format_task_graph(&self, graph: &TaskGraph) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_task_graph(graph),
+            OutputFormat::Yaml => yaml::format_task_graph(graph),
+            _ => table::format_task_graph(graph),
+        }
+    }
 
-fn yaml_format_artifacts(artifacts: &[Artifact]) -> String {
-    serde_yaml::to_string(artifacts).unwrap_or_else(|_| "Error formatting YAML".to_string())
+    /// Format task creation confirmation
+    /// For table/text formats: single line "Task created: <task-id>"
+    /// For JSON: full task object for scripting compatibility
+    pub fn format_task_created(&self, task: &Task) -> String {
+        match self.format {
+            OutputFormat::Json => json::format_task(task),
+            OutputFormat::Yaml => yaml::format_task(task),
+            _ => format!("Task created: {}", task.id),
+        }
+    }
 }
 
 // Markdown formatters
@@ -359,6 +660,12 @@ fn md_format_project(project: &Project) -> String {
     if !tags.is_empty() {
         md.push_str(&format!("\n**Tags:** {}\n", tags.join(", ")));
     }
+    if !project.fields.is_empty() {
+        md.push_str(&format!(
+            "\n**Fields:** {}\n",
+            format_fields(&project.fields)
+        ));
+    }
     md
 }
 
@@ -389,6 +696,15 @@ fn md_format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
     if let Some(owner) = &task.owner {
         md.push_str(&format!("**Owner:** {}\n", owner));
     }
+    if !task.labels.is_empty() {
+        md.push_str(&format!("**Labels:** {}\n", task.labels.join(", ")));
+    }
+    if !task.fields.is_empty() {
+        md.push_str(&format!("**Fields:** {}\n", format_fields(&task.fields)));
+    }
+    if let Some(estimate) = task.estimate {
+        md.push_str(&format!("**Estimate:** {}\n", estimate));
+    }
     if let Some(desc) = &task.description {
         md.push_str(&format!("\n{}\n", desc));
     }
@@ -401,6 +717,90 @@ fn md_format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
     md
 }
 
+fn md_format_task_with_links(
+    task: &Task,
+    blocked_by: &[String],
+    links: &[TaskVcsLink],
+    attachments: &[Attachment],
+    related: &[RelatedTask],
+) -> String {
+    let mut md = md_format_task_with_deps(task, blocked_by);
+    if !links.is_empty() {
+        md.push_str("\n### Git\n\n");
+        md.push_str(&md_vcs_link_items(links));
+    }
+    if !attachments.is_empty() {
+        md.push_str("\n### Attachments\n\n");
+        md.push_str(&md_attachment_items(attachments));
+    }
+    if !related.is_empty() {
+        md.push_str("\n### Related\n\n");
+        md.push_str(&md_related_items(related));
+    }
+    md
+}
+
+fn md_format_related_tasks(related: &[RelatedTask]) -> String {
+    if related.is_empty() {
+        return "No linked tasks.\n".to_string();
+    }
+    let mut md = String::from("# Related Tasks\n\n");
+    md.push_str(&md_related_items(related));
+    md
+}
+
+fn md_related_items(related: &[RelatedTask]) -> String {
+    related
+        .iter()
+        .map(|r| {
+            format!(
+                "- {} **{}** (`{}`) · {}\n",
+                r.relation.replace('_', " "),
+                r.title,
+                r.related_task_id,
+                r.status
+            )
+        })
+        .collect()
+}
+
+fn md_format_attachments(attachments: &[Attachment]) -> String {
+    if attachments.is_empty() {
+        return "No attachments.\n".to_string();
+    }
+    let mut md = String::from("# Attachments\n\n");
+    md.push_str(&md_attachment_items(attachments));
+    md
+}
+
+fn md_attachment_items(attachments: &[Attachment]) -> String {
+    attachments
+        .iter()
+        .map(|a| format!("- [{}]({})\n", a.name, a.path))
+        .collect()
+}
+
+fn md_format_vcs_links(links: &[TaskVcsLink]) -> String {
+    if links.is_empty() {
+        return "No linked branches or commits.\n".to_string();
+    }
+    let mut md = String::from("# Git Links\n\n");
+    md.push_str(&md_vcs_link_items(links));
+    md
+}
+
+fn md_vcs_link_items(links: &[TaskVcsLink]) -> String {
+    let mut md = String::new();
+    for link in links {
+        md.push_str(&format!("- {} `{}`", link.kind, link.short_ref()));
+        if let Some(summary) = &link.summary {
+            md.push_str(&format!(" {}", summary));
+        }
+        md.push('\n');
+    }
+    md
+}
+
 fn md_format_tasks(tasks: &[Task]) -> String {
     let tasks_with_deps: Vec<(&Task, &[String])> = tasks.iter().map(|t| (t, &[][..])).collect();
     md_format_tasks_internal(&tasks_with_deps)
@@ -430,6 +830,9 @@ fn md_format_tasks_internal(tasks_with_deps: &[(&Task, &[String])]) -> String {
         if let Some(owner) = &task.owner {
             md.push_str(&format!(" @{}", owner));
         }
+        for label in &task.labels {
+            md.push_str(&format!(" #{}", label));
+        }
         if !blocked_by.is_empty() {
             md.push_str(&format!(" blocked_by: {}", blocked_by.join(", ")));
         }
@@ -462,6 +865,29 @@ fn md_format_comments(comments: &[Comment]) -> String {
     md
 }
 
+fn md_format_comment_threads(threads: &[CommentThread]) -> String {
+    fn push_thread(md: &mut String, thread: &CommentThread, depth: usize) {
+        let comment = &thread.comment;
+        let author = comment.author.as_deref().unwrap_or("anonymous");
+        md.push_str(&format!(
+            "{}- **[{}]** {} - _{}_\n",
+            "  ".repeat(depth),
+            comment.kind,
+            comment.content,
+            author
+        ));
+        for reply in &thread.replies {
+            push_thread(md, reply, depth + 1);
+        }
+    }
+
+    let mut md = String::from("# Comments\n\n");
+    for thread in threads {
+        push_thread(&mut md, thread, 0);
+    }
+    md
+}
+
 fn md_format_session(session: &Session) -> String {
     let mut md = String::new();
     let name = session.name.as_deref().unwrap_or("Unnamed Session");
@@ -482,6 +908,16 @@ fn md_format_session(session: &Session) -> String {
     if let Some(focus) = &session.focus_task_id {
         md.push_str(&format!("**Focus Task:** `{}`\n", focus));
     }
+    let stack = session.focus_stack_vec();
+    if stack.len() > 1 {
+        let set_aside: Vec<String> = stack
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|id| format!("`{}`", id))
+            .collect();
+        md.push_str(&format!("**Set Aside:** {}\n", set_aside.join(" > ")));
+    }
     md
 }
 
@@ -517,18 +953,6 @@ fn md_format_checkpoints(checkpoints: &[Checkpoint]) -> String {
     md
 }
 
-fn yaml_format_search_results(results: &[SearchResult]) -> String {
-    serde_yaml::to_string(results).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
-fn yaml_format_initiative(initiative: &initiative::Initiative) -> String {
-    serde_yaml::to_string(initiative).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
-fn yaml_format_initiatives(initiatives: &[initiative::Initiative]) -> String {
-    serde_yaml::to_string(initiatives).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
 fn md_format_search_results(results: &[SearchResult]) -> String {
     let mut md = String::from("# Search Results\n\n");
     for result in results {
@@ -564,12 +988,17 @@ fn md_format_search_results(results: &[SearchResult]) -> String {
                 status,
                 priority,
                 project_id,
+                labels,
             } => {
                 md.push_str(&format!("- **[TASK]** {} (`{}`) [{}]", title, id, priority));
                 if let Some(desc) = description {
                     md.push_str(&format!(" - {}", desc));
                 }
-                md.push_str(&format!(" - {} (project: {})\n", status, project_id));
+                md.push_str(&format!(" - {} (project: {})", status, project_id));
+                if !labels.is_empty() {
+                    md.push_str(&format!(" labels: {}", labels.join(", ")));
+                }
+                md.push('\n');
             }
         }
     }
@@ -594,6 +1023,43 @@ fn md_format_initiative(initiative: &initiative::Initiative) -> String {
     md
 }
 
+fn md_format_initiative_with_milestones(
+    initiative: &initiative::Initiative,
+    milestones: &[initiative::MilestoneProgress],
+) -> String {
+    let mut md = md_format_initiative(initiative);
+    if !milestones.is_empty() {
+        md.push_str("\n## Milestones\n\n");
+        md.push_str(&md_format_milestones(milestones));
+    }
+    md
+}
+
+fn md_format_milestones(milestones: &[initiative::MilestoneProgress]) -> String {
+    let mut md = String::new();
+    for milestone in milestones {
+        let m = &milestone.milestone;
+        let check = if m.is_completed() { "[x]" } else { "[ ]" };
+        md.push_str(&format!(
+            "- {} **{}** (`{}`) - {}/{} tasks ({:.0}%)",
+            check,
+            m.name,
+            m.id,
+            milestone.done_tasks,
+            milestone.total_tasks,
+            milestone.percent_complete
+        ));
+        if let Some(target) = &m.target_date {
+            md.push_str(&format!(
+                ", due {}",
+                crate::services::datetime::format_local_date(target)
+            ));
+        }
+        md.push('\n');
+    }
+    md
+}
+
 fn md_format_initiatives(initiatives: &[initiative::Initiative]) -> String {
     let mut md = String::from("# Initiatives\n\n");
     for initiative in initiatives {
@@ -605,10 +1071,6 @@ fn md_format_initiatives(initiatives: &[initiative::Initiative]) -> String {
     md
 }
 
-fn yaml_format_initiative_summary(summary: &initiative::InitiativeSummary) -> String {
-    serde_yaml::to_string(summary).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
 fn md_format_initiative_summary(summary: &initiative::InitiativeSummary) -> String {
     let mut md = String::new();
 
@@ -643,6 +1105,12 @@ fn md_format_initiative_summary(summary: &initiative::InitiativeSummary) -> Stri
         summary.status.tasks_blocked
     ));
 
+    if !summary.milestones.is_empty() {
+        md.push_str("## Milestones\n\n");
+        md.push_str(&md_format_milestones(&summary.milestones));
+        md.push('\n');
+    }
+
     // Projects breakdown
     if !summary.projects.is_empty() {
         md.push_str("## Projects\n\n");
@@ -687,23 +1155,3 @@ fn md_format_initiative_summary(summary: &initiative::InitiativeSummary) -> Stri
 
     md
 }
-
-// === Worker YAML formatters ===
-
-fn yaml_format_worker(worker: &worker::Worker) -> String {
-    serde_yaml::to_string(worker).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
-fn yaml_format_workers(workers: &[worker::Worker]) -> String {
-    serde_yaml::to_string(workers).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
-// === Run YAML formatters ===
-
-fn yaml_format_run(run: &Run) -> String {
-    serde_yaml::to_string(run).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
-
-fn yaml_format_runs(runs: &[Run]) -> String {
-    serde_yaml::to_string(runs).unwrap_or_else(|_| "Error formatting YAML".to_string())
-}
@@ -3,9 +3,9 @@
 //! This format is designed to be machine-readable by LLMs while remaining
 //! human-readable. It uses a consistent structure that's easy to parse.
 
-use crate::models::initiative::Initiative;
+use crate::models::initiative::{Initiative, MilestoneProgress};
 use crate::models::*;
-use crate::output::json::{ContextOutput, HandoffOutput, SummaryOutput};
+use crate::output::json::{ContextOutput, HandoffOutput, SessionSummary, SummaryOutput};
 
 /// Format a project for LLM consumption
 pub fn format_project(project: &Project) -> String {
@@ -24,6 +24,9 @@ pub fn format_project(project: &Project) -> String {
     if !tags.is_empty() {
         output.push_str(&format!("tags: {}\n", tags.join(", ")));
     }
+    for (name, value) in &project.fields {
+        output.push_str(&format!("field.{}: {}\n", name, value));
+    }
     output.push_str("</project>\n");
     output
 }
@@ -46,6 +49,16 @@ pub fn format_task(task: &Task) -> String {
 }
 
 pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
+    format_task_with_links(task, blocked_by, &[], &[], &[])
+}
+
+pub fn format_task_with_links(
+    task: &Task,
+    blocked_by: &[String],
+    links: &[TaskVcsLink],
+    attachments: &[Attachment],
+    related: &[RelatedTask],
+) -> String {
     let mut output = String::new();
     output.push_str("<task>\n");
     output.push_str(&format!("id: {}\n", task.id));
@@ -56,6 +69,12 @@ pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
     if let Some(owner) = &task.owner {
         output.push_str(&format!("owner: {}\n", owner));
     }
+    if !task.labels.is_empty() {
+        output.push_str(&format!("labels: {}\n", task.labels.join(", ")));
+    }
+    for (name, value) in &task.fields {
+        output.push_str(&format!("field.{}: {}\n", name, value));
+    }
     if let Some(parent) = &task.parent_task_id {
         output.push_str(&format!("parent_task: {}\n", parent));
     }
@@ -71,9 +90,32 @@ pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
     if let Some(due) = &task.due_at {
         output.push_str(&format!("due_at: {}\n", due));
     }
+    if let Some(estimate) = task.estimate {
+        output.push_str(&format!("estimate: {}\n", estimate));
+    }
     if task.pinned != 0 {
         output.push_str("pinned: true\n");
     }
+    for link in links {
+        match &link.summary {
+            Some(summary) => output.push_str(&format!(
+                "{}: {} {}\n",
+                link.kind,
+                link.short_ref(),
+                summary
+            )),
+            None => output.push_str(&format!("{}: {}\n", link.kind, link.ref_name)),
+        }
+    }
+    for attachment in attachments {
+        output.push_str(&format!("attachment: {}\n", attachment.path));
+    }
+    for r in related {
+        output.push_str(&format!(
+            "{}: {} ({})\n",
+            r.relation, r.related_task_id, r.title
+        ));
+    }
     output.push_str("</task>\n");
     output
 }
@@ -98,9 +140,14 @@ pub fn format_tasks_with_deps(tasks_with_deps: &[(&Task, &[String])]) -> String
         } else {
             String::new()
         };
+        let labels_info = if !task.labels.is_empty() {
+            format!(" labels: {}", task.labels.join(", "))
+        } else {
+            String::new()
+        };
         output.push_str(&format!(
-            "  - [{}] {} ({}) {}{}{}\n",
-            task.priority, task.title, task.id, task.status, blocked, deps_info
+            "  - [{}] {} ({}) {}{}{}{}\n",
+            task.priority, task.title, task.id, task.status, blocked, deps_info, labels_info
         ));
     }
     output.push_str("</tasks>\n");
@@ -138,6 +185,31 @@ pub fn format_comments(comments: &[Comment]) -> String {
     output
 }
 
+pub fn format_comment_threads(threads: &[CommentThread]) -> String {
+    fn push_thread(output: &mut String, thread: &CommentThread, depth: usize) {
+        let comment = &thread.comment;
+        let author = comment.author.as_deref().unwrap_or("anonymous");
+        output.push_str(&format!(
+            "{}  - [{}] {}: {}\n",
+            "  ".repeat(depth),
+            comment.kind,
+            author,
+            truncate(&comment.content, 60)
+        ));
+        for reply in &thread.replies {
+            push_thread(output, reply, depth + 1);
+        }
+    }
+
+    let mut output = String::new();
+    output.push_str(&format!("<comments threads=\"{}\">\n", threads.len()));
+    for thread in threads {
+        push_thread(&mut output, thread, 0);
+    }
+    output.push_str("</comments>\n");
+    output
+}
+
 pub fn format_session(session: &Session) -> String {
     let mut output = String::new();
     output.push_str("<session>\n");
@@ -160,11 +232,86 @@ pub fn format_session(session: &Session) -> String {
     if let Some(focus) = &session.focus_task_id {
         output.push_str(&format!("focus_task: {}\n", focus));
     }
+    let stack = session.focus_stack_vec();
+    if stack.len() > 1 {
+        let stack: Vec<&str> = stack.iter().rev().map(String::as_str).collect();
+        output.push_str(&format!("focus_stack: {}\n", stack.join(" > ")));
+    }
     output.push_str(&format!("created_at: {}\n", session.created_at));
     output.push_str("</session>\n");
     output
 }
 
+/// A session review for retrospective agents: the session, then a section
+/// per kind of activity, left out when empty
+pub fn format_session_review(review: &SessionReview) -> String {
+    let mut output = format_session(&review.session);
+    output.push_str(&format!(
+        "<activity elapsed_secs=\"{}\" events=\"{}\" />\n",
+        review.elapsed_secs, review.event_count
+    ));
+    if !review.tasks.is_empty() {
+        output.push_str(&format!(
+            "<tasks_touched count=\"{}\">\n",
+            review.tasks.len()
+        ));
+        for task in &review.tasks {
+            output.push_str(&format!(
+                "  - {} ({}) [{}] events: {}\n",
+                task.title, task.id, task.status, task.events
+            ));
+        }
+        output.push_str("</tasks_touched>\n");
+    }
+    if !review.transitions.is_empty() {
+        output.push_str(&format!(
+            "<status_changes count=\"{}\">\n",
+            review.transitions.len()
+        ));
+        for t in &review.transitions {
+            output.push_str(&format!(
+                "  - {}: {} -> {} at {}\n",
+                t.task_id,
+                t.from_status.as_deref().unwrap_or("unknown"),
+                t.to_status,
+                t.changed_at
+            ));
+        }
+        output.push_str("</status_changes>\n");
+    }
+    if !review.comments.is_empty() {
+        output.push_str(&format!("<comments count=\"{}\">\n", review.comments.len()));
+        for comment in &review.comments {
+            output.push_str(&format!(
+                "  - [{}] on {}: {}\n",
+                comment.kind, comment.parent_id, comment.content
+            ));
+        }
+        output.push_str("</comments>\n");
+    }
+    if !review.checkpoints.is_empty() {
+        output.push_str(&format!(
+            "<checkpoints count=\"{}\">\n",
+            review.checkpoints.len()
+        ));
+        for cp in &review.checkpoints {
+            output.push_str(&format!("  - {} ({}) {}\n", cp.name, cp.id, cp.created_at));
+        }
+        output.push_str("</checkpoints>\n");
+    }
+    if !review.runs.is_empty() {
+        output.push_str(&format!("<runs count=\"{}\">\n", review.runs.len()));
+        for run in &review.runs {
+            output.push_str(&format!(
+                "  - {} [{}] {} on {}\n",
+                run.id, run.status, run.event_type, run.entity_id
+            ));
+        }
+        output.push_str("</runs>\n");
+    }
+    output
+}
+
 pub fn format_sessions(sessions: &[Session]) -> String {
     let mut output = String::new();
     output.push_str(&format!("<sessions count=\"{}\">\n", sessions.len()));
@@ -230,6 +377,39 @@ pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
     output
 }
 
+pub fn format_recommendations(recommendations: &[TaskRecommendation]) -> String {
+    let mut output = String::new();
+    output.push_str(&format!(
+        "<recommendations count=\"{}\">\n",
+        recommendations.len()
+    ));
+    for r in recommendations {
+        output.push_str(&format!(
+            "- {} [{}] {} (score {})\n",
+            r.task.id, r.task.priority, r.task.title, r.score
+        ));
+        for f in &r.factors {
+            output.push_str(&format!(
+                "  {}: +{} x{} ({})\n",
+                f.name, f.points, f.weight, f.detail
+            ));
+        }
+    }
+    output.push_str("</recommendations>\n");
+    output
+}
+
+/// The instructions the session's mode gives agents, if any
+fn push_mode_instructions(output: &mut String, session: &SessionSummary) {
+    if let Some(instructions) = &session.instructions {
+        output.push_str(&format!(
+            "<mode_instructions mode=\"{}\">\n{}\n</mode_instructions>\n\n",
+            session.mode.as_deref().unwrap_or_default(),
+            instructions.trim()
+        ));
+    }
+}
+
 /// Format a summary for LLM consumption
 /// This follows the recommended structure from the spec
 pub fn format_summary(summary: &SummaryOutput) -> String {
@@ -253,7 +433,11 @@ pub fn format_summary(summary: &SummaryOutput) -> String {
         if let Some(focus) = &session.focus_task_id {
             output.push_str(&format!("focus_task: {}\n", focus));
         }
+        if !session.emphasize.is_empty() {
+            output.push_str(&format!("emphasize: {}\n", session.emphasize.join(", ")));
+        }
         output.push_str("</session_header>\n\n");
+        push_mode_instructions(&mut output, session);
     }
 
     // State of work
@@ -273,6 +457,36 @@ pub fn format_summary(summary: &SummaryOutput) -> String {
     output.push_str(&format!("  P2: {}\n", summary.state.by_priority.p2));
     output.push_str(&format!("  P3: {}\n", summary.state.by_priority.p3));
     output.push_str(&format!("  P4: {}\n", summary.state.by_priority.p4));
+    if !summary.state.by_label.is_empty() {
+        output.push_str("by_label:\n");
+        for (label, count) in &summary.state.by_label {
+            output.push_str(&format!("  {}: {}\n", label, count));
+        }
+    }
+    if !summary.state.by_assignee.is_empty() {
+        output.push_str("by_assignee:\n");
+        for (assignee, count) in &summary.state.by_assignee {
+            output.push_str(&format!("  {}: {}\n", assignee, count));
+        }
+    }
+    if !summary.state.by_author.is_empty() {
+        output.push_str("by_author:\n");
+        for (author, counts) in &summary.state.by_author {
+            output.push_str(&format!(
+                "  {}: {} tasks, {} comments\n",
+                author, counts.tasks, counts.comments
+            ));
+        }
+    }
+    if !summary.state.estimates.is_empty() {
+        output.push_str("estimates:\n");
+        for rollup in &summary.state.estimates {
+            output.push_str(&format!(
+                "  {} {}: {} of {} remaining\n",
+                rollup.kind, rollup.id, rollup.remaining, rollup.total
+            ));
+        }
+    }
     output.push_str("</state_of_work>\n\n");
 
     // Focus task detail
@@ -288,6 +502,27 @@ pub fn format_summary(summary: &SummaryOutput) -> String {
         output.push_str("</focus_task>\n\n");
     }
 
+    // Tasks set aside on the focus stack, to return to with `focus pop`
+    if !summary.interrupted.is_empty() {
+        output.push_str(&format!(
+            "<focus_stack depth=\"{}\">\n",
+            summary.interrupted.len() + 1
+        ));
+        if let Some(focus_task) = &summary.focus_task {
+            output.push_str(&format!(
+                "- {}: {} (focus)\n",
+                focus_task.id, focus_task.title
+            ));
+        }
+        for task in &summary.interrupted {
+            output.push_str(&format!(
+                "- {}: {} [{}]\n",
+                task.id, task.title, task.status
+            ));
+        }
+        output.push_str("</focus_stack>\n\n");
+    }
+
     // Blockers
     if !summary.blockers.is_empty() {
         output.push_str("<blockers>\n");
@@ -301,6 +536,36 @@ pub fn format_summary(summary: &SummaryOutput) -> String {
         output.push_str("</blockers>\n\n");
     }
 
+    // Tasks due soon
+    if !summary.due_soon.is_empty() {
+        output.push_str("<due_soon>\n");
+        for task in &summary.due_soon {
+            output.push_str(&format!(
+                "  - {} ({}): due {}",
+                task.title,
+                task.id,
+                task.due_at.as_deref().unwrap_or_default()
+            ));
+            if task.is_overdue() {
+                output.push_str(", overdue");
+            }
+            output.push('\n');
+        }
+        output.push_str("</due_soon>\n\n");
+    }
+
+    // Stale tasks
+    if !summary.stale.is_empty() {
+        output.push_str("<stale_tasks>\n");
+        for task in &summary.stale {
+            output.push_str(&format!(
+                "  - {} ({}): {}, last activity {}\n",
+                task.title, task.id, task.status, task.last_activity
+            ));
+        }
+        output.push_str("</stale_tasks>\n\n");
+    }
+
     // Next actionable tasks
     if !summary.next_actions.is_empty() {
         output.push_str("<next_actions>\n");
@@ -316,9 +581,12 @@ pub fn format_summary(summary: &SummaryOutput) -> String {
     // Recent decisions
     if !summary.recent_decisions.is_empty() {
         output.push_str("<recent_decisions>\n");
-        for comment in &summary.recent_decisions {
-            let author = comment.author.as_deref().unwrap_or("unknown");
-            output.push_str(&format!("  - {}: {}\n", author, comment.content));
+        for decision in &summary.recent_decisions {
+            let author = decision.comment.author.as_deref().unwrap_or("unknown");
+            output.push_str(&format!("  - {}: {}\n", author, decision.comment.content));
+            if let Some(context) = decision.thread_context() {
+                output.push_str(&format!("    ({})\n", context));
+            }
         }
         output.push_str("</recent_decisions>\n\n");
     }
@@ -356,6 +624,7 @@ pub fn format_context(context: &ContextOutput) -> String {
             output.push_str(&format!("mode: {}\n", mode));
         }
         output.push_str("</session>\n\n");
+        push_mode_instructions(&mut output, session);
     }
 
     // Projects
@@ -452,6 +721,36 @@ pub fn format_context(context: &ContextOutput) -> String {
         output.push_str("</artifacts>\n\n");
     }
 
+    // Attachments
+    if !context.attachments.is_empty() {
+        output.push_str(&format!(
+            "<attachments count=\"{}\">\n",
+            context.attachments.len()
+        ));
+        for attachment in &context.attachments {
+            output.push_str(&format!(
+                "  - {}: {}\n",
+                attachment.task_id, attachment.path
+            ));
+        }
+        output.push_str("</attachments>\n\n");
+    }
+
+    // Related tasks
+    if !context.related.is_empty() {
+        output.push_str(&format!(
+            "<related_tasks count=\"{}\">\n",
+            context.related.len()
+        ));
+        for r in &context.related {
+            output.push_str(&format!(
+                "  - {} {} {} ({})\n",
+                r.task_id, r.relation, r.related_task_id, r.title
+            ));
+        }
+        output.push_str("</related_tasks>\n\n");
+    }
+
     // Steering files
     if !context.steering.is_empty() {
         output.push_str(&format!(
@@ -609,6 +908,7 @@ pub fn format_search_results(results: &[SearchResult]) -> String {
                 status,
                 priority,
                 project_id,
+                labels,
             } => {
                 output.push_str("<task>\n");
                 output.push_str(&format!("id: {}\n", id));
@@ -616,6 +916,9 @@ pub fn format_search_results(results: &[SearchResult]) -> String {
                 output.push_str(&format!("status: {}\n", status));
                 output.push_str(&format!("priority: {}\n", priority));
                 output.push_str(&format!("project: {}\n", project_id));
+                if !labels.is_empty() {
+                    output.push_str(&format!("labels: {}\n", labels.join(", ")));
+                }
                 if let Some(desc) = description {
                     output.push_str(&format!("description: {}\n", desc));
                 }
@@ -671,6 +974,81 @@ pub fn format_initiatives(initiatives: &[Initiative]) -> String {
     output
 }
 
+pub fn format_initiative_with_milestones(
+    initiative: &Initiative,
+    milestones: &[MilestoneProgress],
+) -> String {
+    let mut output = format_initiative(initiative);
+    if !milestones.is_empty() {
+        output.push_str(&format_milestones(milestones));
+    }
+    output
+}
+
+pub fn format_milestone(milestone: &MilestoneProgress) -> String {
+    let m = &milestone.milestone;
+    let mut output = String::new();
+    output.push_str("<milestone>\n");
+    output.push_str(&format!("id: {}\n", m.id));
+    output.push_str(&format!("name: {}\n", m.name));
+    output.push_str(&format!("initiative: {}\n", m.initiative_id));
+    output.push_str(&format!("status: {}\n", milestone_state(milestone)));
+    if let Some(target) = &m.target_date {
+        output.push_str(&format!("target: {}\n", target));
+    }
+    if let Some(desc) = &m.description {
+        output.push_str(&format!("description: {}\n", desc));
+    }
+    output.push_str(&format!(
+        "progress: {:.0}% ({}/{} tasks)\n",
+        milestone.percent_complete, milestone.done_tasks, milestone.total_tasks
+    ));
+    if !milestone.projects.is_empty() {
+        output.push_str(&format!("projects: {}\n", milestone.projects.join(", ")));
+    }
+    if !milestone.tasks.is_empty() {
+        output.push_str(&format!("tasks: {}\n", milestone.tasks.join(", ")));
+    }
+    output.push_str("</milestone>\n");
+    output
+}
+
+pub fn format_milestones(milestones: &[MilestoneProgress]) -> String {
+    let mut output = format!("<milestones count=\"{}\">\n", milestones.len());
+    for milestone in milestones {
+        output.push_str(&format!("  - {}\n", milestone_line(milestone)));
+    }
+    output.push_str("</milestones>\n");
+    output
+}
+
+fn milestone_state(milestone: &MilestoneProgress) -> &'static str {
+    if milestone.milestone.is_completed() {
+        "complete"
+    } else if milestone.milestone.is_overdue() {
+        "overdue"
+    } else {
+        "open"
+    }
+}
+
+fn milestone_line(milestone: &MilestoneProgress) -> String {
+    let m = &milestone.milestone;
+    let mut line = format!(
+        "{} ({}) [{}] {:.0}% ({}/{} tasks)",
+        m.name,
+        m.id,
+        milestone_state(milestone),
+        milestone.percent_complete,
+        milestone.done_tasks,
+        milestone.total_tasks
+    );
+    if let Some(target) = &m.target_date {
+        line.push_str(&format!(" target={}", target));
+    }
+    line
+}
+
 // === Initiative Summary ===
 
 use crate::models::initiative::InitiativeSummary;
@@ -693,6 +1071,14 @@ pub fn format_initiative_summary(summary: &InitiativeSummary) -> String {
         summary.status.blocked_projects
     ));
 
+    if !summary.milestones.is_empty() {
+        lines.push(String::new());
+        lines.push("## Milestones".to_string());
+        for m in &summary.milestones {
+            lines.push(format!("- {}", milestone_line(m)));
+        }
+    }
+
     // Blockers - if any
     if !summary.blockers.is_empty() {
         lines.push(String::new());
@@ -720,6 +1106,40 @@ pub fn format_initiative_summary(summary: &InitiativeSummary) -> String {
     lines.join("\n")
 }
 
+pub fn format_related_tasks(related: &[RelatedTask]) -> String {
+    let mut output = String::from("<related_tasks>\n");
+    for r in related {
+        output.push_str(&format!(
+            "{} {} {} ({})\n",
+            r.task_id, r.relation, r.related_task_id, r.title
+        ));
+    }
+    output.push_str("</related_tasks>\n");
+    output
+}
+
+pub fn format_attachments(attachments: &[Attachment]) -> String {
+    let mut output = String::from("<attachments>\n");
+    for attachment in attachments {
+        output.push_str(&format!("{} {}\n", attachment.task_id, attachment.path));
+    }
+    output.push_str("</attachments>\n");
+    output
+}
+
+pub fn format_vcs_links(links: &[TaskVcsLink]) -> String {
+    let mut output = String::from("<vcs_links>\n");
+    for link in links {
+        output.push_str(&format!("{} {} {}", link.task_id, link.kind, link.ref_name));
+        if let Some(summary) = &link.summary {
+            output.push_str(&format!(" {}", summary));
+        }
+        output.push('\n');
+    }
+    output.push_str("</vcs_links>\n");
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -735,19 +1155,25 @@ mod tests {
             status: "todo".to_string(),
             priority: "P1".to_string(),
             owner: Some("test-user".to_string()),
+            author: None,
             tags: None,
             blocked_reason: None,
             started_at: None,
             completed_at: None,
             due_at: None,
+            estimate: None,
             claim_owner: None,
             claim_claimed_at: None,
             claim_lease_expires_at: None,
+            claim_session_id: None,
             pinned: 0,
             focus_weight: 0,
+            archived_at: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:00:00Z".to_string(),
             version: 1,
+            labels: vec![],
+            fields: Default::default(),
         }
     }
 
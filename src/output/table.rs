@@ -1,7 +1,15 @@
+use std::io::IsTerminal;
+
+use tabled::builder::Builder;
+use tabled::settings::object::{Cell, Rows};
+use tabled::settings::peaker::Priority;
+use tabled::settings::{Color, Width};
 use tabled::{Table, Tabled};
 
-use crate::models::initiative::Initiative;
+use crate::models::initiative::{Initiative, MilestoneProgress};
 use crate::models::*;
+use crate::output::columns::{self, TaskColumn};
+use crate::services::datetime;
 
 #[derive(Tabled)]
 struct ProjectRow {
@@ -45,6 +53,12 @@ pub fn format_project(project: &Project) -> String {
     if !tags.is_empty() {
         output.push_str(&format!("  Tags:        {}\n", tags.join(", ")));
     }
+    if !project.fields.is_empty() {
+        output.push_str(&format!(
+            "  Fields:      {}\n",
+            format_fields(&project.fields)
+        ));
+    }
     output.push_str(&format!("  Created:     {}\n", project.created_at));
     output.push_str(&format!("  Updated:     {}\n", project.updated_at));
     output
@@ -55,37 +69,143 @@ pub fn format_projects(projects: &[Project]) -> String {
         return "No projects found.\n".to_string();
     }
     let rows: Vec<ProjectRow> = projects.iter().map(ProjectRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
+}
+
+#[derive(Tabled)]
+struct ProjectHealthListRow {
+    #[tabled(inline)]
+    project: ProjectRow,
+    #[tabled(rename = "Health")]
+    health: String,
+}
+
+pub fn format_projects_with_health(projects: &[Project], health: &[ProjectHealth]) -> String {
+    if projects.is_empty() {
+        return "No projects found.\n".to_string();
+    }
+    let rows: Vec<ProjectHealthListRow> = projects
+        .iter()
+        .map(|p| ProjectHealthListRow {
+            project: ProjectRow::from(p),
+            health: health
+                .iter()
+                .find(|h| h.project_id == p.id)
+                .map(ProjectHealth::badge)
+                .unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+#[derive(Tabled)]
+struct ProjectHealthRow {
+    #[tabled(rename = "Project")]
+    project: String,
+    #[tabled(rename = "Done")]
+    done: String,
+    #[tabled(rename = "Blocked")]
+    blocked: String,
+    #[tabled(rename = "Avg Open Age")]
+    age: String,
+    #[tabled(rename = "Throughput")]
+    throughput: String,
+    #[tabled(rename = "Failing Runs")]
+    failing_runs: usize,
+}
+
+pub fn format_project_health(health: &[ProjectHealth]) -> String {
+    if health.is_empty() {
+        return "No projects found.\n".to_string();
+    }
+    let rows: Vec<ProjectHealthRow> = health
+        .iter()
+        .map(|h| ProjectHealthRow {
+            project: format!("{} ({})", truncate(&h.project_name, 30), h.project_id),
+            done: format!(
+                "{:.0}% ({}/{})",
+                h.percent_complete, h.done_tasks, h.total_tasks
+            ),
+            blocked: format!("{} ({:.0}%)", h.blocked_tasks, h.blocked_ratio * 100.0),
+            age: h
+                .avg_open_task_age_days
+                .map(|days| format!("{:.1}d", days))
+                .unwrap_or_else(|| "-".to_string()),
+            throughput: format!("{} / {}d", h.throughput, h.window_days),
+            failing_runs: h.failing_runs,
+        })
+        .collect();
+    render(Table::new(rows))
 }
 
 #[derive(Tabled)]
-struct TaskRow {
+struct CostRow {
     #[tabled(rename = "ID")]
     id: String,
-    #[tabled(rename = "Title")]
-    title: String,
-    #[tabled(rename = "Status")]
-    status: String,
-    #[tabled(rename = "Priority")]
-    priority: String,
-    #[tabled(rename = "Owner")]
-    owner: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Runs")]
+    runs: usize,
+    #[tabled(rename = "Input Tokens")]
+    input_tokens: i64,
+    #[tabled(rename = "Output Tokens")]
+    output_tokens: i64,
+    #[tabled(rename = "Cost")]
+    cost: String,
 }
 
-impl From<&Task> for TaskRow {
-    fn from(t: &Task) -> Self {
-        let status = if t.blocked_reason.is_some() {
-            format!("{} (blocked)", t.status)
-        } else {
-            t.status.clone()
-        };
-        Self {
-            id: t.id.clone(),
-            title: truncate(&t.title, 40),
-            status,
-            priority: t.priority.clone(),
-            owner: t.owner.clone().unwrap_or_else(|| "-".to_string()),
+/// Dollars, with more precision for amounts under a dollar
+fn format_cost(usd: f64) -> String {
+    if usd < 1.0 {
+        format!("${:.4}", usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+pub fn format_cost_report(report: &CostReport) -> String {
+    let since = format_date(&report.since);
+    if report.total.runs == 0 {
+        return format!("No runs reported usage since {}.\n", since);
+    }
+    let total = &report.total;
+    let mut output = format!(
+        "Costs since {}: {} over {} run(s), {} input / {} output tokens\n",
+        since,
+        format_cost(total.cost_usd),
+        total.runs,
+        total.input_tokens,
+        total.output_tokens
+    );
+    for (title, groups) in [
+        ("By worker", &report.by_worker),
+        ("By project", &report.by_project),
+        ("By task", &report.by_task),
+    ] {
+        if groups.is_empty() {
+            continue;
         }
+        let rows: Vec<CostRow> = groups
+            .iter()
+            .map(|g| CostRow {
+                id: g.id.clone(),
+                name: truncate(&g.name, 40),
+                runs: g.totals.runs,
+                input_tokens: g.totals.input_tokens,
+                output_tokens: g.totals.output_tokens,
+                cost: format_cost(g.totals.cost_usd),
+            })
+            .collect();
+        output.push_str(&format!("\n{}:\n{}\n", title, render(Table::new(rows))));
+    }
+    output
+}
+
+fn format_labels(labels: &[String]) -> String {
+    if labels.is_empty() {
+        "-".to_string()
+    } else {
+        labels.join(", ")
     }
 }
 
@@ -94,6 +214,16 @@ pub fn format_task(task: &Task) -> String {
 }
 
 pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
+    format_task_with_links(task, blocked_by, &[], &[], &[])
+}
+
+pub fn format_task_with_links(
+    task: &Task,
+    blocked_by: &[String],
+    links: &[TaskVcsLink],
+    attachments: &[Attachment],
+    related: &[RelatedTask],
+) -> String {
     let mut output = String::new();
     output.push_str(&format!("Task: {}\n", task.title));
     output.push_str(&format!("  ID:          {}\n", task.id));
@@ -104,6 +234,15 @@ pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
         "  Owner:       {}\n",
         task.owner.as_deref().unwrap_or("-")
     ));
+    if let Some(author) = &task.author {
+        output.push_str(&format!("  Author:      {}\n", author));
+    }
+    if !task.labels.is_empty() {
+        output.push_str(&format!("  Labels:      {}\n", task.labels.join(", ")));
+    }
+    if !task.fields.is_empty() {
+        output.push_str(&format!("  Fields:      {}\n", format_fields(&task.fields)));
+    }
     if let Some(parent) = &task.parent_task_id {
         output.push_str(&format!("  Parent:      {}\n", parent));
     }
@@ -113,56 +252,211 @@ pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
     if let Some(reason) = &task.blocked_reason {
         output.push_str(&format!("  Blocked:     {}\n", reason));
     }
+    if let Some(archived) = &task.archived_at {
+        output.push_str(&format!(
+            "  Archived:    {}\n",
+            datetime::format_local_datetime(archived)
+        ));
+    }
     if !blocked_by.is_empty() {
         output.push_str(&format!("  Blocked by:  {}\n", blocked_by.join(", ")));
     }
     if let Some(due) = &task.due_at {
-        output.push_str(&format!("  Due:         {}\n", due));
+        output.push_str(&format!(
+            "  Due:         {}\n",
+            datetime::format_local_datetime(due)
+        ));
+    }
+    if let Some(estimate) = task.estimate {
+        output.push_str(&format!("  Estimate:    {}\n", estimate));
     }
     if task.pinned != 0 {
         output.push_str("  Pinned:      yes\n");
     }
     if let Some(claim) = task.claim_info() {
-        output.push_str(&format!("  Claimed by:  {}\n", claim.owner));
+        match &claim.session_id {
+            Some(session_id) => {
+                output.push_str(&format!("  Claimed by:  session {}\n", session_id))
+            }
+            None => output.push_str(&format!("  Claimed by:  {}\n", claim.owner)),
+        }
         if let Some(expires) = claim.lease_expires_at {
             output.push_str(&format!("  Lease until: {}\n", expires));
         }
     }
+    let branches: Vec<&str> = links
+        .iter()
+        .filter(|l| l.kind_enum() == Some(VcsLinkKind::Branch))
+        .map(|l| l.ref_name.as_str())
+        .collect();
+    if !branches.is_empty() {
+        output.push_str(&format!("  Branches:    {}\n", branches.join(", ")));
+    }
+    let commits: Vec<&TaskVcsLink> = links
+        .iter()
+        .filter(|l| l.kind_enum() == Some(VcsLinkKind::Commit))
+        .collect();
+    if !commits.is_empty() {
+        output.push_str("  Commits:\n");
+        for commit in commits {
+            output.push_str(&format!(
+                "    {} {}\n",
+                commit.short_ref(),
+                commit.summary.as_deref().unwrap_or("")
+            ));
+        }
+    }
+    if !related.is_empty() {
+        output.push_str("  Related:\n");
+        for r in related {
+            output.push_str(&format!(
+                "    {} {} ({}, {})\n",
+                r.relation.replace('_', " "),
+                r.related_task_id,
+                truncate(&r.title, 40),
+                r.status
+            ));
+        }
+    }
+    if !attachments.is_empty() {
+        output.push_str("  Attachments:\n");
+        for attachment in attachments {
+            output.push_str(&format!(
+                "    {} ({})\n",
+                attachment.path,
+                format_size(attachment.size_bytes as u64)
+            ));
+        }
+    }
     output.push_str(&format!("  Created:     {}\n", task.created_at));
     output.push_str(&format!("  Updated:     {}\n", task.updated_at));
     output
 }
 
 pub fn format_tasks(tasks: &[Task]) -> String {
-    if tasks.is_empty() {
+    format_task_rows(tasks.iter().map(|t| (t, &[][..])))
+}
+
+pub fn format_tasks_with_deps(tasks_with_deps: &[(Task, Vec<String>)]) -> String {
+    format_task_rows(tasks_with_deps.iter().map(|(t, deps)| (t, deps.as_slice())))
+}
+
+/// Render tasks with the columns selected for this invocation
+fn format_task_rows<'a>(tasks: impl ExactSizeIterator<Item = (&'a Task, &'a [String])>) -> String {
+    if tasks.len() == 0 {
         return "No tasks found.\n".to_string();
     }
-    let rows: Vec<TaskRow> = tasks.iter().map(TaskRow::from).collect();
-    Table::new(rows).to_string()
+    let columns = columns::options()
+        .columns
+        .unwrap_or_else(TaskColumn::defaults);
+
+    let mut builder = Builder::default();
+    builder.push_record(columns.iter().map(TaskColumn::header));
+    let mut overdue = Vec::new();
+    for (task, deps) in tasks {
+        overdue.push(task.is_overdue());
+        builder.push_record(columns.iter().map(|c| task_cell(c, task, deps)));
+    }
+    render(highlight_overdue(builder.build(), &overdue))
 }
 
-pub fn format_tasks_with_deps(tasks_with_deps: &[(Task, Vec<String>)]) -> String {
-    if tasks_with_deps.is_empty() {
+/// Render tasks from several workspaces, with a Workspace column first
+pub fn format_workspace_tasks(tasks: &[WorkspaceTask]) -> String {
+    if tasks.is_empty() {
         return "No tasks found.\n".to_string();
     }
-    let rows: Vec<TaskRow> = tasks_with_deps
-        .iter()
-        .map(|(t, deps)| {
-            let status = if t.blocked_reason.is_some() || !deps.is_empty() {
-                format!("{} (blocked)", t.status)
+    let columns = columns::options()
+        .columns
+        .unwrap_or_else(TaskColumn::defaults);
+
+    let mut builder = Builder::default();
+    builder.push_record(
+        std::iter::once("Workspace".to_string()).chain(columns.iter().map(TaskColumn::header)),
+    );
+    for WorkspaceTask { workspace, task } in tasks {
+        builder.push_record(
+            std::iter::once(workspace.clone())
+                .chain(columns.iter().map(|c| task_cell(c, task, &[]))),
+        );
+    }
+    let overdue: Vec<bool> = tasks.iter().map(|t| t.task.is_overdue()).collect();
+    render(highlight_overdue(builder.build(), &overdue))
+}
+
+/// Color the rows of overdue tasks red, given whether each task is overdue
+fn highlight_overdue(mut table: Table, overdue: &[bool]) -> Table {
+    if use_color() {
+        for (row, _) in overdue.iter().enumerate().filter(|(_, overdue)| **overdue) {
+            table.modify(Rows::new(row + 1..row + 2), Color::FG_RED);
+        }
+    }
+    table
+}
+
+fn task_cell(column: &TaskColumn, task: &Task, blocked_by: &[String]) -> String {
+    let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+    match column {
+        TaskColumn::Id => task.id.clone(),
+        TaskColumn::Title => truncate(&task.title, 40),
+        TaskColumn::Status => {
+            let mut marks = Vec::new();
+            if task.blocked_reason.is_some() || !blocked_by.is_empty() {
+                marks.push("blocked");
+            }
+            if task.is_overdue() {
+                marks.push("overdue");
+            }
+            if marks.is_empty() {
+                task.status.clone()
             } else {
-                t.status.clone()
-            };
-            TaskRow {
-                id: t.id.clone(),
-                title: truncate(&t.title, 40),
-                status,
-                priority: t.priority.clone(),
-                owner: t.owner.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{} ({})", task.status, marks.join(", "))
             }
-        })
-        .collect();
-    Table::new(rows).to_string()
+        }
+        TaskColumn::Priority => task.priority.clone(),
+        TaskColumn::Owner => or_dash(task.owner.as_deref()),
+        TaskColumn::Author => or_dash(task.author.as_deref()),
+        TaskColumn::Labels => format_labels(&task.labels),
+        TaskColumn::Project => task.project_id.clone(),
+        TaskColumn::Estimate => task
+            .estimate
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        TaskColumn::Due => task
+            .due_at
+            .as_deref()
+            .map(format_date)
+            .unwrap_or_else(|| "-".to_string()),
+        TaskColumn::Tags => {
+            let tags = task.tags_vec();
+            if tags.is_empty() {
+                "-".to_string()
+            } else {
+                tags.join(", ")
+            }
+        }
+        TaskColumn::BlockedBy => {
+            if blocked_by.is_empty() {
+                "-".to_string()
+            } else {
+                blocked_by.join(", ")
+            }
+        }
+        TaskColumn::Created => format_date(&task.created_at),
+        TaskColumn::Updated => format_date(&task.updated_at),
+        TaskColumn::Field(name) => or_dash(task.fields.get(name).map(String::as_str)),
+    }
+}
+
+/// Render a table, truncating the widest columns to fit `--max-width`
+fn render(mut table: Table) -> String {
+    if let Some(max_width) = columns::options().max_width {
+        table.with(
+            Width::truncate(max_width)
+                .suffix("...")
+                .priority(Priority::max(true)),
+        );
+    }
+    table.to_string()
 }
 
 #[derive(Tabled)]
@@ -210,7 +504,44 @@ pub fn format_comments(comments: &[Comment]) -> String {
         return "No comments found.\n".to_string();
     }
     let rows: Vec<CommentRow> = comments.iter().map(CommentRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
+}
+
+pub fn format_comment_threads(threads: &[CommentThread]) -> String {
+    fn push_thread(output: &mut String, thread: &CommentThread, depth: usize) {
+        let comment = &thread.comment;
+        let indent = "  ".repeat(depth);
+        let marker = if depth == 0 { "" } else { "└ " };
+        output.push_str(&format!(
+            "{}{}{} [{}] {} · {}\n",
+            indent,
+            marker,
+            comment.id,
+            comment.kind,
+            comment.author.as_deref().unwrap_or("-"),
+            comment.created_at
+        ));
+        let body_indent = if depth == 0 {
+            "  ".to_string()
+        } else {
+            format!("{}    ", indent)
+        };
+        for line in comment.content.lines() {
+            output.push_str(&format!("{}{}\n", body_indent, line));
+        }
+        for reply in &thread.replies {
+            push_thread(output, reply, depth + 1);
+        }
+    }
+
+    if threads.is_empty() {
+        return "No comments found.\n".to_string();
+    }
+    let mut output = String::new();
+    for thread in threads {
+        push_thread(&mut output, thread, 0);
+    }
+    output
 }
 
 #[derive(Tabled)]
@@ -265,6 +596,11 @@ pub fn format_session(session: &Session) -> String {
     if let Some(focus) = &session.focus_task_id {
         output.push_str(&format!("  Focus:  {}\n", focus));
     }
+    let stack = session.focus_stack_vec();
+    if stack.len() > 1 {
+        let set_aside: Vec<&str> = stack.iter().rev().skip(1).map(String::as_str).collect();
+        output.push_str(&format!("  Set aside: {}\n", set_aside.join(" > ")));
+    }
     output.push_str(&format!("  Created: {}\n", session.created_at));
     if let Some(closed) = &session.closed_at {
         output.push_str(&format!("  Closed:  {}\n", closed));
@@ -272,12 +608,87 @@ pub fn format_session(session: &Session) -> String {
     output
 }
 
+/// A duration as `45s`, `12m 5s` or `3h 20m`
+fn format_elapsed(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+pub fn format_session_review(review: &SessionReview) -> String {
+    let mut output = format_session(&review.session);
+    output.push_str(&format!(
+        "  Elapsed: {}\n",
+        format_elapsed(review.elapsed_secs)
+    ));
+    output.push_str(&format!("  Events:  {}\n", review.event_count));
+
+    output.push_str(&format!("\nTasks touched ({}):\n", review.tasks.len()));
+    for task in &review.tasks {
+        output.push_str(&format!(
+            "  {} [{}] {} ({} event(s))\n",
+            task.id,
+            task.status,
+            truncate(&task.title, 50),
+            task.events
+        ));
+    }
+    if !review.transitions.is_empty() {
+        output.push_str(&format!(
+            "\nStatus changes ({}):\n",
+            review.transitions.len()
+        ));
+        for t in &review.transitions {
+            output.push_str(&format!(
+                "  {} {} {} -> {}\n",
+                t.changed_at,
+                t.task_id,
+                t.from_status.as_deref().unwrap_or("?"),
+                t.to_status
+            ));
+        }
+    }
+    if !review.comments.is_empty() {
+        output.push_str(&format!("\nComments ({}):\n", review.comments.len()));
+        for comment in &review.comments {
+            output.push_str(&format!(
+                "  {} [{}] on {}: {}\n",
+                comment.id,
+                comment.kind,
+                comment.parent_id,
+                truncate(comment.content.lines().next().unwrap_or(""), 60)
+            ));
+        }
+    }
+    if !review.checkpoints.is_empty() {
+        output.push_str(&format!("\nCheckpoints ({}):\n", review.checkpoints.len()));
+        for checkpoint in &review.checkpoints {
+            output.push_str(&format!(
+                "  {} ({}) {}\n",
+                checkpoint.name, checkpoint.id, checkpoint.created_at
+            ));
+        }
+    }
+    if !review.runs.is_empty() {
+        output.push_str(&format!("\nRuns triggered ({}):\n", review.runs.len()));
+        for run in &review.runs {
+            output.push_str(&format!(
+                "  {} [{}] {} on {} by {}\n",
+                run.id, run.status, run.event_type, run.entity_id, run.worker_id
+            ));
+        }
+    }
+    output
+}
+
 pub fn format_sessions(sessions: &[Session]) -> String {
     if sessions.is_empty() {
         return "No sessions found.\n".to_string();
     }
     let rows: Vec<SessionRow> = sessions.iter().map(SessionRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
 }
 
 #[derive(Tabled)]
@@ -308,6 +719,9 @@ pub fn format_checkpoint(checkpoint: &Checkpoint) -> String {
     output.push_str(&format!("Checkpoint: {}\n", checkpoint.name));
     output.push_str(&format!("  ID:      {}\n", checkpoint.id));
     output.push_str(&format!("  Session: {}\n", checkpoint.session_id));
+    if let Some(author) = &checkpoint.author {
+        output.push_str(&format!("  Author:  {}\n", author));
+    }
     output.push_str(&format!("  Created: {}\n", checkpoint.created_at));
     output
 }
@@ -317,7 +731,7 @@ pub fn format_checkpoints(checkpoints: &[Checkpoint]) -> String {
         return "No checkpoints found.\n".to_string();
     }
     let rows: Vec<CheckpointRow> = checkpoints.iter().map(CheckpointRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
 }
 
 #[derive(Tabled)]
@@ -360,7 +774,7 @@ pub fn format_artifacts(artifacts: &[Artifact]) -> String {
         return "No artifacts found.\n".to_string();
     }
     let rows: Vec<ArtifactRow> = artifacts.iter().map(ArtifactRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
 }
 
 pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
@@ -384,6 +798,35 @@ pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
     }
 }
 
+pub fn format_recommendations(recommendations: &[TaskRecommendation]) -> String {
+    if recommendations.is_empty() {
+        return "No next task: No actionable tasks found\n".to_string();
+    }
+    let mut output = String::new();
+    for (i, r) in recommendations.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "{}. {} [{}] {}\n",
+            i + 1,
+            r.task.id,
+            r.task.priority,
+            r.task.title
+        ));
+        output.push_str(&format!("   Score: {}\n", r.score));
+        for f in &r.factors {
+            let points = if f.weight == 1.0 {
+                format!("+{}", f.points)
+            } else {
+                format!("+{} (x{})", f.points, f.weight)
+            };
+            output.push_str(&format!("   {:<9} {:<10} {}\n", f.name, points, f.detail));
+        }
+    }
+    output
+}
+
 #[derive(Tabled)]
 struct SearchResultRow {
     #[tabled(rename = "Type")]
@@ -441,25 +884,44 @@ pub fn format_search_results(results: &[SearchResult]) -> String {
         return "No results found.\n".to_string();
     }
     let rows: Vec<SearchResultRow> = results.iter().map(SearchResultRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
+}
+
+#[derive(Tabled)]
+struct WorkspaceSearchResultRow {
+    #[tabled(rename = "Workspace")]
+    workspace: String,
+    #[tabled(inline)]
+    result: SearchResultRow,
+}
+
+pub fn format_workspace_search_results(results: &[WorkspaceSearchResult]) -> String {
+    if results.is_empty() {
+        return "No results found.\n".to_string();
+    }
+    let rows: Vec<WorkspaceSearchResultRow> = results
+        .iter()
+        .map(|r| WorkspaceSearchResultRow {
+            workspace: r.workspace.clone(),
+            result: SearchResultRow::from(&r.result),
+        })
+        .collect();
+    render(Table::new(rows))
 }
 
 // Helper functions
 fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
+    if s.chars().count() > max_len {
+        let kept: String = s.chars().take(max_len - 3).collect();
+        format!("{}...", kept)
     } else {
         s.to_string()
     }
 }
 
 fn format_date(iso_date: &str) -> String {
-    // Just return date portion for brevity in tables
-    if iso_date.len() >= 10 {
-        iso_date[..10].to_string()
-    } else {
-        iso_date.to_string()
-    }
+    // Just return date portion (in the configured timezone) for brevity in tables
+    datetime::format_local_date(iso_date)
 }
 
 #[derive(Tabled)]
@@ -514,7 +976,95 @@ pub fn format_initiatives(initiatives: &[Initiative]) -> String {
         return "No initiatives found.\n".to_string();
     }
     let rows: Vec<InitiativeRow> = initiatives.iter().map(InitiativeRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
+}
+
+pub fn format_initiative_with_milestones(
+    initiative: &Initiative,
+    milestones: &[MilestoneProgress],
+) -> String {
+    let mut output = format_initiative(initiative);
+    if !milestones.is_empty() {
+        output.push_str("\nMilestones:\n");
+        for milestone in milestones {
+            output.push_str(&format!("  {}\n", milestone_line(milestone)));
+        }
+    }
+    output
+}
+
+pub fn format_milestone(milestone: &MilestoneProgress) -> String {
+    let m = &milestone.milestone;
+    let mut output = String::new();
+    output.push_str(&format!("Milestone: {}\n", m.name));
+    output.push_str(&format!("  ID:          {}\n", m.id));
+    output.push_str(&format!("  Initiative:  {}\n", m.initiative_id));
+    output.push_str(&format!(
+        "  Target:      {}\n",
+        m.target_date
+            .as_deref()
+            .map(datetime::format_local_date)
+            .unwrap_or_else(|| "-".to_string())
+    ));
+    if let Some(completed) = &m.completed_at {
+        output.push_str(&format!(
+            "  Completed:   {}\n",
+            datetime::format_local_date(completed)
+        ));
+    }
+    if let Some(desc) = &m.description {
+        output.push_str(&format!("  Description: {}\n", desc));
+    }
+    output.push_str(&format!(
+        "  Progress:    {} {:.0}% ({}/{} tasks)\n",
+        create_progress_bar(milestone.percent_complete, 20),
+        milestone.percent_complete,
+        milestone.done_tasks,
+        milestone.total_tasks
+    ));
+    if !milestone.projects.is_empty() {
+        output.push_str(&format!(
+            "  Projects:    {}\n",
+            milestone.projects.join(", ")
+        ));
+    }
+    if !milestone.tasks.is_empty() {
+        output.push_str(&format!("  Tasks:       {}\n", milestone.tasks.join(", ")));
+    }
+    output
+}
+
+pub fn format_milestones(milestones: &[MilestoneProgress]) -> String {
+    if milestones.is_empty() {
+        return "No milestones found.\n".to_string();
+    }
+    milestones
+        .iter()
+        .map(|m| format!("{}\n", milestone_line(m)))
+        .collect()
+}
+
+/// One milestone as `[x] [====      ] 40% Beta (2/5 tasks) - due 2026-11-30  <id>`
+fn milestone_line(milestone: &MilestoneProgress) -> String {
+    let m = &milestone.milestone;
+    let mark = if m.is_completed() { "[x]" } else { "[ ]" };
+    let mut line = format!(
+        "{} {} {:>3.0}% {} ({}/{} tasks)",
+        mark,
+        create_progress_bar(milestone.percent_complete, 20),
+        milestone.percent_complete,
+        m.name,
+        milestone.done_tasks,
+        milestone.total_tasks
+    );
+    if let Some(target) = &m.target_date {
+        line.push_str(&format!(" - due {}", datetime::format_local_date(target)));
+        if m.is_overdue() {
+            line.push_str(" (overdue)");
+        }
+    }
+    line.push_str(&format!("  {}", m.id));
+    line
 }
 
 // === Initiative Summary ===
@@ -559,6 +1109,14 @@ pub fn format_initiative_summary(summary: &InitiativeSummary) -> String {
     ));
     output.push('\n');
 
+    if !summary.milestones.is_empty() {
+        output.push_str("Milestones:\n");
+        for milestone in &summary.milestones {
+            output.push_str(&format!("  {}\n", milestone_line(milestone)));
+        }
+        output.push('\n');
+    }
+
     // Projects breakdown
     if !summary.projects.is_empty() {
         output.push_str("Projects:\n");
@@ -613,7 +1171,7 @@ fn create_progress_bar(percent: f32, width: usize) -> String {
 
 // === Worker formatting ===
 
-use crate::models::worker::Worker;
+use crate::models::worker::{FilterTest, PauseMode, Worker};
 
 #[derive(Tabled)]
 struct WorkerRow {
@@ -642,7 +1200,11 @@ impl From<&Worker> for WorkerRow {
                 .unwrap_or_else(|| truncate(&w.command, 20)),
             event_type: w.event_type.clone(),
             concurrency: w.concurrency.to_string(),
-            instance_path: truncate_path(&w.instance_path, 30),
+            instance_path: if w.global {
+                format!("all ({})", truncate_path(&w.instance_path, 24))
+            } else {
+                truncate_path(&w.instance_path, 30)
+            },
         }
     }
 }
@@ -664,8 +1226,41 @@ pub fn format_worker(worker: &Worker) -> String {
     if !filters.is_empty() {
         output.push_str(&format!("  Filters:     {}\n", filters.join(", ")));
     }
+    if let Some(schedule) = &worker.schedule {
+        output.push_str(&format!("  Schedule:    {}\n", schedule));
+        if let Some(next) = &worker.next_fire_at {
+            output.push_str(&format!(
+                "  Next Fire:   {}\n",
+                datetime::format_local_datetime(next)
+            ));
+        }
+    }
     output.push_str(&format!("  Concurrency: {}\n", worker.concurrency));
+    if let Some(secs) = worker.timeout_secs {
+        output.push_str(&format!("  Timeout:     {}s\n", secs));
+    }
+    if let Some(secs) = worker.debounce_secs {
+        output.push_str(&format!("  Debounce:    {}s\n", secs));
+    }
+    if let Some(secs) = worker.throttle_secs {
+        output.push_str(&format!("  Throttle:    {}s\n", secs));
+    }
+    if worker.stdin {
+        output.push_str("  Stdin:       event and context as JSON\n");
+    }
+    if worker.pause_mode() == PauseMode::Drop {
+        output.push_str("  On pause:    drop events\n");
+    }
+    if worker.retry_policy.is_some() {
+        output.push_str(&format!(
+            "  Retries:     {}\n",
+            worker.retry_policy_config().summary()
+        ));
+    }
     output.push_str(&format!("  Workspace:   {}\n", worker.instance_path));
+    if worker.global {
+        output.push_str("  Scope:       events from all registered workspaces\n");
+    }
     output.push_str(&format!(
         "  Detached:    {}\n",
         if worker.detached { "yes" } else { "no" }
@@ -684,32 +1279,132 @@ pub fn format_worker(worker: &Worker) -> String {
     output
 }
 
-pub fn format_workers(workers: &[Worker]) -> String {
-    if workers.is_empty() {
-        return "No workers found.\n".to_string();
-    }
-    let rows: Vec<WorkerRow> = workers.iter().map(WorkerRow::from).collect();
-    Table::new(rows).to_string()
+#[derive(Tabled)]
+struct RelatedTaskRow {
+    #[tabled(rename = "Task")]
+    task_id: String,
+    #[tabled(rename = "Relation")]
+    relation: String,
+    #[tabled(rename = "Linked Task")]
+    related_task_id: String,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Status")]
+    status: String,
 }
 
-/// Truncate a path, keeping the end portion
-fn truncate_path(path: &str, max_len: usize) -> String {
-    if path.len() <= max_len {
-        path.to_string()
-    } else {
-        format!("...{}", &path[path.len() - max_len + 3..])
+pub fn format_related_tasks(related: &[RelatedTask]) -> String {
+    if related.is_empty() {
+        return "No linked tasks.\n".to_string();
     }
+    let rows: Vec<RelatedTaskRow> = related
+        .iter()
+        .map(|r| RelatedTaskRow {
+            task_id: r.task_id.clone(),
+            relation: r.relation.replace('_', " "),
+            related_task_id: r.related_task_id.clone(),
+            title: truncate(&r.title, 40),
+            status: r.status.clone(),
+        })
+        .collect();
+    render(Table::new(rows))
 }
 
-// === Run formatting ===
+#[derive(Tabled)]
+struct AttachmentRow {
+    #[tabled(rename = "Task")]
+    task_id: String,
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Path")]
+    path: String,
+    #[tabled(rename = "Mode")]
+    mode: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
 
-use crate::models::run::Run;
+pub fn format_attachments(attachments: &[Attachment]) -> String {
+    if attachments.is_empty() {
+        return "No attachments.\n".to_string();
+    }
+    let rows: Vec<AttachmentRow> = attachments
+        .iter()
+        .map(|a| AttachmentRow {
+            task_id: a.task_id.clone(),
+            name: a.name.clone(),
+            path: a.path.clone(),
+            mode: a.mode.clone(),
+            size: format_size(a.size_bytes as u64),
+        })
+        .collect();
+    render(Table::new(rows))
+}
 
 #[derive(Tabled)]
-struct RunRow {
-    #[tabled(rename = "ID")]
-    id: String,
-    #[tabled(rename = "Worker")]
+struct VcsLinkRow {
+    #[tabled(rename = "Task")]
+    task_id: String,
+    #[tabled(rename = "Kind")]
+    kind: String,
+    #[tabled(rename = "Ref")]
+    ref_name: String,
+    #[tabled(rename = "Summary")]
+    summary: String,
+}
+
+pub fn format_vcs_links(links: &[TaskVcsLink]) -> String {
+    if links.is_empty() {
+        return "No linked branches or commits.\n".to_string();
+    }
+    let rows: Vec<VcsLinkRow> = links
+        .iter()
+        .map(|l| VcsLinkRow {
+            task_id: l.task_id.clone(),
+            kind: l.kind.clone(),
+            ref_name: l.short_ref().to_string(),
+            summary: l.summary.clone().unwrap_or_default(),
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+pub fn format_workers(workers: &[Worker]) -> String {
+    if workers.is_empty() {
+        return "No workers found.\n".to_string();
+    }
+    let rows: Vec<WorkerRow> = workers.iter().map(WorkerRow::from).collect();
+    render(Table::new(rows))
+}
+
+pub fn format_filter_test(test: &FilterTest) -> String {
+    let mut output = format!("Valid {} filter: {}\n", test.kind, test.filter);
+    match test.matches {
+        Some(true) => output.push_str("The event matches\n"),
+        Some(false) => output.push_str("The event does not match\n"),
+        None => {}
+    }
+    output
+}
+
+/// Truncate a path, keeping the end portion
+fn truncate_path(path: &str, max_len: usize) -> String {
+    if path.len() <= max_len {
+        path.to_string()
+    } else {
+        format!("...{}", &path[path.len() - max_len + 3..])
+    }
+}
+
+// === Run formatting ===
+
+use crate::models::run::Run;
+
+#[derive(Tabled)]
+struct RunRow {
+    #[tabled(rename = "ID")]
+    id: String,
+    #[tabled(rename = "Worker")]
     worker_id: String,
     #[tabled(rename = "Event")]
     event_type: String,
@@ -721,6 +1416,8 @@ struct RunRow {
     attempt: String,
     #[tabled(rename = "Exit")]
     exit_code: String,
+    #[tabled(rename = "Progress")]
+    progress: String,
 }
 
 impl From<&Run> for RunRow {
@@ -736,6 +1433,10 @@ impl From<&Run> for RunRow {
                 .exit_code
                 .map(|c| c.to_string())
                 .unwrap_or_else(|| "-".to_string()),
+            progress: r
+                .progress_display()
+                .map(|p| truncate(&p, 30))
+                .unwrap_or_else(|| "-".to_string()),
         }
     }
 }
@@ -747,11 +1448,17 @@ pub fn format_run(run: &Run) -> String {
     output.push_str(&format!("  Event ID:    {}\n", run.event_id));
     output.push_str(&format!("  Event Type:  {}\n", run.event_type));
     output.push_str(&format!("  Entity ID:   {}\n", run.entity_id));
+    if let Some(ref path) = run.instance_path {
+        output.push_str(&format!("  Workspace:   {}\n", path));
+    }
     output.push_str(&format!("  Status:      {}\n", run.status));
     output.push_str(&format!(
         "  Attempt:     {}/{}\n",
         run.attempt, run.max_attempts
     ));
+    if let Some(progress) = run.progress_display() {
+        output.push_str(&format!("  Progress:    {}\n", progress));
+    }
     output.push_str(&format!("  Command:     {}\n", run.command));
     let args = run.args_vec();
     if !args.is_empty() {
@@ -763,6 +1470,40 @@ pub fn format_run(run: &Run) -> String {
     if let Some(ref error) = run.error_message {
         output.push_str(&format!("  Error:       {}\n", error));
     }
+    if let Some(graceful) = run.exited_gracefully {
+        let how = if graceful {
+            "exited after SIGTERM"
+        } else {
+            "killed after grace period"
+        };
+        output.push_str(&format!("  Stopped:     {}\n", how));
+    }
+    if let Some(ms) = run.duration_ms {
+        output.push_str(&format!("  Duration:    {:.1}s\n", ms as f64 / 1000.0));
+    }
+    if let (Some(user), Some(system)) = (run.user_cpu_ms, run.system_cpu_ms) {
+        output.push_str(&format!(
+            "  CPU Time:    {:.2}s user, {:.2}s system\n",
+            user as f64 / 1000.0,
+            system as f64 / 1000.0
+        ));
+    }
+    if run.input_tokens.is_some() || run.output_tokens.is_some() {
+        output.push_str(&format!(
+            "  Tokens:      {} in, {} out\n",
+            run.input_tokens.unwrap_or(0),
+            run.output_tokens.unwrap_or(0)
+        ));
+    }
+    if let Some(usd) = run.cost_usd {
+        output.push_str(&format!("  Cost:        {}\n", format_cost(usd)));
+    }
+    if let Some(kb) = run.max_rss_kb {
+        output.push_str(&format!(
+            "  Max RSS:     {}\n",
+            format_size(kb.max(0) as u64 * 1024)
+        ));
+    }
     if let Some(pid) = run.pid {
         output.push_str(&format!("  PID:         {}\n", pid));
     }
@@ -788,5 +1529,971 @@ pub fn format_runs(runs: &[Run]) -> String {
         return "No runs found.\n".to_string();
     }
     let rows: Vec<RunRow> = runs.iter().map(RunRow::from).collect();
-    Table::new(rows).to_string()
+    render(Table::new(rows))
+}
+
+/// One line per match, grep style: run, when it ran, line number and line
+#[derive(Tabled)]
+struct QueuedRunRow {
+    #[tabled(rename = "#")]
+    position: usize,
+    #[tabled(rename = "ID")]
+    id: String,
+    #[tabled(rename = "Worker")]
+    worker_id: String,
+    #[tabled(rename = "Event")]
+    event_type: String,
+    #[tabled(rename = "Entity")]
+    entity_id: String,
+    #[tabled(rename = "Queued")]
+    created_at: String,
+}
+
+impl From<&QueuedRun> for QueuedRunRow {
+    fn from(q: &QueuedRun) -> Self {
+        Self {
+            position: q.position,
+            id: q.run.id.clone(),
+            worker_id: truncate(&q.run.worker_id, 15),
+            event_type: truncate(&q.run.event_type, 20),
+            entity_id: truncate(&q.run.entity_id, 20),
+            created_at: datetime::format_local_datetime(&q.run.created_at),
+        }
+    }
+}
+
+pub fn format_run_queue(queue: &[QueuedRun]) -> String {
+    if queue.is_empty() {
+        return "No queued runs.".to_string();
+    }
+    let rows: Vec<QueuedRunRow> = queue.iter().map(QueuedRunRow::from).collect();
+    render(Table::new(rows))
+}
+
+pub fn format_run_log_matches(matches: &[RunLogMatch]) -> String {
+    if matches.is_empty() {
+        return "No matching log lines found.".to_string();
+    }
+    matches
+        .iter()
+        .map(|m| {
+            format!(
+                "{}  {}  {}: {}",
+                m.run_id,
+                datetime::format_local_datetime(&m.run_at),
+                m.line_number,
+                m.line
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// === Audit formatting ===
+
+#[derive(Tabled)]
+struct AuditRow {
+    #[tabled(rename = "When")]
+    created_at: String,
+    #[tabled(rename = "Actor")]
+    actor: String,
+    #[tabled(rename = "Change")]
+    change: String,
+    #[tabled(rename = "Entity")]
+    entity_id: String,
+    #[tabled(rename = "Command")]
+    command: String,
+}
+
+impl From<&AuditEntry> for AuditRow {
+    fn from(e: &AuditEntry) -> Self {
+        let mut command = truncate(&e.command, 40);
+        if e.undone_at.is_some() {
+            command.push_str(" (undone)");
+        }
+        Self {
+            created_at: datetime::format_local_datetime(&e.created_at),
+            actor: e.actor.clone().unwrap_or_else(|| "-".to_string()),
+            change: format!("{} {}", e.action, e.table_name),
+            entity_id: truncate(e.entity_id.as_deref().unwrap_or("-"), 30),
+            command,
+        }
+    }
+}
+
+pub fn format_audit_entries(entries: &[AuditEntry]) -> String {
+    if entries.is_empty() {
+        return "No changes found.\n".to_string();
+    }
+    let rows: Vec<AuditRow> = entries.iter().map(AuditRow::from).collect();
+    render(Table::new(rows))
+}
+
+// === Sync formatting ===
+
+#[derive(Tabled)]
+struct SyncRow {
+    #[tabled(rename = "Direction")]
+    direction: String,
+    #[tabled(rename = "Type")]
+    entity_type: String,
+    #[tabled(rename = "Local")]
+    local_id: String,
+    #[tabled(rename = "Remote")]
+    remote_key: String,
+    #[tabled(rename = "Title")]
+    title: String,
+}
+
+impl From<&SyncChange> for SyncRow {
+    fn from(c: &SyncChange) -> Self {
+        let mut direction = c.direction.as_str().to_string();
+        if c.created {
+            direction.push_str(" (new)");
+        }
+        if c.conflict {
+            direction.push_str(" (conflict)");
+        }
+        Self {
+            direction,
+            entity_type: c.entity_type.clone(),
+            local_id: c.local_id.clone().unwrap_or_else(|| "-".to_string()),
+            remote_key: c.remote_key.clone().unwrap_or_else(|| "-".to_string()),
+            title: truncate(&c.title, 40),
+        }
+    }
+}
+
+pub fn format_sync_report(report: &SyncReport) -> String {
+    let note = if report.dry_run {
+        "Dry run: nothing was changed.\n"
+    } else {
+        ""
+    };
+    if report.changes.is_empty() {
+        return format!("{}Already in sync with {}.\n", note, report.provider);
+    }
+    let rows: Vec<SyncRow> = report.changes.iter().map(SyncRow::from).collect();
+    format!("{}{}", note, render(Table::new(rows)))
+}
+
+// === Import formatting ===
+
+#[derive(Tabled)]
+struct ImportRow {
+    #[tabled(rename = "Source")]
+    source: String,
+    #[tabled(rename = "Task")]
+    task_id: String,
+    #[tabled(rename = "Status")]
+    status: String,
+    #[tabled(rename = "Labels")]
+    labels: String,
+    #[tabled(rename = "Comments")]
+    comments: usize,
+    #[tabled(rename = "Title")]
+    title: String,
+}
+
+impl From<&ImportedItem> for ImportRow {
+    fn from(item: &ImportedItem) -> Self {
+        let task_id = match (&item.task_id, item.skipped) {
+            (Some(id), true) => format!("{} (already imported)", id),
+            (Some(id), false) => id.clone(),
+            (None, _) => "-".to_string(),
+        };
+        Self {
+            source: item.source.clone(),
+            task_id,
+            status: item.status.clone(),
+            labels: if item.labels.is_empty() {
+                "-".to_string()
+            } else {
+                item.labels.join(", ")
+            },
+            comments: item.comments,
+            title: truncate(&item.title, 40),
+        }
+    }
+}
+
+pub fn format_import_report(report: &ImportReport) -> String {
+    if report.items.is_empty() {
+        return format!("Nothing to import from {}.", report.source);
+    }
+    let rows: Vec<ImportRow> = report.items.iter().map(ImportRow::from).collect();
+    let verb = if report.dry_run {
+        "Would import"
+    } else {
+        "Imported"
+    };
+    let skipped = report.items.len() - report.imported();
+    let mut output = render(Table::new(rows));
+    output.push_str(&format!(
+        "\n{} {} item(s) from {} into {}",
+        verb,
+        report.imported(),
+        report.source,
+        report.project_id
+    ));
+    if skipped > 0 {
+        output.push_str(&format!(", skipped {} already imported", skipped));
+    }
+    output
+}
+
+// === Batch plan formatting ===
+
+/// Each operation of a batch with the fields it would change: removed
+/// values in red and added ones in green
+pub fn format_batch_plan(plan: &[PlannedChange]) -> String {
+    let color = use_color();
+    let paint = |text: &str, c: &Color| {
+        if color {
+            c.colorize(text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut output = String::from("Dry run: nothing was changed.\n");
+    for change in plan {
+        let target = match &change.id {
+            Some(id) => id.clone(),
+            None => format!("(new {})", change.entity_type),
+        };
+        if let Some(error) = &change.error {
+            output.push_str(&format!(
+                "{} {} {}: {}\n",
+                paint("!", &Color::FG_RED),
+                change.op,
+                target,
+                paint(error, &Color::FG_RED)
+            ));
+            continue;
+        }
+
+        let (sign, sign_color) = match change.action {
+            ChangeAction::Create => ("+", Color::FG_GREEN),
+            ChangeAction::Update => ("~", Color::FG_YELLOW),
+            ChangeAction::Delete => ("-", Color::FG_RED),
+        };
+        let note = if change.changes.is_empty() {
+            " (no change)"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "{} {} {}{}\n",
+            paint(sign, &sign_color),
+            change.op,
+            target,
+            note
+        ));
+        for field in &change.changes {
+            output.push_str(&format!("    {}:", field.field));
+            if let Some(old) = &field.old {
+                output.push_str(&format!(" {}", paint(&format!("-{}", old), &Color::FG_RED)));
+            }
+            if let Some(new) = &field.new {
+                output.push_str(&format!(
+                    " {}",
+                    paint(&format!("+{}", new), &Color::FG_GREEN)
+                ));
+            }
+            output.push('\n');
+        }
+    }
+
+    let count = |action| {
+        plan.iter()
+            .filter(|c| c.error.is_none() && !c.changes.is_empty() && c.action == action)
+            .count()
+    };
+    let failed = plan.iter().filter(|c| c.error.is_some()).count();
+    output.push_str(&format!(
+        "\n{} to create, {} to update, {} to delete, {} failing",
+        count(ChangeAction::Create),
+        count(ChangeAction::Update),
+        count(ChangeAction::Delete),
+        failed
+    ));
+    output
+}
+
+// === Backup formatting ===
+
+/// Human-readable file size, e.g. "1.5 MB"
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+pub fn format_snapshot(snapshot: &Snapshot) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("Snapshot: {}\n", snapshot.name));
+    output.push_str(&format!("  Path:    {}\n", snapshot.path));
+    output.push_str(&format!("  Files:   {}\n", snapshot.files.join(", ")));
+    output.push_str(&format!("  Size:    {}\n", format_size(snapshot.size)));
+    output
+}
+
+#[derive(Tabled)]
+struct SnapshotRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Created")]
+    created_at: String,
+    #[tabled(rename = "Files")]
+    files: String,
+    #[tabled(rename = "Size")]
+    size: String,
+}
+
+impl From<&Snapshot> for SnapshotRow {
+    fn from(s: &Snapshot) -> Self {
+        Self {
+            name: s.name.clone(),
+            created_at: datetime::format_local_datetime(&s.created_at),
+            files: s.files.join(", "),
+            size: format_size(s.size),
+        }
+    }
+}
+
+pub fn format_snapshots(snapshots: &[Snapshot]) -> String {
+    if snapshots.is_empty() {
+        return "No snapshots found. Take one with: granary backup\n".to_string();
+    }
+    let rows: Vec<SnapshotRow> = snapshots.iter().map(SnapshotRow::from).collect();
+    render(Table::new(rows))
+}
+
+pub fn format_restore_report(report: &RestoreReport) -> String {
+    let databases = if report.global {
+        "workspace and global databases"
+    } else {
+        "workspace database"
+    };
+    format!(
+        "Restored the {} from {}\nThe previous state was saved as {}\n",
+        databases, report.restored.name, report.saved.name
+    )
+}
+
+// === Log retention formatting ===
+
+#[derive(Tabled)]
+struct PrunedLogRow {
+    #[tabled(rename = "Worker")]
+    worker_id: String,
+    #[tabled(rename = "Run")]
+    run_id: String,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Reason")]
+    reason: String,
+    #[tabled(rename = "Path")]
+    path: String,
+}
+
+impl From<&PrunedLog> for PrunedLogRow {
+    fn from(log: &PrunedLog) -> Self {
+        Self {
+            worker_id: log.worker_id.clone(),
+            run_id: log.run_id.clone().unwrap_or_else(|| "-".to_string()),
+            size: format_size(log.size),
+            reason: log.reason.as_str().to_string(),
+            path: log.path.clone(),
+        }
+    }
+}
+
+pub fn format_log_prune_report(report: &LogPruneReport) -> String {
+    if report.pruned.is_empty() {
+        return format!(
+            "Nothing to prune; logs use {}.",
+            format_size(report.total_size)
+        );
+    }
+    let rows: Vec<PrunedLogRow> = report.pruned.iter().map(PrunedLogRow::from).collect();
+    let verb = if report.dry_run {
+        "Would delete"
+    } else {
+        "Deleted"
+    };
+    format!(
+        "{}\n{} {} logs, freeing {} of {}",
+        render(Table::new(rows)),
+        verb,
+        report.pruned.len(),
+        format_size(report.freed),
+        format_size(report.total_size)
+    )
+}
+
+// === Remote formatting ===
+
+fn format_optional_datetime(value: &Option<String>) -> String {
+    value
+        .as_deref()
+        .map(datetime::format_local_datetime)
+        .unwrap_or_else(|| "never".to_string())
+}
+
+#[derive(Tabled)]
+struct RemoteRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "URL")]
+    url: String,
+    #[tabled(rename = "Pushed")]
+    pushed_at: String,
+    #[tabled(rename = "Pulled")]
+    pulled_at: String,
+}
+
+impl From<&Remote> for RemoteRow {
+    fn from(r: &Remote) -> Self {
+        Self {
+            name: r.name.clone(),
+            url: r.url.clone(),
+            pushed_at: format_optional_datetime(&r.pushed_at),
+            pulled_at: format_optional_datetime(&r.pulled_at),
+        }
+    }
+}
+
+pub fn format_remotes(remotes: &[Remote]) -> String {
+    if remotes.is_empty() {
+        return "No remotes configured. Add one with: granary remote add <url>\n".to_string();
+    }
+    let rows: Vec<RemoteRow> = remotes.iter().map(RemoteRow::from).collect();
+    render(Table::new(rows))
+}
+
+pub fn format_remote_sync_report(report: &RemoteSyncReport) -> String {
+    match report.direction {
+        SyncDirection::Push if report.batches.is_empty() => {
+            format!("Nothing to push to {}.\n", report.remote)
+        }
+        SyncDirection::Push => format!(
+            "Pushed {} change(s) to {} as batch {}\n",
+            report.changes,
+            report.remote,
+            report.batches.join(", ")
+        ),
+        SyncDirection::Pull if report.batches.is_empty() => {
+            format!("Already up to date with {}.\n", report.remote)
+        }
+        SyncDirection::Pull => {
+            let mut output = format!(
+                "Pulled {} change(s) in {} batch(es) from {}\n",
+                report.changes,
+                report.batches.len(),
+                report.remote
+            );
+            if report.skipped > 0 {
+                output.push_str(&format!(
+                    "Kept {} local row(s) that changed later\n",
+                    report.skipped
+                ));
+            }
+            output
+        }
+    }
+}
+
+#[derive(Tabled)]
+struct RemoteStatusRow {
+    #[tabled(rename = "Remote")]
+    name: String,
+    #[tabled(rename = "To push")]
+    unpushed: String,
+    #[tabled(rename = "To pull")]
+    unpulled: String,
+    #[tabled(rename = "Pushed")]
+    pushed_at: String,
+    #[tabled(rename = "Pulled")]
+    pulled_at: String,
+}
+
+impl From<&RemoteStatus> for RemoteStatusRow {
+    fn from(s: &RemoteStatus) -> Self {
+        Self {
+            name: s.name.clone(),
+            unpushed: s
+                .unpushed
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| "everything".to_string()),
+            unpulled: s
+                .unpulled
+                .map(|n| format!("{} batch(es)", n))
+                .unwrap_or_else(|| "unknown".to_string()),
+            pushed_at: format_optional_datetime(&s.pushed_at),
+            pulled_at: format_optional_datetime(&s.pulled_at),
+        }
+    }
+}
+
+pub fn format_remote_statuses(statuses: &[RemoteStatus]) -> String {
+    if statuses.is_empty() {
+        return "No remotes configured. Add one with: granary remote add <url>\n".to_string();
+    }
+    let rows: Vec<RemoteStatusRow> = statuses.iter().map(RemoteStatusRow::from).collect();
+    let mut output = render(Table::new(rows));
+    for status in statuses {
+        if let Some(error) = &status.error {
+            output.push_str(&format!("Could not read {}: {}\n", status.name, error));
+        }
+    }
+    output
+}
+
+// === Rule formatting ===
+
+#[derive(Tabled)]
+struct RuleRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "When")]
+    when: String,
+    #[tabled(rename = "Idle")]
+    idle: String,
+    #[tabled(rename = "Then")]
+    action: String,
+    #[tabled(rename = "Apply")]
+    apply: String,
+}
+
+pub fn format_rules(rules: &[Rule]) -> String {
+    if rules.is_empty() {
+        return "No rules defined. Add one with: granary config set rules.<name>.when <filters>"
+            .to_string();
+    }
+    let rows: Vec<RuleRow> = rules
+        .iter()
+        .map(|r| RuleRow {
+            name: r.name.clone(),
+            when: if r.when.is_empty() {
+                "-".to_string()
+            } else {
+                r.when.join(", ")
+            },
+            idle: r.idle.clone().unwrap_or_else(|| "-".to_string()),
+            action: r.action.as_string(),
+            apply: if r.apply { "yes" } else { "no" }.to_string(),
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+#[derive(Tabled)]
+struct RuleMatchRow {
+    #[tabled(rename = "Rule")]
+    rule: String,
+    #[tabled(rename = "Task")]
+    task_id: String,
+    #[tabled(rename = "Title")]
+    title: String,
+    #[tabled(rename = "Action")]
+    action: String,
+}
+
+pub fn format_rule_matches(matches: &[RuleMatch]) -> String {
+    if matches.is_empty() {
+        return "No tasks match the rules".to_string();
+    }
+    let rows: Vec<RuleMatchRow> = matches
+        .iter()
+        .map(|m| RuleMatchRow {
+            rule: m.rule.clone(),
+            task_id: m.task_id.clone(),
+            title: truncate(&m.title, 40),
+            action: if m.applied || m.action == RuleAction::Flag.as_string() {
+                m.action.clone()
+            } else {
+                format!("{} (event only)", m.action)
+            },
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+// === Workspace formatting ===
+
+#[derive(Tabled)]
+struct WorkspaceRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Path")]
+    root: String,
+    #[tabled(rename = "Tasks")]
+    tasks: String,
+    #[tabled(rename = "Open")]
+    open_tasks: String,
+    #[tabled(rename = "Last activity")]
+    last_activity: String,
+}
+
+impl From<&WorkspaceOverview> for WorkspaceRow {
+    fn from(w: &WorkspaceOverview) -> Self {
+        let count = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+        Self {
+            name: w.name.clone(),
+            root: w.root.clone(),
+            tasks: count(w.tasks),
+            open_tasks: count(w.open_tasks),
+            last_activity: datetime::format_local_datetime(&w.last_activity),
+        }
+    }
+}
+
+pub fn format_workspaces(workspaces: &[WorkspaceOverview]) -> String {
+    if workspaces.is_empty() {
+        return "No workspaces registered yet. Create one with: granary init\n".to_string();
+    }
+    let rows: Vec<WorkspaceRow> = workspaces.iter().map(WorkspaceRow::from).collect();
+    let mut output = render(Table::new(rows));
+    for workspace in workspaces {
+        if let Some(error) = &workspace.error {
+            output.push_str(&format!("Could not open {}: {}\n", workspace.name, error));
+        }
+    }
+    output
+}
+
+// === Plugin formatting ===
+
+#[derive(Tabled)]
+struct PluginRow {
+    #[tabled(rename = "Command")]
+    name: String,
+    #[tabled(rename = "Path")]
+    path: String,
+}
+
+pub fn format_plugins(plugins: &[Plugin]) -> String {
+    if plugins.is_empty() {
+        return "No plugins found. Put a granary-<name> executable on PATH to add one.\n"
+            .to_string();
+    }
+    let rows: Vec<PluginRow> = plugins
+        .iter()
+        .map(|p| PluginRow {
+            name: format!("granary {}", p.name),
+            path: p.path.clone(),
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+// === Event type formatting ===
+
+#[derive(Tabled)]
+struct EventTypeRow {
+    #[tabled(rename = "Event")]
+    name: String,
+    #[tabled(rename = "Description")]
+    description: String,
+}
+
+pub fn format_event_types(types: &[EventTypeInfo]) -> String {
+    let rows: Vec<EventTypeRow> = types
+        .iter()
+        .map(|t| EventTypeRow {
+            name: t.name.clone(),
+            description: t.description.clone(),
+        })
+        .collect();
+    render(Table::new(rows))
+}
+
+// === Report formatting ===
+
+/// Width of the longest burndown bar
+const BURNDOWN_WIDTH: usize = 30;
+
+pub fn format_burndown(burndown: &Burndown) -> String {
+    let mut output = format!(
+        "Burndown: {} ({})\n\n",
+        burndown.project_name, burndown.project_id
+    );
+    let scale = burndown.points.iter().map(|p| p.total).fold(0.0, f64::max);
+    if scale == 0.0 {
+        output.push_str(
+            "No estimated tasks. Set estimates with: granary task <id> update --estimate <n>\n",
+        );
+        return output;
+    }
+
+    let label_width = burndown
+        .points
+        .iter()
+        .map(|p| p.checkpoint.as_deref().map_or(3, str::len))
+        .max()
+        .unwrap_or(3);
+    for point in &burndown.points {
+        let cells = |value: f64| (value / scale * BURNDOWN_WIDTH as f64).round() as usize;
+        let remaining = cells(point.remaining);
+        let done = cells(point.total).saturating_sub(remaining);
+        output.push_str(&format!(
+            "{}  {:<width$}  {}{}{}  {} / {}\n",
+            datetime::format_local_datetime(&point.at),
+            point.checkpoint.as_deref().unwrap_or("now"),
+            "█".repeat(remaining),
+            "░".repeat(done),
+            " ".repeat(BURNDOWN_WIDTH.saturating_sub(remaining + done)),
+            point.remaining,
+            point.total,
+            width = label_width
+        ));
+    }
+    if burndown.points.len() == 1 {
+        output.push_str("\nCreate checkpoints to record the estimate over time: granary checkpoint create <name>\n");
+    }
+    output
+}
+
+/// Width of the longest velocity bar
+const VELOCITY_WIDTH: usize = 30;
+
+pub fn format_velocity(velocity: &Velocity) -> String {
+    let mut output = format!(
+        "Velocity: {}, last {} week(s)\n\n",
+        velocity.project_id.as_deref().unwrap_or("all projects"),
+        velocity.weeks.len()
+    );
+    let scale = velocity
+        .weeks
+        .iter()
+        .map(|w| w.completed)
+        .max()
+        .unwrap_or(0);
+    let estimated = velocity.weeks.iter().any(|w| w.estimate > 0.0);
+    for week in &velocity.weeks {
+        let cells = if scale == 0 {
+            0
+        } else {
+            (week.completed * VELOCITY_WIDTH).div_ceil(scale)
+        };
+        output.push_str(&format!(
+            "{}{}  {:>3} done  {}",
+            week.week_start,
+            if week.partial { "*" } else { " " },
+            week.completed,
+            "█".repeat(cells),
+        ));
+        if estimated {
+            output.push_str(&format!(
+                "{}  {} estimated",
+                " ".repeat(VELOCITY_WIDTH - cells),
+                week.estimate
+            ));
+        }
+        output.push('\n');
+    }
+    output.push_str("\n* week in progress\n");
+    if let Some(trend) = velocity.trend {
+        output.push_str(&format!("Trend: {}\n", trend.as_str()));
+    }
+    output
+}
+
+// === Board formatting ===
+
+/// Cards shown per board column before the rest are summarised
+const BOARD_CARDS: usize = 15;
+
+/// Width a board card's title wraps at
+const BOARD_CARD_WIDTH: usize = 28;
+
+pub fn format_board(board: &Board) -> String {
+    if board.is_empty() {
+        let mut output = "No tasks on the board.".to_string();
+        if board.drafts > 0 {
+            output.push_str(&format!(
+                " {} draft task(s) are waiting to be marked ready.",
+                board.drafts
+            ));
+        }
+        return output;
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(
+        board
+            .columns
+            .iter()
+            .map(|c| format!("{} ({})", c.title(), c.tasks.len())),
+    );
+    let rows = board
+        .columns
+        .iter()
+        .map(|c| c.tasks.len().min(BOARD_CARDS + 1))
+        .max()
+        .unwrap_or(0);
+    // Priority of each card, for coloring
+    let mut priorities = Vec::new();
+    for row in 0..rows {
+        let mut record = Vec::new();
+        for (col, column) in board.columns.iter().enumerate() {
+            let card = match column.tasks.get(row) {
+                Some(_) if row == BOARD_CARDS => {
+                    format!("+{} more", column.tasks.len() - BOARD_CARDS)
+                }
+                Some(task) => {
+                    priorities.push((row + 1, col, task.priority_enum()));
+                    format!("{} {}\n{}", task.priority, task.id, task.title)
+                }
+                None => String::new(),
+            };
+            record.push(card);
+        }
+        builder.push_record(record);
+    }
+
+    let mut table = builder.build();
+    table.modify(Rows::new(1..), Width::wrap(BOARD_CARD_WIDTH));
+    if use_color() {
+        for (row, col, priority) in priorities {
+            let color = match priority {
+                TaskPriority::P0 => Color::FG_RED,
+                TaskPriority::P1 => Color::FG_YELLOW,
+                TaskPriority::P2 => continue,
+                TaskPriority::P3 | TaskPriority::P4 => Color::FG_BRIGHT_BLACK,
+            };
+            table.modify(Cell::new(row, col), color);
+        }
+    }
+    let mut output = render(table);
+    if board.drafts > 0 {
+        output.push_str(&format!("\n{} draft task(s) not shown", board.drafts));
+    }
+    output
+}
+
+/// Whether to color output: only on a terminal, and not when NO_COLOR is set
+fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Each task with the tasks it depends on listed under it
+pub fn format_task_graph(graph: &TaskGraph) -> String {
+    if graph.dependencies.is_empty() {
+        return "No task dependencies. Add one with: granary task <id> deps add <other-id>\n"
+            .to_string();
+    }
+    let by_id: std::collections::HashMap<&str, &Task> =
+        graph.tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    let item = |id: &str| match by_id.get(id) {
+        Some(task) => {
+            let check = if task.status == "done" { "[x]" } else { "[ ]" };
+            format!("{} {} ({})", check, task.title, task.id)
+        }
+        None => id.to_string(),
+    };
+
+    let mut output = format!(
+        "Dependency graph: {} task(s), {} dependency link(s)\n",
+        graph.tasks.len(),
+        graph.dependencies.len()
+    );
+    for task in &graph.tasks {
+        output.push_str(&format!("  {}\n", item(&task.id)));
+        for dep in graph.dependencies.iter().filter(|d| d.task_id == task.id) {
+            output.push_str(&format!("      -> {}\n", item(&dep.depends_on_task_id)));
+        }
+    }
+    output
+}
+
+/// Width of the timeline chart
+const TIMELINE_WIDTH: usize = 40;
+
+pub fn format_timeline(timeline: &Timeline) -> String {
+    let mut output = format!(
+        "Timeline: {} ({})\n\n",
+        timeline.project_name, timeline.project_id
+    );
+    let day = |iso: &str| {
+        chrono::NaiveDate::parse_from_str(&datetime::format_local_date(iso), "%Y-%m-%d").ok()
+    };
+    let spans: Vec<_> = timeline
+        .tasks
+        .iter()
+        .filter_map(|t| Some((t, day(&t.start)?, day(&t.end)?)))
+        .collect();
+    let (Some(first), Some(last)) = (
+        spans.iter().map(|(_, start, _)| *start).min(),
+        spans.iter().map(|(_, _, end)| *end).max(),
+    ) else {
+        output.push_str(
+            "No tasks with start or due dates. Set due dates with: granary task <id> update --due <date>\n",
+        );
+        return output;
+    };
+
+    let days = (last - first).num_days() as usize + 1;
+    // Each day covers a range of columns; bars run to the end of their last day
+    let column =
+        |date: chrono::NaiveDate| (date - first).num_days() as usize * TIMELINE_WIDTH / days;
+    let column_end = |date: chrono::NaiveDate| {
+        (((date - first).num_days() as usize + 1) * TIMELINE_WIDTH / days).saturating_sub(1)
+    };
+    let id_width = spans.iter().map(|(t, ..)| t.id.len()).max().unwrap_or(0);
+    output.push_str(&format!(
+        "{:id_width$}  {:24}   {:<half$}{:>half$}\n",
+        "",
+        "",
+        first.format("%Y-%m-%d"),
+        last.format("%Y-%m-%d"),
+        half = TIMELINE_WIDTH / 2,
+    ));
+    for (task, start, end) in &spans {
+        let (from, to) = (column(*start), column_end(*end).max(column(*start)));
+        let mut bar = vec![' '; TIMELINE_WIDTH];
+        if task.milestone {
+            bar[from] = '◆';
+        } else {
+            let fill = match task.status.as_str() {
+                "done" => '█',
+                "in_progress" => '▓',
+                _ => '░',
+            };
+            bar[from..=to].fill(fill);
+        }
+        let title: String = task.title.chars().take(24).collect();
+        let mut line = format!(
+            "{:id_width$}  {:24}  |{}|  {} → {}",
+            task.id,
+            title,
+            bar.into_iter().collect::<String>(),
+            start,
+            end
+        );
+        if !task.depends_on.is_empty() {
+            line.push_str(&format!("  after {}", task.depends_on.join(", ")));
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    output.push_str("\n█ done  ▓ in progress  ░ planned  ◆ due\n");
+    if timeline.undated > 0 {
+        output.push_str(&format!(
+            "{} task(s) without start or due dates not shown\n",
+            timeline.undated
+        ));
+    }
+    output
 }
@@ -0,0 +1,364 @@
+//! YAML output.
+//!
+//! Every formatter here serializes the same structure as its counterpart in
+//! [`super::json`], so scripts can switch between `--json` and
+//! `--format yaml` without reshaping the data.
+
+use serde::Serialize;
+
+use super::json::{
+    CheckpointDiff, ContextOutput, HandoffOutput, InitiativeOutput, SummaryOutput, TaskOutput,
+    next_task_output,
+};
+use crate::models::initiative::{Initiative, InitiativeSummary, MilestoneProgress};
+use crate::models::run::Run;
+use crate::models::worker::{FilterTest, Worker};
+use crate::models::*;
+
+/// Serialize any value as YAML
+pub fn to_yaml<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_yaml::to_string(value).unwrap_or_else(|_| "Error formatting YAML".to_string())
+}
+
+pub fn format_project(project: &Project) -> String {
+    to_yaml(project)
+}
+
+pub fn format_projects(projects: &[Project]) -> String {
+    to_yaml(projects)
+}
+
+pub fn format_task(task: &Task) -> String {
+    to_yaml(&TaskOutput::from_task(task.clone()))
+}
+
+pub fn format_task_with_deps(task: &Task, blocked_by: &[String]) -> String {
+    to_yaml(&TaskOutput::new(task.clone(), blocked_by.to_vec()))
+}
+
+pub fn format_task_with_links(
+    task: &Task,
+    blocked_by: &[String],
+    links: &[TaskVcsLink],
+    attachments: &[Attachment],
+    related: &[RelatedTask],
+) -> String {
+    to_yaml(
+        &TaskOutput::new(task.clone(), blocked_by.to_vec())
+            .with_vcs_links(links.to_vec())
+            .with_attachments(attachments.to_vec())
+            .with_related(related.to_vec()),
+    )
+}
+
+pub fn format_vcs_links(links: &[TaskVcsLink]) -> String {
+    to_yaml(links)
+}
+
+pub fn format_attachments(attachments: &[Attachment]) -> String {
+    to_yaml(attachments)
+}
+
+pub fn format_related_tasks(related: &[RelatedTask]) -> String {
+    to_yaml(related)
+}
+
+pub fn format_tasks(tasks: &[Task]) -> String {
+    let outputs: Vec<TaskOutput> = tasks
+        .iter()
+        .map(|t| TaskOutput::from_task(t.clone()))
+        .collect();
+    to_yaml(&outputs)
+}
+
+pub fn format_tasks_with_deps(tasks_with_deps: &[(Task, Vec<String>)]) -> String {
+    let outputs: Vec<TaskOutput> = tasks_with_deps
+        .iter()
+        .map(|(t, deps)| TaskOutput::new(t.clone(), deps.clone()))
+        .collect();
+    to_yaml(&outputs)
+}
+
+pub fn format_workspace_tasks(tasks: &[WorkspaceTask]) -> String {
+    to_yaml(tasks)
+}
+
+pub fn format_comment(comment: &Comment) -> String {
+    to_yaml(comment)
+}
+
+pub fn format_comments(comments: &[Comment]) -> String {
+    to_yaml(comments)
+}
+
+pub fn format_comment_threads(threads: &[CommentThread]) -> String {
+    to_yaml(threads)
+}
+
+pub fn format_session(session: &Session) -> String {
+    to_yaml(session)
+}
+
+pub fn format_sessions(sessions: &[Session]) -> String {
+    to_yaml(sessions)
+}
+
+pub fn format_checkpoint(checkpoint: &Checkpoint) -> String {
+    to_yaml(checkpoint)
+}
+
+pub fn format_checkpoints(checkpoints: &[Checkpoint]) -> String {
+    to_yaml(checkpoints)
+}
+
+pub fn format_artifact(artifact: &Artifact) -> String {
+    to_yaml(artifact)
+}
+
+pub fn format_artifacts(artifacts: &[Artifact]) -> String {
+    to_yaml(artifacts)
+}
+
+pub fn format_next_task(task: Option<&Task>, reason: Option<&str>) -> String {
+    to_yaml(&next_task_output(task, reason))
+}
+
+pub fn format_recommendations(recommendations: &[TaskRecommendation]) -> String {
+    to_yaml(recommendations)
+}
+
+pub fn format_summary(summary: &SummaryOutput) -> String {
+    to_yaml(summary)
+}
+
+pub fn format_context(context: &ContextOutput) -> String {
+    to_yaml(context)
+}
+
+pub fn format_handoff(handoff: &HandoffOutput) -> String {
+    to_yaml(handoff)
+}
+
+pub fn format_checkpoint_diff(diff: &CheckpointDiff) -> String {
+    to_yaml(diff)
+}
+
+pub fn format_search_results(results: &[SearchResult]) -> String {
+    to_yaml(results)
+}
+
+pub fn format_workspace_search_results(results: &[WorkspaceSearchResult]) -> String {
+    to_yaml(results)
+}
+
+pub fn format_initiative(initiative: &Initiative) -> String {
+    to_yaml(initiative)
+}
+
+pub fn format_initiatives(initiatives: &[Initiative]) -> String {
+    to_yaml(initiatives)
+}
+
+pub fn format_initiative_with_milestones(
+    initiative: &Initiative,
+    milestones: &[MilestoneProgress],
+) -> String {
+    to_yaml(&InitiativeOutput {
+        initiative: initiative.clone(),
+        milestones: milestones.to_vec(),
+    })
+}
+
+pub fn format_milestone(milestone: &MilestoneProgress) -> String {
+    to_yaml(milestone)
+}
+
+pub fn format_milestones(milestones: &[MilestoneProgress]) -> String {
+    to_yaml(milestones)
+}
+
+pub fn format_initiative_summary(summary: &InitiativeSummary) -> String {
+    to_yaml(summary)
+}
+
+pub fn format_worker(worker: &Worker) -> String {
+    to_yaml(worker)
+}
+
+pub fn format_workers(workers: &[Worker]) -> String {
+    to_yaml(workers)
+}
+
+pub fn format_filter_test(test: &FilterTest) -> String {
+    to_yaml(test)
+}
+
+pub fn format_run(run: &Run) -> String {
+    to_yaml(run)
+}
+
+pub fn format_log_prune_report(report: &LogPruneReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_runs(runs: &[Run]) -> String {
+    to_yaml(runs)
+}
+
+pub fn format_run_queue(queue: &[QueuedRun]) -> String {
+    to_yaml(queue)
+}
+
+pub fn format_import_report(report: &ImportReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_batch_plan(plan: &[PlannedChange]) -> String {
+    to_yaml(plan)
+}
+
+pub fn format_run_log_matches(matches: &[RunLogMatch]) -> String {
+    to_yaml(matches)
+}
+
+pub fn format_audit_entries(entries: &[AuditEntry]) -> String {
+    to_yaml(entries)
+}
+
+pub fn format_sync_report(report: &SyncReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_snapshot(snapshot: &Snapshot) -> String {
+    to_yaml(snapshot)
+}
+
+pub fn format_snapshots(snapshots: &[Snapshot]) -> String {
+    to_yaml(snapshots)
+}
+
+pub fn format_restore_report(report: &RestoreReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_remotes(remotes: &[Remote]) -> String {
+    to_yaml(remotes)
+}
+
+pub fn format_remote_sync_report(report: &RemoteSyncReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_remote_statuses(statuses: &[RemoteStatus]) -> String {
+    to_yaml(statuses)
+}
+
+pub fn format_workspaces(workspaces: &[WorkspaceOverview]) -> String {
+    to_yaml(workspaces)
+}
+
+pub fn format_plugins(plugins: &[Plugin]) -> String {
+    to_yaml(plugins)
+}
+
+pub fn format_event_types(types: &[EventTypeInfo]) -> String {
+    to_yaml(types)
+}
+
+pub fn format_burndown(burndown: &Burndown) -> String {
+    to_yaml(burndown)
+}
+
+pub fn format_rules(rules: &[Rule]) -> String {
+    to_yaml(rules)
+}
+
+pub fn format_rule_matches(matches: &[RuleMatch]) -> String {
+    to_yaml(matches)
+}
+
+pub fn format_board(board: &Board) -> String {
+    to_yaml(board)
+}
+
+pub fn format_timeline(timeline: &Timeline) -> String {
+    to_yaml(timeline)
+}
+
+pub fn format_velocity(velocity: &Velocity) -> String {
+    to_yaml(velocity)
+}
+
+pub fn format_session_review(review: &SessionReview) -> String {
+    to_yaml(review)
+}
+
+pub fn format_cost_report(report: &CostReport) -> String {
+    to_yaml(report)
+}
+
+pub fn format_project_health(health: &[ProjectHealth]) -> String {
+    to_yaml(health)
+}
+
+pub fn format_task_graph(graph: &TaskGraph) -> String {
+    to_yaml(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_task() -> Task {
+        Task {
+            id: "test-proj-task-1".to_string(),
+            project_id: "test-proj".to_string(),
+            task_number: 1,
+            parent_task_id: None,
+            title: "Test Task".to_string(),
+            description: None,
+            status: "todo".to_string(),
+            priority: "P1".to_string(),
+            owner: None,
+            author: None,
+            tags: None,
+            blocked_reason: None,
+            started_at: None,
+            completed_at: None,
+            due_at: None,
+            estimate: None,
+            claim_owner: None,
+            claim_claimed_at: None,
+            claim_lease_expires_at: None,
+            claim_session_id: None,
+            pinned: 0,
+            focus_weight: 0,
+            archived_at: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            updated_at: "2024-01-01T00:00:00Z".to_string(),
+            version: 1,
+            labels: vec!["urgent".to_string()],
+            fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_yaml_matches_json_structure() {
+        let task = create_test_task();
+        let deps = vec!["test-proj-task-0".to_string()];
+
+        let from_yaml: serde_json::Value =
+            serde_yaml::from_str(&format_task_with_deps(&task, &deps)).unwrap();
+        let from_json: serde_json::Value =
+            serde_json::from_str(&super::super::json::format_task_with_deps(&task, deps)).unwrap();
+        assert_eq!(from_yaml, from_json);
+        assert_eq!(from_yaml["blocked_by"][0], "test-proj-task-0");
+    }
+
+    #[test]
+    fn test_format_next_task_without_task() {
+        let value: serde_json::Value = serde_yaml::from_str(&format_next_task(None, None)).unwrap();
+        assert!(value["task"].is_null());
+        assert_eq!(value["reason"], "No actionable tasks found");
+    }
+}
@@ -0,0 +1,224 @@
+//! Queries across every workspace on this machine.
+//!
+//! [`across_workspaces`] opens each workspace in the registry (see
+//! [`crate::services::workspace_registry`]) in name order and runs a query
+//! against its pool. A workspace that cannot be opened or queried does not
+//! fail the whole query: it is skipped and reported as a warning, so one
+//! deleted or broken workspace never hides the rest.
+
+use std::cmp::Ordering;
+use std::future::Future;
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::*;
+use crate::services::{Workspace, find_tasks};
+
+/// Statuses `granary tasks --global` shows unless others are asked for
+const OPEN_STATUSES: &[TaskStatus] = &[
+    TaskStatus::Draft,
+    TaskStatus::Todo,
+    TaskStatus::InProgress,
+    TaskStatus::Blocked,
+];
+
+/// Run `query` with the name and pool of every registered workspace and
+/// concatenate the rows it returns. Returns the rows and a warning for each
+/// workspace that was skipped.
+pub async fn across_workspaces<T, F, Fut>(
+    global: &SqlitePool,
+    mut query: F,
+) -> Result<(Vec<T>, Vec<String>)>
+where
+    F: FnMut(String, SqlitePool) -> Fut,
+    Fut: Future<Output = Result<Vec<T>>>,
+{
+    let mut rows = Vec::new();
+    let mut warnings = Vec::new();
+    for registered in db::workspaces::list(global).await? {
+        let pool = match Workspace::open(&registered.root) {
+            Ok(workspace) => workspace.pool().await,
+            Err(e) => Err(e),
+        };
+        let found = match pool {
+            Ok(pool) => query(registered.name.clone(), pool).await,
+            Err(e) => Err(e),
+        };
+        match found {
+            Ok(found) => rows.extend(found),
+            Err(e) => warnings.push(format!("Skipped workspace {}: {}", registered.name, e)),
+        }
+    }
+    Ok((rows, warnings))
+}
+
+/// Tasks matching `query` in every registered workspace, open ones only
+/// unless the query names statuses. Sorted by priority, then due date
+/// (undated last), then creation time; `page` applies to the combined list.
+pub async fn global_tasks(
+    global: &SqlitePool,
+    query: &SearchQuery,
+    page: Page,
+) -> Result<(Vec<WorkspaceTask>, Vec<String>)> {
+    let mut query = query.clone();
+    if query.statuses.is_empty() {
+        query.statuses = OPEN_STATUSES
+            .iter()
+            .map(|s| s.as_str().to_string())
+            .collect();
+    }
+    let query = &query;
+
+    let (mut tasks, warnings) = across_workspaces(global, |workspace, pool| async move {
+        let tasks = find_tasks(&pool, query).await?;
+        Ok(tasks
+            .into_iter()
+            .map(|task| WorkspaceTask {
+                workspace: workspace.clone(),
+                task,
+            })
+            .collect())
+    })
+    .await?;
+    tasks.sort_by(|a, b| compare_plate_order(&a.task, &b.task));
+    Ok((page.apply(tasks), warnings))
+}
+
+/// Most urgent first: priority, then due date with undated tasks last,
+/// then the oldest
+fn compare_plate_order(a: &Task, b: &Task) -> Ordering {
+    a.priority
+        .cmp(&b.priority)
+        .then_with(|| match (&a.due_at, &b.due_at) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        .then_with(|| a.created_at.cmp(&b.created_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{complete_task, create_project, create_task, register_workspace};
+    use tempfile::TempDir;
+
+    async fn add_workspace(
+        global: &SqlitePool,
+        dir: &TempDir,
+        name: &str,
+        tasks: &[(&str, TaskPriority, Option<&str>)],
+    ) -> (SqlitePool, Vec<Task>) {
+        let root = dir.path().join(name);
+        std::fs::create_dir_all(&root).unwrap();
+        let workspace = Workspace::create(&root).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        register_workspace(global, &workspace).await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: name.to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut created = Vec::new();
+        for (title, priority, due) in tasks {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    priority: priority.clone(),
+                    due_at: due.map(str::to_string),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            created.push(task);
+        }
+        (pool, created)
+    }
+
+    #[tokio::test]
+    async fn test_global_tasks_are_sorted_by_urgency() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = db::connection::create_pool(&temp_dir.path().join("workers.db"))
+            .await
+            .unwrap();
+        db::connection::run_migrations(&global).await.unwrap();
+
+        add_workspace(
+            &global,
+            &temp_dir,
+            "api",
+            &[
+                ("Api later", TaskPriority::P1, Some("2026-06-01T00:00:00Z")),
+                ("Api undated", TaskPriority::P1, None),
+            ],
+        )
+        .await;
+        let (web, web_tasks) = add_workspace(
+            &global,
+            &temp_dir,
+            "web",
+            &[
+                ("Web sooner", TaskPriority::P1, Some("2026-05-01T00:00:00Z")),
+                ("Web urgent", TaskPriority::P0, None),
+                ("Web done", TaskPriority::P0, None),
+            ],
+        )
+        .await;
+        complete_task(&web, &web_tasks[2].id, None, None)
+            .await
+            .unwrap();
+
+        let (tasks, warnings) = global_tasks(&global, &SearchQuery::default(), Page::default())
+            .await
+            .unwrap();
+        assert!(warnings.is_empty());
+        let order: Vec<(&str, &str)> = tasks
+            .iter()
+            .map(|t| (t.workspace.as_str(), t.task.title.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("web", "Web urgent"),
+                ("web", "Web sooner"),
+                ("api", "Api later"),
+                ("api", "Api undated"),
+            ]
+        );
+
+        // Filters and paging apply across workspaces
+        let query = SearchQuery {
+            statuses: vec!["done".to_string()],
+            ..Default::default()
+        };
+        let (done, _) = global_tasks(&global, &query, Page::default())
+            .await
+            .unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].task.title, "Web done");
+        let (page, _) = global_tasks(&global, &SearchQuery::default(), Page::new(Some(2), 1))
+            .await
+            .unwrap();
+        assert_eq!(page[0].task.title, "Web sooner");
+        assert_eq!(page.len(), 2);
+
+        // A deleted workspace is skipped with a warning
+        std::fs::remove_dir_all(temp_dir.path().join("api")).unwrap();
+        let (tasks, warnings) = global_tasks(&global, &SearchQuery::default(), Page::default())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("api"));
+    }
+}
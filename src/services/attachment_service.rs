@@ -0,0 +1,204 @@
+//! File attachments on tasks.
+//!
+//! `granary tasks attach` copies a file into `.granary/attachments/<task-id>/`,
+//! or symlinks it there with `--symlink`, and records it in the `attachments`
+//! table. Attaching a file with the same name again replaces the stored copy.
+//! Paths are recorded relative to the workspace root so a moved workspace
+//! keeps its attachments.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::{Workspace, get_task};
+
+/// Directory under `.granary/` holding attached files
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// Directory holding a task's attached files
+pub fn task_dir(workspace: &Workspace, task_id: &str) -> PathBuf {
+    workspace.granary_dir.join(ATTACHMENTS_DIR).join(task_id)
+}
+
+/// Attach a file to a task by copying or symlinking it into the workspace
+pub async fn attach(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    task_id: &str,
+    source: &Path,
+    mode: AttachmentMode,
+) -> Result<Attachment> {
+    let task = get_task(pool, task_id).await?;
+
+    let source = source.canonicalize().map_err(|e| {
+        GranaryError::InvalidArgument(format!("Cannot attach {}: {}", source.display(), e))
+    })?;
+    let metadata = std::fs::metadata(&source)?;
+    if !metadata.is_file() {
+        return Err(GranaryError::InvalidArgument(format!(
+            "{} is not a file",
+            source.display()
+        )));
+    }
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| {
+            GranaryError::InvalidArgument(format!("{} has no file name", source.display()))
+        })?;
+
+    let dir = task_dir(workspace, &task.id);
+    std::fs::create_dir_all(&dir)?;
+    let dest = dir.join(&name);
+    // Replacing the stored copy would delete the file being attached
+    let stored_copy = dest
+        .symlink_metadata()
+        .is_ok_and(|m| !m.file_type().is_symlink());
+    if stored_copy && dest.canonicalize().is_ok_and(|existing| existing == source) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "{} is already attached to {}",
+            name, task.id
+        )));
+    }
+    if dest.symlink_metadata().is_ok() {
+        std::fs::remove_file(&dest)?;
+    }
+    match mode {
+        AttachmentMode::Copy => {
+            std::fs::copy(&source, &dest)?;
+        }
+        AttachmentMode::Symlink => symlink(&source, &dest)?,
+    }
+
+    let path = dest
+        .strip_prefix(&workspace.root)
+        .unwrap_or(&dest)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let attachment = db::attachments::upsert(
+        pool,
+        &task.id,
+        &name,
+        &path,
+        &source.display().to_string(),
+        mode,
+        metadata.len() as i64,
+    )
+    .await?;
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskAttached,
+            entity_type: EntityType::Task,
+            entity_id: task.id.clone(),
+            actor: None,
+            session_id: None,
+            payload: serde_json::json!({
+                "name": attachment.name,
+                "path": attachment.path,
+                "mode": attachment.mode,
+            }),
+        },
+    )
+    .await?;
+
+    Ok(attachment)
+}
+
+/// List the files attached to a task
+pub async fn list(pool: &SqlitePool, task_id: &str) -> Result<Vec<Attachment>> {
+    let _task = get_task(pool, task_id).await?;
+    db::attachments::list_by_task(pool, task_id).await
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, dest)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(source, dest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{create_project, create_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_attach_copies_and_replaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Files".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Review spec".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let source = temp_dir.path().join("spec.md");
+        std::fs::write(&source, "v1").unwrap();
+        let attachment = attach(&pool, &workspace, &task.id, &source, AttachmentMode::Copy)
+            .await
+            .unwrap();
+        assert_eq!(attachment.name, "spec.md");
+        assert_eq!(
+            attachment.path,
+            format!(".granary/attachments/{}/spec.md", task.id)
+        );
+        let stored = attachment.absolute_path(&workspace.root);
+        assert_eq!(std::fs::read_to_string(&stored).unwrap(), "v1");
+
+        // Attaching the same name again replaces the copy and the row
+        std::fs::write(&source, "version 2").unwrap();
+        let attachment = attach(&pool, &workspace, &task.id, &source, AttachmentMode::Copy)
+            .await
+            .unwrap();
+        assert_eq!(attachment.size_bytes, 9);
+        assert_eq!(std::fs::read_to_string(&stored).unwrap(), "version 2");
+        assert_eq!(list(&pool, &task.id).await.unwrap().len(), 1);
+
+        let missing = attach(
+            &pool,
+            &workspace,
+            &task.id,
+            &temp_dir.path().join("missing.md"),
+            AttachmentMode::Copy,
+        )
+        .await;
+        assert!(matches!(missing, Err(GranaryError::InvalidArgument(_))));
+        assert!(matches!(
+            attach(
+                &pool,
+                &workspace,
+                &task.id,
+                temp_dir.path(),
+                AttachmentMode::Copy
+            )
+            .await,
+            Err(GranaryError::InvalidArgument(_))
+        ));
+    }
+}
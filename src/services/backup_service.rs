@@ -0,0 +1,460 @@
+//! Database snapshots for `granary backup` and `granary restore`.
+//!
+//! A snapshot is a directory holding a copy of the workspace database and,
+//! when there is one, the global workers database. Copies are taken with
+//! `VACUUM INTO`, SQLite's online copy, so they are consistent even while
+//! other processes are writing. Snapshots under `.granary/backups/` are
+//! rotated, keeping the newest `backup.keep` (7 by default); the daemon takes
+//! one a day in workspaces that set `backup.daily` to true.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sqlx::SqlitePool;
+
+use crate::daemon::auto_start::daemon_pid;
+use crate::db;
+use crate::db::encryption;
+use crate::error::{GranaryError, Result};
+use crate::models::{RestoreReport, Snapshot};
+use crate::services::global_config as global_config_service;
+use crate::services::{DB_FILE, Workspace};
+
+/// Directory under `.granary/` that holds rotated snapshots
+pub const BACKUP_DIR: &str = "backups";
+
+/// Config key for how many rotated snapshots to keep
+pub const KEEP_KEY: &str = "backup.keep";
+
+/// Config key that lets the daemon take a snapshot every day
+pub const DAILY_KEY: &str = "backup.daily";
+
+/// Snapshots kept when `backup.keep` is not set
+pub const DEFAULT_KEEP: usize = 7;
+
+/// File name of the global workers database inside a snapshot
+const GLOBAL_DB_FILE: &str = "workers.db";
+
+/// Validate a `backup.*` config value before it is stored
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    match key {
+        KEEP_KEY => match value.parse::<usize>() {
+            Ok(keep) if keep > 0 => Ok(()),
+            _ => Err(GranaryError::InvalidArgument(format!(
+                "Invalid {} '{}'. Expected a positive number of snapshots",
+                KEEP_KEY, value
+            ))),
+        },
+        DAILY_KEY => parse_bool(value).map(|_| ()).ok_or_else(|| {
+            GranaryError::InvalidArgument(format!(
+                "Invalid {} '{}'. Expected true or false",
+                DAILY_KEY, value
+            ))
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the workspace asks the daemon for daily snapshots
+pub async fn daily_enabled(pool: &SqlitePool) -> Result<bool> {
+    let value = db::config::get(pool, DAILY_KEY).await?;
+    Ok(value.as_deref().and_then(parse_bool).unwrap_or(false))
+}
+
+/// Directory holding a workspace's rotated snapshots
+pub fn backups_dir(workspace: &Workspace) -> PathBuf {
+    workspace.granary_dir.join(BACKUP_DIR)
+}
+
+/// Snapshot the workspace database, and the global workers database when
+/// it exists. Without `to` the snapshot goes under `.granary/backups/` and
+/// old snapshots are rotated out.
+pub async fn backup(
+    workspace: &Workspace,
+    pool: &SqlitePool,
+    to: Option<&Path>,
+) -> Result<Snapshot> {
+    let global_pool = if global_config_service::global_db_path()?.exists() {
+        Some(global_config_service::global_pool().await?)
+    } else {
+        None
+    };
+    take_snapshot(workspace, pool, global_pool.as_ref(), to).await
+}
+
+pub(crate) async fn take_snapshot(
+    workspace: &Workspace,
+    pool: &SqlitePool,
+    global_pool: Option<&SqlitePool>,
+    to: Option<&Path>,
+) -> Result<Snapshot> {
+    let dir = match to {
+        Some(dir) => dir.to_path_buf(),
+        None => next_snapshot_dir(&backups_dir(workspace)),
+    };
+    if dir.join(DB_FILE).exists() {
+        return Err(GranaryError::InvalidArgument(format!(
+            "{} already holds a backup",
+            dir.display()
+        )));
+    }
+    std::fs::create_dir_all(&dir)?;
+
+    vacuum_into(pool, &dir.join(DB_FILE)).await?;
+    if let Some(global_pool) = global_pool {
+        vacuum_into(global_pool, &dir.join(GLOBAL_DB_FILE)).await?;
+    }
+
+    if to.is_none() {
+        let keep = match db::config::get(pool, KEEP_KEY).await? {
+            Some(value) => value.parse().unwrap_or(DEFAULT_KEEP),
+            None => DEFAULT_KEEP,
+        };
+        prune(workspace, keep)?;
+    }
+    read_snapshot(&dir)
+}
+
+async fn vacuum_into(pool: &SqlitePool, path: &Path) -> Result<()> {
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A directory named after the current time, suffixed if one already exists
+fn next_snapshot_dir(backups_dir: &Path) -> PathBuf {
+    let name = chrono::Utc::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut dir = backups_dir.join(&name);
+    let mut n = 2;
+    while dir.exists() {
+        dir = backups_dir.join(format!("{}-{}", name, n));
+        n += 1;
+    }
+    dir
+}
+
+/// Remove the oldest rotated snapshots beyond `keep`
+fn prune(workspace: &Workspace, keep: usize) -> Result<()> {
+    let snapshots = list_snapshots(workspace)?;
+    for snapshot in snapshots.iter().skip(keep) {
+        std::fs::remove_dir_all(&snapshot.path)?;
+    }
+    Ok(())
+}
+
+/// Rotated snapshots, newest first
+pub fn list_snapshots(workspace: &Workspace) -> Result<Vec<Snapshot>> {
+    let dir = backups_dir(workspace);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.join(DB_FILE).is_file() {
+            snapshots.push(read_snapshot(&path)?);
+        }
+    }
+    snapshots.sort_by(|a, b| sort_key(&b.name).cmp(&sort_key(&a.name)));
+    Ok(snapshots)
+}
+
+/// Order snapshot names by their timestamp, then by the suffix added to
+/// snapshots taken in the same second
+fn sort_key(name: &str) -> (&str, u32) {
+    match name.get(15..).and_then(|rest| rest.strip_prefix('-')) {
+        Some(suffix) => (&name[..15], suffix.parse().unwrap_or(0)),
+        None => (name, 1),
+    }
+}
+
+fn read_snapshot(dir: &Path) -> Result<Snapshot> {
+    let mut files = Vec::new();
+    let mut size = 0;
+    for file in [DB_FILE, GLOBAL_DB_FILE] {
+        if let Ok(metadata) = std::fs::metadata(dir.join(file)) {
+            files.push(file.to_string());
+            size += metadata.len();
+        }
+    }
+    let modified = std::fs::metadata(dir.join(DB_FILE))?
+        .modified()
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    Ok(Snapshot {
+        name: dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        path: dir.display().to_string(),
+        created_at: chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339(),
+        files,
+        size,
+    })
+}
+
+/// Find a snapshot by name under `.granary/backups/`, or by path
+pub fn find_snapshot(workspace: &Workspace, snapshot: &str) -> Result<Snapshot> {
+    let named = backups_dir(workspace).join(snapshot);
+    let dir = if named.join(DB_FILE).is_file() {
+        named
+    } else {
+        PathBuf::from(snapshot)
+    };
+    if !dir.join(DB_FILE).is_file() {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Snapshot not found: {}. Run `granary backup --list` to see snapshots",
+            snapshot
+        )));
+    }
+    read_snapshot(&dir)
+}
+
+/// Replace the workspace database with a snapshot, and the global workers
+/// database too with `global`. The current state is snapshotted first so
+/// the restore can itself be undone by restoring that snapshot.
+pub async fn restore(workspace: &Workspace, snapshot: &str, global: bool) -> Result<RestoreReport> {
+    if daemon_pid().is_some() {
+        return Err(GranaryError::Conflict(
+            "The daemon has the databases open. Stop it first with `granary daemon stop`"
+                .to_string(),
+        ));
+    }
+    let restored = find_snapshot(workspace, snapshot)?;
+    let source = Path::new(&restored.path);
+    let global_db_path = global_config_service::global_db_path()?;
+    if global && !source.join(GLOBAL_DB_FILE).is_file() {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Snapshot {} has no copy of the global workers database",
+            restored.name
+        )));
+    }
+
+    // Stage the copies before snapshotting the current state, which may
+    // rotate out the snapshot being restored
+    let staged = stage(
+        &source.join(DB_FILE),
+        &workspace.db_path,
+        encryption::key_for(&workspace.db_path)?.as_deref(),
+    )
+    .await?;
+    let staged_global = if global {
+        let key = encryption::key_for(&global_db_path)?;
+        match stage(
+            &source.join(GLOBAL_DB_FILE),
+            &global_db_path,
+            key.as_deref(),
+        )
+        .await
+        {
+            Ok(staged_global) => Some(staged_global),
+            Err(e) => {
+                let _ = std::fs::remove_file(&staged);
+                return Err(e);
+            }
+        }
+    } else {
+        None
+    };
+
+    let pool = workspace.pool().await?;
+    let saved = backup(workspace, &pool, None).await?;
+    pool.close().await;
+
+    replace(&staged, &workspace.db_path)?;
+    if let Some(staged_global) = staged_global {
+        global_config_service::global_pool().await?.close().await;
+        replace(&staged_global, &global_db_path)?;
+    }
+
+    Ok(RestoreReport {
+        restored,
+        saved,
+        global,
+    })
+}
+
+/// Copy a snapshot file next to the database it will replace, and check
+/// that the copy opens with `key`, the key of that database, and passes
+/// `PRAGMA quick_check`
+async fn stage(source: &Path, target: &Path, key: Option<&str>) -> Result<PathBuf> {
+    let staged = target.with_extension("db.restore");
+    std::fs::copy(source, &staged)?;
+    let problem = match db::connection::quick_check(&staged, key).await {
+        Ok(problems) if problems.is_empty() => return Ok(staged),
+        Ok(problems) => problems.join("; "),
+        Err(e) => e.to_string(),
+    };
+    let _ = std::fs::remove_file(&staged);
+    Err(GranaryError::InvalidArgument(format!(
+        "{} is not a database that opens with the workspace's key: {}",
+        source.display(),
+        problem
+    )))
+}
+
+/// Move a staged copy into place, dropping the old write-ahead log so it
+/// is not replayed over the restored data. A connection that is still
+/// closing may remove the log first.
+fn replace(staged: &Path, target: &Path) -> Result<()> {
+    for suffix in ["-wal", "-shm"] {
+        let mut path = target.as_os_str().to_os_string();
+        path.push(suffix);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    std::fs::rename(staged, target)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::create_pool;
+    use crate::models::CreateProject;
+    use crate::services;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Workspace, SqlitePool) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        (temp_dir, workspace, pool)
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_and_rotate() {
+        let (_temp_dir, workspace, pool) = setup().await;
+        services::create_project(
+            &pool,
+            CreateProject {
+                name: "Backed up".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        db::config::set(&pool, KEEP_KEY, "2").await.unwrap();
+
+        let first = take_snapshot(&workspace, &pool, None, None).await.unwrap();
+        assert_eq!(first.files, vec![DB_FILE.to_string()]);
+        assert!(first.size > 0);
+        take_snapshot(&workspace, &pool, None, None).await.unwrap();
+        let third = take_snapshot(&workspace, &pool, None, None).await.unwrap();
+
+        let snapshots = list_snapshots(&workspace).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].name, third.name);
+        assert!(!Path::new(&first.path).exists());
+
+        // The copy is a complete database
+        let copy = create_pool(&Path::new(&third.path).join(DB_FILE))
+            .await
+            .unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects")
+            .fetch_one(&copy)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // Explicit destinations are not rotated or overwritten
+        let out = TempDir::new().unwrap();
+        let target = out.path().join("manual");
+        take_snapshot(&workspace, &pool, None, Some(&target))
+            .await
+            .unwrap();
+        assert!(target.join(DB_FILE).is_file());
+        assert_eq!(list_snapshots(&workspace).unwrap().len(), 2);
+        assert!(
+            take_snapshot(&workspace, &pool, None, Some(&target))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_and_replace_snapshot() {
+        let (_temp_dir, workspace, pool) = setup().await;
+        let snapshot = take_snapshot(&workspace, &pool, None, None).await.unwrap();
+        services::create_project(
+            &pool,
+            CreateProject {
+                name: "Added later".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        pool.close().await;
+
+        assert!(find_snapshot(&workspace, "missing").is_err());
+        let found = find_snapshot(&workspace, &snapshot.name).unwrap();
+        assert_eq!(found.path, snapshot.path);
+        assert_eq!(
+            find_snapshot(&workspace, &snapshot.path).unwrap().name,
+            snapshot.name
+        );
+
+        let staged = stage(
+            &Path::new(&found.path).join(DB_FILE),
+            &workspace.db_path,
+            None,
+        )
+        .await
+        .unwrap();
+        replace(&staged, &workspace.db_path).unwrap();
+
+        let pool = workspace.pool().await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM projects")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_stage_refuses_files_that_do_not_open() {
+        let (temp_dir, workspace, pool) = setup().await;
+        pool.close().await;
+        let restore_path = workspace.db_path.with_extension("db.restore");
+
+        // Over an encrypted workspace any file without the SQLite header
+        // looked like an encrypted snapshot; it has to open with the key
+        let junk = temp_dir.path().join("notes.txt");
+        std::fs::write(&junk, "Not a database, only some notes ".repeat(8)).unwrap();
+        let key = "ab".repeat(32);
+        for key in [Some(key.as_str()), None] {
+            let result = stage(&junk, &workspace.db_path, key).await;
+            assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+            assert!(!restore_path.exists());
+        }
+
+        let pool = workspace.pool().await.unwrap();
+        let snapshot = take_snapshot(&workspace, &pool, None, None).await.unwrap();
+        pool.close().await;
+        let source = Path::new(&snapshot.path).join(DB_FILE);
+        let staged = stage(&source, &workspace.db_path, None).await.unwrap();
+        assert_eq!(staged, restore_path);
+    }
+
+    #[test]
+    fn test_validate_backup_config() {
+        assert!(validate_config_value(KEEP_KEY, "3").is_ok());
+        assert!(validate_config_value(KEEP_KEY, "0").is_err());
+        assert!(validate_config_value(KEEP_KEY, "many").is_err());
+        assert!(validate_config_value(DAILY_KEY, "true").is_ok());
+        assert!(validate_config_value(DAILY_KEY, "sometimes").is_err());
+        assert!(validate_config_value("backup.other", "x").is_ok());
+    }
+}
@@ -0,0 +1,217 @@
+//! Reading batch files for `granary apply` and `granary batch`.
+//!
+//! A batch is JSON (`{"ops": [...]}`), JSON lines (one operation per line)
+//! or YAML. A YAML file may hold several documents, each a batch, a list of
+//! operations or a single operation, and may use anchors, aliases and `<<`
+//! merge keys to share fields between operations.
+
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use crate::error::{GranaryError, Result};
+use crate::services::batch_service::{BatchOp, BatchRequest};
+
+/// Format of a batch file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchFormat {
+    Json,
+    Jsonl,
+    Yaml,
+}
+
+impl BatchFormat {
+    /// Format implied by a file's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "json" => Some(Self::Json),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of input with no extension to go by: JSON when it
+    /// starts with `{` or `[` and parses as one value, JSON lines when it
+    /// starts like JSON but does not, and YAML otherwise.
+    pub fn detect(input: &str) -> Self {
+        let trimmed = input.trim_start();
+        if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+            return Self::Yaml;
+        }
+        if serde_json::from_str::<serde_json::Value>(input).is_ok() {
+            Self::Json
+        } else {
+            Self::Jsonl
+        }
+    }
+}
+
+impl std::str::FromStr for BatchFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(format!(
+                "Invalid batch format: {} (expected json, jsonl or yaml)",
+                s
+            )),
+        }
+    }
+}
+
+/// Parse a whole batch in the given format
+pub fn parse(input: &str, format: BatchFormat) -> Result<BatchRequest> {
+    match format {
+        BatchFormat::Json => Ok(serde_json::from_str(input)?),
+        BatchFormat::Jsonl => parse_json_lines(input),
+        BatchFormat::Yaml => parse_yaml(input),
+    }
+}
+
+/// Parse one operation per non-blank line
+fn parse_json_lines(input: &str) -> Result<BatchRequest> {
+    let mut ops = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let op = serde_json::from_str(line)
+            .map_err(|e| GranaryError::InvalidArgument(format!("Line {}: {}", index + 1, e)))?;
+        ops.push(op);
+    }
+    Ok(BatchRequest { ops })
+}
+
+/// Parse every document of a YAML stream, in order
+fn parse_yaml(input: &str) -> Result<BatchRequest> {
+    let mut ops = Vec::new();
+    for (index, document) in serde_yaml::Deserializer::from_str(input).enumerate() {
+        let in_document = |e: serde_yaml::Error| {
+            GranaryError::InvalidArgument(format!("Document {}: {}", index + 1, e))
+        };
+
+        let mut value: Value = serde::Deserialize::deserialize(document).map_err(in_document)?;
+        value.apply_merge().map_err(in_document)?;
+        match value {
+            // An empty document, such as one left by a trailing `---`
+            Value::Null => {}
+            Value::Mapping(ref mapping) if mapping.contains_key("ops") => {
+                let request: BatchRequest = serde_yaml::from_value(value).map_err(in_document)?;
+                ops.extend(request.ops);
+            }
+            Value::Sequence(_) => {
+                let list: Vec<BatchOp> = serde_yaml::from_value(value).map_err(in_document)?;
+                ops.extend(list);
+            }
+            _ => ops.push(serde_yaml::from_value(value).map_err(in_document)?),
+        }
+    }
+    Ok(BatchRequest { ops })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op_names(request: &BatchRequest) -> Vec<String> {
+        request
+            .ops
+            .iter()
+            .map(|op| {
+                serde_json::to_value(op).unwrap()["op"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(
+            BatchFormat::from_path(Path::new("plan.YML")),
+            Some(BatchFormat::Yaml)
+        );
+        assert_eq!(
+            BatchFormat::from_path(Path::new("ops.ndjson")),
+            Some(BatchFormat::Jsonl)
+        );
+        assert_eq!(BatchFormat::from_path(Path::new("plan.txt")), None);
+
+        assert_eq!(BatchFormat::detect(r#"{"ops": []}"#), BatchFormat::Json);
+        assert_eq!(
+            BatchFormat::detect(
+                "{\"op\": \"task.start\", \"id\": \"a\"}\n{\"op\": \"task.start\", \"id\": \"b\"}\n"
+            ),
+            BatchFormat::Jsonl
+        );
+        assert_eq!(
+            BatchFormat::detect("ops:\n  - op: task.unblock\n    id: a\n"),
+            BatchFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn test_parse_yaml_documents_and_anchors() {
+        let input = r#"
+defaults: &backend
+  project_id: api-1234
+  priority: P1
+ops:
+  - op: task.create
+    <<: *backend
+    title: Add rate limiting
+  - op: task.create
+    <<: *backend
+    title: Cache sessions
+---
+- op: task.start
+  id: api-1234-task-1
+---
+op: task.done
+id: api-1234-task-1
+comment: Shipped
+---
+"#;
+        let request = parse(input, BatchFormat::Yaml).unwrap();
+        assert_eq!(
+            op_names(&request),
+            vec!["task.create", "task.create", "task.start", "task.done"]
+        );
+        match &request.ops[1] {
+            BatchOp::TaskCreate {
+                project_id,
+                title,
+                priority,
+                ..
+            } => {
+                assert_eq!(project_id, "api-1234");
+                assert_eq!(title, "Cache sessions");
+                assert_eq!(priority.as_deref(), Some("P1"));
+            }
+            op => panic!("Expected task.create, got {:?}", op),
+        }
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_location() {
+        let err = parse(
+            "op: task.unblock\nid: a\n---\nop: task.nope\n",
+            BatchFormat::Yaml,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Document 2"), "{}", err);
+
+        let err = parse(
+            "{\"op\": \"task.unblock\", \"id\": \"a\"}\n\n{\"op\": \"task.unblock\"}\n",
+            BatchFormat::Jsonl,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Line 3"), "{}", err);
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::{HashMap, HashSet};
+
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
+use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::*;
 use crate::services;
@@ -34,7 +37,12 @@ pub enum BatchOp {
         tags: Option<Vec<String>>,
     },
     #[serde(rename = "project.archive")]
-    ProjectArchive { id: String },
+    ProjectArchive {
+        id: String,
+        /// Archive the project's tasks too
+        #[serde(default)]
+        cascade: bool,
+    },
 
     #[serde(rename = "task.create")]
     TaskCreate {
@@ -97,6 +105,9 @@ pub enum BatchOp {
         kind: Option<String>,
         #[serde(default)]
         author: Option<String>,
+        /// Comment to reply to
+        #[serde(default)]
+        reply_to: Option<String>,
     },
     #[serde(rename = "comment.update")]
     CommentUpdate {
@@ -141,12 +152,16 @@ pub struct BatchRequest {
     pub ops: Vec<BatchOp>,
 }
 
-/// Apply a batch of operations
-pub async fn apply_batch(pool: &SqlitePool, request: &BatchRequest) -> Result<Vec<BatchResult>> {
+/// Apply a batch of operations for a caller in session `session_id`
+pub async fn apply_batch(
+    pool: &SqlitePool,
+    request: &BatchRequest,
+    session_id: Option<&str>,
+) -> Result<Vec<BatchResult>> {
     let mut results = Vec::new();
 
     for (index, op) in request.ops.iter().enumerate() {
-        let result = apply_single_op(pool, op).await;
+        let result = apply_single_op(pool, op, session_id).await;
         let (success, id, error) = match result {
             Ok(id) => (true, id, None),
             Err(e) => (false, None, Some(e.to_string())),
@@ -165,7 +180,11 @@ pub async fn apply_batch(pool: &SqlitePool, request: &BatchRequest) -> Result<Ve
 }
 
 /// Apply a single operation
-async fn apply_single_op(pool: &SqlitePool, op: &BatchOp) -> Result<Option<String>> {
+async fn apply_single_op(
+    pool: &SqlitePool,
+    op: &BatchOp,
+    session_id: Option<&str>,
+) -> Result<Option<String>> {
     match op {
         BatchOp::ProjectCreate {
             name,
@@ -213,8 +232,8 @@ async fn apply_single_op(pool: &SqlitePool, op: &BatchOp) -> Result<Option<Strin
             Ok(Some(id.clone()))
         }
 
-        BatchOp::ProjectArchive { id } => {
-            services::archive_project(pool, id).await?;
+        BatchOp::ProjectArchive { id, cascade } => {
+            services::archive_project(pool, id, *cascade).await?;
             Ok(Some(id.clone()))
         }
 
@@ -273,28 +292,29 @@ async fn apply_single_op(pool: &SqlitePool, op: &BatchOp) -> Result<Option<Strin
                     tags: tags.clone(),
                     ..Default::default()
                 },
+                session_id,
             )
             .await?;
             Ok(Some(id.clone()))
         }
 
         BatchOp::TaskStart { id, owner } => {
-            services::start_task(pool, id, owner.clone()).await?;
+            services::start_task(pool, id, owner.clone(), session_id).await?;
             Ok(Some(id.clone()))
         }
 
         BatchOp::TaskDone { id, comment } => {
-            services::complete_task(pool, id, comment.as_deref()).await?;
+            services::complete_task(pool, id, comment.as_deref(), session_id).await?;
             Ok(Some(id.clone()))
         }
 
         BatchOp::TaskBlock { id, reason } => {
-            services::block_task(pool, id, reason).await?;
+            services::block_task(pool, id, reason, session_id).await?;
             Ok(Some(id.clone()))
         }
 
         BatchOp::TaskUnblock { id } => {
-            services::unblock_task(pool, id).await?;
+            services::unblock_task(pool, id, session_id).await?;
             Ok(Some(id.clone()))
         }
 
@@ -319,6 +339,7 @@ async fn apply_single_op(pool: &SqlitePool, op: &BatchOp) -> Result<Option<Strin
             content,
             kind,
             author,
+            reply_to,
         } => {
             let comment_kind = kind
                 .as_ref()
@@ -342,6 +363,7 @@ async fn apply_single_op(pool: &SqlitePool, op: &BatchOp) -> Result<Option<Strin
                     kind: comment_kind,
                     content: content.clone(),
                     author: author.clone(),
+                    parent_comment_id: reply_to.clone(),
                     ..Default::default()
                 },
             )
@@ -419,8 +441,606 @@ fn op_name(op: &BatchOp) -> String {
     }
 }
 
-/// Create a comment (used by batch operations)
-async fn create_comment(pool: &SqlitePool, input: CreateComment) -> Result<Comment> {
+/// Work out what a batch would change without writing anything.
+///
+/// Operations are resolved in order, each against the state the earlier
+/// ones would leave, so two updates to one task show as two steps. An
+/// operation that would fail, such as one naming an unknown ID, carries the
+/// error instead of changes.
+pub async fn plan_batch(pool: &SqlitePool, request: &BatchRequest) -> Result<Vec<PlannedChange>> {
+    let mut planner = BatchPlanner::new(pool);
+    let mut plan = Vec::new();
+
+    for (index, op) in request.ops.iter().enumerate() {
+        let (changes, error) = match planner.plan(op).await {
+            Ok(changes) => (changes, None),
+            Err(e @ GranaryError::Database(_)) => return Err(e),
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        let (action, entity_type) = op_target(op);
+
+        plan.push(PlannedChange {
+            index,
+            op: op_name(op),
+            action,
+            entity_type: entity_type.to_string(),
+            id: op_entity_id(op),
+            changes,
+            error,
+        });
+    }
+
+    Ok(plan)
+}
+
+/// Resolves batch operations against the database, keeping the entities
+/// earlier operations would change so later ones see those changes
+struct BatchPlanner<'a> {
+    pool: &'a SqlitePool,
+    projects: HashMap<String, Project>,
+    tasks: HashMap<String, Task>,
+    comments: HashMap<String, Comment>,
+    sessions: HashMap<String, Session>,
+    /// Tasks each task depends on
+    dependencies: HashMap<String, HashSet<String>>,
+    /// `(item_type, item_id)` pairs in each session's scope
+    scopes: HashMap<String, HashSet<(String, String)>>,
+}
+
+impl<'a> BatchPlanner<'a> {
+    fn new(pool: &'a SqlitePool) -> Self {
+        Self {
+            pool,
+            projects: HashMap::new(),
+            tasks: HashMap::new(),
+            comments: HashMap::new(),
+            sessions: HashMap::new(),
+            dependencies: HashMap::new(),
+            scopes: HashMap::new(),
+        }
+    }
+
+    async fn project(&mut self, id: &str) -> Result<Project> {
+        if let Some(project) = self.projects.get(id) {
+            return Ok(project.clone());
+        }
+        let project = db::projects::get(self.pool, id)
+            .await?
+            .ok_or_else(|| GranaryError::ProjectNotFound(id.to_string()))?;
+        self.projects.insert(id.to_string(), project.clone());
+        Ok(project)
+    }
+
+    async fn task(&mut self, id: &str) -> Result<Task> {
+        if let Some(task) = self.tasks.get(id) {
+            return Ok(task.clone());
+        }
+        let task = db::tasks::get(self.pool, id)
+            .await?
+            .ok_or_else(|| GranaryError::TaskNotFound(id.to_string()))?;
+        self.tasks.insert(id.to_string(), task.clone());
+        Ok(task)
+    }
+
+    async fn comment(&mut self, id: &str) -> Result<Comment> {
+        if let Some(comment) = self.comments.get(id) {
+            return Ok(comment.clone());
+        }
+        let comment = db::comments::get(self.pool, id)
+            .await?
+            .ok_or_else(|| GranaryError::CommentNotFound(id.to_string()))?;
+        self.comments.insert(id.to_string(), comment.clone());
+        Ok(comment)
+    }
+
+    async fn session(&mut self, id: &str) -> Result<Session> {
+        if let Some(session) = self.sessions.get(id) {
+            return Ok(session.clone());
+        }
+        let session = db::sessions::get(self.pool, id)
+            .await?
+            .ok_or_else(|| GranaryError::SessionNotFound(id.to_string()))?;
+        self.sessions.insert(id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    async fn dependencies(&mut self, task_id: &str) -> Result<&mut HashSet<String>> {
+        if !self.dependencies.contains_key(task_id) {
+            let deps = db::dependencies::list(self.pool, task_id)
+                .await?
+                .into_iter()
+                .map(|d| d.depends_on_task_id)
+                .collect();
+            self.dependencies.insert(task_id.to_string(), deps);
+        }
+        Ok(self.dependencies.get_mut(task_id).expect("just inserted"))
+    }
+
+    async fn scope(&mut self, session_id: &str) -> Result<&mut HashSet<(String, String)>> {
+        self.session(session_id).await?;
+        if !self.scopes.contains_key(session_id) {
+            let items = db::sessions::get_scope(self.pool, session_id)
+                .await?
+                .into_iter()
+                .map(|s| (s.item_type, s.item_id))
+                .collect();
+            self.scopes.insert(session_id.to_string(), items);
+        }
+        Ok(self.scopes.get_mut(session_id).expect("just inserted"))
+    }
+
+    /// Check that the item a scope operation names exists
+    async fn check_scope_item(&mut self, item_type: &ScopeItemType, item_id: &str) -> Result<()> {
+        match item_type {
+            ScopeItemType::Project => {
+                self.project(item_id).await?;
+            }
+            ScopeItemType::Task => {
+                self.task(item_id).await?;
+            }
+            ScopeItemType::Comment => {
+                self.comment(item_id).await?;
+            }
+            ScopeItemType::Artifact => {
+                db::artifacts::get(self.pool, item_id)
+                    .await?
+                    .ok_or_else(|| GranaryError::ArtifactNotFound(item_id.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The fields one operation would change
+    async fn plan(&mut self, op: &BatchOp) -> Result<Vec<FieldChange>> {
+        let mut changes = Vec::new();
+        match op {
+            BatchOp::ProjectCreate {
+                name,
+                description,
+                owner,
+                tags,
+            } => {
+                diff(&mut changes, "name", None, Some(name));
+                diff(&mut changes, "description", None, description.as_deref());
+                diff(&mut changes, "owner", None, owner.as_deref());
+                diff(&mut changes, "tags", None, join_tags(tags).as_deref());
+            }
+
+            BatchOp::ProjectUpdate {
+                id,
+                name,
+                description,
+                owner,
+                status,
+                tags,
+            } => {
+                let status: Option<ProjectStatus> = status
+                    .as_ref()
+                    .map(|s| parse_value(s, "project status"))
+                    .transpose()?;
+                let old = self.project(id).await?;
+                let mut new = old.clone();
+                if let Some(name) = name {
+                    new.name = name.clone();
+                }
+                if let Some(description) = description {
+                    new.description = Some(description.clone());
+                }
+                if let Some(owner) = owner {
+                    new.owner = Some(owner.clone());
+                }
+                if let Some(status) = status {
+                    new.status = status.as_str().to_string();
+                }
+                if let Some(tags) = tags {
+                    new.tags = Some(serde_json::to_string(tags)?);
+                }
+                project_diff(&mut changes, &old, &new);
+                self.projects.insert(id.clone(), new);
+            }
+
+            BatchOp::ProjectArchive { id, cascade } => {
+                let old = self.project(id).await?;
+                let already_archived = old.status == ProjectStatus::Archived.as_str();
+                if already_archived && !cascade {
+                    return Err(GranaryError::Conflict(format!(
+                        "Project {} is already archived",
+                        id
+                    )));
+                }
+                let mut new = old.clone();
+                new.status = ProjectStatus::Archived.as_str().to_string();
+                project_diff(&mut changes, &old, &new);
+                if *cascade {
+                    let tasks = db::tasks::list_by_project(self.pool, id, false).await?;
+                    if !tasks.is_empty() {
+                        let count = tasks.len().to_string();
+                        diff(&mut changes, "archived tasks", None, Some(&count));
+                    }
+                }
+                self.projects.insert(id.clone(), new);
+            }
+
+            BatchOp::TaskCreate {
+                project_id,
+                title,
+                description,
+                priority,
+                owner,
+                parent_task_id,
+                tags,
+            } => {
+                let priority: TaskPriority = priority
+                    .as_ref()
+                    .map(|p| parse_value(p, "priority"))
+                    .transpose()?
+                    .unwrap_or_default();
+                self.project(project_id).await?;
+                if let Some(parent) = parent_task_id {
+                    self.task(parent).await?;
+                }
+                diff(&mut changes, "project", None, Some(project_id));
+                diff(&mut changes, "parent", None, parent_task_id.as_deref());
+                diff(&mut changes, "title", None, Some(title));
+                diff(&mut changes, "description", None, description.as_deref());
+                diff(&mut changes, "priority", None, Some(priority.as_str()));
+                diff(&mut changes, "owner", None, owner.as_deref());
+                diff(&mut changes, "tags", None, join_tags(tags).as_deref());
+            }
+
+            BatchOp::TaskUpdate {
+                id,
+                title,
+                description,
+                status,
+                priority,
+                owner,
+                tags,
+            } => {
+                let status: Option<TaskStatus> = status
+                    .as_ref()
+                    .map(|s| parse_value(s, "status"))
+                    .transpose()?;
+                let priority: Option<TaskPriority> = priority
+                    .as_ref()
+                    .map(|p| parse_value(p, "priority"))
+                    .transpose()?;
+                let old = self.task(id).await?;
+                let mut new = old.clone();
+                if let Some(title) = title {
+                    new.title = title.clone();
+                }
+                if let Some(description) = description {
+                    new.description = Some(description.clone());
+                }
+                if let Some(status) = status {
+                    new.status = status.as_str().to_string();
+                }
+                if let Some(priority) = priority {
+                    new.priority = priority.as_str().to_string();
+                }
+                if let Some(owner) = owner {
+                    new.owner = Some(owner.clone());
+                }
+                if let Some(tags) = tags {
+                    new.tags = Some(serde_json::to_string(tags)?);
+                }
+                self.update_task(&mut changes, old, new);
+            }
+
+            BatchOp::TaskStart { id, owner } => {
+                let old = self.task(id).await?;
+                if old.status_enum().is_draft() {
+                    return Err(GranaryError::Conflict(format!(
+                        "Task {} is in Draft status",
+                        id
+                    )));
+                }
+                if old.status_enum().is_terminal() {
+                    return Err(GranaryError::Conflict(format!(
+                        "Task {} is already completed",
+                        id
+                    )));
+                }
+                let mut new = old.clone();
+                new.status = TaskStatus::InProgress.as_str().to_string();
+                if new.started_at.is_none() {
+                    new.started_at = Some(chrono::Utc::now().to_rfc3339());
+                }
+                if let Some(owner) = owner {
+                    new.owner = Some(owner.clone());
+                }
+                self.update_task(&mut changes, old, new);
+            }
+
+            BatchOp::TaskDone { id, comment } => {
+                let old = self.task(id).await?;
+                let mut new = old.clone();
+                new.status = TaskStatus::Done.as_str().to_string();
+                new.blocked_reason = None;
+                self.update_task(&mut changes, old, new);
+                diff(&mut changes, "comment", None, comment.as_deref());
+            }
+
+            BatchOp::TaskBlock { id, reason } => {
+                let old = self.task(id).await?;
+                let mut new = old.clone();
+                new.status = TaskStatus::Blocked.as_str().to_string();
+                new.blocked_reason = Some(reason.clone());
+                self.update_task(&mut changes, old, new);
+            }
+
+            BatchOp::TaskUnblock { id } => {
+                let old = self.task(id).await?;
+                let mut new = old.clone();
+                new.status = if old.started_at.is_some() {
+                    TaskStatus::InProgress.as_str().to_string()
+                } else {
+                    TaskStatus::Todo.as_str().to_string()
+                };
+                new.blocked_reason = None;
+                self.update_task(&mut changes, old, new);
+            }
+
+            BatchOp::DependencyAdd {
+                task_id,
+                depends_on,
+            } => {
+                self.task(task_id).await?;
+                self.task(depends_on).await?;
+                if self.dependencies(task_id).await?.insert(depends_on.clone()) {
+                    diff(&mut changes, "depends_on", None, Some(depends_on));
+                }
+            }
+
+            BatchOp::DependencyRemove {
+                task_id,
+                depends_on,
+            } => {
+                self.task(task_id).await?;
+                self.task(depends_on).await?;
+                if self.dependencies(task_id).await?.remove(depends_on) {
+                    diff(&mut changes, "depends_on", Some(depends_on), None);
+                }
+            }
+
+            BatchOp::CommentCreate {
+                parent,
+                content,
+                kind,
+                author,
+                reply_to,
+            } => {
+                let kind: CommentKind = kind
+                    .as_ref()
+                    .map(|k| parse_value(k, "comment kind"))
+                    .transpose()?
+                    .unwrap_or_default();
+                if parent.contains("-task-") {
+                    self.task(parent).await?;
+                } else if parent.contains("-comment-") {
+                    self.comment(parent).await?;
+                } else {
+                    self.project(parent).await?;
+                }
+                if let Some(reply_to) = reply_to {
+                    let replied = self.comment(reply_to).await?;
+                    if replied.parent_id != *parent {
+                        return Err(GranaryError::InvalidArgument(format!(
+                            "Comment {} is on {}, not {}",
+                            reply_to, replied.parent_id, parent
+                        )));
+                    }
+                }
+                diff(&mut changes, "parent", None, Some(parent));
+                diff(&mut changes, "reply_to", None, reply_to.as_deref());
+                diff(&mut changes, "kind", None, Some(kind.as_str()));
+                diff(&mut changes, "content", None, Some(content));
+                diff(&mut changes, "author", None, author.as_deref());
+            }
+
+            BatchOp::CommentUpdate { id, content, kind } => {
+                let kind: Option<CommentKind> = kind
+                    .as_ref()
+                    .map(|k| parse_value(k, "comment kind"))
+                    .transpose()?;
+                let old = self.comment(id).await?;
+                let mut new = old.clone();
+                if let Some(content) = content {
+                    new.content = content.clone();
+                }
+                if let Some(kind) = kind {
+                    new.kind = kind.as_str().to_string();
+                }
+                diff(&mut changes, "kind", Some(&old.kind), Some(&new.kind));
+                diff(
+                    &mut changes,
+                    "content",
+                    Some(&old.content),
+                    Some(&new.content),
+                );
+                self.comments.insert(id.clone(), new);
+            }
+
+            BatchOp::SessionScopeAdd {
+                session_id,
+                item_type,
+                item_id,
+            } => {
+                let item_type: ScopeItemType = parse_value(item_type, "item type")?;
+                self.check_scope_item(&item_type, item_id).await?;
+                let item = (item_type.as_str().to_string(), item_id.clone());
+                if self.scope(session_id).await?.insert(item) {
+                    diff(&mut changes, item_type.as_str(), None, Some(item_id));
+                }
+            }
+
+            BatchOp::SessionScopeRemove {
+                session_id,
+                item_type,
+                item_id,
+            } => {
+                let item_type: ScopeItemType = parse_value(item_type, "item type")?;
+                let item = (item_type.as_str().to_string(), item_id.clone());
+                if self.scope(session_id).await?.remove(&item) {
+                    diff(&mut changes, item_type.as_str(), Some(item_id), None);
+                }
+            }
+
+            BatchOp::SessionFocus {
+                session_id,
+                task_id,
+            } => {
+                self.task(task_id).await?;
+                let old = self.session(session_id).await?;
+                diff(
+                    &mut changes,
+                    "focus_task",
+                    old.focus_task_id.as_deref(),
+                    Some(task_id),
+                );
+                let mut new = old;
+                new.focus_task_id = Some(task_id.clone());
+                self.sessions.insert(session_id.clone(), new);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Record a task's changed fields and keep the new state for later ops
+    fn update_task(&mut self, changes: &mut Vec<FieldChange>, old: Task, new: Task) {
+        diff(changes, "title", Some(&old.title), Some(&new.title));
+        diff(
+            changes,
+            "description",
+            old.description.as_deref(),
+            new.description.as_deref(),
+        );
+        diff(changes, "status", Some(&old.status), Some(&new.status));
+        diff(
+            changes,
+            "priority",
+            Some(&old.priority),
+            Some(&new.priority),
+        );
+        diff(changes, "owner", old.owner.as_deref(), new.owner.as_deref());
+        diff(
+            changes,
+            "tags",
+            join_tags(&old.tags_vec()).as_deref(),
+            join_tags(&new.tags_vec()).as_deref(),
+        );
+        diff(
+            changes,
+            "blocked_reason",
+            old.blocked_reason.as_deref(),
+            new.blocked_reason.as_deref(),
+        );
+        self.tasks.insert(new.id.clone(), new);
+    }
+}
+
+/// Record a field change, unless the value stays the same
+fn diff(changes: &mut Vec<FieldChange>, field: &str, old: Option<&str>, new: Option<&str>) {
+    if old != new {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            old: old.map(str::to_string),
+            new: new.map(str::to_string),
+        });
+    }
+}
+
+fn project_diff(changes: &mut Vec<FieldChange>, old: &Project, new: &Project) {
+    diff(changes, "name", Some(&old.name), Some(&new.name));
+    diff(
+        changes,
+        "description",
+        old.description.as_deref(),
+        new.description.as_deref(),
+    );
+    diff(changes, "owner", old.owner.as_deref(), new.owner.as_deref());
+    diff(changes, "status", Some(&old.status), Some(&new.status));
+    diff(
+        changes,
+        "tags",
+        join_tags(&old.tags_vec()).as_deref(),
+        join_tags(&new.tags_vec()).as_deref(),
+    );
+}
+
+fn join_tags(tags: &[String]) -> Option<String> {
+    (!tags.is_empty()).then(|| tags.join(", "))
+}
+
+/// Parse an enum value from a batch operation, naming what was invalid
+fn parse_value<T: std::str::FromStr>(value: &str, what: &str) -> Result<T> {
+    value
+        .parse()
+        .map_err(|_| GranaryError::InvalidArgument(format!("Invalid {}: {}", what, value)))
+}
+
+/// What an operation changes: the kind of change and the entity type
+fn op_target(op: &BatchOp) -> (ChangeAction, &'static str) {
+    match op {
+        BatchOp::ProjectCreate { .. } => (ChangeAction::Create, "project"),
+        BatchOp::ProjectUpdate { .. } | BatchOp::ProjectArchive { .. } => {
+            (ChangeAction::Update, "project")
+        }
+        BatchOp::TaskCreate { .. } => (ChangeAction::Create, "task"),
+        BatchOp::TaskUpdate { .. }
+        | BatchOp::TaskStart { .. }
+        | BatchOp::TaskDone { .. }
+        | BatchOp::TaskBlock { .. }
+        | BatchOp::TaskUnblock { .. } => (ChangeAction::Update, "task"),
+        BatchOp::DependencyAdd { .. } => (ChangeAction::Create, "dependency"),
+        BatchOp::DependencyRemove { .. } => (ChangeAction::Delete, "dependency"),
+        BatchOp::CommentCreate { .. } => (ChangeAction::Create, "comment"),
+        BatchOp::CommentUpdate { .. } => (ChangeAction::Update, "comment"),
+        BatchOp::SessionScopeAdd { .. } => (ChangeAction::Create, "scope"),
+        BatchOp::SessionScopeRemove { .. } => (ChangeAction::Delete, "scope"),
+        BatchOp::SessionFocus { .. } => (ChangeAction::Update, "session"),
+    }
+}
+
+/// ID of the existing entity an operation changes. Dependencies belong to
+/// the dependent task and scope items to their session.
+fn op_entity_id(op: &BatchOp) -> Option<String> {
+    match op {
+        BatchOp::ProjectCreate { .. }
+        | BatchOp::TaskCreate { .. }
+        | BatchOp::CommentCreate { .. } => None,
+        BatchOp::ProjectUpdate { id, .. }
+        | BatchOp::ProjectArchive { id, .. }
+        | BatchOp::TaskUpdate { id, .. }
+        | BatchOp::TaskStart { id, .. }
+        | BatchOp::TaskDone { id, .. }
+        | BatchOp::TaskBlock { id, .. }
+        | BatchOp::TaskUnblock { id }
+        | BatchOp::CommentUpdate { id, .. } => Some(id.clone()),
+        BatchOp::DependencyAdd { task_id, .. } | BatchOp::DependencyRemove { task_id, .. } => {
+            Some(task_id.clone())
+        }
+        BatchOp::SessionScopeAdd { session_id, .. }
+        | BatchOp::SessionScopeRemove { session_id, .. }
+        | BatchOp::SessionFocus { session_id, .. } => Some(session_id.clone()),
+    }
+}
+
+/// Create a comment, checking that a reply is to a comment on the same parent
+pub(crate) async fn create_comment(pool: &SqlitePool, input: CreateComment) -> Result<Comment> {
+    if let Some(reply_to) = &input.parent_comment_id {
+        let parent = crate::db::comments::get(pool, reply_to)
+            .await?
+            .ok_or_else(|| GranaryError::CommentNotFound(reply_to.clone()))?;
+        if parent.parent_id != input.parent_id {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Comment {} is on {}, not {}",
+                reply_to, parent.parent_id, input.parent_id
+            )));
+        }
+    }
+
     let scope = format!("{}:{}:comment", input.parent_type.as_str(), input.parent_id);
     let comment_number = crate::db::counters::next(pool, &scope).await?;
     let id = generate_comment_id(&input.parent_id, comment_number);
@@ -432,10 +1052,11 @@ async fn create_comment(pool: &SqlitePool, input: CreateComment) -> Result<Comme
         id: id.clone(),
         parent_type: input.parent_type.as_str().to_string(),
         parent_id: input.parent_id,
+        parent_comment_id: input.parent_comment_id,
         comment_number,
         kind: input.kind.as_str().to_string(),
         content: input.content,
-        author: input.author,
+        author: input.author.or_else(crate::services::current_author),
         meta,
         created_at: now.clone(),
         updated_at: now,
@@ -456,6 +1077,7 @@ async fn create_comment(pool: &SqlitePool, input: CreateComment) -> Result<Comme
             payload: serde_json::json!({
                 "kind": comment.kind,
                 "parent_id": comment.parent_id,
+                "parent_comment_id": comment.parent_comment_id,
             }),
         },
     )
@@ -465,7 +1087,11 @@ async fn create_comment(pool: &SqlitePool, input: CreateComment) -> Result<Comme
 }
 
 /// Update a comment
-async fn update_comment(pool: &SqlitePool, id: &str, updates: UpdateComment) -> Result<Comment> {
+pub(crate) async fn update_comment(
+    pool: &SqlitePool,
+    id: &str,
+    updates: UpdateComment,
+) -> Result<Comment> {
     let mut comment = crate::db::comments::get(pool, id)
         .await?
         .ok_or_else(|| GranaryError::CommentNotFound(id.to_string()))?;
@@ -488,7 +1114,476 @@ async fn update_comment(pool: &SqlitePool, id: &str, updates: UpdateComment) ->
         });
     }
 
+    crate::db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::CommentUpdated,
+            entity_type: EntityType::Comment,
+            entity_id: comment.id.clone(),
+            actor: crate::services::current_author(),
+            session_id: None,
+            payload: serde_json::json!({
+                "kind": comment.kind,
+                "parent_id": comment.parent_id,
+            }),
+        },
+    )
+    .await?;
+
     crate::db::comments::get(pool, id)
         .await?
         .ok_or_else(|| GranaryError::CommentNotFound(id.to_string()))
 }
+
+/// Changes applied to every task matched by `granary batch update`, parsed
+/// from `--set key:value` arguments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskChangeSet {
+    pub status: Option<TaskStatus>,
+    pub priority: Option<TaskPriority>,
+    pub owner: Option<String>,
+    pub labels: Vec<LabelChange>,
+    pub fields: Vec<FieldAssignment>,
+}
+
+impl TaskChangeSet {
+    /// Parse `status:done`, `priority:p1`, `owner:alice`, `label:+urgent`,
+    /// `label:-frontend` and `field.env:prod` (or `field.env:` to clear)
+    pub fn parse(sets: &[String]) -> Result<Self> {
+        let mut changes = TaskChangeSet::default();
+        for set in sets {
+            let (key, value) = set.split_once(':').ok_or_else(|| {
+                GranaryError::InvalidArgument(format!(
+                    "Invalid change '{}'. Expected KEY:VALUE",
+                    set
+                ))
+            })?;
+            match key.to_lowercase().as_str() {
+                "status" => {
+                    changes.status = Some(value.parse().map_err(|_| {
+                        GranaryError::InvalidArgument(format!("Invalid status: {}", value))
+                    })?)
+                }
+                "priority" => {
+                    changes.priority = Some(value.parse().map_err(|_| {
+                        GranaryError::InvalidArgument(format!("Invalid priority: {}", value))
+                    })?)
+                }
+                "owner" if !value.is_empty() => changes.owner = Some(value.to_string()),
+                "label" => changes
+                    .labels
+                    .extend(services::parse_label_changes(&[value.to_string()])?),
+                other => match other.strip_prefix("field.") {
+                    Some(name) => changes.fields.extend(services::parse_field_args(&[format!(
+                        "{}={}",
+                        name, value
+                    )])?),
+                    None => {
+                        return Err(GranaryError::InvalidArgument(format!(
+                            "Cannot set '{}'. Supported: status, priority, owner, label, field.<name>",
+                            set
+                        )));
+                    }
+                },
+            }
+        }
+        Ok(changes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == TaskChangeSet::default()
+    }
+
+    /// Human-readable summary, e.g. `priority=P1, label +urgent`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(status) = &self.status {
+            parts.push(format!("status={}", status.as_str()));
+        }
+        if let Some(priority) = &self.priority {
+            parts.push(format!("priority={}", priority.as_str()));
+        }
+        if let Some(owner) = &self.owner {
+            parts.push(format!("owner={}", owner));
+        }
+        for change in &self.labels {
+            parts.push(match change {
+                LabelChange::Add(name) => format!("label +{}", name),
+                LabelChange::Remove(name) => format!("label -{}", name),
+            });
+        }
+        for field in &self.fields {
+            parts.push(format!(
+                "field {}={}",
+                field.name,
+                field.value.as_deref().unwrap_or("(cleared)")
+            ));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Apply a change set to tasks in a single transaction.
+///
+/// The tasks are usually the preview shown to the user: if any of them was
+/// modified since, nothing is changed and a version mismatch is returned.
+/// Status changes are checked against the claims and mode of `session_id`.
+pub async fn bulk_update_tasks(
+    pool: &SqlitePool,
+    tasks: &[Task],
+    changes: &TaskChangeSet,
+    session_id: Option<&str>,
+) -> Result<Vec<Task>> {
+    if changes.is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "No changes given. Use --set KEY:VALUE".to_string(),
+        ));
+    }
+    let fields = services::validate_fields(pool, &changes.fields).await?;
+    if let Some(status) = &changes.status {
+        services::check_status_writable(pool, status, session_id).await?;
+    }
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    for change in &changes.labels {
+        match change {
+            LabelChange::Add(name) => add.push(name.clone()),
+            LabelChange::Remove(name) => remove.push(name.clone()),
+        }
+    }
+    let set: Vec<(String, String)> = fields
+        .iter()
+        .filter_map(|f| f.value.clone().map(|v| (f.name.clone(), v)))
+        .collect();
+    let clear: Vec<String> = fields
+        .iter()
+        .filter(|f| f.value.is_none())
+        .map(|f| f.name.clone())
+        .collect();
+
+    if changes.status.is_some() {
+        for task in tasks {
+            services::check_session_claim(task, session_id)?;
+        }
+    }
+    let mut tx = pool.begin().await?;
+    for task in tasks {
+        let mut updated = task.clone();
+        if let Some(status) = &changes.status {
+            updated.status = status.as_str().to_string();
+        }
+        if let Some(priority) = &changes.priority {
+            updated.priority = priority.as_str().to_string();
+        }
+        if let Some(owner) = &changes.owner {
+            updated.owner = Some(owner.clone());
+        }
+
+        // Dropping the transaction on error rolls back earlier tasks
+        if !db::tasks::update(&mut *tx, &updated).await? {
+            return Err(GranaryError::VersionMismatch {
+                expected: task.version,
+                found: task.version + 1,
+            });
+        }
+        if !add.is_empty() || !remove.is_empty() {
+            db::labels::apply_in(&mut tx, &task.id, &add, &remove).await?;
+        }
+        if !fields.is_empty() {
+            db::custom_fields::apply_in(&mut tx, EntityType::Task.as_str(), &task.id, &set, &clear)
+                .await?;
+        }
+
+        let event_type = if updated.status != task.status {
+            EventType::TaskStatusChanged
+        } else {
+            EventType::TaskUpdated
+        };
+        let mut payload = serde_json::json!({
+            "old_status": task.status,
+            "new_status": updated.status,
+            "bulk": true,
+        });
+        if !add.is_empty() || !remove.is_empty() {
+            payload["labels_added"] = serde_json::json!(add);
+            payload["labels_removed"] = serde_json::json!(remove);
+        }
+        if !fields.is_empty() {
+            payload["fields"] = fields
+                .iter()
+                .map(|f| (f.name.clone(), serde_json::json!(f.value)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        db::events::create(
+            &mut *tx,
+            &CreateEvent {
+                event_type,
+                entity_type: EntityType::Task,
+                entity_id: task.id.clone(),
+                actor: None,
+                session_id: None,
+                payload,
+            },
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    let mut updated = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        updated.push(services::get_task(pool, &task.id).await?);
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task, find_tasks};
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, SqlitePool, Vec<Task>) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Bulk".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut tasks = Vec::new();
+        for title in ["First", "Second", "Third"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            tasks.push(task);
+        }
+        (temp_dir, pool, tasks)
+    }
+
+    fn sets(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_change_set() {
+        let changes =
+            TaskChangeSet::parse(&sets(&["priority:p1", "label:+urgent", "field.env:"])).unwrap();
+        assert_eq!(changes.priority, Some(TaskPriority::P1));
+        assert_eq!(changes.labels, vec![LabelChange::Add("urgent".to_string())]);
+        assert_eq!(changes.fields[0].value, None);
+        assert_eq!(
+            changes.describe(),
+            "priority=P1, label +urgent, field env=(cleared)"
+        );
+
+        assert!(TaskChangeSet::parse(&sets(&["priority"])).is_err());
+        assert!(TaskChangeSet::parse(&sets(&["priority:p9"])).is_err());
+        assert!(TaskChangeSet::parse(&sets(&["title:New"])).is_err());
+        assert!(TaskChangeSet::parse(&[]).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_comment_replies_form_threads() {
+        let (_temp_dir, pool, tasks) = setup().await;
+        let comment = |parent: &Task, reply_to: Option<&str>, kind, content: &str| CreateComment {
+            parent_type: ParentType::Task,
+            parent_id: parent.id.clone(),
+            parent_comment_id: reply_to.map(str::to_string),
+            kind,
+            content: content.to_string(),
+            ..Default::default()
+        };
+
+        let root = create_comment(
+            &pool,
+            comment(&tasks[0], None, CommentKind::Note, "Which DB?"),
+        )
+        .await
+        .unwrap();
+        let reply = create_comment(
+            &pool,
+            comment(&tasks[0], Some(&root.id), CommentKind::Note, "Postgres?"),
+        )
+        .await
+        .unwrap();
+        let decision = create_comment(
+            &pool,
+            comment(
+                &tasks[0],
+                Some(&reply.id),
+                CommentKind::Decision,
+                "Postgres",
+            ),
+        )
+        .await
+        .unwrap();
+        create_comment(&pool, comment(&tasks[0], None, CommentKind::Note, "Later"))
+            .await
+            .unwrap();
+
+        // Replies stay on the same task
+        let err = create_comment(
+            &pool,
+            comment(&tasks[1], Some(&root.id), CommentKind::Note, "Elsewhere"),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, GranaryError::InvalidArgument(_)));
+
+        let thread_root = crate::db::comments::thread_root(&pool, &decision.id)
+            .await
+            .unwrap();
+        assert_eq!(thread_root.map(|c| c.id), Some(root.id.clone()));
+        assert!(
+            crate::db::comments::thread_root(&pool, &root.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let comments = crate::db::comments::list_by_parent(&pool, &tasks[0].id)
+            .await
+            .unwrap();
+        let threads = CommentThread::build(comments);
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].comment.id, root.id);
+        assert_eq!(threads[0].replies[0].comment.id, reply.id);
+        assert_eq!(threads[0].replies[0].replies[0].comment.id, decision.id);
+        assert!(threads[1].replies.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_update_matching_tasks() {
+        let (_temp_dir, pool, _tasks) = setup().await;
+        let changes = TaskChangeSet::parse(&sets(&["priority:p1", "label:urgent"])).unwrap();
+
+        let query: SearchQuery = "status:draft".parse().unwrap();
+        let matched = find_tasks(&pool, &query).await.unwrap();
+        assert_eq!(matched.len(), 3);
+
+        let updated = bulk_update_tasks(&pool, &matched[..2], &changes, None)
+            .await
+            .unwrap();
+        assert!(updated.iter().all(|t| t.priority == "P1"));
+        assert!(updated.iter().all(|t| t.labels == vec!["urgent"]));
+
+        let skipped = services::get_task(&pool, &matched[2].id).await.unwrap();
+        assert_eq!(skipped.priority, "P2");
+        assert!(skipped.labels.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_update_rolls_back_on_conflict() {
+        let (_temp_dir, pool, tasks) = setup().await;
+        let changes = TaskChangeSet::parse(&sets(&["status:todo"])).unwrap();
+
+        // The last task changes after the preview was taken
+        services::update_task(
+            &pool,
+            &tasks[2].id,
+            UpdateTask {
+                title: Some("Renamed".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let result = bulk_update_tasks(&pool, &tasks, &changes, None).await;
+        assert!(matches!(result, Err(GranaryError::VersionMismatch { .. })));
+
+        for task in &tasks {
+            let task = services::get_task(&pool, &task.id).await.unwrap();
+            assert_eq!(task.status, "draft");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_plan_batch_writes_nothing() {
+        let (_temp_dir, pool, tasks) = setup().await;
+        let input = format!(
+            r#"
+- op: task.create
+  project_id: {project}
+  title: Fourth
+- op: task.update
+  id: {first}
+  status: todo
+  priority: P1
+- op: task.block
+  id: {first}
+  reason: Waiting on review
+- op: dependency.add
+  task_id: {second}
+  depends_on: {first}
+- op: dependency.remove
+  task_id: {second}
+  depends_on: {third}
+- op: task.start
+  id: {project}-task-99
+"#,
+            project = tasks[0].project_id,
+            first = tasks[0].id,
+            second = tasks[1].id,
+            third = tasks[2].id,
+        );
+        let request =
+            services::batch_input::parse(&input, services::batch_input::BatchFormat::Yaml).unwrap();
+        let plan = plan_batch(&pool, &request).await.unwrap();
+
+        assert_eq!(plan[0].action, ChangeAction::Create);
+        assert_eq!(plan[0].id, None);
+
+        let field = |change: &PlannedChange, name: &str| {
+            change
+                .changes
+                .iter()
+                .find(|c| c.field == name)
+                .map(|c| (c.old.clone(), c.new.clone()))
+        };
+        assert_eq!(
+            field(&plan[1], "status"),
+            Some((Some("draft".to_string()), Some("todo".to_string())))
+        );
+        // The block is compared against the status the update would leave
+        assert_eq!(
+            field(&plan[2], "status"),
+            Some((Some("todo".to_string()), Some("blocked".to_string())))
+        );
+        assert_eq!(plan[3].changes.len(), 1);
+        // Removing a dependency that does not exist changes nothing
+        assert!(plan[4].changes.is_empty() && plan[4].error.is_none());
+        assert!(plan[5].error.as_deref().unwrap().contains("task-99"));
+
+        let first = services::get_task(&pool, &tasks[0].id).await.unwrap();
+        assert_eq!(first.status, "draft");
+        assert_eq!(
+            services::list_tasks_by_project(&pool, &first.project_id, false)
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(
+            db::dependencies::list(&pool, &tasks[1].id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
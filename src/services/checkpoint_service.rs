@@ -4,7 +4,7 @@ use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::*;
 use crate::output::json::{CheckpointDiff, DiffChange};
-use crate::services::{get_scope, get_session};
+use crate::services::{current_author, get_scope, get_session};
 
 /// Create a checkpoint for a session
 pub async fn create_checkpoint(
@@ -61,10 +61,12 @@ pub async fn create_checkpoint(
             owner: session.owner.clone(),
             mode: session.mode.clone(),
             focus_task_id: session.focus_task_id.clone(),
+            focus_stack: session.focus_stack_vec(),
         },
         scope: scope_items,
         tasks: task_snapshots,
         variables: session.variables_map(),
+        estimates: db::tasks::estimate_totals(pool).await?,
     };
 
     let id = generate_checkpoint_id();
@@ -75,6 +77,7 @@ pub async fn create_checkpoint(
         session_id: session_id.to_string(),
         name: name.to_string(),
         snapshot: serde_json::to_string(&snapshot)?,
+        author: current_author(),
         created_at: now,
     };
 
@@ -259,7 +262,17 @@ pub async fn restore_checkpoint(
     // Update session
     let mut session = get_session(pool, session_id).await?;
     session.mode = snapshot.session.mode;
-    session.focus_task_id = snapshot.session.focus_task_id;
+    let focus_stack = if snapshot.session.focus_stack.is_empty() {
+        snapshot.session.focus_task_id.into_iter().collect()
+    } else {
+        snapshot.session.focus_stack
+    };
+    session.focus_task_id = focus_stack.last().cloned();
+    session.focus_stack = if focus_stack.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&focus_stack)?)
+    };
     if !snapshot.variables.is_empty() {
         session.variables = Some(serde_json::to_string(&snapshot.variables)?);
     }
@@ -336,9 +349,11 @@ async fn get_current_snapshot(pool: &SqlitePool, session_id: &str) -> Result<Ses
             owner: session.owner.clone(),
             mode: session.mode.clone(),
             focus_task_id: session.focus_task_id.clone(),
+            focus_stack: session.focus_stack_vec(),
         },
         scope: scope_items,
         tasks: task_snapshots,
         variables,
+        estimates: db::tasks::estimate_totals(pool).await?,
     })
 }
@@ -0,0 +1,425 @@
+//! Checks behind `granary config validate`.
+//!
+//! Looks for problems that would otherwise only surface at runtime: config
+//! files that don't parse, runner commands missing from PATH, webhook and
+//! notifier URLs that aren't HTTP(S), filter and cron expressions that don't
+//! parse, and workspace config keys with invalid values. Each problem is
+//! reported with where it is: a file and line, a workspace config key, or a
+//! worker.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use toml::Spanned;
+use toml::de::{DeTable, DeValue};
+
+use crate::db;
+use crate::error::Result;
+use crate::models::global_config::{
+    GlobalConfig, NotifierConfig, RunnerConfig, WebhookConfig, expand_env_vars, resolve_runner,
+};
+use crate::models::workspace_config::WorkspaceConfig;
+use crate::models::{EventType, Worker};
+use crate::output::columns;
+use crate::services::filter_expr::parse_event_filters;
+use crate::services::runner::find_command;
+use crate::services::schedule::Schedule;
+use crate::services::{
+    backup_service, datetime, field_service, filter, global_config_service, jira_service,
+    linear_service, log_retention, prompt_templates, reminder_service, rule_service,
+    search_service, summary_service, workspace_config,
+};
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Something will fail
+    Error,
+    /// Something may not work as intended
+    Warning,
+}
+
+/// A problem found in the config
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Where the problem is: `path:line`, a config key or a worker
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", self.location, severity, self.message)
+    }
+}
+
+/// Validate a workspace config value before it is stored, as
+/// `granary config set` does
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    datetime::validate_config_value(key, value)?;
+    field_service::validate_config_value(key, value)?;
+    search_service::validate_search_config_value(key, value)?;
+    columns::validate_config_value(key, value)?;
+    linear_service::validate_config_value(key, value)?;
+    jira_service::validate_config_value(key, value)?;
+    backup_service::validate_config_value(key, value)?;
+    rule_service::validate_config_value(key, value)?;
+    summary_service::validate_summary_config_value(key, value)?;
+    reminder_service::validate_config_value(key, value)?;
+    prompt_templates::validate_config_value(key, value)?;
+    Ok(())
+}
+
+/// A config file, parsed with spans so problems can point at a line
+struct Source<'a> {
+    path: &'a Path,
+    text: &'a str,
+    doc: Option<Spanned<DeTable<'a>>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Source<'a> {
+    fn new(path: &'a Path, text: &'a str) -> Self {
+        Self {
+            path,
+            text,
+            doc: DeTable::parse(text).ok(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Line of the setting at `key`, or of the closest enclosing one
+    fn line_of(&self, key: &[&str]) -> Option<usize> {
+        let doc = self.doc.as_ref()?;
+        // `None` stands for the document's root table
+        let mut current: Option<&DeValue> = None;
+        let mut offset = None;
+        for part in key {
+            let found = match current {
+                None => find_key(doc.get_ref(), part),
+                Some(DeValue::Table(table)) => find_key(table, part),
+                Some(DeValue::Array(items)) => part
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| items.get(i))
+                    .map(|item| (item.span().start, item)),
+                Some(_) => None,
+            };
+            let Some((start, item)) = found else {
+                break;
+            };
+            offset = Some(start);
+            current = Some(item.get_ref());
+        }
+        offset.map(|o| self.line_at(o))
+    }
+
+    fn line_at(&self, offset: usize) -> usize {
+        self.text[..offset.min(self.text.len())]
+            .matches('\n')
+            .count()
+            + 1
+    }
+
+    fn location(&self, line: Option<usize>) -> String {
+        match line {
+            Some(line) => format!("{}:{}", self.path.display(), line),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    fn report(&mut self, severity: Severity, key: &[&str], message: String) {
+        let location = self.location(self.line_of(key));
+        self.diagnostics.push(Diagnostic {
+            severity,
+            location,
+            message,
+        });
+    }
+
+    fn parse_error(mut self, error: toml::de::Error) -> Vec<Diagnostic> {
+        let line = error.span().map(|span| self.line_at(span.start));
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            location: self.location(line),
+            message: error.message().trim().to_string(),
+        });
+        self.diagnostics
+    }
+}
+
+fn find_key<'t, 'a>(
+    table: &'t DeTable<'a>,
+    key: &str,
+) -> Option<(usize, &'t Spanned<DeValue<'a>>)> {
+    table
+        .iter()
+        .find(|(k, _)| k.get_ref() == key)
+        .map(|(k, v)| (k.span().start, v))
+}
+
+/// Check the global config file
+pub fn check_global_config(path: &Path) -> Vec<Diagnostic> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let mut source = Source::new(path, &text);
+    let config: GlobalConfig = match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => return source.parse_error(e),
+    };
+
+    check_runners(&mut source, &[], &config.runners, &config.runners);
+    check_webhooks(&mut source, &[], &config.webhooks);
+    check_notifiers(&mut source, &[], &config.notifiers);
+    for (profile_name, profile) in &config.profiles {
+        let prefix = ["profiles", profile_name.as_str()];
+        let mut available = config.runners.clone();
+        available.extend(profile.runners.clone());
+        check_runners(&mut source, &prefix, &profile.runners, &available);
+        if let Some(webhooks) = &profile.webhooks {
+            check_webhooks(&mut source, &prefix, webhooks);
+        }
+        if let Some(notifiers) = &profile.notifiers {
+            check_notifiers(&mut source, &prefix, notifiers);
+        }
+    }
+
+    if let Err(e) = log_retention::RetentionLimits::from_config(&config.logs) {
+        source.report(Severity::Error, &["logs"], e.to_string());
+    }
+    if let Some(url) = &config.update.release_url
+        && let Some(problem) = url_problem(url)
+    {
+        source.report(
+            Severity::Error,
+            &["update", "release_url"],
+            format!("update.release_url {}", problem),
+        );
+    }
+    source.diagnostics
+}
+
+/// Check a workspace's `.granary/granary.toml`
+pub fn check_workspace_config(path: &Path) -> Vec<Diagnostic> {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let mut source = Source::new(path, &text);
+    let config: WorkspaceConfig = match toml::from_str(&text) {
+        Ok(config) => config,
+        Err(e) => return source.parse_error(e),
+    };
+
+    if let Err(invalid) = workspace_config::validate(&config) {
+        let key: Vec<&str> = invalid.key.iter().map(String::as_str).collect();
+        source.report(Severity::Error, &key, invalid.message);
+    }
+    // Workspace runners may extend global ones
+    let mut available = global_config_service::load()
+        .map(|global| global.runners)
+        .unwrap_or_default();
+    available.extend(config.runners.clone());
+    check_runners(&mut source, &[], &config.runners, &available);
+    source.diagnostics
+}
+
+/// Check a workspace's config keys, which may predate the validation
+/// `granary config set` does now or have been synced from elsewhere
+pub async fn check_config_keys(pool: &SqlitePool) -> Result<Vec<Diagnostic>> {
+    Ok(db::config::list(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(key, value)| {
+            validate_config_value(&key, &value)
+                .err()
+                .map(|e| Diagnostic {
+                    severity: Severity::Error,
+                    location: format!("config key {}", key),
+                    message: e.to_string(),
+                })
+        })
+        .collect())
+}
+
+/// Check the cron schedules and filters of a workspace's workers, which
+/// the daemon parses again whenever it restores them
+pub fn check_workers(workers: &[Worker]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for worker in workers {
+        let location = format!("worker {}", worker.id);
+        if let Some(schedule) = &worker.schedule
+            && let Err(e) = Schedule::parse(schedule)
+        {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                location: location.clone(),
+                message: e.to_string(),
+            });
+        }
+        if let Err(e) = parse_event_filters(&worker.filters_vec()) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                location,
+                message: e.to_string(),
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Check the `runners` of a file, resolving what they extend among
+/// `available`
+fn check_runners(
+    source: &mut Source,
+    prefix: &[&str],
+    runners: &HashMap<String, RunnerConfig>,
+    available: &HashMap<String, RunnerConfig>,
+) {
+    for (name, runner) in runners {
+        let key = [prefix, &["runners", name]].concat();
+        if let Err(message) = resolve_runner(available, name) {
+            let extends_key = [key.as_slice(), &["extends"]].concat();
+            source.report(Severity::Error, &extends_key, message);
+        }
+
+        // An inherited command or event is reported where it is set
+        let command = expand_env_vars(&runner.command);
+        if !command.trim().is_empty() && find_command(&command, Path::new(".")).is_none() {
+            let command_key = [key.as_slice(), &["command"]].concat();
+            source.report(
+                Severity::Warning,
+                &command_key,
+                format!(
+                    "runner '{}': command '{}' was not found on PATH",
+                    name, command
+                ),
+            );
+        }
+        if let Some(on) = &runner.on
+            && let Ok(EventType::Custom(_)) = on.parse::<EventType>()
+        {
+            let on_key = [key.as_slice(), &["on"]].concat();
+            source.report(
+                Severity::Warning,
+                &on_key,
+                format!(
+                    "runner '{}': '{}' is not a built-in event type, so only custom events will match it",
+                    name, on
+                ),
+            );
+        }
+    }
+}
+
+fn check_webhooks(source: &mut Source, prefix: &[&str], webhooks: &[WebhookConfig]) {
+    for (i, webhook) in webhooks.iter().enumerate() {
+        if let Some(problem) = url_problem(&expand_env_vars(&webhook.url)) {
+            let index = i.to_string();
+            let key = [prefix, &["webhooks", &index, "url"]].concat();
+            source.report(Severity::Error, &key, format!("webhook URL {}", problem));
+        }
+    }
+}
+
+fn check_notifiers(source: &mut Source, prefix: &[&str], notifiers: &[NotifierConfig]) {
+    for (i, notifier) in notifiers.iter().enumerate() {
+        let index = i.to_string();
+        if let Some(problem) = url_problem(&expand_env_vars(&notifier.url)) {
+            let key = [prefix, &["notifiers", &index, "url"]].concat();
+            source.report(Severity::Error, &key, format!("notifier URL {}", problem));
+        }
+        for (j, rule) in notifier.rules.iter().enumerate() {
+            if let Err(e) = filter::parse_filters(&rule.filters) {
+                let rule_index = j.to_string();
+                let key = [prefix, &["notifiers", &index, "rules", &rule_index]].concat();
+                source.report(Severity::Error, &key, e.to_string());
+            }
+        }
+    }
+}
+
+/// What is wrong with a URL that must be HTTP(S), if anything
+fn url_problem(url: &str) -> Option<String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => None,
+        Ok(parsed) => Some(format!(
+            "'{}' must use http or https, not {}",
+            url,
+            parsed.scheme()
+        )),
+        Err(e) => Some(format!("'{}' is invalid: {}", url, e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_global_config_diagnostics_have_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"[runners.ok]
+command = "sh"
+
+[runners.missing]
+command = "definitely-not-a-granary-command"
+
+[[webhooks]]
+url = "ftp://example.com/hook"
+
+[[notifiers]]
+kind = "slack"
+url = "https://hooks.slack.com/services/x"
+
+[[notifiers.rules]]
+event = "comment.created"
+filters = ["handoff"]
+"#,
+        )
+        .unwrap();
+
+        let diagnostics = check_global_config(&path);
+        let found = |needle: &str| {
+            diagnostics
+                .iter()
+                .find(|d| d.message.contains(needle))
+                .unwrap_or_else(|| panic!("no diagnostic for {needle}: {diagnostics:?}"))
+        };
+        let missing = found("definitely-not-a-granary-command");
+        assert_eq!(missing.severity, Severity::Warning);
+        assert!(missing.location.ends_with("config.toml:5"));
+        assert!(found("ftp").location.ends_with("config.toml:8"));
+        assert!(found("handoff").location.ends_with("config.toml:14"));
+        assert!(!diagnostics.iter().any(|d| d.message.contains("'ok'")));
+    }
+
+    #[test]
+    fn test_parse_errors_and_invalid_settings() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("granary.toml");
+
+        std::fs::write(&path, "[output]\nformat = \"xml\"\n").unwrap();
+        let diagnostics = check_workspace_config(&path);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].location.ends_with("granary.toml:2"));
+
+        std::fs::write(&path, "[defaults]\npriority = \n").unwrap();
+        let diagnostics = check_workspace_config(&path);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(diagnostics[0].location.ends_with("granary.toml:2"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Section selection for `granary context`.
+//!
+//! `--include` and `--exclude` take comma-separated section specs. A spec is
+//! a section name followed by `:`-separated filters and an optional limit, in
+//! the worker filter syntax:
+//!
+//! - `tasks:status=in_progress:limit=10` - only in-progress tasks, at most 10
+//! - `comments:type=decision` - only decision comments (`type` means `kind`)
+//!
+//! An excluded section without filters is left out entirely; with filters,
+//! only the items matching them are dropped.
+
+use serde::Serialize;
+
+use crate::error::{GranaryError, Result};
+use crate::services::filter::{self, Filter};
+
+/// Sections of a context pack, in output order
+pub const CONTEXT_SECTIONS: [&str; 8] = [
+    "projects",
+    "tasks",
+    "comments",
+    "decisions",
+    "blockers",
+    "artifacts",
+    "attachments",
+    "related",
+];
+
+/// One section spec from `--include` or `--exclude`
+#[derive(Debug, Clone)]
+pub struct SectionSpec {
+    pub section: String,
+    pub filters: Vec<Filter>,
+    pub limit: Option<usize>,
+}
+
+impl std::str::FromStr for SectionSpec {
+    type Err = GranaryError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(':').map(str::trim);
+        let section = parts.next().unwrap_or_default().to_lowercase();
+        if !CONTEXT_SECTIONS.contains(&section.as_str()) {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Unknown context section '{}'. Expected one of: {}",
+                section,
+                CONTEXT_SECTIONS.join(", ")
+            )));
+        }
+        let mut filters = Vec::new();
+        let mut limit = None;
+        for part in parts.filter(|p| !p.is_empty()) {
+            if let Some(value) = part.strip_prefix("limit=") {
+                limit = Some(value.trim().parse().map_err(|_| {
+                    GranaryError::InvalidArgument(format!(
+                        "Invalid limit '{}' for {}. Expected a number",
+                        value, section
+                    ))
+                })?);
+                continue;
+            }
+            let mut filter = Filter::parse(part)?;
+            if filter.field == "type" && matches!(section.as_str(), "comments" | "decisions") {
+                filter.field = "kind".to_string();
+            }
+            filters.push(filter);
+        }
+        Ok(SectionSpec {
+            section,
+            filters,
+            limit,
+        })
+    }
+}
+
+/// Which sections, and which items in them, go into a context pack
+#[derive(Debug, Clone, Default)]
+pub struct ContextSelection {
+    /// Sections to include; every section when None
+    include: Option<Vec<SectionSpec>>,
+    exclude: Vec<SectionSpec>,
+}
+
+impl ContextSelection {
+    /// Parse `--include` and `--exclude` values
+    pub fn parse(include: Option<&str>, exclude: Option<&str>) -> Result<Self> {
+        Ok(ContextSelection {
+            include: include.map(parse_specs).transpose()?,
+            exclude: exclude.map(parse_specs).transpose()?.unwrap_or_default(),
+        })
+    }
+
+    /// Whether any of `section` goes into the pack
+    pub fn includes(&self, section: &str) -> bool {
+        let included = match &self.include {
+            Some(specs) => specs.iter().any(|s| s.section == section),
+            None => true,
+        };
+        included
+            && !self
+                .exclude
+                .iter()
+                .any(|s| s.section == section && s.filters.is_empty())
+    }
+
+    /// Keep the items of `section` that match its include filters and none
+    /// of its exclude filters, up to its limit or `max`
+    pub fn select<T: Serialize>(&self, section: &str, items: Vec<T>, max: usize) -> Vec<T> {
+        if !self.includes(section) {
+            return Vec::new();
+        }
+        let include: Vec<&SectionSpec> = self
+            .include
+            .iter()
+            .flatten()
+            .filter(|s| s.section == section)
+            .collect();
+        let exclude: Vec<&SectionSpec> = self
+            .exclude
+            .iter()
+            .filter(|s| s.section == section)
+            .collect();
+        let limit = include.iter().filter_map(|s| s.limit).max().unwrap_or(max);
+
+        items
+            .into_iter()
+            .filter(|item| {
+                let Ok(payload) = serde_json::to_value(item) else {
+                    return false;
+                };
+                let included = include.is_empty()
+                    || include
+                        .iter()
+                        .any(|s| filter::matches_all(&s.filters, &payload));
+                included
+                    && !exclude
+                        .iter()
+                        .any(|s| filter::matches_all(&s.filters, &payload))
+            })
+            .take(limit)
+            .collect()
+    }
+}
+
+fn parse_specs(specs: &str) -> Result<Vec<SectionSpec>> {
+    specs
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_selection_filters_and_limits_sections() {
+        let selection = ContextSelection::parse(
+            Some("tasks:status=in_progress:limit=1,comments:type=decision,related"),
+            Some("related,comments:author=bot"),
+        )
+        .unwrap();
+        assert!(selection.includes("tasks"));
+        assert!(!selection.includes("related"));
+        assert!(!selection.includes("projects"));
+
+        let tasks = vec![
+            json!({ "id": "t1", "status": "todo" }),
+            json!({ "id": "t2", "status": "in_progress" }),
+            json!({ "id": "t3", "status": "in_progress" }),
+        ];
+        let ids = |items: Vec<Value>| -> Vec<String> {
+            items
+                .iter()
+                .map(|i| i["id"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(ids(selection.select("tasks", tasks, 50)), vec!["t2"]);
+
+        let comments = vec![
+            json!({ "id": "c1", "kind": "note", "author": "alice" }),
+            json!({ "id": "c2", "kind": "decision", "author": "bot" }),
+            json!({ "id": "c3", "kind": "decision", "author": "alice" }),
+        ];
+        assert_eq!(ids(selection.select("comments", comments, 50)), vec!["c3"]);
+
+        assert!(ContextSelection::parse(Some("tickets"), None).is_err());
+        assert!(ContextSelection::parse(Some("tasks:limit=ten"), None).is_err());
+    }
+}
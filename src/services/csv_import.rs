@@ -0,0 +1,472 @@
+//! Importing CSV exports from other trackers with `granary import csv`.
+//!
+//! Trello, Asana and most other tools can export a board as CSV, each with
+//! their own column names and status values. A TOML mapping file says which
+//! column holds each task field and how the tool's values translate:
+//!
+//! ```toml
+//! [columns]
+//! title = "Card Name"
+//! description = "Card Description"
+//! status = "List Name"
+//! labels = "Labels"
+//! due = "Due Date"
+//!
+//! [status]
+//! "Backlog" = "todo"
+//! "Doing" = "in_progress"
+//! "Done" = "done"
+//! ```
+//!
+//! Every row is checked before any task is created, so a value the mapping
+//! does not cover fails the import without leaving half a backlog behind.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::{datetime, label_service, project_service, task_service};
+
+/// Which column holds each task field
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnMapping {
+    pub title: String,
+    pub description: Option<String>,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub labels: Option<String>,
+    pub due: Option<String>,
+}
+
+/// How the columns of a CSV export become task fields
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CsvMapping {
+    /// Field separator (default `,`)
+    pub delimiter: Option<char>,
+    /// Separator between the labels in the labels column (default `,`)
+    pub label_separator: Option<String>,
+    /// chrono format of the due column, for dates that are not RFC 3339 or
+    /// `YYYY-MM-DD`, e.g. `%m/%d/%Y`
+    pub date_format: Option<String>,
+    pub columns: ColumnMapping,
+    /// Status column values to task statuses. `""` maps empty cells and
+    /// `"*"` any value not listed; values that are already task statuses
+    /// need no entry. Empty cells default to todo.
+    #[serde(default)]
+    pub status: BTreeMap<String, String>,
+    /// Priority column values to task priorities, like `[status]`
+    #[serde(default)]
+    pub priority: BTreeMap<String, String>,
+}
+
+impl CsvMapping {
+    /// Parse a mapping file
+    pub fn parse(input: &str) -> Result<Self> {
+        let mapping: CsvMapping = toml::from_str(input)
+            .map_err(|e| GranaryError::InvalidArgument(format!("Invalid mapping: {}", e)))?;
+        for (value, status) in &mapping.status {
+            status.parse::<TaskStatus>().map_err(|_| {
+                GranaryError::InvalidArgument(format!(
+                    "Invalid mapping: status '{}' for '{}' is not a task status",
+                    status, value
+                ))
+            })?;
+        }
+        for (value, priority) in &mapping.priority {
+            priority.parse::<TaskPriority>().map_err(|_| {
+                GranaryError::InvalidArgument(format!(
+                    "Invalid mapping: priority '{}' for '{}' is not a task priority",
+                    priority, value
+                ))
+            })?;
+        }
+        Ok(mapping)
+    }
+}
+
+/// A task read from one CSV row
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvTask {
+    /// Number of the row, counting the header as row 1
+    pub row: usize,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: TaskStatus,
+    pub priority: TaskPriority,
+    pub labels: Vec<String>,
+    pub due_at: Option<String>,
+}
+
+/// Split CSV text into records, following RFC 4180: fields may be quoted,
+/// quoted fields may hold delimiters, newlines and doubled quotes
+pub fn parse_csv(input: &str, delimiter: char) -> Result<Vec<Vec<String>>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            records.push(std::mem::take(&mut record));
+        } else {
+            field.push(c);
+        }
+    }
+    if in_quotes {
+        return Err(GranaryError::InvalidArgument(
+            "Invalid CSV: a quoted field is not closed".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    // Blank lines hold a single empty field
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    Ok(records)
+}
+
+/// Read the tasks of a CSV export through a mapping. Rows with an empty
+/// title are skipped; any other row the mapping cannot translate is an
+/// error naming its row.
+pub fn read_tasks(input: &str, mapping: &CsvMapping) -> Result<Vec<CsvTask>> {
+    let mut records = parse_csv(input, mapping.delimiter.unwrap_or(','))?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| GranaryError::InvalidArgument("The CSV file is empty".to_string()))?;
+    let positions: HashMap<&str, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim(), i))
+        .collect();
+    let column = |name: &Option<String>| -> Result<Option<usize>> {
+        name.as_deref()
+            .map(|name| {
+                positions.get(name).copied().ok_or_else(|| {
+                    GranaryError::InvalidArgument(format!(
+                        "Column '{}' is not in the CSV header. Columns: {}",
+                        name,
+                        header.join(", ")
+                    ))
+                })
+            })
+            .transpose()
+    };
+    let columns = &mapping.columns;
+    let title = column(&Some(columns.title.clone()))?.expect("title column");
+    let description = column(&columns.description)?;
+    let status = column(&columns.status)?;
+    let priority = column(&columns.priority)?;
+    let labels = column(&columns.labels)?;
+    let due = column(&columns.due)?;
+
+    let separator = mapping.label_separator.as_deref().unwrap_or(",");
+    let settings = datetime::settings();
+    let mut tasks = Vec::new();
+    for (index, record) in records.enumerate() {
+        let row = index + 2;
+        let cell = |position: Option<usize>| {
+            position
+                .and_then(|i| record.get(i))
+                .map(|value| value.trim())
+                .unwrap_or_default()
+        };
+        let in_row = |e: GranaryError| match e {
+            GranaryError::InvalidArgument(message) => {
+                GranaryError::InvalidArgument(format!("Row {}: {}", row, message))
+            }
+            e => e,
+        };
+
+        let title = cell(Some(title));
+        if title.is_empty() {
+            continue;
+        }
+        tasks.push(CsvTask {
+            row,
+            title: title.to_string(),
+            description: Some(cell(description))
+                .filter(|d| !d.is_empty())
+                .map(str::to_string),
+            status: translate(cell(status), &mapping.status, "status", TaskStatus::Todo)
+                .map_err(in_row)?,
+            priority: translate(
+                cell(priority),
+                &mapping.priority,
+                "priority",
+                TaskPriority::P2,
+            )
+            .map_err(in_row)?,
+            labels: cell(labels)
+                .split(separator)
+                .map(label_service::label_name)
+                .filter(|l| !l.is_empty())
+                .collect(),
+            due_at: parse_due(cell(due), mapping.date_format.as_deref(), &settings)
+                .map_err(in_row)?,
+        });
+    }
+    Ok(tasks)
+}
+
+/// Translate a cell through a value mapping, falling back to the cell as a
+/// value of its own; empty cells get the default
+fn translate<T: std::str::FromStr>(
+    value: &str,
+    mapping: &BTreeMap<String, String>,
+    what: &str,
+    default: T,
+) -> Result<T> {
+    let mapped = mapping
+        .get(value)
+        .or_else(|| (!value.is_empty()).then(|| mapping.get("*")).flatten());
+    if let Some(mapped) = mapped {
+        // Mapped values were checked when the mapping was parsed
+        return Ok(mapped.parse().unwrap_or(default));
+    }
+    if value.is_empty() {
+        return Ok(default);
+    }
+    value.parse().map_err(|_| {
+        GranaryError::InvalidArgument(format!(
+            "Unknown {} '{}'. Map it under [{}] in the mapping file",
+            what, value, what
+        ))
+    })
+}
+
+fn parse_due(
+    value: &str,
+    date_format: Option<&str>,
+    settings: &datetime::DateSettings,
+) -> Result<Option<String>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    if let Some(format) = date_format {
+        // Reformat dates the tool writes its own way into ones parse_due reads
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            let local = date.format("%Y-%m-%dT%H:%M:%S").to_string();
+            return datetime::parse_due(&local, settings).map(Some);
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return datetime::parse_due(&date.to_string(), settings).map(Some);
+        }
+    }
+    datetime::parse_due(value, settings).map(Some)
+}
+
+/// Create the tasks read from a CSV export in a project
+pub async fn import_tasks(
+    pool: &SqlitePool,
+    project_id: &str,
+    source: &str,
+    tasks: &[CsvTask],
+    dry_run: bool,
+) -> Result<ImportReport> {
+    let project = project_service::get_project(pool, project_id).await?;
+    let mut report = ImportReport {
+        source: format!("csv:{}", source),
+        project_id: project.id.clone(),
+        dry_run,
+        items: Vec::new(),
+    };
+
+    for csv in tasks {
+        let mut item = ImportedItem {
+            source: format!("row {}", csv.row),
+            title: csv.title.clone(),
+            task_id: None,
+            status: csv.status.as_str().to_string(),
+            labels: csv.labels.clone(),
+            comments: 0,
+            skipped: false,
+        };
+        if dry_run {
+            report.items.push(item);
+            continue;
+        }
+
+        let task = task_service::create_task(
+            pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: csv.title.clone(),
+                description: csv.description.clone(),
+                priority: csv.priority.clone(),
+                due_at: csv.due_at.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        if !csv.labels.is_empty() {
+            db::labels::apply(pool, &task.id, &csv.labels, &[]).await?;
+        }
+        match csv.status {
+            TaskStatus::Draft => {}
+            TaskStatus::Done => {
+                task_service::complete_task(pool, &task.id, None, None).await?;
+            }
+            ref status => {
+                task_service::update_task(
+                    pool,
+                    &task.id,
+                    UpdateTask {
+                        status: Some(status.clone()),
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await?;
+            }
+        }
+        item.task_id = Some(task.id);
+        report.items.push(item);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use tempfile::TempDir;
+
+    const TRELLO: &str = "\u{feff}Card ID,Card Name,Card Description,Labels,List Name,Due Date\r\n\
+1,Fix login,\"Fails with \"\"invalid token\"\",\nsometimes\",\"bug, high priority\",Doing,2026-03-01\r\n\
+2,Write docs,,,Backlog,\r\n\
+3,,Empty title is skipped,,Backlog,\r\n\
+\r\n\
+4,Ship it,,release,Done,2026-03-05T12:00:00Z\r\n";
+
+    const MAPPING: &str = r#"
+[columns]
+title = "Card Name"
+description = "Card Description"
+status = "List Name"
+labels = "Labels"
+due = "Due Date"
+
+[status]
+Backlog = "todo"
+Doing = "in_progress"
+"#;
+
+    #[test]
+    fn test_parse_csv() {
+        let records = parse_csv("a,\"b,c\",\"d\"\"e\"\n\"multi\nline\",,\n", ',').unwrap();
+        assert_eq!(
+            records,
+            vec![vec!["a", "b,c", "d\"e"], vec!["multi\nline", "", ""]]
+        );
+        assert_eq!(parse_csv("x;y", ';').unwrap(), vec![vec!["x", "y"]]);
+        assert!(parse_csv("\"open", ',').is_err());
+    }
+
+    #[test]
+    fn test_read_tasks_through_mapping() {
+        let mapping = CsvMapping::parse(MAPPING).unwrap();
+        let tasks = read_tasks(TRELLO, &mapping).unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Fix login", "Write docs", "Ship it"]);
+
+        let login = &tasks[0];
+        assert_eq!(login.status, TaskStatus::InProgress);
+        assert_eq!(login.labels, vec!["bug", "high-priority"]);
+        assert_eq!(
+            login.description.as_deref(),
+            Some("Fails with \"invalid token\",\nsometimes")
+        );
+        assert!(login.due_at.as_deref().unwrap().starts_with("2026-03-0"));
+        // "Done" is a task status already, so it needs no mapping
+        assert_eq!(tasks[2].status, TaskStatus::Done);
+        assert_eq!(tasks[1].due_at, None);
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        assert!(CsvMapping::parse("[columns]\ndescription = \"Notes\"\n").is_err());
+        assert!(
+            CsvMapping::parse("[columns]\ntitle = \"Name\"\n[status]\nDoing = \"busy\"\n").is_err()
+        );
+
+        let mapping =
+            CsvMapping::parse("[columns]\ntitle = \"Name\"\nstatus = \"Stage\"\n").unwrap();
+        let err = read_tasks("Name,Stage\nA,todo\nB,Review\n", &mapping).unwrap_err();
+        assert!(err.to_string().contains("Row 3"), "{}", err);
+        assert!(err.to_string().contains("Review"), "{}", err);
+
+        let mapping = CsvMapping::parse("[columns]\ntitle = \"Title\"\n").unwrap();
+        let err = read_tasks("Name\nA\n", &mapping).unwrap_err();
+        assert!(err.to_string().contains("'Title'"), "{}", err);
+
+        // Asana marks completion with a date: any value means done
+        let mapping = CsvMapping::parse(
+            "[columns]\ntitle = \"Name\"\nstatus = \"Completed At\"\n[status]\n\"*\" = \"done\"\n\"\" = \"todo\"\n",
+        )
+        .unwrap();
+        let tasks = read_tasks("Name,Completed At\nA,2026-01-02\nB,\n", &mapping).unwrap();
+        assert_eq!(tasks[0].status, TaskStatus::Done);
+        assert_eq!(tasks[1].status, TaskStatus::Todo);
+    }
+
+    #[tokio::test]
+    async fn test_import_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = project_service::create_project(
+            &pool,
+            CreateProject {
+                name: "Board".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mapping = CsvMapping::parse(MAPPING).unwrap();
+        let tasks = read_tasks(TRELLO, &mapping).unwrap();
+        let report = import_tasks(&pool, &project.id, "board.csv", &tasks, false)
+            .await
+            .unwrap();
+        assert_eq!(report.imported(), 3);
+
+        let login = task_service::get_task(&pool, report.items[0].task_id.as_ref().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(login.status, "in_progress");
+        assert_eq!(login.labels, vec!["bug", "high-priority"]);
+        assert!(login.due_at.is_some());
+        let shipped = task_service::get_task(&pool, report.items[2].task_id.as_ref().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(shipped.status, "done");
+    }
+}
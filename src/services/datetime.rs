@@ -0,0 +1,441 @@
+//! Timezone-aware date handling.
+//!
+//! Resolves the effective timezone and first day of the week from workspace
+//! config (`date.timezone`, `date.week_start`), falling back to the global
+//! config (`[date]` in `~/.granary/config.toml`) and finally to UTC/Monday.
+//!
+//! These settings drive due-date parsing ("friday", "next week") and the
+//! rendering of timestamps in human-readable output. Stored timestamps are
+//! always RFC 3339 in UTC.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::services::global_config as global_config_service;
+
+/// Workspace config key for the timezone
+pub const TIMEZONE_KEY: &str = "date.timezone";
+
+/// Workspace config key for the first day of the week
+pub const WEEK_START_KEY: &str = "date.week_start";
+
+/// Effective date settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSettings {
+    pub timezone: Tz,
+    pub week_start: Weekday,
+}
+
+impl Default for DateSettings {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            week_start: Weekday::Mon,
+        }
+    }
+}
+
+impl DateSettings {
+    /// Build settings from optional raw values, validating each
+    pub fn from_values(timezone: Option<&str>, week_start: Option<&str>) -> Result<Self> {
+        let mut settings = Self::default();
+        if let Some(tz) = timezone {
+            settings.timezone = parse_timezone(tz)?;
+        }
+        if let Some(day) = week_start {
+            settings.week_start = parse_weekday(day).ok_or_else(|| {
+                GranaryError::InvalidArgument(format!("Invalid week start day: {}", day))
+            })?;
+        }
+        Ok(settings)
+    }
+
+    /// Today's date in the configured timezone
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// First day of the week containing `date`
+    pub fn start_of_week(&self, date: NaiveDate) -> NaiveDate {
+        let offset = (7 + date.weekday().num_days_from_monday()
+            - self.week_start.num_days_from_monday())
+            % 7;
+        date - Duration::days(offset as i64)
+    }
+
+    /// Convert a local date and time to a UTC RFC 3339 string
+    fn local_to_utc_string(&self, local: NaiveDateTime) -> String {
+        let resolved = self
+            .timezone
+            .from_local_datetime(&local)
+            .earliest()
+            // Skipped by a DST transition; shift forward an hour
+            .or_else(|| {
+                self.timezone
+                    .from_local_datetime(&(local + Duration::hours(1)))
+                    .earliest()
+            })
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local));
+        resolved.to_rfc3339()
+    }
+}
+
+static DISPLAY_SETTINGS: OnceLock<DateSettings> = OnceLock::new();
+
+/// Resolve date settings for a workspace, falling back to global config.
+pub async fn resolve(pool: Option<&SqlitePool>) -> Result<DateSettings> {
+    let global = global_config_service::load()
+        .map(|c| c.date)
+        .unwrap_or_default();
+
+    let (mut timezone, mut week_start) = (global.timezone, global.week_start);
+    if let Some(pool) = pool {
+        if let Some(tz) = db::config::get(pool, TIMEZONE_KEY).await? {
+            timezone = Some(tz);
+        }
+        if let Some(day) = db::config::get(pool, WEEK_START_KEY).await? {
+            week_start = Some(day);
+        }
+    }
+
+    DateSettings::from_values(timezone.as_deref(), week_start.as_deref())
+}
+
+/// Resolve and install the process-wide settings used for rendering.
+///
+/// Invalid configuration falls back to defaults so that output never fails
+/// because of a bad setting; `granary config set` validates values up front.
+pub async fn init(pool: Option<&SqlitePool>) -> DateSettings {
+    let settings = resolve(pool).await.unwrap_or_default();
+    let _ = DISPLAY_SETTINGS.set(settings);
+    settings
+}
+
+/// The process-wide settings, or defaults if `init` has not been called
+pub fn settings() -> DateSettings {
+    DISPLAY_SETTINGS.get().copied().unwrap_or_default()
+}
+
+/// Validate a value for a date-related config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    match key {
+        TIMEZONE_KEY => parse_timezone(value).map(|_| ()),
+        WEEK_START_KEY => parse_weekday(value).map(|_| ()).ok_or_else(|| {
+            GranaryError::InvalidArgument(format!("Invalid week start day: {}", value))
+        }),
+        _ => Ok(()),
+    }
+}
+
+fn parse_timezone(value: &str) -> Result<Tz> {
+    value
+        .parse::<Tz>()
+        .map_err(|_| GranaryError::InvalidArgument(format!("Invalid timezone: {}", value)))
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    match value.trim().to_lowercase().as_str() {
+        "mon" | "monday" => Some(Weekday::Mon),
+        "tue" | "tues" | "tuesday" => Some(Weekday::Tue),
+        "wed" | "wednesday" => Some(Weekday::Wed),
+        "thu" | "thur" | "thurs" | "thursday" => Some(Weekday::Thu),
+        "fri" | "friday" => Some(Weekday::Fri),
+        "sat" | "saturday" => Some(Weekday::Sat),
+        "sun" | "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parse a due date expression into a UTC RFC 3339 timestamp.
+///
+/// Accepts RFC 3339 timestamps, `YYYY-MM-DD`, `YYYY-MM-DD HH:MM`, and
+/// relative expressions interpreted in the configured timezone:
+/// `today`, `tomorrow`, weekday names (`friday`), `next friday`,
+/// `next week`, `end of week`, `in 3 days`, `+2w`.
+///
+/// Dates without a time resolve to the end of that day, so a task due
+/// "friday" is not overdue until Friday is over.
+pub fn parse_due(input: &str, settings: &DateSettings) -> Result<String> {
+    parse_due_relative_to(input, settings, settings.today())
+}
+
+/// Like [`parse_due`], with an explicit "today" for deterministic results
+pub fn parse_due_relative_to(
+    input: &str,
+    settings: &DateSettings,
+    today: NaiveDate,
+) -> Result<String> {
+    let trimmed = input.trim();
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(trimmed) {
+        return Ok(dt.with_timezone(&Utc).to_rfc3339());
+    }
+    for fmt in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(trimmed, fmt) {
+            return Ok(settings.local_to_utc_string(local));
+        }
+    }
+
+    let date = parse_date_expr(&trimmed.to_lowercase(), settings, today)
+        .ok_or_else(|| GranaryError::InvalidArgument(format!("Invalid due date: {}", input)))?;
+    let end_of_day = date.and_hms_opt(23, 59, 59).expect("valid time");
+    Ok(settings.local_to_utc_string(end_of_day))
+}
+
+fn parse_date_expr(input: &str, settings: &DateSettings, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let start_of_next_week = settings.start_of_week(today) + Duration::days(7);
+    match input {
+        "today" => return Some(today),
+        "tomorrow" => return Some(today + Duration::days(1)),
+        "yesterday" => return Some(today - Duration::days(1)),
+        "next week" => return Some(start_of_next_week),
+        "end of week" | "eow" | "this week" => {
+            return Some(start_of_next_week - Duration::days(1));
+        }
+        _ => {}
+    }
+
+    // "next friday" - that weekday in the following week
+    if let Some(day) = input.strip_prefix("next ").and_then(parse_weekday) {
+        let offset =
+            (7 + day.num_days_from_monday() - settings.week_start.num_days_from_monday()) % 7;
+        return Some(start_of_next_week + Duration::days(offset as i64));
+    }
+
+    // "friday" - the next occurrence, including today
+    if let Some(day) = parse_weekday(input) {
+        let offset = (7 + day.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+        return Some(today + Duration::days(offset as i64));
+    }
+
+    // "in 3 days", "in 2 weeks", "+3d", "+2w"
+    let spec = input
+        .strip_prefix("in ")
+        .or_else(|| input.strip_prefix('+'))?
+        .trim();
+    let split = spec.find(|c: char| !c.is_ascii_digit())?;
+    let (count, unit) = spec.split_at(split);
+    let count: i64 = count.parse().ok()?;
+    let offset = match unit.trim() {
+        "d" | "day" | "days" => Duration::try_days(count)?,
+        "w" | "week" | "weeks" => Duration::try_weeks(count)?,
+        _ => return None,
+    };
+    // Past the supported date range, the date is as invalid as a typo
+    today.checked_add_signed(offset)
+}
+
+/// Parse a duration such as `30s`, `15m`, `2h`, `7d` or `1w`
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let trimmed = input.trim().to_lowercase();
+    let split = trimmed.find(|c: char| !c.is_ascii_digit())?;
+    if split == 0 {
+        return None;
+    }
+    let (count, unit) = trimmed.split_at(split);
+    let count: i64 = count.parse().ok()?;
+    match unit.trim() {
+        "s" | "sec" | "secs" => Duration::try_seconds(count),
+        "m" | "min" | "mins" => Duration::try_minutes(count),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(count),
+        "d" | "day" | "days" => Duration::try_days(count),
+        "w" | "week" | "weeks" => Duration::try_weeks(count),
+        _ => None,
+    }
+}
+
+/// Parse a "since" expression into a UTC RFC 3339 timestamp.
+///
+/// Accepts a duration ago (`30s`, `15m`, `2h`, `7d`, `1w`) or anything
+/// [`parse_due`] accepts, where dates without a time mean the start of
+/// that day.
+pub fn parse_since(input: &str, settings: &DateSettings) -> Result<String> {
+    parse_since_relative_to(input, settings, Utc::now())
+}
+
+/// Like [`parse_since`], with an explicit "now" for deterministic results
+pub fn parse_since_relative_to(
+    input: &str,
+    settings: &DateSettings,
+    now: DateTime<Utc>,
+) -> Result<String> {
+    let trimmed = input.trim().to_lowercase();
+    let invalid = || GranaryError::InvalidArgument(format!("Invalid time: {}", input));
+
+    if let Some(duration) = parse_duration(&trimmed) {
+        let since = now.checked_sub_signed(duration).ok_or_else(invalid)?;
+        return Ok(since.to_rfc3339());
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input.trim()) {
+        return Ok(dt.with_timezone(&Utc).to_rfc3339());
+    }
+    for fmt in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(local) = NaiveDateTime::parse_from_str(input.trim(), fmt) {
+            return Ok(settings.local_to_utc_string(local));
+        }
+    }
+    let today = now.with_timezone(&settings.timezone).date_naive();
+    let date = parse_date_expr(&trimmed, settings, today).ok_or_else(invalid)?;
+    Ok(settings.local_to_utc_string(date.and_hms_opt(0, 0, 0).expect("valid time")))
+}
+
+/// Render an RFC 3339 timestamp as a local date (`YYYY-MM-DD`)
+pub fn format_local_date(iso: &str) -> String {
+    match DateTime::parse_from_rfc3339(iso) {
+        Ok(dt) => dt
+            .with_timezone(&settings().timezone)
+            .format("%Y-%m-%d")
+            .to_string(),
+        Err(_) if iso.len() >= 10 => iso[..10].to_string(),
+        Err(_) => iso.to_string(),
+    }
+}
+
+/// Render an RFC 3339 timestamp as a local date and time
+pub fn format_local_datetime(iso: &str) -> String {
+    match DateTime::parse_from_rfc3339(iso) {
+        Ok(dt) => dt
+            .with_timezone(&settings().timezone)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string(),
+        Err(_) => iso.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_from_values() {
+        let settings = DateSettings::from_values(Some("Europe/London"), Some("sunday")).unwrap();
+        assert_eq!(settings.timezone, chrono_tz::Europe::London);
+        assert_eq!(settings.week_start, Weekday::Sun);
+
+        assert!(DateSettings::from_values(Some("Mars/Olympus"), None).is_err());
+        assert!(DateSettings::from_values(None, Some("someday")).is_err());
+    }
+
+    #[test]
+    fn test_start_of_week() {
+        // 2026-01-14 is a Wednesday
+        let monday = DateSettings::default();
+        assert_eq!(monday.start_of_week(date("2026-01-14")), date("2026-01-12"));
+
+        let sunday = DateSettings::from_values(None, Some("sun")).unwrap();
+        assert_eq!(sunday.start_of_week(date("2026-01-14")), date("2026-01-11"));
+        assert_eq!(sunday.start_of_week(date("2026-01-11")), date("2026-01-11"));
+    }
+
+    #[test]
+    fn test_parse_due_relative() {
+        let settings = DateSettings::default();
+        let today = date("2026-01-14"); // Wednesday
+
+        let parse = |s: &str| parse_due_relative_to(s, &settings, today).unwrap();
+        assert_eq!(parse("today"), "2026-01-14T23:59:59+00:00");
+        assert_eq!(parse("tomorrow"), "2026-01-15T23:59:59+00:00");
+        assert_eq!(parse("friday"), "2026-01-16T23:59:59+00:00");
+        assert_eq!(parse("Wednesday"), "2026-01-14T23:59:59+00:00");
+        assert_eq!(parse("next week"), "2026-01-19T23:59:59+00:00");
+        assert_eq!(parse("next friday"), "2026-01-23T23:59:59+00:00");
+        assert_eq!(parse("end of week"), "2026-01-18T23:59:59+00:00");
+        assert_eq!(parse("in 3 days"), "2026-01-17T23:59:59+00:00");
+        assert_eq!(parse("+2w"), "2026-01-28T23:59:59+00:00");
+        assert_eq!(parse("2026-02-01"), "2026-02-01T23:59:59+00:00");
+
+        assert!(parse_due_relative_to("whenever", &settings, today).is_err());
+    }
+
+    #[test]
+    fn test_parse_out_of_range() {
+        let settings = DateSettings::default();
+        let today = date("2026-01-14");
+        let now = today.and_hms_opt(12, 0, 0).unwrap().and_utc();
+
+        for input in [
+            "+999999999999w",
+            "in 99999999999 days",
+            "+9223372036854775807d",
+        ] {
+            let err = parse_due_relative_to(input, &settings, today).unwrap_err();
+            assert!(matches!(err, GranaryError::InvalidArgument(_)), "{}", input);
+        }
+        assert!(parse_duration("9223372036854775807w").is_none());
+        let err = parse_since_relative_to("999999999w", &settings, now).unwrap_err();
+        assert!(matches!(err, GranaryError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let settings = DateSettings::default();
+        let now = DateTime::parse_from_rfc3339("2026-01-14T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let parse = |s: &str| parse_since_relative_to(s, &settings, now).unwrap();
+
+        assert_eq!(parse("2h"), "2026-01-14T10:00:00+00:00");
+        assert_eq!(parse("30 min"), "2026-01-14T11:30:00+00:00");
+        assert_eq!(parse("1w"), "2026-01-07T12:00:00+00:00");
+        assert_eq!(parse("yesterday"), "2026-01-13T00:00:00+00:00");
+        assert_eq!(parse("2026-01-01"), "2026-01-01T00:00:00+00:00");
+        assert!(parse_since_relative_to("2 fortnights", &settings, now).is_err());
+    }
+
+    #[test]
+    fn test_parse_due_week_start_sunday() {
+        let settings = DateSettings::from_values(None, Some("sunday")).unwrap();
+        let today = date("2026-01-14"); // Wednesday
+
+        let parse = |s: &str| parse_due_relative_to(s, &settings, today).unwrap();
+        assert_eq!(parse("next week"), "2026-01-18T23:59:59+00:00");
+        assert_eq!(parse("end of week"), "2026-01-17T23:59:59+00:00");
+    }
+
+    #[test]
+    fn test_parse_due_timezone() {
+        let settings = DateSettings::from_values(Some("America/New_York"), None).unwrap();
+        let today = date("2026-01-14");
+
+        // End of day in New York (UTC-5 in January) is early next morning UTC
+        assert_eq!(
+            parse_due_relative_to("today", &settings, today).unwrap(),
+            "2026-01-15T04:59:59+00:00"
+        );
+        assert_eq!(
+            parse_due_relative_to("2026-01-14 09:30", &settings, today).unwrap(),
+            "2026-01-14T14:30:00+00:00"
+        );
+        // Explicit offsets are preserved as the same instant
+        assert_eq!(
+            parse_due_relative_to("2026-01-14T09:30:00+01:00", &settings, today).unwrap(),
+            "2026-01-14T08:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value(TIMEZONE_KEY, "Asia/Tokyo").is_ok());
+        assert!(validate_config_value(TIMEZONE_KEY, "Nowhere").is_err());
+        assert!(validate_config_value(WEEK_START_KEY, "sat").is_ok());
+        assert!(validate_config_value(WEEK_START_KEY, "caturday").is_err());
+        assert!(validate_config_value("other.key", "anything").is_ok());
+    }
+}
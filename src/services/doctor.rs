@@ -0,0 +1,507 @@
+//! Repair checks for `granary doctor`.
+//!
+//! Each check reports what it found and, when `fix` is set, repairs it and
+//! reports what changed instead.
+
+use std::path::Path;
+
+use sqlx::SqlitePool;
+
+use crate::daemon::transport::is_process_alive;
+use crate::db;
+use crate::db::connection::{create_pool, run_migrations};
+use crate::models::Project;
+use crate::services::{DiagnosticResult, DiagnosticStatus};
+
+const FIX_HINT: &str = "run `granary doctor --fix` to repair";
+
+fn result(check: &str, status: DiagnosticStatus, message: impl Into<String>) -> DiagnosticResult {
+    DiagnosticResult {
+        check: check.to_string(),
+        status,
+        message: message.into(),
+    }
+}
+
+fn problem(check: &str, message: String) -> DiagnosticResult {
+    result(
+        check,
+        DiagnosticStatus::Warning,
+        format!("{} ({})", message, FIX_HINT),
+    )
+}
+
+/// Migrations sqlx recorded as failed. The database refuses to open until
+/// the row is removed, so this runs on a pool that skips migrations and
+/// re-runs them after the repair.
+pub async fn check_migrations(check: &str, db_path: &Path, fix: bool) -> DiagnosticResult {
+    let pool = match create_pool(db_path).await {
+        Ok(pool) => pool,
+        Err(e) => return result(check, DiagnosticStatus::Error, e.to_string()),
+    };
+    // The table is missing until the first migration has run
+    let failed: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT version, description FROM _sqlx_migrations WHERE success = 0 ORDER BY version",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap_or_default();
+
+    let outcome = if failed.is_empty() {
+        result(check, DiagnosticStatus::Ok, "All applied")
+    } else {
+        let names = failed
+            .iter()
+            .map(|(version, description)| format!("{} {}", version, description))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if !fix {
+            problem(check, format!("Failed: {}", names))
+        } else {
+            match retry_migrations(&pool).await {
+                Ok(()) => result(check, DiagnosticStatus::Fixed, format!("Re-ran {}", names)),
+                Err(e) => result(
+                    check,
+                    DiagnosticStatus::Error,
+                    format!("Re-running {} failed: {}", names, e),
+                ),
+            }
+        }
+    };
+    pool.close().await;
+    outcome
+}
+
+async fn retry_migrations(pool: &SqlitePool) -> crate::Result<()> {
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE success = 0")
+        .execute(pool)
+        .await?;
+    run_migrations(pool).await
+}
+
+/// Tasks whose project no longer exists. The projects are recreated as
+/// placeholders so the tasks and their history are kept.
+pub async fn check_orphaned_tasks(pool: &SqlitePool, fix: bool) -> DiagnosticResult {
+    let check = "Orphaned tasks";
+    let orphans: Vec<(String, i64)> = match sqlx::query_as(
+        r#"
+        SELECT project_id, COUNT(*) FROM tasks
+        WHERE project_id NOT IN (SELECT id FROM projects)
+        GROUP BY project_id
+        ORDER BY project_id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(orphans) => orphans,
+        Err(e) => return result(check, DiagnosticStatus::Error, e.to_string()),
+    };
+    if orphans.is_empty() {
+        return result(check, DiagnosticStatus::Ok, "None");
+    }
+
+    let task_count: i64 = orphans.iter().map(|(_, count)| count).sum();
+    let ids = orphans
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !fix {
+        return problem(
+            check,
+            format!("{} tasks in deleted projects: {}", task_count, ids),
+        );
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    for (project_id, _) in &orphans {
+        let slug = project_id
+            .rsplit_once('-')
+            .map_or(project_id.as_str(), |(slug, _)| slug);
+        let project = Project {
+            id: project_id.clone(),
+            slug: slug.to_string(),
+            name: project_id.clone(),
+            description: Some("Recreated by `granary doctor --fix`".to_string()),
+            owner: None,
+            status: "active".to_string(),
+            tags: None,
+            default_session_policy: None,
+            steering_refs: None,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            version: 1,
+            fields: Default::default(),
+        };
+        if let Err(e) = db::projects::create(pool, &project).await {
+            return result(check, DiagnosticStatus::Error, e.to_string());
+        }
+    }
+    result(
+        check,
+        DiagnosticStatus::Fixed,
+        format!(
+            "Recreated {} for {} tasks: {}",
+            plural(orphans.len(), "project"),
+            task_count,
+            ids
+        ),
+    )
+}
+
+/// SQLite's own consistency check; indexes are rebuilt to repair it
+pub async fn check_integrity(pool: &SqlitePool, fix: bool) -> DiagnosticResult {
+    let check = "Integrity";
+    let errors = match quick_check(pool).await {
+        Ok(errors) => errors,
+        Err(e) => return result(check, DiagnosticStatus::Error, e.to_string()),
+    };
+    if errors.is_empty() {
+        return result(check, DiagnosticStatus::Ok, "ok");
+    }
+    if !fix {
+        return problem(check, errors.join("; "));
+    }
+
+    if let Err(e) = sqlx::query("REINDEX").execute(pool).await {
+        return result(check, DiagnosticStatus::Error, e.to_string());
+    }
+    match quick_check(pool).await {
+        Ok(remaining) if remaining.is_empty() => {
+            result(check, DiagnosticStatus::Fixed, "Rebuilt indexes")
+        }
+        Ok(remaining) => result(
+            check,
+            DiagnosticStatus::Error,
+            format!(
+                "{} (restore from a checkpoint or `granary export`)",
+                remaining.join("; ")
+            ),
+        ),
+        Err(e) => result(check, DiagnosticStatus::Error, e.to_string()),
+    }
+}
+
+async fn quick_check(pool: &SqlitePool) -> crate::Result<Vec<String>> {
+    let rows = sqlx::query_scalar::<_, String>("PRAGMA quick_check")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().filter(|row| row != "ok").collect())
+}
+
+/// Full-text indexes that no longer match their content; repaired by
+/// rebuilding them from the content table
+pub async fn check_fts_indexes(pool: &SqlitePool, fix: bool) -> DiagnosticResult {
+    let check = "Full-text indexes";
+    let tables = match sqlx::query_scalar::<_, String>(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND lower(sql) LIKE '%using fts5%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(tables) => tables,
+        Err(e) => return result(check, DiagnosticStatus::Error, e.to_string()),
+    };
+
+    let mut corrupted = Vec::new();
+    for table in &tables {
+        // A rank of 1 also compares the index against an external content table
+        let sql = format!(
+            "INSERT INTO \"{0}\"(\"{0}\", rank) VALUES('integrity-check', 1)",
+            table
+        );
+        if sqlx::query(&sql).execute(pool).await.is_err() {
+            corrupted.push(table.as_str());
+        }
+    }
+    if tables.is_empty() {
+        return result(check, DiagnosticStatus::Ok, "None");
+    }
+    if corrupted.is_empty() {
+        return result(
+            check,
+            DiagnosticStatus::Ok,
+            format!("{} checked", plural(tables.len(), "index")),
+        );
+    }
+    if !fix {
+        return problem(check, format!("Corrupted: {}", corrupted.join(", ")));
+    }
+
+    for table in &corrupted {
+        let sql = format!("INSERT INTO \"{0}\"(\"{0}\") VALUES('rebuild')", table);
+        if let Err(e) = sqlx::query(&sql).execute(pool).await {
+            return result(
+                check,
+                DiagnosticStatus::Error,
+                format!("Rebuilding {} failed: {}", table, e),
+            );
+        }
+    }
+    result(
+        check,
+        DiagnosticStatus::Fixed,
+        format!("Rebuilt {}", corrupted.join(", ")),
+    )
+}
+
+/// Runs in the global database whose worker has been deleted. They can
+/// never be retried or shown with their worker, so they are removed.
+pub async fn check_orphaned_runs(pool: &SqlitePool, fix: bool) -> DiagnosticResult {
+    let check = "Orphaned runs";
+    let count: i64 = match sqlx::query_scalar(
+        "SELECT COUNT(*) FROM runs WHERE worker_id NOT IN (SELECT id FROM workers)",
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(count) => count,
+        Err(e) => return result(check, DiagnosticStatus::Error, e.to_string()),
+    };
+    if count == 0 {
+        return result(check, DiagnosticStatus::Ok, "None");
+    }
+    let runs = plural(count as usize, "run");
+    if !fix {
+        return problem(check, format!("{} without a worker", runs));
+    }
+
+    match sqlx::query("DELETE FROM runs WHERE worker_id NOT IN (SELECT id FROM workers)")
+        .execute(pool)
+        .await
+    {
+        Ok(_) => result(
+            check,
+            DiagnosticStatus::Fixed,
+            format!("Removed {} without a worker", runs),
+        ),
+        Err(e) => result(check, DiagnosticStatus::Error, e.to_string()),
+    }
+}
+
+/// A PID file for a process that has exited, or a socket nothing listens
+/// on, left behind by a daemon that did not shut down cleanly
+pub fn check_daemon_files(
+    pid_path: &Path,
+    socket_path: Option<&Path>,
+    fix: bool,
+) -> DiagnosticResult {
+    let check = "Daemon files";
+    let mut stale = Vec::new();
+
+    if pid_path.exists() {
+        let alive = std::fs::read_to_string(pid_path)
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok())
+            .is_some_and(is_process_alive);
+        if !alive {
+            stale.push(pid_path);
+        }
+    }
+    if let Some(socket_path) = socket_path
+        && socket_path.exists()
+        && !socket_accepts(socket_path)
+    {
+        stale.push(socket_path);
+    }
+
+    if stale.is_empty() {
+        return result(check, DiagnosticStatus::Ok, "None stale");
+    }
+    let paths = stale
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !fix {
+        return problem(check, format!("Stale: {}", paths));
+    }
+
+    for path in &stale {
+        if let Err(e) = std::fs::remove_file(path) {
+            return result(
+                check,
+                DiagnosticStatus::Error,
+                format!("Removing {} failed: {}", path.display(), e),
+            );
+        }
+    }
+    result(check, DiagnosticStatus::Fixed, format!("Removed {}", paths))
+}
+
+#[cfg(unix)]
+fn socket_accepts(path: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(path).is_ok()
+}
+
+#[cfg(not(unix))]
+fn socket_accepts(_path: &Path) -> bool {
+    true
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun) {
+        (1, _) => format!("1 {}", noun),
+        (_, "index") => format!("{} indexes", count),
+        _ => format!("{} {}s", count, noun),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Workspace, SqlitePool) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        (temp_dir, workspace, pool)
+    }
+
+    fn is(result: &DiagnosticResult, status: DiagnosticStatus) -> bool {
+        std::mem::discriminant(&result.status) == std::mem::discriminant(&status)
+    }
+
+    #[tokio::test]
+    async fn test_repair_failed_migration() {
+        let (_temp_dir, workspace, pool) = setup().await;
+        // A failed migration is rolled back, so pick one that is safe to
+        // run again over its own changes
+        sqlx::query("UPDATE _sqlx_migrations SET success = 0 WHERE version = 20260210000000")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+        assert!(workspace.pool().await.is_err());
+
+        let found = check_migrations("Migrations", &workspace.db_path, false).await;
+        assert!(is(&found, DiagnosticStatus::Warning), "{}", found.message);
+        let fixed = check_migrations("Migrations", &workspace.db_path, true).await;
+        assert!(is(&fixed, DiagnosticStatus::Fixed), "{}", fixed.message);
+
+        assert!(workspace.pool().await.is_ok());
+        let again = check_migrations("Migrations", &workspace.db_path, false).await;
+        assert!(is(&again, DiagnosticStatus::Ok));
+    }
+
+    #[tokio::test]
+    async fn test_repair_orphaned_tasks() {
+        let (_temp_dir, _workspace, pool) = setup().await;
+        // Orphans are only possible with foreign keys off
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO tasks (id, project_id, task_number, title, status, priority, created_at, updated_at)
+             VALUES ('gone-ab12-task-1', 'gone-ab12', 1, 'Lost', 'todo', 'P2', ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        let found = check_orphaned_tasks(&pool, false).await;
+        assert!(is(&found, DiagnosticStatus::Warning));
+        assert!(found.message.contains("gone-ab12"));
+
+        let fixed = check_orphaned_tasks(&pool, true).await;
+        assert!(is(&fixed, DiagnosticStatus::Fixed), "{}", fixed.message);
+        let project = db::projects::get(&pool, "gone-ab12")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.slug, "gone");
+        assert!(is(
+            &check_orphaned_tasks(&pool, false).await,
+            DiagnosticStatus::Ok
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_repair_fts_index() {
+        let (_temp_dir, _workspace, pool) = setup().await;
+        for sql in [
+            "CREATE TABLE docs (id INTEGER PRIMARY KEY, body TEXT)",
+            "CREATE VIRTUAL TABLE docs_fts USING fts5(body, content='docs', content_rowid='id')",
+            // Content written without updating the index
+            "INSERT INTO docs (id, body) VALUES (1, 'hello world')",
+        ] {
+            sqlx::query(sql).execute(&pool).await.unwrap();
+        }
+
+        let found = check_fts_indexes(&pool, false).await;
+        assert!(is(&found, DiagnosticStatus::Warning), "{}", found.message);
+        let fixed = check_fts_indexes(&pool, true).await;
+        assert!(is(&fixed, DiagnosticStatus::Fixed), "{}", fixed.message);
+        assert!(is(
+            &check_fts_indexes(&pool, false).await,
+            DiagnosticStatus::Ok
+        ));
+        assert!(is(
+            &check_integrity(&pool, false).await,
+            DiagnosticStatus::Ok
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_repair_orphaned_runs() {
+        let temp_dir = TempDir::new().unwrap();
+        let pool = create_pool(&temp_dir.path().join("workers.db"))
+            .await
+            .unwrap();
+        run_migrations(&pool).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            "INSERT INTO runs (id, worker_id, event_id, event_type, entity_id, command, args, status, attempt, max_attempts, created_at, updated_at)
+             VALUES ('run-gone', 'worker-gone', 1, 'task.unblocked', 't', 'echo', '[]', 'failed', 1, 3, ?, ?)",
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+        drop(conn);
+
+        assert!(is(
+            &check_orphaned_runs(&pool, false).await,
+            DiagnosticStatus::Warning
+        ));
+        let fixed = check_orphaned_runs(&pool, true).await;
+        assert!(is(&fixed, DiagnosticStatus::Fixed), "{}", fixed.message);
+        assert!(db::runs::get(&pool, "run-gone").await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_repair_stale_daemon_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_path = temp_dir.path().join("granaryd.pid");
+        let socket_path = temp_dir.path().join("granaryd.sock");
+
+        let clean = check_daemon_files(&pid_path, Some(&socket_path), false);
+        assert!(is(&clean, DiagnosticStatus::Ok));
+
+        std::fs::write(&pid_path, "not-a-pid").unwrap();
+        std::fs::write(&socket_path, "").unwrap();
+        let found = check_daemon_files(&pid_path, Some(&socket_path), false);
+        assert!(is(&found, DiagnosticStatus::Warning));
+        assert!(pid_path.exists());
+
+        let fixed = check_daemon_files(&pid_path, Some(&socket_path), true);
+        assert!(is(&fixed, DiagnosticStatus::Fixed));
+        assert!(!pid_path.exists());
+        assert!(!socket_path.exists());
+    }
+}
@@ -0,0 +1,190 @@
+//! Debounce and throttle for worker triggers.
+//!
+//! A worker with `debounce_secs` or `throttle_secs` does not start a run for
+//! every matching event. Events are held in an [`EventBatch`] and released
+//! together once the worker's settings allow another run:
+//!
+//! - debounce: no new event has arrived for `debounce_secs`
+//! - throttle: at least `throttle_secs` have passed since the last release
+//!
+//! With both set, both must hold. The released events start a single run
+//! for the last of them, with the whole batch in its payload (see
+//! [`coalesce`]).
+//!
+//! Held events are not acknowledged, so the worker's cursor stays before
+//! them. The poller returns them again on every poll, which is why
+//! [`EventBatch::push`] ignores events it already holds, and a daemon
+//! restart replays them rather than losing them.
+
+use std::time::{Duration, Instant};
+
+use serde_json::{Map, Value, json};
+
+use crate::models::event::Event;
+use crate::models::worker::Worker;
+
+/// Events held back by a worker's debounce or throttle
+#[derive(Debug, Default)]
+pub struct EventBatch {
+    debounce: Option<Duration>,
+    throttle: Option<Duration>,
+    events: Vec<Event>,
+    last_event_at: Option<Instant>,
+    last_release_at: Option<Instant>,
+}
+
+impl EventBatch {
+    pub fn new(debounce: Option<Duration>, throttle: Option<Duration>) -> Self {
+        Self {
+            debounce,
+            throttle,
+            ..Default::default()
+        }
+    }
+
+    /// Batch for a worker's debounce and throttle settings
+    pub fn for_worker(worker: &Worker) -> Self {
+        let secs = |s: Option<i64>| s.map(|s| Duration::from_secs(s.max(0) as u64));
+        Self::new(secs(worker.debounce_secs), secs(worker.throttle_secs))
+    }
+
+    /// Whether events are batched at all
+    pub fn is_enabled(&self) -> bool {
+        self.debounce.is_some() || self.throttle.is_some()
+    }
+
+    /// Whether no events are held
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Hold an event. Stored events already held are ignored; synthetic
+    /// polled events (id 0) are always added.
+    pub fn push(&mut self, event: Event, now: Instant) {
+        let newest = self.events.iter().map(|e| e.id).max().unwrap_or(0);
+        if event.id != 0 && event.id <= newest {
+            return;
+        }
+        self.events.push(event);
+        self.last_event_at = Some(now);
+    }
+
+    /// Release the held events if the debounce and throttle allow a run now
+    pub fn take_due(&mut self, now: Instant) -> Option<Vec<Event>> {
+        if self.events.is_empty() {
+            return None;
+        }
+        let elapsed = |since: Option<Instant>, window: Duration| {
+            since.is_none_or(|at| now.saturating_duration_since(at) >= window)
+        };
+        if let Some(debounce) = self.debounce
+            && !elapsed(self.last_event_at, debounce)
+        {
+            return None;
+        }
+        if let Some(throttle) = self.throttle
+            && !elapsed(self.last_release_at, throttle)
+        {
+            return None;
+        }
+        self.last_release_at = Some(now);
+        self.last_event_at = None;
+        Some(std::mem::take(&mut self.events))
+    }
+}
+
+/// Merge released events into the event that triggers the run.
+///
+/// The last event triggers the run, so its fields and payload are used for
+/// `{placeholders}`. Its payload gains `event_count` and `events`, the
+/// whole batch oldest first, which runners can read as `{event_count}` and
+/// `{events}` (a JSON array).
+pub fn coalesce(mut events: Vec<Event>) -> Option<Event> {
+    let batch: Vec<Value> = events.iter().map(event_summary).collect();
+    let mut event = events.pop()?;
+
+    let mut payload = match serde_json::from_str::<Value>(&event.payload) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    payload.insert("event_count".to_string(), json!(batch.len()));
+    payload.insert("events".to_string(), Value::Array(batch));
+    event.payload = Value::Object(payload).to_string();
+    Some(event)
+}
+
+/// One event of a batch as it appears in the `events` payload field
+fn event_summary(event: &Event) -> Value {
+    json!({
+        "id": event.id,
+        "event_type": event.event_type,
+        "entity_type": event.entity_type,
+        "entity_id": event.entity_id,
+        "actor": event.actor,
+        "created_at": event.created_at,
+        "payload": serde_json::from_str::<Value>(&event.payload).unwrap_or(Value::Null),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: i64) -> Event {
+        Event {
+            id,
+            event_type: "task.updated".to_string(),
+            entity_type: "task".to_string(),
+            entity_id: format!("task-{}", id),
+            actor: None,
+            session_id: None,
+            payload: format!(r#"{{"n": {}}}"#, id),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_debounce_waits_for_quiet_period() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let mut batch = EventBatch::new(Some(secs(10)), None);
+
+        batch.push(event(1), start);
+        batch.push(event(2), start + secs(5));
+        // Polled again before being acknowledged
+        batch.push(event(1), start + secs(8));
+        assert!(batch.take_due(start + secs(12)).is_none());
+
+        let events = batch.take_due(start + secs(15)).unwrap();
+        assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), [1, 2]);
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_throttle_releases_first_event_then_limits_rate() {
+        let start = Instant::now();
+        let secs = Duration::from_secs;
+        let mut batch = EventBatch::new(None, Some(secs(60)));
+
+        batch.push(event(1), start);
+        assert_eq!(batch.take_due(start).unwrap().len(), 1);
+
+        for id in 2..=50 {
+            batch.push(event(id), start + secs(1));
+        }
+        assert!(batch.take_due(start + secs(30)).is_none());
+        assert_eq!(batch.take_due(start + secs(60)).unwrap().len(), 49);
+    }
+
+    #[test]
+    fn test_coalesce_adds_batch_to_last_event() {
+        let event = coalesce(vec![event(1), event(2), event(3)]).unwrap();
+        assert_eq!(event.id, 3);
+
+        let payload: Value = serde_json::from_str(&event.payload).unwrap();
+        assert_eq!(payload["n"], 3);
+        assert_eq!(payload["event_count"], 3);
+        assert_eq!(payload["events"][0]["entity_id"], "task-1");
+        assert_eq!(payload["events"][1]["payload"]["n"], 2);
+    }
+}
@@ -12,7 +12,7 @@ use tokio::sync::mpsc;
 use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::event::Event;
-use crate::services::filter::{Filter, matches_all, parse_filters};
+use crate::services::filter_expr::{EventFilter, matches_all_events, parse_event_filters};
 
 /// Default polling interval in milliseconds
 const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
@@ -74,8 +74,8 @@ pub struct EventPoller {
     worker_id: String,
     /// The event type to subscribe to (e.g., "task.unblocked")
     event_type: String,
-    /// Parsed filter expressions
-    filters: Vec<Filter>,
+    /// Parsed filters: `field=value` filters and expressions
+    filters: Vec<EventFilter>,
     /// Configuration
     config: EventPollerConfig,
 }
@@ -101,7 +101,7 @@ impl EventPoller {
         filter_strings: &[String],
         config: EventPollerConfig,
     ) -> Result<Self> {
-        let filters = parse_filters(filter_strings)?;
+        let filters = parse_event_filters(filter_strings)?;
         Ok(Self {
             workspace_pool,
             global_pool,
@@ -167,16 +167,7 @@ impl EventPoller {
         }
 
         // Apply custom filters
-        let matching: Vec<_> = events
-            .into_iter()
-            .filter(|e| {
-                if self.filters.is_empty() {
-                    return true;
-                }
-                let payload = e.payload_json();
-                matches_all(&self.filters, &payload)
-            })
-            .collect();
+        let matching: Vec<_> = events.into_iter().filter(|e| self.matches(e)).collect();
 
         // Apply batch size limit if configured
         let matching = if let Some(batch_size) = self.config.batch_size {
@@ -195,6 +186,11 @@ impl EventPoller {
         Ok(matching)
     }
 
+    /// Whether an event passes the worker's filters
+    pub fn matches(&self, event: &Event) -> bool {
+        self.filters.is_empty() || matches_all_events(&self.filters, &event.payload_json())
+    }
+
     /// Manually update the cursor to a specific event ID
     ///
     /// This is useful when auto_update_cursor is disabled and you want
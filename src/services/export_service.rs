@@ -0,0 +1,427 @@
+//! Workspace export and import.
+//!
+//! An export captures every workspace table (initiatives, projects, tasks,
+//! comments, sessions, checkpoints and the tables that relate them) with IDs
+//! intact, so importing it into a fresh `.granary` directory reproduces the
+//! workspace exactly. Two archive formats are supported:
+//!
+//! - `json`: a single JSON document with a manifest and all tables
+//! - `tar`: a gzip-compressed tarball with `manifest.json` and one
+//!   `tables/<name>.json` file per table
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::db::export::RawRow;
+use crate::error::{GranaryError, Result};
+
+/// Version of the export archive layout
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Workspace tables included in an export, in dependency order.
+///
+/// The global worker and run tables are machine-specific and deliberately excluded.
+/// Sent reminders and sync state are included, so an imported workspace
+/// neither repeats reminders nor loses track of its copies in other trackers.
+pub const EXPORT_TABLES: &[&str] = &[
+    "initiatives",
+    "projects",
+    "initiative_projects",
+    "project_dependencies",
+    "tasks",
+    "task_status_history",
+    "task_dependencies",
+    "task_vcs_links",
+    "task_links",
+    "task_reminders",
+    "initiative_milestones",
+    "milestone_projects",
+    "milestone_tasks",
+    "attachments",
+    "labels",
+    "task_labels",
+    "custom_fields",
+    "sessions",
+    "session_scope",
+    "comments",
+    "artifacts",
+    "checkpoints",
+    "steering",
+    "events",
+    "config",
+    "counters",
+    "sync_links",
+    "sync_cursors",
+];
+
+const MANIFEST_FILE: &str = "manifest.json";
+const TABLES_DIR: &str = "tables";
+
+/// Archive format for `granary export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Json,
+    Tar,
+}
+
+/// Metadata describing an export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub version: u32,
+    pub granary_version: String,
+    /// Latest migration applied to the exported database
+    pub schema_version: i64,
+    pub exported_at: String,
+    /// Row count per table
+    pub counts: BTreeMap<String, usize>,
+}
+
+/// A complete workspace export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceExport {
+    pub manifest: ExportManifest,
+    pub tables: BTreeMap<String, Vec<RawRow>>,
+}
+
+/// Result of importing an export
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub counts: BTreeMap<String, usize>,
+    pub total: u64,
+}
+
+/// Read the full workspace into an export
+pub async fn export_workspace(pool: &SqlitePool) -> Result<WorkspaceExport> {
+    let mut tables = BTreeMap::new();
+    let mut counts = BTreeMap::new();
+
+    for table in EXPORT_TABLES {
+        let rows = db::export::dump_table(pool, table).await?;
+        counts.insert(table.to_string(), rows.len());
+        tables.insert(table.to_string(), rows);
+    }
+
+    Ok(WorkspaceExport {
+        manifest: ExportManifest {
+            version: EXPORT_VERSION,
+            granary_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: db::export::schema_version(pool).await?,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            counts,
+        },
+        tables,
+    })
+}
+
+/// Restore an export into a workspace database.
+///
+/// The target should be freshly initialized: rows keep their original IDs and
+/// will conflict with any existing data.
+pub async fn import_workspace(
+    pool: &SqlitePool,
+    export: &WorkspaceExport,
+) -> Result<ImportSummary> {
+    if export.manifest.version > EXPORT_VERSION {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Export format version {} is newer than supported version {}. Update granary and try again.",
+            export.manifest.version, EXPORT_VERSION
+        )));
+    }
+
+    let schema_version = db::export::schema_version(pool).await?;
+    if export.manifest.schema_version > schema_version {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Export was created by a newer granary ({}). Update granary and try again.",
+            export.manifest.granary_version
+        )));
+    }
+
+    // Validate table and column names before they are interpolated into SQL
+    for (table, rows) in &export.tables {
+        if !EXPORT_TABLES.contains(&table.as_str()) {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Unknown table in export: {}",
+                table
+            )));
+        }
+        let columns = db::export::table_columns(pool, table).await?;
+        for row in rows {
+            if let Some(column) = row.keys().find(|c| !columns.contains(c)) {
+                return Err(GranaryError::InvalidArgument(format!(
+                    "Unknown column in export: {}.{}",
+                    table, column
+                )));
+            }
+        }
+    }
+
+    let ordered: Vec<(&str, &[RawRow])> = EXPORT_TABLES
+        .iter()
+        .filter_map(|table| {
+            export
+                .tables
+                .get(*table)
+                .map(|rows| (*table, rows.as_slice()))
+        })
+        .collect();
+
+    let total = db::export::insert_rows(pool, &ordered).await?;
+    let counts = ordered
+        .iter()
+        .map(|(table, rows)| (table.to_string(), rows.len()))
+        .collect();
+
+    Ok(ImportSummary { counts, total })
+}
+
+/// Serialize an export in the given archive format
+pub fn write_archive(
+    export: &WorkspaceExport,
+    format: ArchiveFormat,
+    writer: impl Write,
+) -> Result<()> {
+    match format {
+        ArchiveFormat::Json => {
+            serde_json::to_writer_pretty(writer, export)?;
+            Ok(())
+        }
+        ArchiveFormat::Tar => write_tar(export, writer),
+    }
+}
+
+fn write_tar(export: &WorkspaceExport, writer: impl Write) -> Result<()> {
+    let encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    append_json(&mut builder, MANIFEST_FILE, &export.manifest)?;
+    for (table, rows) in &export.tables {
+        append_json(
+            &mut builder,
+            &format!("{}/{}.json", TABLES_DIR, table),
+            rows,
+        )?;
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn append_json<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    value: &impl Serialize,
+) -> Result<()> {
+    let data = serde_json::to_vec_pretty(value)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp() as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data.as_slice())?;
+    Ok(())
+}
+
+/// Parse an archive, detecting whether it is JSON or a gzip tarball
+pub fn read_archive(data: &[u8]) -> Result<WorkspaceExport> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        read_tar(data)
+    } else {
+        serde_json::from_slice(data).map_err(|e| {
+            GranaryError::InvalidArgument(format!("Not a valid granary export: {}", e))
+        })
+    }
+}
+
+fn read_tar(data: &[u8]) -> Result<WorkspaceExport> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+    let mut manifest = None;
+    let mut tables = BTreeMap::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+
+        if path == MANIFEST_FILE {
+            manifest = Some(serde_json::from_slice::<ExportManifest>(&contents)?);
+        } else if let Some(name) = path
+            .strip_prefix(&format!("{}/", TABLES_DIR))
+            .and_then(|p| p.strip_suffix(".json"))
+        {
+            tables.insert(
+                name.to_string(),
+                serde_json::from_slice::<Vec<RawRow>>(&contents)?,
+            );
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| {
+        GranaryError::InvalidArgument(format!(
+            "Not a valid granary export: missing {}",
+            MANIFEST_FILE
+        ))
+    })?;
+
+    Ok(WorkspaceExport { manifest, tables })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateProject, CreateTask};
+    use crate::services::{Workspace, project_service, task_service};
+    use tempfile::TempDir;
+
+    async fn setup_workspace() -> (TempDir, SqlitePool) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        (temp_dir, pool)
+    }
+
+    async fn seed(pool: &SqlitePool) -> (String, String, String) {
+        let project = project_service::create_project(
+            pool,
+            CreateProject {
+                name: "Export Test".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let first = task_service::create_task(
+            pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "First".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let second = task_service::create_task(
+            pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Second".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        task_service::add_dependency(pool, &second.id, &first.id)
+            .await
+            .unwrap();
+
+        (project.id, first.id, second.id)
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_preserves_ids_and_relationships() {
+        for format in [ArchiveFormat::Json, ArchiveFormat::Tar] {
+            let (_src_dir, src) = setup_workspace().await;
+            let (project_id, first_id, second_id) = seed(&src).await;
+
+            let export = export_workspace(&src).await.unwrap();
+            assert_eq!(export.manifest.counts["projects"], 1);
+            assert_eq!(export.manifest.counts["tasks"], 2);
+
+            let mut archive = Vec::new();
+            write_archive(&export, format, &mut archive).unwrap();
+            let parsed = read_archive(&archive).unwrap();
+
+            let (_dst_dir, dst) = setup_workspace().await;
+            let summary = import_workspace(&dst, &parsed).await.unwrap();
+            assert_eq!(summary.counts["tasks"], 2);
+
+            let project = db::projects::get(&dst, &project_id).await.unwrap().unwrap();
+            assert_eq!(project.name, "Export Test");
+
+            let deps = db::dependencies::list(&dst, &second_id).await.unwrap();
+            assert_eq!(deps.len(), 1);
+            assert_eq!(deps[0].depends_on_task_id, first_id);
+
+            // Task counters carry over, so new tasks don't reuse IDs
+            let next = task_service::create_task(
+                &dst,
+                CreateTask {
+                    project_id: project_id.clone(),
+                    title: "Third".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            assert_ne!(next.id, first_id);
+            assert_ne!(next.id, second_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_keeps_reminders_and_sync_state() {
+        let (_src_dir, src) = setup_workspace().await;
+        let (_, first_id, _) = seed(&src).await;
+        let due_at = "2026-03-01T23:59:59+00:00";
+        db::task_reminders::record(&src, &first_id, "overdue", due_at)
+            .await
+            .unwrap();
+        db::sync::set_cursor(&src, "linear", "task", "2026-02-28T10:00:00Z")
+            .await
+            .unwrap();
+
+        let export = export_workspace(&src).await.unwrap();
+        let (_dst_dir, dst) = setup_workspace().await;
+        import_workspace(&dst, &export).await.unwrap();
+
+        assert_eq!(
+            db::task_reminders::sent_for(&dst, &first_id, "overdue")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(due_at)
+        );
+        assert_eq!(
+            db::sync::get_cursor(&dst, "linear", "task")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("2026-02-28T10:00:00Z")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_unknown_columns() {
+        let (_src_dir, src) = setup_workspace().await;
+        seed(&src).await;
+        let mut export = export_workspace(&src).await.unwrap();
+        export.tables.get_mut("projects").unwrap()[0]
+            .insert("name; DROP TABLE tasks".to_string(), "x".into());
+
+        let (_dst_dir, dst) = setup_workspace().await;
+        let result = import_workspace(&dst, &export).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+        assert!(db::tasks::list_all(&dst, true).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_newer_schema() {
+        let (_src_dir, src) = setup_workspace().await;
+        let mut export = export_workspace(&src).await.unwrap();
+        export.manifest.schema_version += 1;
+
+        let (_dst_dir, dst) = setup_workspace().await;
+        let result = import_workspace(&dst, &export).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_read_archive_rejects_garbage() {
+        assert!(read_archive(b"not an export").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Custom fields.
+//!
+//! Each workspace declares its fields in config (`granary config set
+//! field.env enum:dev,staging,prod`). Values are checked against that schema
+//! when they are set with `--field NAME=VALUE` and stored in the
+//! `custom_fields` table.
+
+use std::collections::{BTreeMap, HashMap};
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+
+/// Prefix of the config keys that declare custom fields
+pub const FIELD_KEY_PREFIX: &str = "field.";
+
+/// Validate a value for a `field.<name>` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(name) = key.strip_prefix(FIELD_KEY_PREFIX) else {
+        return Ok(());
+    };
+    if !is_valid_field_name(name) || name.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Invalid field name '{}'. Use lowercase letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    value
+        .parse::<FieldType>()
+        .map(|_| ())
+        .map_err(GranaryError::InvalidArgument)
+}
+
+/// Fields declared in workspace config, sorted by name
+pub async fn load_schema(pool: &SqlitePool) -> Result<Vec<FieldDefinition>> {
+    db::config::list(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(FIELD_KEY_PREFIX)?.to_string();
+            Some(
+                value
+                    .parse()
+                    .map(|field_type| FieldDefinition { name, field_type })
+                    .map_err(|e| {
+                        GranaryError::InvalidArgument(format!("Config key {}: {}", key, e))
+                    }),
+            )
+        })
+        .collect()
+}
+
+/// Parse `NAME=VALUE` arguments
+pub fn parse_field_args(args: &[String]) -> Result<Vec<FieldAssignment>> {
+    args.iter()
+        .map(|arg| arg.parse().map_err(GranaryError::InvalidArgument))
+        .collect()
+}
+
+/// Check assignments against the workspace schema and normalize their values
+pub async fn validate_fields(
+    pool: &SqlitePool,
+    fields: &[FieldAssignment],
+) -> Result<Vec<FieldAssignment>> {
+    if fields.is_empty() {
+        return Ok(Vec::new());
+    }
+    let schema = load_schema(pool).await?;
+
+    fields
+        .iter()
+        .map(|field| {
+            let definition = schema
+                .iter()
+                .find(|d| d.name == field.name)
+                .ok_or_else(|| {
+                    GranaryError::InvalidArgument(format!(
+                        "Unknown field '{}'. Declare it with: granary config set {}{} text",
+                        field.name, FIELD_KEY_PREFIX, field.name
+                    ))
+                })?;
+            let value = field
+                .value
+                .as_deref()
+                .map(|v| {
+                    definition.field_type.normalize(v).map_err(|e| {
+                        GranaryError::InvalidArgument(format!("Field '{}': {}", field.name, e))
+                    })
+                })
+                .transpose()?;
+            Ok(FieldAssignment {
+                name: field.name.clone(),
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Store validated assignments on an entity.
+///
+/// Returns the changes as a JSON object for event payloads, with cleared
+/// fields set to `null`.
+pub async fn write_fields(
+    pool: &SqlitePool,
+    entity_type: EntityType,
+    entity_id: &str,
+    fields: &[FieldAssignment],
+) -> Result<serde_json::Value> {
+    let mut set = Vec::new();
+    let mut clear = Vec::new();
+    let mut changes = serde_json::Map::new();
+    for field in fields {
+        match &field.value {
+            Some(value) => set.push((field.name.clone(), value.clone())),
+            None => clear.push(field.name.clone()),
+        }
+        changes.insert(field.name.clone(), serde_json::json!(field.value));
+    }
+    db::custom_fields::apply(pool, entity_type.as_str(), entity_id, &set, &clear).await?;
+    Ok(serde_json::Value::Object(changes))
+}
+
+async fn load_fields(
+    pool: &SqlitePool,
+    entity_type: EntityType,
+    ids: &[&str],
+) -> Result<HashMap<String, BTreeMap<String, String>>> {
+    let mut by_entity: HashMap<String, BTreeMap<String, String>> = HashMap::new();
+    if ids.is_empty() {
+        return Ok(by_entity);
+    }
+    for field in db::custom_fields::list_for(pool, entity_type.as_str(), ids).await? {
+        by_entity
+            .entry(field.entity_id)
+            .or_default()
+            .insert(field.name, field.value);
+    }
+    Ok(by_entity)
+}
+
+/// Fill in the custom fields of each task with a single query
+pub async fn attach_task_fields(pool: &SqlitePool, tasks: &mut [Task]) -> Result<()> {
+    let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let mut by_task = load_fields(pool, EntityType::Task, &ids).await?;
+    for task in tasks.iter_mut() {
+        task.fields = by_task.remove(&task.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Fill in the custom fields of each project with a single query
+pub async fn attach_project_fields(pool: &SqlitePool, projects: &mut [Project]) -> Result<()> {
+    let ids: Vec<&str> = projects.iter().map(|p| p.id.as_str()).collect();
+    let mut by_project = load_fields(pool, EntityType::Project, &ids).await?;
+    for project in projects.iter_mut() {
+        project.fields = by_project.remove(&project.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{
+        Workspace, create_project, create_task, find_tasks, get_task, update_task,
+    };
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, SqlitePool, Project) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        db::config::set(&pool, "field.env", "enum:dev,staging,prod")
+            .await
+            .unwrap();
+        db::config::set(&pool, "field.customer", "text")
+            .await
+            .unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Fields".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        (temp_dir, pool, project)
+    }
+
+    fn args(values: &[&str]) -> Vec<FieldAssignment> {
+        let values: Vec<String> = values.iter().map(|s| s.to_string()).collect();
+        parse_field_args(&values).unwrap()
+    }
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value("field.env", "enum:dev,prod").is_ok());
+        assert!(validate_config_value("field.env", "date").is_err());
+        assert!(validate_config_value("field.Env", "text").is_err());
+        assert!(validate_config_value("field.", "text").is_err());
+        assert!(validate_config_value("other.key", "anything").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_validate_fields_against_schema() {
+        let (_temp_dir, pool, _project) = setup().await;
+
+        let fields = validate_fields(&pool, &args(&["env=PROD", "customer="]))
+            .await
+            .unwrap();
+        assert_eq!(fields[0].value.as_deref(), Some("prod"));
+        assert_eq!(fields[1].value, None);
+
+        assert!(validate_fields(&pool, &args(&["env=qa"])).await.is_err());
+        assert!(validate_fields(&pool, &args(&["region=eu"])).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_and_filter_task_fields() {
+        let (_temp_dir, pool, project) = setup().await;
+        let mut ids = Vec::new();
+        for title in ["First", "Second"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        let task = update_task(
+            &pool,
+            &ids[0],
+            UpdateTask {
+                fields: args(&["env=prod", "customer=Acme"]),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(task.fields.get("env").map(String::as_str), Some("prod"));
+        assert_eq!(task.fields.len(), 2);
+
+        update_task(
+            &pool,
+            &ids[0],
+            UpdateTask {
+                fields: args(&["customer="]),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let task = get_task(&pool, &ids[0]).await.unwrap();
+        assert!(!task.fields.contains_key("customer"));
+
+        let filter = |fields| SearchQuery {
+            fields,
+            ..Default::default()
+        };
+        let prod = find_tasks(&pool, &filter(args(&["env=Prod"])))
+            .await
+            .unwrap();
+        assert_eq!(prod.len(), 1);
+        assert_eq!(prod[0].id, ids[0]);
+
+        // A filter without a value matches tasks where the field is not set
+        let unset = find_tasks(&pool, &filter(args(&["env="]))).await.unwrap();
+        assert_eq!(unset.len(), 1);
+        assert_eq!(unset[0].id, ids[1]);
+    }
+}
@@ -6,6 +6,8 @@
 //! - `field~=pattern` - Contains check (substring match)
 //!
 //! Fields can be nested using dot notation: `task.status`, `payload.project_id`
+//!
+//! Workers also accept expressions, see [`crate::services::filter_expr`].
 
 use crate::error::{GranaryError, Result};
 use serde_json::Value;
@@ -136,7 +138,7 @@ impl std::fmt::Display for Filter {
 ///
 /// For example, `get_nested_value(obj, "task.status")` will return
 /// the value at `obj["task"]["status"]`
-fn get_nested_value<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+pub(crate) fn get_nested_value<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
     let parts: Vec<&str> = path.split('.').collect();
     let mut current = value;
 
@@ -0,0 +1,502 @@
+//! Filter expressions for worker events.
+//!
+//! An expression compares fields of the event payload with literals, and
+//! combines comparisons with `&&`, `||`, `!` and parentheses:
+//!
+//! ```text
+//! event.priority == "P0" && event.project_id == "proj-1"
+//! !(event.status == "done") || event.estimate >= 3
+//! event.tags ~= "backend"
+//! ```
+//!
+//! Fields use dot notation like `field=value` filters; the `event.` prefix
+//! is optional and names the payload itself. Missing fields are `null`.
+//! Operators are `==`, `!=`, `<`, `<=`, `>`, `>=` and `~=` (contains: a
+//! substring of a string, or an element of an array). Numbers compare
+//! numerically and strings lexically, so `event.priority <= "P1"` and
+//! timestamp comparisons work; comparing values of different types is
+//! false. A field on its own is true unless it is missing, `null`,
+//! `false`, `0` or `""`.
+
+use std::cmp::Ordering;
+
+use serde_json::Value;
+
+use crate::error::{GranaryError, Result};
+use crate::services::filter::{Filter, get_nested_value};
+
+/// Prefix naming the event payload in expressions
+const EVENT_PREFIX: &str = "event";
+
+/// Comparison operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+}
+
+/// A field reference or a literal
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Field(String),
+    Literal(Value),
+}
+
+/// A parsed filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare(Operand, CompareOp, Operand),
+    /// A field or literal on its own, true unless empty
+    Truthy(Operand),
+}
+
+impl FilterExpr {
+    /// Parse an expression
+    pub fn parse(s: &str) -> Result<Self> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: s.chars().count(),
+        };
+        if parser.tokens.is_empty() {
+            return Err(invalid(1, "expected an expression"));
+        }
+        let expr = parser.or()?;
+        if let Some((column, token)) = parser.tokens.get(parser.pos) {
+            return Err(invalid(
+                *column,
+                &format!("unexpected {}", token.describe()),
+            ));
+        }
+        Ok(expr)
+    }
+
+    /// Evaluate this expression against an event payload
+    pub fn matches(&self, payload: &Value) -> bool {
+        match self {
+            FilterExpr::Or(a, b) => a.matches(payload) || b.matches(payload),
+            FilterExpr::And(a, b) => a.matches(payload) && b.matches(payload),
+            FilterExpr::Not(e) => !e.matches(payload),
+            FilterExpr::Compare(left, op, right) => {
+                compare(&left.resolve(payload), *op, &right.resolve(payload))
+            }
+            FilterExpr::Truthy(operand) => is_truthy(&operand.resolve(payload)),
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, payload: &Value) -> Value {
+        match self {
+            Operand::Literal(value) => value.clone(),
+            Operand::Field(path) => {
+                let path = match path.strip_prefix(EVENT_PREFIX) {
+                    Some("") => return payload.clone(),
+                    Some(rest) if rest.starts_with('.') => &rest[1..],
+                    _ => path.as_str(),
+                };
+                get_nested_value(payload, path)
+                    .cloned()
+                    .unwrap_or(Value::Null)
+            }
+        }
+    }
+}
+
+/// A worker filter: a `field=value` filter or an expression
+#[derive(Debug, Clone)]
+pub enum EventFilter {
+    Field(Filter),
+    Expr(FilterExpr),
+}
+
+impl EventFilter {
+    /// Parse a worker filter. Filters using `==`, `&&`, `||`, `<`, `>`,
+    /// parentheses or a leading `!` are expressions; the rest use the
+    /// `field=value` syntax.
+    pub fn parse(s: &str) -> Result<Self> {
+        if is_expression(s) {
+            FilterExpr::parse(s).map(EventFilter::Expr)
+        } else {
+            Filter::parse(s).map(EventFilter::Field)
+        }
+    }
+
+    pub fn matches(&self, payload: &Value) -> bool {
+        match self {
+            EventFilter::Field(filter) => filter.matches(payload),
+            EventFilter::Expr(expr) => expr.matches(payload),
+        }
+    }
+}
+
+/// Parse worker filter strings
+pub fn parse_event_filters(filters: &[String]) -> Result<Vec<EventFilter>> {
+    filters.iter().map(|s| EventFilter::parse(s)).collect()
+}
+
+/// Check if all worker filters match the given payload
+pub fn matches_all_events(filters: &[EventFilter], payload: &Value) -> bool {
+    filters.iter().all(|f| f.matches(payload))
+}
+
+/// Whether a filter string is an expression rather than `field=value`
+pub fn is_expression(s: &str) -> bool {
+    let s = s.trim();
+    ["==", "&&", "||", "<", ">", "("]
+        .iter()
+        .any(|op| s.contains(op))
+        || (s.starts_with('!') && !s.starts_with("!="))
+        || !s.contains('=')
+}
+
+fn invalid(column: usize, message: &str) -> GranaryError {
+    GranaryError::InvalidArgument(format!(
+        "Invalid filter expression at column {}: {}",
+        column, message
+    ))
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    match op {
+        CompareOp::Eq => values_equal(left, right),
+        CompareOp::NotEq => !values_equal(left, right),
+        CompareOp::Contains => match (left, right) {
+            (Value::String(s), Value::String(pattern)) => s.contains(pattern.as_str()),
+            (Value::Array(items), _) => items.iter().any(|item| values_equal(item, right)),
+            _ => false,
+        },
+        CompareOp::Lt | CompareOp::Le | CompareOp::Gt | CompareOp::Ge => {
+            let ordering = match (left, right) {
+                (Value::Number(a), Value::Number(b)) => a
+                    .as_f64()
+                    .zip(b.as_f64())
+                    .and_then(|(a, b)| a.partial_cmp(&b)),
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            match ordering {
+                Some(ordering) => match op {
+                    CompareOp::Lt => ordering == Ordering::Less,
+                    CompareOp::Le => ordering != Ordering::Greater,
+                    CompareOp::Gt => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                },
+                None => false,
+            }
+        }
+    }
+}
+
+/// Equality with numbers compared by value, so `1 == 1.0`
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => a == b,
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Field(String),
+    Literal(Value),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Field(name) => format!("field '{}'", name),
+            Token::Literal(value) => format!("value {}", value),
+            Token::Op(_) => "operator".to_string(),
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Not => "'!'".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+        }
+    }
+}
+
+/// Split an expression into tokens, each with its 1-based column
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('=', Some('=')) => (Token::Op(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Op(CompareOp::NotEq), 2),
+            ('~', Some('=')) => (Token::Op(CompareOp::Contains), 2),
+            ('<', Some('=')) => (Token::Op(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Op(CompareOp::Ge), 2),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('<', _) => (Token::Op(CompareOp::Lt), 1),
+            ('>', _) => (Token::Op(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            ('"' | '\'', _) => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(invalid(column, "unterminated string")),
+                        Some('\\') if j + 1 < chars.len() => {
+                            value.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&other) => {
+                            value.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                (Token::Literal(Value::String(value)), j + 1 - i)
+            }
+            (c, _)
+                if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let mut j = i + 1;
+                while j < chars.len() && (chars[j].is_ascii_digit() || chars[j] == '.') {
+                    j += 1;
+                }
+                let text: String = chars[i..j].iter().collect();
+                let number = text
+                    .parse::<f64>()
+                    .ok()
+                    .and_then(|n| {
+                        if n.fract() == 0.0 && !text.contains('.') {
+                            Some(Value::from(n as i64))
+                        } else {
+                            serde_json::Number::from_f64(n).map(Value::Number)
+                        }
+                    })
+                    .ok_or_else(|| invalid(column, &format!("invalid number '{}'", text)))?;
+                (Token::Literal(number), j - i)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let mut j = i + 1;
+                while j < chars.len()
+                    && (chars[j].is_alphanumeric() || chars[j] == '_' || chars[j] == '.')
+                {
+                    j += 1;
+                }
+                let word: String = chars[i..j].iter().collect();
+                let token = match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Field(word),
+                };
+                (token, j - i)
+            }
+            (c, _) => return Err(invalid(column, &format!("unexpected '{}'", c))),
+        };
+        tokens.push((column, token));
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `or := and ('||' and)*`, `and := not ('&&' not)*`,
+/// `not := '!' not | primary`, `primary := '(' or ')' | operand (op operand)?`
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Column after the last character, for errors at the end
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end + 1, |(column, _)| *column)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(_, token)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<FilterExpr> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<FilterExpr> {
+        let mut expr = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            expr = FilterExpr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<FilterExpr> {
+        if self.peek() == Some(&Token::Not) {
+            self.pos += 1;
+            return Ok(FilterExpr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<FilterExpr> {
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or()?;
+            let column = self.column();
+            if self.next() != Some(Token::RParen) {
+                return Err(invalid(column, "expected ')'"));
+            }
+            return Ok(expr);
+        }
+
+        let left = self.operand()?;
+        match self.peek() {
+            Some(Token::Op(op)) => {
+                let op = *op;
+                self.pos += 1;
+                let right = self.operand()?;
+                Ok(FilterExpr::Compare(left, op, right))
+            }
+            _ => Ok(FilterExpr::Truthy(left)),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        let column = self.column();
+        match self.next() {
+            Some(Token::Field(name)) => Ok(Operand::Field(name)),
+            Some(Token::Literal(value)) => Ok(Operand::Literal(value)),
+            Some(token) => Err(invalid(
+                column,
+                &format!("expected a field or value, found {}", token.describe()),
+            )),
+            None => Err(invalid(column, "expected a field or value")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn matches(expr: &str, payload: &Value) -> bool {
+        FilterExpr::parse(expr).unwrap().matches(payload)
+    }
+
+    #[test]
+    fn test_and_or_not() {
+        let payload = json!({"priority": "P0", "project_id": "proj-1", "status": "todo"});
+        assert!(matches(
+            r#"event.priority == "P0" && event.project_id == "proj-1""#,
+            &payload
+        ));
+        assert!(!matches(
+            r#"priority == "P0" && project_id == "proj-2""#,
+            &payload
+        ));
+        assert!(matches(
+            r#"priority == "P1" || (status != "done" && !blocked)"#,
+            &payload
+        ));
+        // && binds tighter than ||
+        assert!(matches(
+            r#"priority == "P0" || priority == "P1" && status == "done""#,
+            &payload
+        ));
+    }
+
+    #[test]
+    fn test_ordering_and_contains() {
+        let payload = json!({
+            "priority": "P1",
+            "estimate": 3,
+            "tags": ["backend", "api"],
+            "title": "Fix login flow",
+            "task": {"owner": null}
+        });
+        assert!(matches(r#"priority <= "P1""#, &payload));
+        assert!(!matches(r#"priority < "P1""#, &payload));
+        assert!(matches("estimate >= 3 && estimate < 3.5", &payload));
+        assert!(matches("estimate == 3.0", &payload));
+        assert!(!matches(r#"estimate > "2""#, &payload));
+        assert!(matches(r#"tags ~= "api""#, &payload));
+        assert!(matches(r#"title ~= 'login'"#, &payload));
+        assert!(matches("task.owner == null && missing == null", &payload));
+        assert!(!matches("task.owner", &payload));
+        assert!(matches("event.tags", &payload));
+    }
+
+    #[test]
+    fn test_parse_errors_report_column() {
+        let err = FilterExpr::parse(r#"priority == "P0" &&"#).unwrap_err();
+        assert!(err.to_string().contains("column 20"), "{}", err);
+        let err = FilterExpr::parse(r#"(priority == "P0""#).unwrap_err();
+        assert!(err.to_string().contains("expected ')'"), "{}", err);
+        let err = FilterExpr::parse(r#"priority == "P0"#).unwrap_err();
+        assert!(err.to_string().contains("unterminated string"), "{}", err);
+        assert!(FilterExpr::parse("priority == == 1").is_err());
+        assert!(FilterExpr::parse("").is_err());
+    }
+
+    #[test]
+    fn test_event_filter_keeps_field_value_syntax() {
+        let payload = json!({"status": "in_progress", "priority": "P0"});
+        let filters = parse_event_filters(&[
+            "status=in_progress".to_string(),
+            r#"priority == "P0""#.to_string(),
+        ])
+        .unwrap();
+        assert!(matches!(filters[0], EventFilter::Field(_)));
+        assert!(matches!(filters[1], EventFilter::Expr(_)));
+        assert!(matches_all_events(&filters, &payload));
+        assert!(is_expression("!blocked"));
+        assert!(!is_expression("status!=done"));
+    }
+}
@@ -0,0 +1,377 @@
+//! Git integration: link tasks to branches and commits.
+//!
+//! The repository is located by walking up from the workspace root, the same
+//! way git itself finds `.git`. All git operations shell out to the `git`
+//! binary so they respect the user's configuration and hooks.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::get_task;
+
+/// Maximum length of a generated branch name
+const MAX_BRANCH_NAME_LEN: usize = 60;
+
+/// Find the git repository containing `start` by walking up the directory tree.
+///
+/// `.git` may be a directory or, for worktrees and submodules, a file.
+pub fn find_repo_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Find the git repository for a workspace, or fail with a helpful error
+pub fn require_repo(workspace_root: &Path) -> Result<PathBuf> {
+    find_repo_root(workspace_root).ok_or_else(|| {
+        GranaryError::InvalidArgument(format!(
+            "No git repository found at or above {}",
+            workspace_root.display()
+        ))
+    })
+}
+
+/// Run a git command in `repo` and return its trimmed stdout
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .map_err(|e| GranaryError::Other(format!("Failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(GranaryError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Generate a branch name from a task: `<task-id>-<title-slug>`
+pub fn branch_name_for_task(task: &Task) -> String {
+    let slug = normalize_slug(&task.title);
+    let mut name = if slug.is_empty() {
+        task.id.clone()
+    } else {
+        format!("{}-{}", task.id, slug)
+    };
+
+    if name.len() > MAX_BRANCH_NAME_LEN {
+        // Never cut into the task ID; trim the slug at a word boundary
+        let mut end = MAX_BRANCH_NAME_LEN;
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        let cut = name[..end]
+            .rfind('-')
+            .filter(|&i| i >= task.id.len())
+            .unwrap_or(task.id.len().max(MAX_BRANCH_NAME_LEN));
+        name.truncate(cut);
+    }
+
+    name
+}
+
+/// Create (or switch to) a branch for a task and record the link.
+///
+/// If the branch already exists it is reused rather than recreated.
+pub async fn create_branch(
+    pool: &SqlitePool,
+    repo: &Path,
+    task_id: &str,
+    name: Option<String>,
+    checkout: bool,
+) -> Result<TaskVcsLink> {
+    let task = get_task(pool, task_id).await?;
+    let name = name.unwrap_or_else(|| branch_name_for_task(&task));
+
+    git(repo, &["check-ref-format", "--branch", &name])
+        .map_err(|_| GranaryError::InvalidArgument(format!("Invalid branch name: {}", name)))?;
+
+    let exists = git(
+        repo,
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("refs/heads/{}", name),
+        ],
+    )
+    .is_ok();
+
+    match (exists, checkout) {
+        (true, true) => {
+            git(repo, &["checkout", &name])?;
+        }
+        (false, true) => {
+            git(repo, &["checkout", "-b", &name])?;
+        }
+        (false, false) => {
+            git(repo, &["branch", &name])?;
+        }
+        (true, false) => {}
+    }
+
+    record_link(pool, &task.id, VcsLinkKind::Branch, &name, None).await
+}
+
+/// Resolve revisions to commits and record them against a task.
+///
+/// Defaults to `HEAD` when no revisions are given.
+pub async fn link_commits(
+    pool: &SqlitePool,
+    repo: &Path,
+    task_id: &str,
+    revs: &[String],
+) -> Result<Vec<TaskVcsLink>> {
+    let task = get_task(pool, task_id).await?;
+    let revs = if revs.is_empty() {
+        vec!["HEAD".to_string()]
+    } else {
+        revs.to_vec()
+    };
+
+    // Resolve everything before recording anything
+    let mut commits = Vec::new();
+    for rev in &revs {
+        let sha = git(
+            repo,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", rev),
+            ],
+        )
+        .map_err(|_| GranaryError::InvalidArgument(format!("Unknown commit: {}", rev)))?;
+        let summary = git(repo, &["log", "-1", "--format=%s", &sha])?;
+        commits.push((sha, summary));
+    }
+
+    let mut links = Vec::new();
+    for (sha, summary) in commits {
+        links.push(record_link(pool, &task.id, VcsLinkKind::Commit, &sha, Some(&summary)).await?);
+    }
+    Ok(links)
+}
+
+/// Name of the currently checked-out branch, if any
+pub fn current_branch(repo: &Path) -> Option<String> {
+    git(repo, &["symbolic-ref", "--quiet", "--short", "HEAD"]).ok()
+}
+
+/// Find the task linked to a branch
+pub async fn task_for_branch(pool: &SqlitePool, branch: &str) -> Result<Option<String>> {
+    let links = db::vcs_links::list_by_ref(pool, branch).await?;
+    Ok(links
+        .into_iter()
+        .find(|l| l.kind_enum() == Some(VcsLinkKind::Branch))
+        .map(|l| l.task_id))
+}
+
+/// List branches and commits linked to a task
+pub async fn list_links(pool: &SqlitePool, task_id: &str) -> Result<Vec<TaskVcsLink>> {
+    let _task = get_task(pool, task_id).await?;
+    db::vcs_links::list_by_task(pool, task_id).await
+}
+
+/// Remove a branch or commit link from a task
+pub async fn unlink(pool: &SqlitePool, task_id: &str, ref_name: &str) -> Result<u64> {
+    let _task = get_task(pool, task_id).await?;
+    let removed = db::vcs_links::remove(pool, task_id, ref_name).await?;
+    if removed == 0 {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Task {} is not linked to {}",
+            task_id, ref_name
+        )));
+    }
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskVcsUnlinked,
+            entity_type: EntityType::Task,
+            entity_id: task_id.to_string(),
+            actor: None,
+            session_id: None,
+            payload: serde_json::json!({ "ref": ref_name }),
+        },
+    )
+    .await?;
+
+    Ok(removed)
+}
+
+async fn record_link(
+    pool: &SqlitePool,
+    task_id: &str,
+    kind: VcsLinkKind,
+    ref_name: &str,
+    summary: Option<&str>,
+) -> Result<TaskVcsLink> {
+    let added = db::vcs_links::add(pool, task_id, kind, ref_name, summary).await?;
+
+    if added {
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::TaskVcsLinked,
+                entity_type: EntityType::Task,
+                entity_id: task_id.to_string(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({
+                    "kind": kind.as_str(),
+                    "ref": ref_name,
+                    "summary": summary,
+                }),
+            },
+        )
+        .await?;
+    }
+
+    db::vcs_links::list_by_task(pool, task_id)
+        .await?
+        .into_iter()
+        .find(|l| l.kind == kind.as_str() && l.ref_name == ref_name)
+        .ok_or_else(|| GranaryError::Conflict("Failed to record VCS link".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task};
+    use tempfile::TempDir;
+
+    fn git_available() -> bool {
+        Command::new("git").arg("--version").output().is_ok()
+    }
+
+    fn init_repo(dir: &Path) {
+        for args in [
+            vec!["init", "-q", "-b", "main"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["config", "user.name", "Test"],
+            vec!["commit", "-q", "--allow-empty", "-m", "Initial commit"],
+        ] {
+            git(dir, &args).unwrap();
+        }
+    }
+
+    async fn setup() -> (TempDir, SqlitePool, Task) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Git Test".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id,
+                title: "Add login form".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        (temp_dir, pool, task)
+    }
+
+    #[test]
+    fn test_find_repo_root_walks_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        assert_eq!(find_repo_root(&nested).unwrap(), temp_dir.path());
+    }
+
+    #[tokio::test]
+    async fn test_branch_name_for_task() {
+        let (_temp_dir, _pool, mut task) = setup().await;
+        task.id = "my-project-abc1-task-3".to_string();
+        task.title = "Fix the OAuth callback!".to_string();
+        assert_eq!(
+            branch_name_for_task(&task),
+            "my-project-abc1-task-3-fix-the-oauth-callback"
+        );
+
+        task.title =
+            "A very long title that keeps going well past the branch name limit".to_string();
+        let name = branch_name_for_task(&task);
+        assert!(name.len() <= MAX_BRANCH_NAME_LEN);
+        assert!(name.starts_with("my-project-abc1-task-3-"));
+        assert!(!name.ends_with('-'));
+    }
+
+    #[tokio::test]
+    async fn test_create_branch_and_link_commits() {
+        if !git_available() {
+            return;
+        }
+        let (temp_dir, pool, task) = setup().await;
+        let repo = temp_dir.path();
+        init_repo(repo);
+
+        let link = create_branch(&pool, repo, &task.id, None, true)
+            .await
+            .unwrap();
+        assert_eq!(link.kind, "branch");
+        assert_eq!(
+            current_branch(repo).as_deref(),
+            Some(link.ref_name.as_str())
+        );
+        assert_eq!(
+            task_for_branch(&pool, &link.ref_name).await.unwrap(),
+            Some(task.id.clone())
+        );
+
+        git(repo, &["commit", "-q", "--allow-empty", "-m", "Add form"]).unwrap();
+        let commits = link_commits(&pool, repo, &task.id, &[]).await.unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].summary.as_deref(), Some("Add form"));
+        assert_eq!(commits[0].ref_name.len(), 40);
+
+        // Linking the same commit twice is a no-op
+        link_commits(&pool, repo, &task.id, &["HEAD".to_string()])
+            .await
+            .unwrap();
+        let links = list_links(&pool, &task.id).await.unwrap();
+        assert_eq!(links.len(), 2);
+
+        // Unlink by abbreviated SHA
+        let short = commits[0].short_ref().to_string();
+        assert_eq!(unlink(&pool, &task.id, &short).await.unwrap(), 1);
+        assert_eq!(list_links(&pool, &task.id).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_link_unknown_commit() {
+        if !git_available() {
+            return;
+        }
+        let (temp_dir, pool, task) = setup().await;
+        init_repo(temp_dir.path());
+
+        let result = link_commits(&pool, temp_dir.path(), &task.id, &["nope".to_string()]).await;
+        assert!(matches!(result, Err(GranaryError::InvalidArgument(_))));
+        assert!(list_links(&pool, &task.id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,510 @@
+//! One-shot import of GitHub issues with `granary import github`.
+//!
+//! Each issue becomes a task with its title, body, labels and comments;
+//! closed issues become done tasks. Nothing is synced back or kept in step
+//! afterwards, but imported issues are recorded so running the import again
+//! only brings in new ones.
+//!
+//! Requests are made with `GITHUB_TOKEN` (or `GH_TOKEN`) when set, which
+//! private repositories and higher rate limits need. `GITHUB_API_URL`
+//! points the import at a GitHub Enterprise server.
+
+use std::time::Duration;
+
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::{batch_service, label_service, project_service, task_service};
+
+/// Provider name in the links recording imported issues
+pub const PROVIDER: &str = "github";
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+const PAGE_SIZE: usize = 100;
+const TASK: &str = "task";
+
+/// Which issues to import by state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IssueState {
+    #[default]
+    Open,
+    Closed,
+    All,
+}
+
+impl IssueState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueState::Open => "open",
+            IssueState::Closed => "closed",
+            IssueState::All => "all",
+        }
+    }
+}
+
+impl std::str::FromStr for IssueState {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(IssueState::Open),
+            "closed" => Ok(IssueState::Closed),
+            "all" => Ok(IssueState::All),
+            _ => Err(format!(
+                "Invalid issue state: {} (expected open, closed or all)",
+                s
+            )),
+        }
+    }
+}
+
+/// What to import and where to
+#[derive(Debug, Clone)]
+pub struct GithubImport {
+    /// Repository as `owner/name`
+    pub repo: String,
+    /// Only issues with all of these labels
+    pub labels: Vec<String>,
+    pub state: IssueState,
+    pub project_id: String,
+    pub dry_run: bool,
+}
+
+/// A GitHub issue with the fields an import uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct GithubIssue {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    /// "open" or "closed"
+    pub state: String,
+    /// Number of comments on the issue
+    pub comments: u64,
+    pub updated_at: String,
+}
+
+/// A comment on a GitHub issue
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct GithubComment {
+    pub author: Option<String>,
+    pub body: String,
+}
+
+/// The parts of GitHub's REST API an import uses
+pub(crate) trait GithubApi {
+    async fn issues(
+        &self,
+        repo: &str,
+        state: IssueState,
+        labels: &[String],
+    ) -> Result<Vec<GithubIssue>>;
+
+    async fn comments(&self, repo: &str, number: u64) -> Result<Vec<GithubComment>>;
+}
+
+/// GitHub's REST API
+pub(crate) struct GithubClient {
+    client: reqwest::Client,
+    api_url: String,
+    token: Option<String>,
+}
+
+impl GithubClient {
+    pub fn from_env() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("granary/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| GranaryError::Other(format!("Failed to create HTTP client: {}", e)))?;
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Ok(Self {
+            client,
+            api_url: env("GITHUB_API_URL")
+                .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            token: env("GITHUB_TOKEN").or_else(|| env("GH_TOKEN")),
+        })
+    }
+
+    /// Fetch every page of a list endpoint
+    async fn get_all(&self, path: &str, params: &[(&str, &str)]) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        for page in 1.. {
+            let page = page.to_string();
+            let per_page = PAGE_SIZE.to_string();
+            let mut query = params.to_vec();
+            query.push(("per_page", &per_page));
+            query.push(("page", &page));
+            let url =
+                reqwest::Url::parse_with_params(&format!("{}/{}", self.api_url, path), &query)
+                    .map_err(|e| {
+                        GranaryError::InvalidArgument(format!("Invalid GitHub URL: {}", e))
+                    })?;
+
+            let mut request = self
+                .client
+                .get(url)
+                .header("Accept", "application/vnd.github+json")
+                .header("X-GitHub-Api-Version", "2022-11-28");
+            if let Some(token) = &self.token {
+                request = request.bearer_auth(token);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| GranaryError::Other(format!("GitHub request failed: {}", e)))?;
+            let status = response.status();
+            let body: Value = response
+                .json()
+                .await
+                .map_err(|e| GranaryError::Other(format!("Invalid response from GitHub: {}", e)))?;
+            if !status.is_success() {
+                let message = body["message"].as_str().unwrap_or(status.as_str());
+                return Err(GranaryError::Other(format!(
+                    "GitHub API error: {}",
+                    message
+                )));
+            }
+
+            let batch = body.as_array().cloned().unwrap_or_default();
+            let last = batch.len() < PAGE_SIZE;
+            items.extend(batch);
+            if last {
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+impl GithubApi for GithubClient {
+    async fn issues(
+        &self,
+        repo: &str,
+        state: IssueState,
+        labels: &[String],
+    ) -> Result<Vec<GithubIssue>> {
+        let labels = labels.join(",");
+        let mut params = vec![
+            ("state", state.as_str()),
+            ("sort", "created"),
+            ("direction", "asc"),
+        ];
+        if !labels.is_empty() {
+            params.push(("labels", &labels));
+        }
+        let items = self
+            .get_all(&format!("repos/{}/issues", repo), &params)
+            .await?;
+        Ok(items
+            .iter()
+            // The issues endpoint lists pull requests too
+            .filter(|item| item.get("pull_request").is_none())
+            .filter_map(parse_issue)
+            .collect())
+    }
+
+    async fn comments(&self, repo: &str, number: u64) -> Result<Vec<GithubComment>> {
+        let items = self
+            .get_all(&format!("repos/{}/issues/{}/comments", repo, number), &[])
+            .await?;
+        Ok(items
+            .iter()
+            .map(|c| GithubComment {
+                author: c["user"]["login"].as_str().map(str::to_string),
+                body: c["body"].as_str().unwrap_or_default().to_string(),
+            })
+            .collect())
+    }
+}
+
+fn parse_issue(node: &Value) -> Option<GithubIssue> {
+    Some(GithubIssue {
+        number: node["number"].as_u64()?,
+        title: node["title"].as_str()?.to_string(),
+        body: node["body"]
+            .as_str()
+            .filter(|b| !b.trim().is_empty())
+            .map(str::to_string),
+        labels: node["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l["name"].as_str().map(str::to_string))
+            .collect(),
+        state: node["state"].as_str().unwrap_or("open").to_string(),
+        comments: node["comments"].as_u64().unwrap_or_default(),
+        updated_at: node["updated_at"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Check a repository is given as `owner/name`
+fn validate_repo(repo: &str) -> Result<()> {
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok(())
+        }
+        _ => Err(GranaryError::InvalidArgument(format!(
+            "Invalid repository '{}'. Expected owner/name",
+            repo
+        ))),
+    }
+}
+
+/// Import a repository's issues into a project
+pub async fn import(pool: &SqlitePool, options: &GithubImport) -> Result<ImportReport> {
+    validate_repo(&options.repo)?;
+    let client = GithubClient::from_env()?;
+    import_with(pool, &client, options).await
+}
+
+pub(crate) async fn import_with(
+    pool: &SqlitePool,
+    api: &impl GithubApi,
+    options: &GithubImport,
+) -> Result<ImportReport> {
+    let project = project_service::get_project(pool, &options.project_id).await?;
+    let mut report = ImportReport {
+        source: format!("{}:{}", PROVIDER, options.repo),
+        project_id: project.id.clone(),
+        dry_run: options.dry_run,
+        items: Vec::new(),
+    };
+
+    let issues = api
+        .issues(&options.repo, options.state, &options.labels)
+        .await?;
+    for issue in issues {
+        let remote_id = format!("{}#{}", options.repo, issue.number);
+        let status = if issue.state == "closed" {
+            TaskStatus::Done
+        } else {
+            TaskStatus::Todo
+        };
+        let labels: Vec<String> = issue
+            .labels
+            .iter()
+            .map(|l| label_service::label_name(l))
+            .filter(|l| !l.is_empty())
+            .collect();
+        let mut item = ImportedItem {
+            source: remote_id.clone(),
+            title: issue.title.clone(),
+            task_id: None,
+            status: status.as_str().to_string(),
+            labels: labels.clone(),
+            comments: issue.comments as usize,
+            skipped: false,
+        };
+
+        if let Some(link) = db::sync::get_link_by_remote(pool, PROVIDER, TASK, &remote_id).await? {
+            item.task_id = Some(link.local_id);
+            item.skipped = true;
+            report.items.push(item);
+            continue;
+        }
+        if options.dry_run {
+            report.items.push(item);
+            continue;
+        }
+
+        let task = task_service::create_task(
+            pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: issue.title.clone(),
+                description: issue.body.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        if !labels.is_empty() {
+            db::labels::apply(pool, &task.id, &labels, &[]).await?;
+        }
+        if issue.comments > 0 {
+            let comments = api.comments(&options.repo, issue.number).await?;
+            item.comments = comments.len();
+            for comment in comments {
+                batch_service::create_comment(
+                    pool,
+                    CreateComment {
+                        parent_type: ParentType::Task,
+                        parent_id: task.id.clone(),
+                        content: comment.body,
+                        author: comment.author,
+                        ..Default::default()
+                    },
+                )
+                .await?;
+            }
+        }
+        let task = if status == TaskStatus::Done {
+            task_service::complete_task(pool, &task.id, None, None).await?
+        } else {
+            task_service::update_task(
+                pool,
+                &task.id,
+                UpdateTask {
+                    status: Some(status),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?
+        };
+
+        db::sync::upsert_link(
+            pool,
+            &SyncLink {
+                provider: PROVIDER.to_string(),
+                entity_type: TASK.to_string(),
+                local_id: task.id.clone(),
+                remote_id: remote_id.clone(),
+                remote_key: Some(remote_id),
+                local_updated_at: task.updated_at.clone(),
+                remote_updated_at: issue.updated_at.clone(),
+                synced_at: chrono::Utc::now().to_rfc3339(),
+            },
+        )
+        .await?;
+        item.task_id = Some(task.id);
+        report.items.push(item);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use tempfile::TempDir;
+
+    /// A repository with a fixed list of issues
+    struct FakeGithub {
+        issues: Vec<GithubIssue>,
+    }
+
+    impl GithubApi for FakeGithub {
+        async fn issues(
+            &self,
+            _repo: &str,
+            state: IssueState,
+            labels: &[String],
+        ) -> Result<Vec<GithubIssue>> {
+            Ok(self
+                .issues
+                .iter()
+                .filter(|i| state == IssueState::All || i.state == state.as_str())
+                .filter(|i| labels.iter().all(|l| i.labels.contains(l)))
+                .cloned()
+                .collect())
+        }
+
+        async fn comments(&self, _repo: &str, number: u64) -> Result<Vec<GithubComment>> {
+            Ok((0..self.issues[number as usize - 1].comments)
+                .map(|n| GithubComment {
+                    author: Some("octocat".to_string()),
+                    body: format!("Comment {}", n + 1),
+                })
+                .collect())
+        }
+    }
+
+    fn issue(number: u64, title: &str, state: &str, labels: &[&str], comments: u64) -> GithubIssue {
+        GithubIssue {
+            number,
+            title: title.to_string(),
+            body: Some(format!("Body of {}", title)),
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            state: state.to_string(),
+            comments,
+            updated_at: "2030-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_validate_repo() {
+        assert!(validate_repo("rust-lang/rust").is_ok());
+        assert!(validate_repo("rust").is_err());
+        assert!(validate_repo("a/b/c").is_err());
+        assert!(validate_repo("/rust").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_import_issues_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = project_service::create_project(
+            &pool,
+            CreateProject {
+                name: "Bugs".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let github = FakeGithub {
+            issues: vec![
+                issue(1, "Crash on start", "open", &["bug", "good first issue"], 2),
+                issue(2, "Slow sync", "closed", &["bug"], 0),
+                issue(3, "Add dark mode", "open", &["feature"], 0),
+            ],
+        };
+        let mut options = GithubImport {
+            repo: "acme/app".to_string(),
+            labels: vec!["bug".to_string()],
+            state: IssueState::All,
+            project_id: project.id.clone(),
+            dry_run: true,
+        };
+
+        let report = import_with(&pool, &github, &options).await.unwrap();
+        assert_eq!(report.imported(), 2);
+        assert!(report.items.iter().all(|i| i.task_id.is_none()));
+        assert!(
+            task_service::list_tasks_by_project(&pool, &project.id, true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        options.dry_run = false;
+        let report = import_with(&pool, &github, &options).await.unwrap();
+        assert_eq!(report.imported(), 2);
+        let crash = task_service::get_task(&pool, report.items[0].task_id.as_ref().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(crash.title, "Crash on start");
+        assert_eq!(crash.status, "todo");
+        assert_eq!(crash.description.as_deref(), Some("Body of Crash on start"));
+        assert_eq!(crash.labels, vec!["bug", "good-first-issue"]);
+        let comments = db::comments::list_by_parent(&pool, &crash.id)
+            .await
+            .unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].author.as_deref(), Some("octocat"));
+
+        let slow = task_service::get_task(&pool, report.items[1].task_id.as_ref().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(slow.status, "done");
+
+        // A second run skips what was imported
+        let report = import_with(&pool, &github, &options).await.unwrap();
+        assert_eq!(report.imported(), 0);
+        assert!(report.items.iter().all(|i| i.skipped));
+        assert_eq!(
+            task_service::list_tasks_by_project(&pool, &project.id, true)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}
@@ -8,12 +8,19 @@ use crate::error::{GranaryError, Result};
 use crate::models::global_config::{GlobalConfig, RunnerConfig};
 use sqlx::SqlitePool;
 use std::path::PathBuf;
+use std::sync::OnceLock;
 use tokio::sync::OnceCell;
 
 /// Singleton for the global database pool.
 /// Ensures migrations run exactly once before any queries.
 static GLOBAL_POOL: OnceCell<SqlitePool> = OnceCell::const_new();
 
+/// Environment variable selecting the config profile
+pub const PROFILE_ENV: &str = "GRANARY_PROFILE";
+
+/// Profile chosen with `--profile`, which takes precedence over the environment
+static PROFILE_OVERRIDE: OnceLock<String> = OnceLock::new();
+
 /// Get the global granary config directory (~/.granary)
 pub fn config_dir() -> Result<PathBuf> {
     dirs::home_dir()
@@ -138,9 +145,49 @@ pub async fn global_pool() -> Result<SqlitePool> {
         .cloned()
 }
 
-/// Load the global configuration from ~/.granary/config.toml
+/// Use a profile for the rest of the process, as `--profile` does.
+/// Fails if the config has no such profile.
+pub fn set_profile(name: &str) -> Result<()> {
+    if !load_file()?.profiles.contains_key(name) {
+        return Err(unknown_profile(name));
+    }
+    let _ = PROFILE_OVERRIDE.set(name.to_string());
+    Ok(())
+}
+
+/// The profile in use: `--profile`, else `GRANARY_PROFILE`
+pub fn active_profile() -> Option<String> {
+    PROFILE_OVERRIDE
+        .get()
+        .cloned()
+        .or_else(|| std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty()))
+}
+
+fn unknown_profile(name: &str) -> GranaryError {
+    GranaryError::GlobalConfig(format!(
+        "No profile '{}' in {}",
+        name,
+        config_path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| "config.toml".to_string())
+    ))
+}
+
+/// Load the global configuration with the active profile applied.
 /// Returns default config if file doesn't exist.
 pub fn load() -> Result<GlobalConfig> {
+    let config = load_file()?;
+    match active_profile() {
+        Some(name) => config
+            .with_profile(&name)
+            .ok_or_else(|| unknown_profile(&name)),
+        None => Ok(config),
+    }
+}
+
+/// Load ~/.granary/config.toml as written, without applying a profile.
+/// Returns default config if file doesn't exist.
+pub fn load_file() -> Result<GlobalConfig> {
     let path = config_path()?;
     if !path.exists() {
         return Ok(GlobalConfig::default());
@@ -173,17 +220,35 @@ pub fn get_runner(name: &str) -> Result<Option<RunnerConfig>> {
     Ok(config.runners.get(name).cloned())
 }
 
-/// Add or update a runner configuration
+/// Add or update a runner configuration, in the active profile if there is one
 pub fn set_runner(name: &str, runner: RunnerConfig) -> Result<()> {
-    let mut config = load()?;
-    config.runners.insert(name.to_string(), runner);
+    let mut config = load_file()?;
+    match active_profile() {
+        Some(profile) => config
+            .profiles
+            .get_mut(&profile)
+            .ok_or_else(|| unknown_profile(&profile))?
+            .runners
+            .insert(name.to_string(), runner),
+        None => config.runners.insert(name.to_string(), runner),
+    };
     save(&config)
 }
 
-/// Remove a runner configuration
+/// Remove a runner configuration, from the active profile if there is one
 pub fn remove_runner(name: &str) -> Result<bool> {
-    let mut config = load()?;
-    let removed = config.runners.remove(name).is_some();
+    let mut config = load_file()?;
+    let runners = match active_profile() {
+        Some(profile) => {
+            &mut config
+                .profiles
+                .get_mut(&profile)
+                .ok_or_else(|| unknown_profile(&profile))?
+                .runners
+        }
+        None => &mut config.runners,
+    };
+    let removed = runners.remove(name).is_some();
     if removed {
         save(&config)?;
     }
@@ -0,0 +1,717 @@
+//! GraphQL API over the workspace, served by `granary serve --graphql`.
+//!
+//! Initiatives, projects, tasks, comments and runs can be queried with their
+//! relations nested (a project's tasks, a task's comments and runs, ...), so
+//! a tool gets exactly the shape it needs in one request. Mutations call the
+//! same services as the CLI, so events, validation and status rules apply
+//! the same way.
+//!
+//! Built only with the `graphql` feature.
+
+use async_graphql::{Context, EmptySubscription, InputObject, Object, Schema};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::GranaryError;
+use crate::models::*;
+use crate::services::{self, batch_service, global_config};
+
+/// Runs returned by the `runs` query when no limit is given
+const DEFAULT_RUN_LIMIT: u32 = 50;
+
+pub type GranarySchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+type GqlResult<T> = async_graphql::Result<T>;
+
+/// Build the schema over a workspace database
+pub fn schema(pool: SqlitePool) -> GranarySchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(pool)
+        .finish()
+}
+
+fn pool<'a>(ctx: &Context<'a>) -> &'a SqlitePool {
+    ctx.data_unchecked::<SqlitePool>()
+}
+
+/// The session a request changes tasks as, checked against task claims and
+/// the session's mode. Added to a request with `Request::data`.
+pub struct SessionId(pub String);
+
+fn session_id<'a>(ctx: &Context<'a>) -> Option<&'a str> {
+    ctx.data_opt::<SessionId>()
+        .map(|session| session.0.as_str())
+}
+
+fn parse<T: std::str::FromStr>(value: &str, what: &str) -> Result<T, GranaryError> {
+    value
+        .parse()
+        .map_err(|_| GranaryError::InvalidArgument(format!("Invalid {}: {}", what, value)))
+}
+
+fn tasks(tasks: Vec<Task>) -> Vec<TaskNode> {
+    tasks.into_iter().map(TaskNode).collect()
+}
+
+fn comments(comments: Vec<Comment>) -> Vec<CommentNode> {
+    comments.into_iter().map(CommentNode).collect()
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    async fn initiatives(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> GqlResult<Vec<InitiativeNode>> {
+        let initiatives = services::list_initiatives(pool(ctx), include_archived).await?;
+        Ok(initiatives.into_iter().map(InitiativeNode).collect())
+    }
+
+    async fn initiative(&self, ctx: &Context<'_>, id: String) -> GqlResult<Option<InitiativeNode>> {
+        let initiative = services::get_initiative(pool(ctx), &id).await?;
+        Ok(initiative.map(InitiativeNode))
+    }
+
+    async fn projects(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] include_archived: bool,
+    ) -> GqlResult<Vec<ProjectNode>> {
+        let projects = services::list_projects(pool(ctx), include_archived).await?;
+        Ok(projects.into_iter().map(ProjectNode).collect())
+    }
+
+    async fn project(&self, ctx: &Context<'_>, id: String) -> GqlResult<ProjectNode> {
+        Ok(ProjectNode(services::get_project(pool(ctx), &id).await?))
+    }
+
+    /// Tasks of every project, or of one, optionally with one status
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        project_id: Option<String>,
+        status: Option<String>,
+        #[graphql(default)] include_archived: bool,
+    ) -> GqlResult<Vec<TaskNode>> {
+        let pool = pool(ctx);
+        let mut list = match project_id {
+            Some(id) => {
+                let project = services::get_project(pool, &id).await?;
+                services::list_tasks_by_project(pool, &project.id, include_archived).await?
+            }
+            None => services::list_all_tasks(pool, include_archived).await?,
+        };
+        if let Some(status) = status {
+            let status: TaskStatus = parse(&status, "task status")?;
+            list.retain(|t| t.status == status.as_str());
+        }
+        Ok(tasks(list))
+    }
+
+    async fn task(&self, ctx: &Context<'_>, id: String) -> GqlResult<TaskNode> {
+        Ok(TaskNode(services::get_task(pool(ctx), &id).await?))
+    }
+
+    async fn comment(&self, ctx: &Context<'_>, id: String) -> GqlResult<Option<CommentNode>> {
+        Ok(db::comments::get(pool(ctx), &id).await?.map(CommentNode))
+    }
+
+    /// Runs of every workspace, newest first
+    async fn runs(
+        &self,
+        worker_id: Option<String>,
+        status: Option<String>,
+        limit: Option<u32>,
+    ) -> GqlResult<Vec<RunNode>> {
+        let statuses = match status {
+            Some(status) => vec![parse(&status, "run status")?],
+            None => RunStatus::ALL.to_vec(),
+        };
+        let runs = db::runs::list_filtered(
+            &global_config::global_pool().await?,
+            worker_id.as_deref(),
+            &statuses,
+            false,
+            &Sort::default(),
+            Page::new(Some(limit.unwrap_or(DEFAULT_RUN_LIMIT)), 0),
+        )
+        .await?;
+        Ok(runs.into_iter().map(RunNode).collect())
+    }
+
+    async fn run(&self, id: String) -> GqlResult<Option<RunNode>> {
+        let run = db::runs::get(&global_config::global_pool().await?, &id).await?;
+        Ok(run.map(RunNode))
+    }
+}
+
+/// A task to create
+#[derive(InputObject)]
+pub struct CreateTaskInput {
+    pub project_id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub priority: Option<String>,
+    /// Makes the task a subtask of this one
+    pub parent_task_id: Option<String>,
+    pub owner: Option<String>,
+    pub due_at: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// Fields to change on a task; fields left out are kept
+#[derive(InputObject)]
+pub struct UpdateTaskInput {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<String>,
+    pub priority: Option<String>,
+    pub owner: Option<String>,
+    pub due_at: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    async fn create_project(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        description: Option<String>,
+    ) -> GqlResult<ProjectNode> {
+        let project = services::create_project(
+            pool(ctx),
+            CreateProject {
+                name,
+                description,
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(ProjectNode(project))
+    }
+
+    async fn create_task(&self, ctx: &Context<'_>, input: CreateTaskInput) -> GqlResult<TaskNode> {
+        let priority = input
+            .priority
+            .map(|p| parse(&p, "priority"))
+            .transpose()?
+            .unwrap_or_default();
+        let due_at = input
+            .due_at
+            .map(|due| services::datetime::parse_due(&due, &services::datetime::settings()))
+            .transpose()?;
+        let task = services::create_task(
+            pool(ctx),
+            CreateTask {
+                project_id: input.project_id,
+                parent_task_id: input.parent_task_id,
+                title: input.title,
+                description: input.description,
+                priority,
+                owner: input.owner,
+                tags: input.tags.unwrap_or_default(),
+                due_at,
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(TaskNode(task))
+    }
+
+    async fn update_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        input: UpdateTaskInput,
+    ) -> GqlResult<TaskNode> {
+        let updates = UpdateTask {
+            title: input.title,
+            description: input.description,
+            status: input.status.map(|s| parse(&s, "task status")).transpose()?,
+            priority: input.priority.map(|p| parse(&p, "priority")).transpose()?,
+            owner: input.owner,
+            due_at: input
+                .due_at
+                .map(|due| services::datetime::parse_due(&due, &services::datetime::settings()))
+                .transpose()?,
+            tags: input.tags,
+            ..Default::default()
+        };
+        Ok(TaskNode(
+            services::update_task(pool(ctx), &id, updates, session_id(ctx)).await?,
+        ))
+    }
+
+    /// Move a draft task to todo
+    async fn ready_task(&self, ctx: &Context<'_>, id: String) -> GqlResult<TaskNode> {
+        Ok(TaskNode(
+            services::ready_task(pool(ctx), &id, session_id(ctx)).await?,
+        ))
+    }
+
+    async fn start_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        owner: Option<String>,
+    ) -> GqlResult<TaskNode> {
+        Ok(TaskNode(
+            services::start_task(pool(ctx), &id, owner, session_id(ctx)).await?,
+        ))
+    }
+
+    async fn complete_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        comment: Option<String>,
+    ) -> GqlResult<TaskNode> {
+        let task =
+            services::complete_task(pool(ctx), &id, comment.as_deref(), session_id(ctx)).await?;
+        Ok(TaskNode(task))
+    }
+
+    async fn block_task(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        reason: String,
+    ) -> GqlResult<TaskNode> {
+        Ok(TaskNode(
+            services::block_task(pool(ctx), &id, &reason, session_id(ctx)).await?,
+        ))
+    }
+
+    async fn unblock_task(&self, ctx: &Context<'_>, id: String) -> GqlResult<TaskNode> {
+        Ok(TaskNode(
+            services::unblock_task(pool(ctx), &id, session_id(ctx)).await?,
+        ))
+    }
+
+    /// Make a task wait for another; returns the waiting task
+    async fn add_dependency(
+        &self,
+        ctx: &Context<'_>,
+        task_id: String,
+        depends_on: String,
+    ) -> GqlResult<TaskNode> {
+        let pool = pool(ctx);
+        services::add_dependency(pool, &task_id, &depends_on).await?;
+        Ok(TaskNode(services::get_task(pool, &task_id).await?))
+    }
+
+    /// Comment on a task, or reply to one of its comments
+    async fn add_comment(
+        &self,
+        ctx: &Context<'_>,
+        task_id: String,
+        content: String,
+        #[graphql(default_with = "\"note\".to_string()")] kind: String,
+        reply_to: Option<String>,
+        author: Option<String>,
+    ) -> GqlResult<CommentNode> {
+        let pool = pool(ctx);
+        let task = services::get_task(pool, &task_id).await?;
+        let comment = batch_service::create_comment(
+            pool,
+            CreateComment {
+                parent_type: ParentType::Task,
+                parent_id: task.id,
+                parent_comment_id: reply_to,
+                kind: parse(&kind, "comment kind")?,
+                content,
+                author,
+                meta: None,
+            },
+        )
+        .await?;
+        Ok(CommentNode(comment))
+    }
+}
+
+pub struct InitiativeNode(Initiative);
+
+#[Object(name = "Initiative")]
+impl InitiativeNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+    async fn owner(&self) -> Option<&str> {
+        self.0.owner.as_deref()
+    }
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+    async fn tags(&self) -> Vec<String> {
+        self.0.tags_vec()
+    }
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+    async fn updated_at(&self) -> &str {
+        &self.0.updated_at
+    }
+
+    async fn projects(&self, ctx: &Context<'_>) -> GqlResult<Vec<ProjectNode>> {
+        let projects = services::get_initiative_projects(pool(ctx), &self.0.id).await?;
+        Ok(projects.into_iter().map(ProjectNode).collect())
+    }
+}
+
+pub struct ProjectNode(Project);
+
+#[Object(name = "Project")]
+impl ProjectNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+    async fn slug(&self) -> &str {
+        &self.0.slug
+    }
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+    async fn owner(&self) -> Option<&str> {
+        self.0.owner.as_deref()
+    }
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+    async fn tags(&self) -> Vec<String> {
+        self.0.tags_vec()
+    }
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+    async fn updated_at(&self) -> &str {
+        &self.0.updated_at
+    }
+
+    async fn tasks(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        #[graphql(default)] include_archived: bool,
+    ) -> GqlResult<Vec<TaskNode>> {
+        let mut list =
+            services::list_tasks_by_project(pool(ctx), &self.0.id, include_archived).await?;
+        if let Some(status) = status {
+            let status: TaskStatus = parse(&status, "task status")?;
+            list.retain(|t| t.status == status.as_str());
+        }
+        Ok(tasks(list))
+    }
+
+    async fn initiatives(&self, ctx: &Context<'_>) -> GqlResult<Vec<InitiativeNode>> {
+        let initiatives = services::get_project_initiatives(pool(ctx), &self.0.id).await?;
+        Ok(initiatives.into_iter().map(InitiativeNode).collect())
+    }
+
+    async fn comments(&self, ctx: &Context<'_>) -> GqlResult<Vec<CommentNode>> {
+        Ok(comments(
+            db::comments::list_by_parent(pool(ctx), &self.0.id).await?,
+        ))
+    }
+}
+
+pub struct TaskNode(Task);
+
+#[Object(name = "Task")]
+impl TaskNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+    async fn project_id(&self) -> &str {
+        &self.0.project_id
+    }
+    async fn number(&self) -> i64 {
+        self.0.task_number
+    }
+    async fn parent_task_id(&self) -> Option<&str> {
+        self.0.parent_task_id.as_deref()
+    }
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+    async fn priority(&self) -> &str {
+        &self.0.priority
+    }
+    async fn owner(&self) -> Option<&str> {
+        self.0.owner.as_deref()
+    }
+    async fn author(&self) -> Option<&str> {
+        self.0.author.as_deref()
+    }
+    async fn tags(&self) -> Vec<String> {
+        self.0.tags_vec()
+    }
+    async fn labels(&self) -> &[String] {
+        &self.0.labels
+    }
+    /// Value of a custom field
+    async fn field(&self, name: String) -> Option<&str> {
+        self.0.fields.get(&name).map(String::as_str)
+    }
+    async fn blocked_reason(&self) -> Option<&str> {
+        self.0.blocked_reason.as_deref()
+    }
+    async fn due_at(&self) -> Option<&str> {
+        self.0.due_at.as_deref()
+    }
+    async fn estimate(&self) -> Option<f64> {
+        self.0.estimate
+    }
+    async fn claim_owner(&self) -> Option<&str> {
+        self.0.claim_owner.as_deref()
+    }
+    async fn pinned(&self) -> bool {
+        self.0.pinned != 0
+    }
+    async fn started_at(&self) -> Option<&str> {
+        self.0.started_at.as_deref()
+    }
+    async fn completed_at(&self) -> Option<&str> {
+        self.0.completed_at.as_deref()
+    }
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+    async fn updated_at(&self) -> &str {
+        &self.0.updated_at
+    }
+
+    async fn project(&self, ctx: &Context<'_>) -> GqlResult<ProjectNode> {
+        Ok(ProjectNode(
+            services::get_project(pool(ctx), &self.0.project_id).await?,
+        ))
+    }
+
+    async fn parent(&self, ctx: &Context<'_>) -> GqlResult<Option<TaskNode>> {
+        match &self.0.parent_task_id {
+            Some(id) => Ok(Some(TaskNode(services::get_task(pool(ctx), id).await?))),
+            None => Ok(None),
+        }
+    }
+
+    async fn subtasks(&self, ctx: &Context<'_>) -> GqlResult<Vec<TaskNode>> {
+        Ok(tasks(services::list_subtasks(pool(ctx), &self.0.id).await?))
+    }
+
+    /// Tasks this task waits for
+    async fn dependencies(&self, ctx: &Context<'_>) -> GqlResult<Vec<TaskNode>> {
+        Ok(tasks(
+            services::list_dependencies(pool(ctx), &self.0.id).await?,
+        ))
+    }
+
+    async fn comments(&self, ctx: &Context<'_>) -> GqlResult<Vec<CommentNode>> {
+        Ok(comments(
+            db::comments::list_by_parent(pool(ctx), &self.0.id).await?,
+        ))
+    }
+
+    /// Runs started by events on this task, newest first
+    async fn runs(&self) -> GqlResult<Vec<RunNode>> {
+        let runs =
+            db::runs::list_by_entity(&global_config::global_pool().await?, &self.0.id).await?;
+        Ok(runs.into_iter().map(RunNode).collect())
+    }
+}
+
+pub struct CommentNode(Comment);
+
+#[Object(name = "Comment")]
+impl CommentNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+    async fn parent_type(&self) -> &str {
+        &self.0.parent_type
+    }
+    async fn parent_id(&self) -> &str {
+        &self.0.parent_id
+    }
+    /// The comment this one replies to
+    async fn reply_to(&self) -> Option<&str> {
+        self.0.parent_comment_id.as_deref()
+    }
+    async fn number(&self) -> i64 {
+        self.0.comment_number
+    }
+    async fn kind(&self) -> &str {
+        &self.0.kind
+    }
+    async fn content(&self) -> &str {
+        &self.0.content
+    }
+    async fn author(&self) -> Option<&str> {
+        self.0.author.as_deref()
+    }
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    /// The task commented on; null for comments on other entities
+    async fn task(&self, ctx: &Context<'_>) -> GqlResult<Option<TaskNode>> {
+        if self.0.parent_type_enum() != Some(ParentType::Task) {
+            return Ok(None);
+        }
+        Ok(Some(TaskNode(
+            services::get_task(pool(ctx), &self.0.parent_id).await?,
+        )))
+    }
+}
+
+pub struct RunNode(Run);
+
+#[Object(name = "Run")]
+impl RunNode {
+    async fn id(&self) -> &str {
+        &self.0.id
+    }
+    async fn worker_id(&self) -> &str {
+        &self.0.worker_id
+    }
+    async fn event_type(&self) -> &str {
+        &self.0.event_type
+    }
+    async fn entity_id(&self) -> &str {
+        &self.0.entity_id
+    }
+    async fn command(&self) -> &str {
+        &self.0.command
+    }
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+    async fn exit_code(&self) -> Option<i32> {
+        self.0.exit_code
+    }
+    async fn error_message(&self) -> Option<&str> {
+        self.0.error_message.as_deref()
+    }
+    async fn attempt(&self) -> i32 {
+        self.0.attempt
+    }
+    async fn max_attempts(&self) -> i32 {
+        self.0.max_attempts
+    }
+    async fn log_path(&self) -> Option<&str> {
+        self.0.log_path.as_deref()
+    }
+    async fn started_at(&self) -> Option<&str> {
+        self.0.started_at.as_deref()
+    }
+    async fn completed_at(&self) -> Option<&str> {
+        self.0.completed_at.as_deref()
+    }
+    async fn created_at(&self) -> &str {
+        &self.0.created_at
+    }
+
+    /// The task the run was started for, when its entity is a task of this
+    /// workspace
+    async fn task(&self, ctx: &Context<'_>) -> GqlResult<Option<TaskNode>> {
+        match services::get_task(pool(ctx), &self.0.entity_id).await {
+            Ok(task) => Ok(Some(TaskNode(task))),
+            Err(GranaryError::TaskNotFound(_)) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_nested_query_and_mutations() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let schema = schema(pool);
+
+        let response = schema
+            .execute(r#"mutation { createProject(name: "Api") { id } }"#)
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let project_id = data["createProject"]["id"].as_str().unwrap().to_string();
+
+        let response = schema
+            .execute(format!(
+                r#"mutation {{ createTask(input: {{ projectId: "{}", title: "Schema", priority: "P1" }}) {{ id }} }}"#,
+                project_id
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let task_id = data["createTask"]["id"].as_str().unwrap().to_string();
+
+        let response = schema
+            .execute(format!(
+                r#"mutation {{
+                    updateTask(id: "{0}", input: {{ status: "todo", tags: ["api"] }}) {{ status }}
+                    addComment(taskId: "{0}", content: "Use async-graphql", kind: "decision") {{ kind }}
+                }}"#,
+                task_id
+            ))
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let response = schema
+            .execute(
+                r#"{ projects { name tasks(status: "todo") { title priority tags comments { kind content task { id } } project { name } } } }"#,
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let data = response.data.into_json().unwrap();
+        let task = &data["projects"][0]["tasks"][0];
+        assert_eq!(task["title"], "Schema");
+        assert_eq!(task["priority"], "P1");
+        assert_eq!(task["tags"][0], "api");
+        assert_eq!(task["comments"][0]["kind"], "decision");
+        assert_eq!(task["comments"][0]["task"]["id"], task_id.as_str());
+        assert_eq!(task["project"]["name"], "Api");
+
+        let response = schema
+            .execute(r#"{ task(id: "missing-0000-task-1") { id } }"#)
+            .await;
+        assert_eq!(response.errors.len(), 1);
+        assert!(response.errors[0].message.contains("not found"));
+
+        let response = schema
+            .execute(format!(
+                r#"mutation {{ updateTask(id: "{}", input: {{ status: "later" }}) {{ id }} }}"#,
+                task_id
+            ))
+            .await;
+        assert!(response.errors[0].message.contains("Invalid task status"));
+    }
+}
@@ -130,6 +130,124 @@ pub async fn get_project_initiatives(
     db::initiative_projects::list_initiatives(pool, project_id).await
 }
 
+// === Milestones ===
+
+use crate::models::ids::generate_milestone_id;
+use crate::models::initiative::{CreateMilestone, Milestone, MilestoneProgress};
+
+/// Add a milestone to an initiative, attaching projects and tasks to it.
+///
+/// Attached projects, and the projects of attached tasks, must already
+/// belong to the initiative.
+pub async fn add_milestone(pool: &SqlitePool, input: CreateMilestone) -> Result<MilestoneProgress> {
+    let initiative = get_initiative_or_error(pool, &input.initiative_id).await?;
+    if input.name.trim().is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "Milestone name cannot be empty".to_string(),
+        ));
+    }
+
+    let member_ids: Vec<String> = db::initiative_projects::list_projects(pool, &initiative.id)
+        .await?
+        .into_iter()
+        .map(|p| p.id)
+        .collect();
+    let check_member = |project_id: &str| {
+        if member_ids.iter().any(|id| id == project_id) {
+            Ok(())
+        } else {
+            Err(GranaryError::InvalidArgument(format!(
+                "Project {} is not in initiative {}; add it with `granary initiative {} add-project {}`",
+                project_id, initiative.id, initiative.id, project_id
+            )))
+        }
+    };
+    for project_id in &input.project_ids {
+        let project = services::get_project(pool, project_id).await?;
+        check_member(&project.id)?;
+    }
+    for task_id in &input.task_ids {
+        let task = services::get_task(pool, task_id).await?;
+        check_member(&task.project_id)?;
+    }
+
+    let scope = format!("initiative:{}:milestone", initiative.id);
+    let milestone_number = db::counters::next(pool, &scope).await?;
+    let milestone = db::milestones::create(
+        pool,
+        &generate_milestone_id(&initiative.id, milestone_number),
+        &initiative.id,
+        milestone_number,
+        input.name.trim(),
+        input.description.as_deref(),
+        input.target_date.as_deref(),
+    )
+    .await?;
+    for project_id in &input.project_ids {
+        db::milestones::add_project(pool, &milestone.id, project_id).await?;
+    }
+    for task_id in &input.task_ids {
+        db::milestones::add_task(pool, &milestone.id, task_id).await?;
+    }
+
+    milestone_progress(pool, milestone).await
+}
+
+/// List an initiative's milestones with their progress
+pub async fn list_milestones(
+    pool: &SqlitePool,
+    initiative_id: &str,
+) -> Result<Vec<MilestoneProgress>> {
+    let _ = get_initiative_or_error(pool, initiative_id).await?;
+
+    let mut milestones = Vec::new();
+    for milestone in db::milestones::list(pool, initiative_id).await? {
+        milestones.push(milestone_progress(pool, milestone).await?);
+    }
+    Ok(milestones)
+}
+
+/// Get a milestone with its progress
+pub async fn get_milestone(pool: &SqlitePool, id: &str) -> Result<MilestoneProgress> {
+    let milestone = db::milestones::get(pool, id)
+        .await?
+        .ok_or_else(|| GranaryError::MilestoneNotFound(id.to_string()))?;
+    milestone_progress(pool, milestone).await
+}
+
+/// Mark a milestone complete
+pub async fn complete_milestone(pool: &SqlitePool, id: &str) -> Result<MilestoneProgress> {
+    let milestone = get_milestone(pool, id).await?;
+    if !db::milestones::complete(pool, &milestone.milestone.id).await? {
+        return Err(GranaryError::Conflict(format!(
+            "Milestone {} is already complete",
+            id
+        )));
+    }
+
+    get_milestone(pool, id).await
+}
+
+async fn milestone_progress(pool: &SqlitePool, milestone: Milestone) -> Result<MilestoneProgress> {
+    let projects = db::milestones::list_projects(pool, &milestone.id).await?;
+    let tasks = db::milestones::list_tasks(pool, &milestone.id).await?;
+    let (total, done) = db::milestones::task_counts(pool, &milestone.id).await?;
+    let percent_complete = if total > 0 {
+        (done as f32 / total as f32) * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(MilestoneProgress {
+        milestone,
+        projects,
+        tasks,
+        total_tasks: total as usize,
+        done_tasks: done as usize,
+        percent_complete,
+    })
+}
+
 // === Next task operations ===
 
 use crate::models::Task;
@@ -189,7 +307,7 @@ pub async fn generate_initiative_summary(
     let mut blockers = Vec::new();
 
     for proj in &projects {
-        let tasks = db::tasks::list_by_project(pool, &proj.id).await?;
+        let tasks = db::tasks::list_by_project(pool, &proj.id, false).await?;
         let proj_total = tasks.len();
         let proj_done = tasks.iter().filter(|t| t.status == "done").count();
         let proj_blocked_tasks: Vec<_> = tasks
@@ -295,5 +413,120 @@ pub async fn generate_initiative_summary(
         projects: project_summaries,
         blockers,
         next_actions,
+        milestones: list_milestones(pool, initiative_id).await?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateProject, CreateTask, TaskStatus, UpdateTask};
+    use crate::services::{Workspace, create_project, create_task, update_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_milestone_progress_counts_attached_work() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let initiative = create_initiative(
+            &pool,
+            CreateInitiative {
+                name: "Platform".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut projects = Vec::new();
+        for name in ["Api", "Web", "Docs"] {
+            let project = create_project(
+                &pool,
+                CreateProject {
+                    name: name.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            projects.push(project.id);
+        }
+        add_project_to_initiative(&pool, &initiative.id, &projects[0])
+            .await
+            .unwrap();
+        add_project_to_initiative(&pool, &initiative.id, &projects[1])
+            .await
+            .unwrap();
+        let mut tasks = Vec::new();
+        for project_id in [&projects[0], &projects[0], &projects[1], &projects[1]] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project_id.clone(),
+                    title: "Work".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            tasks.push(task.id);
+        }
+        update_task(
+            &pool,
+            &tasks[0],
+            UpdateTask {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        // Projects outside the initiative can't be attached
+        let outside = add_milestone(
+            &pool,
+            CreateMilestone {
+                initiative_id: initiative.id.clone(),
+                name: "Beta".to_string(),
+                project_ids: vec![projects[2].clone()],
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(matches!(outside, Err(GranaryError::InvalidArgument(_))));
+
+        // The Api project's two tasks plus one Web task; tasks[0] is counted once
+        let milestone = add_milestone(
+            &pool,
+            CreateMilestone {
+                initiative_id: initiative.id.clone(),
+                name: "Beta".to_string(),
+                project_ids: vec![projects[0].clone()],
+                task_ids: vec![tasks[0].clone(), tasks[2].clone()],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            milestone.milestone.id,
+            format!("{}-milestone-1", initiative.id)
+        );
+        assert_eq!((milestone.done_tasks, milestone.total_tasks), (1, 3));
+
+        let completed = complete_milestone(&pool, &milestone.milestone.id)
+            .await
+            .unwrap();
+        assert!(completed.milestone.is_completed());
+        assert!(matches!(
+            complete_milestone(&pool, &milestone.milestone.id).await,
+            Err(GranaryError::Conflict(_))
+        ));
+
+        let summary = generate_initiative_summary(&pool, &initiative.id, 5)
+            .await
+            .unwrap();
+        assert_eq!(summary.milestones.len(), 1);
+    }
+}
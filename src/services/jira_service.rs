@@ -0,0 +1,1166 @@
+//! Import from and sync with Jira.
+//!
+//! `granary sync jira` imports the issues matched by a JQL query into a
+//! project as tasks, then keeps them in step: status changes and comments
+//! made on either side are copied to the other. Jira statuses map to task
+//! statuses through a mapping table in workspace config, falling back to
+//! the status category (to do, in progress, done).
+//!
+//! Settings are workspace config keys:
+//! - `sync.jira.url`: site URL, e.g. `https://acme.atlassian.net` (required)
+//! - `sync.jira.email`: account email for the API token (required)
+//! - `sync.jira.token`: API token, defaults to `${JIRA_API_TOKEN}`
+//! - `sync.jira.jql`: query selecting the issues to sync
+//! - `sync.jira.project`: project the issues are imported into
+//! - `sync.jira.conflict`: `local`, `remote` or `newest` (the default)
+//! - `sync.jira.status.<jira status>`: task status for a Jira status
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::global_config::expand_env_vars;
+use crate::models::*;
+use crate::services::{batch_service, project_service, task_service};
+
+/// Provider name in sync links and cursors
+pub const PROVIDER: &str = "jira";
+
+/// Prefix of the config keys for Jira sync
+pub const JIRA_KEY_PREFIX: &str = "sync.jira.";
+
+/// Prefix of the config keys mapping Jira statuses to task statuses
+pub const JIRA_STATUS_PREFIX: &str = "sync.jira.status.";
+
+const DEFAULT_TOKEN: &str = "${JIRA_API_TOKEN}";
+const PAGE_SIZE: usize = 100;
+const TASK: &str = "task";
+const COMMENT: &str = "comment";
+
+/// Settings for a Jira sync, from `sync.jira.*` config
+#[derive(Debug, Clone)]
+pub struct JiraConfig {
+    pub url: String,
+    pub email: String,
+    pub token: String,
+    pub jql: String,
+    pub project: String,
+    pub conflict: ConflictStrategy,
+    pub statuses: StatusMap,
+}
+
+/// What to sync, given on the command line instead of in config
+#[derive(Debug, Clone, Default)]
+pub struct JiraTarget {
+    pub jql: Option<String>,
+    pub project: Option<String>,
+}
+
+impl JiraConfig {
+    /// Read the settings from workspace config, with `target` overriding
+    /// the configured query and project
+    pub async fn load(pool: &SqlitePool, target: &JiraTarget) -> Result<JiraConfig> {
+        let required = |value: Option<String>, name: &str, example: &str| {
+            value.filter(|v| !v.trim().is_empty()).ok_or_else(|| {
+                GranaryError::InvalidArgument(format!(
+                    "No Jira {} configured. Set one with: granary config set {}{} {}",
+                    name, JIRA_KEY_PREFIX, name, example
+                ))
+            })
+        };
+
+        let url = required(
+            setting(pool, "url").await?,
+            "url",
+            "https://acme.atlassian.net",
+        )?;
+        let email = required(setting(pool, "email").await?, "email", "me@example.com")?;
+        let token = expand_env_vars(
+            &setting(pool, "token")
+                .await?
+                .unwrap_or_else(|| DEFAULT_TOKEN.to_string()),
+        );
+        if token.trim().is_empty() {
+            return Err(GranaryError::InvalidArgument(
+                "No Jira API token. Set JIRA_API_TOKEN or sync.jira.token".to_string(),
+            ));
+        }
+        let jql = match &target.jql {
+            Some(jql) => Some(jql.clone()),
+            None => setting(pool, "jql").await?,
+        };
+        let jql = required(jql, "jql", "'project = ENG'")?;
+        let project = match &target.project {
+            Some(project) => Some(project.clone()),
+            None => setting(pool, "project").await?,
+        };
+        let project = required(project, "project", "<PROJECT_ID>")?;
+        let conflict = match setting(pool, "conflict").await? {
+            Some(value) => value.parse().map_err(GranaryError::InvalidArgument)?,
+            None => ConflictStrategy::default(),
+        };
+
+        Ok(JiraConfig {
+            url: url.trim().trim_end_matches('/').to_string(),
+            email: email.trim().to_string(),
+            token,
+            jql,
+            project,
+            conflict,
+            statuses: StatusMap::load(pool).await?,
+        })
+    }
+}
+
+async fn setting(pool: &SqlitePool, name: &str) -> Result<Option<String>> {
+    db::config::get(pool, &format!("{}{}", JIRA_KEY_PREFIX, name)).await
+}
+
+/// Validate a value for a `sync.jira.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(name) = key.strip_prefix(JIRA_KEY_PREFIX) else {
+        return Ok(());
+    };
+    if let Some(status) = name.strip_prefix("status.") {
+        if status.trim().is_empty() {
+            return Err(GranaryError::InvalidArgument(
+                "Name the Jira status to map, e.g. sync.jira.status.\"In Review\"".to_string(),
+            ));
+        }
+        return value.parse::<TaskStatus>().map(|_| ()).map_err(|_| {
+            GranaryError::InvalidArgument(format!(
+                "Invalid task status for {}: {}. Expected draft, todo, in_progress, done or blocked",
+                key, value
+            ))
+        });
+    }
+    match name {
+        "email" | "token" | "jql" | "project" if value.trim().is_empty() => Err(
+            GranaryError::InvalidArgument(format!("{} cannot be empty", key)),
+        ),
+        "email" | "token" | "jql" | "project" => Ok(()),
+        "url" if value.starts_with("http://") || value.starts_with("https://") => Ok(()),
+        "url" => Err(GranaryError::InvalidArgument(format!(
+            "Invalid URL for {}: {}",
+            key, value
+        ))),
+        "conflict" => value
+            .parse::<ConflictStrategy>()
+            .map(|_| ())
+            .map_err(GranaryError::InvalidArgument),
+        _ => Err(GranaryError::InvalidArgument(format!(
+            "Unknown Jira sync setting: {}. Expected url, email, token, jql, project, \
+             conflict or status.<name>",
+            key
+        ))),
+    }
+}
+
+/// Jira statuses mapped to task statuses, from `sync.jira.status.<name>`
+#[derive(Debug, Clone, Default)]
+pub struct StatusMap {
+    /// Lowercased Jira status names and their task status
+    entries: Vec<(String, TaskStatus)>,
+}
+
+impl StatusMap {
+    pub async fn load(pool: &SqlitePool) -> Result<StatusMap> {
+        let entries = db::config::list(pool)
+            .await?
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let name = key.strip_prefix(JIRA_STATUS_PREFIX)?;
+                Some((name.trim().to_lowercase(), value.parse().ok()?))
+            })
+            .collect();
+        Ok(StatusMap { entries })
+    }
+
+    /// The task status for a Jira status, from the mapping table or else the
+    /// status category ("new", "indeterminate" or "done")
+    pub fn to_local(&self, status: &str, category: &str) -> Option<TaskStatus> {
+        let status = status.trim().to_lowercase();
+        if let Some((_, mapped)) = self.entries.iter().find(|(name, _)| *name == status) {
+            return Some(mapped.clone());
+        }
+        match category {
+            "new" => Some(TaskStatus::Todo),
+            "indeterminate" => Some(TaskStatus::InProgress),
+            "done" => Some(TaskStatus::Done),
+            _ => None,
+        }
+    }
+}
+
+/// Sync the workspace with Jira using the configured settings.
+///
+/// A query or project in `target` is saved for later syncs unless this is
+/// a dry run.
+pub async fn sync(
+    pool: &SqlitePool,
+    options: &SyncOptions,
+    target: &JiraTarget,
+) -> Result<SyncReport> {
+    let config = JiraConfig::load(pool, target).await?;
+    let client = JiraClient::new(&config)?;
+    let report = sync_with(pool, &client, &config, options).await?;
+
+    if !options.dry_run {
+        for (name, value) in [("jql", &target.jql), ("project", &target.project)] {
+            if let Some(value) = value {
+                db::config::set(pool, &format!("{}{}", JIRA_KEY_PREFIX, name), value).await?;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// A comment on a Jira issue
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct JiraComment {
+    pub id: String,
+    pub author: Option<String>,
+    pub body: String,
+    pub created: String,
+}
+
+/// A Jira issue with the fields a sync uses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct JiraIssue {
+    pub id: String,
+    pub key: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub status: String,
+    /// Status category key: "new", "indeterminate" or "done"
+    pub status_category: String,
+    pub priority: Option<String>,
+    /// Last update as RFC 3339
+    pub updated: String,
+    pub comments: Vec<JiraComment>,
+}
+
+/// A workflow transition available on an issue
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JiraTransition {
+    pub id: String,
+    pub status: String,
+    pub status_category: String,
+}
+
+/// The parts of Jira's REST API a sync uses
+pub(crate) trait JiraApi {
+    /// Issues matching `jql`, only those updated since `since` if given
+    async fn search(&self, jql: &str, since: Option<&str>) -> Result<Vec<JiraIssue>>;
+
+    async fn issue(&self, key: &str) -> Result<JiraIssue>;
+
+    async fn transitions(&self, key: &str) -> Result<Vec<JiraTransition>>;
+
+    async fn transition(&self, key: &str, transition_id: &str) -> Result<()>;
+
+    async fn add_comment(&self, key: &str, body: &str) -> Result<JiraComment>;
+}
+
+/// Jira Cloud's REST API
+pub(crate) struct JiraClient {
+    client: reqwest::Client,
+    url: String,
+    email: String,
+    token: String,
+}
+
+const ISSUE_FIELDS: &str = "summary,description,status,priority,updated,comment";
+
+impl JiraClient {
+    pub fn new(config: &JiraConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| GranaryError::Other(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            url: config.url.clone(),
+            email: config.email.clone(),
+            token: config.token.clone(),
+        })
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        let response = request
+            .basic_auth(&self.email, Some(&self.token))
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(|e| GranaryError::Other(format!("Jira request failed: {}", e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GranaryError::Other(format!("Invalid response from Jira: {}", e)))?;
+        let body: Value = if text.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).unwrap_or(Value::Null)
+        };
+
+        if !status.is_success() {
+            let mut messages: Vec<String> = body["errorMessages"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str().map(str::to_string))
+                .collect();
+            if let Some(errors) = body["errors"].as_object() {
+                messages.extend(errors.iter().map(|(field, e)| format!("{}: {}", field, e)));
+            }
+            if messages.is_empty() {
+                messages.push(status.to_string());
+            }
+            return Err(GranaryError::Other(format!(
+                "Jira API error: {}",
+                messages.join("; ")
+            )));
+        }
+        Ok(body)
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/rest/api/2/{}", self.url, path)
+    }
+
+    fn url_with(&self, path: &str, params: &[(&str, &str)]) -> Result<reqwest::Url> {
+        reqwest::Url::parse_with_params(&self.endpoint(path), params)
+            .map_err(|e| GranaryError::InvalidArgument(format!("Invalid Jira URL: {}", e)))
+    }
+}
+
+impl JiraApi for JiraClient {
+    async fn search(&self, jql: &str, since: Option<&str>) -> Result<Vec<JiraIssue>> {
+        let jql = scoped_jql(jql, since, chrono::Utc::now());
+        let mut issues = Vec::new();
+        loop {
+            let url = self.url_with(
+                "search",
+                &[
+                    ("jql", jql.as_str()),
+                    ("fields", ISSUE_FIELDS),
+                    ("startAt", &issues.len().to_string()),
+                    ("maxResults", &PAGE_SIZE.to_string()),
+                ],
+            )?;
+            let request = self.client.get(url);
+            let body = self.send(request).await?;
+            let page = body["issues"].as_array().cloned().unwrap_or_default();
+            for issue in &page {
+                issues.push(parse_issue(issue)?);
+            }
+            let total = body["total"].as_u64().unwrap_or_default() as usize;
+            if page.is_empty() || issues.len() >= total {
+                break;
+            }
+        }
+        Ok(issues)
+    }
+
+    async fn issue(&self, key: &str) -> Result<JiraIssue> {
+        let url = self.url_with(&format!("issue/{}", key), &[("fields", ISSUE_FIELDS)])?;
+        let request = self.client.get(url);
+        parse_issue(&self.send(request).await?)
+    }
+
+    async fn transitions(&self, key: &str) -> Result<Vec<JiraTransition>> {
+        let request = self
+            .client
+            .get(self.endpoint(&format!("issue/{}/transitions", key)));
+        let body = self.send(request).await?;
+        Ok(body["transitions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| {
+                Some(JiraTransition {
+                    id: t["id"].as_str()?.to_string(),
+                    status: t["to"]["name"].as_str()?.to_string(),
+                    status_category: t["to"]["statusCategory"]["key"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                })
+            })
+            .collect())
+    }
+
+    async fn transition(&self, key: &str, transition_id: &str) -> Result<()> {
+        let request = self
+            .client
+            .post(self.endpoint(&format!("issue/{}/transitions", key)))
+            .json(&json!({ "transition": { "id": transition_id } }));
+        self.send(request).await.map(|_| ())
+    }
+
+    async fn add_comment(&self, key: &str, body: &str) -> Result<JiraComment> {
+        let request = self
+            .client
+            .post(self.endpoint(&format!("issue/{}/comment", key)))
+            .json(&json!({ "body": body }));
+        parse_comment(&self.send(request).await?)
+    }
+}
+
+/// Restrict a query to issues updated since `since`, oldest first.
+///
+/// Absolute dates in JQL are read in the Jira user's timezone, so the
+/// cursor becomes a relative `-Nm` with a minute of slack. Issues matched
+/// twice are skipped by their unchanged update time.
+fn scoped_jql(jql: &str, since: Option<&str>, now: chrono::DateTime<chrono::Utc>) -> String {
+    let query = match jql.to_lowercase().find("order by") {
+        Some(index) => jql[..index].trim(),
+        None => jql.trim(),
+    };
+    let since = since
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|since| (now - since.to_utc()).num_minutes().max(0) + 1);
+    match since {
+        Some(minutes) => format!(
+            "({}) AND updated >= \"-{}m\" ORDER BY updated ASC",
+            query, minutes
+        ),
+        None => format!("{} ORDER BY updated ASC", query),
+    }
+}
+
+/// Jira timestamps like `2026-01-05T10:00:00.000+0000` as RFC 3339
+fn normalize_time(value: &str) -> String {
+    chrono::DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .map(|t| t.to_utc().to_rfc3339())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+fn string_field(node: &Value, name: &str) -> Result<String> {
+    node[name]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| GranaryError::Other(format!("Jira response is missing {}", name)))
+}
+
+fn parse_comment(node: &Value) -> Result<JiraComment> {
+    Ok(JiraComment {
+        id: string_field(node, "id")?,
+        author: node["author"]["displayName"].as_str().map(str::to_string),
+        body: node["body"].as_str().unwrap_or_default().to_string(),
+        created: normalize_time(node["created"].as_str().unwrap_or_default()),
+    })
+}
+
+fn parse_issue(node: &Value) -> Result<JiraIssue> {
+    let fields = &node["fields"];
+    Ok(JiraIssue {
+        id: string_field(node, "id")?,
+        key: string_field(node, "key")?,
+        summary: fields["summary"].as_str().unwrap_or_default().to_string(),
+        description: fields["description"]
+            .as_str()
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        status: fields["status"]["name"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        status_category: fields["status"]["statusCategory"]["key"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        priority: fields["priority"]["name"].as_str().map(str::to_string),
+        updated: normalize_time(fields["updated"].as_str().unwrap_or_default()),
+        comments: fields["comment"]["comments"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(parse_comment)
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Task priority for a Jira priority name
+fn task_priority(priority: Option<&str>) -> TaskPriority {
+    match priority.map(str::to_lowercase).as_deref() {
+        Some("highest" | "blocker") => TaskPriority::P0,
+        Some("high" | "critical") => TaskPriority::P1,
+        Some("low" | "minor") => TaskPriority::P3,
+        Some("lowest" | "trivial") => TaskPriority::P4,
+        _ => TaskPriority::P2,
+    }
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().to_string()
+}
+
+fn comment_change(
+    local_id: Option<String>,
+    remote_key: String,
+    body: &str,
+    direction: SyncDirection,
+) -> SyncChange {
+    SyncChange {
+        entity_type: COMMENT.to_string(),
+        local_id,
+        remote_key: Some(remote_key),
+        title: first_line(body),
+        direction,
+        created: true,
+        conflict: false,
+    }
+}
+
+async fn save_link(
+    pool: &SqlitePool,
+    entity_type: &str,
+    local_id: &str,
+    local_updated_at: &str,
+    remote_id: &str,
+    remote_key: &str,
+    remote_updated_at: &str,
+) -> Result<()> {
+    db::sync::upsert_link(
+        pool,
+        &SyncLink {
+            provider: PROVIDER.to_string(),
+            entity_type: entity_type.to_string(),
+            local_id: local_id.to_string(),
+            remote_id: remote_id.to_string(),
+            remote_key: Some(remote_key.to_string()),
+            local_updated_at: local_updated_at.to_string(),
+            remote_updated_at: remote_updated_at.to_string(),
+            synced_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+    .await
+}
+
+/// Copy the issue's comments that are not on the task yet
+async fn pull_comments(
+    pool: &SqlitePool,
+    task_id: Option<&str>,
+    issue: &JiraIssue,
+    dry_run: bool,
+    report: &mut SyncReport,
+) -> Result<()> {
+    for comment in &issue.comments {
+        let remote_key = format!("{}#{}", issue.key, comment.id);
+        if db::sync::get_link_by_remote(pool, PROVIDER, COMMENT, &comment.id)
+            .await?
+            .is_some()
+        {
+            continue;
+        }
+        let local_id = match (task_id, dry_run) {
+            (Some(task_id), false) => {
+                let created = batch_service::create_comment(
+                    pool,
+                    CreateComment {
+                        parent_type: ParentType::Task,
+                        parent_id: task_id.to_string(),
+                        parent_comment_id: None,
+                        kind: CommentKind::Note,
+                        content: comment.body.clone(),
+                        author: comment.author.clone(),
+                        meta: Some(json!({ "jira_comment": remote_key })),
+                    },
+                )
+                .await?;
+                save_link(
+                    pool,
+                    COMMENT,
+                    &created.id,
+                    &created.updated_at,
+                    &comment.id,
+                    &remote_key,
+                    &comment.created,
+                )
+                .await?;
+                Some(created.id)
+            }
+            _ => None,
+        };
+        report.changes.push(comment_change(
+            local_id,
+            remote_key,
+            &comment.body,
+            SyncDirection::Pull,
+        ));
+    }
+    Ok(())
+}
+
+/// Move the issue to a status that maps to the task's status. Returns the
+/// updated issue, or `None` when it already matches or no transition leads
+/// to a matching status.
+async fn push_status(
+    api: &impl JiraApi,
+    statuses: &StatusMap,
+    issue: &JiraIssue,
+    status: &TaskStatus,
+    dry_run: bool,
+) -> Result<Option<JiraIssue>> {
+    if statuses
+        .to_local(&issue.status, &issue.status_category)
+        .as_ref()
+        == Some(status)
+    {
+        return Ok(None);
+    }
+    let transitions = api.transitions(&issue.key).await?;
+    let Some(transition) = transitions
+        .iter()
+        .find(|t| statuses.to_local(&t.status, &t.status_category).as_ref() == Some(status))
+    else {
+        return Ok(None);
+    };
+    if dry_run {
+        return Ok(Some(JiraIssue {
+            status: transition.status.clone(),
+            status_category: transition.status_category.clone(),
+            ..issue.clone()
+        }));
+    }
+    api.transition(&issue.key, &transition.id).await?;
+    Ok(Some(api.issue(&issue.key).await?))
+}
+
+fn task_change(task: &Task, issue: &JiraIssue, direction: SyncDirection) -> SyncChange {
+    SyncChange {
+        entity_type: TASK.to_string(),
+        local_id: Some(task.id.clone()),
+        remote_key: Some(issue.key.clone()),
+        title: task.title.clone(),
+        direction,
+        created: false,
+        conflict: false,
+    }
+}
+
+/// Sync with Jira through `api`
+pub(crate) async fn sync_with(
+    pool: &SqlitePool,
+    api: &impl JiraApi,
+    config: &JiraConfig,
+    options: &SyncOptions,
+) -> Result<SyncReport> {
+    let project = project_service::get_project(pool, &config.project).await?;
+    let strategy = options.conflict.unwrap_or(config.conflict);
+    let dry_run = options.dry_run;
+    let mut report = SyncReport {
+        provider: PROVIDER.to_string(),
+        dry_run,
+        conflict_strategy: strategy,
+        changes: Vec::new(),
+    };
+
+    let cursor = if options.full {
+        None
+    } else {
+        db::sync::get_cursor(pool, PROVIDER, TASK).await?
+    };
+    let issues = api.search(&config.jql, cursor.as_deref()).await?;
+    let mut next_cursor = cursor.clone();
+    let mut handled: HashSet<String> = HashSet::new();
+
+    // Pull: import new issues, and copy remote status changes and comments
+    for issue in &issues {
+        if next_cursor.as_deref() < Some(issue.updated.as_str()) {
+            next_cursor = Some(issue.updated.clone());
+        }
+        let remote_status = config
+            .statuses
+            .to_local(&issue.status, &issue.status_category);
+        let link = db::sync::get_link_by_remote(pool, PROVIDER, TASK, &issue.id).await?;
+
+        let Some(link) = link else {
+            let task_id = if dry_run {
+                None
+            } else {
+                let task = task_service::create_task(
+                    pool,
+                    CreateTask {
+                        project_id: project.id.clone(),
+                        title: issue.summary.clone(),
+                        description: issue.description.clone(),
+                        priority: task_priority(issue.priority.as_deref()),
+                        ..Default::default()
+                    },
+                )
+                .await?;
+                if let Some(status) = remote_status.clone().filter(|s| !s.is_draft()) {
+                    task_service::update_task(
+                        pool,
+                        &task.id,
+                        UpdateTask {
+                            status: Some(status),
+                            ..Default::default()
+                        },
+                        None,
+                    )
+                    .await?;
+                }
+                Some(task.id)
+            };
+            report.changes.push(SyncChange {
+                entity_type: TASK.to_string(),
+                local_id: task_id.clone(),
+                remote_key: Some(issue.key.clone()),
+                title: issue.summary.clone(),
+                direction: SyncDirection::Pull,
+                created: true,
+                conflict: false,
+            });
+            pull_comments(pool, task_id.as_deref(), issue, dry_run, &mut report).await?;
+            if let Some(task_id) = task_id {
+                let task = task_service::get_task(pool, &task_id).await?;
+                save_link(
+                    pool,
+                    TASK,
+                    &task.id,
+                    &task.updated_at,
+                    &issue.id,
+                    &issue.key,
+                    &issue.updated,
+                )
+                .await?;
+                handled.insert(task_id);
+            }
+            continue;
+        };
+
+        if issue.updated == link.remote_updated_at {
+            continue;
+        }
+        let Ok(task) = task_service::get_task(pool, &link.local_id).await else {
+            continue;
+        };
+        if task.archived_at.is_some() {
+            continue;
+        }
+        handled.insert(task.id.clone());
+
+        let mut issue = issue.clone();
+        let local_changed = task.updated_at != link.local_updated_at;
+        let status_differs = remote_status.as_ref() != Some(&task.status_enum());
+        if status_differs {
+            let direction = if local_changed {
+                strategy.resolve(&task.updated_at, &issue.updated)
+            } else {
+                SyncDirection::Pull
+            };
+            match direction {
+                SyncDirection::Pull => {
+                    if let Some(status) = remote_status.clone()
+                        && !dry_run
+                    {
+                        task_service::update_task(
+                            pool,
+                            &task.id,
+                            UpdateTask {
+                                status: Some(status),
+                                ..Default::default()
+                            },
+                            None,
+                        )
+                        .await?;
+                    }
+                    if remote_status.is_some() {
+                        let mut change = task_change(&task, &issue, SyncDirection::Pull);
+                        change.conflict = local_changed;
+                        report.changes.push(change);
+                    }
+                }
+                SyncDirection::Push => {
+                    let status = task.status_enum();
+                    if let Some(moved) =
+                        push_status(api, &config.statuses, &issue, &status, dry_run).await?
+                    {
+                        issue = moved;
+                        let mut change = task_change(&task, &issue, SyncDirection::Push);
+                        change.conflict = true;
+                        report.changes.push(change);
+                    }
+                }
+            }
+        }
+        pull_comments(pool, Some(&task.id), &issue, dry_run, &mut report).await?;
+
+        if !dry_run {
+            let task = task_service::get_task(pool, &task.id).await?;
+            save_link(
+                pool,
+                TASK,
+                &task.id,
+                &task.updated_at,
+                &issue.id,
+                &issue.key,
+                &issue.updated,
+            )
+            .await?;
+        }
+    }
+
+    // Push: local status changes and new comments on linked tasks
+    for link in db::sync::list_links(pool, PROVIDER).await? {
+        if link.entity_type != TASK {
+            continue;
+        }
+        let Ok(task) = task_service::get_task(pool, &link.local_id).await else {
+            continue;
+        };
+        if task.archived_at.is_some() {
+            continue;
+        }
+        let key = link
+            .remote_key
+            .clone()
+            .unwrap_or_else(|| link.remote_id.clone());
+        let mut changed = false;
+
+        if !handled.contains(&task.id) && task.updated_at != link.local_updated_at {
+            let issue = api.issue(&key).await?;
+            let status = task.status_enum();
+            if let Some(moved) =
+                push_status(api, &config.statuses, &issue, &status, dry_run).await?
+            {
+                report
+                    .changes
+                    .push(task_change(&task, &moved, SyncDirection::Push));
+            }
+            changed = true;
+        }
+
+        for comment in db::comments::list_by_parent(pool, &task.id).await? {
+            if db::sync::get_link(pool, PROVIDER, COMMENT, &comment.id)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            if dry_run {
+                report.changes.push(comment_change(
+                    Some(comment.id),
+                    key.clone(),
+                    &comment.content,
+                    SyncDirection::Push,
+                ));
+                continue;
+            }
+            let body = match &comment.author {
+                Some(author) => format!("{}\n\n— {} (via granary)", comment.content, author),
+                None => comment.content.clone(),
+            };
+            let created = api.add_comment(&key, &body).await?;
+            let remote_key = format!("{}#{}", key, created.id);
+            save_link(
+                pool,
+                COMMENT,
+                &comment.id,
+                &comment.updated_at,
+                &created.id,
+                &remote_key,
+                &created.created,
+            )
+            .await?;
+            report.changes.push(comment_change(
+                Some(comment.id),
+                remote_key,
+                &comment.content,
+                SyncDirection::Push,
+            ));
+            changed = true;
+        }
+
+        // Record both sides as they are now, so these pushes are not
+        // pulled back by the next sync
+        if changed && !dry_run {
+            let issue = api.issue(&key).await?;
+            save_link(
+                pool,
+                TASK,
+                &task.id,
+                &task.updated_at,
+                &issue.id,
+                &issue.key,
+                &issue.updated,
+            )
+            .await?;
+        }
+    }
+
+    if !dry_run
+        && let Some(next_cursor) = next_cursor
+        && Some(&next_cursor) != cursor.as_ref()
+    {
+        db::sync::set_cursor(pool, PROVIDER, TASK, &next_cursor).await?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    const STATUSES: [(&str, &str); 4] = [
+        ("To Do", "new"),
+        ("In Progress", "indeterminate"),
+        ("In Review", "indeterminate"),
+        ("Done", "done"),
+    ];
+
+    /// An in-memory Jira site with a clock that ticks on every change
+    #[derive(Default)]
+    struct FakeJira {
+        issues: Mutex<Vec<JiraIssue>>,
+        clock: Mutex<u32>,
+    }
+
+    impl FakeJira {
+        fn tick(&self) -> String {
+            let mut clock = self.clock.lock().unwrap();
+            *clock += 1;
+            format!("2030-01-01T00:00:{:02}+00:00", *clock)
+        }
+
+        fn add(&self, key: &str, summary: &str, status: &str, comments: Vec<JiraComment>) {
+            let updated = self.tick();
+            let category = STATUSES.iter().find(|(s, _)| *s == status).unwrap().1;
+            self.issues.lock().unwrap().push(JiraIssue {
+                id: format!("id-{}", key),
+                key: key.to_string(),
+                summary: summary.to_string(),
+                status: status.to_string(),
+                status_category: category.to_string(),
+                updated,
+                comments,
+                ..Default::default()
+            });
+        }
+
+        fn get(&self, key: &str) -> JiraIssue {
+            let issues = self.issues.lock().unwrap();
+            issues.iter().find(|i| i.key == key).unwrap().clone()
+        }
+
+        fn set_status(&self, key: &str, status: &str) {
+            let updated = self.tick();
+            let category = STATUSES.iter().find(|(s, _)| *s == status).unwrap().1;
+            let mut issues = self.issues.lock().unwrap();
+            let issue = issues.iter_mut().find(|i| i.key == key).unwrap();
+            issue.status = status.to_string();
+            issue.status_category = category.to_string();
+            issue.updated = updated;
+        }
+    }
+
+    impl JiraApi for FakeJira {
+        async fn search(&self, _jql: &str, since: Option<&str>) -> Result<Vec<JiraIssue>> {
+            Ok(self
+                .issues
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|i| since.is_none_or(|since| i.updated.as_str() >= since))
+                .cloned()
+                .collect())
+        }
+
+        async fn issue(&self, key: &str) -> Result<JiraIssue> {
+            Ok(self.get(key))
+        }
+
+        async fn transitions(&self, _key: &str) -> Result<Vec<JiraTransition>> {
+            Ok(STATUSES
+                .iter()
+                .map(|(status, category)| JiraTransition {
+                    id: status.to_string(),
+                    status: status.to_string(),
+                    status_category: category.to_string(),
+                })
+                .collect())
+        }
+
+        async fn transition(&self, key: &str, transition_id: &str) -> Result<()> {
+            self.set_status(key, transition_id);
+            Ok(())
+        }
+
+        async fn add_comment(&self, key: &str, body: &str) -> Result<JiraComment> {
+            let created = self.tick();
+            let mut issues = self.issues.lock().unwrap();
+            let issue = issues.iter_mut().find(|i| i.key == key).unwrap();
+            let comment = JiraComment {
+                id: format!("c{}", issue.comments.len() + 100),
+                author: Some("Bot".to_string()),
+                body: body.to_string(),
+                created: created.clone(),
+            };
+            issue.comments.push(comment.clone());
+            issue.updated = created;
+            Ok(comment)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_jira_import_and_sync() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = project_service::create_project(
+            &pool,
+            CreateProject {
+                name: "Support".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        db::config::set(&pool, "sync.jira.status.in review", "blocked")
+            .await
+            .unwrap();
+        let config = JiraConfig {
+            url: "https://acme.atlassian.net".to_string(),
+            email: "me@example.com".to_string(),
+            token: "token".to_string(),
+            jql: "project = SUP".to_string(),
+            project: project.id.clone(),
+            conflict: ConflictStrategy::Newest,
+            statuses: StatusMap::load(&pool).await.unwrap(),
+        };
+        let options = SyncOptions::default();
+
+        let jira = FakeJira::default();
+        jira.add(
+            "SUP-1",
+            "Login fails",
+            "To Do",
+            vec![JiraComment {
+                id: "c1".to_string(),
+                author: Some("Ann".to_string()),
+                body: "Seen in prod".to_string(),
+                created: "2030-01-01T00:00:00+00:00".to_string(),
+            }],
+        );
+        jira.add("SUP-2", "Slow export", "In Review", Vec::new());
+
+        // The first sync imports both issues and the comment
+        let report = sync_with(&pool, &jira, &config, &options).await.unwrap();
+        assert_eq!(report.changes.len(), 3);
+        let tasks = task_service::list_tasks_by_project(&pool, &project.id, false)
+            .await
+            .unwrap();
+        let login = tasks.iter().find(|t| t.title == "Login fails").unwrap();
+        let export = tasks.iter().find(|t| t.title == "Slow export").unwrap();
+        assert_eq!(login.status, "todo");
+        assert_eq!(export.status, "blocked");
+        let comments = db::comments::list_by_parent(&pool, &login.id)
+            .await
+            .unwrap();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].author.as_deref(), Some("Ann"));
+
+        // Nothing changed, so nothing syncs
+        let report = sync_with(&pool, &jira, &config, &options).await.unwrap();
+        assert!(report.changes.is_empty(), "{:?}", report.changes);
+
+        // A local status change and comment are pushed
+        task_service::update_task(
+            &pool,
+            &login.id,
+            UpdateTask {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        batch_service::create_comment(
+            &pool,
+            CreateComment {
+                parent_type: ParentType::Task,
+                parent_id: login.id.clone(),
+                content: "Fixed in 1.2".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let report = sync_with(&pool, &jira, &config, &options).await.unwrap();
+        assert_eq!(report.changes.len(), 2);
+        let issue = jira.get("SUP-1");
+        assert_eq!(issue.status, "Done");
+        assert_eq!(issue.comments.len(), 2);
+
+        let report = sync_with(&pool, &jira, &config, &options).await.unwrap();
+        assert!(report.changes.is_empty(), "{:?}", report.changes);
+
+        // A remote status change is pulled
+        jira.set_status("SUP-2", "Done");
+        let report = sync_with(&pool, &jira, &config, &options).await.unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].direction, SyncDirection::Pull);
+        let export = task_service::get_task(&pool, &export.id).await.unwrap();
+        assert_eq!(export.status, "done");
+    }
+
+    #[test]
+    fn test_status_map_and_jql() {
+        let map = StatusMap {
+            entries: vec![("in review".to_string(), TaskStatus::Blocked)],
+        };
+        assert_eq!(
+            map.to_local("In Review", "indeterminate"),
+            Some(TaskStatus::Blocked)
+        );
+        assert_eq!(
+            map.to_local("In Progress", "indeterminate"),
+            Some(TaskStatus::InProgress)
+        );
+        assert_eq!(map.to_local("Closed", "done"), Some(TaskStatus::Done));
+        assert_eq!(map.to_local("Odd", "undefined"), None);
+
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        assert_eq!(
+            scoped_jql("project = SUP order by rank", None, now),
+            "project = SUP ORDER BY updated ASC"
+        );
+        assert_eq!(
+            scoped_jql("project = SUP", Some("2026-01-01T11:30:00+00:00"), now),
+            "(project = SUP) AND updated >= \"-31m\" ORDER BY updated ASC"
+        );
+        assert_eq!(
+            normalize_time("2026-01-05T10:00:00.000+0100"),
+            "2026-01-05T09:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value("sync.jira.url", "https://acme.atlassian.net").is_ok());
+        assert!(validate_config_value("sync.jira.url", "acme").is_err());
+        assert!(validate_config_value("sync.jira.status.In Review", "blocked").is_ok());
+        assert!(validate_config_value("sync.jira.status.In Review", "review").is_err());
+        assert!(validate_config_value("sync.jira.status.", "done").is_err());
+        assert!(validate_config_value("sync.jira.jql", " ").is_err());
+        assert!(validate_config_value("sync.jira.bogus", "x").is_err());
+        assert!(validate_config_value("other.key", "x").is_ok());
+    }
+}
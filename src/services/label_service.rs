@@ -0,0 +1,240 @@
+//! Task labels.
+//!
+//! Labels are short names such as `urgent` or `backend` attached to tasks
+//! through the `task_labels` table. Unlike the free-form `tags` column they
+//! can be filtered on in task lists and are matched by search.
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::get_task;
+
+/// Parse label arguments like `+urgent -frontend backend`
+pub fn parse_label_changes(args: &[String]) -> Result<Vec<LabelChange>> {
+    // Label arguments may start with '-', so the CLI passes any options that
+    // follow them through as labels
+    if let Some(option) = args.iter().find(|arg| arg.starts_with("--")) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Unexpected option '{}' among labels; put options before the task ID",
+            option
+        )));
+    }
+
+    args.iter()
+        .flat_map(|arg| arg.split(','))
+        .filter(|arg| !arg.trim().is_empty())
+        .map(|arg| arg.parse().map_err(GranaryError::InvalidArgument))
+        .collect()
+}
+
+/// A label name for free text from another tool, such as `good first issue`:
+/// spaces and commas become hyphens and a leading + or - is dropped
+pub fn label_name(text: &str) -> String {
+    text.trim()
+        .trim_start_matches(['+', '-'])
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c == ',' {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Add and remove labels on a task
+pub async fn label_task(pool: &SqlitePool, id: &str, changes: &[LabelChange]) -> Result<Task> {
+    let task = get_task(pool, id).await?;
+    if changes.is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "No labels given. Use +label to add and -label to remove".to_string(),
+        ));
+    }
+
+    let mut add = Vec::new();
+    let mut remove = Vec::new();
+    for change in changes {
+        match change {
+            LabelChange::Add(name) => add.push(name.clone()),
+            LabelChange::Remove(name) => remove.push(name.clone()),
+        }
+    }
+    db::labels::apply(pool, &task.id, &add, &remove).await?;
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskUpdated,
+            entity_type: EntityType::Task,
+            entity_id: task.id.clone(),
+            actor: None,
+            session_id: None,
+            payload: serde_json::json!({
+                "labels_added": add,
+                "labels_removed": remove,
+            }),
+        },
+    )
+    .await?;
+
+    get_task(pool, &task.id).await
+}
+
+/// Fill in the labels of each task with a single query
+pub async fn attach_labels(pool: &SqlitePool, tasks: &mut [Task]) -> Result<()> {
+    if tasks.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let labels = db::labels::list_for_tasks(pool, &ids).await?;
+
+    let mut by_task: std::collections::HashMap<String, Vec<String>> =
+        std::collections::HashMap::new();
+    for label in labels {
+        by_task.entry(label.task_id).or_default().push(label.name);
+    }
+    for task in tasks.iter_mut() {
+        task.labels = by_task.remove(&task.id).unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// Keep only tasks that have every one of the given labels (case-insensitive).
+///
+/// Labels must already be attached with [`attach_labels`].
+pub fn filter_by_labels(tasks: Vec<Task>, labels: &[String]) -> Vec<Task> {
+    if labels.is_empty() {
+        return tasks;
+    }
+    tasks
+        .into_iter()
+        .filter(|task| {
+            labels
+                .iter()
+                .all(|wanted| task.labels.iter().any(|l| l.eq_ignore_ascii_case(wanted)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task};
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, SqlitePool, Vec<Task>) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Labels".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut tasks = Vec::new();
+        for title in ["First", "Second"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            tasks.push(task);
+        }
+        (temp_dir, pool, tasks)
+    }
+
+    #[test]
+    fn test_parse_label_changes() {
+        let args: Vec<String> = ["+urgent", "-frontend", "backend,api"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            parse_label_changes(&args).unwrap(),
+            vec![
+                LabelChange::Add("urgent".to_string()),
+                LabelChange::Remove("frontend".to_string()),
+                LabelChange::Add("backend".to_string()),
+                LabelChange::Add("api".to_string()),
+            ]
+        );
+
+        for invalid in ["+", "-", "two words", "--flag"] {
+            let args = vec![invalid.to_string()];
+            assert!(
+                parse_label_changes(&args).is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_label_name() {
+        assert_eq!(label_name("good first issue"), "good-first-issue");
+        assert_eq!(label_name("-wontfix"), "wontfix");
+        assert_eq!(label_name("type: bug"), "type:-bug");
+        assert!(label_name("a, b").parse::<LabelChange>().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_label_task_add_remove() {
+        let (_temp_dir, pool, tasks) = setup().await;
+        let id = &tasks[0].id;
+
+        let changes =
+            parse_label_changes(&["+urgent".to_string(), "+frontend".to_string()]).unwrap();
+        let task = label_task(&pool, id, &changes).await.unwrap();
+        assert_eq!(task.labels, vec!["frontend", "urgent"]);
+
+        // Adding an existing label is a no-op, and removal is case-insensitive
+        let changes =
+            parse_label_changes(&["urgent".to_string(), "-Frontend".to_string()]).unwrap();
+        let task = label_task(&pool, id, &changes).await.unwrap();
+        assert_eq!(task.labels, vec!["urgent"]);
+
+        // Unused labels are cleaned up
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM labels")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        assert!(matches!(
+            label_task(&pool, "missing-task", &changes).await,
+            Err(GranaryError::TaskNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_attach_and_filter_labels() {
+        let (_temp_dir, pool, tasks) = setup().await;
+        let both = parse_label_changes(&["urgent".to_string(), "backend".to_string()]).unwrap();
+        label_task(&pool, &tasks[0].id, &both).await.unwrap();
+        let one = parse_label_changes(&["urgent".to_string()]).unwrap();
+        label_task(&pool, &tasks[1].id, &one).await.unwrap();
+
+        let mut listed = db::tasks::list_all(&pool, false).await.unwrap();
+        attach_labels(&pool, &mut listed).await.unwrap();
+
+        let urgent = filter_by_labels(listed.clone(), &["URGENT".to_string()]);
+        assert_eq!(urgent.len(), 2);
+
+        let backend = filter_by_labels(listed, &["urgent".to_string(), "backend".to_string()]);
+        assert_eq!(backend.len(), 1);
+        assert_eq!(backend[0].id, tasks[0].id);
+    }
+}
@@ -0,0 +1,1146 @@
+//! Two-way sync with Linear.
+//!
+//! `granary sync linear` links initiatives to Linear initiatives, projects to
+//! Linear projects and tasks to Linear issues through Linear's GraphQL API.
+//! A sync first pulls what changed in Linear since the previous sync's
+//! cursor, then pushes what changed locally. When an entity changed on both
+//! sides, the conflict strategy decides which side wins.
+//!
+//! Settings are workspace config keys:
+//! - `sync.linear.team`: key of the Linear team to sync with (required)
+//! - `sync.linear.api_key`: API key, defaults to `${LINEAR_API_KEY}`
+//! - `sync.linear.conflict`: `local`, `remote` or `newest` (the default)
+//! - `sync.linear.api_url`: GraphQL endpoint, defaults to Linear's
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::global_config::expand_env_vars;
+use crate::models::initiative::{CreateInitiative, UpdateInitiative};
+use crate::models::*;
+use crate::services::{initiative_service, project_service, task_service};
+
+/// Provider name in sync links and cursors
+pub const PROVIDER: &str = "linear";
+
+/// Prefix of the config keys for Linear sync
+pub const LINEAR_KEY_PREFIX: &str = "sync.linear.";
+
+const DEFAULT_API_URL: &str = "https://api.linear.app/graphql";
+const DEFAULT_API_KEY: &str = "${LINEAR_API_KEY}";
+const PAGE_SIZE: usize = 100;
+
+/// Settings for a Linear sync, from `sync.linear.*` config
+#[derive(Debug, Clone)]
+pub struct LinearConfig {
+    pub team: String,
+    pub api_key: String,
+    pub api_url: String,
+    pub conflict: ConflictStrategy,
+}
+
+impl LinearConfig {
+    /// Read the settings from workspace config
+    pub async fn load(pool: &SqlitePool) -> Result<LinearConfig> {
+        let team = setting(pool, "team")
+            .await?
+            .filter(|team| !team.trim().is_empty());
+        let Some(team) = team else {
+            return Err(GranaryError::InvalidArgument(
+                "No Linear team configured. Set one with: granary config set sync.linear.team <KEY>"
+                    .to_string(),
+            ));
+        };
+        let api_key = expand_env_vars(
+            &setting(pool, "api_key")
+                .await?
+                .unwrap_or_else(|| DEFAULT_API_KEY.to_string()),
+        );
+        if api_key.trim().is_empty() {
+            return Err(GranaryError::InvalidArgument(
+                "No Linear API key. Set LINEAR_API_KEY or sync.linear.api_key".to_string(),
+            ));
+        }
+        let api_url = setting(pool, "api_url")
+            .await?
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        let conflict = match setting(pool, "conflict").await? {
+            Some(value) => value.parse().map_err(GranaryError::InvalidArgument)?,
+            None => ConflictStrategy::default(),
+        };
+
+        Ok(LinearConfig {
+            team: team.trim().to_string(),
+            api_key,
+            api_url,
+            conflict,
+        })
+    }
+}
+
+async fn setting(pool: &SqlitePool, name: &str) -> Result<Option<String>> {
+    db::config::get(pool, &format!("{}{}", LINEAR_KEY_PREFIX, name)).await
+}
+
+/// Validate a value for a `sync.linear.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(name) = key.strip_prefix(LINEAR_KEY_PREFIX) else {
+        return Ok(());
+    };
+    match name {
+        "team" | "api_key" if value.trim().is_empty() => Err(GranaryError::InvalidArgument(
+            format!("{} cannot be empty", key),
+        )),
+        "team" | "api_key" => Ok(()),
+        "api_url" if value.starts_with("http://") || value.starts_with("https://") => Ok(()),
+        "api_url" => Err(GranaryError::InvalidArgument(format!(
+            "Invalid URL for {}: {}",
+            key, value
+        ))),
+        "conflict" => value
+            .parse::<ConflictStrategy>()
+            .map(|_| ())
+            .map_err(GranaryError::InvalidArgument),
+        _ => Err(GranaryError::InvalidArgument(format!(
+            "Unknown Linear sync setting: {}. Expected team, api_key, conflict or api_url",
+            key
+        ))),
+    }
+}
+
+/// Sync the workspace with Linear using the configured settings
+pub async fn sync(pool: &SqlitePool, options: &SyncOptions) -> Result<SyncReport> {
+    let config = LinearConfig::load(pool).await?;
+    let client = LinearClient::new(&config)?;
+    sync_with(pool, &client, &config, options).await
+}
+
+/// The kinds of entity that are synced, in the order they sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Initiative,
+    Project,
+    Task,
+}
+
+impl Kind {
+    const ALL: [Kind; 3] = [Kind::Initiative, Kind::Project, Kind::Task];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Initiative => "initiative",
+            Kind::Project => "project",
+            Kind::Task => "task",
+        }
+    }
+}
+
+/// The synced fields of an entity, in Linear's terms
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RemoteFields {
+    pub title: String,
+    pub description: Option<String>,
+    /// Workflow state type of an issue: backlog, unstarted, started,
+    /// completed or canceled
+    pub state: Option<String>,
+    /// Issue priority: 0 none, 1 urgent, 2 high, 3 medium, 4 low
+    pub priority: Option<i64>,
+    /// Linear ID of an issue's project
+    pub project_id: Option<String>,
+}
+
+/// An initiative, project or issue in Linear
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct RemoteItem {
+    pub id: String,
+    /// Issue identifier, e.g. "ENG-42"
+    pub key: Option<String>,
+    pub updated_at: String,
+    pub fields: RemoteFields,
+}
+
+impl RemoteItem {
+    fn display_key(&self) -> String {
+        self.key.clone().unwrap_or_else(|| self.id.clone())
+    }
+}
+
+/// A Linear team and its workflow states
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LinearTeam {
+    pub id: String,
+    /// `(state id, state type)` in workflow order
+    pub states: Vec<(String, String)>,
+}
+
+impl LinearTeam {
+    /// The first workflow state of a state type
+    fn state_id(&self, state_type: &str) -> Option<&str> {
+        self.states
+            .iter()
+            .find(|(_, t)| t == state_type)
+            .map(|(id, _)| id.as_str())
+    }
+}
+
+/// The parts of Linear's API a sync uses
+pub(crate) trait LinearApi {
+    /// Look up a team by key
+    async fn team(&self, key: &str) -> Result<LinearTeam>;
+
+    /// Items of a kind updated after `since`, or all of them
+    async fn changed(
+        &self,
+        kind: Kind,
+        team: &LinearTeam,
+        since: Option<&str>,
+    ) -> Result<Vec<RemoteItem>>;
+
+    /// Create an item, or update the one with `remote_id`
+    async fn save(
+        &self,
+        kind: Kind,
+        team: &LinearTeam,
+        remote_id: Option<&str>,
+        fields: &RemoteFields,
+    ) -> Result<RemoteItem>;
+}
+
+/// Linear's GraphQL API
+pub(crate) struct LinearClient {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+}
+
+impl LinearClient {
+    pub fn new(config: &LinearConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .map_err(|e| GranaryError::Other(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            client,
+            url: config.api_url.clone(),
+            api_key: config.api_key.clone(),
+        })
+    }
+
+    async fn request(&self, query: &str, variables: Value) -> Result<Value> {
+        let response = self
+            .client
+            .post(&self.url)
+            .header("Authorization", &self.api_key)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .map_err(|e| GranaryError::Other(format!("Linear request failed: {}", e)))?;
+        let status = response.status();
+        let body: Value = response.json().await.map_err(|e| {
+            GranaryError::Other(format!("Invalid response from Linear ({}): {}", status, e))
+        })?;
+
+        if let Some(errors) = body.get("errors").and_then(Value::as_array)
+            && !errors.is_empty()
+        {
+            let messages: Vec<&str> = errors
+                .iter()
+                .filter_map(|e| e.get("message").and_then(Value::as_str))
+                .collect();
+            return Err(GranaryError::Other(format!(
+                "Linear API error: {}",
+                messages.join("; ")
+            )));
+        }
+        if !status.is_success() {
+            return Err(GranaryError::Other(format!(
+                "Linear API returned {}",
+                status
+            )));
+        }
+        Ok(body.get("data").cloned().unwrap_or(Value::Null))
+    }
+}
+
+/// GraphQL names for a kind: type name, collection and selected fields
+fn graphql_names(kind: Kind) -> (&'static str, &'static str, &'static str) {
+    match kind {
+        Kind::Initiative => ("Initiative", "initiatives", "id name description updatedAt"),
+        Kind::Project => ("Project", "projects", "id name description updatedAt"),
+        Kind::Task => (
+            "Issue",
+            "issues",
+            "id identifier title description priority updatedAt state { type } project { id }",
+        ),
+    }
+}
+
+impl LinearApi for LinearClient {
+    async fn team(&self, key: &str) -> Result<LinearTeam> {
+        let data = self
+            .request(
+                "query($key: String!) { teams(filter: { key: { eq: $key } }) { nodes { id \
+                 states { nodes { id type position } } } } }",
+                json!({ "key": key }),
+            )
+            .await?;
+        let Some(team) = data["teams"]["nodes"].as_array().and_then(|t| t.first()) else {
+            return Err(GranaryError::InvalidArgument(format!(
+                "No Linear team with key {}",
+                key
+            )));
+        };
+
+        let mut states: Vec<(f64, String, String)> = team["states"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|state| {
+                Some((
+                    state["position"].as_f64().unwrap_or_default(),
+                    state["id"].as_str()?.to_string(),
+                    state["type"].as_str()?.to_string(),
+                ))
+            })
+            .collect();
+        states.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(LinearTeam {
+            id: string_field(team, "id")?,
+            states: states.into_iter().map(|(_, id, t)| (id, t)).collect(),
+        })
+    }
+
+    async fn changed(
+        &self,
+        kind: Kind,
+        team: &LinearTeam,
+        since: Option<&str>,
+    ) -> Result<Vec<RemoteItem>> {
+        let (type_name, collection, fields) = graphql_names(kind);
+        let query = format!(
+            "query($filter: {type_name}Filter, $after: String) {{ {collection}(filter: $filter, \
+             first: {PAGE_SIZE}, after: $after) {{ nodes {{ {fields} }} \
+             pageInfo {{ hasNextPage endCursor }} }} }}"
+        );
+
+        let mut filter = match kind {
+            Kind::Initiative => json!({}),
+            Kind::Project => json!({ "accessibleTeams": { "some": { "id": { "eq": team.id } } } }),
+            Kind::Task => json!({ "team": { "id": { "eq": team.id } } }),
+        };
+        if let Some(since) = since {
+            filter["updatedAt"] = json!({ "gt": since });
+        }
+
+        let mut items = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let data = self
+                .request(&query, json!({ "filter": filter, "after": after }))
+                .await?;
+            let page = &data[collection];
+            for node in page["nodes"].as_array().into_iter().flatten() {
+                items.push(parse_item(node)?);
+            }
+            match page["pageInfo"]["endCursor"].as_str() {
+                Some(cursor) if page["pageInfo"]["hasNextPage"].as_bool() == Some(true) => {
+                    after = Some(cursor.to_string());
+                }
+                _ => break,
+            }
+        }
+        items.sort_by(|a, b| a.updated_at.cmp(&b.updated_at));
+        Ok(items)
+    }
+
+    async fn save(
+        &self,
+        kind: Kind,
+        team: &LinearTeam,
+        remote_id: Option<&str>,
+        fields: &RemoteFields,
+    ) -> Result<RemoteItem> {
+        let (type_name, _, selected) = graphql_names(kind);
+        let node = match kind {
+            Kind::Initiative => "initiative",
+            Kind::Project => "project",
+            Kind::Task => "issue",
+        };
+
+        let mut input = json!({ "description": fields.description });
+        input[if kind == Kind::Task { "title" } else { "name" }] = json!(fields.title);
+        if kind == Kind::Task {
+            input["priority"] = json!(fields.priority);
+            input["projectId"] = json!(fields.project_id);
+            if let Some(state_id) = fields.state.as_deref().and_then(|s| team.state_id(s)) {
+                input["stateId"] = json!(state_id);
+            }
+        }
+
+        let (query, variables) = match remote_id {
+            Some(id) => (
+                format!(
+                    "mutation($id: String!, $input: {type_name}UpdateInput!) {{ \
+                     {node}Update(id: $id, input: $input) {{ {node} {{ {selected} }} }} }}"
+                ),
+                json!({ "id": id, "input": input }),
+            ),
+            None => {
+                match kind {
+                    Kind::Task => input["teamId"] = json!(team.id),
+                    Kind::Project => input["teamIds"] = json!([team.id]),
+                    Kind::Initiative => {}
+                }
+                (
+                    format!(
+                        "mutation($input: {type_name}CreateInput!) {{ \
+                         {node}Create(input: $input) {{ {node} {{ {selected} }} }} }}"
+                    ),
+                    json!({ "input": input }),
+                )
+            }
+        };
+
+        let data = self.request(&query, variables).await?;
+        let action = if remote_id.is_some() {
+            "Update"
+        } else {
+            "Create"
+        };
+        parse_item(&data[format!("{}{}", node, action)][node])
+    }
+}
+
+fn string_field(node: &Value, name: &str) -> Result<String> {
+    node[name]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| GranaryError::Other(format!("Linear response is missing {}", name)))
+}
+
+fn parse_item(node: &Value) -> Result<RemoteItem> {
+    let title = node["title"].as_str().or_else(|| node["name"].as_str());
+    Ok(RemoteItem {
+        id: string_field(node, "id")?,
+        key: node["identifier"].as_str().map(str::to_string),
+        updated_at: string_field(node, "updatedAt")?,
+        fields: RemoteFields {
+            title: title.unwrap_or_default().to_string(),
+            description: node["description"]
+                .as_str()
+                .filter(|d| !d.is_empty())
+                .map(str::to_string),
+            state: node["state"]["type"].as_str().map(str::to_string),
+            priority: node["priority"].as_f64().map(|p| p as i64),
+            project_id: node["project"]["id"].as_str().map(str::to_string),
+        },
+    })
+}
+
+/// Linear workflow state type for a task status
+fn state_for_status(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Draft => "backlog",
+        TaskStatus::Todo => "unstarted",
+        TaskStatus::InProgress | TaskStatus::Blocked => "started",
+        TaskStatus::Done => "completed",
+    }
+}
+
+/// Task status for a Linear workflow state type
+fn status_for_state(state: &str) -> Option<TaskStatus> {
+    match state {
+        "backlog" => Some(TaskStatus::Draft),
+        "triage" | "unstarted" => Some(TaskStatus::Todo),
+        "started" => Some(TaskStatus::InProgress),
+        "completed" | "canceled" => Some(TaskStatus::Done),
+        _ => None,
+    }
+}
+
+fn linear_priority(priority: TaskPriority) -> i64 {
+    match priority {
+        TaskPriority::P0 => 1,
+        TaskPriority::P1 => 2,
+        TaskPriority::P2 => 3,
+        TaskPriority::P3 => 4,
+        TaskPriority::P4 => 0,
+    }
+}
+
+fn task_priority(priority: i64) -> TaskPriority {
+    match priority {
+        1 => TaskPriority::P0,
+        2 => TaskPriority::P1,
+        3 => TaskPriority::P2,
+        4 => TaskPriority::P3,
+        _ => TaskPriority::P4,
+    }
+}
+
+/// A local entity as it would be written to Linear
+struct LocalItem {
+    id: String,
+    updated_at: String,
+    fields: RemoteFields,
+}
+
+/// Local entities of a kind that take part in a sync, skipping archived ones
+async fn local_items(pool: &SqlitePool, kind: Kind) -> Result<Vec<LocalItem>> {
+    let items = match kind {
+        Kind::Initiative => initiative_service::list_initiatives(pool, false)
+            .await?
+            .into_iter()
+            .map(|i| LocalItem {
+                id: i.id,
+                updated_at: i.updated_at,
+                fields: RemoteFields {
+                    title: i.name,
+                    description: i.description,
+                    ..Default::default()
+                },
+            })
+            .collect(),
+        Kind::Project => project_service::list_projects(pool, false)
+            .await?
+            .into_iter()
+            .map(|p| LocalItem {
+                id: p.id,
+                updated_at: p.updated_at,
+                fields: RemoteFields {
+                    title: p.name,
+                    description: p.description,
+                    ..Default::default()
+                },
+            })
+            .collect(),
+        Kind::Task => {
+            let mut items = Vec::new();
+            for task in task_service::list_all_tasks(pool, false).await? {
+                items.push(local_task(pool, task).await?);
+            }
+            items
+        }
+    };
+    Ok(items)
+}
+
+async fn local_task(pool: &SqlitePool, task: Task) -> Result<LocalItem> {
+    let project = db::sync::get_link(pool, PROVIDER, Kind::Project.as_str(), &task.project_id)
+        .await?
+        .map(|link| link.remote_id);
+    Ok(LocalItem {
+        fields: RemoteFields {
+            state: Some(state_for_status(task.status_enum()).to_string()),
+            priority: Some(linear_priority(task.priority_enum())),
+            project_id: project,
+            title: task.title,
+            description: task.description,
+        },
+        id: task.id,
+        updated_at: task.updated_at,
+    })
+}
+
+/// The local entity with this ID, unless it was deleted or archived
+async fn local_item(pool: &SqlitePool, kind: Kind, id: &str) -> Result<Option<LocalItem>> {
+    let items = match kind {
+        Kind::Task => match task_service::get_task(pool, id).await {
+            Ok(task) if task.archived_at.is_none() => vec![local_task(pool, task).await?],
+            _ => Vec::new(),
+        },
+        _ => local_items(pool, kind).await?,
+    };
+    Ok(items.into_iter().find(|item| item.id == id))
+}
+
+/// Create or update a local entity from a Linear item. Returns the local ID
+/// and `updated_at`, or `None` for an issue whose project is not synced.
+async fn apply_remote(
+    pool: &SqlitePool,
+    kind: Kind,
+    local_id: Option<&str>,
+    item: &RemoteItem,
+) -> Result<Option<(String, String)>> {
+    let fields = &item.fields;
+    let applied = match (kind, local_id) {
+        (Kind::Initiative, None) => {
+            let initiative = initiative_service::create_initiative(
+                pool,
+                CreateInitiative {
+                    name: fields.title.clone(),
+                    description: fields.description.clone(),
+                    owner: None,
+                    tags: Vec::new(),
+                },
+            )
+            .await?;
+            (initiative.id, initiative.updated_at)
+        }
+        (Kind::Initiative, Some(id)) => {
+            let initiative = initiative_service::update_initiative(
+                pool,
+                id,
+                UpdateInitiative {
+                    name: Some(fields.title.clone()),
+                    description: fields.description.clone(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            (initiative.id, initiative.updated_at)
+        }
+        (Kind::Project, None) => {
+            let project = project_service::create_project(
+                pool,
+                CreateProject {
+                    name: fields.title.clone(),
+                    description: fields.description.clone(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            (project.id, project.updated_at)
+        }
+        (Kind::Project, Some(id)) => {
+            let project = project_service::update_project(
+                pool,
+                id,
+                UpdateProject {
+                    name: Some(fields.title.clone()),
+                    description: fields.description.clone(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            (project.id, project.updated_at)
+        }
+        (Kind::Task, local_id) => {
+            let task_id = match local_id {
+                Some(id) => id.to_string(),
+                None => {
+                    let Some(project_id) = local_project_id(pool, fields).await? else {
+                        return Ok(None);
+                    };
+                    task_service::create_task(
+                        pool,
+                        CreateTask {
+                            project_id,
+                            title: fields.title.clone(),
+                            description: fields.description.clone(),
+                            priority: task_priority(fields.priority.unwrap_or_default()),
+                            ..Default::default()
+                        },
+                    )
+                    .await?
+                    .id
+                }
+            };
+            let task = task_service::get_task(pool, &task_id).await?;
+            // Linear has no blocked state, so a started issue leaves a
+            // blocked task blocked
+            let status = fields
+                .state
+                .as_deref()
+                .and_then(status_for_state)
+                .filter(|status| {
+                    let current = task.status_enum();
+                    *status != current
+                        && !(current == TaskStatus::Blocked && *status == TaskStatus::InProgress)
+                });
+            let task = task_service::update_task(
+                pool,
+                &task_id,
+                UpdateTask {
+                    title: Some(fields.title.clone()),
+                    description: fields.description.clone(),
+                    status,
+                    priority: fields.priority.map(task_priority),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+            (task.id, task.updated_at)
+        }
+    };
+    Ok(Some(applied))
+}
+
+/// The local project linked to an issue's Linear project
+async fn local_project_id(pool: &SqlitePool, fields: &RemoteFields) -> Result<Option<String>> {
+    let Some(remote_project) = fields.project_id.as_deref() else {
+        return Ok(None);
+    };
+    Ok(
+        db::sync::get_link_by_remote(pool, PROVIDER, Kind::Project.as_str(), remote_project)
+            .await?
+            .map(|link| link.local_id),
+    )
+}
+
+async fn save_link(
+    pool: &SqlitePool,
+    kind: Kind,
+    local_id: &str,
+    local_updated_at: &str,
+    item: &RemoteItem,
+) -> Result<()> {
+    db::sync::upsert_link(
+        pool,
+        &SyncLink {
+            provider: PROVIDER.to_string(),
+            entity_type: kind.as_str().to_string(),
+            local_id: local_id.to_string(),
+            remote_id: item.id.clone(),
+            remote_key: item.key.clone(),
+            local_updated_at: local_updated_at.to_string(),
+            remote_updated_at: item.updated_at.clone(),
+            synced_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+    .await
+}
+
+/// Sync with Linear through `api`
+pub(crate) async fn sync_with(
+    pool: &SqlitePool,
+    api: &impl LinearApi,
+    config: &LinearConfig,
+    options: &SyncOptions,
+) -> Result<SyncReport> {
+    let strategy = options.conflict.unwrap_or(config.conflict);
+    let team = api.team(&config.team).await?;
+    let mut report = SyncReport {
+        provider: PROVIDER.to_string(),
+        dry_run: options.dry_run,
+        conflict_strategy: strategy,
+        changes: Vec::new(),
+    };
+    // During a dry run nothing is linked, so remember which entities would
+    // have been created to sync the issues and tasks that belong to them
+    let mut planned: HashSet<String> = HashSet::new();
+
+    for kind in Kind::ALL {
+        let cursor = if options.full {
+            None
+        } else {
+            db::sync::get_cursor(pool, PROVIDER, kind.as_str()).await?
+        };
+        let remote = api.changed(kind, &team, cursor.as_deref()).await?;
+        let mut next_cursor = cursor.clone();
+        let mut handled: HashSet<String> = HashSet::new();
+
+        // Pull what changed in Linear
+        for item in &remote {
+            if next_cursor.as_deref() < Some(item.updated_at.as_str()) {
+                next_cursor = Some(item.updated_at.clone());
+            }
+            let link =
+                db::sync::get_link_by_remote(pool, PROVIDER, kind.as_str(), &item.id).await?;
+
+            let Some(link) = link else {
+                let project_known = match &item.fields.project_id {
+                    Some(project) => {
+                        planned.contains(project)
+                            || db::sync::get_link_by_remote(
+                                pool,
+                                PROVIDER,
+                                Kind::Project.as_str(),
+                                project,
+                            )
+                            .await?
+                            .is_some()
+                    }
+                    None => false,
+                };
+                if kind == Kind::Task && !project_known {
+                    continue;
+                }
+                let local_id = if options.dry_run {
+                    planned.insert(item.id.clone());
+                    None
+                } else {
+                    let Some((id, updated_at)) = apply_remote(pool, kind, None, item).await? else {
+                        continue;
+                    };
+                    save_link(pool, kind, &id, &updated_at, item).await?;
+                    handled.insert(id.clone());
+                    Some(id)
+                };
+                report.changes.push(SyncChange {
+                    entity_type: kind.as_str().to_string(),
+                    local_id,
+                    remote_key: Some(item.display_key()),
+                    title: item.fields.title.clone(),
+                    direction: SyncDirection::Pull,
+                    created: true,
+                    conflict: false,
+                });
+                continue;
+            };
+
+            // Our own earlier push
+            if item.updated_at == link.remote_updated_at {
+                continue;
+            }
+            let Some(local) = local_item(pool, kind, &link.local_id).await? else {
+                continue;
+            };
+            handled.insert(local.id.clone());
+
+            let conflict = local.updated_at != link.local_updated_at;
+            let direction = if conflict {
+                strategy.resolve(&local.updated_at, &item.updated_at)
+            } else {
+                SyncDirection::Pull
+            };
+            if !options.dry_run {
+                match direction {
+                    SyncDirection::Pull => {
+                        if let Some((id, updated_at)) =
+                            apply_remote(pool, kind, Some(&local.id), item).await?
+                        {
+                            save_link(pool, kind, &id, &updated_at, item).await?;
+                        }
+                    }
+                    SyncDirection::Push => {
+                        let saved = api
+                            .save(kind, &team, Some(&link.remote_id), &local.fields)
+                            .await?;
+                        save_link(pool, kind, &local.id, &local.updated_at, &saved).await?;
+                    }
+                }
+            }
+            report.changes.push(SyncChange {
+                entity_type: kind.as_str().to_string(),
+                local_id: Some(local.id),
+                remote_key: Some(item.display_key()),
+                title: match direction {
+                    SyncDirection::Pull => item.fields.title.clone(),
+                    SyncDirection::Push => local.fields.title,
+                },
+                direction,
+                created: false,
+                conflict,
+            });
+        }
+
+        // Push what changed locally
+        for local in local_items(pool, kind).await? {
+            if handled.contains(&local.id) {
+                continue;
+            }
+            let link = db::sync::get_link(pool, PROVIDER, kind.as_str(), &local.id).await?;
+            if let Some(link) = &link
+                && link.local_updated_at == local.updated_at
+            {
+                continue;
+            }
+
+            if kind == Kind::Task && local.fields.project_id.is_none() {
+                let task = task_service::get_task(pool, &local.id).await?;
+                if !planned.contains(&task.project_id) {
+                    continue;
+                }
+            }
+            if options.dry_run {
+                if link.is_none() {
+                    planned.insert(local.id.clone());
+                }
+                report.changes.push(SyncChange {
+                    entity_type: kind.as_str().to_string(),
+                    local_id: Some(local.id),
+                    remote_key: link
+                        .as_ref()
+                        .map(|l| l.remote_key.clone().unwrap_or_else(|| l.remote_id.clone())),
+                    title: local.fields.title,
+                    direction: SyncDirection::Push,
+                    created: link.is_none(),
+                    conflict: false,
+                });
+                continue;
+            }
+
+            let remote_id = link.as_ref().map(|l| l.remote_id.as_str());
+            let saved = api.save(kind, &team, remote_id, &local.fields).await?;
+            save_link(pool, kind, &local.id, &local.updated_at, &saved).await?;
+            report.changes.push(SyncChange {
+                entity_type: kind.as_str().to_string(),
+                local_id: Some(local.id),
+                remote_key: Some(saved.display_key()),
+                title: local.fields.title,
+                direction: SyncDirection::Push,
+                created: link.is_none(),
+                conflict: false,
+            });
+        }
+
+        if !options.dry_run
+            && let Some(next_cursor) = next_cursor
+            && Some(&next_cursor) != cursor.as_ref()
+        {
+            db::sync::set_cursor(pool, PROVIDER, kind.as_str(), &next_cursor).await?;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// An in-memory Linear workspace with a clock that ticks on every save
+    #[derive(Default)]
+    struct FakeLinear {
+        items: Mutex<Vec<(Kind, RemoteItem)>>,
+        clock: Mutex<u32>,
+    }
+
+    impl FakeLinear {
+        fn tick(&self) -> String {
+            let mut clock = self.clock.lock().unwrap();
+            *clock += 1;
+            format!("2030-01-01T00:00:{:02}.000Z", *clock)
+        }
+
+        fn find(&self, kind: Kind, title: &str) -> Option<RemoteItem> {
+            self.items
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|(k, item)| *k == kind && item.fields.title == title)
+                .map(|(_, item)| item.clone())
+        }
+
+        fn edit(&self, id: &str, title: &str) {
+            let updated_at = self.tick();
+            let mut items = self.items.lock().unwrap();
+            let (_, item) = items.iter_mut().find(|(_, item)| item.id == id).unwrap();
+            item.fields.title = title.to_string();
+            item.updated_at = updated_at;
+        }
+    }
+
+    impl LinearApi for FakeLinear {
+        async fn team(&self, key: &str) -> Result<LinearTeam> {
+            Ok(LinearTeam {
+                id: format!("team-{}", key),
+                states: Vec::new(),
+            })
+        }
+
+        async fn changed(
+            &self,
+            kind: Kind,
+            _team: &LinearTeam,
+            since: Option<&str>,
+        ) -> Result<Vec<RemoteItem>> {
+            Ok(self
+                .items
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(k, item)| *k == kind && since < Some(item.updated_at.as_str()))
+                .map(|(_, item)| item.clone())
+                .collect())
+        }
+
+        async fn save(
+            &self,
+            kind: Kind,
+            _team: &LinearTeam,
+            remote_id: Option<&str>,
+            fields: &RemoteFields,
+        ) -> Result<RemoteItem> {
+            let updated_at = self.tick();
+            let mut items = self.items.lock().unwrap();
+            let id = remote_id
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("remote-{}", items.len() + 1));
+            let item = RemoteItem {
+                key: (kind == Kind::Task).then(|| format!("ENG-{}", items.len() + 1)),
+                id: id.clone(),
+                updated_at,
+                fields: fields.clone(),
+            };
+            items.retain(|(_, existing)| existing.id != id);
+            items.push((kind, item.clone()));
+            Ok(item)
+        }
+    }
+
+    fn config(conflict: ConflictStrategy) -> LinearConfig {
+        LinearConfig {
+            team: "ENG".to_string(),
+            api_key: "key".to_string(),
+            api_url: DEFAULT_API_URL.to_string(),
+            conflict,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_pushes_pulls_and_resolves_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let linear = FakeLinear::default();
+        let options = SyncOptions::default();
+
+        let project = project_service::create_project(
+            &pool,
+            CreateProject {
+                name: "Billing".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = task_service::create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Send invoices".to_string(),
+                priority: TaskPriority::P0,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // A dry run reports the pushes without making them
+        let dry_run = SyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = sync_with(&pool, &linear, &config(ConflictStrategy::Newest), &dry_run)
+            .await
+            .unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert!(linear.items.lock().unwrap().is_empty());
+
+        // The first sync creates the project and the issue in it
+        let report = sync_with(&pool, &linear, &config(ConflictStrategy::Newest), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert!(report.changes.iter().all(|c| c.created));
+        let remote_project = linear.find(Kind::Project, "Billing").unwrap();
+        let issue = linear.find(Kind::Task, "Send invoices").unwrap();
+        assert_eq!(issue.fields.project_id, Some(remote_project.id.clone()));
+        assert_eq!(issue.fields.priority, Some(1));
+        assert_eq!(issue.fields.state.as_deref(), Some("backlog"));
+
+        // Nothing changed, so nothing syncs
+        let report = sync_with(&pool, &linear, &config(ConflictStrategy::Newest), &options)
+            .await
+            .unwrap();
+        assert!(report.changes.is_empty());
+
+        // A remote edit is pulled, and a new issue in the project is created
+        linear.edit(&issue.id, "Send invoices monthly");
+        linear
+            .save(
+                Kind::Task,
+                &LinearTeam::default(),
+                None,
+                &RemoteFields {
+                    title: "Chase payments".to_string(),
+                    state: Some("started".to_string()),
+                    project_id: Some(remote_project.id.clone()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let report = sync_with(&pool, &linear, &config(ConflictStrategy::Newest), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert!(
+            report
+                .changes
+                .iter()
+                .all(|c| c.direction == SyncDirection::Pull)
+        );
+        let pulled = task_service::get_task(&pool, &task.id).await.unwrap();
+        assert_eq!(pulled.title, "Send invoices monthly");
+        let tasks = task_service::list_tasks_by_project(&pool, &project.id, false)
+            .await
+            .unwrap();
+        let created = tasks.iter().find(|t| t.title == "Chase payments").unwrap();
+        assert_eq!(created.status, "in_progress");
+
+        // Both sides change: local wins pushes, remote wins pulls
+        linear.edit(&issue.id, "Remote title");
+        task_service::update_task(
+            &pool,
+            &task.id,
+            UpdateTask {
+                title: Some("Local title".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let report = sync_with(&pool, &linear, &config(ConflictStrategy::Remote), &options)
+            .await
+            .unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert!(report.changes[0].conflict);
+        assert_eq!(report.changes[0].direction, SyncDirection::Pull);
+        let pulled = task_service::get_task(&pool, &task.id).await.unwrap();
+        assert_eq!(pulled.title, "Remote title");
+
+        linear.edit(&issue.id, "Remote again");
+        task_service::update_task(
+            &pool,
+            &task.id,
+            UpdateTask {
+                title: Some("Local again".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let local_wins = SyncOptions {
+            conflict: Some(ConflictStrategy::Local),
+            ..Default::default()
+        };
+        let report = sync_with(
+            &pool,
+            &linear,
+            &config(ConflictStrategy::Remote),
+            &local_wins,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.changes[0].direction, SyncDirection::Push);
+        assert!(linear.find(Kind::Task, "Local again").is_some());
+    }
+
+    #[test]
+    fn test_validate_config_value() {
+        assert!(validate_config_value("sync.linear.team", "ENG").is_ok());
+        assert!(validate_config_value("sync.linear.team", " ").is_err());
+        assert!(validate_config_value("sync.linear.conflict", "remote").is_ok());
+        assert!(validate_config_value("sync.linear.conflict", "oldest").is_err());
+        assert!(validate_config_value("sync.linear.api_url", "ftp://x").is_err());
+        assert!(validate_config_value("sync.linear.colour", "red").is_err());
+        assert!(validate_config_value("other.key", "anything").is_ok());
+    }
+}
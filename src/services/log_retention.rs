@@ -0,0 +1,544 @@
+//! Retention policy for run and worker logs in `~/.granary/logs/`.
+//!
+//! Each worker's directory holds `worker.log`, one `<run-id>.log` (or
+//! `<run-id>.jsonl`) per run and the runs' artifacts. Pruning deletes, oldest first:
+//!
+//! - directories of workers that no longer exist
+//! - logs last written longer ago than `logs.max_age`
+//! - run logs beyond `logs.max_files_per_worker` for their worker
+//! - further logs while the directory is larger than `logs.max_total_size`
+//!
+//! A pruned run log takes the run's artifacts with it, and the run forgets
+//! its log path. Logs of runs that have not finished and of running or
+//! paused workers are never pruned.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::global_config::LogRetentionConfig;
+use crate::models::{LogPruneReport, PruneReason, PrunedLog};
+use crate::services::{datetime, global_config_service, run_artifacts};
+
+/// Name of a worker's own log file within its log directory
+const WORKER_LOG: &str = "worker";
+
+/// A [`LogRetentionConfig`] with its durations and sizes parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetentionLimits {
+    pub max_age: Duration,
+    pub max_total_size: u64,
+    pub max_files_per_worker: usize,
+}
+
+impl RetentionLimits {
+    pub fn from_config(config: &LogRetentionConfig) -> Result<Self> {
+        let max_age = datetime::parse_duration(&config.max_age)
+            .and_then(|d| d.to_std().ok())
+            .ok_or_else(|| {
+                GranaryError::GlobalConfig(format!(
+                    "Invalid logs.max_age '{}' (expected e.g. 12h, 7d)",
+                    config.max_age
+                ))
+            })?;
+        let max_total_size = parse_size(&config.max_total_size).ok_or_else(|| {
+            GranaryError::GlobalConfig(format!(
+                "Invalid logs.max_total_size '{}' (expected e.g. 500MB, 1GB)",
+                config.max_total_size
+            ))
+        })?;
+        Ok(Self {
+            max_age,
+            max_total_size,
+            max_files_per_worker: config.max_files_per_worker,
+        })
+    }
+}
+
+/// Parse a size such as `512KB`, `100MB`, `1.5GB` or `4096` (bytes)
+pub fn parse_size(input: &str) -> Option<u64> {
+    let trimmed = input.trim().to_uppercase();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (count, unit) = trimmed.split_at(split);
+    let count: f64 = count.parse().ok()?;
+    let multiplier: u64 = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" => 1 << 10,
+        "M" | "MB" => 1 << 20,
+        "G" | "GB" => 1 << 30,
+        _ => return None,
+    };
+    Some((count * multiplier as f64) as u64)
+}
+
+/// Prune `~/.granary/logs/` by the policy in the global config
+pub async fn prune(
+    pool: &SqlitePool,
+    config: &LogRetentionConfig,
+    dry_run: bool,
+) -> Result<LogPruneReport> {
+    let limits = RetentionLimits::from_config(config)?;
+    let logs_dir = global_config_service::logs_dir()?;
+    prune_in(pool, &logs_dir, &limits, dry_run, SystemTime::now()).await
+}
+
+/// A log that may be pruned
+struct Candidate {
+    path: PathBuf,
+    worker_id: String,
+    run_id: Option<String>,
+    artifacts_dir: Option<PathBuf>,
+    size: u64,
+    modified: SystemTime,
+    orphaned: bool,
+}
+
+/// Prune a logs directory as of `now`
+pub async fn prune_in(
+    pool: &SqlitePool,
+    logs_dir: &Path,
+    limits: &RetentionLimits,
+    dry_run: bool,
+    now: SystemTime,
+) -> Result<LogPruneReport> {
+    let workers: HashMap<String, _> = db::workers::list(pool)
+        .await?
+        .into_iter()
+        .map(|w| (w.id.clone(), w))
+        .collect();
+    let runs: HashMap<String, _> = db::runs::list_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| (r.id.clone(), r))
+        .collect();
+
+    let entries = match std::fs::read_dir(logs_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(LogPruneReport {
+                dry_run,
+                pruned: Vec::new(),
+                total_size: 0,
+                freed: 0,
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut candidates = Vec::new();
+    let mut run_logs: HashMap<String, usize> = HashMap::new();
+    for entry in entries.flatten() {
+        if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        let dir = entry.path();
+        let worker_id = entry.file_name().to_string_lossy().to_string();
+
+        let Some(worker) = workers.get(&worker_id) else {
+            let (size, modified) = dir_usage(&dir);
+            candidates.push(Candidate {
+                path: dir,
+                worker_id,
+                run_id: None,
+                artifacts_dir: None,
+                size,
+                modified: modified.unwrap_or(now),
+                orphaned: true,
+            });
+            continue;
+        };
+
+        for file in std::fs::read_dir(&dir)?.flatten() {
+            let path = file.path();
+            if path
+                .extension()
+                .is_none_or(|ext| ext != "log" && ext != "jsonl")
+            {
+                continue;
+            }
+            let Ok(metadata) = file.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let Some(stem) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let modified = metadata.modified().unwrap_or(now);
+
+            if stem == WORKER_LOG {
+                if !worker.is_running() && !worker.is_paused() {
+                    candidates.push(Candidate {
+                        path,
+                        worker_id: worker_id.clone(),
+                        run_id: None,
+                        artifacts_dir: None,
+                        size: metadata.len(),
+                        modified,
+                        orphaned: false,
+                    });
+                }
+                continue;
+            }
+
+            *run_logs.entry(worker_id.clone()).or_default() += 1;
+            if runs.get(&stem).is_some_and(|run| !run.is_finished()) {
+                continue;
+            }
+            let artifacts_dir = run_artifacts::artifacts_dir(&dir, &stem);
+            candidates.push(Candidate {
+                path,
+                worker_id: worker_id.clone(),
+                run_id: Some(stem),
+                size: metadata.len() + dir_usage(&artifacts_dir).0,
+                artifacts_dir: Some(artifacts_dir),
+                modified,
+                orphaned: false,
+            });
+        }
+    }
+
+    let total_size = dir_usage(logs_dir).0;
+    let cutoff = now
+        .checked_sub(limits.max_age)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    candidates.sort_by_key(|c| c.modified);
+
+    let mut size = total_size;
+    let mut selected = Vec::new();
+    let mut remaining = Vec::new();
+    for candidate in candidates {
+        let over_count = candidate.run_id.is_some()
+            && run_logs
+                .get(&candidate.worker_id)
+                .is_some_and(|&count| count > limits.max_files_per_worker);
+        let reason = if candidate.orphaned {
+            PruneReason::Orphaned
+        } else if candidate.modified < cutoff {
+            PruneReason::Age
+        } else if over_count {
+            PruneReason::Count
+        } else {
+            remaining.push(candidate);
+            continue;
+        };
+        if candidate.run_id.is_some()
+            && let Some(count) = run_logs.get_mut(&candidate.worker_id)
+        {
+            *count -= 1;
+        }
+        size = size.saturating_sub(candidate.size);
+        selected.push((candidate, reason));
+    }
+    for candidate in remaining {
+        if size <= limits.max_total_size {
+            break;
+        }
+        size = size.saturating_sub(candidate.size);
+        selected.push((candidate, PruneReason::Size));
+    }
+
+    let mut pruned = Vec::with_capacity(selected.len());
+    for (candidate, reason) in selected {
+        if !dry_run && let Err(e) = remove(pool, &candidate).await {
+            tracing::warn!("Failed to prune {}: {}", candidate.path.display(), e);
+            continue;
+        }
+        pruned.push(PrunedLog {
+            path: candidate.path.display().to_string(),
+            worker_id: candidate.worker_id,
+            run_id: candidate.run_id,
+            size: candidate.size,
+            reason,
+        });
+    }
+
+    Ok(LogPruneReport {
+        dry_run,
+        freed: pruned.iter().map(|p| p.size).sum(),
+        pruned,
+        total_size,
+    })
+}
+
+/// Delete a log, and for a run log its artifacts and database references
+async fn remove(pool: &SqlitePool, candidate: &Candidate) -> Result<()> {
+    if candidate.orphaned {
+        std::fs::remove_dir_all(&candidate.path)?;
+        return Ok(());
+    }
+    std::fs::remove_file(&candidate.path)?;
+    if let Some(dir) = &candidate.artifacts_dir {
+        match std::fs::remove_dir_all(dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    if let Some(run_id) = &candidate.run_id {
+        db::artifacts::delete_by_parent(pool, run_id).await?;
+        db::runs::clear_log_path(pool, run_id).await?;
+    }
+    Ok(())
+}
+
+/// Total size of the files under `dir` and when the newest was modified.
+/// Symlinks are not followed.
+fn dir_usage(dir: &Path) -> (u64, Option<SystemTime>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, None);
+    };
+    let mut size = 0;
+    let mut newest: Option<SystemTime> = None;
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let (entry_size, modified) = if file_type.is_dir() {
+            dir_usage(&entry.path())
+        } else if file_type.is_file() {
+            match entry.metadata() {
+                Ok(metadata) => (metadata.len(), metadata.modified().ok()),
+                Err(_) => continue,
+            }
+        } else {
+            continue;
+        };
+        size += entry_size;
+        newest = newest.max(modified);
+    }
+    (size, newest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::{create_pool, run_migrations};
+    use crate::models::run::{CreateRun, RunStatus, UpdateRunStatus};
+    use crate::models::worker::CreateWorker;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(86400);
+
+    fn limits(max_total_size: u64, max_files_per_worker: usize) -> RetentionLimits {
+        RetentionLimits {
+            max_age: 7 * DAY,
+            max_total_size,
+            max_files_per_worker,
+        }
+    }
+
+    fn write_log(path: &Path, contents: &str, modified: SystemTime) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    async fn setup() -> (SqlitePool, TempDir, String) {
+        let temp = TempDir::new().unwrap();
+        let pool = create_pool(&temp.path().join("workers.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+        let worker = db::workers::create(
+            &pool,
+            &CreateWorker {
+                command: "agent".to_string(),
+                event_type: "task.unblocked".to_string(),
+                instance_path: temp.path().display().to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        (pool, temp, worker.id)
+    }
+
+    async fn create_run(
+        pool: &SqlitePool,
+        worker_id: &str,
+        dir: &Path,
+        status: RunStatus,
+    ) -> String {
+        let run = db::runs::create(
+            pool,
+            &CreateRun {
+                worker_id: worker_id.to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let log_path = dir.join(format!("{}.log", run.id));
+        sqlx::query("UPDATE runs SET log_path = ? WHERE id = ?")
+            .bind(log_path.display().to_string())
+            .bind(&run.id)
+            .execute(pool)
+            .await
+            .unwrap();
+        db::runs::update_status(
+            pool,
+            &run.id,
+            &UpdateRunStatus {
+                status,
+                exit_code: None,
+                error_message: None,
+                pid: None,
+            },
+        )
+        .await
+        .unwrap();
+        run.id
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("512KB"), Some(512 * 1024));
+        assert_eq!(parse_size("100 mb"), Some(100 * 1024 * 1024));
+        assert_eq!(parse_size("1.5G"), Some(3 * (1 << 29)));
+        assert_eq!(parse_size("lots"), None);
+        assert_eq!(parse_size("10 parsecs"), None);
+
+        let config = LogRetentionConfig {
+            max_age: "forever".to_string(),
+            ..Default::default()
+        };
+        assert!(RetentionLimits::from_config(&config).is_err());
+        assert_eq!(
+            RetentionLimits::from_config(&LogRetentionConfig::default()).unwrap(),
+            limits(100 << 20, 100)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_age_keeps_active_runs() {
+        let (pool, temp, worker_id) = setup().await;
+        let logs_dir = temp.path().join("logs");
+        let dir = logs_dir.join(&worker_id);
+        let now = SystemTime::now();
+        let old = now - 10 * DAY;
+
+        let finished = create_run(&pool, &worker_id, &dir, RunStatus::Completed).await;
+        let running = create_run(&pool, &worker_id, &dir, RunStatus::Running).await;
+        write_log(&dir.join(format!("{}.log", finished)), "done", old);
+        write_log(&dir.join(format!("{}.log", running)), "working", old);
+        let artifacts = run_artifacts::artifacts_dir(&dir, &finished);
+        std::fs::create_dir_all(&artifacts).unwrap();
+        std::fs::write(artifacts.join("fix.patch"), "diff").unwrap();
+        run_artifacts::record(&pool, &finished, &artifacts)
+            .await
+            .unwrap();
+        // A config file is not a log
+        write_log(&dir.join("notes.txt"), "keep", old);
+
+        let report = prune_in(&pool, &logs_dir, &limits(u64::MAX, 100), true, now)
+            .await
+            .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.pruned.len(), 1);
+        assert_eq!(report.freed, 8);
+        assert!(dir.join(format!("{}.log", finished)).exists());
+
+        let report = prune_in(&pool, &logs_dir, &limits(u64::MAX, 100), false, now)
+            .await
+            .unwrap();
+        assert_eq!(report.pruned.len(), 1);
+        assert_eq!(report.pruned[0].run_id.as_deref(), Some(finished.as_str()));
+        assert_eq!(report.pruned[0].reason, PruneReason::Age);
+        assert!(!dir.join(format!("{}.log", finished)).exists());
+        assert!(!artifacts.exists());
+        assert!(dir.join(format!("{}.log", running)).exists());
+        assert!(dir.join("notes.txt").exists());
+
+        let run = db::runs::get(&pool, &finished).await.unwrap().unwrap();
+        assert!(run.log_path.is_none());
+        assert!(
+            run_artifacts::list(&pool, &finished)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_count_and_size() {
+        let (pool, temp, worker_id) = setup().await;
+        let logs_dir = temp.path().join("logs");
+        let dir = logs_dir.join(&worker_id);
+        let now = SystemTime::now();
+
+        let mut run_ids = Vec::new();
+        for i in 0..5u64 {
+            let run_id = create_run(&pool, &worker_id, &dir, RunStatus::Failed).await;
+            write_log(
+                &dir.join(format!("{}.log", run_id)),
+                "0123456789",
+                now - Duration::from_secs(60 * (5 - i)),
+            );
+            run_ids.push(run_id);
+        }
+        write_log(&dir.join("worker.log"), "0123456789", now);
+
+        // Keep 3 run logs, then get under 25 bytes: 60 bytes in total
+        let report = prune_in(&pool, &logs_dir, &limits(25, 3), false, now)
+            .await
+            .unwrap();
+        assert_eq!(report.total_size, 60);
+        let pruned: Vec<_> = report
+            .pruned
+            .iter()
+            .map(|p| (p.run_id.clone(), p.reason))
+            .collect();
+        assert_eq!(
+            pruned,
+            [
+                (Some(run_ids[0].clone()), PruneReason::Count),
+                (Some(run_ids[1].clone()), PruneReason::Count),
+                (Some(run_ids[2].clone()), PruneReason::Size),
+                (Some(run_ids[3].clone()), PruneReason::Size),
+            ]
+        );
+        assert!(dir.join(format!("{}.log", run_ids[4])).exists());
+        assert!(dir.join("worker.log").exists());
+    }
+
+    #[tokio::test]
+    async fn test_prune_orphaned_worker_directory() {
+        let (pool, temp, _) = setup().await;
+        let logs_dir = temp.path().join("logs");
+        let now = SystemTime::now();
+        write_log(
+            &logs_dir.join("worker-gone").join("run-abc.log"),
+            "old",
+            now,
+        );
+
+        let report = prune_in(&pool, &logs_dir, &limits(u64::MAX, 100), false, now)
+            .await
+            .unwrap();
+        assert_eq!(report.pruned.len(), 1);
+        assert_eq!(report.pruned[0].reason, PruneReason::Orphaned);
+        assert!(!logs_dir.join("worker-gone").exists());
+
+        let report = prune_in(
+            &pool,
+            &temp.path().join("missing"),
+            &limits(0, 0),
+            false,
+            now,
+        )
+        .await
+        .unwrap();
+        assert!(report.pruned.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+//! Clean-up of runner output for `granary runs logs`.
+//!
+//! Runners often write for a terminal: colors and cursor movement as ANSI
+//! escape sequences, and progress bars that redraw their line with `\r`.
+//! Neither reads well once the output is saved to a file, so log lines are
+//! post-processed before they are shown.
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{07}';
+/// Single-character form of `ESC [`
+const CSI: char = '\u{9b}';
+
+/// Clean up one log line: collapse `\r`-rewritten text, and remove ANSI
+/// escape sequences when `strip_ansi` is set.
+pub fn sanitize(line: &str, strip_ansi: bool) -> String {
+    let line = collapse_carriage_returns(line);
+    if strip_ansi {
+        self::strip_ansi(line)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Keep what a terminal shows last on a line that was redrawn with `\r`:
+/// the text after the last `\r` that is followed by any.
+pub fn collapse_carriage_returns(line: &str) -> &str {
+    line.split('\r')
+        .rfind(|segment| !segment.is_empty())
+        .unwrap_or("")
+}
+
+/// Remove ANSI escape sequences: CSI sequences such as colors and cursor
+/// movement, OSC sequences such as window titles and hyperlinks, and
+/// two-character escapes. The text of a hyperlink is kept.
+pub fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => match chars.next() {
+                Some('[') => skip_csi(&mut chars),
+                Some(']') => skip_osc(&mut chars),
+                // Two-character escape, such as `ESC 7` (save cursor)
+                Some(_) | None => {}
+            },
+            CSI => skip_csi(&mut chars),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Skip the parameters and final byte of a CSI sequence
+fn skip_csi(chars: &mut impl Iterator<Item = char>) {
+    for c in chars {
+        if ('\u{40}'..='\u{7e}').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// Skip an OSC sequence up to its BEL or `ESC \` terminator
+fn skip_osc(chars: &mut std::iter::Peekable<impl Iterator<Item = char>>) {
+    while let Some(c) = chars.next() {
+        match c {
+            BEL => break,
+            ESC => {
+                if chars.peek() == Some(&'\\') {
+                    chars.next();
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\x1b[1;31merror\x1b[0m: failed"),
+            "error: failed"
+        );
+        assert_eq!(strip_ansi("\x1b[2K\x1b[1Gdone"), "done");
+        assert_eq!(strip_ansi("\x1b]0;title\x07text"), "text");
+        assert_eq!(
+            strip_ansi("see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\"),
+            "see docs"
+        );
+        assert_eq!(strip_ansi("\u{9b}32mok"), "ok");
+        assert_eq!(strip_ansi("plain text"), "plain text");
+        // A truncated sequence at the end of the line is dropped
+        assert_eq!(strip_ansi("cut\x1b[3"), "cut");
+    }
+
+    #[test]
+    fn test_collapse_carriage_returns() {
+        assert_eq!(
+            collapse_carriage_returns("  10%\r  55%\r 100% done"),
+            " 100% done"
+        );
+        assert_eq!(collapse_carriage_returns("downloading\r"), "downloading");
+        assert_eq!(collapse_carriage_returns("no rewrites"), "no rewrites");
+        assert_eq!(collapse_carriage_returns("\r"), "");
+        assert_eq!(
+            sanitize("\x1b[32m10%\x1b[0m\r\x1b[32m100%\x1b[0m", true),
+            "100%"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Importing Markdown checklists with `granary import markdown`.
+//!
+//! Each `- [ ]` or `- [x]` item becomes a task, done when checked. Items
+//! indented under another become its subtasks, and the heading an item sits
+//! under becomes a label on it, so the sections of a TODO.md survive as
+//! something tasks can be filtered by.
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services;
+
+/// Width a tab counts for when comparing indentation
+const TAB_WIDTH: usize = 4;
+
+/// A checklist item and the items nested under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecklistItem {
+    pub title: String,
+    pub done: bool,
+    /// Text of the heading the item is under
+    pub section: Option<String>,
+    pub children: Vec<ChecklistItem>,
+}
+
+/// Parse the checklist items of a Markdown document into a tree. Lines that
+/// are neither headings nor checklist items are skipped.
+pub fn parse_checklist(input: &str) -> Vec<ChecklistItem> {
+    let mut roots: Vec<ChecklistItem> = Vec::new();
+    // Indentation of each item new items may nest under, outermost first;
+    // the innermost is the last item of its siblings at each level
+    let mut open: Vec<usize> = Vec::new();
+    let mut section: Option<String> = None;
+    let mut in_code_block = false;
+
+    for line in input.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if let Some(heading) = parse_heading(line) {
+            section = Some(heading);
+            open.clear();
+            continue;
+        }
+        let Some((indent, done, title)) = parse_item(line) else {
+            continue;
+        };
+
+        while open.last().is_some_and(|&outer| outer >= indent) {
+            open.pop();
+        }
+        let item = ChecklistItem {
+            title,
+            done,
+            section: section.clone(),
+            children: Vec::new(),
+        };
+        let mut siblings = &mut roots;
+        for _ in 0..open.len() {
+            siblings = &mut siblings.last_mut().expect("open item").children;
+        }
+        siblings.push(item);
+        open.push(indent);
+    }
+    roots
+}
+
+/// The text of an ATX heading such as `## Backend`
+fn parse_heading(line: &str) -> Option<String> {
+    let hashes = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&hashes) {
+        return None;
+    }
+    let rest = &line[hashes..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// The indentation, checked state and text of a checklist item such as
+/// `  - [x] Write docs`
+fn parse_item(line: &str) -> Option<(usize, bool, String)> {
+    let trimmed = line.trim_start();
+    let indent: usize = line[..line.len() - trimmed.len()]
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum();
+
+    let rest = trimmed.strip_prefix(['-', '*', '+'])?.strip_prefix(' ')?;
+    let rest = rest.trim_start();
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let title = rest[3..].trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some((indent, done, title.to_string()))
+}
+
+/// Number of items in a checklist, nested ones included
+pub fn count_items(items: &[ChecklistItem]) -> usize {
+    items.iter().map(|i| 1 + count_items(&i.children)).sum()
+}
+
+/// Create a task for every checklist item in a project: subtasks for nested
+/// items, a label for each item's section, done for checked items and todo
+/// for the rest. Returns the tasks in document order.
+pub async fn import_checklist(
+    pool: &SqlitePool,
+    project_id: &str,
+    items: &[ChecklistItem],
+) -> Result<Vec<Task>> {
+    services::get_project(pool, project_id).await?;
+    if items.is_empty() {
+        return Err(GranaryError::InvalidArgument(
+            "No checklist items found. Expected lines like '- [ ] Task' or '- [x] Done task'"
+                .to_string(),
+        ));
+    }
+
+    let mut created = Vec::new();
+    // Items waiting to be created, with the task they are a subtask of
+    let mut pending: Vec<(&ChecklistItem, Option<String>)> =
+        items.iter().rev().map(|item| (item, None)).collect();
+    while let Some((item, parent)) = pending.pop() {
+        let task = services::create_task(
+            pool,
+            CreateTask {
+                project_id: project_id.to_string(),
+                parent_task_id: parent,
+                title: item.title.clone(),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        if let Some(section) = &item.section {
+            let label = normalize_slug(section);
+            if !label.is_empty() {
+                db::labels::apply(pool, &task.id, &[label], &[]).await?;
+            }
+        }
+        let task = if item.done {
+            services::complete_task(pool, &task.id, None, None).await?
+        } else {
+            services::update_task(
+                pool,
+                &task.id,
+                UpdateTask {
+                    status: Some(TaskStatus::Todo),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?
+        };
+
+        pending.extend(
+            item.children
+                .iter()
+                .rev()
+                .map(|child| (child, Some(task.id.clone()))),
+        );
+        created.push(task);
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Workspace;
+    use tempfile::TempDir;
+
+    const TODO: &str = "\
+# Launch
+
+- [x] Pick a name
+- [ ] Build the site
+  - [x] Landing page
+  - [ ] Pricing page
+    * [ ] Annual plans
+- Not a checklist item
+
+## Backend API
+1. numbered items are skipped
+- [X] Rate limiting
+\t- [ ] Per-key quotas
+
+```
+- [ ] Inside a code block
+```
+";
+
+    #[test]
+    fn test_parse_checklist() {
+        let items = parse_checklist(TODO);
+        assert_eq!(count_items(&items), 7);
+        assert_eq!(items.len(), 3);
+
+        assert!(items[0].done);
+        assert_eq!(items[0].section.as_deref(), Some("Launch"));
+
+        let site = &items[1];
+        assert_eq!(site.title, "Build the site");
+        assert!(!site.done);
+        assert_eq!(site.children.len(), 2);
+        assert!(site.children[0].done);
+        assert_eq!(site.children[1].children[0].title, "Annual plans");
+
+        let api = &items[2];
+        assert_eq!(api.section.as_deref(), Some("Backend API"));
+        assert!(api.done);
+        assert_eq!(api.children[0].title, "Per-key quotas");
+    }
+
+    #[test]
+    fn test_parse_heading_and_item() {
+        assert_eq!(parse_heading("### Done ###"), Some("Done".to_string()));
+        assert_eq!(parse_heading("#hashtag"), None);
+        assert_eq!(parse_heading("#######  Too deep"), None);
+        assert_eq!(
+            parse_item("    + [ ] Deep"),
+            Some((4, false, "Deep".to_string()))
+        );
+        assert_eq!(parse_item("- [ ]"), None);
+        assert_eq!(parse_item("-[ ] No space"), None);
+        assert_eq!(parse_item("- [~] Unknown"), None);
+    }
+
+    #[tokio::test]
+    async fn test_import_checklist() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Launch".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let tasks = import_checklist(&pool, &project.id, &parse_checklist(TODO))
+            .await
+            .unwrap();
+        let titles: Vec<&str> = tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Pick a name",
+                "Build the site",
+                "Landing page",
+                "Pricing page",
+                "Annual plans",
+                "Rate limiting",
+                "Per-key quotas"
+            ]
+        );
+        let statuses: Vec<&str> = tasks.iter().map(|t| t.status.as_str()).collect();
+        assert_eq!(
+            statuses,
+            vec!["done", "todo", "done", "todo", "todo", "done", "todo"]
+        );
+        assert_eq!(tasks[4].parent_task_id.as_ref(), Some(&tasks[3].id));
+        assert_eq!(tasks[2].parent_task_id.as_ref(), Some(&tasks[1].id));
+        assert_eq!(tasks[6].labels, vec!["backend-api"]);
+        assert_eq!(tasks[0].labels, vec!["launch"]);
+
+        let err = import_checklist(&pool, &project.id, &[]).await.unwrap_err();
+        assert!(matches!(err, GranaryError::InvalidArgument(_)));
+    }
+}
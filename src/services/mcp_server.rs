@@ -0,0 +1,988 @@
+//! Model Context Protocol (MCP) server.
+//!
+//! Exposes granary's task, project, and initiative operations as MCP tools so
+//! that AI agents can use granary natively instead of shelling out to the CLI.
+//!
+//! The server speaks JSON-RPC 2.0 over newline-delimited stdio, as described
+//! by the MCP stdio transport. Every tool call is backed by the regular
+//! `services` layer, so events, optimistic locking and validation behave
+//! exactly as they do for CLI commands.
+//!
+//! The `mcp` command isn't recorded as a whole: each tool call that changes
+//! the workspace is saved as an operation of its own, attributed to the
+//! actor the server was started as, so agents' changes show up in
+//! `granary audit` and can be undone.
+
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::tokens::{TokenBudget, TokenModel};
+use crate::services::{self, Workspace, datetime};
+
+/// MCP protocol version implemented by this server
+pub const MCP_PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error codes
+mod rpc_codes {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+}
+
+/// Tools that change the workspace, each call of which is recorded as an
+/// operation
+const MUTATING_TOOLS: &[&str] = &[
+    "granary_create_project",
+    "granary_update_project",
+    "granary_archive_project",
+    "granary_create_task",
+    "granary_update_task",
+    "granary_start_task",
+    "granary_complete_task",
+    "granary_block_task",
+    "granary_create_initiative",
+    "granary_update_initiative",
+    "granary_archive_initiative",
+];
+
+/// MCP server bound to a single workspace
+pub struct McpServer {
+    pool: SqlitePool,
+    workspace: Workspace,
+    /// Who the changes made through the server are attributed to
+    actor: Actor,
+}
+
+impl McpServer {
+    pub fn new(pool: SqlitePool, workspace: Workspace) -> Self {
+        Self {
+            pool,
+            workspace,
+            actor: Actor::default(),
+        }
+    }
+
+    /// Attribute the changes made through the server to `actor`
+    pub fn with_actor(mut self, actor: Actor) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// The session a tool call changes tasks as: its `session_id` argument,
+    /// else the session of the server's actor
+    fn session_id<'a>(&'a self, args: &'a Value) -> Option<&'a str> {
+        args.get("session_id")
+            .and_then(|v| v.as_str())
+            .or(self.actor.session_id.as_deref())
+    }
+
+    /// Serve requests from `reader` until EOF, writing responses to `writer`.
+    ///
+    /// Each message is a single line of JSON. Notifications (messages without
+    /// an `id`) never produce a response.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(response) = self.handle_line(line).await {
+                let mut out = serde_json::to_string(&response)?;
+                out.push('\n');
+                writer.write_all(out.as_bytes()).await?;
+                writer.flush().await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle a single raw JSON-RPC message, returning the response (if any)
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => {
+                return Some(rpc_error(
+                    Value::Null,
+                    rpc_codes::PARSE_ERROR,
+                    &format!("Parse error: {}", e),
+                ));
+            }
+        };
+
+        self.handle_message(message).await
+    }
+
+    /// Handle a parsed JSON-RPC message
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(|m| m.as_str()) else {
+            return id.map(|id| rpc_error(id, rpc_codes::INVALID_REQUEST, "Missing method"));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications have no id and never get a response
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(self.initialize()),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(&params).await,
+            _ => {
+                return Some(rpc_error(
+                    id,
+                    rpc_codes::METHOD_NOT_FOUND,
+                    &format!("Method not found: {}", method),
+                ));
+            }
+        };
+
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => rpc_error(id, rpc_codes::INVALID_PARAMS, &e.to_string()),
+        })
+    }
+
+    fn initialize(&self) -> Value {
+        json!({
+            "protocolVersion": MCP_PROTOCOL_VERSION,
+            "capabilities": { "tools": { "listChanged": false } },
+            "serverInfo": {
+                "name": "granary",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "instructions": "Granary is a task manager for agentic workflows. \
+                Use granary_summary to orient yourself, granary_next_task to pick up work, \
+                and the task tools to record progress.",
+        })
+    }
+
+    /// Dispatch a `tools/call` request.
+    ///
+    /// Tool failures are reported in-band (`isError: true`) as the MCP spec
+    /// requires, so the agent can see and react to them. Only malformed calls
+    /// are surfaced as JSON-RPC errors.
+    async fn call_tool(&self, params: &Value) -> Result<Value> {
+        let name = params
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or_else(|| GranaryError::InvalidArgument("Missing tool name".to_string()))?;
+        let args = params.get("arguments").cloned().unwrap_or(json!({}));
+        if !tool_definitions().iter().any(|tool| tool["name"] == name) {
+            return Err(GranaryError::InvalidArgument(format!(
+                "Unknown tool: {}",
+                name
+            )));
+        }
+
+        // Each change an agent makes is saved as an operation of its own,
+        // for `granary audit` and `granary undo`
+        let outcome = if MUTATING_TOOLS.contains(&name) {
+            let command = format!("mcp {}", name);
+            services::record_operation(&self.workspace, &command, self.actor.clone(), |pool| {
+                let args = &args;
+                async move { self.dispatch(&pool, name, args).await }
+            })
+            .await
+        } else {
+            self.dispatch(&self.pool, name, &args).await
+        };
+
+        Ok(match outcome {
+            Ok(value) => tool_result(&value, false),
+            Err(e) => tool_result(&Value::String(e.to_string()), true),
+        })
+    }
+
+    /// Run a tool against `pool`
+    async fn dispatch(&self, pool: &SqlitePool, name: &str, args: &Value) -> Result<Value> {
+        match name {
+            "granary_search" => self.search(pool, args).await,
+            "granary_summary" => self.summary(pool, args).await,
+            "granary_list_projects" => self.list_projects(pool, args).await,
+            "granary_get_project" => self.get_project(pool, args).await,
+            "granary_create_project" => self.create_project(pool, args).await,
+            "granary_update_project" => self.update_project(pool, args).await,
+            "granary_archive_project" => self.archive_project(pool, args).await,
+            "granary_list_tasks" => self.list_tasks(pool, args).await,
+            "granary_get_task" => self.get_task(pool, args).await,
+            "granary_create_task" => self.create_task(pool, args).await,
+            "granary_update_task" => self.update_task(pool, args).await,
+            "granary_start_task" => self.start_task(pool, args).await,
+            "granary_complete_task" => self.complete_task(pool, args).await,
+            "granary_block_task" => self.block_task(pool, args).await,
+            "granary_next_task" => self.next_task(pool, args).await,
+            "granary_list_initiatives" => self.list_initiatives(pool, args).await,
+            "granary_get_initiative" => self.get_initiative(pool, args).await,
+            "granary_create_initiative" => self.create_initiative(pool, args).await,
+            "granary_update_initiative" => self.update_initiative(pool, args).await,
+            "granary_archive_initiative" => self.archive_initiative(pool, args).await,
+            "granary_initiative_summary" => self.initiative_summary(pool, args).await,
+            _ => Err(GranaryError::InvalidArgument(format!(
+                "Unknown tool: {}",
+                name
+            ))),
+        }
+    }
+
+    // ========================================================================
+    // Search and summary
+    // ========================================================================
+
+    async fn search(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let query = required_str(args, "query")?;
+        let include_archived = optional_bool(args, "include_archived");
+        let results = services::search(pool, query, include_archived, Page::default()).await?;
+        Ok(serde_json::to_value(results)?)
+    }
+
+    async fn summary(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let token_budget = optional_u64(args, "token_budget").map(|b| b as usize);
+        let model = match optional_string(args, "model") {
+            Some(model) => model.parse().map_err(GranaryError::InvalidArgument)?,
+            None => TokenModel::default(),
+        };
+        let include_archived = optional_bool(args, "include_archived");
+        let budget = TokenBudget::new(token_budget, model);
+        let summary =
+            services::generate_summary(pool, &self.workspace, budget, include_archived).await?;
+        Ok(serde_json::to_value(summary)?)
+    }
+
+    // ========================================================================
+    // Projects
+    // ========================================================================
+
+    async fn list_projects(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let include_archived = optional_bool(args, "include_archived");
+        let projects = services::list_projects(pool, include_archived).await?;
+        Ok(serde_json::to_value(projects)?)
+    }
+
+    async fn get_project(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let project = services::get_project(pool, id).await?;
+        let tasks = services::list_tasks_by_project(pool, id, false).await?;
+        Ok(json!({ "project": project, "tasks": tasks }))
+    }
+
+    async fn create_project(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let input = CreateProject {
+            name: required_str(args, "name")?.to_string(),
+            description: optional_string(args, "description"),
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags").unwrap_or_default(),
+            ..Default::default()
+        };
+        let project = services::create_project(pool, input).await?;
+        Ok(serde_json::to_value(project)?)
+    }
+
+    async fn update_project(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let updates = UpdateProject {
+            name: optional_string(args, "name"),
+            description: optional_string(args, "description"),
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags"),
+            ..Default::default()
+        };
+        let project = services::update_project(pool, id, updates).await?;
+        Ok(serde_json::to_value(project)?)
+    }
+
+    async fn archive_project(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let cascade = optional_bool(args, "cascade");
+        let project = services::archive_project(pool, id, cascade).await?;
+        Ok(serde_json::to_value(project)?)
+    }
+
+    // ========================================================================
+    // Tasks
+    // ========================================================================
+
+    async fn list_tasks(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let include_archived = optional_bool(args, "include_archived");
+        let mut tasks = if let Some(project_id) = args.get("project_id").and_then(|v| v.as_str()) {
+            services::list_tasks_by_project(pool, project_id, include_archived).await?
+        } else {
+            services::list_tasks_filtered(
+                pool,
+                args.get("status").and_then(|v| v.as_str()),
+                args.get("priority").and_then(|v| v.as_str()),
+                args.get("owner").and_then(|v| v.as_str()),
+                include_archived,
+            )
+            .await?
+        };
+        services::attach_labels(pool, &mut tasks).await?;
+        let labels: Vec<String> = args
+            .get("label")
+            .and_then(|v| v.as_str())
+            .map(|label| vec![label.to_string()])
+            .unwrap_or_default();
+        let tasks = services::filter_by_labels(tasks, &labels);
+        Ok(serde_json::to_value(tasks)?)
+    }
+
+    async fn get_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let (task, blocked_by) = services::get_task_with_deps(pool, id).await?;
+        let subtasks = services::list_subtasks(pool, id).await?;
+        Ok(json!({ "task": task, "blocked_by": blocked_by, "subtasks": subtasks }))
+    }
+
+    async fn create_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let input = CreateTask {
+            project_id: required_str(args, "project_id")?.to_string(),
+            parent_task_id: optional_string(args, "parent_task_id"),
+            title: required_str(args, "title")?.to_string(),
+            description: optional_string(args, "description"),
+            priority: parse_priority(args)?.unwrap_or_default(),
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags").unwrap_or_default(),
+            due_at: parse_due_at(args)?,
+            estimate: optional_f64(args, "estimate"),
+        };
+        let task = services::create_task(pool, input).await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    async fn update_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let status = match args.get("status").and_then(|v| v.as_str()) {
+            Some(s) => Some(
+                s.parse::<TaskStatus>()
+                    .map_err(|_| GranaryError::InvalidArgument(format!("Invalid status: {}", s)))?,
+            ),
+            None => None,
+        };
+        let updates = UpdateTask {
+            title: optional_string(args, "title"),
+            description: optional_string(args, "description"),
+            status,
+            priority: parse_priority(args)?,
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags"),
+            due_at: parse_due_at(args)?,
+            estimate: optional_f64(args, "estimate"),
+            fields: services::parse_field_args(
+                &optional_string_list(args, "fields").unwrap_or_default(),
+            )?,
+            ..Default::default()
+        };
+        let task = services::update_task(pool, id, updates, self.session_id(args)).await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    async fn start_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let task = services::start_task(
+            pool,
+            id,
+            optional_string(args, "owner"),
+            self.session_id(args),
+        )
+        .await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    async fn complete_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let comment = optional_string(args, "comment");
+        let task =
+            services::complete_task(pool, id, comment.as_deref(), self.session_id(args)).await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    async fn block_task(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let reason = required_str(args, "reason")?;
+        let task = services::block_task(pool, id, reason, self.session_id(args)).await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    async fn next_task(&self, pool: &SqlitePool, _args: &Value) -> Result<Value> {
+        // Scope to the current session's projects, matching `granary next`
+        let project_ids = if let Some(session_id) = self.workspace.current_session_id() {
+            let ids =
+                services::get_scope_by_type(pool, &session_id, ScopeItemType::Project).await?;
+            if ids.is_empty() { None } else { Some(ids) }
+        } else {
+            None
+        };
+        let task = services::get_next_task(pool, project_ids.as_deref()).await?;
+        Ok(serde_json::to_value(task)?)
+    }
+
+    // ========================================================================
+    // Initiatives
+    // ========================================================================
+
+    async fn list_initiatives(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let include_archived = optional_bool(args, "include_archived");
+        let initiatives = services::list_initiatives(pool, include_archived).await?;
+        Ok(serde_json::to_value(initiatives)?)
+    }
+
+    async fn get_initiative(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let initiative = services::get_initiative_or_error(pool, id).await?;
+        let projects = services::get_initiative_projects(pool, id).await?;
+        Ok(json!({ "initiative": initiative, "projects": projects }))
+    }
+
+    async fn create_initiative(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let input = CreateInitiative {
+            name: required_str(args, "name")?.to_string(),
+            description: optional_string(args, "description"),
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags").unwrap_or_default(),
+        };
+        let initiative = services::create_initiative(pool, input).await?;
+        Ok(serde_json::to_value(initiative)?)
+    }
+
+    async fn update_initiative(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let updates = UpdateInitiative {
+            name: optional_string(args, "name"),
+            description: optional_string(args, "description"),
+            owner: optional_string(args, "owner"),
+            tags: optional_string_list(args, "tags"),
+            ..Default::default()
+        };
+        let initiative = services::update_initiative(pool, id, updates).await?;
+        Ok(serde_json::to_value(initiative)?)
+    }
+
+    async fn archive_initiative(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let initiative = services::archive_initiative(pool, id).await?;
+        Ok(serde_json::to_value(initiative)?)
+    }
+
+    async fn initiative_summary(&self, pool: &SqlitePool, args: &Value) -> Result<Value> {
+        let id = required_str(args, "id")?;
+        let max_next_actions = optional_u64(args, "max_next_actions").unwrap_or(5) as usize;
+        let summary = services::generate_initiative_summary(pool, id, max_next_actions).await?;
+        Ok(serde_json::to_value(summary)?)
+    }
+}
+
+/// Build a JSON-RPC error response
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Build an MCP tool result with a single text content block
+fn tool_result(value: &Value, is_error: bool) -> Value {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    })
+}
+
+fn required_str<'a>(args: &'a Value, key: &str) -> Result<&'a str> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| GranaryError::InvalidArgument(format!("Missing required argument: {}", key)))
+}
+
+fn optional_string(args: &Value, key: &str) -> Option<String> {
+    args.get(key).and_then(|v| v.as_str()).map(String::from)
+}
+
+fn optional_bool(args: &Value, key: &str) -> bool {
+    args.get(key).and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+fn optional_u64(args: &Value, key: &str) -> Option<u64> {
+    args.get(key).and_then(|v| v.as_u64())
+}
+
+fn optional_f64(args: &Value, key: &str) -> Option<f64> {
+    args.get(key).and_then(|v| v.as_f64())
+}
+
+fn optional_string_list(args: &Value, key: &str) -> Option<Vec<String>> {
+    args.get(key).and_then(|v| v.as_array()).map(|items| {
+        items
+            .iter()
+            .filter_map(|i| i.as_str().map(String::from))
+            .collect()
+    })
+}
+
+fn parse_due_at(args: &Value) -> Result<Option<String>> {
+    optional_string(args, "due_at")
+        .map(|d| datetime::parse_due(&d, &datetime::settings()))
+        .transpose()
+}
+
+fn parse_priority(args: &Value) -> Result<Option<TaskPriority>> {
+    match args.get("priority").and_then(|v| v.as_str()) {
+        Some(p) => p
+            .parse::<TaskPriority>()
+            .map(Some)
+            .map_err(|_| GranaryError::InvalidArgument(format!("Invalid priority: {}", p))),
+        None => Ok(None),
+    }
+}
+
+/// Build a tool definition with a JSON Schema for its input
+fn tool(name: &str, description: &str, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "inputSchema": {
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        },
+    })
+}
+
+/// All tools exposed by the server
+pub fn tool_definitions() -> Vec<Value> {
+    let string = |description: &str| json!({ "type": "string", "description": description });
+    let tags = json!({ "type": "array", "items": { "type": "string" }, "description": "Tags" });
+    let priority = json!({
+        "type": "string",
+        "enum": ["P0", "P1", "P2", "P3", "P4"],
+        "description": "Task priority",
+    });
+    let include_archived = json!({ "type": "boolean", "description": "Include archived items" });
+    let session_id = string(
+        "Session making the change, checked against task claims and the session's mode (default: the server's session)",
+    );
+
+    vec![
+        tool(
+            "granary_search",
+            "Search initiatives, projects, and tasks by title",
+            json!({
+                "query": string("Search query. Qualifiers like status:todo priority:p0 label:backend field.env:prod restrict results to matching tasks"),
+                "include_archived": include_archived,
+            }),
+            &["query"],
+        ),
+        tool(
+            "granary_summary",
+            "Summarize the current session or workspace: task counts, focus, blockers, next actions",
+            json!({
+                "token_budget": { "type": "integer", "description": "Token budget; lists are trimmed to fit" },
+                "model": { "type": "string", "enum": ["gpt-4o", "claude"], "description": "Model whose tokenizer measures the budget (default gpt-4o)" },
+                "include_archived": include_archived,
+            }),
+            &[],
+        ),
+        tool(
+            "granary_list_projects",
+            "List projects",
+            json!({ "include_archived": include_archived }),
+            &[],
+        ),
+        tool(
+            "granary_get_project",
+            "Get a project and its tasks",
+            json!({ "id": string("Project ID") }),
+            &["id"],
+        ),
+        tool(
+            "granary_create_project",
+            "Create a new project",
+            json!({
+                "name": string("Project name"),
+                "description": string("Project description"),
+                "owner": string("Project owner"),
+                "tags": tags,
+            }),
+            &["name"],
+        ),
+        tool(
+            "granary_update_project",
+            "Update a project",
+            json!({
+                "id": string("Project ID"),
+                "name": string("New name"),
+                "description": string("New description"),
+                "owner": string("New owner"),
+                "tags": tags,
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_archive_project",
+            "Archive a project",
+            json!({
+                "id": string("Project ID"),
+                "cascade": { "type": "boolean", "description": "Archive the project's tasks too" },
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_list_tasks",
+            "List tasks, either for one project or across the workspace with filters",
+            json!({
+                "project_id": string("Only list tasks in this project"),
+                "status": string("Filter by status (draft, todo, in_progress, done, blocked)"),
+                "priority": priority,
+                "owner": string("Filter by owner"),
+                "label": string("Only list tasks with this label"),
+                "include_archived": include_archived,
+            }),
+            &[],
+        ),
+        tool(
+            "granary_get_task",
+            "Get a task with its unmet dependencies and subtasks",
+            json!({ "id": string("Task ID") }),
+            &["id"],
+        ),
+        tool(
+            "granary_create_task",
+            "Create a new task in a project",
+            json!({
+                "project_id": string("Project ID"),
+                "title": string("Task title"),
+                "description": string("Task description"),
+                "priority": priority,
+                "owner": string("Task owner"),
+                "parent_task_id": string("Parent task ID for subtasks"),
+                "due_at": string("Due date (RFC 3339, or relative: today, friday, next week, in 3 days)"),
+                "estimate": { "type": "number", "description": "Estimated effort in points or hours" },
+                "tags": tags,
+            }),
+            &["project_id", "title"],
+        ),
+        tool(
+            "granary_update_task",
+            "Update a task",
+            json!({
+                "id": string("Task ID"),
+                "title": string("New title"),
+                "description": string("New description"),
+                "status": string("New status (draft, todo, in_progress, done, blocked)"),
+                "priority": priority,
+                "owner": string("New owner"),
+                "due_at": string("Due date (RFC 3339, or relative: today, friday, next week, in 3 days)"),
+                "estimate": { "type": "number", "description": "Estimated effort in points or hours" },
+                "tags": tags,
+                "fields": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Custom fields to set as NAME=VALUE (NAME= clears the field)"
+                },
+                "session_id": session_id,
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_start_task",
+            "Start working on a task",
+            json!({
+                "id": string("Task ID"),
+                "owner": string("Owner to assign"),
+                "session_id": session_id,
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_complete_task",
+            "Mark a task as done",
+            json!({
+                "id": string("Task ID"),
+                "comment": string("Completion comment"),
+                "session_id": session_id,
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_block_task",
+            "Mark a task as blocked",
+            json!({
+                "id": string("Task ID"),
+                "reason": string("Why the task is blocked"),
+                "session_id": session_id,
+            }),
+            &["id", "reason"],
+        ),
+        tool(
+            "granary_next_task",
+            "Get the next actionable task",
+            json!({}),
+            &[],
+        ),
+        tool(
+            "granary_list_initiatives",
+            "List initiatives",
+            json!({ "include_archived": include_archived }),
+            &[],
+        ),
+        tool(
+            "granary_get_initiative",
+            "Get an initiative and its projects",
+            json!({ "id": string("Initiative ID") }),
+            &["id"],
+        ),
+        tool(
+            "granary_create_initiative",
+            "Create a new initiative",
+            json!({
+                "name": string("Initiative name"),
+                "description": string("Initiative description"),
+                "owner": string("Initiative owner"),
+                "tags": tags,
+            }),
+            &["name"],
+        ),
+        tool(
+            "granary_update_initiative",
+            "Update an initiative",
+            json!({
+                "id": string("Initiative ID"),
+                "name": string("New name"),
+                "description": string("New description"),
+                "owner": string("New owner"),
+                "tags": tags,
+            }),
+            &["id"],
+        ),
+        tool(
+            "granary_archive_initiative",
+            "Archive an initiative",
+            json!({ "id": string("Initiative ID") }),
+            &["id"],
+        ),
+        tool(
+            "granary_initiative_summary",
+            "Summarize an initiative: progress, blockers, and next actions",
+            json!({
+                "id": string("Initiative ID"),
+                "max_next_actions": { "type": "integer", "description": "Maximum next actions to include" },
+            }),
+            &["id"],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    async fn setup_server() -> (McpServer, tempfile::TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        (McpServer::new(pool, workspace), temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_initialize() {
+        let (server, _temp) = setup_server().await;
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["serverInfo"]["name"], "granary");
+        assert_eq!(response["result"]["protocolVersion"], MCP_PROTOCOL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_notification_has_no_response() {
+        let (server, _temp) = setup_server().await;
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_and_parse_error() {
+        let (server, _temp) = setup_server().await;
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"nope"}"#)
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], rpc_codes::METHOD_NOT_FOUND);
+
+        let response = server.handle_line("{not json").await.unwrap();
+        assert_eq!(response["error"]["code"], rpc_codes::PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_matches_dispatch() {
+        let (server, _temp) = setup_server().await;
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), tool_definitions().len());
+
+        // Every listed tool must be dispatchable (no "Unknown tool" JSON-RPC error)
+        for tool in tools {
+            let call = json!({
+                "jsonrpc": "2.0",
+                "id": 4,
+                "method": "tools/call",
+                "params": { "name": tool["name"], "arguments": {} },
+            });
+            let response = server.handle_message(call).await.unwrap();
+            assert!(response.get("result").is_some(), "{}", tool["name"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_project_and_task() {
+        let (server, _temp) = setup_server().await;
+        let server = server.with_actor(Actor {
+            name: Some("agent".to_string()),
+            session_id: None,
+        });
+
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 5,
+            "method": "tools/call",
+            "params": { "name": "granary_create_project", "arguments": { "name": "MCP Test" } },
+        });
+        let response = server.handle_message(call).await.unwrap();
+        assert_eq!(response["result"]["isError"], false);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let project: Value = serde_json::from_str(text).unwrap();
+        let project_id = project["id"].as_str().unwrap();
+
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 6,
+            "method": "tools/call",
+            "params": {
+                "name": "granary_create_task",
+                "arguments": { "project_id": project_id, "title": "Do it", "priority": "P1" },
+            },
+        });
+        let response = server.handle_message(call).await.unwrap();
+        assert_eq!(response["result"]["isError"], false);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        let task: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(task["priority"], "P1");
+        assert_eq!(task["project_id"], project_id);
+
+        let call = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "tools/call",
+            "params": { "name": "granary_get_task", "arguments": { "id": "missing-task-1" } },
+        });
+        let response = server.handle_message(call).await.unwrap();
+        assert_eq!(response["result"]["isError"], true);
+
+        // Each call that changed something is its own operation
+        let operations = services::list_undoable(&server.pool, 5).await.unwrap();
+        let commands: Vec<&str> = operations.iter().map(|o| o.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec!["mcp granary_create_task", "mcp granary_create_project"]
+        );
+        assert_eq!(operations[0].actor.as_deref(), Some("agent"));
+    }
+
+    #[tokio::test]
+    async fn test_update_task_claimed_by_another_session() {
+        let (server, _temp) = setup_server().await;
+        let project = services::create_project(
+            &server.pool,
+            CreateProject {
+                name: "Claims".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = services::create_task(
+            &server.pool,
+            CreateTask {
+                project_id: project.id,
+                title: "Shared".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut sessions = Vec::new();
+        for name in ["agent-1", "agent-2"] {
+            let session = services::create_session(
+                &server.pool,
+                CreateSession {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            sessions.push(session.id);
+        }
+        services::claim_task_for_session(&server.pool, &task.id, &sessions[0], 30, false)
+            .await
+            .unwrap();
+
+        // The server runs as the second session; the first holds the claim
+        let server = server.with_actor(Actor {
+            name: Some("agent-2".to_string()),
+            session_id: Some(sessions[1].clone()),
+        });
+        let update = |arguments: Value| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 8,
+                "method": "tools/call",
+                "params": { "name": "granary_update_task", "arguments": arguments },
+            })
+        };
+        let response = server
+            .handle_message(update(json!({ "id": task.id, "status": "in_progress" })))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("is claimed by session"), "{}", text);
+        let unchanged = services::get_task(&server.pool, &task.id).await.unwrap();
+        assert_eq!(unchanged.status, "draft");
+
+        // The claim holder can make the change
+        let response = server
+            .handle_message(update(json!({
+                "id": task.id,
+                "status": "in_progress",
+                "session_id": sessions[0],
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], false);
+    }
+}
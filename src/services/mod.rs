@@ -1,20 +1,63 @@
 pub mod agent_files;
+pub mod aggregate;
+pub mod attachment_service;
+pub mod backup_service;
+pub mod batch_input;
 pub mod batch_service;
 pub mod checkpoint_service;
+pub mod config_validation;
+pub mod context_spec;
+pub mod csv_import;
+pub mod datetime;
+pub mod doctor;
+pub mod event_batch;
 pub mod event_poller;
+pub mod export_service;
+pub mod field_service;
 pub mod filter;
+pub mod filter_expr;
+pub mod git_service;
+pub mod github_import;
 pub mod global_config;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod initiative_service;
+pub mod jira_service;
+pub mod label_service;
+pub mod linear_service;
+pub mod log_retention;
+pub mod log_sanitize;
+pub mod markdown_import;
+pub mod mcp_server;
+pub mod operation_service;
+pub mod plugin_service;
 pub mod polled_events;
 pub mod project_service;
+pub mod prompt_templates;
+pub mod recommendation;
+pub mod reminder_service;
+pub mod remote_service;
+pub mod remote_store;
+pub mod report_service;
+pub mod rule_service;
+pub mod run_artifacts;
+pub mod run_log_search;
+pub mod run_progress;
 pub mod runner;
+pub mod runner_input;
+pub mod schedule;
 pub mod search_service;
 pub mod session_service;
+pub mod summary_sections;
 pub mod summary_service;
 pub mod task_service;
 pub mod template;
+pub mod tokens;
+pub mod web_ui;
 pub mod worker_runtime;
 pub mod workspace;
+pub mod workspace_config;
+pub mod workspace_registry;
 
 // Test modules
 #[cfg(test)]
@@ -27,12 +70,16 @@ mod template_tests;
 mod worker_tests;
 
 pub use agent_files::*;
+pub use aggregate::*;
 pub use batch_service::*;
 pub use checkpoint_service::*;
 pub use event_poller::{EventPoller, EventPollerConfig, create_poller_for_worker};
+pub use field_service::*;
 pub use filter::{Filter, FilterOp, matches_all, matches_any, parse_filters};
 pub use global_config as global_config_service;
 pub use initiative_service::*;
+pub use label_service::*;
+pub use operation_service::*;
 pub use polled_events::PolledEventEmitter;
 pub use project_service::*;
 pub use runner::{RunnerHandle, spawn_runner, spawn_runner_with_env};
@@ -46,3 +93,4 @@ pub use worker_runtime::{
     start_worker_runtime,
 };
 pub use workspace::*;
+pub use workspace_registry::*;
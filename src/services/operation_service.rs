@@ -0,0 +1,371 @@
+//! Undo and audit for mutating commands.
+//!
+//! Each command that changes the workspace is saved as an operation holding
+//! before and after snapshots of the rows it touched (see
+//! [`db::operations`]), attributed to the actor that ran it. `granary undo`
+//! reverses the most recent operations and `granary audit` shows the history.
+
+use std::future::Future;
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::db::connection::create_recorded_pool;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::{Workspace, global_config};
+
+/// Commands that are never recorded: those that create a workspace,
+/// long-running servers, which record an operation per request with
+/// [`record_operation`], `pull`, whose changes were made elsewhere, and `workspaces`,
+/// which only changes the global registry
+pub const UNRECORDED_COMMANDS: &[&str] = &[
+    "init",
+    "import",
+    "mcp",
+    "serve",
+    "tui",
+    "daemon",
+    "completions",
+    "__complete",
+    "backup",
+    "restore",
+    "pull",
+    "workspaces",
+];
+
+/// Commands that are recorded for the audit trail but cannot be undone
+pub const NON_UNDOABLE_COMMANDS: &[&str] = &["undo"];
+
+/// Work out who is running this command: the name given with `--actor` (or
+/// `GRANARY_ACTOR`), else the owner of the current session, else the
+/// `[user] name` from the global config, else the user's login name
+pub async fn resolve_actor(name: Option<&str>) -> Actor {
+    let workspace = Workspace::find().ok();
+    let session_id = workspace.as_ref().and_then(|w| w.current_session_id());
+
+    let mut name = name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string);
+    if name.is_none()
+        && let (Some(workspace), Some(session_id)) = (&workspace, &session_id)
+        && let Ok(pool) = workspace.pool().await
+    {
+        name = db::sessions::get(&pool, session_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|session| session.owner);
+    }
+    let name = name
+        .or_else(|| {
+            global_config::load()
+                .ok()
+                .and_then(|config| config.user.name)
+                .filter(|n| !n.trim().is_empty())
+        })
+        .or_else(|| {
+            std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .ok()
+        });
+
+    Actor { name, session_id }
+}
+
+/// Name of the actor this command runs as, recorded as the author of the
+/// tasks, comments and checkpoints it creates
+pub fn current_author() -> Option<String> {
+    db::operations::recording_actor().and_then(|actor| actor.name)
+}
+
+/// Record the changes this process makes to the workspace from now on,
+/// returning the ID of the operation they will be saved under
+pub fn start_recording(actor: Actor) -> String {
+    let operation_id = generate_operation_id();
+    db::operations::start_recording(operation_id.clone(), actor);
+    operation_id
+}
+
+/// Save the recorded operation if the command changed anything
+pub async fn finish_recording(
+    pool: &SqlitePool,
+    operation_id: &str,
+    command: &str,
+    actor: &Actor,
+    undoable: bool,
+) -> Result<bool> {
+    db::operations::finish(pool, operation_id, command, actor, undoable).await
+}
+
+/// Run `f` as one recorded operation, for servers that make changes on
+/// behalf of many callers: what `f` changes through the pool it is given is
+/// saved under a new operation attributed to `actor`, as are the events and
+/// records it creates. The operation is saved even if `f` fails partway.
+pub async fn record_operation<T, F, Fut>(
+    workspace: &Workspace,
+    command: &str,
+    actor: Actor,
+    f: F,
+) -> Result<T>
+where
+    F: FnOnce(SqlitePool) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let operation_id = generate_operation_id();
+    let pool = create_recorded_pool(&workspace.db_path, operation_id.clone()).await?;
+    let result = db::operations::with_actor(actor.clone(), f(pool.clone())).await;
+    let finished = finish_recording(&pool, &operation_id, command, &actor, true).await;
+    pool.close().await;
+    let value = result?;
+    finished?;
+    Ok(value)
+}
+
+/// Recorded changes matching `filter`, newest first
+pub async fn audit(pool: &SqlitePool, filter: &AuditFilter, page: Page) -> Result<Vec<AuditEntry>> {
+    db::operations::audit(pool, filter, page).await
+}
+
+/// The operations `undo` would reverse, newest first
+pub async fn list_undoable(pool: &SqlitePool, last: u32) -> Result<Vec<Operation>> {
+    if last == 0 {
+        return Err(GranaryError::InvalidArgument(
+            "--last must be at least 1".to_string(),
+        ));
+    }
+    db::operations::list_undoable(pool, last).await
+}
+
+/// Reverse the `last` most recent operations, newest first.
+///
+/// Either every operation is undone or none is. Returns the operations that
+/// were undone, which is empty when there is nothing left to undo.
+pub async fn undo(pool: &SqlitePool, last: u32) -> Result<Vec<Operation>> {
+    let operations = list_undoable(pool, last).await?;
+    if !operations.is_empty() {
+        db::operations::undo(pool, &operations).await?;
+    }
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task, update_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_undo_reverses_recorded_operations() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let actor = Actor {
+            name: Some("alice".to_string()),
+            session_id: None,
+        };
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Undo".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        // First operation creates a task, the second retitles it
+        let created = create_recorded_pool(&workspace.db_path, "op-create")
+            .await
+            .unwrap();
+        let task = create_task(
+            &created,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Original".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            finish_recording(&created, "op-create", "tasks create", &actor, true)
+                .await
+                .unwrap()
+        );
+
+        let updated = create_recorded_pool(&workspace.db_path, "op-update")
+            .await
+            .unwrap();
+        update_task(
+            &updated,
+            &task.id,
+            UpdateTask {
+                title: Some("Mistake".to_string()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            finish_recording(&updated, "op-update", "task update", &actor, true)
+                .await
+                .unwrap()
+        );
+
+        let history = audit(
+            &pool,
+            &AuditFilter {
+                entity: Some(task.id.clone()),
+                ..Default::default()
+            },
+            Page::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, "update");
+        assert_eq!(history[0].actor.as_deref(), Some("alice"));
+        assert_eq!(history[1].entity_id.as_deref(), Some(task.id.as_str()));
+        let by_bob = AuditFilter {
+            actor: Some("bob".to_string()),
+            ..Default::default()
+        };
+        assert!(
+            audit(&pool, &by_bob, Page::default())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            sqlx::query("DELETE FROM operation_changes")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+        assert!(
+            sqlx::query("UPDATE operations SET actor = 'mallory'")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+
+        let undone = undo(&pool, 1).await.unwrap();
+        assert_eq!(undone[0].id, "op-update");
+        let task = db::tasks::get(&pool, &task.id).await.unwrap().unwrap();
+        assert_eq!(task.title, "Original");
+
+        let undone = undo(&pool, 5).await.unwrap();
+        assert_eq!(undone.len(), 1);
+        assert!(db::tasks::get(&pool, &task.id).await.unwrap().is_none());
+        assert!(undo(&pool, 1).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_undo_refuses_rows_changed_since() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+
+        let recorded = create_recorded_pool(&workspace.db_path, "op-create")
+            .await
+            .unwrap();
+        let project = create_project(
+            &recorded,
+            CreateProject {
+                name: "Conflict".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        finish_recording(
+            &recorded,
+            "op-create",
+            "projects create",
+            &Actor::default(),
+            true,
+        )
+        .await
+        .unwrap();
+
+        // An unrecorded change, as another process would make
+        crate::services::archive_project(&pool, &project.id, false)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            undo(&pool, 1).await,
+            Err(GranaryError::Conflict(_))
+        ));
+        assert!(
+            db::projects::get(&pool, &project.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_saves_an_operation_per_call() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let actor = Actor {
+            name: Some("agent".to_string()),
+            session_id: Some("sess-1".to_string()),
+        };
+
+        let project = record_operation(
+            &workspace,
+            "mcp granary_create_project",
+            actor,
+            |pool| async move {
+                create_project(
+                    &pool,
+                    CreateProject {
+                        name: "Served".to_string(),
+                        ..Default::default()
+                    },
+                )
+                .await
+            },
+        )
+        .await
+        .unwrap();
+        // Calls that change nothing aren't saved
+        record_operation(
+            &workspace,
+            "mcp granary_get_project",
+            Actor::default(),
+            |pool| {
+                let id = project.id.clone();
+                async move { crate::services::get_project(&pool, &id).await }
+            },
+        )
+        .await
+        .unwrap();
+
+        let operations = list_undoable(&pool, 5).await.unwrap();
+        assert_eq!(operations.len(), 1);
+        assert_eq!(operations[0].command, "mcp granary_create_project");
+        assert_eq!(operations[0].actor.as_deref(), Some("agent"));
+        assert_eq!(operations[0].session_id.as_deref(), Some("sess-1"));
+        let events = db::events::list_by_entity(&pool, "project", &project.id)
+            .await
+            .unwrap();
+        assert_eq!(events[0].actor.as_deref(), Some("agent"));
+        assert_eq!(events[0].session_id.as_deref(), Some("sess-1"));
+
+        // Undone operations stay in the log, and only their undo time can change
+        undo(&pool, 1).await.unwrap();
+        assert!(
+            sqlx::query("UPDATE operations SET undone_at = NULL")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+    }
+}
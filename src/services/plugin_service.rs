@@ -0,0 +1,204 @@
+//! External plugins: `granary-<name>` executables on PATH, run as
+//! `granary <name> ...` the way git runs `git-<name>`.
+//!
+//! A plugin gets its arguments as given and learns about the invocation
+//! from the environment:
+//!
+//! - `GRANARY_HOME`: root of the workspace in use, when there is one, so
+//!   that `granary` run by the plugin sees the same workspace
+//! - `GRANARY_FORMAT`: the output format asked for (`table`, `json`, ...)
+//! - `GRANARY_SESSION` and `GRANARY_ACTOR`: when given to granary
+//! - `GRANARY_PROFILE`: the config profile in use, if any
+//! - `GRANARY_BIN`: the granary executable that ran the plugin
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::error::{GranaryError, Result};
+use crate::models::Plugin;
+use crate::output::OutputFormat;
+use crate::services::global_config::{self, PROFILE_ENV};
+use crate::services::workspace::{SESSION_ENV, WORKSPACE_ENV, Workspace};
+
+/// Prefix of plugin executable names
+pub const PLUGIN_PREFIX: &str = "granary-";
+
+/// Environment variable holding the output format
+pub const FORMAT_ENV: &str = "GRANARY_FORMAT";
+
+/// Environment variable holding the actor
+pub const ACTOR_ENV: &str = "GRANARY_ACTOR";
+
+/// Environment variable holding the path of the granary executable
+pub const BIN_ENV: &str = "GRANARY_BIN";
+
+/// What a plugin is told about the invocation
+#[derive(Debug, Default)]
+pub struct PluginInvocation {
+    pub format: OutputFormat,
+    pub session: Option<String>,
+    pub actor: Option<String>,
+}
+
+/// Plugins on PATH, by name. When several directories hold the same
+/// plugin, the first one on PATH wins, as it would in a shell.
+pub fn discover_plugins() -> Vec<Plugin> {
+    std::env::var_os("PATH")
+        .map(|path| discover_in(&path))
+        .unwrap_or_default()
+}
+
+/// Plugins in the directories of a PATH-style list
+pub fn discover_in(path: &OsStr) -> Vec<Plugin> {
+    let mut plugins = BTreeMap::new();
+    for dir in std::env::split_paths(path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<(String, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let name = plugin_name(&path)?;
+                is_executable(&path).then_some((name, path))
+            })
+            .collect();
+        found.sort();
+        for (name, path) in found {
+            plugins.entry(name.clone()).or_insert_with(|| Plugin {
+                name,
+                path: path.display().to_string(),
+            });
+        }
+    }
+    plugins.into_values().collect()
+}
+
+/// Find the plugin for `granary <name>`
+pub fn find_plugin(name: &str) -> Option<Plugin> {
+    discover_plugins().into_iter().find(|p| p.name == name)
+}
+
+/// Run a plugin with the terminal attached and return its exit code
+pub fn run_plugin(plugin: &Plugin, args: &[String], invocation: &PluginInvocation) -> Result<i32> {
+    let mut command = std::process::Command::new(&plugin.path);
+    command.args(args).envs(plugin_env(invocation));
+    let status = command
+        .status()
+        .map_err(|e| GranaryError::Other(format!("Failed to run plugin {}: {}", plugin.name, e)))?;
+    // A plugin killed by a signal has no exit code; report it as failed
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Environment variables passed to a plugin
+pub fn plugin_env(invocation: &PluginInvocation) -> Vec<(&'static str, String)> {
+    let mut env = vec![(FORMAT_ENV, format_name(invocation.format).to_string())];
+    if let Ok(workspace) = Workspace::find() {
+        env.push((WORKSPACE_ENV, workspace.root.display().to_string()));
+    }
+    if let Some(session) = &invocation.session {
+        env.push((SESSION_ENV, session.clone()));
+    }
+    if let Some(actor) = &invocation.actor {
+        env.push((ACTOR_ENV, actor.clone()));
+    }
+    if let Some(profile) = global_config::active_profile() {
+        env.push((PROFILE_ENV, profile));
+    }
+    if let Ok(exe) = std::env::current_exe() {
+        env.push((BIN_ENV, exe.display().to_string()));
+    }
+    env
+}
+
+/// Name of an output format as given to `--format`
+fn format_name(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Table => "table",
+        OutputFormat::Json => "json",
+        OutputFormat::Yaml => "yaml",
+        OutputFormat::Md => "md",
+        OutputFormat::Prompt => "prompt",
+    }
+}
+
+/// Plugin name for an executable path, if it is named like a plugin
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = if cfg!(windows) {
+        path.file_stem()?
+    } else {
+        path.file_name()?
+    };
+    let name = file_name.to_str()?.strip_prefix(PLUGIN_PREFIX)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Whether a path is a file the OS can run
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Whether a path is a file the OS can run
+#[cfg(windows)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "exe" | "bat" | "cmd"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    fn write_plugin(dir: &Path, file_name: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(file_name);
+        std::fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_plugins_first_on_path_wins() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        write_plugin(first.path(), "granary-lint", 0o755);
+        write_plugin(first.path(), "granary-notes", 0o644);
+        write_plugin(first.path(), "granary-", 0o755);
+        write_plugin(first.path(), "other-tool", 0o755);
+        write_plugin(second.path(), "granary-lint", 0o755);
+        write_plugin(second.path(), "granary-deploy", 0o755);
+
+        let path = std::env::join_paths([first.path(), second.path()]).unwrap();
+        let plugins = discover_in(&path);
+
+        let names: Vec<&str> = plugins.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["deploy", "lint"]);
+        assert!(
+            plugins[1]
+                .path
+                .starts_with(&first.path().display().to_string())
+        );
+    }
+
+    #[test]
+    fn test_plugin_env_passes_format() {
+        let env = plugin_env(&PluginInvocation {
+            format: OutputFormat::Json,
+            actor: Some("ci".to_string()),
+            ..Default::default()
+        });
+        assert!(env.contains(&(FORMAT_ENV, "json".to_string())));
+        assert!(env.contains(&(ACTOR_ENV, "ci".to_string())));
+        assert!(!env.iter().any(|(key, _)| *key == SESSION_ENV));
+    }
+}
@@ -3,6 +3,7 @@ use sqlx::SqlitePool;
 use crate::db;
 use crate::error::{GranaryError, Result};
 use crate::models::*;
+use crate::services::field_service::{attach_project_fields, validate_fields, write_fields};
 
 /// Create a new project
 pub async fn create_project(pool: &SqlitePool, input: CreateProject) -> Result<Project> {
@@ -40,6 +41,7 @@ pub async fn create_project(pool: &SqlitePool, input: CreateProject) -> Result<P
         created_at: now.clone(),
         updated_at: now,
         version: 1,
+        fields: Default::default(),
     };
 
     db::projects::create(pool, &project).await?;
@@ -65,14 +67,27 @@ pub async fn create_project(pool: &SqlitePool, input: CreateProject) -> Result<P
 
 /// Get a project by ID
 pub async fn get_project(pool: &SqlitePool, id: &str) -> Result<Project> {
-    db::projects::get(pool, id)
+    let mut project = db::projects::get(pool, id)
         .await?
-        .ok_or_else(|| GranaryError::ProjectNotFound(id.to_string()))
+        .ok_or_else(|| GranaryError::ProjectNotFound(id.to_string()))?;
+    attach_project_fields(pool, std::slice::from_mut(&mut project)).await?;
+    Ok(project)
 }
 
 /// List all projects
 pub async fn list_projects(pool: &SqlitePool, include_archived: bool) -> Result<Vec<Project>> {
-    db::projects::list(pool, include_archived).await
+    list_projects_sorted(pool, include_archived, &Sort::default()).await
+}
+
+/// List all projects in the given order
+pub async fn list_projects_sorted(
+    pool: &SqlitePool,
+    include_archived: bool,
+    sort: &Sort,
+) -> Result<Vec<Project>> {
+    let mut projects = db::projects::list_sorted(pool, include_archived, sort).await?;
+    attach_project_fields(pool, &mut projects).await?;
+    Ok(projects)
 }
 
 /// Update a project
@@ -82,6 +97,7 @@ pub async fn update_project(
     updates: UpdateProject,
 ) -> Result<Project> {
     let mut project = get_project(pool, id).await?;
+    let fields = validate_fields(pool, &updates.fields).await?;
 
     if let Some(name) = updates.name {
         project.name = name;
@@ -113,6 +129,11 @@ pub async fn update_project(
         });
     }
 
+    let mut payload = serde_json::json!({});
+    if !fields.is_empty() {
+        payload["fields"] = write_fields(pool, EntityType::Project, &project.id, &fields).await?;
+    }
+
     // Log event
     db::events::create(
         pool,
@@ -122,7 +143,7 @@ pub async fn update_project(
             entity_id: project.id.clone(),
             actor: None,
             session_id: None,
-            payload: serde_json::json!({}),
+            payload,
         },
     )
     .await?;
@@ -131,32 +152,57 @@ pub async fn update_project(
     get_project(pool, id).await
 }
 
-/// Archive a project
-pub async fn archive_project(pool: &SqlitePool, id: &str) -> Result<Project> {
+/// Archive a project.
+///
+/// With `cascade`, its tasks are archived too, in the same transaction. An
+/// already archived project can be cascaded again to archive tasks added
+/// since.
+pub async fn archive_project(pool: &SqlitePool, id: &str, cascade: bool) -> Result<Project> {
     let project = get_project(pool, id).await?;
+    let already_archived = project.status == ProjectStatus::Archived.as_str();
 
-    if project.status == ProjectStatus::Archived.as_str() {
+    if already_archived && !cascade {
         return Err(GranaryError::Conflict(format!(
             "Project {} is already archived",
             id
         )));
     }
 
-    db::projects::archive(pool, id).await?;
-
-    // Log event
-    db::events::create(
-        pool,
-        &CreateEvent {
-            event_type: EventType::ProjectArchived,
-            entity_type: EntityType::Project,
-            entity_id: id.to_string(),
-            actor: None,
-            session_id: None,
-            payload: serde_json::json!({}),
-        },
-    )
-    .await?;
+    let mut tx = pool.begin().await?;
+    if !already_archived {
+        db::projects::archive(&mut *tx, id).await?;
+
+        // Log event
+        db::events::create(
+            &mut *tx,
+            &CreateEvent {
+                event_type: EventType::ProjectArchived,
+                entity_type: EntityType::Project,
+                entity_id: id.to_string(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({ "cascade": cascade }),
+            },
+        )
+        .await?;
+    }
+    if cascade {
+        for task_id in db::tasks::archive_by_project(&mut *tx, id).await? {
+            db::events::create(
+                &mut *tx,
+                &CreateEvent {
+                    event_type: EventType::TaskArchived,
+                    entity_type: EntityType::Task,
+                    entity_id: task_id,
+                    actor: None,
+                    session_id: None,
+                    payload: serde_json::json!({ "project_id": id }),
+                },
+            )
+            .await?;
+        }
+    }
+    tx.commit().await?;
 
     get_project(pool, id).await
 }
@@ -0,0 +1,181 @@
+//! User templates for prompt output.
+//!
+//! The prompt renderers for `summary`, `context` and `handoff` can be
+//! replaced with a minijinja template stored in `.granary/templates/`. A
+//! template is picked by `--template <name>`, or per command with the
+//! `templates.summary`, `templates.context` and `templates.handoff` config
+//! keys, and is rendered with the same fields as the JSON output:
+//!
+//! ```text
+//! {% for task in next_actions %}- {{ task.title }} ({{ task.id }})
+//! {% endfor %}
+//! ```
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::services::Workspace;
+
+/// Directory under `.granary/` holding user templates
+pub const TEMPLATES_DIR: &str = "templates";
+
+/// Extension added to template names that have none
+pub const TEMPLATE_EXTENSION: &str = "jinja";
+
+/// Outputs a user template can replace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    Summary,
+    Context,
+    Handoff,
+}
+
+impl PromptKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptKind::Summary => "summary",
+            PromptKind::Context => "context",
+            PromptKind::Handoff => "handoff",
+        }
+    }
+
+    /// Config key naming the default template for this output
+    pub fn config_key(&self) -> String {
+        format!("templates.{}", self.as_str())
+    }
+}
+
+/// Validate a value for a `templates.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(kind) = key.strip_prefix("templates.") else {
+        return Ok(());
+    };
+    if !["summary", "context", "handoff"].contains(&kind) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Unknown template key '{}'. Expected templates.summary, templates.context or templates.handoff",
+            key
+        )));
+    }
+    validate_name(value)
+}
+
+/// Path of a named template in the workspace
+pub fn template_path(workspace: &Workspace, name: &str) -> PathBuf {
+    let dir = workspace.granary_dir.join(TEMPLATES_DIR);
+    if name.contains('.') {
+        dir.join(name)
+    } else {
+        dir.join(format!("{}.{}", name, TEMPLATE_EXTENSION))
+    }
+}
+
+/// Render `data` with the template picked by `template`, or else by the
+/// config key for `kind`. Returns None when neither names a template, so
+/// the built-in renderer is used.
+pub async fn render<T: Serialize>(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    kind: PromptKind,
+    template: Option<&str>,
+    data: &T,
+) -> Result<Option<String>> {
+    let name = match template {
+        Some(name) => name.to_string(),
+        None => match db::config::get(pool, &kind.config_key()).await? {
+            Some(name) => name,
+            None => return Ok(None),
+        },
+    };
+    validate_name(&name)?;
+    let path = template_path(workspace, &name);
+    let source = std::fs::read_to_string(&path).map_err(|_| {
+        GranaryError::InvalidArgument(format!(
+            "Template '{}' not found at {}",
+            name,
+            path.display()
+        ))
+    })?;
+    render_source(&name, &source, data).map(Some)
+}
+
+/// Render a template source with `data` as its context
+pub fn render_source<T: Serialize>(name: &str, source: &str, data: &T) -> Result<String> {
+    let mut env = minijinja::Environment::new();
+    env.set_undefined_behavior(minijinja::UndefinedBehavior::Lenient);
+    env.add_template(name, source)
+        .map_err(|e| template_error(name, e))?;
+    let template = env
+        .get_template(name)
+        .map_err(|e| template_error(name, e))?;
+    let mut output = template
+        .render(minijinja::Value::from_serialize(data))
+        .map_err(|e| template_error(name, e))?;
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Template names are file names in the templates directory
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Invalid template name '{}'. Expected a file name in .granary/{}/",
+            name, TEMPLATES_DIR
+        )));
+    }
+    Ok(())
+}
+
+fn template_error(name: &str, error: minijinja::Error) -> GranaryError {
+    GranaryError::InvalidArgument(format!("Template '{}': {}", name, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_render_uses_flag_then_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let data = serde_json::json!({
+            "next_actions": [{ "id": "t-1", "title": "Ship it" }],
+        });
+
+        // No template picked: the built-in renderer is used
+        let output = render(&pool, &workspace, PromptKind::Summary, None, &data)
+            .await
+            .unwrap();
+        assert!(output.is_none());
+
+        let dir = workspace.granary_dir.join(TEMPLATES_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("brief.jinja"),
+            "{% for t in next_actions %}- {{ t.title }} ({{ t.id }}){% endfor %}",
+        )
+        .unwrap();
+        db::config::set(&pool, "templates.summary", "brief")
+            .await
+            .unwrap();
+        let output = render(&pool, &workspace, PromptKind::Summary, None, &data)
+            .await
+            .unwrap();
+        assert_eq!(output.as_deref(), Some("- Ship it (t-1)\n"));
+
+        let missing = render(&pool, &workspace, PromptKind::Summary, Some("other"), &data).await;
+        assert!(matches!(missing, Err(GranaryError::InvalidArgument(_))));
+        assert!(render_source("bad", "{% for %}", &data).is_err());
+        assert!(validate_config_value("templates.summary", "../escape").is_err());
+        assert!(validate_config_value("templates.report", "brief").is_err());
+    }
+}
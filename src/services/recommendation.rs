@@ -0,0 +1,248 @@
+//! Task recommendations for `granary next`.
+//!
+//! The candidates are the ready tasks in the session's scope: todo, not
+//! blocked, with every dependency done. Each is scored on [`FACTORS`] and
+//! the points are weighted by the current session mode's `next_weights`
+//! from `.granary/granary.toml`. Ties go to the oldest task.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::*;
+use crate::services::workspace_config as workspace_config_service;
+use crate::services::{Workspace, get_current_session, get_scope_by_type, mode_config};
+
+/// Scoring factors, in the order they are listed
+pub const FACTORS: [&str; 4] = ["priority", "due", "stale", "unblocks"];
+
+/// Ready tasks in the current session's scope, best recommendation first
+pub async fn recommend(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<Vec<TaskRecommendation>> {
+    let session = get_current_session(pool, workspace).await?;
+    let project_ids = match &session {
+        Some(session) => {
+            let ids = get_scope_by_type(pool, &session.id, ScopeItemType::Project).await?;
+            (!ids.is_empty()).then_some(ids)
+        }
+        None => None,
+    };
+    let config = workspace_config_service::load(workspace)?;
+    let weights = session
+        .as_ref()
+        .and_then(|s| mode_config(&config, s))
+        .map(|mode| mode.next_weights.clone())
+        .unwrap_or_default();
+
+    let tasks = db::tasks::get_all_next(pool, project_ids.as_deref()).await?;
+    rank(pool, tasks, &weights, Utc::now()).await
+}
+
+/// Score tasks and sort them, best first
+pub async fn rank(
+    pool: &SqlitePool,
+    tasks: Vec<Task>,
+    weights: &HashMap<String, f64>,
+    now: DateTime<Utc>,
+) -> Result<Vec<TaskRecommendation>> {
+    let ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let last_activity: HashMap<String, String> = db::tasks::list_last_activity(pool, &ids)
+        .await?
+        .into_iter()
+        .collect();
+    let dependents: HashMap<String, i64> = db::dependencies::count_open_dependents(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut ranked: Vec<TaskRecommendation> = tasks
+        .into_iter()
+        .map(|task| {
+            let idle_days = last_activity
+                .get(&task.id)
+                .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+                .map(|at| (now - at.with_timezone(&Utc)).num_days())
+                .unwrap_or_default();
+            let dependents = dependents.get(&task.id).copied().unwrap_or_default();
+            score(task, idle_days, dependents, weights, now)
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.task.created_at.cmp(&b.task.created_at))
+    });
+    Ok(ranked)
+}
+
+/// Score a task on each factor
+fn score(
+    task: Task,
+    idle_days: i64,
+    dependents: i64,
+    weights: &HashMap<String, f64>,
+    now: DateTime<Utc>,
+) -> TaskRecommendation {
+    let priority = match task.priority_enum() {
+        TaskPriority::P0 => 40.0,
+        TaskPriority::P1 => 30.0,
+        TaskPriority::P2 => 20.0,
+        TaskPriority::P3 => 10.0,
+        TaskPriority::P4 => 0.0,
+    };
+    let due = task
+        .due_at
+        .as_deref()
+        .and_then(|due| DateTime::parse_from_rfc3339(due).ok())
+        .map(|due| due.with_timezone(&Utc) - now);
+    let (due_points, due_detail) = match due {
+        None => (0.0, "no due date".to_string()),
+        Some(left) if left.num_seconds() < 0 => (
+            30.0,
+            format!("overdue by {}", plural(-left.num_days(), "day")),
+        ),
+        Some(left) => {
+            let points = match left.num_days() {
+                0 => 25.0,
+                1..=2 => 15.0,
+                3..=6 => 5.0,
+                _ => 0.0,
+            };
+            (points, format!("due in {}", plural(left.num_days(), "day")))
+        }
+    };
+    // A point every two idle days, so old tasks aren't passed over forever
+    let stale = (idle_days.clamp(0, 30) / 2) as f64;
+    let unblocks = (dependents * 5).min(15) as f64;
+
+    let factors: Vec<ScoreFactor> = [
+        ("priority", priority, task.priority.clone()),
+        ("due", due_points, due_detail),
+        ("stale", stale, format!("idle {}", plural(idle_days, "day"))),
+        (
+            "unblocks",
+            unblocks,
+            format!("{} waiting on it", plural(dependents, "task")),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, points, detail)| {
+        let weight = weights.get(name).copied().unwrap_or(1.0);
+        ScoreFactor {
+            name: name.to_string(),
+            points: points * weight,
+            weight,
+            detail,
+        }
+    })
+    .collect();
+
+    TaskRecommendation {
+        score: factors.iter().map(|f| f.points).sum(),
+        task,
+        factors,
+    }
+}
+
+fn plural(count: i64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// The factors that added to a recommendation's score, for `--include-reason`
+pub fn reason(recommendation: &TaskRecommendation) -> String {
+    let factors: Vec<String> = recommendation
+        .factors
+        .iter()
+        .filter(|f| f.points > 0.0)
+        .map(|f| format!("{} (+{})", f.detail, f.points))
+        .collect();
+    if factors.is_empty() {
+        format!("score {}; ready to start", recommendation.score)
+    } else {
+        format!(
+            "score {}; ready to start; {}",
+            recommendation.score,
+            factors.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{add_dependency, create_project, create_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_rank_scores_ready_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Next".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (title, priority, due_at) in [
+            (
+                "Low but overdue",
+                TaskPriority::P3,
+                Some(now - chrono::Duration::days(2)),
+            ),
+            ("Urgent", TaskPriority::P0, None),
+            ("Blocked by urgent", TaskPriority::P1, None),
+        ] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    priority,
+                    due_at: due_at.map(|d| d.to_rfc3339()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            crate::services::ready_task(&pool, &task.id, None)
+                .await
+                .unwrap();
+            ids.push(task.id);
+        }
+        add_dependency(&pool, &ids[2], &ids[1]).await.unwrap();
+
+        let tasks = db::tasks::get_all_next(&pool, None).await.unwrap();
+        let ranked = rank(&pool, tasks.clone(), &HashMap::new(), now)
+            .await
+            .unwrap();
+        let order: Vec<&str> = ranked.iter().map(|r| r.task.id.as_str()).collect();
+        // The blocked task isn't ready; the urgent one also unblocks it
+        assert_eq!(order, vec![ids[1].as_str(), ids[0].as_str()]);
+        assert_eq!(ranked[0].score, 45.0);
+        assert_eq!(ranked[0].factors[3].detail, "1 task waiting on it");
+        assert_eq!(ranked[1].factors[1].detail, "overdue by 2 days");
+        assert_eq!(ranked[1].score, 40.0);
+
+        // A mode that cares about deadlines puts the overdue task first
+        let weights = HashMap::from([("due".to_string(), 2.0)]);
+        let ranked = rank(&pool, tasks, &weights, now).await.unwrap();
+        assert_eq!(ranked[0].task.id, ids[0]);
+        assert_eq!(ranked[0].score, 70.0);
+        assert!(reason(&ranked[0]).contains("overdue by 2 days (+60)"));
+    }
+}
@@ -0,0 +1,171 @@
+//! Due date reminders.
+//!
+//! The daemon checks the open tasks of every workspace it serves and emits
+//! `task.due_soon` when a task comes due within `reminders.due_soon`, then
+//! `task.overdue` once its due date passes. Each reminder is sent once per
+//! due date, so moving the date sends them again.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::datetime;
+use crate::services::list_all_tasks;
+
+/// Config key for how long before its due date a task counts as due soon
+pub const DUE_SOON_KEY: &str = "reminders.due_soon";
+
+/// Due soon window when `reminders.due_soon` is not set
+pub const DEFAULT_DUE_SOON: &str = "3d";
+
+/// Validate a value for a `reminders.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    if key != DUE_SOON_KEY {
+        return Ok(());
+    }
+    datetime::parse_duration(value).map(|_| ()).ok_or_else(|| {
+        GranaryError::InvalidArgument(format!(
+            "Invalid {} '{}'. Expected a duration such as 3d or 12h",
+            key, value
+        ))
+    })
+}
+
+/// How long before its due date a task counts as due soon
+pub async fn due_soon_window(pool: &SqlitePool) -> Result<Duration> {
+    let value = db::config::get(pool, DUE_SOON_KEY).await?;
+    Ok(value
+        .as_deref()
+        .and_then(datetime::parse_duration)
+        .or_else(|| datetime::parse_duration(DEFAULT_DUE_SOON))
+        .expect("valid default"))
+}
+
+/// Emit `task.due_soon` and `task.overdue` events for open tasks that have
+/// not been reminded about their current due date. Returns the reminders
+/// sent.
+pub async fn run_reminders(pool: &SqlitePool) -> Result<Vec<(EventType, Task)>> {
+    run_reminders_at(pool, Utc::now()).await
+}
+
+async fn run_reminders_at(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<(EventType, Task)>> {
+    let due_soon = now
+        .checked_add_signed(due_soon_window(pool).await?)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let mut sent = Vec::new();
+    for task in list_all_tasks(pool, false).await? {
+        let Some(due_at) = task.due_at.clone() else {
+            continue;
+        };
+        let event_type = if task.is_due_before(now) {
+            EventType::TaskOverdue
+        } else if task.is_due_before(due_soon) {
+            EventType::TaskDueSoon
+        } else {
+            continue;
+        };
+        let kind = event_type.as_str();
+        if db::task_reminders::sent_for(pool, &task.id, &kind).await? == Some(due_at.clone()) {
+            continue;
+        }
+
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: event_type.clone(),
+                entity_type: EntityType::Task,
+                entity_id: task.id.clone(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({
+                    "due_at": due_at,
+                    "title": task.title,
+                    "priority": task.priority,
+                    "owner": task.owner,
+                }),
+            },
+        )
+        .await?;
+        db::task_reminders::record(pool, &task.id, &kind, &due_at).await?;
+        sent.push((event_type, task));
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task, update_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_reminders_sent_once_per_due_date() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Deadlines".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (title, due_in) in [("Late", -1), ("Soon", 1), ("Later", 10)] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    due_at: Some((now + Duration::days(due_in)).to_rfc3339()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        let mut sent: Vec<(String, String)> = run_reminders_at(&pool, now)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(event, task)| (task.title, event.as_str()))
+            .collect();
+        sent.sort();
+        assert_eq!(
+            sent,
+            vec![
+                ("Late".to_string(), "task.overdue".to_string()),
+                ("Soon".to_string(), "task.due_soon".to_string()),
+            ]
+        );
+        assert!(run_reminders_at(&pool, now).await.unwrap().is_empty());
+
+        // A new due date is reminded about again
+        update_task(
+            &pool,
+            &ids[1],
+            UpdateTask {
+                due_at: Some((now + Duration::days(2)).to_rfc3339()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let sent = run_reminders_at(&pool, now).await.unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1.id, ids[1]);
+
+        assert!(validate_config_value(DUE_SOON_KEY, "12h").is_ok());
+        assert!(validate_config_value(DUE_SOON_KEY, "friday").is_err());
+    }
+}
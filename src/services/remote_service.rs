@@ -0,0 +1,696 @@
+//! Workspace sync between machines through a remote.
+//!
+//! `granary push` uploads the row changes recorded in the operation log (see
+//! [`db::operations`]) since the last push as a batch file, and lists it in
+//! the remote's `index.json`. The first push to a remote uploads every row
+//! instead, so a new machine can start from it. `granary pull` applies the
+//! batches it has not seen yet. Pulls are not recorded in the operation
+//! log, so pulled changes are never pushed back and `granary undo` does not
+//! reverse them.
+//!
+//! Conflicts are settled per row, last writer wins: a pulled change is
+//! dropped when the local row changed later, whether by an unpushed local
+//! change or by an earlier push or pull. Only the tables `granary undo`
+//! tracks are synchronised, and credentials in config (keys ending in
+//! `api_key`, `token`, `secret` or `password`) never leave the machine.
+
+use std::collections::HashMap;
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::db::remotes::LocalChange;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::remote_store::{RemoteStore, normalize_url};
+
+/// Remote used when none is named and the workspace has several
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// File listing the batches on a remote
+const INDEX_FILE: &str = "index.json";
+
+/// Attempts to add a batch to the index when another machine pushes at the
+/// same moment
+const INDEX_ATTEMPTS: usize = 3;
+
+/// Config key endings that hold credentials
+const SECRET_SUFFIXES: &[&str] = &["api_key", "token", "secret", "password"];
+
+/// Add a remote, named `origin` unless another name is given
+pub async fn add_remote(pool: &SqlitePool, url: &str, name: Option<&str>) -> Result<Remote> {
+    let name = name.unwrap_or(DEFAULT_REMOTE);
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Invalid remote name: {}. Use letters, digits, - and _",
+            name
+        )));
+    }
+    if db::remotes::get(pool, name).await?.is_some() {
+        return Err(GranaryError::Conflict(format!(
+            "Remote {} already exists",
+            name
+        )));
+    }
+    let url = normalize_url(url)?;
+    db::remotes::create(pool, name, &url).await
+}
+
+pub async fn remove_remote(pool: &SqlitePool, name: &str) -> Result<()> {
+    if !db::remotes::delete(pool, name).await? {
+        return Err(GranaryError::InvalidArgument(format!(
+            "No remote named {}",
+            name
+        )));
+    }
+    Ok(())
+}
+
+pub async fn list_remotes(pool: &SqlitePool) -> Result<Vec<Remote>> {
+    db::remotes::list(pool).await
+}
+
+/// The named remote, or else the only remote, or else `origin`
+pub async fn resolve_remote(pool: &SqlitePool, name: Option<&str>) -> Result<Remote> {
+    if let Some(name) = name {
+        return db::remotes::get(pool, name)
+            .await?
+            .ok_or_else(|| GranaryError::InvalidArgument(format!("No remote named {}", name)));
+    }
+    let mut remotes = db::remotes::list(pool).await?;
+    match remotes.len() {
+        0 => Err(GranaryError::InvalidArgument(
+            "No remote configured. Add one with: granary remote add <url>".to_string(),
+        )),
+        1 => Ok(remotes.remove(0)),
+        _ => remotes
+            .into_iter()
+            .find(|r| r.name == DEFAULT_REMOTE)
+            .ok_or_else(|| {
+                GranaryError::InvalidArgument(
+                    "Several remotes are configured; name one with --remote".to_string(),
+                )
+            }),
+    }
+}
+
+/// Upload local changes made since the last push
+pub async fn push(pool: &SqlitePool, remote: &Remote) -> Result<RemoteSyncReport> {
+    let store = RemoteStore::open(&remote.url)?;
+    // The push covers the changes up to `pushed_change_id`: those read here,
+    // or for a snapshot, those recorded before it was read. A change still
+    // being recorded is left for the next push.
+    let (changes, pushed_change_id) = if remote.pushed_at.is_none() {
+        let last_change_id = db::remotes::last_change_id(pool).await?;
+        (snapshot(pool).await?, last_change_id)
+    } else {
+        let local = db::remotes::changes_since(pool, remote.pushed_change_id).await?;
+        let pushed_change_id = local.last().map_or(remote.pushed_change_id, |c| c.id);
+        (to_row_changes(pool, &local).await?, pushed_change_id)
+    };
+
+    let mut report = RemoteSyncReport {
+        remote: remote.name.clone(),
+        direction: SyncDirection::Push,
+        batches: Vec::new(),
+        changes: changes.len(),
+        skipped: 0,
+    };
+    if changes.is_empty() {
+        db::remotes::set_pushed(pool, &remote.name, pushed_change_id).await?;
+        return Ok(report);
+    }
+
+    let name = batch_name();
+    let batch = ChangeBatch {
+        created_at: chrono::Utc::now().to_rfc3339(),
+        changes,
+    };
+    store
+        .put(&batch_path(&name), serde_json::to_vec(&batch)?)
+        .await?;
+    append_to_index(&store, &name).await?;
+
+    let mut tx = pool.begin().await?;
+    for change in &batch.changes {
+        advance_clock(&mut tx, change).await?;
+    }
+    db::remotes::add_batch(&mut *tx, &remote.name, &name).await?;
+    db::remotes::set_pushed(&mut *tx, &remote.name, pushed_change_id).await?;
+    tx.commit().await?;
+
+    report.batches.push(name);
+    Ok(report)
+}
+
+/// Apply the batches on the remote that have not been pulled yet
+pub async fn pull(pool: &SqlitePool, remote: &Remote) -> Result<RemoteSyncReport> {
+    let store = RemoteStore::open(&remote.url)?;
+    let index = read_index(&store).await?;
+    let known = db::remotes::known_batches(pool, &remote.name).await?;
+
+    let mut batches = Vec::new();
+    for name in index.batches.iter().filter(|b| !known.contains(*b)) {
+        let bytes = store.get(&batch_path(name)).await?.ok_or_else(|| {
+            GranaryError::Conflict(format!(
+                "Batch {} is listed but missing on the remote",
+                name
+            ))
+        })?;
+        let batch: ChangeBatch = serde_json::from_slice(&bytes)?;
+        batches.push((name.clone(), batch));
+    }
+
+    // Local changes not pushed yet count as the latest state of their rows
+    let mut unpushed: HashMap<(String, String), String> = HashMap::new();
+    let local = db::remotes::changes_since(pool, remote.pushed_change_id).await?;
+    for change in to_row_changes(pool, &local).await? {
+        unpushed.insert((change.table.clone(), change.row_key()), change.changed_at);
+    }
+
+    let mut report = RemoteSyncReport {
+        remote: remote.name.clone(),
+        direction: SyncDirection::Pull,
+        batches: Vec::new(),
+        changes: 0,
+        skipped: 0,
+    };
+    let mut tx = pool.begin().await?;
+    sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await?;
+    for (name, batch) in &batches {
+        for change in &batch.changes {
+            if !db::operations::TRACKED_TABLES.contains(&change.table.as_str()) {
+                return Err(GranaryError::Conflict(format!(
+                    "Batch {} changes table {}, which is not synchronised",
+                    name, change.table
+                )));
+            }
+            let row_key = change.row_key();
+            let recorded = db::remotes::clock(&mut tx, &change.table, &row_key).await?;
+            let local = unpushed
+                .get(&(change.table.clone(), row_key))
+                .into_iter()
+                .chain(recorded.as_ref())
+                .max_by(compare_times);
+            if let Some(local) = local {
+                match compare_times(&local, &&change.changed_at) {
+                    // The same change, pushed again in a snapshot
+                    std::cmp::Ordering::Equal => continue,
+                    std::cmp::Ordering::Greater => {
+                        report.skipped += 1;
+                        continue;
+                    }
+                    std::cmp::Ordering::Less => {}
+                }
+            }
+            db::remotes::apply(&mut tx, change).await?;
+            advance_clock(&mut tx, change).await?;
+            report.changes += 1;
+        }
+        db::remotes::add_batch(&mut *tx, &remote.name, name).await?;
+        report.batches.push(name.clone());
+    }
+    db::counters::catch_up(&mut tx).await?;
+    db::remotes::set_pulled(&mut *tx, &remote.name).await?;
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+/// How far each remote is ahead of or behind the workspace
+pub async fn status(pool: &SqlitePool) -> Result<Vec<RemoteStatus>> {
+    let mut statuses = Vec::new();
+    for remote in db::remotes::list(pool).await? {
+        let unpushed = if remote.pushed_at.is_none() {
+            None
+        } else {
+            let local = db::remotes::changes_since(pool, remote.pushed_change_id).await?;
+            Some(to_row_changes(pool, &local).await?.len())
+        };
+        let (unpulled, error) = match unpulled_batches(pool, &remote).await {
+            Ok(count) => (Some(count), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        statuses.push(RemoteStatus {
+            name: remote.name,
+            url: remote.url,
+            unpushed,
+            unpulled,
+            pushed_at: remote.pushed_at,
+            pulled_at: remote.pulled_at,
+            error,
+        });
+    }
+    Ok(statuses)
+}
+
+async fn unpulled_batches(pool: &SqlitePool, remote: &Remote) -> Result<usize> {
+    let store = RemoteStore::open(&remote.url)?;
+    let index = read_index(&store).await?;
+    let known = db::remotes::known_batches(pool, &remote.name).await?;
+    Ok(index.batches.iter().filter(|b| !known.contains(*b)).count())
+}
+
+/// Every row of the synchronised tables, dated by its own update time
+async fn snapshot(pool: &SqlitePool) -> Result<Vec<RowChange>> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut changes = Vec::new();
+    for table in db::operations::TRACKED_TABLES {
+        let keys = db::remotes::key_columns(pool, table).await?;
+        if keys.is_empty() {
+            continue;
+        }
+        for row in db::export::dump_table(pool, table).await? {
+            let changed_at = ["updated_at", "created_at"]
+                .iter()
+                .find_map(|c| row.get(*c).and_then(|v| v.as_str()))
+                .unwrap_or(&now)
+                .to_string();
+            changes.push(RowChange {
+                table: table.to_string(),
+                key: keys
+                    .iter()
+                    .map(|k| (k.clone(), row.get(k).cloned().unwrap_or_default()))
+                    .collect(),
+                row: Some(row),
+                changed_at,
+            });
+        }
+    }
+    changes.retain(|c| !is_secret(c));
+    Ok(changes)
+}
+
+/// Turn operation log entries into row changes
+async fn to_row_changes(pool: &SqlitePool, local: &[LocalChange]) -> Result<Vec<RowChange>> {
+    let mut keys: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut changes = Vec::new();
+    for change in local {
+        let table = change.table_name.as_str();
+        if !db::operations::TRACKED_TABLES.contains(&table) {
+            continue;
+        }
+        let Some(snapshot) = change.snapshot()? else {
+            continue;
+        };
+        if !keys.contains_key(table) {
+            keys.insert(table, db::remotes::key_columns(pool, table).await?);
+        }
+        let key = keys[table]
+            .iter()
+            .map(|k| (k.clone(), snapshot.get(k).cloned().unwrap_or_default()))
+            .collect();
+        changes.push(RowChange {
+            table: table.to_string(),
+            key,
+            row: (!change.is_delete()).then_some(snapshot),
+            changed_at: change.changed_at.clone(),
+        });
+    }
+    changes.retain(|c| !is_secret(c));
+    Ok(changes)
+}
+
+/// Whether a change is to a config key holding a credential
+fn is_secret(change: &RowChange) -> bool {
+    change.table == "config"
+        && change
+            .key
+            .get("key")
+            .and_then(|k| k.as_str())
+            .is_some_and(|key| SECRET_SUFFIXES.iter().any(|s| key.ends_with(s)))
+}
+
+/// Record a change as the latest state of its row, unless a later one is
+/// already recorded
+async fn advance_clock(conn: &mut sqlx::SqliteConnection, change: &RowChange) -> Result<()> {
+    let row_key = change.row_key();
+    if let Some(current) = db::remotes::clock(conn, &change.table, &row_key).await?
+        && ConflictStrategy::Newest.resolve(&current, &change.changed_at) == SyncDirection::Push
+    {
+        return Ok(());
+    }
+    db::remotes::set_clock(conn, &change.table, &row_key, &change.changed_at).await
+}
+
+/// Order RFC 3339 times so that the later one compares greater
+fn compare_times(a: &&String, b: &&String) -> std::cmp::Ordering {
+    if ConflictStrategy::Newest.resolve(a, b) == SyncDirection::Pull {
+        std::cmp::Ordering::Less
+    } else if ConflictStrategy::Newest.resolve(b, a) == SyncDirection::Pull {
+        std::cmp::Ordering::Greater
+    } else {
+        std::cmp::Ordering::Equal
+    }
+}
+
+/// Read the remote's index. Batch names become paths on the remote, so a
+/// name that isn't a plain identifier is refused.
+async fn read_index(store: &RemoteStore) -> Result<RemoteIndex> {
+    let index: RemoteIndex = match store.get(INDEX_FILE).await? {
+        Some(bytes) => serde_json::from_slice(&bytes)?,
+        None => return Ok(RemoteIndex::default()),
+    };
+    if let Some(name) = index.batches.iter().find(|name| !is_batch_name(name)) {
+        return Err(GranaryError::Conflict(format!(
+            "The remote index lists an invalid batch name: {:?}",
+            name
+        )));
+    }
+    Ok(index)
+}
+
+/// Whether a name from the index is a plain identifier, as [`batch_name`]
+/// makes them
+fn is_batch_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Add a batch to the index, checking that a concurrent push from another
+/// machine did not overwrite it
+async fn append_to_index(store: &RemoteStore, name: &str) -> Result<()> {
+    for _ in 0..INDEX_ATTEMPTS {
+        let mut index = read_index(store).await?;
+        if !index.batches.iter().any(|b| b == name) {
+            index.batches.push(name.to_string());
+            store
+                .put(INDEX_FILE, serde_json::to_vec_pretty(&index)?)
+                .await?;
+        }
+        if read_index(store).await?.batches.iter().any(|b| b == name) {
+            return Ok(());
+        }
+    }
+    Err(GranaryError::Conflict(format!(
+        "Could not add batch {} to the remote index; try pushing again",
+        name
+    )))
+}
+
+fn batch_path(name: &str) -> String {
+    format!("batches/{}.json", name)
+}
+
+/// Batch names sort by push time: e.g. "20260214T093000123Z-k3f9"
+fn batch_name() -> String {
+    format!(
+        "{}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ"),
+        generate_suffix(4)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task, get_task};
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, Workspace, SqlitePool) {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        (temp_dir, workspace, pool)
+    }
+
+    /// Log a change the way a recorded command would
+    async fn record(pool: &SqlitePool, table: &str, before: &str, after: &str, at: &str) {
+        let operation_id = format!("op-{}", generate_suffix(4));
+        sqlx::query(
+            "INSERT INTO operation_changes (operation_id, table_name, action, before, after) VALUES (?, ?, 'update', ?, ?)",
+        )
+        .bind(&operation_id)
+        .bind(table)
+        .bind(before)
+        .bind(after)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO operations (id, command, created_at) VALUES (?, 'task', ?)")
+            .bind(&operation_id)
+            .bind(at)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    /// A time after every row created by the test
+    fn hours_from_now(hours: i64) -> String {
+        (chrono::Utc::now() + chrono::Duration::hours(hours)).to_rfc3339()
+    }
+
+    async fn set_title(pool: &SqlitePool, id: &str, title: &str, at: &str) {
+        let before = db::export::dump_table(pool, "tasks")
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|r| r["id"] == id)
+            .unwrap();
+        sqlx::query("UPDATE tasks SET title = ?, updated_at = ? WHERE id = ?")
+            .bind(title)
+            .bind(at)
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+        let mut after = before.clone();
+        after.insert("title".to_string(), title.into());
+        after.insert("updated_at".to_string(), at.into());
+        record(
+            pool,
+            "tasks",
+            &serde_json::to_string(&before).unwrap(),
+            &serde_json::to_string(&after).unwrap(),
+            at,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_push_and_pull_between_workspaces() {
+        let remote_dir = TempDir::new().unwrap();
+        let url = remote_dir.path().display().to_string();
+        let (_a_dir, _a, a) = setup().await;
+        let (_b_dir, _b, b) = setup().await;
+        let a_remote = add_remote(&a, &url, None).await.unwrap();
+        let b_remote = add_remote(&b, &url, None).await.unwrap();
+
+        let project = create_project(
+            &a,
+            CreateProject {
+                name: "Shared".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &a,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Write docs".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        db::config::set(&a, "sync.linear.api_key", "lin_secret")
+            .await
+            .unwrap();
+
+        // The first push sends everything
+        let pushed = push(&a, &a_remote).await.unwrap();
+        assert_eq!(pushed.batches.len(), 1);
+        let pulled = pull(&b, &b_remote).await.unwrap();
+        assert_eq!(pulled.batches, pushed.batches);
+        assert_eq!(get_task(&b, &task.id).await.unwrap().title, "Write docs");
+        assert!(
+            db::config::get(&b, "sync.linear.api_key")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Counters catch up, so B's next task gets a new number
+        let next = create_task(
+            &b,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Review".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(next.task_number, 2);
+
+        // Pulling again finds nothing new, and A never re-applies its own batch
+        assert!(pull(&b, &b_remote).await.unwrap().batches.is_empty());
+        let a_remote = resolve_remote(&a, None).await.unwrap();
+        assert!(pull(&a, &a_remote).await.unwrap().batches.is_empty());
+
+        // Later changes travel through the operation log
+        set_title(&a, &task.id, "Write better docs", &hours_from_now(1)).await;
+        let statuses = status(&a).await.unwrap();
+        assert_eq!(statuses[0].unpushed, Some(1));
+        push(&a, &a_remote).await.unwrap();
+        let b_remote = resolve_remote(&b, None).await.unwrap();
+        assert_eq!(status(&b).await.unwrap()[0].unpulled, Some(1));
+        let pulled = pull(&b, &b_remote).await.unwrap();
+        assert_eq!((pulled.changes, pulled.skipped), (1, 0));
+        assert_eq!(
+            get_task(&b, &task.id).await.unwrap().title,
+            "Write better docs"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_keeps_later_local_changes() {
+        let remote_dir = TempDir::new().unwrap();
+        let url = remote_dir.path().display().to_string();
+        let (_a_dir, _a, a) = setup().await;
+        let (_b_dir, _b, b) = setup().await;
+        add_remote(&a, &url, None).await.unwrap();
+        add_remote(&b, &url, None).await.unwrap();
+
+        let project = create_project(
+            &a,
+            CreateProject {
+                name: "Shared".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &a,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Original".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        push(&a, &resolve_remote(&a, None).await.unwrap())
+            .await
+            .unwrap();
+        pull(&b, &resolve_remote(&b, None).await.unwrap())
+            .await
+            .unwrap();
+        push(&b, &resolve_remote(&b, None).await.unwrap())
+            .await
+            .unwrap();
+
+        // Both edit the task; B's edit is later
+        set_title(&a, &task.id, "From A", &hours_from_now(1)).await;
+        set_title(&b, &task.id, "From B", &hours_from_now(2)).await;
+        push(&a, &resolve_remote(&a, None).await.unwrap())
+            .await
+            .unwrap();
+
+        let pulled = pull(&b, &resolve_remote(&b, None).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(pulled.skipped, 1);
+        assert_eq!(get_task(&b, &task.id).await.unwrap().title, "From B");
+
+        push(&b, &resolve_remote(&b, None).await.unwrap())
+            .await
+            .unwrap();
+        pull(&a, &resolve_remote(&a, None).await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(get_task(&a, &task.id).await.unwrap().title, "From B");
+    }
+
+    #[tokio::test]
+    async fn test_push_leaves_changes_still_being_recorded() {
+        let remote_dir = TempDir::new().unwrap();
+        let url = remote_dir.path().display().to_string();
+        let (_a_dir, _a, a) = setup().await;
+        let (_b_dir, _b, b) = setup().await;
+        add_remote(&a, &url, None).await.unwrap();
+        add_remote(&b, &url, None).await.unwrap();
+
+        let project = create_project(
+            &a,
+            CreateProject {
+                name: "Shared".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &a,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Original".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        push(&a, &resolve_remote(&a, None).await.unwrap())
+            .await
+            .unwrap();
+        pull(&b, &resolve_remote(&b, None).await.unwrap())
+            .await
+            .unwrap();
+
+        // A later change whose operation row isn't written yet is not pushed,
+        // and the cursor stays before it
+        set_title(&a, &task.id, "Pushed", &hours_from_now(1)).await;
+        let at = hours_from_now(2);
+        sqlx::query(
+            "INSERT INTO operation_changes (operation_id, table_name, action, before, after) VALUES ('op-late', 'tasks', 'update', '{}', ?)",
+        )
+        .bind(serde_json::json!({ "id": task.id, "title": "Late", "updated_at": at }).to_string())
+        .execute(&a)
+        .await
+        .unwrap();
+        push(&a, &resolve_remote(&a, None).await.unwrap())
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "INSERT INTO operations (id, command, created_at) VALUES ('op-late', 'task', ?)",
+        )
+        .bind(&at)
+        .execute(&a)
+        .await
+        .unwrap();
+        assert_eq!(status(&a).await.unwrap()[0].unpushed, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_pull_refuses_batch_names_that_are_not_identifiers() {
+        let remote_dir = TempDir::new().unwrap();
+        let url = remote_dir.path().display().to_string();
+        let (_dir, _workspace, pool) = setup().await;
+        let remote = add_remote(&pool, &url, None).await.unwrap();
+
+        std::fs::write(
+            remote_dir.path().join(INDEX_FILE),
+            r#"{"batches": ["../../outside"]}"#,
+        )
+        .unwrap();
+        let err = pull(&pool, &remote).await.unwrap_err();
+        assert!(err.to_string().contains("invalid batch name"));
+
+        assert!(is_batch_name("20260214T093000123Z-k3f9"));
+        assert!(!is_batch_name(""));
+        assert!(!is_batch_name("a/b"));
+        assert!(!is_batch_name("a\\b"));
+        assert!(!is_batch_name(".."));
+    }
+}
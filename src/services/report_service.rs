@@ -0,0 +1,909 @@
+//! Reports built from workspace history.
+//!
+//! A burndown charts a project's remaining estimate over time. Every
+//! checkpoint records the total and remaining estimate of each project with
+//! estimated tasks, so the chart has one point per checkpoint that saw the
+//! project, across all sessions, followed by the current state.
+//!
+//! A timeline lays a project's tasks out between their start and due dates.
+//! Tasks come after the tasks they depend on, so the chart reads as the
+//! order the work has to happen in.
+//!
+//! Velocity buckets completed tasks, and their estimates, into weeks from
+//! the task status history, with a trend comparing the later complete weeks
+//! to the earlier ones.
+//!
+//! A cost report adds up the tokens and cost AI runners reported on
+//! `GRANARY_USAGE` lines, by worker and by the task or project each run's
+//! event was about.
+//!
+//! Project health summarizes completion, blocked work, the age of open
+//! tasks, how many tasks were done in the last week, and how many runs of
+//! the workspace's workers failed on the project's tasks in that week.
+
+use std::collections::{BTreeSet, HashMap};
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::run::Run;
+use crate::models::*;
+use crate::services::datetime::{self, DateSettings};
+use crate::services::global_config as global_config_service;
+use crate::services::{Workspace, get_project};
+
+/// Days of history behind throughput and failing run counts
+pub const HEALTH_WINDOW_DAYS: i64 = 7;
+
+/// Remaining estimate of a project at each checkpoint and now
+pub async fn burndown(pool: &SqlitePool, project_id: &str) -> Result<Burndown> {
+    let project = get_project(pool, project_id).await?;
+
+    let mut points = Vec::new();
+    for checkpoint in db::checkpoints::list_all(pool).await? {
+        let Ok(snapshot) = serde_json::from_str::<SessionSnapshot>(&checkpoint.snapshot) else {
+            continue;
+        };
+        if let Some(estimate) = snapshot
+            .estimates
+            .into_iter()
+            .find(|e| e.project_id == project.id)
+        {
+            points.push(BurndownPoint {
+                at: checkpoint.created_at,
+                checkpoint: Some(checkpoint.name),
+                total: estimate.total,
+                remaining: estimate.remaining,
+            });
+        }
+    }
+
+    let now = db::tasks::estimate_totals(pool)
+        .await?
+        .into_iter()
+        .find(|e| e.project_id == project.id);
+    points.push(BurndownPoint {
+        at: chrono::Utc::now().to_rfc3339(),
+        checkpoint: None,
+        total: now.as_ref().map_or(0.0, |e| e.total),
+        remaining: now.as_ref().map_or(0.0, |e| e.remaining),
+    });
+
+    Ok(Burndown {
+        project_id: project.id,
+        project_name: project.name,
+        points,
+    })
+}
+
+/// A project's dated tasks in dependency order
+pub async fn timeline(pool: &SqlitePool, project_id: &str) -> Result<Timeline> {
+    let project = get_project(pool, project_id).await?;
+    let tasks = db::tasks::list_by_project(pool, &project.id, false).await?;
+    let edges = db::dependencies::list_by_project(pool, &project.id).await?;
+
+    let mut depends_on: HashMap<&str, Vec<String>> = HashMap::new();
+    for edge in &edges {
+        depends_on
+            .entry(edge.task_id.as_str())
+            .or_default()
+            .push(edge.depends_on_task_id.clone());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut undated = 0;
+    let mut entries = Vec::new();
+    for task in dependency_order(&tasks, &edges) {
+        let Some(start) = task.started_at.as_ref().or(task.due_at.as_ref()) else {
+            undated += 1;
+            continue;
+        };
+        let end = task
+            .completed_at
+            .as_ref()
+            .or(task.due_at.as_ref())
+            .unwrap_or(&now);
+        // A task completed or due before it started still spans its start
+        let end = if parse(end) < parse(start) {
+            start
+        } else {
+            end
+        };
+        entries.push(TimelineTask {
+            id: task.id.clone(),
+            title: task.title.clone(),
+            status: task.status.clone(),
+            start: start.clone(),
+            end: end.clone(),
+            milestone: task.started_at.is_none(),
+            depends_on: depends_on.remove(task.id.as_str()).unwrap_or_default(),
+        });
+    }
+
+    Ok(Timeline {
+        project_id: project.id,
+        project_name: project.name,
+        tasks: entries,
+        undated,
+    })
+}
+
+fn parse(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok()
+}
+
+/// Order tasks so each comes after the tasks it depends on, taking the
+/// earliest start (then task number) among those ready. Tasks left in a
+/// cycle go last.
+fn dependency_order<'a>(tasks: &'a [Task], edges: &[TaskDependency]) -> Vec<&'a Task> {
+    let index: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .map(|(i, task)| (task.id.as_str(), i))
+        .collect();
+    let mut waiting = vec![0; tasks.len()];
+    let mut dependents = vec![Vec::new(); tasks.len()];
+    for edge in edges {
+        if let (Some(&task), Some(&dep)) = (
+            index.get(edge.task_id.as_str()),
+            index.get(edge.depends_on_task_id.as_str()),
+        ) {
+            waiting[task] += 1;
+            dependents[dep].push(task);
+        }
+    }
+
+    let key = |i: usize| {
+        let task = &tasks[i];
+        let start = task
+            .started_at
+            .as_ref()
+            .or(task.due_at.as_ref())
+            .and_then(|s| parse(s));
+        (start.is_none(), start, task.task_number, i)
+    };
+    let mut ready: BTreeSet<_> = (0..tasks.len())
+        .filter(|&i| waiting[i] == 0)
+        .map(key)
+        .collect();
+    let mut ordered = Vec::with_capacity(tasks.len());
+    let mut placed = vec![false; tasks.len()];
+    while let Some((_, _, _, i)) = ready.pop_first() {
+        ordered.push(&tasks[i]);
+        placed[i] = true;
+        for &next in &dependents[i] {
+            waiting[next] -= 1;
+            if waiting[next] == 0 {
+                ready.insert(key(next));
+            }
+        }
+    }
+    ordered.extend(tasks.iter().zip(placed).filter(|(_, p)| !p).map(|(t, _)| t));
+    ordered
+}
+
+/// Completed tasks per week over the last `weeks` weeks, including this one
+pub async fn velocity(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+    weeks: usize,
+    settings: &DateSettings,
+) -> Result<Velocity> {
+    velocity_at(pool, project_id, weeks, settings, settings.today()).await
+}
+
+/// Like [`velocity`], with an explicit "today" for deterministic results
+pub async fn velocity_at(
+    pool: &SqlitePool,
+    project_id: Option<&str>,
+    weeks: usize,
+    settings: &DateSettings,
+    today: NaiveDate,
+) -> Result<Velocity> {
+    if let Some(id) = project_id {
+        get_project(pool, id).await?;
+    }
+    let weeks = weeks.max(1);
+    let this_week = settings.start_of_week(today);
+    let first_week = this_week - chrono::Duration::weeks(weeks as i64 - 1);
+
+    let mut buckets: Vec<VelocityWeek> = (0..weeks)
+        .map(|i| VelocityWeek {
+            week_start: (first_week + chrono::Duration::weeks(i as i64))
+                .format("%Y-%m-%d")
+                .to_string(),
+            completed: 0,
+            estimate: 0.0,
+            partial: i == weeks - 1,
+        })
+        .collect();
+
+    // Widen the query by a day either side of the local week boundary
+    let since = (first_week - chrono::Duration::days(1))
+        .format("%Y-%m-%d")
+        .to_string();
+    for (_, completed_at, estimate) in
+        db::status_history::completions_since(pool, project_id, &since).await?
+    {
+        let Ok(at) = DateTime::parse_from_rfc3339(&completed_at) else {
+            continue;
+        };
+        let local = at.with_timezone(&settings.timezone).date_naive();
+        let offset = (settings.start_of_week(local) - first_week).num_weeks();
+        if let Some(bucket) = usize::try_from(offset)
+            .ok()
+            .and_then(|i| buckets.get_mut(i))
+        {
+            bucket.completed += 1;
+            bucket.estimate += estimate.unwrap_or(0.0);
+        }
+    }
+
+    let trend = velocity_trend(&buckets[..buckets.len() - 1]);
+    Ok(Velocity {
+        project_id: project_id.map(str::to_string),
+        weeks: buckets,
+        trend,
+    })
+}
+
+/// Compare the average completions of the later half of `weeks` with the
+/// earlier half; a change of more than 10% is a trend
+fn velocity_trend(weeks: &[VelocityWeek]) -> Option<VelocityTrend> {
+    if weeks.len() < 2 {
+        return None;
+    }
+    let (earlier, later) = weeks.split_at(weeks.len() / 2);
+    let average =
+        |w: &[VelocityWeek]| w.iter().map(|w| w.completed as f64).sum::<f64>() / w.len() as f64;
+    let (earlier, later) = (average(earlier), average(later));
+    Some(if later > earlier * 1.1 {
+        VelocityTrend::Increasing
+    } else if later < earlier * 0.9 {
+        VelocityTrend::Dropping
+    } else {
+        VelocityTrend::Steady
+    })
+}
+
+/// Parse a `--window` such as `4w` or `30d` into whole weeks, rounding up
+pub fn parse_velocity_window(input: &str) -> Result<usize> {
+    datetime::parse_duration(input)
+        .filter(|d| d.num_seconds() > 0)
+        .map(|d| ((d.num_days() + 6) / 7).max(1) as usize)
+        .ok_or_else(|| {
+            GranaryError::InvalidArgument(format!("Invalid window: {} (use e.g. 4w or 30d)", input))
+        })
+}
+
+/// Health metrics for each of `projects`
+pub async fn project_health(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    projects: &[Project],
+) -> Result<Vec<ProjectHealth>> {
+    let now = chrono::Utc::now();
+    let since = (now - chrono::Duration::days(HEALTH_WINDOW_DAYS)).to_rfc3339();
+    let failing = failing_runs(workspace, &since).await?;
+
+    let mut health = Vec::new();
+    for project in projects {
+        let tasks = db::tasks::list_by_project(pool, &project.id, false).await?;
+        health.push(health_of(project, &tasks, &failing, now));
+    }
+    Ok(health)
+}
+
+/// Token usage and cost of the workspace's runs created since `since`
+pub async fn costs(pool: &SqlitePool, workspace: &Workspace, since: &str) -> Result<CostReport> {
+    // Runs live in the global database, which is not created just to find none
+    if !global_config_service::global_db_path()?.exists() {
+        return cost_report(pool, since, &[], &HashMap::new()).await;
+    }
+    let global = global_config_service::global_pool().await?;
+    let runs = db::runs::list_with_token_usage_for_instance(
+        &global,
+        &workspace.root.to_string_lossy(),
+        since,
+    )
+    .await?;
+    let mut worker_names = HashMap::new();
+    for run in &runs {
+        if worker_names.contains_key(&run.worker_id) {
+            continue;
+        }
+        if let Some(worker) = db::workers::get(&global, &run.worker_id).await? {
+            worker_names.insert(
+                worker.id.clone(),
+                worker.runner_name.unwrap_or(worker.command),
+            );
+        }
+    }
+    cost_report(pool, since, &runs, &worker_names).await
+}
+
+/// Group runs' usage by worker, and by the task or project their event was
+/// about. Runs about other entities only count towards their worker.
+async fn cost_report(
+    pool: &SqlitePool,
+    since: &str,
+    runs: &[Run],
+    worker_names: &HashMap<String, String>,
+) -> Result<CostReport> {
+    let mut total = CostTotals::default();
+    let mut workers: HashMap<String, CostGroup> = HashMap::new();
+    let mut projects: HashMap<String, CostGroup> = HashMap::new();
+    let mut tasks: HashMap<String, CostGroup> = HashMap::new();
+    let group = |id: &str, name: &str| CostGroup {
+        id: id.to_string(),
+        name: name.to_string(),
+        totals: CostTotals::default(),
+    };
+
+    // What each run's event was about: a task and its project, or a project
+    let mut entities: HashMap<String, (Option<Task>, Option<Project>)> = HashMap::new();
+    for run in runs {
+        if !entities.contains_key(&run.entity_id) {
+            let task = db::tasks::get(pool, &run.entity_id).await?;
+            let project_id = task
+                .as_ref()
+                .map_or(run.entity_id.as_str(), |t| t.project_id.as_str());
+            let project = db::projects::get(pool, project_id).await?;
+            entities.insert(run.entity_id.clone(), (task, project));
+        }
+
+        total.add_run(run);
+        let name = worker_names.get(&run.worker_id).map_or("", String::as_str);
+        workers
+            .entry(run.worker_id.clone())
+            .or_insert_with(|| group(&run.worker_id, name))
+            .totals
+            .add_run(run);
+        let (task, project) = &entities[&run.entity_id];
+        if let Some(task) = task {
+            tasks
+                .entry(task.id.clone())
+                .or_insert_with(|| group(&task.id, &task.title))
+                .totals
+                .add_run(run);
+        }
+        if let Some(project) = project {
+            projects
+                .entry(project.id.clone())
+                .or_insert_with(|| group(&project.id, &project.name))
+                .totals
+                .add_run(run);
+        }
+    }
+
+    Ok(CostReport {
+        since: since.to_string(),
+        total,
+        by_worker: most_expensive_first(workers),
+        by_project: most_expensive_first(projects),
+        by_task: most_expensive_first(tasks),
+    })
+}
+
+fn most_expensive_first(groups: HashMap<String, CostGroup>) -> Vec<CostGroup> {
+    let mut groups: Vec<CostGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        let tokens = |g: &CostGroup| g.totals.input_tokens + g.totals.output_tokens;
+        b.totals
+            .cost_usd
+            .total_cmp(&a.totals.cost_usd)
+            .then_with(|| tokens(b).cmp(&tokens(a)))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    groups
+}
+
+/// Failing runs of the workspace's workers since `since`. Workers live in
+/// the global database, which is not created just to find none.
+async fn failing_runs(workspace: &Workspace, since: &str) -> Result<Vec<Run>> {
+    if !global_config_service::global_db_path()?.exists() {
+        return Ok(Vec::new());
+    }
+    let global = global_config_service::global_pool().await?;
+    db::runs::list_failing_for_instance(&global, &workspace.root.to_string_lossy(), since).await
+}
+
+fn health_of(
+    project: &Project,
+    tasks: &[Task],
+    failing: &[Run],
+    now: chrono::DateTime<chrono::Utc>,
+) -> ProjectHealth {
+    let since = now - chrono::Duration::days(HEALTH_WINDOW_DAYS);
+    let parse = |ts: &str| DateTime::parse_from_rfc3339(ts).ok();
+
+    let done = tasks.iter().filter(|t| t.status == "done").count();
+    let open: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| !t.status_enum().is_terminal())
+        .collect();
+    let blocked = open
+        .iter()
+        .filter(|t| t.status == "blocked" || t.blocked_reason.is_some())
+        .count();
+    let ages: Vec<f64> = open
+        .iter()
+        .filter_map(|t| parse(&t.created_at))
+        .map(|created| (now - created.with_timezone(&chrono::Utc)).num_seconds() as f64 / 86400.0)
+        .collect();
+    let throughput = tasks
+        .iter()
+        .filter(|t| t.status == "done")
+        // Tasks set done by an update rather than completed have no completed_at
+        .filter_map(|t| parse(t.completed_at.as_deref().unwrap_or(&t.updated_at)))
+        .filter(|completed| *completed >= since)
+        .count();
+    let task_ids: BTreeSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+    let failing_runs = failing
+        .iter()
+        .filter(|r| r.entity_id == project.id || task_ids.contains(r.entity_id.as_str()))
+        .count();
+
+    ProjectHealth {
+        project_id: project.id.clone(),
+        project_name: project.name.clone(),
+        total_tasks: tasks.len(),
+        done_tasks: done,
+        percent_complete: ratio(done, tasks.len()) * 100.0,
+        blocked_tasks: blocked,
+        blocked_ratio: ratio(blocked, open.len()),
+        avg_open_task_age_days: (!ages.is_empty())
+            .then(|| ages.iter().sum::<f64>() / ages.len() as f64),
+        throughput,
+        failing_runs,
+        window_days: HEALTH_WINDOW_DAYS,
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{
+        Workspace, add_dependency, create_checkpoint, create_project, create_session, create_task,
+        start_task, update_task,
+    };
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_burndown_from_checkpoints() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Sprint".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut tasks = Vec::new();
+        for estimate in [Some(3.0), Some(5.0), None] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: "Task".to_string(),
+                    estimate,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            tasks.push(task);
+        }
+        let session = create_session(
+            &pool,
+            CreateSession {
+                name: Some("Sprint 1".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        create_checkpoint(&pool, &session.id, "start")
+            .await
+            .unwrap();
+        update_task(
+            &pool,
+            &tasks[1].id,
+            UpdateTask {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        create_checkpoint(&pool, &session.id, "day-2")
+            .await
+            .unwrap();
+        update_task(
+            &pool,
+            &tasks[2].id,
+            UpdateTask {
+                estimate: Some(2.0),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let burndown = burndown(&pool, &project.id).await.unwrap();
+        let points: Vec<(Option<&str>, f64, f64)> = burndown
+            .points
+            .iter()
+            .map(|p| (p.checkpoint.as_deref(), p.total, p.remaining))
+            .collect();
+        assert_eq!(
+            points,
+            vec![
+                (Some("start"), 8.0, 8.0),
+                (Some("day-2"), 8.0, 3.0),
+                (None, 10.0, 5.0),
+            ]
+        );
+
+        assert!(matches!(
+            update_task(
+                &pool,
+                &tasks[0].id,
+                UpdateTask {
+                    estimate: Some(-1.0),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await,
+            Err(crate::error::GranaryError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_timeline_follows_dependencies() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Launch".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut tasks = Vec::new();
+        for (title, due) in [
+            ("Ship", Some("2026-03-10T00:00:00+00:00")),
+            ("Build", Some("2026-03-20T00:00:00+00:00")),
+            ("Docs", None),
+            ("Plan", Some("2026-03-01T00:00:00+00:00")),
+        ] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    due_at: due.map(str::to_string),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            tasks.push(task);
+        }
+        // Ship is due first but waits for Build
+        add_dependency(&pool, &tasks[0].id, &tasks[1].id)
+            .await
+            .unwrap();
+
+        let timeline = timeline(&pool, &project.id).await.unwrap();
+        let titles: Vec<&str> = timeline.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Plan", "Build", "Ship"]);
+        assert_eq!(timeline.undated, 1);
+        assert_eq!(timeline.tasks[2].depends_on, vec![tasks[1].id.clone()]);
+        assert!(timeline.tasks.iter().all(|t| t.milestone));
+
+        // Starting Build after its due date spans from the start
+        update_task(
+            &pool,
+            &tasks[1].id,
+            UpdateTask {
+                status: Some(TaskStatus::Todo),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        start_task(&pool, &tasks[1].id, None, None).await.unwrap();
+        let timeline = super::timeline(&pool, &project.id).await.unwrap();
+        let build = timeline.tasks.iter().find(|t| t.title == "Build").unwrap();
+        assert!(!build.milestone);
+        assert_eq!(build.start, build.end);
+    }
+
+    #[tokio::test]
+    async fn test_project_health_metrics() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Api".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for title in ["Done", "Blocked", "Open", "Open"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+        for (id, status) in [(&ids[0], TaskStatus::Done), (&ids[1], TaskStatus::Blocked)] {
+            update_task(
+                &pool,
+                id,
+                UpdateTask {
+                    status: Some(status),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let failed_run = |entity_id: &str| Run {
+            id: "run-1".to_string(),
+            worker_id: "worker-1".to_string(),
+            event_id: 1,
+            event_type: "task.unblocked".to_string(),
+            entity_id: entity_id.to_string(),
+            command: "true".to_string(),
+            args: "[]".to_string(),
+            status: "failed".to_string(),
+            exit_code: Some(1),
+            error_message: None,
+            attempt: 1,
+            max_attempts: 1,
+            next_retry_at: None,
+            pid: None,
+            log_path: None,
+            started_at: None,
+            completed_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            instance_path: None,
+            progress: None,
+            progress_message: None,
+            priority: 0,
+            exited_gracefully: None,
+            max_rss_kb: None,
+            user_cpu_ms: None,
+            system_cpu_ms: None,
+            duration_ms: None,
+            input_tokens: None,
+            output_tokens: None,
+            cost_usd: None,
+        };
+        let tasks = db::tasks::list_by_project(&pool, &project.id, false)
+            .await
+            .unwrap();
+        let health = health_of(
+            &project,
+            &tasks,
+            &[failed_run(&ids[1]), failed_run("other-proj-task-1")],
+            chrono::Utc::now() + chrono::Duration::days(2),
+        );
+
+        assert_eq!((health.done_tasks, health.total_tasks), (1, 4));
+        assert_eq!(health.percent_complete, 25.0);
+        assert_eq!(health.blocked_tasks, 1);
+        assert!((health.blocked_ratio - 1.0 / 3.0).abs() < 1e-6);
+        assert!(health.avg_open_task_age_days.unwrap() >= 2.0);
+        assert_eq!(health.throughput, 1);
+        assert_eq!(health.failing_runs, 1);
+        assert_eq!(
+            health.badge(),
+            "25% done, 1 blocked, 1 done/7d, 1 failing run"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cost_report_groups_by_worker_project_and_task() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Agents".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Fix the build".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let run = |worker_id: &str, entity_id: &str, tokens: i64, cost: f64| Run {
+            id: "run-1".to_string(),
+            worker_id: worker_id.to_string(),
+            event_id: 1,
+            event_type: "task.unblocked".to_string(),
+            entity_id: entity_id.to_string(),
+            command: "claude".to_string(),
+            args: "[]".to_string(),
+            status: "completed".to_string(),
+            exit_code: Some(0),
+            error_message: None,
+            attempt: 1,
+            max_attempts: 1,
+            next_retry_at: None,
+            pid: None,
+            log_path: None,
+            started_at: None,
+            completed_at: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            instance_path: None,
+            progress: None,
+            progress_message: None,
+            priority: 0,
+            exited_gracefully: None,
+            max_rss_kb: None,
+            user_cpu_ms: None,
+            system_cpu_ms: None,
+            duration_ms: None,
+            input_tokens: Some(tokens),
+            output_tokens: None,
+            cost_usd: Some(cost),
+        };
+        let runs = [
+            run("worker-1", &task.id, 1000, 0.5),
+            run("worker-1", &project.id, 200, 0.25),
+            run("worker-2", &task.id, 3000, 2.0),
+            run("worker-2", "sess-20260101-abcd", 10, 0.125),
+        ];
+        let names = HashMap::from([("worker-1".to_string(), "claude".to_string())]);
+
+        let report = cost_report(&pool, "2026-01-01T00:00:00Z", &runs, &names)
+            .await
+            .unwrap();
+
+        assert_eq!(report.total.runs, 4);
+        assert_eq!(report.total.input_tokens, 4210);
+        assert_eq!(report.total.cost_usd, 2.875);
+        let summary = |groups: &[CostGroup]| -> Vec<(String, usize, f64)> {
+            groups
+                .iter()
+                .map(|g| (g.id.clone(), g.totals.runs, g.totals.cost_usd))
+                .collect()
+        };
+        assert_eq!(
+            summary(&report.by_worker),
+            vec![
+                ("worker-2".to_string(), 2, 2.125),
+                ("worker-1".to_string(), 2, 0.75)
+            ]
+        );
+        assert_eq!(report.by_worker[1].name, "claude");
+        assert_eq!(
+            summary(&report.by_project),
+            vec![(project.id.clone(), 3, 2.75)]
+        );
+        assert_eq!(summary(&report.by_task), vec![(task.id.clone(), 2, 2.5)]);
+        assert_eq!(report.by_task[0].name, "Fix the build");
+    }
+
+    #[tokio::test]
+    async fn test_velocity_buckets_completions_by_week() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Pace".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for estimate in [Some(2.0), None, Some(3.0)] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: "Task".to_string(),
+                    estimate,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+        crate::services::complete_task(&pool, &ids[0], None, None)
+            .await
+            .unwrap();
+        for (id, status) in [
+            (&ids[1], TaskStatus::Done),
+            (&ids[1], TaskStatus::Todo),
+            (&ids[2], TaskStatus::Done),
+        ] {
+            update_task(
+                &pool,
+                id,
+                UpdateTask {
+                    status: Some(status),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        let two_weeks_ago = (chrono::Utc::now() - chrono::Duration::days(14)).to_rfc3339();
+        sqlx::query("UPDATE task_status_history SET changed_at = ? WHERE task_id = ?")
+            .bind(&two_weeks_ago)
+            .bind(&ids[2])
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let settings = DateSettings::default();
+        let velocity = velocity(&pool, Some(&project.id), 4, &settings)
+            .await
+            .unwrap();
+        let counts: Vec<usize> = velocity.weeks.iter().map(|w| w.completed).collect();
+        // The reopened task no longer counts
+        assert_eq!(counts, vec![0, 1, 0, 1]);
+        assert_eq!(velocity.weeks[1].estimate, 3.0);
+        assert!(velocity.weeks[3].partial);
+        assert_eq!(velocity.trend, Some(VelocityTrend::Increasing));
+
+        assert_eq!(parse_velocity_window("30d").unwrap(), 5);
+        assert!(parse_velocity_window("soon").is_err());
+    }
+}
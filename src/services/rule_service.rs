@@ -0,0 +1,397 @@
+//! Rules that flag or escalate tasks.
+//!
+//! A rule is defined by `rules.<name>.<field>` workspace config keys:
+//!
+//! - `when`: comma-separated filters on the task, in the worker filter
+//!   syntax (`status=blocked,priority=P2`)
+//! - `idle`: how long the task must have gone unchanged (`3d`, `12h`)
+//! - `then`: `flag` (the default) or `priority=<P0-P4>`
+//! - `apply`: `true` to apply the action; otherwise only the event is emitted
+//!
+//! The daemon evaluates the rules of every workspace it serves. Each match
+//! emits a `rule.triggered` event, once per task until the task changes
+//! again. Done and archived tasks never match.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::backup_service::parse_bool;
+use crate::services::datetime::{self, DateSettings};
+use crate::services::filter::{self, Filter};
+use crate::services::{list_all_tasks, update_task};
+
+/// Prefix of workspace config keys that define rules
+pub const KEY_PREFIX: &str = "rules.";
+
+/// Validate a `rules.*` config value before it is stored
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some((name, field)) = key
+        .strip_prefix(KEY_PREFIX)
+        .and_then(|rest| rest.rsplit_once('.'))
+    else {
+        return Ok(());
+    };
+    let invalid = |expected: &str| {
+        GranaryError::InvalidArgument(format!(
+            "Invalid {} '{}'. Expected {}",
+            key, value, expected
+        ))
+    };
+    match field {
+        "when" => parse_filters(value).map(|_| ()),
+        "idle" => idle_cutoff(value, Utc::now())
+            .map(|_| ())
+            .map_err(|_| invalid("a duration such as 3d or 12h")),
+        "then" => value
+            .parse::<RuleAction>()
+            .map(|_| ())
+            .map_err(|_| invalid("flag or priority=<P0-P4>")),
+        "apply" => parse_bool(value)
+            .map(|_| ())
+            .ok_or_else(|| invalid("true or false")),
+        _ => Err(GranaryError::InvalidArgument(format!(
+            "Unknown field '{}' for rule '{}'. Expected when, idle, then or apply",
+            field, name
+        ))),
+    }
+}
+
+/// Parse rules from workspace config entries
+pub fn parse_rules(entries: &[(String, String)]) -> Result<Vec<Rule>> {
+    let mut rules: Vec<Rule> = Vec::new();
+    for (key, value) in entries {
+        let Some((name, field)) = key
+            .strip_prefix(KEY_PREFIX)
+            .and_then(|rest| rest.rsplit_once('.'))
+        else {
+            continue;
+        };
+        validate_config_value(key, value)?;
+        let index = match rules.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                rules.push(Rule {
+                    name: name.to_string(),
+                    when: Vec::new(),
+                    idle: None,
+                    action: RuleAction::Flag,
+                    apply: false,
+                });
+                rules.len() - 1
+            }
+        };
+        let rule = &mut rules[index];
+        match field {
+            "when" => rule.when = split_filters(value),
+            "idle" => rule.idle = Some(value.clone()),
+            "then" => rule.action = value.parse().unwrap_or_default(),
+            "apply" => rule.apply = parse_bool(value).unwrap_or(false),
+            _ => {}
+        }
+    }
+
+    // A rule without conditions would match every open task
+    if let Some(rule) = rules.iter().find(|r| r.when.is_empty() && r.idle.is_none()) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Rule '{}' has no conditions. Set {}{}.when or {}{}.idle",
+            rule.name, KEY_PREFIX, rule.name, KEY_PREFIX, rule.name
+        )));
+    }
+    Ok(rules)
+}
+
+/// The workspace's rules, in name order
+pub async fn list_rules(pool: &SqlitePool) -> Result<Vec<Rule>> {
+    parse_rules(&db::config::list(pool).await?)
+}
+
+/// Tasks the rules match now, without emitting events or applying changes.
+/// With a name, only that rule is evaluated.
+pub async fn test_rules(pool: &SqlitePool, name: Option<&str>) -> Result<Vec<RuleMatch>> {
+    let mut rules = list_rules(pool).await?;
+    if let Some(name) = name {
+        rules.retain(|r| r.name == name);
+        if rules.is_empty() {
+            return Err(GranaryError::InvalidArgument(format!(
+                "No rule named '{}'. Define one with: granary config set {}{}.when <filters>",
+                name, KEY_PREFIX, name
+            )));
+        }
+    }
+    let matches = find_matches(pool, &rules, Utc::now()).await?;
+    Ok(matches
+        .into_iter()
+        .map(|(rule, task)| rule_match(rule, &task))
+        .collect())
+}
+
+/// Fire the rules: emit a `rule.triggered` event for each new match and
+/// apply the action of rules that ask for it. A rule fires once per task
+/// until the task changes again.
+pub async fn run_rules(pool: &SqlitePool) -> Result<Vec<RuleMatch>> {
+    run_rules_at(pool, Utc::now()).await
+}
+
+async fn run_rules_at(pool: &SqlitePool, now: DateTime<Utc>) -> Result<Vec<RuleMatch>> {
+    let rules = list_rules(pool).await?;
+    let mut fired = Vec::new();
+    // Task versions after this pass's changes, so a task one rule changed
+    // does not count as changed again for the rules that also fired for it
+    let mut versions: HashMap<String, i64> = HashMap::new();
+    for (rule, task) in find_matches(pool, &rules, now).await? {
+        if db::rule_firings::fired_version(pool, &rule.name, &task.id).await? == Some(task.version)
+        {
+            continue;
+        }
+
+        versions.entry(task.id.clone()).or_insert(task.version);
+        if rule.apply
+            && let RuleAction::Priority(priority) = &rule.action
+        {
+            let updated = update_task(
+                pool,
+                &task.id,
+                UpdateTask {
+                    priority: Some(priority.clone()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+            versions.insert(task.id.clone(), updated.version);
+        }
+
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::RuleTriggered,
+                entity_type: EntityType::Task,
+                entity_id: task.id.clone(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({
+                    "rule": rule.name,
+                    "action": rule.action.as_string(),
+                    "applied": applies(rule),
+                    "priority": task.priority,
+                    "status": task.status,
+                }),
+            },
+        )
+        .await?;
+        fired.push(rule_match(rule, &task));
+    }
+
+    for fire in &fired {
+        db::rule_firings::record(pool, &fire.rule, &fire.task_id, versions[&fire.task_id]).await?;
+    }
+    Ok(fired)
+}
+
+/// Each open task paired with every rule it matches
+async fn find_matches<'a>(
+    pool: &SqlitePool,
+    rules: &'a [Rule],
+    now: DateTime<Utc>,
+) -> Result<Vec<(&'a Rule, Task)>> {
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    let tasks: Vec<Task> = list_all_tasks(pool, false)
+        .await?
+        .into_iter()
+        .filter(|t| !t.status_enum().is_terminal())
+        .collect();
+
+    let mut matches = Vec::new();
+    for rule in rules {
+        let filters = parse_filters(&rule.when.join(","))?;
+        let cutoff = rule
+            .idle
+            .as_deref()
+            .map(|idle| idle_cutoff(idle, now))
+            .transpose()?;
+        for task in &tasks {
+            if let RuleAction::Priority(priority) = &rule.action
+                && task.priority == priority.as_str()
+            {
+                continue;
+            }
+            if let Some(cutoff) = cutoff {
+                let idle = DateTime::parse_from_rfc3339(&task.updated_at)
+                    .is_ok_and(|updated| updated <= cutoff);
+                if !idle {
+                    continue;
+                }
+            }
+            let payload = serde_json::to_value(task)?;
+            if filter::matches_all(&filters, &payload) {
+                matches.push((rule, task.clone()));
+            }
+        }
+    }
+    Ok(matches)
+}
+
+fn rule_match(rule: &Rule, task: &Task) -> RuleMatch {
+    RuleMatch {
+        rule: rule.name.clone(),
+        task_id: task.id.clone(),
+        title: task.title.clone(),
+        action: rule.action.as_string(),
+        applied: applies(rule),
+    }
+}
+
+/// Whether firing the rule changes the task
+fn applies(rule: &Rule) -> bool {
+    rule.apply && rule.action != RuleAction::Flag
+}
+
+fn split_filters(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn parse_filters(value: &str) -> Result<Vec<Filter>> {
+    filter::parse_filters(&split_filters(value))
+}
+
+/// The time a task must not have changed since to count as idle
+fn idle_cutoff(idle: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let cutoff = datetime::parse_since_relative_to(idle, &DateSettings::default(), now)?;
+    DateTime::parse_from_rfc3339(&cutoff)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|_| GranaryError::InvalidArgument(format!("Invalid duration: {}", idle)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project, create_task};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_rules() {
+        let entries = vec![
+            ("date.timezone".to_string(), "UTC".to_string()),
+            ("rules.escalate.apply".to_string(), "true".to_string()),
+            ("rules.escalate.idle".to_string(), "3d".to_string()),
+            ("rules.escalate.then".to_string(), "priority=P1".to_string()),
+            (
+                "rules.escalate.when".to_string(),
+                "status=blocked, priority=P2".to_string(),
+            ),
+            ("rules.idle.idle".to_string(), "7d".to_string()),
+        ];
+        let rules = parse_rules(&entries).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].name, "escalate");
+        assert_eq!(rules[0].when, vec!["status=blocked", "priority=P2"]);
+        assert_eq!(rules[0].action, RuleAction::Priority(TaskPriority::P1));
+        assert!(rules[0].apply);
+        assert_eq!(rules[1].action, RuleAction::Flag);
+        assert!(!rules[1].apply);
+
+        assert!(validate_config_value("rules.x.then", "delete").is_err());
+        assert!(validate_config_value("rules.x.idle", "soon").is_err());
+        assert!(validate_config_value("rules.x.colour", "red").is_err());
+        assert!(parse_rules(&[("rules.empty.then".to_string(), "flag".to_string())]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rules_fire_once_per_change() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Rules".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut tasks = Vec::new();
+        for status in [TaskStatus::Blocked, TaskStatus::Todo] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: format!("{} task", status.as_str()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            let task = update_task(
+                &pool,
+                &task.id,
+                UpdateTask {
+                    status: Some(status),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+            tasks.push(task);
+        }
+        for (key, value) in [
+            ("rules.escalate.when", "status=blocked,priority=P2"),
+            ("rules.escalate.idle", "3d"),
+            ("rules.escalate.then", "priority=P1"),
+            ("rules.escalate.apply", "true"),
+            ("rules.stale.idle", "7d"),
+        ] {
+            db::config::set(&pool, key, value).await.unwrap();
+        }
+
+        // Nothing is idle yet
+        assert!(test_rules(&pool, None).await.unwrap().is_empty());
+        assert!(test_rules(&pool, Some("missing")).await.is_err());
+
+        let later = Utc::now() + chrono::Duration::days(8);
+        let fired = run_rules_at(&pool, later).await.unwrap();
+        let mut fired: Vec<(&str, &str, bool)> = fired
+            .iter()
+            .map(|m| (m.rule.as_str(), m.task_id.as_str(), m.applied))
+            .collect();
+        fired.sort();
+        assert_eq!(
+            fired,
+            vec![
+                ("escalate", tasks[0].id.as_str(), true),
+                ("stale", tasks[0].id.as_str(), false),
+                ("stale", tasks[1].id.as_str(), false),
+            ]
+        );
+        let escalated = crate::services::get_task(&pool, &tasks[0].id)
+            .await
+            .unwrap();
+        assert_eq!(escalated.priority, "P1");
+        let events = db::events::list_by_entity(&pool, "task", &tasks[0].id)
+            .await
+            .unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| e.event_type == "rule.triggered")
+                .count(),
+            2
+        );
+
+        // Neither task has changed since its rules fired
+        assert!(run_rules_at(&pool, later).await.unwrap().is_empty());
+    }
+}
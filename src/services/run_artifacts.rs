@@ -0,0 +1,155 @@
+//! Files produced by runs.
+//!
+//! Every run gets its own artifacts directory, passed to the runner in
+//! `GRANARY_ARTIFACTS_DIR`. When the run finishes, each file left in it is
+//! recorded in the global database's `artifacts` table with parent type
+//! `run`, so `granary runs artifacts <run-id>` can list and extract them.
+//!
+//! The directory lives under the worker's log directory
+//! (`~/.granary/logs/<worker-id>/artifacts/<run-id>/`), so pruning the
+//! worker removes it along with the logs.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::{Artifact, ArtifactType, generate_artifact_id};
+
+/// Environment variable holding the run's artifacts directory
+pub const ARTIFACTS_ENV: &str = "GRANARY_ARTIFACTS_DIR";
+
+/// Parent type of artifacts recorded for runs
+pub const RUN_PARENT_TYPE: &str = "run";
+
+/// Artifacts directory of a run, given its worker's log directory
+pub fn artifacts_dir(log_dir: &Path, run_id: &str) -> PathBuf {
+    log_dir.join("artifacts").join(run_id)
+}
+
+/// Record the files in a finished run's artifacts directory.
+///
+/// A retried run reuses its directory, so the run's earlier records are
+/// replaced rather than added to. Files are named by their path relative
+/// to the directory, with `/` separators.
+pub async fn record(pool: &SqlitePool, run_id: &str, dir: &Path) -> Result<Vec<Artifact>> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+
+    db::artifacts::delete_by_parent(pool, run_id).await?;
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut artifacts = Vec::with_capacity(files.len());
+    for (index, (name, path)) in files.into_iter().enumerate() {
+        let artifact_number = index as i64 + 1;
+        let size = std::fs::metadata(&path).map(|m| m.len()).ok();
+        let artifact = Artifact {
+            id: generate_artifact_id(run_id, artifact_number),
+            parent_type: RUN_PARENT_TYPE.to_string(),
+            parent_id: run_id.to_string(),
+            artifact_number,
+            artifact_type: ArtifactType::File.as_str().to_string(),
+            path_or_url: path.display().to_string(),
+            description: Some(name),
+            meta: Some(json!({ "size": size }).to_string()),
+            created_at: now.clone(),
+        };
+        db::artifacts::create(pool, &artifact).await?;
+        artifacts.push(artifact);
+    }
+    Ok(artifacts)
+}
+
+/// Artifacts recorded for a run
+pub async fn list(pool: &SqlitePool, run_id: &str) -> Result<Vec<Artifact>> {
+    db::artifacts::list_by_parent(pool, run_id).await
+}
+
+/// Find a run's artifact by name or ID
+pub fn find<'a>(artifacts: &'a [Artifact], name: &str) -> Result<&'a Artifact> {
+    artifacts
+        .iter()
+        .find(|a| a.description.as_deref() == Some(name) || a.id == name)
+        .ok_or_else(|| GranaryError::ArtifactNotFound(name.to_string()))
+}
+
+/// Files under `dir`, as (name relative to `root`, path). Symlinks are
+/// skipped so a runner cannot point an artifact outside its directory.
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            collect_files(root, &path, files)?;
+        } else if file_type.is_file() {
+            let name = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::{create_pool, run_migrations};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_record_replaces_earlier_artifacts() {
+        let temp = TempDir::new().unwrap();
+        let pool = create_pool(&temp.path().join("workers.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let dir = artifacts_dir(temp.path(), "run-abc");
+        std::fs::create_dir_all(dir.join("reports")).unwrap();
+        std::fs::write(dir.join("fix.patch"), "diff").unwrap();
+
+        let first = record(&pool, "run-abc", &dir).await.unwrap();
+        assert_eq!(first.len(), 1);
+
+        std::fs::write(dir.join("reports/summary.md"), "# Done").unwrap();
+        record(&pool, "run-abc", &dir).await.unwrap();
+
+        let artifacts = list(&pool, "run-abc").await.unwrap();
+        let names: Vec<_> = artifacts
+            .iter()
+            .filter_map(|a| a.description.as_deref())
+            .collect();
+        assert_eq!(names, ["fix.patch", "reports/summary.md"]);
+        assert_eq!(artifacts[1].parent_type, RUN_PARENT_TYPE);
+        assert_eq!(
+            find(&artifacts, "reports/summary.md").unwrap().path_or_url,
+            dir.join("reports/summary.md").display().to_string()
+        );
+        assert!(find(&artifacts, "missing.txt").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_missing_directory() {
+        let temp = TempDir::new().unwrap();
+        let pool = create_pool(&temp.path().join("workers.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let artifacts = record(&pool, "run-abc", &temp.path().join("nothing"))
+            .await
+            .unwrap();
+        assert!(artifacts.is_empty());
+    }
+}
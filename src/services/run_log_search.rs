@@ -0,0 +1,202 @@
+//! Search across stored run logs for `granary runs grep`.
+//!
+//! Logs are read straight from the files recorded on each run, so runs
+//! whose logs were pruned are skipped rather than reported as errors. In
+//! JSON-lines logs the pattern is matched against each record's line.
+
+use std::io::BufRead;
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::RunLogRecord;
+use crate::models::run::{Run, RunLogMatch};
+
+/// What a log line has to contain to match
+#[derive(Debug)]
+pub enum LogPattern {
+    /// Plain substring; `needle` is lowercased when ignoring case
+    Text {
+        needle: String,
+        ignore_case: bool,
+    },
+    Regex(Regex),
+}
+
+impl LogPattern {
+    /// Build a pattern, as a regular expression when `regex` is set
+    pub fn new(pattern: &str, regex: bool, ignore_case: bool) -> Result<Self> {
+        if regex {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(ignore_case)
+                .build()
+                .map_err(|e| GranaryError::InvalidArgument(format!("Invalid regex: {}", e)))?;
+            Ok(Self::Regex(regex))
+        } else {
+            let needle = if ignore_case {
+                pattern.to_lowercase()
+            } else {
+                pattern.to_string()
+            };
+            Ok(Self::Text {
+                needle,
+                ignore_case,
+            })
+        }
+    }
+
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Self::Text {
+                needle,
+                ignore_case: true,
+            } => line.to_lowercase().contains(needle),
+            Self::Text { needle, .. } => line.contains(needle.as_str()),
+            Self::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// Matching lines in the logs of runs created at or after `since`,
+/// oldest run first
+pub async fn search(
+    pool: &SqlitePool,
+    worker_id: Option<&str>,
+    since: Option<&str>,
+    pattern: &LogPattern,
+) -> Result<Vec<RunLogMatch>> {
+    let runs = db::runs::list_with_logs(pool, worker_id, since).await?;
+    let mut matches = Vec::new();
+    for run in &runs {
+        matches.extend(search_run(run, pattern)?);
+    }
+    Ok(matches)
+}
+
+/// Matching lines in one run's log
+pub fn search_run(run: &Run, pattern: &LogPattern) -> Result<Vec<RunLogMatch>> {
+    let Some(log_path) = run.log_path.as_deref() else {
+        return Ok(Vec::new());
+    };
+    let file = match std::fs::File::open(Path::new(log_path)) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let json_lines = Path::new(log_path)
+        .extension()
+        .is_some_and(|ext| ext == "jsonl");
+    let run_at = run.started_at.as_ref().unwrap_or(&run.created_at);
+    let mut reader = std::io::BufReader::new(file);
+    let mut buf = Vec::new();
+    let mut matches = Vec::new();
+    let mut line_number = 0;
+    // Runners may write anything, so lines are read as bytes and decoded lossily
+    while reader.read_until(b'\n', &mut buf)? > 0 {
+        line_number += 1;
+        let line = String::from_utf8_lossy(&buf);
+        let mut line = line.trim_end_matches(['\n', '\r']).to_string();
+        if json_lines && let Ok(record) = serde_json::from_str::<RunLogRecord>(&line) {
+            line = record.line;
+        }
+        if pattern.is_match(&line) {
+            matches.push(RunLogMatch {
+                run_id: run.id.clone(),
+                worker_id: run.worker_id.clone(),
+                run_at: run_at.clone(),
+                line_number,
+                line,
+            });
+        }
+        buf.clear();
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::connection::{create_pool, run_migrations};
+    use crate::models::run::CreateRun;
+    use crate::models::worker::CreateWorker;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_log_pattern() {
+        let text = LogPattern::new("Error", false, false).unwrap();
+        assert!(text.is_match("Error: disk full"));
+        assert!(!text.is_match("error: disk full"));
+
+        let text = LogPattern::new("ERROR", false, true).unwrap();
+        assert!(text.is_match("error: disk full"));
+
+        let regex = LogPattern::new(r"exit code \d+", true, false).unwrap();
+        assert!(regex.is_match("process failed with exit code 3"));
+        assert!(!regex.is_match("exit code unknown"));
+
+        assert!(LogPattern::new("(unclosed", true, false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_filters_by_worker() {
+        let temp = TempDir::new().unwrap();
+        let pool = create_pool(&temp.path().join("workers.db")).await.unwrap();
+        run_migrations(&pool).await.unwrap();
+
+        let mut workers = Vec::new();
+        for _ in 0..2 {
+            let create = CreateWorker {
+                command: "agent".to_string(),
+                event_type: "task.unblocked".to_string(),
+                instance_path: temp.path().display().to_string(),
+                ..Default::default()
+            };
+            workers.push(db::workers::create(&pool, &create).await.unwrap().id);
+        }
+
+        let mut runs = Vec::new();
+        for (worker, log) in [
+            (&workers[0], "starting\nerror: token expired\ndone\n"),
+            (&workers[1], "error: token expired\n"),
+            (&workers[0], "all good"),
+            // A run whose log was pruned
+            (&workers[0], ""),
+        ] {
+            let log_path = temp.path().join(format!("{}.log", runs.len()));
+            if !log.is_empty() {
+                std::fs::write(&log_path, log).unwrap();
+            }
+            let run = db::runs::create(
+                &pool,
+                &CreateRun {
+                    worker_id: worker.clone(),
+                    log_path: Some(log_path.display().to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            runs.push(run);
+        }
+
+        let pattern = LogPattern::new("token expired", false, false).unwrap();
+        let matches = search(&pool, Some(&workers[0]), None, &pattern)
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].run_id, runs[0].id);
+        assert_eq!(matches[0].line_number, 2);
+        assert_eq!(matches[0].line, "error: token expired");
+
+        let matches = search(&pool, None, None, &pattern).await.unwrap();
+        assert_eq!(matches.len(), 2);
+
+        let future = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let matches = search(&pool, None, Some(&future), &pattern).await.unwrap();
+        assert!(matches.is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+//! Progress and usage reports from runners.
+//!
+//! A runner reports progress by writing a line such as
+//! `GRANARY_PROGRESS: 42% building` to stdout or stderr. The worker runtime
+//! tails each active run's log with a [`ProgressReader`] and stores the last
+//! report on the run, where `granary runs` and the dashboard show it.
+//!
+//! AI runners can also report what they spent with lines such as
+//! `GRANARY_USAGE: {"input_tokens":1200,"output_tokens":340,"cost_usd":0.012}`.
+//! Each usage report is added to the run's totals, so a runner can report
+//! every model call as it goes.
+
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::models::RunLogRecord;
+use crate::models::run::{RunProgress, TokenUsage};
+use crate::services::log_sanitize;
+
+/// Prefix of a progress report line
+pub const PROGRESS_PREFIX: &str = "GRANARY_PROGRESS:";
+
+/// Prefix of a usage report line
+pub const USAGE_PREFIX: &str = "GRANARY_USAGE:";
+
+/// Parse a progress report: an optional percentage followed by an optional
+/// message. Percentages are clamped to 0-100 and may have a fraction, which
+/// is dropped. Returns `None` for lines that are not progress reports.
+pub fn parse_line(line: &str) -> Option<RunProgress> {
+    let line = log_sanitize::sanitize(line, true);
+    let rest = line.trim_start().strip_prefix(PROGRESS_PREFIX)?.trim();
+
+    let (percent, message) = match rest.split_once('%') {
+        Some((number, message)) => match number.trim().parse::<f64>() {
+            Ok(percent) if percent.is_finite() => {
+                (Some(percent.clamp(0.0, 100.0) as i64), message.trim())
+            }
+            _ => (None, rest),
+        },
+        None => (None, rest),
+    };
+    let message = (!message.is_empty()).then(|| message.to_string());
+    if percent.is_none() && message.is_none() {
+        return None;
+    }
+    Some(RunProgress { percent, message })
+}
+
+/// Parse a usage report: a JSON object with any of `input_tokens`,
+/// `output_tokens` and `cost_usd`. Returns `None` for lines that are not
+/// usage reports, and for reports with none of the fields or negative values.
+pub fn parse_usage_line(line: &str) -> Option<TokenUsage> {
+    let line = log_sanitize::sanitize(line, true);
+    let rest = line.trim_start().strip_prefix(USAGE_PREFIX)?.trim();
+    let usage: TokenUsage = serde_json::from_str(rest).ok()?;
+    if usage == TokenUsage::default()
+        || usage.input_tokens.is_some_and(|n| n < 0)
+        || usage.output_tokens.is_some_and(|n| n < 0)
+        || usage.cost_usd.is_some_and(|c| !c.is_finite() || c < 0.0)
+    {
+        return None;
+    }
+    Some(usage)
+}
+
+/// Reports read from a run's log
+#[derive(Debug, Default, PartialEq)]
+pub struct LogReports {
+    /// The last progress report
+    pub progress: Option<RunProgress>,
+    /// The sum of the usage reports
+    pub usage: Option<TokenUsage>,
+}
+
+/// Reads progress and usage reports a run appends to its log
+#[derive(Debug)]
+pub struct ProgressReader {
+    path: PathBuf,
+    /// Byte offset of the first line not read yet
+    offset: u64,
+    /// Whether the log holds JSON-lines records
+    json_lines: bool,
+}
+
+impl ProgressReader {
+    pub fn new(path: PathBuf) -> Self {
+        let json_lines = path.extension().is_some_and(|ext| ext == "jsonl");
+        Self {
+            path,
+            offset: 0,
+            json_lines,
+        }
+    }
+
+    /// The reports among the complete lines written since the previous
+    /// read. A line still being written is left for the next read.
+    pub fn read(&mut self) -> std::io::Result<LogReports> {
+        let mut reports = LogReports::default();
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(reports),
+            Err(e) => return Err(e),
+        };
+        file.seek(SeekFrom::Start(self.offset))?;
+
+        let mut reader = BufReader::new(file);
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let read = reader.read_until(b'\n', &mut buf)?;
+            if read == 0 || buf.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += read as u64;
+
+            let line = String::from_utf8_lossy(&buf);
+            let line = if self.json_lines {
+                match serde_json::from_str::<RunLogRecord>(&line) {
+                    Ok(record) => record.line,
+                    Err(_) => continue,
+                }
+            } else {
+                line.into_owned()
+            };
+            if let Some(progress) = parse_line(&line) {
+                reports.progress = Some(progress);
+            } else if let Some(usage) = parse_usage_line(&line) {
+                reports.usage.get_or_insert_default().add(&usage);
+            }
+        }
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn progress(percent: Option<i64>, message: Option<&str>) -> Option<RunProgress> {
+        Some(RunProgress {
+            percent,
+            message: message.map(String::from),
+        })
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("GRANARY_PROGRESS: 42% building"),
+            progress(Some(42), Some("building"))
+        );
+        assert_eq!(
+            parse_line("GRANARY_PROGRESS:100%"),
+            progress(Some(100), None)
+        );
+        assert_eq!(
+            parse_line("GRANARY_PROGRESS: 12.5%"),
+            progress(Some(12), None)
+        );
+        assert_eq!(
+            parse_line("GRANARY_PROGRESS: 140%"),
+            progress(Some(100), None)
+        );
+        assert_eq!(
+            parse_line("GRANARY_PROGRESS: running tests"),
+            progress(None, Some("running tests"))
+        );
+        assert_eq!(
+            parse_line("\x1b[2mGRANARY_PROGRESS: 5% cloning\x1b[0m\n"),
+            progress(Some(5), Some("cloning"))
+        );
+        assert_eq!(parse_line("GRANARY_PROGRESS:"), None);
+        assert_eq!(parse_line("building 42%"), None);
+    }
+
+    #[test]
+    fn test_parse_usage_line() {
+        assert_eq!(
+            parse_usage_line(
+                r#"GRANARY_USAGE: {"input_tokens":1200,"output_tokens":340,"cost_usd":0.012}"#
+            ),
+            Some(TokenUsage {
+                input_tokens: Some(1200),
+                output_tokens: Some(340),
+                cost_usd: Some(0.012),
+            })
+        );
+        assert_eq!(
+            parse_usage_line(r#"GRANARY_USAGE:{"cost_usd":1.5,"model":"x"}"#),
+            Some(TokenUsage {
+                cost_usd: Some(1.5),
+                ..Default::default()
+            })
+        );
+        assert_eq!(parse_usage_line("GRANARY_USAGE: {}"), None);
+        assert_eq!(
+            parse_usage_line(r#"GRANARY_USAGE: {"input_tokens":-1}"#),
+            None
+        );
+        assert_eq!(parse_usage_line("GRANARY_USAGE: 1200 tokens"), None);
+        assert_eq!(parse_usage_line(r#"{"input_tokens":1200}"#), None);
+    }
+
+    #[test]
+    fn test_reader_sums_usage() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("run.log");
+        std::fs::write(
+            &path,
+            concat!(
+                "GRANARY_USAGE: {\"input_tokens\":1000,\"output_tokens\":200,\"cost_usd\":0.5}\n",
+                "GRANARY_PROGRESS: 50%\n",
+                "GRANARY_USAGE: {\"input_tokens\":500,\"cost_usd\":0.25}\n",
+            ),
+        )
+        .unwrap();
+        let mut reader = ProgressReader::new(path);
+        let reports = reader.read().unwrap();
+        assert_eq!(reports.progress, progress(Some(50), None));
+        assert_eq!(
+            reports.usage,
+            Some(TokenUsage {
+                input_tokens: Some(1500),
+                output_tokens: Some(200),
+                cost_usd: Some(0.75),
+            })
+        );
+        assert_eq!(reader.read().unwrap(), LogReports::default());
+    }
+
+    #[test]
+    fn test_reader_follows_appended_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("run.log");
+        let mut file = std::fs::File::create(&path).unwrap();
+        let mut reader = ProgressReader::new(path);
+
+        write!(
+            file,
+            "starting\nGRANARY_PROGRESS: 10% cloning\nGRANARY_PROGRESS: 30% building\n"
+        )
+        .unwrap();
+        assert_eq!(
+            reader.read().unwrap().progress,
+            progress(Some(30), Some("building"))
+        );
+        assert_eq!(reader.read().unwrap().progress, None);
+
+        // An unfinished line is only read once it is complete
+        write!(file, "GRANARY_PROGRESS: 90% test").unwrap();
+        assert_eq!(reader.read().unwrap().progress, None);
+        writeln!(file, "ing").unwrap();
+        assert_eq!(
+            reader.read().unwrap().progress,
+            progress(Some(90), Some("testing"))
+        );
+    }
+
+    #[test]
+    fn test_reader_json_lines() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("run.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"timestamp":"2026-01-15T10:00:00.000Z","stream":"stderr","line":"GRANARY_PROGRESS: 75% deploying"}"#,
+                "\n",
+                r#"{"timestamp":"2026-01-15T10:00:01.000Z","stream":"stdout","line":"ok"}"#,
+                "\n"
+            ),
+        )
+        .unwrap();
+        let mut reader = ProgressReader::new(path);
+        assert_eq!(
+            reader.read().unwrap().progress,
+            progress(Some(75), Some("deploying"))
+        );
+    }
+}
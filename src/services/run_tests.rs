@@ -20,6 +20,7 @@ mod tests {
         assert_eq!(RunStatus::Failed.as_str(), "failed");
         assert_eq!(RunStatus::Paused.as_str(), "paused");
         assert_eq!(RunStatus::Cancelled.as_str(), "cancelled");
+        assert_eq!(RunStatus::TimedOut.as_str(), "timed_out");
     }
 
     #[test]
@@ -36,6 +37,10 @@ mod tests {
             "cancelled".parse::<RunStatus>().unwrap(),
             RunStatus::Cancelled
         );
+        assert_eq!(
+            "timed_out".parse::<RunStatus>().unwrap(),
+            RunStatus::TimedOut
+        );
     }
 
     #[test]
@@ -93,6 +98,18 @@ mod tests {
             completed_at: None,
             created_at: "2026-01-15T10:00:00Z".to_string(),
             updated_at: "2026-01-15T10:00:00Z".to_string(),
+            instance_path: None,
+            progress: None,
+            progress_message: None,
+            priority: 0,
+            exited_gracefully: None,
+            max_rss_kb: None,
+            user_cpu_ms: None,
+            system_cpu_ms: None,
+            duration_ms: None,
+            input_tokens: None,
+            output_tokens: None,
+            cost_usd: None,
         }
     }
 
@@ -176,6 +193,9 @@ mod tests {
         run.status = "cancelled".to_string();
         assert!(run.is_finished());
 
+        run.status = "timed_out".to_string();
+        assert!(run.is_finished());
+
         run.status = "running".to_string();
         assert!(!run.is_finished());
 
@@ -234,6 +254,23 @@ mod tests {
         assert!(!run.is_pending_retry());
     }
 
+    #[test]
+    fn test_run_is_queued() {
+        let mut run = create_test_run();
+
+        run.status = "pending".to_string();
+        run.attempt = 1;
+        assert!(run.is_queued());
+
+        // Retries wait for their backoff, not for a slot
+        run.attempt = 2;
+        assert!(!run.is_queued());
+
+        run.status = "running".to_string();
+        run.attempt = 1;
+        assert!(!run.is_queued());
+    }
+
     // ==========================================
     // CreateRun Tests
     // ==========================================
@@ -263,6 +300,7 @@ mod tests {
             args: vec!["--print".to_string(), "Execute task".to_string()],
             max_attempts: 5,
             log_path: Some("/logs/run.log".to_string()),
+            instance_path: None,
         };
 
         assert_eq!(create.worker_id, "worker-abc");
@@ -337,6 +375,7 @@ mod tests {
         let retry = ScheduleRetry {
             next_retry_at: "2026-01-15T10:05:00Z".to_string(),
             attempt: 2,
+            error_message: None,
         };
 
         assert_eq!(retry.attempt, 2);
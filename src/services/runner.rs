@@ -4,16 +4,77 @@
 //! is a child process that executes a command in response to an event.
 //! Runners capture stdout/stderr to log files and report exit status.
 //!
-//! On Unix systems, runner processes are spawned in their own process groups
-//! so that the entire process tree can be killed when stopping a run.
+//! Runner processes are spawned in their own process group on Unix, and in
+//! their own Job Object on Windows, so that the entire process tree can be
+//! killed when stopping a run.
+//!
+//! Stopping is done in two phases: the process group is sent SIGTERM, and
+//! only killed if it hasn't exited when the grace period runs out, so agent
+//! runners can flush their state first.
+//!
+//! With [`LogFormat::Jsonl`], output is piped through the daemon instead and
+//! each line is written as a [`RunLogRecord`] with its stream and the time
+//! it was read.
+//!
+//! Runners may be given [`RunnerLimits`]: memory and CPU time are enforced with
+//! rlimits on Unix and a Job Object on Windows, and the working directory can
+//! be moved to a subdirectory of the workspace but never outside it.
+//!
+//! When a runner exits, the resources it used are measured as a [`RunUsage`]:
+//! on Unix it is reaped with `wait4`, which reports its CPU time and peak
+//! memory.
+//!
+//! A runner's environment is its `env` entries, then the variables of its
+//! `env_file`, read with [`load_env_file`] as each run starts.
+//!
+//! The command is resolved from the working directory and the daemon's PATH,
+//! never the runner's environment, and the canonical path it resolves to is
+//! what gets spawned. [`check_command_allowed`] resolves it the same way, so
+//! the file that runs is the file that was checked.
 
-use std::path::Path;
-use std::process::Stdio;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
 use crate::error::{GranaryError, Result};
-use crate::models::run::Run;
+use crate::models::global_config::{LogFormat, RunnerLimits, SecurityConfig};
+use crate::models::run::{Run, RunUsage};
+use crate::models::{LogStream, RunLogRecord};
+use crate::services::plugin_service::is_executable;
+
+/// Extra CPU seconds between the soft limit (SIGXCPU) and the hard limit (SIGKILL)
+#[cfg(unix)]
+const CPU_HARD_LIMIT_GRACE_SECS: u64 = 5;
+
+/// How long a stopped run has to exit after SIGTERM before it is killed,
+/// unless its runner sets `grace_period_secs`
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How long after a runner exits its captured output may still be written.
+/// Output of processes it left behind that keep the pipes open is written
+/// after the run finishes.
+const CAPTURE_GRACE: Duration = Duration::from_secs(2);
+
+/// How often to check whether a runner has exited while waiting for it
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How a runner process exited
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunnerExit {
+    /// Exit code, or -1 if the process was terminated by a signal
+    pub exit_code: i32,
+    /// Error message if the process did not exit successfully
+    pub error: Option<String>,
+    /// Whether the process was terminated for exceeding a resource limit
+    pub limit_exceeded: bool,
+    /// Resources the process used
+    pub usage: RunUsage,
+}
 
 /// Handle to a spawned runner process.
 ///
@@ -27,6 +88,19 @@ pub struct RunnerHandle {
     child: Child,
     /// Process ID (captured at spawn time)
     pub pid: u32,
+    /// Limits applied at spawn, used to explain how the process exited
+    limits: RunnerLimits,
+    /// When the process was spawned
+    started_at: Instant,
+    /// Task writing piped output to the log as JSON lines
+    capture: Option<JoinHandle<()>>,
+    /// How the process exited and when that was seen, while its captured
+    /// output is still being written
+    exited: Option<(RunnerExit, Instant)>,
+    /// Job Object holding the process tree and enforcing the limits;
+    /// closed when the handle is dropped
+    #[cfg(windows)]
+    job: job::JobHandle,
 }
 
 impl RunnerHandle {
@@ -35,39 +109,65 @@ impl RunnerHandle {
         self.pid
     }
 
+    /// Time since the process was spawned.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
     /// Check if the process has exited without blocking.
     ///
-    /// Returns `Some((exit_code, error_message))` if the process has exited,
-    /// or `None` if it's still running.
-    pub fn try_wait(&mut self) -> Result<Option<(i32, Option<String>)>> {
-        match self.child.try_wait() {
-            Ok(Some(status)) => {
-                let exit_code = status.code().unwrap_or(-1);
-                let error = if !status.success() {
-                    Some(format!("Process exited with code {}", exit_code))
-                } else {
-                    None
-                };
-                Ok(Some((exit_code, error)))
-            }
-            Ok(None) => Ok(None),
-            Err(e) => Err(GranaryError::Io(e)),
+    /// Returns `Some(exit)` if the process has exited, or `None` if it's
+    /// still running. When output is captured, the run is only reported as
+    /// exited once its output is written, or [`CAPTURE_GRACE`] has passed.
+    pub fn try_wait(&mut self) -> Result<Option<RunnerExit>> {
+        let Some(exit) = self.poll_exit()? else {
+            return Ok(None);
+        };
+        let capturing = self.capture.as_ref().is_some_and(|c| !c.is_finished());
+        let exited_at = self
+            .exited
+            .as_ref()
+            .map_or_else(Instant::now, |(_, at)| *at);
+        if capturing && exited_at.elapsed() < CAPTURE_GRACE {
+            return Ok(None);
         }
+        Ok(Some(exit))
     }
 
     /// Wait for the process to exit.
-    ///
-    /// Returns `(exit_code, error_message)` where error_message is Some
-    /// if the process exited with a non-zero code.
-    pub async fn wait(mut self) -> Result<(i32, Option<String>)> {
-        let status = self.child.wait().await?;
-        let exit_code = status.code().unwrap_or(-1);
-        let error = if !status.success() {
-            Some(format!("Process exited with code {}", exit_code))
-        } else {
-            None
+    pub async fn wait(mut self) -> Result<RunnerExit> {
+        let exit = loop {
+            if let Some(exit) = self.poll_exit()? {
+                break exit;
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        };
+        if let Some(capture) = self.capture.take() {
+            let _ = tokio::time::timeout(CAPTURE_GRACE, capture).await;
+        }
+        Ok(exit)
+    }
+
+    /// Collect the process's exit without blocking, the first time it is
+    /// seen along with the resources it used.
+    fn poll_exit(&mut self) -> Result<Option<RunnerExit>> {
+        if let Some((exit, _)) = &self.exited {
+            return Ok(Some(exit.clone()));
+        }
+        #[cfg(unix)]
+        let reaped = reap(self.pid)?;
+        #[cfg(not(unix))]
+        let reaped = self
+            .child
+            .try_wait()?
+            .map(|status| (status, RunUsage::default()));
+        let Some((status, mut usage)) = reaped else {
+            return Ok(None);
         };
-        Ok((exit_code, error))
+        usage.duration_ms = self.started_at.elapsed().as_millis() as i64;
+        let exit = runner_exit(status, &self.limits, usage);
+        self.exited = Some((exit.clone(), Instant::now()));
+        Ok(Some(exit))
     }
 
     /// Kill the process and its entire process group.
@@ -75,7 +175,8 @@ impl RunnerHandle {
     /// On Unix, this sends SIGKILL to the process group (negative PID),
     /// which kills the process and all its descendants. It also starts
     /// the kill on the child handle to ensure proper cleanup.
-    /// On Windows, this terminates just the process.
+    /// On Windows, this terminates the process's Job Object, which holds
+    /// its descendants too.
     pub async fn kill(&mut self) -> Result<()> {
         #[cfg(unix)]
         {
@@ -86,19 +187,75 @@ impl RunnerHandle {
             unsafe {
                 libc::kill(-pid, libc::SIGKILL);
             }
-            // Also start kill on the child handle to ensure tokio cleans up properly
-            // This is a no-op if the process is already dead, but ensures the handle
-            // transitions to the terminated state
+            // Also start kill on the child handle, unless the process was
+            // already reaped and its PID may have been reused
+            if self.exited.is_none() {
+                let _ = self.child.start_kill();
+            }
+            Ok(())
+        }
+        #[cfg(windows)]
+        {
+            self.job.terminate()?;
             let _ = self.child.start_kill();
             Ok(())
         }
-        #[cfg(not(unix))]
+    }
+
+    /// Ask the process and its process group to exit by sending SIGTERM.
+    ///
+    /// Windows has no equivalent, so there the Job Object is terminated
+    /// straight away.
+    pub fn terminate(&mut self) -> Result<()> {
+        #[cfg(unix)]
+        {
+            let pid = self.pid as i32;
+            // SAFETY: libc::kill with negative pid is safe, just sends signal to process group
+            unsafe {
+                libc::kill(-pid, libc::SIGTERM);
+            }
+            Ok(())
+        }
+        #[cfg(windows)]
         {
-            self.child.kill().await.map_err(GranaryError::Io)
+            self.job.terminate()
         }
     }
 
-    /// Start the process termination (sends SIGKILL to process group).
+    /// Stop the process in two phases: send SIGTERM, give it `grace` to
+    /// exit, then kill its process group.
+    ///
+    /// Returns whether the process exited within the grace period. The exit
+    /// is kept, so [`RunnerHandle::try_wait`] reports it afterwards.
+    pub async fn stop(&mut self, grace: Duration) -> Result<bool> {
+        self.terminate()?;
+        // Without SIGTERM the process was killed rather than asked to exit
+        if cfg!(windows) {
+            return Ok(false);
+        }
+        let deadline = Instant::now() + grace;
+        let exited = loop {
+            if self.poll_exit()?.is_some() {
+                break true;
+            }
+            if Instant::now() >= deadline {
+                self.kill().await?;
+                break false;
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        };
+        // Descendants that ignored SIGTERM or left the leader behind would
+        // otherwise outlive the run
+        #[cfg(unix)]
+        // SAFETY: libc::kill with negative pid is safe, just sends signal to process group
+        unsafe {
+            libc::kill(-(self.pid as i32), libc::SIGKILL);
+        }
+        Ok(exited)
+    }
+
+    /// Start the process termination (sends SIGKILL to the process group,
+    /// or terminates the Job Object on Windows).
     ///
     /// This begins killing the process and its descendants but doesn't wait for completion.
     pub fn start_kill(&mut self) -> Result<()> {
@@ -112,9 +269,9 @@ impl RunnerHandle {
             }
             Ok(())
         }
-        #[cfg(not(unix))]
+        #[cfg(windows)]
         {
-            self.child.start_kill().map_err(GranaryError::Io)
+            self.job.terminate()
         }
     }
 }
@@ -124,7 +281,8 @@ impl RunnerHandle {
 /// # Arguments
 /// * `run` - The run record containing command and arguments
 /// * `log_dir` - Directory to write log files to
-/// * `working_dir` - Working directory for the spawned process
+/// * `working_dir` - Working directory for the spawned process (the workspace root)
+/// * `limits` - Resource limits to apply to the process
 ///
 /// # Returns
 /// A `RunnerHandle` that can be used to track and wait for the process.
@@ -135,112 +293,242 @@ impl RunnerHandle {
 ///
 /// # Process Groups
 /// On Unix, the spawned process becomes a session leader and process group leader
-/// via `setsid()`. This allows the entire process tree to be killed when stopping.
-pub async fn spawn_runner(run: &Run, log_dir: &Path, working_dir: &Path) -> Result<RunnerHandle> {
-    // Ensure log directory exists
-    std::fs::create_dir_all(log_dir)?;
-
-    let log_path = log_dir.join(format!("{}.log", run.id));
-    let log_file = std::fs::File::create(&log_path)?;
-    let log_file_stderr = log_file.try_clone()?;
-
-    let args = run.args_vec();
-
-    let mut cmd = Command::new(&run.command);
-    cmd.args(&args)
-        .current_dir(working_dir)
-        .stdout(Stdio::from(log_file))
-        .stderr(Stdio::from(log_file_stderr));
-
-    // On Unix, create a new process group so we can kill the entire tree
-    #[cfg(unix)]
-    // SAFETY: setsid() is safe to call in pre_exec - it creates a new session
-    // and process group, making this process the leader. This is standard practice
-    // for daemon child processes.
-    unsafe {
-        cmd.pre_exec(|| {
-            // Create new session and process group
-            // setsid() makes this process the leader of a new process group
-            // The process group ID will equal the process's PID
-            libc::setsid();
-            Ok(())
-        });
-    }
-
-    let child = cmd.spawn().map_err(|e| {
-        GranaryError::Io(std::io::Error::new(
-            e.kind(),
-            format!("Failed to spawn runner '{}': {}", run.command, e),
-        ))
-    })?;
-
-    let pid = child.id().ok_or_else(|| {
-        GranaryError::Conflict("Failed to get PID of spawned process".to_string())
-    })?;
-
-    Ok(RunnerHandle {
-        run_id: run.id.clone(),
-        child,
-        pid,
-    })
+/// via `setsid()`. On Windows, it is assigned to a Job Object. This allows the
+/// entire process tree to be killed when stopping.
+pub async fn spawn_runner(
+    run: &Run,
+    log_dir: &Path,
+    working_dir: &Path,
+    limits: &RunnerLimits,
+) -> Result<RunnerHandle> {
+    spawn_command(
+        run,
+        log_dir,
+        working_dir,
+        &[],
+        limits,
+        None,
+        LogFormat::Text,
+    )
 }
 
-/// Spawn a runner process with environment variables.
+/// Spawn a runner process with environment variables and, optionally,
+/// input on stdin.
 ///
 /// # Arguments
 /// * `run` - The run record containing command and arguments
 /// * `log_dir` - Directory to write log files to
 /// * `working_dir` - Working directory for the spawned process
 /// * `env_vars` - Environment variables to set for the process
+/// * `limits` - Resource limits to apply to the process
+/// * `stdin` - Bytes written to the process's stdin, which is then closed;
+///   with `None` the process inherits stdin
+/// * `log_format` - How output is written to `{log_dir}/{run_id}.{extension}`
 ///
 /// # Returns
 /// A `RunnerHandle` that can be used to track and wait for the process.
 ///
 /// # Process Groups
 /// On Unix, the spawned process becomes a session leader and process group leader
-/// via `setsid()`. This allows the entire process tree to be killed when stopping.
+/// via `setsid()`. On Windows, it is assigned to a Job Object. This allows the
+/// entire process tree to be killed when stopping.
 pub async fn spawn_runner_with_env(
     run: &Run,
     log_dir: &Path,
     working_dir: &Path,
     env_vars: &[(String, String)],
+    limits: &RunnerLimits,
+    stdin: Option<Vec<u8>>,
+    log_format: LogFormat,
+) -> Result<RunnerHandle> {
+    spawn_command(
+        run,
+        log_dir,
+        working_dir,
+        env_vars,
+        limits,
+        stdin,
+        log_format,
+    )
+}
+
+/// Read the variables of a runner's dotenv file, relative to the workspace
+/// root.
+///
+/// The file is read when each run starts, so its values are never stored
+/// with the worker or the run.
+///
+/// It may not set PATH: the file lives in the workspace, and PATH decides
+/// what the runner's own subprocesses resolve to.
+pub fn load_env_file(workspace_root: &Path, env_file: &str) -> Result<Vec<(String, String)>> {
+    let path = workspace_root.join(env_file);
+    let invalid = |e: dotenvy::Error| {
+        GranaryError::InvalidArgument(format!("Invalid env file {}: {}", path.display(), e))
+    };
+    let vars: Vec<(String, String)> = dotenvy::from_path_iter(&path)
+        .map_err(invalid)?
+        .map(|item| item.map_err(invalid))
+        .collect::<Result<_>>()?;
+    if vars.iter().any(|(key, _)| key.eq_ignore_ascii_case("PATH")) {
+        return Err(GranaryError::CommandNotAllowed(format!(
+            "Env file {} may not set PATH",
+            path.display()
+        )));
+    }
+    Ok(vars)
+}
+
+/// Resolve a command the way it will be run: a path relative to
+/// `working_dir`, or a name on PATH
+pub fn find_command(command: &str, working_dir: &Path) -> Option<PathBuf> {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        let path = working_dir.join(path);
+        return is_executable(&path).then_some(path);
+    }
+    let candidates: Vec<String> = if cfg!(windows) {
+        ["", ".exe", ".cmd", ".bat"]
+            .iter()
+            .map(|ext| format!("{}{}", command, ext))
+            .collect()
+    } else {
+        vec![command.to_string()]
+    };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| candidates.iter().map(move |c| dir.join(c)))
+        .find(|candidate| is_executable(candidate))
+}
+
+/// The canonical path a runner's command resolves to from `working_dir`
+fn resolve_command(command: &str, working_dir: &Path) -> Option<PathBuf> {
+    find_command(command, working_dir).and_then(|p| p.canonicalize().ok())
+}
+
+/// Check a worker's command against the `[security]` policy of the global
+/// config, resolving it from the directory it will run in
+pub fn check_command_allowed(
+    policy: &SecurityConfig,
+    command: &str,
+    working_dir: &Path,
+) -> Result<()> {
+    let resolved = resolve_command(command, working_dir);
+    if policy.allows_command(command, resolved.as_deref()) {
+        return Ok(());
+    }
+    Err(GranaryError::CommandNotAllowed(format!(
+        "'{}' is not in security.allowed_commands of the global config",
+        command
+    )))
+}
+
+fn spawn_command(
+    run: &Run,
+    log_dir: &Path,
+    working_dir: &Path,
+    env_vars: &[(String, String)],
+    limits: &RunnerLimits,
+    stdin: Option<Vec<u8>>,
+    log_format: LogFormat,
 ) -> Result<RunnerHandle> {
+    let working_dir = resolve_working_dir(working_dir, limits.cwd.as_deref())?;
+
     // Ensure log directory exists
     std::fs::create_dir_all(log_dir)?;
 
-    let log_path = log_dir.join(format!("{}.log", run.id));
+    let log_path = log_dir.join(log_file_name(&run.id, log_format));
     let log_file = std::fs::File::create(&log_path)?;
-    let log_file_stderr = log_file.try_clone()?;
 
     let args = run.args_vec();
 
-    let mut cmd = Command::new(&run.command);
-    cmd.args(&args)
-        .current_dir(working_dir)
-        .stdout(Stdio::from(log_file))
-        .stderr(Stdio::from(log_file_stderr));
+    // Spawn the file the command resolves to, as checked against the
+    // security policy, whatever PATH the runner's environment sets
+    let program = resolve_command(&run.command, &working_dir).ok_or_else(|| {
+        GranaryError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!(
+                "Failed to spawn runner '{}': command not found",
+                run.command
+            ),
+        ))
+    })?;
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&args).current_dir(&working_dir);
+    // Keep the name the runner was configured with, for multi-call binaries
+    #[cfg(unix)]
+    cmd.arg0(&run.command);
+    let mut json_log = None;
+    match log_format {
+        LogFormat::Text => {
+            let log_file_stderr = log_file.try_clone()?;
+            cmd.stdout(Stdio::from(log_file))
+                .stderr(Stdio::from(log_file_stderr));
+        }
+        LogFormat::Jsonl => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            json_log = Some(log_file);
+        }
+    }
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
 
     // Add environment variables
     for (key, value) in env_vars {
         cmd.env(key, value);
     }
 
-    // On Unix, create a new process group so we can kill the entire tree
+    // On Unix, create a new process group so we can kill the entire tree,
+    // then apply resource limits before exec so they cover the runner itself
     #[cfg(unix)]
-    // SAFETY: setsid() is safe to call in pre_exec - it creates a new session
-    // and process group, making this process the leader. This is standard practice
-    // for daemon child processes.
-    unsafe {
-        cmd.pre_exec(|| {
-            // Create new session and process group
-            // setsid() makes this process the leader of a new process group
-            // The process group ID will equal the process's PID
-            libc::setsid();
-            Ok(())
-        });
+    {
+        let max_memory = limits
+            .max_memory_mb
+            .map(|mb| mb.saturating_mul(1024 * 1024) as libc::rlim_t);
+        let max_cpu = limits.max_cpu_secs;
+        let nice = limits.nice;
+
+        // SAFETY: setsid(), setrlimit() and setpriority() are async-signal-safe
+        // syscalls, so they may be called between fork and exec. setsid() makes
+        // this process the leader of a new session and process group.
+        unsafe {
+            cmd.pre_exec(move || {
+                // The process group ID will equal the process's PID
+                libc::setsid();
+
+                if let Some(bytes) = max_memory {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes,
+                        rlim_max: bytes,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+
+                if let Some(secs) = max_cpu {
+                    // SIGXCPU at the soft limit, SIGKILL if the runner ignores it
+                    let limit = libc::rlimit {
+                        rlim_cur: secs as libc::rlim_t,
+                        rlim_max: secs.saturating_add(CPU_HARD_LIMIT_GRACE_SECS) as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+
+                if let Some(nice) = nice
+                    && libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                Ok(())
+            });
+        }
     }
 
-    let child = cmd.spawn().map_err(|e| {
+    let mut child = cmd.spawn().map_err(|e| {
         GranaryError::Io(std::io::Error::new(
             e.kind(),
             format!("Failed to spawn runner '{}': {}", run.command, e),
@@ -251,13 +539,345 @@ pub async fn spawn_runner_with_env(
         GranaryError::Conflict("Failed to get PID of spawned process".to_string())
     })?;
 
+    let capture = match (json_log, child.stdout.take(), child.stderr.take()) {
+        (Some(file), Some(stdout), Some(stderr)) => Some(capture_json_lines(file, stdout, stderr)),
+        _ => None,
+    };
+
+    // Write the input in the background so a runner that reads slowly, or
+    // not at all, cannot block the worker; closing the pipe signals EOF
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let _ = pipe.write_all(&input).await;
+        });
+    }
+
+    // On Windows, the process is assigned to a Job Object, which its
+    // descendants join, so the tree can be killed and limits enforced
+    #[cfg(windows)]
+    let job = {
+        let process = child.raw_handle().ok_or_else(|| {
+            GranaryError::Conflict("Failed to get handle of spawned process".to_string())
+        })?;
+        job::assign(process, limits)?
+    };
+
     Ok(RunnerHandle {
         run_id: run.id.clone(),
         child,
         pid,
+        limits: limits.clone(),
+        started_at: Instant::now(),
+        capture,
+        exited: None,
+        #[cfg(windows)]
+        job,
     })
 }
 
+/// Write a runner's stdout and stderr to its log as JSON lines, in the order
+/// the lines are read. The task ends once both pipes are closed.
+fn capture_json_lines(
+    file: std::fs::File,
+    stdout: impl AsyncRead + Unpin + Send + 'static,
+    stderr: impl AsyncRead + Unpin + Send + 'static,
+) -> JoinHandle<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(read_lines(stdout, LogStream::Stdout, tx.clone()));
+    tokio::spawn(read_lines(stderr, LogStream::Stderr, tx));
+
+    tokio::spawn(async move {
+        let mut file = tokio::fs::File::from_std(file);
+        while let Some(record) = rx.recv().await {
+            let Ok(mut bytes) = serde_json::to_vec(&record) else {
+                continue;
+            };
+            bytes.push(b'\n');
+            if file.write_all(&bytes).await.is_err() {
+                break;
+            }
+        }
+        let _ = file.flush().await;
+    })
+}
+
+/// Send each line of a stream as a record until it is closed. Output that
+/// is not UTF-8 is decoded lossily.
+async fn read_lines(
+    reader: impl AsyncRead + Unpin,
+    stream: LogStream,
+    tx: mpsc::UnboundedSender<RunLogRecord>,
+) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let line = String::from_utf8_lossy(&buf);
+        let record = RunLogRecord {
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            stream,
+            line: line.trim_end_matches(['\n', '\r']).to_string(),
+        };
+        if tx.send(record).is_err() {
+            break;
+        }
+    }
+}
+
+/// Resolve a runner's working directory, confined to the workspace root.
+///
+/// `cwd` is relative to `workspace_root`; paths that resolve outside it
+/// (including through `..` or symlinks) are rejected.
+pub fn resolve_working_dir(workspace_root: &Path, cwd: Option<&str>) -> Result<PathBuf> {
+    let Some(cwd) = cwd else {
+        return Ok(workspace_root.to_path_buf());
+    };
+
+    let root = workspace_root.canonicalize()?;
+    let dir = root.join(cwd).canonicalize().map_err(|e| {
+        GranaryError::InvalidArgument(format!("Invalid runner working directory '{}': {}", cwd, e))
+    })?;
+
+    if !dir.starts_with(&root) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Runner working directory '{}' is outside the workspace",
+            cwd
+        )));
+    }
+
+    Ok(dir)
+}
+
+/// Reap a runner process with `wait4` if it has exited, returning its exit
+/// status and the resources it and its waited-for descendants used.
+///
+/// The process is reaped here rather than through its tokio handle, which
+/// would discard the usage.
+#[cfg(unix)]
+fn reap(pid: u32) -> std::io::Result<Option<(ExitStatus, RunUsage)>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let mut status = 0;
+    // SAFETY: rusage is a plain C struct, for which all zeroes is valid
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: both pointers are valid for the duration of the call
+        let reaped =
+            unsafe { libc::wait4(pid as libc::pid_t, &mut status, libc::WNOHANG, &mut rusage) };
+        match reaped {
+            0 => return Ok(None),
+            -1 => {
+                let err = std::io::Error::last_os_error();
+                if err.kind() != std::io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            _ => break,
+        }
+    }
+
+    // The widths of timeval's fields vary between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let millis = |time: libc::timeval| time.tv_sec as i64 * 1000 + time.tv_usec as i64 / 1000;
+    // ru_maxrss is in KiB, except on macOS where it is in bytes
+    let max_rss_kb = if cfg!(target_os = "macos") {
+        rusage.ru_maxrss as i64 / 1024
+    } else {
+        rusage.ru_maxrss as i64
+    };
+    let usage = RunUsage {
+        max_rss_kb: Some(max_rss_kb),
+        user_cpu_ms: Some(millis(rusage.ru_utime)),
+        system_cpu_ms: Some(millis(rusage.ru_stime)),
+        duration_ms: 0,
+    };
+    Ok(Some((ExitStatus::from_raw(status), usage)))
+}
+
+/// Describe how a process exited, attributing the exit to a limit if possible
+fn runner_exit(status: ExitStatus, limits: &RunnerLimits, usage: RunUsage) -> RunnerExit {
+    let exit_code = status.code().unwrap_or(-1);
+
+    if status.success() {
+        return RunnerExit {
+            exit_code,
+            error: None,
+            limit_exceeded: false,
+            usage,
+        };
+    }
+
+    match limit_violation(&status, limits) {
+        Some(reason) => RunnerExit {
+            exit_code,
+            error: Some(reason),
+            limit_exceeded: true,
+            usage,
+        },
+        None => RunnerExit {
+            exit_code,
+            error: Some(format!("Process exited with code {}", exit_code)),
+            limit_exceeded: false,
+            usage,
+        },
+    }
+}
+
+/// Check whether an exit status was caused by one of the limits.
+///
+/// CPU limits are detected exactly. The memory limit makes allocations fail,
+/// and the usual response to that is to abort, so only a SIGABRT while a
+/// memory limit is set is attributed to it. Other crashes are ordinary failures.
+#[cfg(unix)]
+fn limit_violation(status: &ExitStatus, limits: &RunnerLimits) -> Option<String> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let signal = status.signal()?;
+    if let Some(secs) = limits.max_cpu_secs
+        && signal == libc::SIGXCPU
+    {
+        return Some(format!("CPU time limit of {}s exceeded", secs));
+    }
+    if let Some(mb) = limits.max_memory_mb
+        && signal == libc::SIGABRT
+    {
+        return Some(format!("Memory limit of {} MB exceeded", mb));
+    }
+    None
+}
+
+#[cfg(windows)]
+fn limit_violation(status: &ExitStatus, limits: &RunnerLimits) -> Option<String> {
+    use windows_sys::Win32::Foundation::{ERROR_NOT_ENOUGH_QUOTA, STATUS_NO_MEMORY};
+
+    let code = status.code()? as u32;
+    if let Some(secs) = limits.max_cpu_secs
+        && code == ERROR_NOT_ENOUGH_QUOTA
+    {
+        return Some(format!("CPU time limit of {}s exceeded", secs));
+    }
+    if let Some(mb) = limits.max_memory_mb
+        && code == STATUS_NO_MEMORY as u32
+    {
+        return Some(format!("Memory limit of {} MB exceeded", mb));
+    }
+    None
+}
+
+#[cfg(not(any(unix, windows)))]
+fn limit_violation(_status: &ExitStatus, _limits: &RunnerLimits) -> Option<String> {
+    None
+}
+
+/// Job Objects for enforcing runner limits on Windows
+#[cfg(windows)]
+mod job {
+    use std::os::windows::io::RawHandle;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_PRIORITY_CLASS,
+        JOB_OBJECT_LIMIT_PROCESS_MEMORY, JOB_OBJECT_LIMIT_PROCESS_TIME,
+        JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject,
+    };
+    use windows_sys::Win32::System::Threading::{
+        ABOVE_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS, HIGH_PRIORITY_CLASS,
+        IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+    };
+
+    use crate::error::Result;
+    use crate::models::global_config::RunnerLimits;
+
+    /// Owned Job Object handle
+    pub struct JobHandle(HANDLE);
+
+    // SAFETY: a job handle is a kernel object reference usable from any thread
+    unsafe impl Send for JobHandle {}
+
+    impl Drop for JobHandle {
+        fn drop(&mut self) {
+            // SAFETY: the handle was returned by CreateJobObjectW and is closed once
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    /// Map a Unix nice value to the closest Windows priority class
+    fn priority_class(nice: i32) -> u32 {
+        match nice {
+            i32::MIN..=-10 => HIGH_PRIORITY_CLASS,
+            -9..=-1 => ABOVE_NORMAL_PRIORITY_CLASS,
+            0 => NORMAL_PRIORITY_CLASS,
+            1..=9 => BELOW_NORMAL_PRIORITY_CLASS,
+            _ => IDLE_PRIORITY_CLASS,
+        }
+    }
+
+    impl JobHandle {
+        /// Kill every process in the job
+        pub fn terminate(&self) -> Result<()> {
+            // SAFETY: the handle is a valid job handle until dropped
+            if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            Ok(())
+        }
+    }
+
+    /// Create a Job Object with the given limits and assign the process to
+    /// it. Processes it starts afterwards join the job too.
+    pub fn assign(process: RawHandle, limits: &RunnerLimits) -> Result<JobHandle> {
+        // SAFETY: all pointers passed to the Win32 calls are valid for the
+        // duration of each call, and `info` is a plain C struct
+        unsafe {
+            let handle = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if handle.is_null() {
+                return Err(std::io::Error::last_os_error().into());
+            }
+            let job = JobHandle(handle);
+
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            if let Some(mb) = limits.max_memory_mb {
+                info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+                info.ProcessMemoryLimit = (mb as usize).saturating_mul(1024 * 1024);
+            }
+            let basic = &mut info.BasicLimitInformation;
+            if let Some(secs) = limits.max_cpu_secs {
+                // User time is measured in 100ns ticks
+                basic.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+                basic.PerProcessUserTimeLimit = (secs as i64).saturating_mul(10_000_000);
+            }
+            if let Some(nice) = limits.nice {
+                basic.LimitFlags |= JOB_OBJECT_LIMIT_PRIORITY_CLASS;
+                basic.PriorityClass = priority_class(nice);
+            }
+
+            if SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) == 0
+            {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            if AssignProcessToJobObject(job.0, process as HANDLE) == 0 {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            Ok(job)
+        }
+    }
+}
+
 /// Read the contents of a run's log file.
 ///
 /// # Arguments
@@ -271,6 +891,11 @@ pub fn read_log(run_id: &str, log_dir: &Path) -> Result<String> {
     std::fs::read_to_string(&log_path).map_err(GranaryError::Io)
 }
 
+/// File name of a run's log in the given format
+pub fn log_file_name(run_id: &str, format: LogFormat) -> String {
+    format!("{}.{}", run_id, format.extension())
+}
+
 /// Get the path to a run's log file.
 ///
 /// # Arguments
@@ -309,6 +934,18 @@ mod tests {
             completed_at: None,
             created_at: "2024-01-15T10:00:00Z".to_string(),
             updated_at: "2024-01-15T10:00:00Z".to_string(),
+            instance_path: None,
+            progress: None,
+            progress_message: None,
+            priority: 0,
+            exited_gracefully: None,
+            max_rss_kb: None,
+            user_cpu_ms: None,
+            system_cpu_ms: None,
+            duration_ms: None,
+            input_tokens: None,
+            output_tokens: None,
+            cost_usd: None,
         }
     }
 
@@ -317,34 +954,223 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let run = create_test_run("echo", vec!["hello", "world"]);
 
-        let handle = spawn_runner(&run, temp_dir.path(), temp_dir.path())
-            .await
-            .unwrap();
+        let handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
         assert!(!handle.run_id.is_empty());
         assert!(handle.pid > 0);
 
-        let (exit_code, error) = handle.wait().await.unwrap();
-        assert_eq!(exit_code, 0);
-        assert!(error.is_none());
+        let exit = handle.wait().await.unwrap();
+        assert_eq!(exit.exit_code, 0);
+        assert!(exit.error.is_none());
 
         // Check log file was created
         let log_content = read_log(&run.id, temp_dir.path()).unwrap();
         assert!(log_content.contains("hello world"));
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_runner_with_stdin() {
+        let temp_dir = TempDir::new().unwrap();
+        let run = create_test_run("cat", vec![]);
+        let env = [("GREETING".to_string(), "hi".to_string())];
+
+        let handle = spawn_runner_with_env(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &env,
+            &RunnerLimits::default(),
+            Some(b"{\"event\": 1}\n".to_vec()),
+            LogFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        // cat only exits once stdin is closed
+        let exit = handle.wait().await.unwrap();
+        assert_eq!(exit.exit_code, 0);
+        let log_content = read_log(&run.id, temp_dir.path()).unwrap();
+        assert_eq!(log_content, "{\"event\": 1}\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_command_allowed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let agents = temp_dir.path().join("agents");
+        std::fs::create_dir(&agents).unwrap();
+        let script = agents.join("agent.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::write(temp_dir.path().join("other.sh"), "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(
+            temp_dir.path().join("other.sh"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let root = temp_dir.path().canonicalize().unwrap();
+        let policy = SecurityConfig {
+            allowed_commands: Some(vec![format!("{}/agents/*", root.display())]),
+        };
+        assert!(check_command_allowed(&policy, "agents/agent.sh", temp_dir.path()).is_ok());
+        assert!(matches!(
+            check_command_allowed(&policy, "agents/../other.sh", temp_dir.path()),
+            Err(GranaryError::CommandNotAllowed(_))
+        ));
+        assert!(check_command_allowed(&policy, "sh", temp_dir.path()).is_err());
+        assert!(check_command_allowed(&SecurityConfig::default(), "sh", temp_dir.path()).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_env_file_overrides_env() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(".env.agent"),
+            "# secrets\nTOKEN=from-file\nQUOTED=\"two words\"\n",
+        )
+        .unwrap();
+        let run = create_test_run("sh", vec!["-c", "echo \"$TOKEN $QUOTED $MODE\""]);
+
+        let mut env = vec![
+            ("TOKEN".to_string(), "inline".to_string()),
+            ("MODE".to_string(), "fast".to_string()),
+        ];
+        env.extend(load_env_file(temp_dir.path(), ".env.agent").unwrap());
+        let handle = spawn_runner_with_env(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &env,
+            &RunnerLimits::default(),
+            None,
+            LogFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(handle.wait().await.unwrap().exit_code, 0);
+        let log_content = read_log(&run.id, temp_dir.path()).unwrap();
+        assert_eq!(log_content, "from-file two words fast\n");
+        assert!(load_env_file(temp_dir.path(), ".env.missing").is_err());
+
+        std::fs::write(temp_dir.path().join(".env.path"), "PATH=./bin\n").unwrap();
+        assert!(matches!(
+            load_env_file(temp_dir.path(), ".env.path"),
+            Err(GranaryError::CommandNotAllowed(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_ignores_path_from_env() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // A PATH in the runner's environment can't swap out the command
+        let temp_dir = TempDir::new().unwrap();
+        let bin = temp_dir.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        std::fs::write(bin.join("sh"), "#!/bin/echo hijacked\n").unwrap();
+        std::fs::set_permissions(bin.join("sh"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        let run = create_test_run("sh", vec!["-c", "echo \"$0\""]);
+        let env = [("PATH".to_string(), bin.display().to_string())];
+
+        let handle = spawn_runner_with_env(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &env,
+            &RunnerLimits::default(),
+            None,
+            LogFormat::Text,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(handle.wait().await.unwrap().exit_code, 0);
+        assert_eq!(read_log(&run.id, temp_dir.path()).unwrap(), "sh\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_runner_json_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let run = create_test_run("sh", vec!["-c", "echo out; echo err >&2; printf tail"]);
+
+        let mut handle = spawn_runner_with_env(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &[],
+            &RunnerLimits::default(),
+            None,
+            LogFormat::Jsonl,
+        )
+        .await
+        .unwrap();
+
+        // The exit is only reported once the output has been written
+        let exit = loop {
+            if let Some(exit) = handle.try_wait().unwrap() {
+                break exit;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        };
+        assert_eq!(exit.exit_code, 0);
+
+        let path = temp_dir
+            .path()
+            .join(log_file_name(&run.id, LogFormat::Jsonl));
+        let content = std::fs::read_to_string(path).unwrap();
+        let mut records: Vec<RunLogRecord> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        // The streams are read concurrently, so only order within one is kept
+        records.sort_by_key(|r| r.stream.as_str());
+        let lines: Vec<_> = records
+            .iter()
+            .map(|r| (r.stream, r.line.as_str()))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                (LogStream::Stderr, "err"),
+                (LogStream::Stdout, "out"),
+                (LogStream::Stdout, "tail"),
+            ]
+        );
+        assert!(chrono::DateTime::parse_from_rfc3339(&records[0].timestamp).is_ok());
+    }
+
     #[tokio::test]
     async fn test_spawn_runner_failure() {
         let temp_dir = TempDir::new().unwrap();
         let run = create_test_run("false", vec![]); // 'false' command always exits with 1
 
-        let handle = spawn_runner(&run, temp_dir.path(), temp_dir.path())
-            .await
-            .unwrap();
-        let (exit_code, error) = handle.wait().await.unwrap();
+        let handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
+        let exit = handle.wait().await.unwrap();
 
-        assert_eq!(exit_code, 1);
-        assert!(error.is_some());
-        assert!(error.unwrap().contains("exited with code 1"));
+        assert_eq!(exit.exit_code, 1);
+        assert!(!exit.limit_exceeded);
+        assert!(exit.error.unwrap().contains("exited with code 1"));
     }
 
     #[tokio::test]
@@ -352,7 +1178,13 @@ mod tests {
         let temp_dir = TempDir::new().unwrap();
         let run = create_test_run("nonexistent_command_12345", vec![]);
 
-        let result = spawn_runner(&run, temp_dir.path(), temp_dir.path()).await;
+        let result = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await;
         assert!(result.is_err());
     }
 
@@ -362,9 +1194,14 @@ mod tests {
         // Use 'sleep' to have a long-running process
         let run = create_test_run("sleep", vec!["10"]);
 
-        let mut handle = spawn_runner(&run, temp_dir.path(), temp_dir.path())
-            .await
-            .unwrap();
+        let mut handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
 
         // Process should still be running
         let result = handle.try_wait().unwrap();
@@ -383,6 +1220,237 @@ mod tests {
         assert!(result.is_some());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_stops_grandchildren() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("grandchild.pid");
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+        let run = create_test_run("sh", vec!["-c", &script]);
+
+        let mut handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
+        let grandchild = read_pid(&pid_file).await;
+
+        handle.kill().await.unwrap();
+        handle.wait().await.unwrap();
+        assert_killed(grandchild).await;
+    }
+
+    /// Wait for a spawned process to be gone, or a zombie waiting to be reaped
+    #[cfg(unix)]
+    async fn assert_killed(pid: i32) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let state = std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .ok()
+                .and_then(|stat| stat.rsplit(')').next().map(|s| s.trim().to_string()));
+            // SAFETY: signal 0 only checks whether the process exists
+            let exists = unsafe { libc::kill(pid, 0) } == 0;
+            if !exists || state.is_some_and(|s| s.starts_with('Z')) {
+                break;
+            }
+            assert!(Instant::now() < deadline, "process {pid} survived");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Read the PID a test script wrote to `path`
+    #[cfg(unix)]
+    async fn read_pid(path: &Path) -> i32 {
+        loop {
+            if let Some(pid) = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+            {
+                return pid;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_kills_what_outlives_the_leader() {
+        let temp_dir = TempDir::new().unwrap();
+        let pid_file = temp_dir.path().join("grandchild.pid");
+        // The leader exits on SIGTERM; the grandchild ignores it
+        let script = format!(
+            "(trap '' TERM; exec sleep 30) & echo $! > {}; trap 'exit 0' TERM; wait",
+            pid_file.display()
+        );
+        let run = create_test_run("sh", vec!["-c", &script]);
+        let mut handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
+        let grandchild = read_pid(&pid_file).await;
+
+        assert!(handle.stop(Duration::from_secs(5)).await.unwrap());
+        assert_killed(grandchild).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_kills_after_grace_period() {
+        let temp_dir = TempDir::new().unwrap();
+        let ready = temp_dir.path().join("ready");
+        for (trap, graceful) in [("exit 0", true), ("", false)] {
+            let _ = std::fs::remove_file(&ready);
+            let script = format!(
+                "trap '{}' TERM; touch {}; while :; do sleep 0.1; done",
+                trap,
+                ready.display()
+            );
+            let run = create_test_run("sh", vec!["-c", &script]);
+            let mut handle = spawn_runner(
+                &run,
+                temp_dir.path(),
+                temp_dir.path(),
+                &RunnerLimits::default(),
+            )
+            .await
+            .unwrap();
+            while !ready.exists() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+
+            let exited = handle.stop(Duration::from_millis(500)).await.unwrap();
+            assert_eq!(exited, graceful, "trap '{}'", trap);
+            let exit = handle.wait().await.unwrap();
+            assert_eq!(exit.exit_code == 0, graceful);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_runner_cpu_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let run = create_test_run("sh", vec!["-c", "while :; do :; done"]);
+        let limits = RunnerLimits {
+            max_cpu_secs: Some(1),
+            ..Default::default()
+        };
+
+        let handle = spawn_runner(&run, temp_dir.path(), temp_dir.path(), &limits)
+            .await
+            .unwrap();
+        let exit = tokio::time::timeout(std::time::Duration::from_secs(30), handle.wait())
+            .await
+            .expect("CPU limit was not enforced")
+            .unwrap();
+
+        assert!(exit.limit_exceeded);
+        assert_eq!(exit.error.as_deref(), Some("CPU time limit of 1s exceeded"));
+        assert!(exit.usage.user_cpu_ms.is_some_and(|ms| ms >= 900));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_aborts_count_against_the_memory_limit() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let limits = RunnerLimits {
+            max_memory_mb: Some(64),
+            ..Default::default()
+        };
+        let signaled = ExitStatus::from_raw;
+        assert_eq!(
+            limit_violation(&signaled(libc::SIGABRT), &limits).as_deref(),
+            Some("Memory limit of 64 MB exceeded")
+        );
+        assert_eq!(limit_violation(&signaled(libc::SIGSEGV), &limits), None);
+        assert_eq!(limit_violation(&signaled(libc::SIGBUS), &limits), None);
+        assert_eq!(
+            limit_violation(&signaled(libc::SIGABRT), &RunnerLimits::default()),
+            None
+        );
+
+        let exit = runner_exit(signaled(libc::SIGSEGV), &limits, RunUsage::default());
+        assert!(!exit.limit_exceeded);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_reports_usage() {
+        let temp_dir = TempDir::new().unwrap();
+        let run = create_test_run("sh", vec!["-c", "sleep 0.2"]);
+
+        let handle = spawn_runner(
+            &run,
+            temp_dir.path(),
+            temp_dir.path(),
+            &RunnerLimits::default(),
+        )
+        .await
+        .unwrap();
+        let usage = handle.wait().await.unwrap().usage;
+
+        assert!(usage.duration_ms >= 200);
+        assert!(usage.max_rss_kb.is_some_and(|kb| kb > 0));
+        assert!(usage.user_cpu_ms.is_some());
+        assert!(usage.system_cpu_ms.is_some());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_runner_applies_nice_and_cwd() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let run = create_test_run("sh", vec!["-c", "pwd; nice"]);
+        let limits = RunnerLimits {
+            nice: Some(5),
+            cwd: Some("sub".to_string()),
+            ..Default::default()
+        };
+
+        let handle = spawn_runner(&run, temp_dir.path(), temp_dir.path(), &limits)
+            .await
+            .unwrap();
+        let exit = handle.wait().await.unwrap();
+        assert_eq!(exit.exit_code, 0);
+
+        let log = read_log(&run.id, temp_dir.path()).unwrap();
+        let mut lines = log.lines();
+        assert!(lines.next().unwrap().ends_with("/sub"));
+        assert_eq!(lines.next(), Some("5"));
+    }
+
+    #[test]
+    fn test_resolve_working_dir_is_confined() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join("sub")).unwrap();
+
+        assert_eq!(resolve_working_dir(root, None).unwrap(), root);
+        assert_eq!(
+            resolve_working_dir(root, Some("sub")).unwrap(),
+            root.canonicalize().unwrap().join("sub")
+        );
+        assert!(matches!(
+            resolve_working_dir(root, Some("..")),
+            Err(GranaryError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            resolve_working_dir(root, Some("/")),
+            Err(GranaryError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            resolve_working_dir(root, Some("missing")),
+            Err(GranaryError::InvalidArgument(_))
+        ));
+    }
+
     #[test]
     fn test_log_path() {
         let dir = Path::new("/var/logs");
@@ -0,0 +1,176 @@
+//! Structured input for runners of workers started with `--stdin`.
+//!
+//! Instead of reading everything from `{placeholders}` and calling back into
+//! granary, such a runner gets one JSON document on stdin, followed by a
+//! newline and EOF:
+//!
+//! ```json
+//! {
+//!   "run": { "id": "run-abc12345", "worker_id": "worker-...", "attempt": 1 },
+//!   "event": { "id": 42, "event_type": "task.unblocked", "payload": { ... }, ... },
+//!   "context": { "projects": [...], "tasks": [...], ... }
+//! }
+//! ```
+//!
+//! `context` is the same context pack `granary context --format json`
+//! prints in the workspace.
+
+use serde_json::{Value, json};
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::Result;
+use crate::models::event::Event;
+use crate::models::run::Run;
+use crate::services::context_spec::ContextSelection;
+use crate::services::summary_service::generate_context;
+use crate::services::tokens::TokenBudget;
+use crate::services::workspace::Workspace;
+
+/// The event that triggered a run.
+///
+/// Stored events are read back from the workspace. Synthetic ones, such as
+/// `task.next` or `schedule.fired`, are not stored, so only what the run
+/// recorded about them is available.
+pub async fn event_for_run(pool: &SqlitePool, run: &Run) -> Result<Event> {
+    if run.event_id != 0
+        && let Some(event) = db::events::get(pool, run.event_id).await?
+    {
+        return Ok(event);
+    }
+    Ok(Event {
+        id: run.event_id,
+        event_type: run.event_type.clone(),
+        entity_type: String::new(),
+        entity_id: run.entity_id.clone(),
+        actor: None,
+        session_id: None,
+        payload: "{}".to_string(),
+        created_at: run.created_at.clone(),
+    })
+}
+
+/// Build the stdin document for a run
+pub async fn build(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    run: &Run,
+    event: &Event,
+) -> Result<Vec<u8>> {
+    let context = generate_context(
+        pool,
+        workspace,
+        &ContextSelection::default(),
+        None,
+        TokenBudget::default(),
+    )
+    .await?;
+
+    let input = json!({
+        "run": {
+            "id": run.id,
+            "worker_id": run.worker_id,
+            "attempt": run.attempt,
+            "max_attempts": run.max_attempts,
+        },
+        "event": {
+            "id": event.id,
+            "event_type": event.event_type,
+            "entity_type": event.entity_type,
+            "entity_id": event.entity_id,
+            "actor": event.actor,
+            "session_id": event.session_id,
+            "created_at": event.created_at,
+            "payload": serde_json::from_str::<Value>(&event.payload).unwrap_or(Value::Null),
+        },
+        "context": context,
+    });
+
+    let mut bytes = serde_json::to_vec(&input)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateProject, CreateTask};
+    use crate::services;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_build_includes_event_and_context() {
+        let temp = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Input".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = services::create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Read stdin".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let event = db::events::list_since_id_by_type(&pool, 0, "task.created")
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        let run = Run {
+            id: "run-test".to_string(),
+            worker_id: "worker-test".to_string(),
+            event_id: event.id,
+            event_type: event.event_type.clone(),
+            entity_id: task.id.clone(),
+            command: "agent".to_string(),
+            args: "[]".to_string(),
+            status: "pending".to_string(),
+            exit_code: None,
+            error_message: None,
+            attempt: 1,
+            max_attempts: 3,
+            next_retry_at: None,
+            pid: None,
+            log_path: None,
+            started_at: None,
+            completed_at: None,
+            created_at: event.created_at.clone(),
+            updated_at: event.created_at.clone(),
+            instance_path: None,
+            progress: None,
+            progress_message: None,
+            priority: 0,
+            exited_gracefully: None,
+            max_rss_kb: None,
+            user_cpu_ms: None,
+            system_cpu_ms: None,
+            duration_ms: None,
+            input_tokens: None,
+            output_tokens: None,
+            cost_usd: None,
+        };
+
+        let event = event_for_run(&pool, &run).await.unwrap();
+        let bytes = build(&pool, &workspace, &run, &event).await.unwrap();
+        assert_eq!(bytes.last(), Some(&b'\n'));
+
+        let input: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(input["run"]["id"], "run-test");
+        assert_eq!(input["event"]["event_type"], "task.created");
+        assert_eq!(input["event"]["entity_id"], task.id);
+        assert_eq!(input["context"]["tasks"][0]["title"], "Read stdin");
+    }
+}
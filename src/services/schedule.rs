@@ -0,0 +1,227 @@
+//! Cron schedules for workers.
+//!
+//! A worker may carry an optional cron expression in addition to (or instead
+//! of) its event subscription. The worker runtime fires a synthetic
+//! `schedule.fired` event each time the schedule comes due and persists the
+//! next fire time on the worker record, so a restarted daemon picks up where
+//! it left off.
+//!
+//! Both the standard 5-field form (`min hour dom mon dow`) and the 6/7-field
+//! form with a leading seconds column are accepted. The 5-field form numbers
+//! days of the week the standard way (0 or 7 = Sunday); the longer forms go
+//! to the `cron` crate as written, where 1 = Sunday. Schedules are evaluated
+//! in the configured timezone (see [`crate::services::datetime`]).
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::error::{GranaryError, Result};
+use crate::models::event::Event;
+use crate::models::worker::Worker;
+
+/// Event type emitted when a worker's schedule fires
+pub const SCHEDULE_EVENT_TYPE: &str = "schedule.fired";
+
+/// A parsed cron schedule
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    expression: String,
+    inner: cron::Schedule,
+}
+
+impl Schedule {
+    /// Parse a cron expression.
+    ///
+    /// Five-field expressions are treated as firing at second zero.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let normalized = match fields.len() {
+            5 => {
+                let day_of_week = day_names(fields[4]).map_err(|e| {
+                    GranaryError::InvalidArgument(format!(
+                        "Invalid schedule '{}': {}",
+                        expression, e
+                    ))
+                })?;
+                format!("0 {} {}", fields[..4].join(" "), day_of_week)
+            }
+            6 | 7 => expression.to_string(),
+            _ => {
+                return Err(GranaryError::InvalidArgument(format!(
+                    "Invalid schedule '{}': expected 5 fields (min hour dom mon dow)",
+                    expression
+                )));
+            }
+        };
+
+        let inner = cron::Schedule::from_str(&normalized).map_err(|e| {
+            GranaryError::InvalidArgument(format!("Invalid schedule '{}': {}", expression, e))
+        })?;
+
+        Ok(Self {
+            expression: expression.to_string(),
+            inner,
+        })
+    }
+
+    /// The expression as given by the user
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Next fire time strictly after `after`, evaluated in `tz`
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        self.inner
+            .after(&after.with_timezone(&tz))
+            .next()
+            .map(|dt| dt.with_timezone(&Utc))
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Rewrite a standard day-of-week field, where 0 and 7 are Sunday, as day
+/// names. The `cron` crate counts from 1 = Sunday, so numbers can't be
+/// passed through. Items that already use names are kept as they are.
+fn day_names(field: &str) -> std::result::Result<String, String> {
+    let invalid = || format!("invalid day of week '{}'", field);
+    let day = |n: &str| {
+        n.parse::<usize>()
+            .ok()
+            .filter(|n| *n <= 7)
+            .ok_or_else(invalid)
+    };
+
+    let mut items: Vec<String> = Vec::new();
+    let mut push = |item: &str| {
+        if !items.iter().any(|i| i == item) {
+            items.push(item.to_string());
+        }
+    };
+    for item in field.split(',') {
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => (base, Some(step)),
+            None => (item, None),
+        };
+        let numeric = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+        let (start, end) = match base.split_once('-') {
+            Some((a, b)) if numeric(a) && numeric(b) => (day(a)?, day(b)?),
+            None if numeric(base) => (day(base)?, if step.is_some() { 6 } else { day(base)? }),
+            None if base == "*" && step.is_some() => (0, 6),
+            _ => {
+                push(item);
+                continue;
+            }
+        };
+        let step = match step {
+            Some(step) => step
+                .parse::<usize>()
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or_else(invalid)?,
+            None => 1,
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for d in (start..=end).step_by(step) {
+            push(DAY_NAMES[d % 7]);
+        }
+    }
+    Ok(items.join(","))
+}
+
+/// Build the synthetic event used to trigger a scheduled run.
+///
+/// Like polled events, scheduled events have id 0 and are never acknowledged.
+pub fn fired_event(worker: &Worker, scheduled_at: &str) -> Event {
+    Event {
+        id: 0,
+        event_type: SCHEDULE_EVENT_TYPE.to_string(),
+        entity_type: "worker".to_string(),
+        entity_id: worker.id.clone(),
+        actor: None,
+        session_id: None,
+        payload: serde_json::json!({
+            "schedule": worker.schedule,
+            "scheduled_at": scheduled_at,
+        })
+        .to_string(),
+        created_at: Utc::now().to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_five_field() {
+        let schedule = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(schedule.expression(), "*/15 * * * *");
+
+        let after = Utc.with_ymd_and_hms(2026, 3, 2, 10, 7, 30).unwrap();
+        let next = schedule.next_after(after, Tz::UTC).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 3, 2, 10, 15, 0).unwrap());
+    }
+
+    #[test]
+    fn test_parse_with_seconds() {
+        let schedule = Schedule::parse("30 0 9 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 3, 2, 10, 0, 0).unwrap();
+        let next = schedule.next_after(after, Tz::UTC).unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 3, 3, 9, 0, 30).unwrap());
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Schedule::parse("").is_err());
+        assert!(Schedule::parse("* * *").is_err());
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(Schedule::parse("not a cron expr").is_err());
+    }
+
+    /// The days a schedule fires on over the week starting Monday 2026-03-02
+    fn fire_days(expression: &str) -> Vec<String> {
+        let schedule = Schedule::parse(expression).unwrap();
+        let mut after = Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 3, 9, 0, 0, 0).unwrap();
+        let mut days = Vec::new();
+        while let Some(next) = schedule.next_after(after, Tz::UTC).filter(|n| *n < end) {
+            days.push(next.format("%a").to_string());
+            after = next;
+        }
+        days
+    }
+
+    #[test]
+    fn test_five_field_day_of_week_is_standard() {
+        let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri"];
+        assert_eq!(fire_days("0 9 * * 1-5"), weekdays);
+        assert_eq!(fire_days("0 9 * * MON-FRI"), weekdays);
+        assert_eq!(fire_days("0 9 * * 0"), ["Sun"]);
+        assert_eq!(fire_days("0 9 * * 7"), ["Sun"]);
+        assert_eq!(fire_days("0 9 * * 5-7"), ["Fri", "Sat", "Sun"]);
+        assert_eq!(fire_days("0 9 * * 1,3"), ["Mon", "Wed"]);
+        assert_eq!(fire_days("0 9 * * */2"), ["Tue", "Thu", "Sat", "Sun"]);
+        assert_eq!(fire_days("0 9 * * *"), fire_days("0 9 * * 0-6"));
+
+        assert!(Schedule::parse("0 9 * * 8").is_err());
+        assert!(Schedule::parse("0 9 * * 5-1").is_err());
+    }
+
+    #[test]
+    fn test_next_after_respects_timezone() {
+        // 09:00 in New York is 14:00 UTC during standard time
+        let schedule = Schedule::parse("0 9 * * *").unwrap();
+        let after = Utc.with_ymd_and_hms(2026, 1, 5, 12, 0, 0).unwrap();
+        let next = schedule
+            .next_after(after, chrono_tz::America::New_York)
+            .unwrap();
+        assert_eq!(next, Utc.with_ymd_and_hms(2026, 1, 5, 14, 0, 0).unwrap());
+    }
+}
@@ -1,15 +1,44 @@
 use sqlx::SqlitePool;
 
 use crate::db;
-use crate::error::Result;
+use crate::error::{GranaryError, Result};
 use crate::models::*;
+use crate::services::{across_workspaces, attach_labels, attach_task_fields, validate_fields};
 
-/// Search initiatives, projects, and tasks by query string
-pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<SearchResult>> {
+/// Prefix of the config keys that store saved searches
+pub const SAVED_SEARCH_PREFIX: &str = "search.";
+
+/// Config key naming the saved search that `granary tasks` shows by default
+pub const DEFAULT_TASK_VIEW_KEY: &str = "tasks.default_search";
+
+/// Search initiatives, projects, and tasks by query string.
+///
+/// Queries with qualifiers such as `status:todo` only return tasks; see
+/// [`SearchQuery`] for the syntax. Archived items are skipped unless
+/// `include_archived` is set. `page` applies to the combined results.
+pub async fn search(
+    pool: &SqlitePool,
+    query: &str,
+    include_archived: bool,
+    page: Page,
+) -> Result<Vec<SearchResult>> {
+    let mut query: SearchQuery = query.parse().map_err(GranaryError::InvalidArgument)?;
+    query.include_archived = include_archived;
     let mut results = Vec::new();
 
+    if query.has_qualifiers() {
+        for task in find_tasks_page(pool, &query, None, &Sort::default(), page).await? {
+            results.push(task_result(task));
+        }
+        return Ok(results);
+    }
+    let query = query.text.as_str();
+
+    // Each kind of result can only contribute rows up to the end of the page
+    let prefix = page.prefix();
+
     // Search initiatives first (highest hierarchy level)
-    let initiatives = db::search::search_initiatives(pool, query).await?;
+    let initiatives = db::search::search_initiatives(pool, query, include_archived, prefix).await?;
     for initiative in initiatives {
         results.push(SearchResult::Initiative {
             id: initiative.id,
@@ -20,7 +49,7 @@ pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<SearchResult>>
     }
 
     // Search projects
-    let projects = db::search::search_projects(pool, query).await?;
+    let projects = db::search::search_projects(pool, query, include_archived, prefix).await?;
     for project in projects {
         results.push(SearchResult::Project {
             id: project.id,
@@ -31,17 +60,484 @@ pub async fn search(pool: &SqlitePool, query: &str) -> Result<Vec<SearchResult>>
     }
 
     // Search tasks
-    let tasks = db::search::search_tasks(pool, query).await?;
+    let mut tasks = db::search::search_tasks(pool, query, include_archived, prefix).await?;
+    attach_labels(pool, &mut tasks).await?;
     for task in tasks {
-        results.push(SearchResult::Task {
-            id: task.id,
-            title: task.title,
-            description: task.description,
-            status: task.status,
-            priority: task.priority,
-            project_id: task.project_id,
-        });
+        results.push(task_result(task));
+    }
+
+    Ok(page.apply(results))
+}
+
+/// Run [`search`] in every registered workspace, in name order, with `page`
+/// applying to the combined results. Returns the results and a warning for
+/// each workspace that could not be searched.
+pub async fn search_all_workspaces(
+    global: &SqlitePool,
+    query: &str,
+    include_archived: bool,
+    page: Page,
+) -> Result<(Vec<WorkspaceSearchResult>, Vec<String>)> {
+    // A malformed query would fail in every workspace; report it once
+    query
+        .parse::<SearchQuery>()
+        .map_err(GranaryError::InvalidArgument)?;
+
+    let (results, warnings) = across_workspaces(global, |workspace, pool| async move {
+        let results = search(&pool, query, include_archived, page.prefix()).await?;
+        Ok(results
+            .into_iter()
+            .map(|result| WorkspaceSearchResult {
+                workspace: workspace.clone(),
+                result,
+            })
+            .collect())
+    })
+    .await?;
+    Ok((page.apply(results), warnings))
+}
+
+fn task_result(task: Task) -> SearchResult {
+    SearchResult::Task {
+        id: task.id,
+        title: task.title,
+        description: task.description,
+        status: task.status,
+        priority: task.priority,
+        project_id: task.project_id,
+        labels: task.labels,
+    }
+}
+
+/// Tasks matching a parsed query, with labels and custom fields attached
+pub async fn find_tasks(pool: &SqlitePool, query: &SearchQuery) -> Result<Vec<Task>> {
+    find_tasks_page(pool, query, None, &Sort::default(), Page::default()).await
+}
+
+/// One page of the tasks matching a query, optionally within some projects.
+///
+/// Filtering, sorting and paging happen in SQL, so only the rows shown are
+/// loaded.
+pub async fn find_tasks_page(
+    pool: &SqlitePool,
+    query: &SearchQuery,
+    project_ids: Option<&[String]>,
+    sort: &Sort,
+    page: Page,
+) -> Result<Vec<Task>> {
+    let query = SearchQuery {
+        fields: validate_fields(pool, &query.fields).await?,
+        ..query.clone()
+    };
+    let mut tasks = db::search::find_tasks(pool, &query, project_ids, sort, page).await?;
+    attach_labels(pool, &mut tasks).await?;
+    attach_task_fields(pool, &mut tasks).await?;
+    Ok(tasks)
+}
+
+/// Validate a value for a `search.<name>` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_search_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(name) = key.strip_prefix(SAVED_SEARCH_PREFIX) else {
+        return Ok(());
+    };
+    if !is_valid_field_name(name) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Invalid saved search name '{}'. Use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    value
+        .parse::<SearchQuery>()
+        .map(|_| ())
+        .map_err(GranaryError::InvalidArgument)
+}
+
+/// Save a query under a name, replacing any existing search with that name
+pub async fn save_search(pool: &SqlitePool, name: &str, query: &str) -> Result<()> {
+    let key = format!("{}{}", SAVED_SEARCH_PREFIX, name);
+    validate_search_config_value(&key, query)?;
+    db::config::set(pool, &key, query).await
+}
+
+/// Look up the query of a saved search
+pub async fn get_saved_search(pool: &SqlitePool, name: &str) -> Result<String> {
+    db::config::get(pool, &format!("{}{}", SAVED_SEARCH_PREFIX, name))
+        .await?
+        .ok_or_else(|| {
+            GranaryError::InvalidArgument(format!(
+                "No saved search named '{}'. List them with: granary search list",
+                name
+            ))
+        })
+}
+
+/// One page of the tasks matched by a saved search
+pub async fn find_saved_tasks(
+    pool: &SqlitePool,
+    name: &str,
+    include_archived: bool,
+    sort: &Sort,
+    page: Page,
+) -> Result<Vec<Task>> {
+    let query = get_saved_search(pool, name).await?;
+    let mut query: SearchQuery = query.parse().map_err(GranaryError::InvalidArgument)?;
+    query.include_archived = include_archived;
+    find_tasks_page(pool, &query, None, sort, page).await
+}
+
+/// All saved searches as `(name, query)` pairs, sorted by name
+pub async fn list_saved_searches(pool: &SqlitePool) -> Result<Vec<(String, String)>> {
+    Ok(db::config::list(pool)
+        .await?
+        .into_iter()
+        .filter_map(|(key, query)| {
+            key.strip_prefix(SAVED_SEARCH_PREFIX)
+                .map(|name| (name.to_string(), query))
+        })
+        .collect())
+}
+
+/// Delete a saved search. Returns false if it did not exist.
+pub async fn delete_saved_search(pool: &SqlitePool, name: &str) -> Result<bool> {
+    db::config::delete(pool, &format!("{}{}", SAVED_SEARCH_PREFIX, name)).await
+}
+
+/// The saved search `granary tasks` shows when run without filters, if configured
+pub async fn default_task_view(pool: &SqlitePool) -> Result<Option<String>> {
+    db::config::get(pool, DEFAULT_TASK_VIEW_KEY).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{
+        Workspace, archive_project, archive_task, create_project, create_task, label_task,
+        parse_label_changes, unarchive_task, update_task,
+    };
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_saved_search_with_qualifiers() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Search".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut ids = Vec::new();
+        for (title, priority) in [
+            ("Fix login", TaskPriority::P0),
+            ("Fix logout", TaskPriority::P2),
+        ] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    priority,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            update_task(
+                &pool,
+                &task.id,
+                UpdateTask {
+                    status: Some(TaskStatus::Todo),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+            label_task(
+                &pool,
+                &task.id,
+                &parse_label_changes(&["backend".to_string()]).unwrap(),
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        save_search(&pool, "my-queue", "status:todo priority:p0 label:backend")
+            .await
+            .unwrap();
+        assert!(save_search(&pool, "bad name", "fix").await.is_err());
+        assert!(save_search(&pool, "broken", "status:later").await.is_err());
+
+        let saved = list_saved_searches(&pool).await.unwrap();
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].0, "my-queue");
+
+        let tasks = find_saved_tasks(&pool, "my-queue", false, &Sort::default(), Page::default())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, ids[0]);
+
+        // Qualified queries only return tasks; free text still narrows them
+        let results = search(&pool, "logout label:backend", false, Page::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), ids[1]);
+
+        assert!(delete_saved_search(&pool, "my-queue").await.unwrap());
+        assert!(matches!(
+            find_saved_tasks(&pool, "my-queue", false, &Sort::default(), Page::default()).await,
+            Err(GranaryError::InvalidArgument(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_search_pages() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Paging".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        for n in 1..=5 {
+            create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: format!("Paging task {}", n),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // Scoped lists come back in task number order
+        let scope = [project.id.clone()];
+        let tasks = find_tasks_page(
+            &pool,
+            &SearchQuery::default(),
+            Some(&scope),
+            &Sort::default(),
+            Page::new(Some(2), 1),
+        )
+        .await
+        .unwrap();
+        let numbers: Vec<i64> = tasks.iter().map(|t| t.task_number).collect();
+        assert_eq!(numbers, vec![2, 3]);
+
+        // Free-text results are paged across projects and tasks together
+        let all = search(&pool, "paging", false, Page::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 6);
+        let page = search(&pool, "paging", false, Page::new(Some(2), 1))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = page.iter().map(SearchResult::id).collect();
+        assert_eq!(ids, vec![all[1].id(), all[2].id()]);
+    }
+
+    #[tokio::test]
+    async fn test_find_tasks_sorted() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Sorting".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        for priority in [TaskPriority::P2, TaskPriority::P0, TaskPriority::P2] {
+            create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: "Sorted task".to_string(),
+                    priority,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // Ties on the first key fall back to the next one
+        let sort = Sort::parse("priority,-number", TASK_SORT_FIELDS).unwrap();
+        let scope = [project.id.clone()];
+        let tasks = find_tasks_page(
+            &pool,
+            &SearchQuery::default(),
+            Some(&scope),
+            &sort,
+            Page::default(),
+        )
+        .await
+        .unwrap();
+        let numbers: Vec<i64> = tasks.iter().map(|t| t.task_number).collect();
+        assert_eq!(numbers, vec![2, 3, 1]);
     }
 
-    Ok(results)
+    #[tokio::test]
+    async fn test_archived_items_hidden() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Archive me".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        let mut status = String::new();
+        for title in ["Archive first", "Archive second"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            status = task.status;
+            ids.push(task.id);
+        }
+
+        let task = archive_task(&pool, &ids[0]).await.unwrap();
+        assert!(task.is_archived());
+        assert_eq!(task.status, status);
+        assert!(archive_task(&pool, &ids[0]).await.is_err());
+
+        let ids_of = |results: Vec<SearchResult>| -> Vec<String> {
+            results.iter().map(|r| r.id().to_string()).collect()
+        };
+        let visible = ids_of(
+            search(&pool, "archive", false, Page::default())
+                .await
+                .unwrap(),
+        );
+        assert_eq!(visible, vec![project.id.clone(), ids[1].clone()]);
+        let all = search(&pool, "archive", true, Page::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+
+        // Cascading archives the remaining task and hides the project
+        archive_project(&pool, &project.id, true).await.unwrap();
+        assert!(
+            search(&pool, "archive", false, Page::default())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let query = SearchQuery {
+            include_archived: true,
+            ..Default::default()
+        };
+        assert_eq!(find_tasks(&pool, &query).await.unwrap().len(), 2);
+
+        unarchive_task(&pool, &ids[1]).await.unwrap();
+        let tasks = find_tasks(&pool, &SearchQuery::default()).await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].id, ids[1]);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_workspaces() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = crate::db::connection::create_pool(&temp_dir.path().join("workers.db"))
+            .await
+            .unwrap();
+        crate::db::connection::run_migrations(&global)
+            .await
+            .unwrap();
+
+        for (name, titles) in [
+            ("api", ["Deploy api", "Write tests"]),
+            ("web", ["Deploy web", "Fix css"]),
+        ] {
+            let root = temp_dir.path().join(name);
+            std::fs::create_dir_all(&root).unwrap();
+            let workspace = Workspace::create(&root).unwrap();
+            let pool = workspace.init_db().await.unwrap();
+            let project = create_project(
+                &pool,
+                CreateProject {
+                    name: name.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            for title in titles {
+                create_task(
+                    &pool,
+                    CreateTask {
+                        project_id: project.id.clone(),
+                        title: title.to_string(),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .unwrap();
+            }
+            crate::services::register_workspace(&global, &workspace)
+                .await
+                .unwrap();
+        }
+        // A registered workspace that was deleted is skipped with a warning
+        let gone = temp_dir.path().join("gone");
+        std::fs::create_dir_all(&gone).unwrap();
+        crate::services::register_workspace(&global, &Workspace::create(&gone).unwrap())
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&gone).unwrap();
+
+        let (results, warnings) = search_all_workspaces(&global, "deploy", false, Page::default())
+            .await
+            .unwrap();
+        let found: Vec<(&str, &str)> = results
+            .iter()
+            .map(|r| (r.workspace.as_str(), r.result.title()))
+            .collect();
+        assert_eq!(found, vec![("api", "Deploy api"), ("web", "Deploy web")]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("gone"));
+
+        let (page, _) = search_all_workspaces(&global, "deploy", false, Page::new(Some(1), 1))
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].workspace, "web");
+
+        assert!(matches!(
+            search_all_workspaces(&global, "status:nope", false, Page::default()).await,
+            Err(GranaryError::InvalidArgument(_))
+        ));
+    }
 }
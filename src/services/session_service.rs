@@ -2,8 +2,11 @@ use sqlx::SqlitePool;
 
 use crate::db::{self, counters};
 use crate::error::{GranaryError, Result};
+use crate::models::workspace_config::{ModeConfig, WorkspaceConfig};
 use crate::models::*;
 use crate::services::Workspace;
+use crate::services::global_config as global_config_service;
+use crate::services::workspace_config as workspace_config_service;
 
 /// Create a new session
 pub async fn create_session(pool: &SqlitePool, input: CreateSession) -> Result<Session> {
@@ -16,6 +19,7 @@ pub async fn create_session(pool: &SqlitePool, input: CreateSession) -> Result<S
         owner: input.owner,
         mode: Some(input.mode.as_str().to_string()),
         focus_task_id: None,
+        focus_stack: None,
         variables: None,
         created_at: now.clone(),
         updated_at: now,
@@ -74,6 +78,10 @@ pub async fn update_session(
         session.mode = Some(mode.as_str().to_string());
     }
     if let Some(focus) = updates.focus_task_id {
+        let mut stack = session.focus_stack_vec();
+        stack.pop();
+        stack.push(focus.clone());
+        session.focus_stack = Some(serde_json::to_string(&stack)?);
         session.focus_task_id = Some(focus);
     }
     if let Some(vars) = updates.variables {
@@ -134,16 +142,36 @@ pub async fn close_session(
             id: comment_id,
             parent_type: "session".to_string(),
             parent_id: id.to_string(),
+            parent_comment_id: None,
             comment_number,
             kind: CommentKind::Handoff.as_str().to_string(),
             content: content.to_string(),
-            author: session.owner.clone(),
+            author: session
+                .owner
+                .clone()
+                .or_else(crate::services::current_author),
             meta: None,
             created_at: now.clone(),
             updated_at: now,
             version: 1,
         };
         db::comments::create(pool, &comment).await?;
+
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::CommentCreated,
+                entity_type: EntityType::Comment,
+                entity_id: comment.id.clone(),
+                actor: comment.author.clone(),
+                session_id: Some(id.to_string()),
+                payload: serde_json::json!({
+                    "kind": comment.kind,
+                    "parent_id": comment.parent_id,
+                }),
+            },
+        )
+        .await?;
     }
 
     // Log event
@@ -248,40 +276,89 @@ pub async fn get_scope_by_type(
     Ok(scope.into_iter().map(|s| s.item_id).collect())
 }
 
-/// Set the focus task for a session
+/// Set the focus task for a session, replacing the top of its focus stack
 pub async fn set_focus_task(pool: &SqlitePool, session_id: &str, task_id: &str) -> Result<Session> {
-    // Verify task exists
-    let _task = crate::services::get_task(pool, task_id).await?;
+    crate::services::get_task(pool, task_id).await?;
+
+    let session = get_session(pool, session_id).await?;
+    let mut stack = session.focus_stack_vec();
+    stack.pop();
+    stack.push(task_id.to_string());
+    save_focus_stack(pool, session, stack, "set").await
+}
+
+/// Push a task onto a session's focus stack, making it the focus task.
+/// The task it replaces is focused again by [`pop_focus_task`].
+pub async fn push_focus_task(
+    pool: &SqlitePool,
+    session_id: &str,
+    task_id: &str,
+) -> Result<Session> {
+    crate::services::get_task(pool, task_id).await?;
+
+    let session = get_session(pool, session_id).await?;
+    let mut stack = session.focus_stack_vec();
+    if stack.last().map(String::as_str) == Some(task_id) {
+        return Err(GranaryError::Conflict(format!(
+            "Task {} is already the focus task",
+            task_id
+        )));
+    }
+    stack.push(task_id.to_string());
+    save_focus_stack(pool, session, stack, "push").await
+}
+
+/// Pop the focus task off a session's focus stack, returning focus to the
+/// task below it. Returns the session and the task popped.
+pub async fn pop_focus_task(pool: &SqlitePool, session_id: &str) -> Result<(Session, String)> {
+    let session = get_session(pool, session_id).await?;
+    let mut stack = session.focus_stack_vec();
+    let popped = stack.pop().ok_or_else(|| {
+        GranaryError::Conflict(format!("Session {} has no focus task", session_id))
+    })?;
+    let session = save_focus_stack(pool, session, stack, "pop").await?;
+    Ok((session, popped))
+}
+
+/// Clear the focus stack of a session
+pub async fn clear_focus_task(pool: &SqlitePool, session_id: &str) -> Result<Session> {
+    let session = get_session(pool, session_id).await?;
+    save_focus_stack(pool, session, Vec::new(), "clear").await
+}
 
-    let mut session = get_session(pool, session_id).await?;
-    session.focus_task_id = Some(task_id.to_string());
+/// Save a session's focus stack, with its top as the focus task
+async fn save_focus_stack(
+    pool: &SqlitePool,
+    mut session: Session,
+    stack: Vec<String>,
+    action: &str,
+) -> Result<Session> {
+    session.focus_task_id = stack.last().cloned();
+    session.focus_stack = if stack.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&stack)?)
+    };
     db::sessions::update(pool, &session).await?;
 
-    // Log event
     db::events::create(
         pool,
         &CreateEvent {
             event_type: EventType::SessionFocusChanged,
             entity_type: EntityType::Session,
-            entity_id: session_id.to_string(),
+            entity_id: session.id.clone(),
             actor: None,
-            session_id: Some(session_id.to_string()),
+            session_id: Some(session.id.clone()),
             payload: serde_json::json!({
-                "focus_task_id": task_id,
+                "focus_task_id": session.focus_task_id,
+                "action": action,
+                "depth": stack.len(),
             }),
         },
     )
     .await?;
 
-    get_session(pool, session_id).await
-}
-
-/// Clear the focus task for a session
-pub async fn clear_focus_task(pool: &SqlitePool, session_id: &str) -> Result<Session> {
-    let mut session = get_session(pool, session_id).await?;
-    session.focus_task_id = None;
-    db::sessions::update(pool, &session).await?;
-    get_session(pool, session_id).await
+    get_session(pool, &session.id).await
 }
 
 /// Get session environment variables for shell export
@@ -310,3 +387,394 @@ pub async fn get_current_session(
 pub fn set_current_session(workspace: &Workspace, session_id: &str) -> Result<()> {
     workspace.set_current_session(session_id)
 }
+
+/// Resolve a session mode by name: a built-in mode or one defined as
+/// `[modes.<name>]` in the workspace config
+pub fn resolve_mode(config: &WorkspaceConfig, name: &str) -> Result<SessionMode> {
+    if let Ok(mode) = name.parse() {
+        return Ok(mode);
+    }
+    if config.modes.contains_key(name) {
+        return Ok(SessionMode::Custom(name.to_string()));
+    }
+    let mut names: Vec<&str> = SessionMode::BUILT_IN.iter().map(|m| m.as_str()).collect();
+    let mut custom: Vec<&str> = config
+        .modes
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !names.contains(name))
+        .collect();
+    custom.sort();
+    names.extend(custom);
+    Err(GranaryError::InvalidArgument(format!(
+        "Unknown session mode '{}'. Expected one of: {}",
+        name,
+        names.join(", ")
+    )))
+}
+
+/// The effects of a session's mode, if the workspace config gives it any
+pub fn mode_config<'a>(config: &'a WorkspaceConfig, session: &Session) -> Option<&'a ModeConfig> {
+    config.modes.get(session.mode_enum().as_str())
+}
+
+/// Reject moving a task to `status` when the mode of session `session_id`
+/// doesn't allow it
+pub async fn check_status_writable(
+    pool: &SqlitePool,
+    status: &TaskStatus,
+    session_id: Option<&str>,
+) -> Result<()> {
+    let Some(session_id) = session_id else {
+        return Ok(());
+    };
+    let config = workspace_config_service::load_current()?;
+    check_mode_status(pool, &config, session_id, status).await
+}
+
+/// Reject moving a task to `status` when the mode of a session doesn't
+/// allow it
+pub async fn check_mode_status(
+    pool: &SqlitePool,
+    config: &WorkspaceConfig,
+    session_id: &str,
+    status: &TaskStatus,
+) -> Result<()> {
+    if config.modes.is_empty() {
+        return Ok(());
+    }
+    let Some(session) = db::sessions::get(pool, session_id).await? else {
+        return Ok(());
+    };
+    match mode_config(config, &session) {
+        Some(mode) if !mode.allows_status(status) => Err(GranaryError::Conflict(format!(
+            "Tasks can't be moved to {} in {} mode (session {}). Allowed: {}",
+            status.as_str(),
+            session.mode_enum().as_str(),
+            session.id,
+            match mode.statuses.as_deref().unwrap_or_default() {
+                [] => "none".to_string(),
+                statuses => statuses.join(", "),
+            }
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Summarize what happened during a session from the events recorded in it:
+/// the tasks touched and their status changes while the session was open,
+/// comments, checkpoints, and the worker runs its events triggered.
+pub async fn review_session(
+    pool: &SqlitePool,
+    workspace: &Workspace,
+    session_id: &str,
+) -> Result<SessionReview> {
+    let session = get_session(pool, session_id).await?;
+    let mut events = db::events::list_by_session(pool, session_id).await?;
+    events.sort_by_key(|e| e.id);
+
+    let mut tasks: Vec<ReviewedTask> = Vec::new();
+    let mut comments = Vec::new();
+    let mut checkpoints = Vec::new();
+    for event in &events {
+        let task_id = match event.entity_type_enum() {
+            Some(EntityType::Task) => Some(event.entity_id.clone()),
+            Some(EntityType::Comment) => {
+                let Some(comment) = db::comments::get(pool, &event.entity_id).await? else {
+                    continue;
+                };
+                let on_task = comment.parent_type == "task";
+                let task_id = on_task.then(|| comment.parent_id.clone());
+                if event.event_type == EventType::CommentCreated.as_str() {
+                    comments.push(comment);
+                }
+                task_id
+            }
+            Some(EntityType::Checkpoint) => {
+                if event.event_type == EventType::CheckpointCreated.as_str()
+                    && let Some(checkpoint) = db::checkpoints::get(pool, &event.entity_id).await?
+                {
+                    checkpoints.push(ReviewedCheckpoint {
+                        id: checkpoint.id,
+                        name: checkpoint.name,
+                        created_at: checkpoint.created_at,
+                    });
+                }
+                None
+            }
+            _ => None,
+        };
+        let Some(task_id) = task_id else {
+            continue;
+        };
+        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
+            task.events += 1;
+        } else if let Some(task) = db::tasks::get(pool, &task_id).await? {
+            tasks.push(ReviewedTask {
+                id: task.id,
+                title: task.title,
+                status: task.status,
+                events: 1,
+            });
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let until = session
+        .closed_at
+        .clone()
+        .unwrap_or_else(|| now.to_rfc3339());
+    let task_ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+    let transitions =
+        db::status_history::list_for_tasks(pool, &task_ids, &session.created_at, &until).await?;
+
+    // Runs live in the global database, which is not created just to find none
+    let runs = if global_config_service::global_db_path()?.exists() && !events.is_empty() {
+        let global = global_config_service::global_pool().await?;
+        let event_ids: Vec<i64> = events.iter().map(|e| e.id).collect();
+        db::runs::list_for_events(&global, &workspace.root.to_string_lossy(), &event_ids).await?
+    } else {
+        Vec::new()
+    };
+
+    let parse = |ts: &str| chrono::DateTime::parse_from_rfc3339(ts).ok();
+    let elapsed_secs = match (parse(&session.created_at), parse(&until)) {
+        (Some(start), Some(end)) => (end - start).num_seconds().max(0),
+        _ => 0,
+    };
+
+    Ok(SessionReview {
+        session,
+        elapsed_secs,
+        event_count: events.len(),
+        tasks,
+        transitions,
+        comments,
+        checkpoints,
+        runs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{create_checkpoint, create_project, create_task, ready_task, start_task};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_review_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let session = create_session(
+            &pool,
+            CreateSession {
+                name: Some("Retro".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Review".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Ship it".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        // Commands run in a session record their events with it
+        ready_task(&pool, &task.id, None).await.unwrap();
+        start_task(&pool, &task.id, None, None).await.unwrap();
+        db::events::create(
+            &pool,
+            &CreateEvent {
+                event_type: EventType::TaskStarted,
+                entity_type: EntityType::Task,
+                entity_id: task.id.clone(),
+                actor: None,
+                session_id: Some(session.id.clone()),
+                payload: serde_json::json!({}),
+            },
+        )
+        .await
+        .unwrap();
+        create_checkpoint(&pool, &session.id, "midway")
+            .await
+            .unwrap();
+        close_session(&pool, &session.id, Some("Shipped"), &workspace)
+            .await
+            .unwrap();
+
+        let review = review_session(&pool, &workspace, &session.id)
+            .await
+            .unwrap();
+
+        assert!(review.session.is_closed());
+        assert_eq!(review.event_count, 5);
+        assert_eq!(
+            review.tasks,
+            vec![ReviewedTask {
+                id: task.id.clone(),
+                title: "Ship it".to_string(),
+                status: "in_progress".to_string(),
+                events: 1,
+            }]
+        );
+        let transitions: Vec<(Option<&str>, &str)> = review
+            .transitions
+            .iter()
+            .map(|t| (t.from_status.as_deref(), t.to_status.as_str()))
+            .collect();
+        assert_eq!(
+            transitions,
+            vec![(Some("draft"), "todo"), (Some("todo"), "in_progress")]
+        );
+        assert_eq!(review.comments.len(), 1);
+        assert_eq!(review.comments[0].content, "Shipped");
+        assert_eq!(review.checkpoints.len(), 1);
+        assert_eq!(review.checkpoints[0].name, "midway");
+        assert!(review.runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_modes() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let config = workspace_config_service::parse(
+            "[modes.triage]\nstatuses = [\"todo\", \"blocked\"]\n\n[modes.review]\nstatuses = []",
+        )
+        .unwrap();
+
+        assert_eq!(resolve_mode(&config, "plan").unwrap(), SessionMode::Plan);
+        let triage = resolve_mode(&config, "triage").unwrap();
+        assert_eq!(triage, SessionMode::Custom("triage".to_string()));
+        let err = resolve_mode(&config, "nap").unwrap_err().to_string();
+        assert!(err.contains("execute, plan, review, triage"), "{}", err);
+
+        let session = create_session(
+            &pool,
+            CreateSession {
+                mode: triage,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(session.mode.as_deref(), Some("triage"));
+        assert!(
+            check_mode_status(&pool, &config, &session.id, &TaskStatus::Blocked)
+                .await
+                .is_ok()
+        );
+        let err = check_mode_status(&pool, &config, &session.id, &TaskStatus::Done)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, GranaryError::Conflict(_)));
+
+        // A built-in mode configured with no statuses is read-only
+        let session = update_session(
+            &pool,
+            &session.id,
+            UpdateSession {
+                mode: Some(SessionMode::Review),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert!(
+            check_mode_status(&pool, &config, &session.id, &TaskStatus::Todo)
+                .await
+                .is_err()
+        );
+        let unconfigured = WorkspaceConfig::default();
+        assert!(
+            check_mode_status(&pool, &unconfigured, &session.id, &TaskStatus::Todo)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_focus_stack() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let session = create_session(&pool, CreateSession::default())
+            .await
+            .unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Focus".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for title in ["Feature", "Incident", "Hotfix"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        set_focus_task(&pool, &session.id, &ids[0]).await.unwrap();
+        push_focus_task(&pool, &session.id, &ids[1]).await.unwrap();
+        assert!(push_focus_task(&pool, &session.id, &ids[1]).await.is_err());
+        let session = push_focus_task(&pool, &session.id, &ids[2]).await.unwrap();
+        assert_eq!(session.focus_stack_vec(), ids);
+        assert_eq!(session.focus_task_id.as_deref(), Some(ids[2].as_str()));
+
+        // Setting the focus replaces the top of the stack
+        let session = set_focus_task(&pool, &session.id, &ids[0]).await.unwrap();
+        assert_eq!(
+            session.focus_stack_vec(),
+            vec![ids[0].clone(), ids[1].clone(), ids[0].clone()]
+        );
+
+        let (session, popped) = pop_focus_task(&pool, &session.id).await.unwrap();
+        assert_eq!(popped, ids[0]);
+        assert_eq!(session.focus_task_id.as_deref(), Some(ids[1].as_str()));
+
+        workspace.set_current_session(&session.id).unwrap();
+        let summary = crate::services::generate_summary(
+            &pool,
+            &workspace,
+            crate::services::tokens::TokenBudget::default(),
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.focus_task.unwrap().id, ids[1]);
+        let interrupted: Vec<&str> = summary.interrupted.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(interrupted, vec![ids[0].as_str()]);
+
+        pop_focus_task(&pool, &session.id).await.unwrap();
+        let (session, _) = pop_focus_task(&pool, &session.id).await.unwrap();
+        assert!(session.focus_task_id.is_none());
+        assert!(session.focus_stack_vec().is_empty());
+        assert!(pop_focus_task(&pool, &session.id).await.is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//! Sections of `granary summary`.
+//!
+//! `generate_summary` gathers the tasks in scope, then runs each section
+//! returned by [`sections`] in order. A section fills its part of the
+//! [`SummaryOutput`] and can be turned off with
+//! `summary.sections.<name> = false`. Sections run in order of importance,
+//! so with a token budget the later ones are the first to be trimmed.
+//!
+//! To add a section, implement [`SummarySection`], give [`SummaryOutput`] a
+//! field for it and list it in [`sections`].
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::output::json::{
+    AuthorCounts, EstimateRollup, PriorityCounts, RecentDecision, StaleTask, StateSummary,
+    StatusCounts, SummaryOutput,
+};
+use crate::services::datetime::{self, DateSettings};
+use crate::services::summary_service::{DEFAULT_STALE_AFTER, STALE_AFTER_KEY};
+use crate::services::tokens::TokenBudget;
+use crate::services::{get_task, reminder_service};
+
+/// Config key prefix for turning sections on and off
+pub const SECTIONS_KEY_PREFIX: &str = "summary.sections.";
+
+/// What a section is built from
+pub struct SectionContext<'a> {
+    pub pool: &'a SqlitePool,
+    pub session: Option<&'a Session>,
+    /// Tasks in the session's scope, or every task
+    pub tasks: &'a [Task],
+    pub now: DateTime<Utc>,
+    /// Tokens left for this and later sections
+    pub budget: TokenBudget,
+}
+
+/// A section of the summary
+pub trait SummarySection: Send + Sync {
+    /// Name used in `summary.sections.<name>`
+    fn name(&self) -> &'static str;
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>>;
+}
+
+/// Every section, in the order they are built
+pub fn sections() -> Vec<Box<dyn SummarySection>> {
+    vec![
+        Box::new(StateSection),
+        Box::new(FocusSection),
+        Box::new(BlockersSection),
+        Box::new(NextActionsSection),
+        Box::new(DueSoonSection),
+        Box::new(StaleSection),
+        Box::new(DecisionsSection),
+        Box::new(ArtifactsSection),
+    ]
+}
+
+/// Whether a section is turned on. Sections are on unless set to false.
+pub async fn is_enabled(pool: &SqlitePool, name: &str) -> Result<bool> {
+    let value = db::config::get(pool, &format!("{}{}", SECTIONS_KEY_PREFIX, name)).await?;
+    Ok(value.is_none_or(|v| v.parse().unwrap_or(true)))
+}
+
+/// Validate a value for a `summary.sections.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_config_value(key: &str, value: &str) -> Result<()> {
+    let Some(name) = key.strip_prefix(SECTIONS_KEY_PREFIX) else {
+        return Ok(());
+    };
+    let names: Vec<&str> = sections().iter().map(|s| s.name()).collect();
+    if !names.contains(&name) {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Unknown summary section '{}'. Expected one of: {}",
+            name,
+            names.join(", ")
+        )));
+    }
+    value.parse::<bool>().map(|_| ()).map_err(|_| {
+        GranaryError::InvalidArgument(format!(
+            "Invalid {} '{}'. Expected true or false",
+            key, value
+        ))
+    })
+}
+
+/// Task counts by status, priority, label, assignee and author, and
+/// estimate rollups
+struct StateSection;
+
+impl SummarySection for StateSection {
+    fn name(&self) -> &'static str {
+        "state"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut by_status = StatusCounts::default();
+            let mut by_priority = PriorityCounts::default();
+            let mut by_label = std::collections::BTreeMap::new();
+            let mut by_assignee = std::collections::BTreeMap::new();
+            let mut by_author = std::collections::BTreeMap::<String, AuthorCounts>::new();
+
+            for task in cx.tasks {
+                if task.status != "done" {
+                    for label in &task.labels {
+                        *by_label.entry(label.clone()).or_insert(0) += 1;
+                    }
+                    if let Some(owner) = &task.owner {
+                        *by_assignee.entry(owner.clone()).or_insert(0) += 1;
+                    }
+                }
+                if let Some(author) = &task.author {
+                    by_author.entry(author.clone()).or_default().tasks += 1;
+                }
+                match task.status.as_str() {
+                    "todo" => by_status.todo += 1,
+                    "in_progress" => by_status.in_progress += 1,
+                    "done" => by_status.done += 1,
+                    "blocked" => by_status.blocked += 1,
+                    _ => {}
+                }
+                match task.priority.as_str() {
+                    "P0" => by_priority.p0 += 1,
+                    "P1" => by_priority.p1 += 1,
+                    "P2" => by_priority.p2 += 1,
+                    "P3" => by_priority.p3 += 1,
+                    "P4" => by_priority.p4 += 1,
+                    _ => {}
+                }
+            }
+
+            // Count comments on the tasks in scope
+            let task_ids: std::collections::HashSet<&str> =
+                cx.tasks.iter().map(|t| t.id.as_str()).collect();
+            for (task_id, author) in db::comments::list_task_authors(cx.pool).await? {
+                if task_ids.contains(task_id.as_str()) {
+                    by_author.entry(author).or_default().comments += 1;
+                }
+            }
+
+            summary.state = StateSummary {
+                total_tasks: cx.tasks.len(),
+                by_status,
+                by_priority,
+                by_label,
+                by_assignee,
+                by_author,
+                estimates: estimate_rollups(cx.pool, cx.tasks).await?,
+            };
+            cx.budget.charge(&summary.state);
+            Ok(())
+        })
+    }
+}
+
+/// The session's focus task, and the tasks set aside for it on the focus
+/// stack
+struct FocusSection;
+
+impl SummarySection for FocusSection {
+    fn name(&self) -> &'static str {
+        "focus"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            if let Some(focus_id) = cx.session.and_then(|s| s.focus_task_id.as_deref()) {
+                summary.focus_task = get_task(cx.pool, focus_id).await.ok();
+            }
+            cx.budget.charge(&summary.focus_task);
+            let mut stack = cx.session.map(|s| s.focus_stack_vec()).unwrap_or_default();
+            stack.pop();
+            let mut interrupted = Vec::new();
+            for id in stack.iter().rev() {
+                if let Ok(task) = get_task(cx.pool, id).await {
+                    interrupted.push(task);
+                }
+            }
+            summary.interrupted = cx.budget.fit(interrupted);
+            Ok(())
+        })
+    }
+}
+
+/// Blocked tasks, and tasks with a blocked reason
+struct BlockersSection;
+
+impl SummarySection for BlockersSection {
+    fn name(&self) -> &'static str {
+        "blockers"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let blockers: Vec<Task> = cx
+                .tasks
+                .iter()
+                .filter(|t| t.blocked_reason.is_some() || t.status == "blocked")
+                .cloned()
+                .collect();
+            summary.blockers = cx.budget.fit(blockers);
+            Ok(())
+        })
+    }
+}
+
+/// Open, unblocked tasks to pick up next; a token budget decides how many
+/// fit, otherwise five
+struct NextActionsSection;
+
+impl SummarySection for NextActionsSection {
+    fn name(&self) -> &'static str {
+        "next_actions"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let max_actions = if cx.budget.is_limited() {
+                usize::MAX
+            } else {
+                5
+            };
+            let next_actions: Vec<Task> = cx
+                .tasks
+                .iter()
+                .filter(|t| {
+                    (t.status == "todo" || t.status == "in_progress") && t.blocked_reason.is_none()
+                })
+                .take(max_actions)
+                .cloned()
+                .collect();
+            summary.next_actions = cx.budget.fit(next_actions);
+            Ok(())
+        })
+    }
+}
+
+/// Tasks due within `reminders.due_soon`, including overdue ones
+struct DueSoonSection;
+
+impl SummarySection for DueSoonSection {
+    fn name(&self) -> &'static str {
+        "due_soon"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let due_by = cx.now + reminder_service::due_soon_window(cx.pool).await?;
+            let mut due_soon: Vec<Task> = cx
+                .tasks
+                .iter()
+                .filter(|t| t.is_due_before(due_by))
+                .cloned()
+                .collect();
+            due_soon.sort_by(|a, b| a.due_at.cmp(&b.due_at));
+            summary.due_soon = cx.budget.fit(due_soon);
+            Ok(())
+        })
+    }
+}
+
+/// Open tasks with no activity within `summary.stale_after`
+struct StaleSection;
+
+impl SummarySection for StaleSection {
+    fn name(&self) -> &'static str {
+        "stale"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let stale = stale_tasks(cx.pool, cx.tasks, cx.now).await?;
+            summary.stale = cx.budget.fit(stale);
+            Ok(())
+        })
+    }
+}
+
+/// The latest decision comments
+struct DecisionsSection;
+
+impl SummarySection for DecisionsSection {
+    fn name(&self) -> &'static str {
+        "decisions"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut recent_decisions = Vec::new();
+            for comment in db::comments::list_by_kind(cx.pool, "decision")
+                .await?
+                .into_iter()
+                .take(5)
+            {
+                let thread_root = db::comments::thread_root(cx.pool, &comment.id).await?;
+                recent_decisions.push(RecentDecision {
+                    comment,
+                    thread_root,
+                });
+            }
+            summary.recent_decisions = cx.budget.fit(recent_decisions);
+            Ok(())
+        })
+    }
+}
+
+/// Artifacts recorded on the tasks in scope
+struct ArtifactsSection;
+
+impl SummarySection for ArtifactsSection {
+    fn name(&self) -> &'static str {
+        "artifacts"
+    }
+
+    fn build<'a>(
+        &'a self,
+        cx: &'a mut SectionContext<'_>,
+        summary: &'a mut SummaryOutput,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut recent_artifacts = Vec::new();
+            for task in cx.tasks.iter().take(10) {
+                let artifacts = db::artifacts::list_by_parent(cx.pool, &task.id).await?;
+                recent_artifacts.extend(artifacts);
+            }
+            recent_artifacts.truncate(5);
+            summary.recent_artifacts = cx.budget.fit(recent_artifacts);
+            Ok(())
+        })
+    }
+}
+
+/// Open tasks among `tasks` with no activity within `summary.stale_after`,
+/// least recently active first
+async fn stale_tasks(
+    pool: &SqlitePool,
+    tasks: &[Task],
+    now: DateTime<Utc>,
+) -> Result<Vec<StaleTask>> {
+    let stale_after = db::config::get(pool, STALE_AFTER_KEY)
+        .await?
+        .unwrap_or_else(|| DEFAULT_STALE_AFTER.to_string());
+    let cutoff = datetime::parse_since_relative_to(&stale_after, &DateSettings::default(), now)?;
+
+    let by_id: std::collections::HashMap<&str, &Task> =
+        tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    let mut stale = Vec::new();
+    for (task_id, last_activity) in db::tasks::list_stale(pool, &cutoff).await? {
+        let Some(task) = by_id.get(task_id.as_str()) else {
+            continue;
+        };
+        let idle_days = DateTime::parse_from_rfc3339(&last_activity)
+            .map(|at| (now - at.with_timezone(&Utc)).num_days())
+            .unwrap_or_default();
+        stale.push(StaleTask {
+            id: task.id.clone(),
+            title: task.title.clone(),
+            status: task.status.clone(),
+            priority: task.priority.clone(),
+            last_activity,
+            idle_days,
+        });
+    }
+    Ok(stale)
+}
+
+/// Roll task estimates up per project, then per initiative containing those
+/// projects. Tasks without an estimate are left out.
+async fn estimate_rollups(pool: &SqlitePool, tasks: &[Task]) -> Result<Vec<EstimateRollup>> {
+    let mut by_project = std::collections::BTreeMap::<&str, (f64, f64)>::new();
+    for task in tasks {
+        if let Some(estimate) = task.estimate {
+            let entry = by_project.entry(task.project_id.as_str()).or_default();
+            entry.0 += estimate;
+            if task.status != "done" {
+                entry.1 += estimate;
+            }
+        }
+    }
+
+    let mut projects = Vec::new();
+    let mut initiatives = std::collections::BTreeMap::<String, EstimateRollup>::new();
+    for (project_id, (total, remaining)) in by_project {
+        let name = db::projects::get(pool, project_id)
+            .await?
+            .map(|p| p.name)
+            .unwrap_or_else(|| project_id.to_string());
+        for initiative in db::initiative_projects::list_initiatives(pool, project_id).await? {
+            let rollup =
+                initiatives
+                    .entry(initiative.id.clone())
+                    .or_insert_with(|| EstimateRollup {
+                        kind: "initiative".to_string(),
+                        id: initiative.id,
+                        name: initiative.name,
+                        total: 0.0,
+                        remaining: 0.0,
+                    });
+            rollup.total += total;
+            rollup.remaining += remaining;
+        }
+        projects.push(EstimateRollup {
+            kind: "project".to_string(),
+            id: project_id.to_string(),
+            name,
+            total,
+            remaining,
+        });
+    }
+    projects.extend(initiatives.into_values());
+    Ok(projects)
+}
@@ -1,184 +1,178 @@
+use chrono::Utc;
 use sqlx::SqlitePool;
 
 use crate::db;
-use crate::error::Result;
+use crate::error::{GranaryError, Result};
+use crate::models::workspace_config::ModeConfig;
 use crate::models::*;
 use crate::output::json::{
-    BlockerInfo, ContextOutput, HandoffOutput, PriorityCounts, SessionSummary, StateSummary,
-    StatusCounts, SteeringInfo, SummaryOutput,
+    AttachmentInfo, BlockerInfo, ContextOutput, HandoffOutput, SessionSummary, SteeringInfo,
+    SummaryOutput,
 };
-use crate::services::{Workspace, get_current_session, get_scope_by_type, get_task};
+use crate::services::context_spec::ContextSelection;
+use crate::services::datetime::{self, DateSettings};
+use crate::services::summary_sections::{self, SectionContext};
+use crate::services::tokens::TokenBudget;
+use crate::services::{
+    Workspace, attach_labels, get_current_session, get_scope_by_type, get_task, mode_config,
+    related_tasks, workspace_config,
+};
+
+/// Config key for how long an open task can go without activity before the
+/// summary lists it as stale
+pub const STALE_AFTER_KEY: &str = "summary.stale_after";
+
+/// Stale threshold when `summary.stale_after` is not set
+pub const DEFAULT_STALE_AFTER: &str = "14d";
+
+/// Validate a value for a `summary.*` config key.
+///
+/// Returns `Ok(())` for keys this module does not own.
+pub fn validate_summary_config_value(key: &str, value: &str) -> Result<()> {
+    summary_sections::validate_config_value(key, value)?;
+    if key != STALE_AFTER_KEY {
+        return Ok(());
+    }
+    datetime::parse_since(value, &DateSettings::default())
+        .map(|_| ())
+        .map_err(|_| {
+            GranaryError::InvalidArgument(format!(
+                "Invalid {} '{}'. Expected a duration such as 14d or 2w",
+                key, value
+            ))
+        })
+}
 
-/// Generate a summary for the current session or workspace
+/// Generate a summary for the current session or workspace.
+///
+/// Archived tasks are left out unless `include_archived` is set. The
+/// sections in [`summary_sections::sections`] are built in order, skipping
+/// those turned off in config, and fill their lists until `budget` runs out.
 pub async fn generate_summary(
     pool: &SqlitePool,
     workspace: &Workspace,
-    token_budget: Option<usize>,
+    mut budget: TokenBudget,
+    include_archived: bool,
 ) -> Result<SummaryOutput> {
     let current_session = get_current_session(pool, workspace).await?;
 
     // Get tasks based on session scope or all tasks
-    let tasks = if let Some(ref session) = current_session {
+    let mut tasks = if let Some(ref session) = current_session {
         let project_ids = get_scope_by_type(pool, &session.id, ScopeItemType::Project).await?;
         if project_ids.is_empty() {
-            db::tasks::list_all(pool).await?
+            db::tasks::list_all(pool, include_archived).await?
         } else {
             let mut all_tasks = Vec::new();
             for project_id in project_ids {
-                let project_tasks = db::tasks::list_by_project(pool, &project_id).await?;
+                let project_tasks =
+                    db::tasks::list_by_project(pool, &project_id, include_archived).await?;
                 all_tasks.extend(project_tasks);
             }
             all_tasks
         }
     } else {
-        db::tasks::list_all(pool).await?
+        db::tasks::list_all(pool, include_archived).await?
     };
+    attach_labels(pool, &mut tasks).await?;
 
-    // Calculate state summary
-    let mut by_status = StatusCounts::default();
-    let mut by_priority = PriorityCounts::default();
-
-    for task in &tasks {
-        match task.status.as_str() {
-            "todo" => by_status.todo += 1,
-            "in_progress" => by_status.in_progress += 1,
-            "done" => by_status.done += 1,
-            "blocked" => by_status.blocked += 1,
-            _ => {}
-        }
-        match task.priority.as_str() {
-            "P0" => by_priority.p0 += 1,
-            "P1" => by_priority.p1 += 1,
-            "P2" => by_priority.p2 += 1,
-            "P3" => by_priority.p3 += 1,
-            "P4" => by_priority.p4 += 1,
-            _ => {}
-        }
-    }
-
-    let state = StateSummary {
-        total_tasks: tasks.len(),
-        by_status,
-        by_priority,
+    let config = workspace_config::load(workspace)?;
+    let mode = current_session
+        .as_ref()
+        .and_then(|s| mode_config(&config, s));
+    let mut summary = SummaryOutput {
+        session: current_session
+            .as_ref()
+            .map(|s| session_summary(s.clone(), mode)),
+        ..Default::default()
     };
+    budget.charge(&summary.session);
 
-    // Get focus task
-    let focus_task = if let Some(ref session) = current_session {
-        if let Some(ref focus_id) = session.focus_task_id {
-            get_task(pool, focus_id).await.ok()
-        } else {
-            None
-        }
-    } else {
-        None
+    let mut cx = SectionContext {
+        pool,
+        session: current_session.as_ref(),
+        tasks: &tasks,
+        now: Utc::now(),
+        budget,
     };
-
-    // Get blockers
-    let blockers: Vec<Task> = tasks
-        .iter()
-        .filter(|t| t.blocked_reason.is_some() || t.status == "blocked")
-        .cloned()
-        .collect();
-
-    // Get next actionable tasks (limit based on token budget)
-    let max_actions = token_budget.map(|b| b / 100).unwrap_or(5).max(3);
-    let next_actions: Vec<Task> = tasks
-        .iter()
-        .filter(|t| (t.status == "todo" || t.status == "in_progress") && t.blocked_reason.is_none())
-        .take(max_actions)
-        .cloned()
-        .collect();
-
-    // Get recent decisions
-    let recent_decisions = db::comments::list_by_kind(pool, "decision").await?;
-    let recent_decisions: Vec<Comment> = recent_decisions.into_iter().take(5).collect();
-
-    // Get recent artifacts (across all tasks in scope)
-    let mut recent_artifacts = Vec::new();
-    for task in tasks.iter().take(10) {
-        let artifacts = db::artifacts::list_by_parent(pool, &task.id).await?;
-        recent_artifacts.extend(artifacts);
+    let mut sections = summary_sections::sections();
+    // Sections the mode emphasizes go first, so they are trimmed last
+    if let Some(mode) = mode {
+        sections.sort_by_key(|s| {
+            mode.emphasize
+                .iter()
+                .position(|name| name == s.name())
+                .unwrap_or(usize::MAX)
+        });
+    }
+    for section in sections {
+        if summary_sections::is_enabled(pool, section.name()).await? {
+            section.build(&mut cx, &mut summary).await?;
+        }
     }
-    recent_artifacts.truncate(5);
-
-    let session_summary = current_session.map(|s| SessionSummary {
-        id: s.id,
-        name: s.name,
-        mode: s.mode,
-        owner: s.owner,
-        focus_task_id: s.focus_task_id,
-    });
 
-    Ok(SummaryOutput {
-        session: session_summary,
-        state,
-        focus_task,
-        blockers,
-        next_actions,
-        recent_decisions,
-        recent_artifacts,
-    })
+    Ok(summary)
+}
+
+/// The session as shown in summaries and context packs, with what its
+/// mode emphasizes and asks of agents
+fn session_summary(session: Session, mode: Option<&ModeConfig>) -> SessionSummary {
+    SessionSummary {
+        focus_stack: session.focus_stack_vec(),
+        id: session.id,
+        name: session.name,
+        mode: session.mode,
+        owner: session.owner,
+        focus_task_id: session.focus_task_id,
+        emphasize: mode.map(|m| m.emphasize.clone()).unwrap_or_default(),
+        instructions: mode.and_then(|m| m.prompt.clone()),
+    }
 }
 
-/// Generate a context pack for LLM consumption
+/// Generate a context pack for LLM consumption.
+///
+/// `selection` picks the sections and the items in them, each section
+/// holding at most `max_items` unless its spec sets a limit. Steering is
+/// always included; the other sections are filled in order of importance
+/// until `budget` runs out.
 pub async fn generate_context(
     pool: &SqlitePool,
     workspace: &Workspace,
-    include: Option<Vec<String>>,
+    selection: &ContextSelection,
     max_items: Option<usize>,
+    mut budget: TokenBudget,
 ) -> Result<ContextOutput> {
     let current_session = get_current_session(pool, workspace).await?;
     let max = max_items.unwrap_or(50);
 
-    // Determine what to include
-    let include_set: std::collections::HashSet<&str> = include
-        .as_ref()
-        .map(|v| v.iter().map(|s| s.as_str()).collect())
-        .unwrap_or_else(|| {
-            [
-                "projects",
-                "tasks",
-                "comments",
-                "decisions",
-                "blockers",
-                "artifacts",
-            ]
-            .into_iter()
-            .collect()
-        });
-
     // Get projects
-    let projects = if include_set.contains("projects") {
-        if let Some(ref session) = current_session {
+    let projects = if selection.includes("projects") {
+        let projects = if let Some(ref session) = current_session {
             let project_ids = get_scope_by_type(pool, &session.id, ScopeItemType::Project).await?;
             let mut projects = Vec::new();
-            for id in project_ids.iter().take(max) {
+            for id in &project_ids {
                 if let Ok(p) = crate::services::get_project(pool, id).await {
                     projects.push(p);
                 }
             }
             projects
         } else {
-            db::projects::list(pool, false)
-                .await?
-                .into_iter()
-                .take(max)
-                .collect()
-        }
+            db::projects::list(pool, false).await?
+        };
+        selection.select("projects", projects, max)
     } else {
         Vec::new()
     };
 
     // Get tasks
-    let tasks = if include_set.contains("tasks") {
-        if let Some(ref session) = current_session {
+    let tasks = if selection.includes("tasks") {
+        let tasks = if let Some(ref session) = current_session {
             let project_ids = get_scope_by_type(pool, &session.id, ScopeItemType::Project).await?;
             let task_ids = get_scope_by_type(pool, &session.id, ScopeItemType::Task).await?;
 
             let mut all_tasks = Vec::new();
 
             // Add explicitly pinned tasks
-            for id in task_ids.iter().take(max) {
+            for id in &task_ids {
                 if let Ok(t) = get_task(pool, id).await {
                     all_tasks.push(t);
                 }
@@ -186,56 +180,45 @@ pub async fn generate_context(
 
             // Add tasks from pinned projects
             for project_id in project_ids {
-                let project_tasks = db::tasks::list_by_project(pool, &project_id).await?;
+                let project_tasks = db::tasks::list_by_project(pool, &project_id, false).await?;
                 for task in project_tasks {
                     if !all_tasks.iter().any(|t| t.id == task.id) {
                         all_tasks.push(task);
                     }
-                    if all_tasks.len() >= max {
-                        break;
-                    }
                 }
             }
 
-            all_tasks.truncate(max);
             all_tasks
         } else {
-            db::tasks::list_all(pool)
-                .await?
-                .into_iter()
-                .take(max)
-                .collect()
-        }
+            db::tasks::list_all(pool, false).await?
+        };
+        selection.select("tasks", tasks, max)
     } else {
         Vec::new()
     };
 
     // Get comments
-    let comments = if include_set.contains("comments") {
+    let comments = if selection.includes("comments") {
         let mut all_comments = Vec::new();
         for task in tasks.iter().take(10) {
             let task_comments = db::comments::list_by_parent(pool, &task.id).await?;
             all_comments.extend(task_comments);
         }
-        all_comments.truncate(max);
-        all_comments
+        selection.select("comments", all_comments, max)
     } else {
         Vec::new()
     };
 
     // Get decisions
-    let decisions = if include_set.contains("decisions") {
-        db::comments::list_by_kind(pool, "decision")
-            .await?
-            .into_iter()
-            .take(max)
-            .collect()
+    let decisions = if selection.includes("decisions") {
+        let decisions = db::comments::list_by_kind(pool, "decision").await?;
+        selection.select("decisions", decisions, max)
     } else {
         Vec::new()
     };
 
     // Get blockers
-    let blockers: Vec<BlockerInfo> = if include_set.contains("blockers") {
+    let blockers: Vec<BlockerInfo> = if selection.includes("blockers") {
         let mut blocker_info = Vec::new();
         for task in &tasks {
             if task.blocked_reason.is_some() || task.status == "blocked" {
@@ -248,21 +231,50 @@ pub async fn generate_context(
                 });
             }
         }
-        blocker_info.truncate(max);
-        blocker_info
+        selection.select("blockers", blocker_info, max)
     } else {
         Vec::new()
     };
 
     // Get artifacts
-    let artifacts = if include_set.contains("artifacts") {
+    let artifacts = if selection.includes("artifacts") {
         let mut all_artifacts = Vec::new();
         for task in tasks.iter().take(10) {
             let task_artifacts = db::artifacts::list_by_parent(pool, &task.id).await?;
             all_artifacts.extend(task_artifacts);
         }
-        all_artifacts.truncate(max);
-        all_artifacts
+        selection.select("artifacts", all_artifacts, max)
+    } else {
+        Vec::new()
+    };
+
+    // Get attached files, with absolute paths
+    let attachments = if selection.includes("attachments") {
+        let mut all_attachments = Vec::new();
+        for task in tasks.iter().take(10) {
+            for attachment in db::attachments::list_by_task(pool, &task.id).await? {
+                all_attachments.push(AttachmentInfo {
+                    path: attachment
+                        .absolute_path(&workspace.root)
+                        .display()
+                        .to_string(),
+                    task_id: attachment.task_id,
+                    name: attachment.name,
+                });
+            }
+        }
+        selection.select("attachments", all_attachments, max)
+    } else {
+        Vec::new()
+    };
+
+    // Get typed links between tasks
+    let related = if selection.includes("related") {
+        let mut all_related = Vec::new();
+        for task in tasks.iter().take(10) {
+            all_related.extend(related_tasks(pool, &task.id).await?);
+        }
+        selection.select("related", all_related, max)
     } else {
         Vec::new()
     };
@@ -286,14 +298,23 @@ pub async fn generate_context(
     )
     .await?;
 
-    let session_summary = current_session.map(|s| SessionSummary {
-        id: s.id,
-        name: s.name,
-        mode: s.mode,
-        owner: s.owner,
-        focus_task_id: s.focus_task_id,
+    let config = workspace_config::load(workspace)?;
+    let session_summary = current_session.map(|s| {
+        let mode = mode_config(&config, &s);
+        session_summary(s, mode)
     });
 
+    budget.charge(&session_summary);
+    budget.charge(&steering);
+    let tasks = budget.fit(tasks);
+    let blockers = budget.fit(blockers);
+    let projects = budget.fit(projects);
+    let decisions = budget.fit(decisions);
+    let related = budget.fit(related);
+    let comments = budget.fit(comments);
+    let attachments = budget.fit(attachments);
+    let artifacts = budget.fit(artifacts);
+
     Ok(ContextOutput {
         session: session_summary,
         projects,
@@ -302,11 +323,16 @@ pub async fn generate_context(
         decisions,
         blockers,
         artifacts,
+        attachments,
+        related,
         steering,
     })
 }
 
-/// Generate a handoff document for agent delegation
+/// Generate a handoff document for agent delegation.
+///
+/// The tasks, instructions and steering are always included; comments for
+/// context are kept, newest first, while they fit in `budget`.
 pub async fn generate_handoff(
     pool: &SqlitePool,
     to: &str,
@@ -314,6 +340,7 @@ pub async fn generate_handoff(
     constraints: Option<&str>,
     acceptance_criteria: Option<&str>,
     output_schema: Option<serde_json::Value>,
+    mut budget: TokenBudget,
 ) -> Result<HandoffOutput> {
     let workspace = Workspace::find()?;
     let mut tasks = Vec::new();
@@ -338,6 +365,11 @@ pub async fn generate_handoff(
     // - Task-attached steering (for the specific tasks being handed off)
     let steering = fetch_steering_for_handoff(pool, &workspace, task_ids).await?;
 
+    budget.charge(&tasks);
+    budget.charge(&(constraints, acceptance_criteria, &output_schema));
+    budget.charge(&steering);
+    let context = budget.fit(context);
+
     Ok(HandoffOutput {
         to: to.to_string(),
         tasks,
@@ -441,3 +473,259 @@ fn convert_to_steering_info(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{create_project, create_task, find_tasks};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_summary_breaks_down_by_assignee_and_author() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Team".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut ids = Vec::new();
+        for owner in [Some("alice"), Some("alice"), None] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: "Task".to_string(),
+                    owner: owner.map(str::to_string),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+        sqlx::query("UPDATE tasks SET author = 'bob' WHERE id != ?")
+            .bind(&ids[2])
+            .execute(&pool)
+            .await
+            .unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+        db::comments::create(
+            &pool,
+            &Comment {
+                id: format!("{}-comment-1", ids[2]),
+                parent_type: "task".to_string(),
+                parent_id: ids[2].clone(),
+                parent_comment_id: None,
+                comment_number: 1,
+                kind: "note".to_string(),
+                content: "Looks good".to_string(),
+                author: Some("carol".to_string()),
+                meta: None,
+                created_at: now.clone(),
+                updated_at: now,
+                version: 1,
+            },
+        )
+        .await
+        .unwrap();
+
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        assert_eq!(summary.state.by_assignee.get("alice"), Some(&2));
+        assert_eq!(summary.state.by_assignee.len(), 1);
+        assert_eq!(summary.state.by_author["bob"].tasks, 2);
+        assert_eq!(summary.state.by_author["carol"].comments, 1);
+
+        let query: SearchQuery = "author:bob".parse().unwrap();
+        assert_eq!(find_tasks(&pool, &query).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_summary_lists_stale_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Stale".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for title in ["Forgotten", "Discussed", "Finished"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+        let month_ago = (Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        sqlx::query("UPDATE tasks SET created_at = ?")
+            .bind(&month_ago)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // A recent comment keeps a task fresh; done tasks are never stale
+        let now = Utc::now().to_rfc3339();
+        db::comments::create(
+            &pool,
+            &Comment {
+                id: format!("{}-comment-1", ids[1]),
+                parent_type: "task".to_string(),
+                parent_id: ids[1].clone(),
+                parent_comment_id: None,
+                comment_number: 1,
+                kind: "note".to_string(),
+                content: "Still on it".to_string(),
+                author: None,
+                meta: None,
+                created_at: now.clone(),
+                updated_at: now,
+                version: 1,
+            },
+        )
+        .await
+        .unwrap();
+        crate::services::update_task(
+            &pool,
+            &ids[2],
+            UpdateTask {
+                status: Some(TaskStatus::Done),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        let stale: Vec<&str> = summary.stale.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(stale, vec![ids[0].as_str()]);
+        assert_eq!(summary.stale[0].idle_days, 30);
+
+        let query = SearchQuery {
+            stale_since: Some(datetime::parse_since("14d", &DateSettings::default()).unwrap()),
+            ..Default::default()
+        };
+        let found = find_tasks(&pool, &query).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, ids[0]);
+
+        db::config::set(&pool, STALE_AFTER_KEY, "60d")
+            .await
+            .unwrap();
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        assert!(summary.stale.is_empty());
+        assert!(validate_summary_config_value(STALE_AFTER_KEY, "soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_summary_sections_can_be_turned_off() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Sections".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Next up".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        crate::services::ready_task(&pool, &task.id, None)
+            .await
+            .unwrap();
+
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        assert_eq!(summary.next_actions.len(), 1);
+
+        db::config::set(&pool, "summary.sections.next_actions", "false")
+            .await
+            .unwrap();
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        assert!(summary.next_actions.is_empty());
+        assert_eq!(summary.state.total_tasks, 1);
+
+        assert!(validate_summary_config_value("summary.sections.stale", "false").is_ok());
+        assert!(validate_summary_config_value("summary.sections.stale", "maybe").is_err());
+        assert!(validate_summary_config_value("summary.sections.runs", "true").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_summary_follows_session_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        std::fs::write(
+            workspace_config::path(&workspace),
+            "[modes.review]\nemphasize = [\"decisions\", \"blockers\"]\nprompt = \"Check finished work.\"\n",
+        )
+        .unwrap();
+        let session = crate::services::create_session(
+            &pool,
+            CreateSession {
+                mode: SessionMode::Review,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        workspace.set_current_session(&session.id).unwrap();
+
+        let summary = generate_summary(&pool, &workspace, TokenBudget::default(), false)
+            .await
+            .unwrap();
+        let header = summary.session.unwrap();
+        assert_eq!(header.emphasize, vec!["decisions", "blockers"]);
+        assert_eq!(header.instructions.as_deref(), Some("Check finished work."));
+
+        let context = generate_context(
+            &pool,
+            &workspace,
+            &ContextSelection::default(),
+            None,
+            TokenBudget::default(),
+        )
+        .await
+        .unwrap();
+        let header = context.session.unwrap();
+        assert_eq!(header.instructions.as_deref(), Some("Check finished work."));
+    }
+}
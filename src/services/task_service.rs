@@ -3,11 +3,57 @@ use sqlx::SqlitePool;
 use crate::db::{self, counters};
 use crate::error::{GranaryError, Result};
 use crate::models::*;
+use crate::services::current_author;
+use crate::services::field_service::{attach_task_fields, validate_fields, write_fields};
+use crate::services::session_service::{check_status_writable, get_session};
+
+/// Reject negative or non-finite estimates
+fn check_estimate(estimate: f64) -> Result<f64> {
+    if estimate.is_finite() && estimate >= 0.0 {
+        Ok(estimate)
+    } else {
+        Err(GranaryError::InvalidArgument(format!(
+            "Estimate must be a non-negative number, got {}",
+            estimate
+        )))
+    }
+}
+
+/// Check that the caller in session `session_id` can move a task to
+/// `status`: no other session holds a claim on the task, and the mode of the
+/// caller's session allows the status
+async fn check_status_change(
+    pool: &SqlitePool,
+    task: &Task,
+    status: &TaskStatus,
+    session_id: Option<&str>,
+) -> Result<()> {
+    check_session_claim(task, session_id)?;
+    check_status_writable(pool, status, session_id).await
+}
+
+/// Reject changing a task claimed by another session than `session_id`.
+/// Callers outside a session can't change claimed tasks.
+pub fn check_session_claim(task: &Task, session_id: Option<&str>) -> Result<()> {
+    match &task.claim_session_id {
+        Some(holder) if task.is_claimed() && session_id != Some(holder.as_str()) => {
+            Err(GranaryError::Conflict(format!(
+                "Task {} is claimed by session {} until {}. Take over the claim with 'granary tasks claim {} --steal'.",
+                task.id,
+                holder,
+                task.claim_lease_expires_at.as_deref().unwrap_or_default(),
+                task.id
+            )))
+        }
+        _ => Ok(()),
+    }
+}
 
 /// Create a new task in a project
 pub async fn create_task(pool: &SqlitePool, input: CreateTask) -> Result<Task> {
     // Verify project exists
     let _project = crate::services::get_project(pool, &input.project_id).await?;
+    input.estimate.map(check_estimate).transpose()?;
 
     // Get next task number
     let scope = format!("project:{}:task", input.project_id);
@@ -32,19 +78,25 @@ pub async fn create_task(pool: &SqlitePool, input: CreateTask) -> Result<Task> {
         status: TaskStatus::Draft.as_str().to_string(),
         priority: input.priority.as_str().to_string(),
         owner: input.owner,
+        author: current_author(),
         tags,
         blocked_reason: None,
         started_at: None,
         completed_at: None,
         due_at: input.due_at,
+        estimate: input.estimate,
         claim_owner: None,
         claim_claimed_at: None,
         claim_lease_expires_at: None,
+        claim_session_id: None,
         pinned: 0,
         focus_weight: 0,
+        archived_at: None,
         created_at: now.clone(),
         updated_at: now,
         version: 1,
+        labels: Vec::new(),
+        fields: Default::default(),
     };
 
     db::tasks::create(pool, &task).await?;
@@ -69,21 +121,28 @@ pub async fn create_task(pool: &SqlitePool, input: CreateTask) -> Result<Task> {
     Ok(task)
 }
 
-/// Get a task by ID
+/// Get a task by ID, with its labels
 pub async fn get_task(pool: &SqlitePool, id: &str) -> Result<Task> {
-    db::tasks::get(pool, id)
+    let mut task = db::tasks::get(pool, id)
         .await?
-        .ok_or_else(|| GranaryError::TaskNotFound(id.to_string()))
+        .ok_or_else(|| GranaryError::TaskNotFound(id.to_string()))?;
+    task.labels = db::labels::list_by_task(pool, &task.id).await?;
+    attach_task_fields(pool, std::slice::from_mut(&mut task)).await?;
+    Ok(task)
 }
 
 /// List tasks in a project
-pub async fn list_tasks_by_project(pool: &SqlitePool, project_id: &str) -> Result<Vec<Task>> {
-    db::tasks::list_by_project(pool, project_id).await
+pub async fn list_tasks_by_project(
+    pool: &SqlitePool,
+    project_id: &str,
+    include_archived: bool,
+) -> Result<Vec<Task>> {
+    db::tasks::list_by_project(pool, project_id, include_archived).await
 }
 
 /// List all tasks
-pub async fn list_all_tasks(pool: &SqlitePool) -> Result<Vec<Task>> {
-    db::tasks::list_all(pool).await
+pub async fn list_all_tasks(pool: &SqlitePool, include_archived: bool) -> Result<Vec<Task>> {
+    db::tasks::list_all(pool, include_archived).await
 }
 
 /// List tasks with filters
@@ -92,8 +151,9 @@ pub async fn list_tasks_filtered(
     status: Option<&str>,
     priority: Option<&str>,
     owner: Option<&str>,
+    include_archived: bool,
 ) -> Result<Vec<Task>> {
-    db::tasks::list_filtered(pool, status, priority, owner).await
+    db::tasks::list_filtered(pool, status, priority, owner, include_archived).await
 }
 
 /// List subtasks of a task
@@ -102,9 +162,21 @@ pub async fn list_subtasks(pool: &SqlitePool, parent_task_id: &str) -> Result<Ve
 }
 
 /// Update a task
-pub async fn update_task(pool: &SqlitePool, id: &str, updates: UpdateTask) -> Result<Task> {
+pub async fn update_task(
+    pool: &SqlitePool,
+    id: &str,
+    updates: UpdateTask,
+    session_id: Option<&str>,
+) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
     let old_status = task.status.clone();
+    let fields = validate_fields(pool, &updates.fields).await?;
+    updates.estimate.map(check_estimate).transpose()?;
+    if let Some(status) = &updates.status
+        && status.as_str() != old_status
+    {
+        check_status_change(pool, &task, status, session_id).await?;
+    }
 
     if let Some(title) = updates.title {
         task.title = title;
@@ -130,6 +202,9 @@ pub async fn update_task(pool: &SqlitePool, id: &str, updates: UpdateTask) -> Re
     if let Some(due) = updates.due_at {
         task.due_at = Some(due);
     }
+    if let Some(estimate) = updates.estimate {
+        task.estimate = Some(estimate);
+    }
     if let Some(pinned) = updates.pinned {
         task.pinned = if pinned { 1 } else { 0 };
     }
@@ -145,6 +220,14 @@ pub async fn update_task(pool: &SqlitePool, id: &str, updates: UpdateTask) -> Re
         });
     }
 
+    let mut payload = serde_json::json!({
+        "old_status": old_status,
+        "new_status": task.status,
+    });
+    if !fields.is_empty() {
+        payload["fields"] = write_fields(pool, EntityType::Task, &task.id, &fields).await?;
+    }
+
     // Log event
     let event_type = if updates.status.is_some() && old_status != task.status {
         EventType::TaskStatusChanged
@@ -160,10 +243,7 @@ pub async fn update_task(pool: &SqlitePool, id: &str, updates: UpdateTask) -> Re
             entity_id: task.id.clone(),
             actor: None,
             session_id: None,
-            payload: serde_json::json!({
-                "old_status": old_status,
-                "new_status": task.status,
-            }),
+            payload,
         },
     )
     .await?;
@@ -172,7 +252,7 @@ pub async fn update_task(pool: &SqlitePool, id: &str, updates: UpdateTask) -> Re
 }
 
 /// Mark a draft task as ready (transition Draft -> Todo)
-pub async fn ready_task(pool: &SqlitePool, id: &str) -> Result<Task> {
+pub async fn ready_task(pool: &SqlitePool, id: &str, session_id: Option<&str>) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
 
     // Check if task is in Draft status
@@ -183,6 +263,7 @@ pub async fn ready_task(pool: &SqlitePool, id: &str) -> Result<Task> {
         )));
     }
 
+    check_status_change(pool, &task, &TaskStatus::Todo, session_id).await?;
     task.status = TaskStatus::Todo.as_str().to_string();
 
     let updated = db::tasks::update(pool, &task).await?;
@@ -215,7 +296,12 @@ pub async fn ready_task(pool: &SqlitePool, id: &str) -> Result<Task> {
 }
 
 /// Start a task (set status to in_progress)
-pub async fn start_task(pool: &SqlitePool, id: &str, owner: Option<String>) -> Result<Task> {
+pub async fn start_task(
+    pool: &SqlitePool,
+    id: &str,
+    owner: Option<String>,
+    session_id: Option<&str>,
+) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
 
     // Check if task is in Draft status
@@ -241,6 +327,7 @@ pub async fn start_task(pool: &SqlitePool, id: &str, owner: Option<String>) -> R
         return Err(GranaryError::UnmetDependencies(unmet_ids.join(", ")));
     }
 
+    check_status_change(pool, &task, &TaskStatus::InProgress, session_id).await?;
     task.status = TaskStatus::InProgress.as_str().to_string();
     if task.started_at.is_none() {
         task.started_at = Some(chrono::Utc::now().to_rfc3339());
@@ -269,9 +356,15 @@ pub async fn start_task(pool: &SqlitePool, id: &str, owner: Option<String>) -> R
 }
 
 /// Complete a task
-pub async fn complete_task(pool: &SqlitePool, id: &str, comment: Option<&str>) -> Result<Task> {
+pub async fn complete_task(
+    pool: &SqlitePool,
+    id: &str,
+    comment: Option<&str>,
+    session_id: Option<&str>,
+) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
 
+    check_status_change(pool, &task, &TaskStatus::Done, session_id).await?;
     task.status = TaskStatus::Done.as_str().to_string();
     task.completed_at = Some(chrono::Utc::now().to_rfc3339());
     task.blocked_reason = None;
@@ -289,16 +382,33 @@ pub async fn complete_task(pool: &SqlitePool, id: &str, comment: Option<&str>) -
             id: comment_id,
             parent_type: "task".to_string(),
             parent_id: id.to_string(),
+            parent_comment_id: None,
             comment_number,
             kind: CommentKind::Progress.as_str().to_string(),
             content: content.to_string(),
-            author: task.owner.clone(),
+            author: task.owner.clone().or_else(current_author),
             meta: None,
             created_at: now.clone(),
             updated_at: now,
             version: 1,
         };
         db::comments::create(pool, &comment).await?;
+
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::CommentCreated,
+                entity_type: EntityType::Comment,
+                entity_id: comment.id.clone(),
+                actor: comment.author.clone(),
+                session_id: None,
+                payload: serde_json::json!({
+                    "kind": comment.kind,
+                    "parent_id": comment.parent_id,
+                }),
+            },
+        )
+        .await?;
     }
 
     // Log event
@@ -319,9 +429,15 @@ pub async fn complete_task(pool: &SqlitePool, id: &str, comment: Option<&str>) -
 }
 
 /// Block a task
-pub async fn block_task(pool: &SqlitePool, id: &str, reason: &str) -> Result<Task> {
+pub async fn block_task(
+    pool: &SqlitePool,
+    id: &str,
+    reason: &str,
+    session_id: Option<&str>,
+) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
 
+    check_status_change(pool, &task, &TaskStatus::Blocked, session_id).await?;
     task.status = TaskStatus::Blocked.as_str().to_string();
     task.blocked_reason = Some(reason.to_string());
 
@@ -347,15 +463,17 @@ pub async fn block_task(pool: &SqlitePool, id: &str, reason: &str) -> Result<Tas
 }
 
 /// Unblock a task
-pub async fn unblock_task(pool: &SqlitePool, id: &str) -> Result<Task> {
+pub async fn unblock_task(pool: &SqlitePool, id: &str, session_id: Option<&str>) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
 
     // Return to in_progress if it was started, otherwise todo
-    task.status = if task.started_at.is_some() {
-        TaskStatus::InProgress.as_str().to_string()
+    let status = if task.started_at.is_some() {
+        TaskStatus::InProgress
     } else {
-        TaskStatus::Todo.as_str().to_string()
+        TaskStatus::Todo
     };
+    check_status_change(pool, &task, &status, session_id).await?;
+    task.status = status.as_str().to_string();
     task.blocked_reason = None;
 
     db::tasks::update(pool, &task).await?;
@@ -377,6 +495,74 @@ pub async fn unblock_task(pool: &SqlitePool, id: &str) -> Result<Task> {
     get_task(pool, id).await
 }
 
+/// Archive a task, hiding it from default listings without changing its status
+pub async fn archive_task(pool: &SqlitePool, id: &str) -> Result<Task> {
+    let mut task = get_task(pool, id).await?;
+    if task.is_archived() {
+        return Err(GranaryError::Conflict(format!(
+            "Task {} is already archived",
+            id
+        )));
+    }
+
+    task.archived_at = Some(chrono::Utc::now().to_rfc3339());
+    if !db::tasks::update(pool, &task).await? {
+        return Err(GranaryError::VersionMismatch {
+            expected: task.version,
+            found: task.version + 1,
+        });
+    }
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskArchived,
+            entity_type: EntityType::Task,
+            entity_id: task.id.clone(),
+            actor: None,
+            session_id: None,
+            payload: serde_json::json!({}),
+        },
+    )
+    .await?;
+
+    get_task(pool, id).await
+}
+
+/// Restore an archived task
+pub async fn unarchive_task(pool: &SqlitePool, id: &str) -> Result<Task> {
+    let mut task = get_task(pool, id).await?;
+    if !task.is_archived() {
+        return Err(GranaryError::Conflict(format!(
+            "Task {} is not archived",
+            id
+        )));
+    }
+
+    task.archived_at = None;
+    if !db::tasks::update(pool, &task).await? {
+        return Err(GranaryError::VersionMismatch {
+            expected: task.version,
+            found: task.version + 1,
+        });
+    }
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskUnarchived,
+            entity_type: EntityType::Task,
+            entity_id: task.id.clone(),
+            actor: None,
+            session_id: None,
+            payload: serde_json::json!({}),
+        },
+    )
+    .await?;
+
+    get_task(pool, id).await
+}
+
 /// Claim a task with a lease
 pub async fn claim_task(
     pool: &SqlitePool,
@@ -400,6 +586,7 @@ pub async fn claim_task(
     let now = chrono::Utc::now();
     task.claim_owner = Some(owner.to_string());
     task.claim_claimed_at = Some(now.to_rfc3339());
+    task.claim_session_id = None;
 
     if let Some(minutes) = lease_minutes {
         let expires = now + chrono::Duration::minutes(minutes as i64);
@@ -427,6 +614,73 @@ pub async fn claim_task(
     get_task(pool, id).await
 }
 
+/// Claim a task for a session for `ttl_minutes`. While the claim lasts,
+/// other sessions can't change the task's status.
+///
+/// A task claimed by anyone else is a conflict unless `steal` is set.
+pub async fn claim_task_for_session(
+    pool: &SqlitePool,
+    id: &str,
+    session_id: &str,
+    ttl_minutes: u32,
+    steal: bool,
+) -> Result<Task> {
+    let mut task = get_task(pool, id).await?;
+    let session = get_session(pool, session_id).await?;
+    if session.is_closed() {
+        return Err(GranaryError::Conflict(format!(
+            "Session {} is closed",
+            session_id
+        )));
+    }
+
+    let mut stolen_from = None;
+    if let Some(claim) = task.claim_info().filter(|_| task.is_claimed())
+        && claim.session_id.as_deref() != Some(session_id)
+    {
+        if !steal {
+            return Err(GranaryError::ClaimConflict {
+                owner: claim.owner,
+                expires_at: claim.lease_expires_at.unwrap_or_default(),
+            });
+        }
+        stolen_from = Some(claim.owner);
+    }
+
+    let now = chrono::Utc::now();
+    let expires = now + chrono::Duration::minutes(ttl_minutes as i64);
+    task.claim_owner = Some(session_id.to_string());
+    task.claim_claimed_at = Some(now.to_rfc3339());
+    task.claim_lease_expires_at = Some(expires.to_rfc3339());
+    task.claim_session_id = Some(session_id.to_string());
+
+    if !db::tasks::update(pool, &task).await? {
+        return Err(GranaryError::VersionMismatch {
+            expected: task.version,
+            found: task.version + 1,
+        });
+    }
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::TaskClaimed,
+            entity_type: EntityType::Task,
+            entity_id: task.id.clone(),
+            actor: session.owner,
+            session_id: Some(session_id.to_string()),
+            payload: serde_json::json!({
+                "lease_minutes": ttl_minutes,
+                "session_id": session_id,
+                "stolen_from": stolen_from,
+            }),
+        },
+    )
+    .await?;
+
+    get_task(pool, id).await
+}
+
 /// Extend the lease on a claimed task (heartbeat)
 pub async fn heartbeat_task(pool: &SqlitePool, id: &str, lease_minutes: u32) -> Result<Task> {
     let mut task = get_task(pool, id).await?;
@@ -454,6 +708,7 @@ pub async fn release_task(pool: &SqlitePool, id: &str) -> Result<Task> {
     task.claim_owner = None;
     task.claim_claimed_at = None;
     task.claim_lease_expires_at = None;
+    task.claim_session_id = None;
 
     db::tasks::update(pool, &task).await?;
 
@@ -480,11 +735,14 @@ pub async fn add_dependency(pool: &SqlitePool, task_id: &str, depends_on: &str)
     let _task = get_task(pool, task_id).await?;
     let _dep = get_task(pool, depends_on).await?;
 
-    // Check for cycles
-    if db::dependencies::would_create_cycle(pool, task_id, depends_on).await? {
+    // Reject cycles, naming the path back to the task
+    if let Some(path) = dependency_path(pool, depends_on, task_id).await? {
         return Err(GranaryError::DependencyCycle(format!(
-            "Adding dependency {} -> {} would create a cycle",
-            task_id, depends_on
+            "Adding dependency {} -> {} would create a cycle: {} -> {}",
+            task_id,
+            depends_on,
+            task_id,
+            path.join(" -> ")
         )));
     }
 
@@ -509,6 +767,166 @@ pub async fn add_dependency(pool: &SqlitePool, task_id: &str, depends_on: &str)
     Ok(())
 }
 
+/// The shortest chain of dependencies leading from `from` to `to`, both
+/// included, if `from` depends on `to` directly or transitively
+async fn dependency_path(pool: &SqlitePool, from: &str, to: &str) -> Result<Option<Vec<String>>> {
+    let mut previous: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let mut queue = std::collections::VecDeque::from([from.to_string()]);
+    while let Some(id) = queue.pop_front() {
+        if id == to {
+            let mut path = vec![id];
+            while let Some(prev) = previous.get(path.last().expect("path is not empty")) {
+                path.push(prev.clone());
+            }
+            path.reverse();
+            return Ok(Some(path));
+        }
+        for dep in db::dependencies::list(pool, &id).await? {
+            if dep.depends_on_task_id != from && !previous.contains_key(&dep.depends_on_task_id) {
+                previous.insert(dep.depends_on_task_id.clone(), id.clone());
+                queue.push_back(dep.depends_on_task_id);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Tasks and their dependencies, for `granary tasks graph`. For a project,
+/// every task of the project and the dependencies between them; otherwise
+/// every unarchived task with a dependency or a dependent.
+pub async fn dependency_graph(pool: &SqlitePool, project_id: Option<&str>) -> Result<TaskGraph> {
+    let (tasks, dependencies) = match project_id {
+        Some(project_id) => {
+            let project = db::projects::get(pool, project_id)
+                .await?
+                .ok_or_else(|| GranaryError::ProjectNotFound(project_id.to_string()))?;
+            (
+                db::tasks::list_by_project(pool, &project.id, false).await?,
+                db::dependencies::list_by_project(pool, &project.id).await?,
+            )
+        }
+        None => {
+            let tasks = db::tasks::list_all(pool, false).await?;
+            let ids: std::collections::HashSet<&str> =
+                tasks.iter().map(|t| t.id.as_str()).collect();
+            let dependencies: Vec<TaskDependency> = db::dependencies::list_all(pool)
+                .await?
+                .into_iter()
+                .filter(|d| {
+                    ids.contains(d.task_id.as_str()) && ids.contains(d.depends_on_task_id.as_str())
+                })
+                .collect();
+            let linked: std::collections::HashSet<&str> = dependencies
+                .iter()
+                .flat_map(|d| [d.task_id.as_str(), d.depends_on_task_id.as_str()])
+                .collect();
+            let tasks = tasks
+                .iter()
+                .filter(|t| linked.contains(t.id.as_str()))
+                .cloned()
+                .collect();
+            (tasks, dependencies)
+        }
+    };
+    Ok(TaskGraph {
+        project_id: project_id.map(str::to_string),
+        tasks,
+        dependencies,
+    })
+}
+
+/// Link two tasks with a typed relationship: `task_id <kind> linked_task_id`.
+/// Linking an already linked pair is a no-op.
+pub async fn link_tasks(
+    pool: &SqlitePool,
+    task_id: &str,
+    linked_task_id: &str,
+    kind: TaskLinkKind,
+) -> Result<Vec<RelatedTask>> {
+    let task = get_task(pool, task_id).await?;
+    let linked = get_task(pool, linked_task_id).await?;
+    if task.id == linked.id {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Cannot link {} to itself",
+            task.id
+        )));
+    }
+
+    // A symmetric link recorded from the other task already covers this one
+    let exists = kind.is_symmetric()
+        && db::task_links::list_for_task(pool, &task.id)
+            .await?
+            .iter()
+            .any(|l| l.kind == kind.as_str() && l.task_id == linked.id);
+    if !exists && db::task_links::add(pool, &task.id, &linked.id, kind.as_str()).await? {
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::TaskLinked,
+                entity_type: EntityType::Task,
+                entity_id: task.id.clone(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({
+                    "kind": kind.as_str(),
+                    "linked_task_id": linked.id,
+                }),
+            },
+        )
+        .await?;
+    }
+
+    related_tasks(pool, &task.id).await
+}
+
+/// Remove every link between two tasks. Returns whether any link existed.
+pub async fn unlink_tasks(pool: &SqlitePool, task_id: &str, linked_task_id: &str) -> Result<bool> {
+    let removed = db::task_links::remove_between(pool, task_id, linked_task_id).await?;
+    if removed > 0 {
+        db::events::create(
+            pool,
+            &CreateEvent {
+                event_type: EventType::TaskUnlinked,
+                entity_type: EntityType::Task,
+                entity_id: task_id.to_string(),
+                actor: None,
+                session_id: None,
+                payload: serde_json::json!({
+                    "linked_task_id": linked_task_id,
+                }),
+            },
+        )
+        .await?;
+    }
+    Ok(removed > 0)
+}
+
+/// Tasks linked to a task, each relationship described from the task's side
+pub async fn related_tasks(pool: &SqlitePool, task_id: &str) -> Result<Vec<RelatedTask>> {
+    let mut related = Vec::new();
+    for link in db::task_links::list_for_task(pool, task_id).await? {
+        let Some(kind) = link.kind_enum() else {
+            continue;
+        };
+        let (relation, other_id) = if link.task_id == task_id {
+            (kind.as_str(), link.linked_task_id)
+        } else {
+            (kind.inverse(), link.task_id)
+        };
+        let Some(other) = db::tasks::get(pool, &other_id).await? else {
+            continue;
+        };
+        related.push(RelatedTask {
+            task_id: task_id.to_string(),
+            relation: relation.to_string(),
+            related_task_id: other.id,
+            title: other.title,
+            status: other.status,
+        });
+    }
+    Ok(related)
+}
+
 /// Remove a dependency from a task
 pub async fn remove_dependency(pool: &SqlitePool, task_id: &str, depends_on: &str) -> Result<bool> {
     let removed = db::dependencies::remove(pool, task_id, depends_on).await?;
@@ -603,3 +1021,270 @@ pub async fn get_tasks_with_deps(
     }
     Ok(result)
 }
+
+/// Record an artifact on a task
+pub async fn add_artifact(
+    pool: &SqlitePool,
+    parent_id: &str,
+    artifact_type: &str,
+    path: &str,
+    description: Option<String>,
+) -> Result<Artifact> {
+    let scope = format!("task:{}:artifact", parent_id);
+    let artifact_number = counters::next(pool, &scope).await?;
+    let id = generate_artifact_id(parent_id, artifact_number);
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let art_type: ArtifactType = artifact_type.parse().unwrap_or_default();
+
+    let artifact = Artifact {
+        id: id.clone(),
+        parent_type: "task".to_string(),
+        parent_id: parent_id.to_string(),
+        artifact_number,
+        artifact_type: art_type.as_str().to_string(),
+        path_or_url: path.to_string(),
+        description,
+        meta: None,
+        created_at: now,
+    };
+
+    db::artifacts::create(pool, &artifact).await?;
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::ArtifactAdded,
+            entity_type: EntityType::Artifact,
+            entity_id: artifact.id.clone(),
+            actor: current_author(),
+            session_id: None,
+            payload: serde_json::json!({
+                "parent_id": artifact.parent_id,
+                "artifact_type": artifact.artifact_type,
+                "path_or_url": artifact.path_or_url,
+            }),
+        },
+    )
+    .await?;
+
+    Ok(artifact)
+}
+
+/// Remove an artifact
+pub async fn remove_artifact(pool: &SqlitePool, id: &str) -> Result<()> {
+    let artifact = db::artifacts::get(pool, id)
+        .await?
+        .ok_or_else(|| GranaryError::ArtifactNotFound(id.to_string()))?;
+
+    db::artifacts::delete(pool, id).await?;
+
+    db::events::create(
+        pool,
+        &CreateEvent {
+            event_type: EventType::ArtifactRemoved,
+            entity_type: EntityType::Artifact,
+            entity_id: artifact.id.clone(),
+            actor: current_author(),
+            session_id: None,
+            payload: serde_json::json!({
+                "parent_id": artifact.parent_id,
+                "path_or_url": artifact.path_or_url,
+            }),
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{Workspace, create_project};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_dependency_cycle_names_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Cycles".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for title in ["A", "B", "C", "Unrelated"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+        add_dependency(&pool, &ids[0], &ids[1]).await.unwrap();
+        add_dependency(&pool, &ids[1], &ids[2]).await.unwrap();
+
+        let err = add_dependency(&pool, &ids[2], &ids[0]).await.unwrap_err();
+        let GranaryError::DependencyCycle(message) = err else {
+            panic!("expected a dependency cycle, got {:?}", err);
+        };
+        assert!(message.ends_with(&format!(
+            "{} -> {} -> {} -> {}",
+            ids[2], ids[0], ids[1], ids[2]
+        )));
+        assert!(matches!(
+            add_dependency(&pool, &ids[0], &ids[0]).await,
+            Err(GranaryError::DependencyCycle(_))
+        ));
+
+        let graph = dependency_graph(&pool, None).await.unwrap();
+        assert_eq!(graph.tasks.len(), 3);
+        assert_eq!(graph.dependencies.len(), 2);
+        let graph = dependency_graph(&pool, Some(&project.id)).await.unwrap();
+        assert_eq!(graph.tasks.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_task_links_read_both_ways() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Links".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut ids = Vec::new();
+        for title in ["Original", "Copy", "Regression"] {
+            let task = create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(task.id);
+        }
+
+        link_tasks(&pool, &ids[1], &ids[0], TaskLinkKind::Duplicates)
+            .await
+            .unwrap();
+        link_tasks(&pool, &ids[2], &ids[0], TaskLinkKind::CausedBy)
+            .await
+            .unwrap();
+        link_tasks(&pool, &ids[0], &ids[2], TaskLinkKind::RelatesTo)
+            .await
+            .unwrap();
+        // The reverse of a symmetric link is the same link
+        let related = link_tasks(&pool, &ids[2], &ids[0], TaskLinkKind::RelatesTo)
+            .await
+            .unwrap();
+        assert_eq!(related.len(), 2);
+
+        let relations: Vec<(String, String)> = related_tasks(&pool, &ids[0])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.relation, r.related_task_id))
+            .collect();
+        assert_eq!(
+            relations,
+            vec![
+                ("duplicated_by".to_string(), ids[1].clone()),
+                ("causes".to_string(), ids[2].clone()),
+                ("relates_to".to_string(), ids[2].clone()),
+            ]
+        );
+        assert!(matches!(
+            link_tasks(&pool, &ids[0], &ids[0], TaskLinkKind::RelatesTo).await,
+            Err(GranaryError::InvalidArgument(_))
+        ));
+
+        assert!(unlink_tasks(&pool, &ids[0], &ids[2]).await.unwrap());
+        assert!(!unlink_tasks(&pool, &ids[0], &ids[2]).await.unwrap());
+        assert_eq!(related_tasks(&pool, &ids[0]).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_session_claims() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "Claims".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let task = create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id,
+                title: "Shared".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let mut sessions = Vec::new();
+        for name in ["agent-1", "agent-2"] {
+            let session = crate::services::create_session(
+                &pool,
+                CreateSession {
+                    name: Some(name.to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+            sessions.push(session.id);
+        }
+
+        let task = claim_task_for_session(&pool, &task.id, &sessions[0], 30, false)
+            .await
+            .unwrap();
+        assert_eq!(task.claim_session_id.as_deref(), Some(sessions[0].as_str()));
+        assert!(check_session_claim(&task, Some(&sessions[0])).is_ok());
+        assert!(matches!(
+            check_session_claim(&task, Some(&sessions[1])),
+            Err(GranaryError::Conflict(_))
+        ));
+        assert!(check_session_claim(&task, None).is_err());
+
+        assert!(matches!(
+            claim_task_for_session(&pool, &task.id, &sessions[1], 30, false).await,
+            Err(GranaryError::ClaimConflict { .. })
+        ));
+        let task = claim_task_for_session(&pool, &task.id, &sessions[1], 30, true)
+            .await
+            .unwrap();
+        assert!(check_session_claim(&task, Some(&sessions[1])).is_ok());
+        assert!(check_session_claim(&task, Some(&sessions[0])).is_err());
+
+        // Once released, anyone can change it
+        let task = release_task(&pool, &task.id).await.unwrap();
+        assert!(task.claim_session_id.is_none());
+        assert!(check_session_claim(&task, Some(&sessions[0])).is_ok());
+    }
+}
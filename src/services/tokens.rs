@@ -0,0 +1,166 @@
+//! Token counting for output budgets.
+//!
+//! `--token-budget` on `summary`, `context` and `handoff` is measured with a
+//! real tokenizer for the model picked by `--model`. GPT-4o uses its own
+//! `o200k_base` encoding. Anthropic does not publish Claude's tokenizer, so
+//! `claude` counts with `cl100k_base` and adds a margin, erring on the side of
+//! trimming too much rather than overflowing the budget.
+
+use serde::Serialize;
+use tiktoken_rs::CoreBPE;
+
+/// Counts the tokens a model would see for a piece of text
+pub trait TokenCounter: Send + Sync {
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Models `--model` can count tokens for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TokenModel {
+    #[default]
+    Gpt4o,
+    Claude,
+}
+
+impl TokenModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenModel::Gpt4o => "gpt-4o",
+            TokenModel::Claude => "claude",
+        }
+    }
+
+    /// The counter for this model
+    pub fn counter(&self) -> Box<dyn TokenCounter> {
+        match self {
+            TokenModel::Gpt4o => Box::new(BpeCounter {
+                bpe: tiktoken_rs::o200k_base_singleton(),
+                margin_percent: 0,
+            }),
+            TokenModel::Claude => Box::new(BpeCounter {
+                bpe: tiktoken_rs::cl100k_base_singleton(),
+                margin_percent: CLAUDE_MARGIN_PERCENT,
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for TokenModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "gpt-4o" | "gpt4o" => Ok(TokenModel::Gpt4o),
+            "claude" => Ok(TokenModel::Claude),
+            other => Err(format!(
+                "Unknown model '{}'. Expected gpt-4o or claude",
+                other
+            )),
+        }
+    }
+}
+
+/// How much to add to `cl100k_base` counts when approximating Claude
+const CLAUDE_MARGIN_PERCENT: usize = 15;
+
+/// Counts with a tiktoken byte-pair encoding
+struct BpeCounter {
+    bpe: &'static CoreBPE,
+    margin_percent: usize,
+}
+
+impl TokenCounter for BpeCounter {
+    fn count(&self, text: &str) -> usize {
+        let tokens = self.bpe.encode_with_special_tokens(text).len();
+        tokens + (tokens * self.margin_percent).div_ceil(100)
+    }
+}
+
+/// Tokens left to spend on optional sections of an output
+pub struct TokenBudget {
+    counter: Box<dyn TokenCounter>,
+    remaining: Option<usize>,
+}
+
+impl Default for TokenBudget {
+    fn default() -> Self {
+        TokenBudget::new(None, TokenModel::default())
+    }
+}
+
+impl TokenBudget {
+    /// A budget of `limit` tokens, or an unlimited one
+    pub fn new(limit: Option<usize>, model: TokenModel) -> Self {
+        TokenBudget {
+            counter: model.counter(),
+            remaining: limit,
+        }
+    }
+
+    /// Whether a token limit was set
+    pub fn is_limited(&self) -> bool {
+        self.remaining.is_some()
+    }
+
+    /// Tokens `value` takes when serialized as JSON
+    pub fn count<T: Serialize>(&self, value: &T) -> usize {
+        serde_json::to_string(value)
+            .map(|text| self.counter.count(&text))
+            .unwrap_or_default()
+    }
+
+    /// Spend tokens on a part of the output that is always included
+    pub fn charge<T: Serialize>(&mut self, value: &T) {
+        if self.remaining.is_some() {
+            let cost = self.count(value);
+            self.remaining = self.remaining.map(|r| r.saturating_sub(cost));
+        }
+    }
+
+    /// Keep items in order while they fit, dropping the rest
+    pub fn fit<T: Serialize>(&mut self, items: Vec<T>) -> Vec<T> {
+        if self.remaining.is_none() {
+            return items;
+        }
+        let mut kept = Vec::with_capacity(items.len());
+        for item in items {
+            let cost = self.count(&item);
+            match self.remaining {
+                Some(remaining) if cost <= remaining => {
+                    self.remaining = Some(remaining - cost);
+                    kept.push(item);
+                }
+                _ => {
+                    self.remaining = Some(0);
+                    break;
+                }
+            }
+        }
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_keeps_items_that_fit() {
+        let counter = TokenModel::Gpt4o.counter();
+        assert_eq!(counter.count("hello world"), 2);
+        assert!(TokenModel::Claude.counter().count("hello world") > 2);
+        assert_eq!("Claude".parse(), Ok(TokenModel::Claude));
+        assert!("llama".parse::<TokenModel>().is_err());
+
+        let items = vec!["alpha beta".to_string(); 10];
+        let one = TokenBudget::new(None, TokenModel::Gpt4o).count(&items[0]);
+        let mut budget = TokenBudget::new(Some(one * 3 + 1), TokenModel::Gpt4o);
+        assert_eq!(budget.fit(items.clone()).len(), 3);
+        // Once an item is dropped, later sections get nothing
+        assert!(budget.fit(items.clone()).is_empty());
+
+        let mut unlimited = TokenBudget::new(None, TokenModel::Claude);
+        unlimited.charge(&items);
+        assert_eq!(unlimited.fit(items).len(), 10);
+    }
+}
@@ -0,0 +1,471 @@
+//! Web dashboard served by `granary serve`.
+//!
+//! A single page (`web_ui.html`, compiled into the binary) polls a small
+//! read-only JSON API: the task board, the workspace summary, and runs with
+//! their logs. Tasks and the summary come straight from the workspace
+//! database; runs come from the daemon, like in the TUI, and the runs view
+//! says so when the daemon isn't running.
+//!
+//! With `--graphql` (and the `graphql` feature), the GraphQL API in
+//! [`crate::services::graphql`] is served at `/graphql` as well: POST a query
+//! to it, or GET it for the schema in SDL. Mutations must carry the daemon
+//! auth token (`~/.granary/daemon/auth.token`) as `Authorization: Bearer
+//! <token>`. Each mutation request is saved as one `serve graphql`
+//! operation, attributed to the actor the server was started as, or to the
+//! `X-Granary-Actor` header when a client sends one. Status changes are made
+//! as the server's session, or the session in the `X-Granary-Session` header,
+//! so task claims and session modes apply.
+//!
+//! Requests whose `Host` isn't the address the server listens on are
+//! refused, so a web page can't reach the server by rebinding its own
+//! hostname to a loopback address.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use tokio::net::TcpListener;
+
+use crate::daemon::DaemonClient;
+use crate::error::{GranaryError, Result, exit_codes};
+use crate::models::{Actor, Board};
+use crate::services::tokens::{TokenBudget, TokenModel};
+use crate::services::{self, Workspace};
+
+const INDEX_HTML: &str = include_str!("web_ui.html");
+
+/// Default number of log lines shown for a run
+const DEFAULT_LOG_LINES: i32 = 200;
+
+struct WebState {
+    pool: SqlitePool,
+    workspace: Workspace,
+    /// Who GraphQL mutations are attributed to
+    #[cfg_attr(not(feature = "graphql"), allow(dead_code))]
+    actor: Actor,
+}
+
+#[derive(Deserialize)]
+struct BoardQuery {
+    project: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RunLogQuery {
+    lines: Option<i32>,
+}
+
+/// Whether this build can serve the GraphQL API
+pub fn graphql_supported() -> bool {
+    cfg!(feature = "graphql")
+}
+
+/// Build the dashboard router, with the GraphQL API when `graphql` holds the
+/// token its mutations must carry. GraphQL mutations are attributed to `actor`.
+pub fn router(
+    pool: SqlitePool,
+    workspace: Workspace,
+    actor: Actor,
+    graphql: Option<String>,
+) -> Result<Router> {
+    if graphql.is_some() && !graphql_supported() {
+        return Err(GranaryError::InvalidArgument(
+            "This granary was built without the `graphql` feature. Rebuild with `cargo install granary --features graphql`".to_string(),
+        ));
+    }
+    let state = Arc::new(WebState {
+        pool,
+        workspace,
+        actor,
+    });
+    #[cfg(feature = "graphql")]
+    let graphql_routes = graphql.map(|token| graphql_router(state.clone(), token));
+
+    let router = Router::new()
+        .route("/", get(index))
+        .route("/api/projects", get(projects))
+        .route("/api/board", get(board))
+        .route("/api/summary", get(summary))
+        .route("/api/runs", get(runs))
+        .route("/api/runs/{id}/log", get(run_log))
+        .with_state(state);
+
+    #[cfg(feature = "graphql")]
+    if let Some(routes) = graphql_routes {
+        return Ok(router.merge(routes));
+    }
+    Ok(router)
+}
+
+#[cfg(feature = "graphql")]
+fn graphql_router(state: Arc<WebState>, token: String) -> Router {
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use services::graphql::GranarySchema;
+
+    struct GraphqlState {
+        schema: GranarySchema,
+        web: Arc<WebState>,
+        token: String,
+    }
+
+    fn error(message: String) -> async_graphql::Response {
+        async_graphql::Response::from_errors(vec![async_graphql::ServerError::new(message, None)])
+    }
+
+    async fn execute(
+        State(state): State<Arc<GraphqlState>>,
+        headers: HeaderMap,
+        Json(mut request): Json<async_graphql::Request>,
+    ) -> Response {
+        if !is_mutation(&request) {
+            return Json(state.schema.execute(request).await).into_response();
+        }
+        // Only a caller holding the token may change the workspace, or say
+        // who the change is by
+        let authorized = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), state.token.as_bytes()));
+        if !authorized {
+            let message = "Mutations need the daemon auth token as a bearer token".to_string();
+            return (StatusCode::UNAUTHORIZED, Json(error(message))).into_response();
+        }
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        let mut actor = state.web.actor.clone();
+        if let Some(name) = header("x-granary-actor") {
+            actor.name = Some(name);
+        }
+        if let Some(session_id) = header("x-granary-session") {
+            actor.session_id = Some(session_id);
+        }
+        if let Some(session_id) = actor.session_id.clone() {
+            request = request.data(services::graphql::SessionId(session_id));
+        }
+        let schema = &state.schema;
+        let response = services::record_operation(
+            &state.web.workspace,
+            "serve graphql",
+            actor,
+            |pool| async move { Ok(schema.execute(request.data(pool)).await) },
+        )
+        .await;
+        Json(response.unwrap_or_else(|e| error(e.to_string()))).into_response()
+    }
+
+    async fn sdl(State(state): State<Arc<GraphqlState>>) -> String {
+        state.schema.sdl()
+    }
+
+    let state = Arc::new(GraphqlState {
+        schema: services::graphql::schema(state.pool.clone()),
+        web: state,
+        token,
+    });
+    Router::new()
+        .route("/graphql", post(execute).get(sdl))
+        .with_state(state)
+}
+
+/// Whether a GraphQL request may change the workspace. Requests that don't
+/// parse are executed as they are, for the schema to report the error.
+#[cfg(feature = "graphql")]
+fn is_mutation(request: &async_graphql::Request) -> bool {
+    use async_graphql::parser::types::OperationType;
+
+    async_graphql::parser::parse_query(&request.query).is_ok_and(|document| {
+        document
+            .operations
+            .iter()
+            .any(|(_, operation)| operation.node.ty == OperationType::Mutation)
+    })
+}
+
+/// Listen on `addr` for the dashboard
+pub async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    TcpListener::bind(addr)
+        .await
+        .map_err(|e| GranaryError::InvalidArgument(format!("Cannot listen on {}: {}", addr, e)))
+}
+
+#[cfg(feature = "graphql")]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Serve a dashboard router until Ctrl+C, letting requests in flight finish
+pub async fn serve_on(listener: TcpListener, router: Router) -> Result<()> {
+    let addr = listener.local_addr()?;
+    let router = router.layer(middleware::from_fn_with_state(addr, require_host));
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// Refuse requests addressed to another host. A page whose hostname was
+/// rebound to this address still sends its own name as the `Host`.
+async fn require_host(State(addr): State<SocketAddr>, request: Request, next: Next) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    if !host.is_some_and(|host| is_own_host(host, addr)) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(serde_json::json!({ "error": "Unexpected Host header" })),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+/// Whether a `Host` header names the address the server listens on. On an
+/// unspecified address (`0.0.0.0`) any host may be used to reach it.
+fn is_own_host(host: &str, addr: SocketAddr) -> bool {
+    if addr.ip().is_unspecified() {
+        return true;
+    }
+    let host = host.to_ascii_lowercase();
+    host == addr.to_string()
+        || (addr.ip().is_loopback() && host == format!("localhost:{}", addr.port()))
+}
+
+/// Map a granary error to an HTTP error with a JSON body
+fn error_response(error: GranaryError) -> Response {
+    let status = match error {
+        GranaryError::DaemonConnection(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => match error.exit_code() {
+            exit_codes::NOT_FOUND => StatusCode::NOT_FOUND,
+            exit_codes::USER_ERROR => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        },
+    };
+    (
+        status,
+        Json(serde_json::json!({ "error": error.to_string() })),
+    )
+        .into_response()
+}
+
+fn json_response<T: serde::Serialize>(result: Result<T>) -> Response {
+    match result {
+        Ok(body) => Json(body).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+async fn projects(State(state): State<Arc<WebState>>) -> Response {
+    json_response(services::list_projects(&state.pool, false).await)
+}
+
+async fn board(State(state): State<Arc<WebState>>, Query(query): Query<BoardQuery>) -> Response {
+    let result = async {
+        let tasks = match query.project.as_deref().filter(|p| !p.is_empty()) {
+            Some(id) => {
+                let project = services::get_project(&state.pool, id).await?;
+                services::list_tasks_by_project(&state.pool, &project.id, false).await?
+            }
+            None => services::list_all_tasks(&state.pool, false).await?,
+        };
+        Ok(Board::new(query.project, tasks))
+    }
+    .await;
+    json_response(result)
+}
+
+async fn summary(State(state): State<Arc<WebState>>) -> Response {
+    let budget = TokenBudget::new(None, TokenModel::default());
+    json_response(services::generate_summary(&state.pool, &state.workspace, budget, false).await)
+}
+
+async fn runs() -> Response {
+    let result = async {
+        let mut client = DaemonClient::connect().await?;
+        client.list_runs(None, None, false).await
+    }
+    .await;
+    json_response(result)
+}
+
+async fn run_log(Path(run_id): Path<String>, Query(query): Query<RunLogQuery>) -> Response {
+    let lines = query.lines.unwrap_or(DEFAULT_LOG_LINES);
+    let result = async {
+        let mut client = DaemonClient::connect().await?;
+        client.run_logs(&run_id, false, lines).await
+    }
+    .await;
+    match result {
+        Ok(log) => ([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], log).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateProject, CreateTask};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_serves_board_and_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Web".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        services::create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id.clone(),
+                title: "Show the board".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_on(
+            listener,
+            router(pool, workspace, Actor::default(), None).unwrap(),
+        ));
+
+        let page = reqwest::get(&base).await.unwrap().text().await.unwrap();
+        assert!(page.contains("<title>granary</title>"));
+
+        let board: serde_json::Value =
+            reqwest::get(format!("{}/api/board?project={}", base, project.id))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        assert_eq!(board["project_id"], project.id);
+        assert_eq!(board["drafts"], 1);
+
+        let missing = reqwest::get(format!("{}/api/board?project=nope-0000", base))
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), 404);
+
+        let summary: serde_json::Value = reqwest::get(format!("{}/api/summary", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(summary["state"]["total_tasks"], 1);
+
+        let rebound = reqwest::Client::new()
+            .get(format!("{}/api/board", base))
+            .header(header::HOST, "attacker.example")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rebound.status(), 403);
+    }
+
+    #[test]
+    fn test_is_own_host() {
+        let loopback: SocketAddr = "127.0.0.1:7777".parse().unwrap();
+        assert!(is_own_host("127.0.0.1:7777", loopback));
+        assert!(is_own_host("LOCALHOST:7777", loopback));
+        assert!(!is_own_host("localhost:8080", loopback));
+        assert!(!is_own_host("attacker.example:7777", loopback));
+        assert!(is_own_host("[::1]:7777", "[::1]:7777".parse().unwrap()));
+        assert!(is_own_host("box.lan:7777", "0.0.0.0:7777".parse().unwrap()));
+    }
+
+    #[cfg(feature = "graphql")]
+    #[tokio::test]
+    async fn test_graphql_mutation_is_an_operation() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let actor = Actor {
+            name: Some("server".to_string()),
+            session_id: None,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/graphql", listener.local_addr().unwrap());
+        tokio::spawn(serve_on(
+            listener,
+            router(pool.clone(), workspace, actor, Some("secret".to_string())).unwrap(),
+        ));
+
+        // Without the token, a mutation is refused and nothing is recorded
+        let client = reqwest::Client::new();
+        let refused = client
+            .post(&url)
+            .header("X-Granary-Actor", "mallory")
+            .json(
+                &serde_json::json!({ "query": r#"mutation { createProject(name: "X") { id } }"# }),
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(refused.status(), 401);
+        assert!(
+            services::list_projects(&pool, false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        for (query, by) in [
+            (r#"mutation { createProject(name: "Api") { id } }"#, None),
+            (
+                r#"mutation { createProject(name: "Cli") { id } }"#,
+                Some("alice"),
+            ),
+            ("{ projects { id } }", None),
+        ] {
+            let mut request = client
+                .post(&url)
+                .bearer_auth("secret")
+                .json(&serde_json::json!({ "query": query }));
+            if let Some(by) = by {
+                request = request.header("X-Granary-Actor", by);
+            }
+            let response: serde_json::Value = request.send().await.unwrap().json().await.unwrap();
+            assert!(response.get("errors").is_none(), "{}", response);
+        }
+
+        // One operation per mutation; the query changed nothing
+        let operations = services::list_undoable(&pool, 5).await.unwrap();
+        let actors: Vec<Option<&str>> = operations.iter().map(|o| o.actor.as_deref()).collect();
+        assert_eq!(actors, vec![Some("alice"), Some("server")]);
+        assert!(operations.iter().all(|o| o.command == "serve graphql"));
+    }
+}
@@ -5,7 +5,13 @@
 //! 2. Spawns runner processes to handle events
 //! 3. Manages concurrency limits
 //! 4. Handles retries with exponential backoff
-//! 5. Tracks run status and logs
+//! 5. Tracks run status, logs and resource usage
+//! 6. Fires runs on a cron schedule, if the worker has one
+//! 7. Debounces or throttles bursts of events into fewer runs
+//! 8. Records the files runs leave in their artifacts directory
+//! 9. For global workers, also polls every other registered workspace
+//! 10. Stops runs that are cancelled or time out with SIGTERM, killing them
+//!     if they outlast their runner's grace period
 //!
 //! # Architecture
 //!
@@ -26,22 +32,34 @@
 //! ```
 
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use sqlx::SqlitePool;
 use tokio::sync::watch;
 
 use crate::db;
 use crate::error::{GranaryError, Result};
-use crate::models::event::Event;
-use crate::models::run::{CreateRun, RunStatus, ScheduleRetry, UpdateRunStatus};
-use crate::models::worker::{UpdateWorkerStatus, Worker, WorkerStatus};
+use crate::models::event::{CreateEvent, EntityType, Event, EventType};
+use crate::models::global_config::{LogFormat, RunnerLimits, expand_env_vars};
+use crate::models::run::{CreateRun, Run, RunStatus, ScheduleRetry, UpdateRunStatus};
+use crate::models::worker::{PauseMode, RetryPolicy, UpdateWorkerStatus, Worker, WorkerStatus};
+use crate::services::datetime;
+use crate::services::event_batch::{self, EventBatch};
 use crate::services::event_poller::{EventPoller, EventPollerConfig, create_poller_for_worker};
 use crate::services::global_config;
 use crate::services::polled_events::PolledEventEmitter;
-use crate::services::runner::{RunnerHandle, spawn_runner};
+use crate::services::run_artifacts::{self, ARTIFACTS_ENV};
+use crate::services::run_progress::ProgressReader;
+use crate::services::runner::{
+    DEFAULT_GRACE_PERIOD, RunnerExit, RunnerHandle, check_command_allowed, load_env_file,
+    log_file_name, resolve_working_dir, spawn_runner_with_env,
+};
+use crate::services::runner_input;
+use crate::services::schedule::{self, SCHEDULE_EVENT_TYPE, Schedule};
 use crate::services::template;
+use crate::services::workspace::Workspace;
+use crate::services::workspace_config;
 
 /// Default base delay for exponential backoff (in seconds)
 const DEFAULT_BASE_DELAY_SECS: u64 = 5;
@@ -52,12 +70,23 @@ const DEFAULT_MAX_ATTEMPTS: i32 = 3;
 /// Default poll interval (in milliseconds)
 const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
 
+/// A run that was sent SIGTERM, because it was stopped or timed out
+struct StoppingRun {
+    /// When the run is killed if it hasn't exited
+    deadline: Instant,
+    /// Whether the deadline passed and the run was killed
+    killed: bool,
+    /// Whether the run exceeded the worker's timeout, rather than being
+    /// stopped with `granary run stop`
+    timed_out: bool,
+}
+
 /// Worker runtime configuration
 #[derive(Debug, Clone)]
 pub struct WorkerRuntimeConfig {
-    /// Base delay for exponential backoff retries
+    /// Base delay for exponential backoff retries, unless the worker has its own retry policy
     pub base_delay_secs: u64,
-    /// Maximum retry attempts for failed runs
+    /// Maximum retry attempts for failed runs, unless the worker has its own retry policy
     pub max_attempts: i32,
     /// Interval between event polls
     pub poll_interval: Duration,
@@ -95,6 +124,10 @@ pub struct WorkerRuntime {
     poller: EventPoller,
     /// Currently active runner handles
     active_runs: HashMap<String, RunnerHandle>,
+    /// Progress reports read from the logs of active runs
+    progress_readers: HashMap<String, ProgressReader>,
+    /// Active runs sent SIGTERM and waiting to exit
+    stopping: HashMap<String, StoppingRun>,
     /// Shutdown signal receiver
     shutdown_rx: watch::Receiver<bool>,
     /// Runtime configuration
@@ -103,6 +136,25 @@ pub struct WorkerRuntime {
     log_dir: PathBuf,
     /// Polled event emitter for task.next/project.next (None for regular events)
     polled_emitter: Option<PolledEventEmitter>,
+    /// Parsed cron schedule (None for purely event-driven workers)
+    schedule: Option<Schedule>,
+    /// Retry policy for failed runs
+    retry_policy: RetryPolicy,
+    /// Resource limits from the worker's runner config
+    limits: RunnerLimits,
+    /// How run output is written, from the worker's runner config
+    log_format: LogFormat,
+    /// Environment variables from the worker's runner config
+    env: Vec<(String, String)>,
+    /// Dotenv file from the worker's runner config, read for each run
+    env_file: Option<String>,
+    /// How long stopped runs have to exit after SIGTERM, from the worker's
+    /// runner config
+    grace_period: Duration,
+    /// Events held back by the worker's debounce or throttle
+    batch: EventBatch,
+    /// Pools of the other workspaces a global worker polls, by root
+    other_workspaces: HashMap<String, SqlitePool>,
 }
 
 impl WorkerRuntime {
@@ -148,16 +200,69 @@ impl WorkerRuntime {
                 None
             };
 
+        let schedule = worker
+            .schedule
+            .as_deref()
+            .map(Schedule::parse)
+            .transpose()?;
+
+        let retry_policy = if worker.retry_policy.is_some() {
+            worker.retry_policy_config()
+        } else {
+            RetryPolicy {
+                max_attempts: config.max_attempts,
+                base_delay_secs: config.base_delay_secs,
+                ..Default::default()
+            }
+        };
+
+        // Limits, log format, environment and grace period are read from
+        // the runner config when the worker starts
+        let runner = match worker.runner_name.as_deref() {
+            Some(name) => workspace_config::get_runner_in(Path::new(&worker.instance_path), name)?,
+            None => None,
+        };
+        let grace_period = runner
+            .as_ref()
+            .and_then(|runner| runner.grace_period_secs)
+            .map_or(DEFAULT_GRACE_PERIOD, Duration::from_secs);
+        let (limits, log_format, env, env_file) = match runner {
+            Some(runner) => (
+                runner.limits,
+                runner.log_format,
+                runner
+                    .env
+                    .into_iter()
+                    .map(|(key, value)| (key, expand_env_vars(&value)))
+                    .collect(),
+                runner.env_file,
+            ),
+            None => Default::default(),
+        };
+
+        let batch = EventBatch::for_worker(&worker);
+
         Ok(Self {
             worker,
             global_pool,
             workspace_pool,
             poller,
             active_runs: HashMap::new(),
+            progress_readers: HashMap::new(),
+            stopping: HashMap::new(),
             shutdown_rx,
             config,
             log_dir,
             polled_emitter,
+            schedule,
+            retry_policy,
+            limits,
+            log_format,
+            env,
+            env_file,
+            grace_period,
+            batch,
+            other_workspaces: HashMap::new(),
         })
     }
 
@@ -168,9 +273,18 @@ impl WorkerRuntime {
     /// - The workspace is deleted
     /// - An unrecoverable error occurs
     pub async fn run(&mut self) -> Result<()> {
-        // Mark worker as running
-        self.update_worker_status(WorkerStatus::Running, None)
-            .await?;
+        // Mark worker as running, unless it was paused before a restart
+        let status = if self.worker.is_paused() {
+            WorkerStatus::Paused
+        } else {
+            WorkerStatus::Running
+        };
+        self.update_worker_status(status, None).await?;
+
+        // Make sure a scheduled worker has a next fire time persisted
+        if self.schedule.is_some() && self.worker.next_fire_at.is_none() {
+            self.schedule_next_fire().await?;
+        }
 
         // Main event loop
         loop {
@@ -191,6 +305,11 @@ impl WorkerRuntime {
                         break;
                     }
 
+                    // Pick up pause and resume requests
+                    if let Err(e) = self.refresh_pause_state().await {
+                        eprintln!("[worker:{}] Error reading worker status: {}", self.worker.id, e);
+                    }
+
                     // Process pending retries
                     if let Err(e) = self.process_pending_retries().await {
                         eprintln!("[worker:{}] Error processing retries: {}", self.worker.id, e);
@@ -199,6 +318,27 @@ impl WorkerRuntime {
                     // Check for completed runs
                     self.check_completed_runs().await?;
 
+                    // Start queued runs in the slots that freed up
+                    if let Err(e) = self.promote_queued_runs().await {
+                        eprintln!("[worker:{}] Error starting queued runs: {}", self.worker.id, e);
+                    }
+
+                    if self.worker.is_paused() {
+                        // A paused worker leaves new events for when it
+                        // resumes, or skips them
+                        if self.worker.pause_mode() == PauseMode::Drop
+                            && let Err(e) = self.drop_events().await
+                        {
+                            eprintln!("[worker:{}] Error dropping events: {}", self.worker.id, e);
+                        }
+                        continue;
+                    }
+
+                    // Fire the schedule if it is due
+                    if let Err(e) = self.process_schedule().await {
+                        eprintln!("[worker:{}] Error processing schedule: {}", self.worker.id, e);
+                    }
+
                     // Poll and handle new events
                     if let Err(e) = self.poll_and_handle_events().await {
                         eprintln!("[worker:{}] Error polling events: {}", self.worker.id, e);
@@ -219,32 +359,269 @@ impl WorkerRuntime {
 
     /// Poll for new events and handle them.
     async fn poll_and_handle_events(&mut self) -> Result<()> {
+        // Schedule-only workers have no event subscription
+        if self.worker.event_type == SCHEDULE_EVENT_TYPE {
+            return Ok(());
+        }
+
         let events = if let Some(ref mut emitter) = self.polled_emitter {
             // Use polled event emitter for task.next/project.next
-            match self.worker.event_type.as_str() {
+            let events = match self.worker.event_type.as_str() {
                 "task.next" => emitter.poll_task_next(&self.workspace_pool, None).await?,
                 "project.next" => emitter.poll_project_next(&self.workspace_pool).await?,
                 _ => vec![],
-            }
+            };
+            events
+                .into_iter()
+                .filter(|e| self.poller.matches(e))
+                .collect()
         } else {
             // Use regular event poller for other event types
             self.poller.poll().await?
         };
 
+        if self.batch.is_enabled() {
+            let now = Instant::now();
+            for event in events {
+                self.batch.push(event, now);
+            }
+            return self.release_batch().await;
+        }
+
         for event in events {
             if let Err(e) = self.handle_event(event).await {
                 eprintln!("[worker:{}] Error handling event: {}", self.worker.id, e);
             }
         }
 
+        if self.worker.global {
+            for (root, event) in self.poll_other_workspaces().await? {
+                if let Err(e) = self.handle_event_in(event, Some(root)).await {
+                    eprintln!("[worker:{}] Error handling event: {}", self.worker.id, e);
+                }
+            }
+        }
+
         Ok(())
     }
 
-    /// Handle a single event by creating and spawning a run.
+    /// Reload the worker's status, which `granary workers pause` and
+    /// `resume` change in the database.
+    async fn refresh_pause_state(&mut self) -> Result<()> {
+        let Some(worker) = db::workers::get(&self.global_pool, &self.worker.id).await? else {
+            return Ok(());
+        };
+        let paused = worker.is_paused();
+        if paused != self.worker.is_paused() {
+            eprintln!(
+                "[worker:{}] {}",
+                self.worker.id,
+                if paused { "Paused" } else { "Resumed" }
+            );
+        }
+        self.worker.status = worker.status;
+        Ok(())
+    }
+
+    /// Skip the events that arrive while the worker is paused with
+    /// `--on-pause drop`, and any scheduled fire that falls due.
+    ///
+    /// Polled events (task.next, project.next) are recomputed on every poll,
+    /// so there is nothing to skip for them.
+    async fn drop_events(&mut self) -> Result<()> {
+        let is_due = self.worker.next_fire_at.as_deref().is_some_and(|due| {
+            chrono::DateTime::parse_from_rfc3339(due)
+                .map(|dt| dt <= chrono::Utc::now())
+                .unwrap_or(true)
+        });
+        if self.schedule.is_some() && is_due {
+            eprintln!(
+                "[worker:{}] Skipped scheduled run while paused",
+                self.worker.id
+            );
+            self.schedule_next_fire().await?;
+        }
+
+        if self.worker.event_type == SCHEDULE_EVENT_TYPE || self.polled_emitter.is_some() {
+            return Ok(());
+        }
+
+        let events = self.poller.poll().await?;
+        if let Some(last) = events.last() {
+            self.poller.acknowledge(last.id).await?;
+        }
+        let mut dropped = events.len();
+        if self.worker.global {
+            for (root, event) in self.poll_other_workspaces().await? {
+                db::workers::update_workspace_cursor(
+                    &self.global_pool,
+                    &self.worker.id,
+                    &root,
+                    event.id,
+                )
+                .await?;
+                dropped += 1;
+            }
+        }
+        if dropped > 0 {
+            eprintln!(
+                "[worker:{}] Dropped {} events while paused",
+                self.worker.id, dropped
+            );
+        }
+        Ok(())
+    }
+
+    /// Poll the registered workspaces other than the worker's own, for a
+    /// global worker.
+    ///
+    /// Each workspace has its own cursor. A workspace seen for the first time
+    /// starts at its newest event rather than replaying its history, and one
+    /// that cannot be opened is skipped until it can.
+    async fn poll_other_workspaces(&mut self) -> Result<Vec<(String, Event)>> {
+        let mut polled = Vec::new();
+        for registered in db::workspaces::list(&self.global_pool).await? {
+            let root = registered.root;
+            if root == self.worker.instance_path {
+                continue;
+            }
+            if !self.other_workspaces.contains_key(&root) {
+                let pool = match Workspace::open(&root) {
+                    Ok(workspace) => workspace.pool().await,
+                    Err(e) => Err(e),
+                };
+                let Ok(pool) = pool else {
+                    continue;
+                };
+                self.other_workspaces.insert(root.clone(), pool);
+            }
+            let pool = &self.other_workspaces[&root];
+
+            let cursor =
+                db::workers::get_workspace_cursor(&self.global_pool, &self.worker.id, &root)
+                    .await?;
+            let Some(cursor) = cursor else {
+                let latest = db::events::latest_id(pool).await?;
+                db::workers::update_workspace_cursor(
+                    &self.global_pool,
+                    &self.worker.id,
+                    &root,
+                    latest,
+                )
+                .await?;
+                continue;
+            };
+
+            let events =
+                db::events::list_since_id_by_type(pool, cursor, &self.worker.event_type).await?;
+            polled.extend(
+                events
+                    .into_iter()
+                    .filter(|e| self.poller.matches(e))
+                    .map(|e| (root.clone(), e)),
+            );
+        }
+        Ok(polled)
+    }
+
+    /// Start one run for the held events once the debounce and throttle
+    /// allow it.
+    async fn release_batch(&mut self) -> Result<()> {
+        let Some(events) = self.batch.take_due(Instant::now()) else {
+            return Ok(());
+        };
+        let count = events.len();
+        let Some(event) = event_batch::coalesce(events) else {
+            return Ok(());
+        };
+        if count > 1 {
+            eprintln!(
+                "[worker:{}] Coalesced {} events into one run",
+                self.worker.id, count
+            );
+        }
+        if let Err(e) = self.handle_event(event).await {
+            eprintln!("[worker:{}] Error handling event: {}", self.worker.id, e);
+        }
+        Ok(())
+    }
+
+    /// Fire a run if the schedule is due, then persist the next fire time.
+    ///
+    /// The next fire time is computed from now rather than from the missed
+    /// fire time, so a worker that was down for a while fires once to catch
+    /// up instead of replaying every missed occurrence.
+    async fn process_schedule(&mut self) -> Result<()> {
+        if self.schedule.is_none() {
+            return Ok(());
+        }
+
+        // No next fire time means the schedule will never fire again
+        let Some(due) = self.worker.next_fire_at.clone() else {
+            return Ok(());
+        };
+        let is_due = chrono::DateTime::parse_from_rfc3339(&due)
+            .map(|dt| dt <= chrono::Utc::now())
+            .unwrap_or(true);
+        if !is_due {
+            return Ok(());
+        }
+
+        if self.available_slots() == 0 {
+            eprintln!(
+                "[worker:{}] Skipping scheduled run at {}: concurrency limit reached",
+                self.worker.id, due
+            );
+        } else {
+            let event = schedule::fired_event(&self.worker, &due);
+            if let Err(e) = self.handle_event(event).await {
+                eprintln!(
+                    "[worker:{}] Error starting scheduled run: {}",
+                    self.worker.id, e
+                );
+            }
+        }
+
+        self.schedule_next_fire().await
+    }
+
+    /// Compute and persist the next fire time for a scheduled worker.
+    async fn schedule_next_fire(&mut self) -> Result<()> {
+        let Some(ref schedule) = self.schedule else {
+            return Ok(());
+        };
+
+        let timezone = datetime::resolve(Some(&self.workspace_pool))
+            .await
+            .map(|settings| settings.timezone)
+            .unwrap_or(chrono_tz::Tz::UTC);
+        let next = schedule
+            .next_after(chrono::Utc::now(), timezone)
+            .map(|dt| dt.to_rfc3339());
+
+        db::workers::update_next_fire(&self.global_pool, &self.worker.id, next.as_deref()).await?;
+        self.worker.next_fire_at = next;
+        Ok(())
+    }
+
+    /// Handle a single event from the worker's own workspace.
     async fn handle_event(&mut self, event: Event) -> Result<()> {
-        // Check concurrency limit
-        if self.active_runs.len() >= self.worker.concurrency as usize {
-            // Don't acknowledge the event - it will be picked up on next poll
+        self.handle_event_in(event, None).await
+    }
+
+    /// Handle a single event by creating and spawning a run.
+    ///
+    /// `workspace` is the root of the workspace the event comes from, when
+    /// it is not the worker's own; the run then executes there.
+    ///
+    /// When the worker is at its concurrency limit the run is created as
+    /// `pending` and started later by [`Self::promote_queued_runs`].
+    async fn handle_event_in(&mut self, event: Event, workspace: Option<String>) -> Result<()> {
+        let at_capacity = self.available_slots() == 0;
+
+        // Polled events are recomputed on every poll, so there is nothing to
+        // queue; the next free slot picks up whatever is next at that point
+        if at_capacity && event.id == 0 {
             return Ok(());
         }
 
@@ -260,28 +637,87 @@ impl WorkerRuntime {
             entity_id: event.entity_id.clone(),
             command: self.worker.command.clone(),
             args: resolved_args.clone(),
-            max_attempts: self.config.max_attempts,
+            max_attempts: self.retry_policy.max_attempts,
             log_path: Some(
                 self.log_dir
                     .join("run-placeholder.log")
                     .to_string_lossy()
                     .to_string(),
             ),
+            instance_path: workspace.clone(),
         };
 
         let run = db::runs::create(&self.global_pool, &create_run).await?;
 
         // Update log path with actual run ID
-        let log_path = self.log_dir.join(format!("{}.log", run.id));
+        let log_path = self.log_dir.join(log_file_name(&run.id, self.log_format));
         sqlx::query("UPDATE runs SET log_path = ? WHERE id = ?")
             .bind(log_path.to_string_lossy().to_string())
             .bind(&run.id)
             .execute(&self.global_pool)
             .await?;
 
-        // Spawn the runner in the workspace directory
-        let workspace_path = std::path::Path::new(&self.worker.instance_path);
-        let handle = spawn_runner(&run, &self.log_dir, workspace_path).await?;
+        // Acknowledge the event (update cursor) - skip for synthetic polled events.
+        // The run record now carries the event, whether it starts now or later.
+        if event.id != 0 {
+            match &workspace {
+                None => self.poller.acknowledge(event.id).await?,
+                Some(root) => {
+                    db::workers::update_workspace_cursor(
+                        &self.global_pool,
+                        &self.worker.id,
+                        root,
+                        event.id,
+                    )
+                    .await?
+                }
+            }
+        }
+
+        if at_capacity {
+            eprintln!(
+                "[worker:{}] Queued run {} for event {} ({}): concurrency limit reached",
+                self.worker.id, run.id, event.id, event.event_type
+            );
+            return Ok(());
+        }
+
+        self.start_run(&run, Some(&event)).await?;
+
+        eprintln!(
+            "[worker:{}] Started run {} for event {} ({})",
+            self.worker.id, run.id, event.id, event.event_type
+        );
+
+        Ok(())
+    }
+
+    /// Number of runs that can be started without exceeding the concurrency limit.
+    fn available_slots(&self) -> usize {
+        (self.worker.concurrency.max(0) as usize).saturating_sub(self.active_runs.len())
+    }
+
+    /// Spawn the runner for a run and track it as active.
+    ///
+    /// If the process cannot be spawned the run is marked failed, so a queued
+    /// run that can never start does not block the queue.
+    ///
+    /// `event` is the event that triggered the run, when at hand; runs started
+    /// from the queue or for a retry read it back from the workspace.
+    async fn start_run(&mut self, run: &Run, event: Option<&Event>) -> Result<()> {
+        let handle = match self.spawn(run, event).await {
+            Ok(handle) => handle,
+            Err(e) => {
+                let update = UpdateRunStatus {
+                    status: RunStatus::Failed,
+                    exit_code: None,
+                    error_message: Some(format!("Failed to start: {}", e)),
+                    pid: None,
+                };
+                db::runs::update_status(&self.global_pool, &run.id, &update).await?;
+                return Err(e);
+            }
+        };
 
         // Update run status to running with PID
         let update = UpdateRunStatus {
@@ -291,53 +727,353 @@ impl WorkerRuntime {
             pid: Some(handle.pid() as i64),
         };
         db::runs::update_status(&self.global_pool, &run.id, &update).await?;
+        self.emit_run_event(EventType::RunStarted, run, None, None)
+            .await;
 
         // Track the active run
         self.active_runs.insert(run.id.clone(), handle);
+        Ok(())
+    }
 
-        // Acknowledge the event (update cursor) - skip for synthetic polled events
-        if event.id != 0 {
-            self.poller.acknowledge(event.id).await?;
+    /// Spawn the runner for a run in the workspace directory, with a
+    /// directory of its own for files it wants kept and, if the worker asks
+    /// for it, the event and a context pack on stdin.
+    async fn spawn(&self, run: &Run, event: Option<&Event>) -> Result<RunnerHandle> {
+        let workspace_path = std::path::Path::new(
+            run.instance_path
+                .as_deref()
+                .unwrap_or(&self.worker.instance_path),
+        );
+        // Check the command where this run resolves it: a global worker's
+        // runs are in other workspaces than the one it was checked in
+        let working_dir = resolve_working_dir(workspace_path, self.limits.cwd.as_deref())?;
+        check_command_allowed(
+            &global_config::load_file()?.security,
+            &run.command,
+            &working_dir,
+        )?;
+        let artifacts_dir = run_artifacts::artifacts_dir(&self.log_dir, &run.id);
+        std::fs::create_dir_all(&artifacts_dir)?;
+        // Later entries win: the runner's env file over its env, and the
+        // artifacts directory over both
+        let mut env = self.env.clone();
+        if let Some(env_file) = &self.env_file {
+            env.extend(load_env_file(workspace_path, env_file)?);
         }
+        env.push((
+            ARTIFACTS_ENV.to_string(),
+            artifacts_dir.display().to_string(),
+        ));
+
+        let stdin = if self.worker.stdin {
+            let pool = self.run_pool(run).await?;
+            let event = match event {
+                Some(event) => event.clone(),
+                None => runner_input::event_for_run(&pool, run).await?,
+            };
+            let workspace = Workspace::open(workspace_path)?;
+            Some(runner_input::build(&pool, &workspace, run, &event).await?)
+        } else {
+            None
+        };
 
-        eprintln!(
-            "[worker:{}] Started run {} for event {} ({})",
-            self.worker.id, run.id, event.id, event.event_type
-        );
+        spawn_runner_with_env(
+            run,
+            &self.log_dir,
+            workspace_path,
+            &env,
+            &self.limits,
+            stdin,
+            self.log_format,
+        )
+        .await
+    }
+
+    /// Pool of the workspace a run belongs to
+    async fn run_pool(&self, run: &Run) -> Result<SqlitePool> {
+        match run.instance_path.as_deref() {
+            Some(root) if root != self.worker.instance_path => {
+                match self.other_workspaces.get(root) {
+                    Some(pool) => Ok(pool.clone()),
+                    None => Workspace::open(root)?.pool().await,
+                }
+            }
+            _ => Ok(self.workspace_pool.clone()),
+        }
+    }
+
+    /// Start queued runs, oldest first, while there are free slots.
+    async fn promote_queued_runs(&mut self) -> Result<()> {
+        let available_slots = self.available_slots();
+        if available_slots == 0 {
+            return Ok(());
+        }
+
+        let queued = db::runs::get_pending(&self.global_pool, &self.worker.id).await?;
+        for run in queued.into_iter().take(available_slots) {
+            self.start_run(&run, None).await?;
+            eprintln!(
+                "[worker:{}] Started queued run {} for event {} ({})",
+                self.worker.id, run.id, run.event_id, run.event_type
+            );
+        }
 
         Ok(())
     }
 
     /// Check for completed runs and update their status.
+    ///
+    /// Runs that have been going for longer than the worker's timeout, or
+    /// were stopped with `granary run stop`, are sent SIGTERM here and
+    /// killed once the grace period is over.
     async fn check_completed_runs(&mut self) -> Result<()> {
+        let timeout = self
+            .worker
+            .timeout_secs
+            .map(|secs| Duration::from_secs(secs.max(0) as u64));
+        self.watch_cancelled_runs().await?;
+        let now = Instant::now();
         let mut completed_runs = Vec::new();
+        let mut timed_out_runs = Vec::new();
+        let mut overdue_runs = Vec::new();
+        let mut progress_reports = Vec::new();
+        let mut usage_reports = Vec::new();
 
         for (run_id, handle) in self.active_runs.iter_mut() {
-            if let Some((exit_code, error)) = handle.try_wait()? {
-                completed_runs.push((run_id.clone(), exit_code, error));
+            let exit = handle.try_wait()?;
+            // Read reports after checking for exit, so a report written
+            // just before the runner exited is not missed
+            let reader = self
+                .progress_readers
+                .entry(run_id.clone())
+                .or_insert_with(|| {
+                    ProgressReader::new(self.log_dir.join(log_file_name(run_id, self.log_format)))
+                });
+            match reader.read() {
+                Ok(reports) => {
+                    if let Some(progress) = reports.progress {
+                        progress_reports.push((run_id.clone(), progress));
+                    }
+                    if let Some(usage) = reports.usage {
+                        usage_reports.push((run_id.clone(), usage));
+                    }
+                }
+                Err(e) => eprintln!(
+                    "[worker:{}] Failed to read reports of run {}: {}",
+                    self.worker.id, run_id, e
+                ),
+            }
+
+            if let Some(exit) = exit {
+                completed_runs.push((run_id.clone(), exit));
+            } else if let Some(stopping) = self.stopping.get(run_id) {
+                if !stopping.killed && now >= stopping.deadline {
+                    overdue_runs.push(run_id.clone());
+                }
+            } else if timeout.is_some_and(|timeout| handle.elapsed() > timeout) {
+                timed_out_runs.push(run_id.clone());
             }
         }
 
-        for (run_id, exit_code, error) in completed_runs {
-            self.handle_run_completion(&run_id, exit_code, error)
-                .await?;
+        for (run_id, progress) in progress_reports {
+            db::runs::update_progress(&self.global_pool, &run_id, &progress).await?;
+        }
+
+        for (run_id, usage) in usage_reports {
+            db::runs::add_token_usage(&self.global_pool, &run_id, &usage).await?;
+        }
+
+        for (run_id, exit) in completed_runs {
+            db::runs::set_usage(&self.global_pool, &run_id, &exit.usage).await?;
+            let stopping = self.stopping.remove(&run_id);
+            if let Some(stopping) = &stopping {
+                db::runs::set_exited_gracefully(&self.global_pool, &run_id, !stopping.killed)
+                    .await?;
+            }
+            self.record_artifacts(&run_id).await;
+            if stopping.is_some_and(|stopping| stopping.timed_out) {
+                self.handle_run_timeout(&run_id).await?;
+            } else {
+                self.handle_run_completion(&run_id, exit).await?;
+            }
             self.active_runs.remove(&run_id);
+            self.progress_readers.remove(&run_id);
         }
 
+        for run_id in overdue_runs {
+            if let Some(handle) = self.active_runs.get_mut(&run_id) {
+                eprintln!(
+                    "[worker:{}] Run {} did not exit within {}s of SIGTERM, killing it",
+                    self.worker.id,
+                    run_id,
+                    self.grace_period.as_secs()
+                );
+                if let Err(e) = handle.kill().await {
+                    eprintln!(
+                        "[worker:{}] Failed to kill run {}: {}",
+                        self.worker.id, run_id, e
+                    );
+                }
+            }
+            if let Some(stopping) = self.stopping.get_mut(&run_id) {
+                stopping.killed = true;
+            }
+        }
+
+        for run_id in timed_out_runs {
+            if let Some(handle) = self.active_runs.get_mut(&run_id) {
+                if let Err(e) = handle.terminate() {
+                    eprintln!(
+                        "[worker:{}] Failed to stop run {}: {}",
+                        self.worker.id, run_id, e
+                    );
+                }
+                self.stopping.insert(
+                    run_id,
+                    StoppingRun {
+                        deadline: now + self.grace_period,
+                        killed: false,
+                        timed_out: true,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start the grace period of active runs that were stopped with
+    /// `granary run stop`. The daemon marks them cancelled and sends them
+    /// SIGTERM; killing them if they don't exit is left to the runtime,
+    /// which holds their process handles.
+    async fn watch_cancelled_runs(&mut self) -> Result<()> {
+        let run_ids: Vec<String> = self
+            .active_runs
+            .keys()
+            .filter(|run_id| !self.stopping.contains_key(*run_id))
+            .cloned()
+            .collect();
+        for run_id in run_ids {
+            let cancelled = db::runs::get(&self.global_pool, &run_id)
+                .await?
+                .is_some_and(|run| run.status_enum() == RunStatus::Cancelled);
+            if cancelled {
+                self.stopping.insert(
+                    run_id,
+                    StoppingRun {
+                        deadline: Instant::now() + self.grace_period,
+                        killed: false,
+                        timed_out: false,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the files a finished run left in its artifacts directory.
+    ///
+    /// Failures are logged rather than propagated so they never affect the
+    /// run's own status.
+    async fn record_artifacts(&self, run_id: &str) {
+        let dir = run_artifacts::artifacts_dir(&self.log_dir, run_id);
+        match run_artifacts::record(&self.global_pool, run_id, &dir).await {
+            Ok(artifacts) if !artifacts.is_empty() => eprintln!(
+                "[worker:{}] Run {} left {} artifact(s)",
+                self.worker.id,
+                run_id,
+                artifacts.len()
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!(
+                "[worker:{}] Failed to record artifacts for run {}: {}",
+                self.worker.id, run_id, e
+            ),
+        }
+    }
+
+    /// Handle a run stopped for exceeding the worker's timeout.
+    ///
+    /// Timed-out runs are only retried when the retry policy opts in, since
+    /// a run that hangs will usually hang again.
+    async fn handle_run_timeout(&self, run_id: &str) -> Result<()> {
+        let run = db::runs::get(&self.global_pool, run_id)
+            .await?
+            .ok_or_else(|| GranaryError::Conflict(format!("Run {} not found", run_id)))?;
+        let error = format!(
+            "Timed out after {}s",
+            self.worker.timeout_secs.unwrap_or_default()
+        );
+
+        if self.retry_policy.retry_timeouts && run.has_attempts_left() {
+            self.schedule_retry(&run, Some(error)).await?;
+            return Ok(());
+        }
+
+        let update = UpdateRunStatus {
+            status: RunStatus::TimedOut,
+            exit_code: None,
+            error_message: Some(error.clone()),
+            pid: None,
+        };
+        db::runs::update_status(&self.global_pool, run_id, &update).await?;
+        eprintln!("[worker:{}] Run {} {}", self.worker.id, run_id, error);
+        self.emit_run_event(EventType::RunFailed, &run, Some(-1), Some(error))
+            .await;
+        Ok(())
+    }
+
+    /// Put a failed run back in the queue for its next attempt.
+    async fn schedule_retry(&self, run: &Run, error: Option<String>) -> Result<()> {
+        let next_attempt = run.attempt + 1;
+        let backoff = self.retry_policy.delay(run.attempt);
+        let next_retry_at = chrono::Duration::from_std(backoff)
+            .ok()
+            .and_then(|backoff| chrono::Utc::now().checked_add_signed(backoff))
+            .ok_or_else(|| {
+                GranaryError::InvalidArgument(format!(
+                    "Retry delay of {}s is out of range",
+                    backoff.as_secs()
+                ))
+            })?;
+
+        let retry = ScheduleRetry {
+            next_retry_at: next_retry_at.to_rfc3339(),
+            attempt: next_attempt,
+            error_message: error,
+        };
+        db::runs::update_for_retry(&self.global_pool, &run.id, &retry).await?;
+
+        eprintln!(
+            "[worker:{}] Run {} failed (attempt {}/{}), scheduled retry at {}",
+            self.worker.id, run.id, run.attempt, run.max_attempts, next_retry_at
+        );
         Ok(())
     }
 
     /// Handle a run completion (success or failure).
-    async fn handle_run_completion(
-        &self,
-        run_id: &str,
-        exit_code: i32,
-        error: Option<String>,
-    ) -> Result<()> {
+    ///
+    /// Runs killed for exceeding a resource limit are not retried, since
+    /// another attempt would hit the same limit.
+    async fn handle_run_completion(&self, run_id: &str, exit: RunnerExit) -> Result<()> {
+        let RunnerExit {
+            exit_code,
+            error,
+            limit_exceeded,
+            ..
+        } = exit;
         let run = db::runs::get(&self.global_pool, run_id)
             .await?
             .ok_or_else(|| GranaryError::Conflict(format!("Run {} not found", run_id)))?;
 
+        // Stopped with `granary run stop`: the run is already marked cancelled
+        if run.status_enum() == RunStatus::Cancelled {
+            eprintln!("[worker:{}] Run {} cancelled", self.worker.id, run_id);
+            self.emit_run_event(EventType::RunCancelled, &run, Some(exit_code), error)
+                .await;
+            return Ok(());
+        }
+
         if exit_code == 0 {
             // Success
             let update = UpdateRunStatus {
@@ -351,30 +1087,35 @@ impl WorkerRuntime {
                 "[worker:{}] Run {} completed successfully",
                 self.worker.id, run_id
             );
+            self.emit_run_event(EventType::RunCompleted, &run, Some(exit_code), None)
+                .await;
+        } else if limit_exceeded {
+            let update = UpdateRunStatus {
+                status: RunStatus::Killed,
+                exit_code: Some(exit_code),
+                error_message: error.clone(),
+                pid: None,
+            };
+            db::runs::update_status(&self.global_pool, run_id, &update).await?;
+            eprintln!(
+                "[worker:{}] Run {} killed: {}",
+                self.worker.id,
+                run_id,
+                error.as_deref().unwrap_or("resource limit exceeded")
+            );
+            self.emit_run_event(EventType::RunFailed, &run, Some(exit_code), error)
+                .await;
         } else {
-            // Failure - check if we should retry
-            if run.can_retry() {
-                let next_attempt = run.attempt + 1;
-                let backoff = calculate_backoff(run.attempt, self.config.base_delay_secs);
-                let next_retry_at =
-                    chrono::Utc::now() + chrono::Duration::from_std(backoff).unwrap();
-
-                let retry = ScheduleRetry {
-                    next_retry_at: next_retry_at.to_rfc3339(),
-                    attempt: next_attempt,
-                };
-                db::runs::update_for_retry(&self.global_pool, run_id, &retry).await?;
-
-                eprintln!(
-                    "[worker:{}] Run {} failed (attempt {}/{}), scheduled retry at {}",
-                    self.worker.id, run_id, run.attempt, run.max_attempts, next_retry_at
-                );
+            // Failure - check if we should retry. The run is still marked running
+            // here, so only the attempt count decides.
+            if run.has_attempts_left() {
+                self.schedule_retry(&run, error).await?;
             } else {
                 // No more retries
                 let update = UpdateRunStatus {
                     status: RunStatus::Failed,
                     exit_code: Some(exit_code),
-                    error_message: error,
+                    error_message: error.clone(),
                     pid: None,
                 };
                 db::runs::update_status(&self.global_pool, run_id, &update).await?;
@@ -382,16 +1123,56 @@ impl WorkerRuntime {
                     "[worker:{}] Run {} failed after {} attempts",
                     self.worker.id, run_id, run.attempt
                 );
+                self.emit_run_event(EventType::RunFailed, &run, Some(exit_code), error)
+                    .await;
             }
         }
 
         Ok(())
     }
 
+    /// Record a run starting or finishing in the workspace event log.
+    ///
+    /// This lets webhooks and other workers react to run outcomes. Failures are
+    /// logged rather than propagated so they never affect the run's own status.
+    async fn emit_run_event(
+        &self,
+        event_type: EventType,
+        run: &Run,
+        exit_code: Option<i32>,
+        error: Option<String>,
+    ) {
+        let event = CreateEvent {
+            event_type,
+            entity_type: EntityType::Run,
+            entity_id: run.id.clone(),
+            actor: Some(self.worker.id.clone()),
+            session_id: None,
+            payload: serde_json::json!({
+                "worker_id": self.worker.id,
+                "trigger_event_type": run.event_type,
+                "trigger_entity_id": run.entity_id,
+                "exit_code": exit_code,
+                "error": error,
+                "attempt": run.attempt,
+            }),
+        };
+        let created = match self.run_pool(run).await {
+            Ok(pool) => db::events::create(&pool, &event).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = created {
+            eprintln!(
+                "[worker:{}] Failed to record event for run {}: {}",
+                self.worker.id, run.id, e
+            );
+        }
+    }
+
     /// Process pending retries that are due.
     async fn process_pending_retries(&mut self) -> Result<()> {
         // Check concurrency limit
-        let available_slots = self.worker.concurrency as usize - self.active_runs.len();
+        let available_slots = self.available_slots();
         if available_slots == 0 {
             return Ok(());
         }
@@ -399,32 +1180,20 @@ impl WorkerRuntime {
         let now = chrono::Utc::now().to_rfc3339();
         let pending_retries = db::runs::list_pending_retries(&self.global_pool, &now).await?;
 
-        for run in pending_retries.into_iter().take(available_slots) {
-            // Only retry runs for this worker
-            if run.worker_id != self.worker.id {
-                continue;
-            }
+        // Only retry runs for this worker
+        let own_retries: Vec<Run> = pending_retries
+            .into_iter()
+            .filter(|run| run.worker_id == self.worker.id)
+            .take(available_slots)
+            .collect();
 
+        for run in own_retries {
             eprintln!(
                 "[worker:{}] Retrying run {} (attempt {}/{})",
                 self.worker.id, run.id, run.attempt, run.max_attempts
             );
 
-            // Spawn the runner in the workspace directory
-            let workspace_path = std::path::Path::new(&self.worker.instance_path);
-            let handle = spawn_runner(&run, &self.log_dir, workspace_path).await?;
-
-            // Update run status to running with PID
-            let update = UpdateRunStatus {
-                status: RunStatus::Running,
-                exit_code: None,
-                error_message: None,
-                pid: Some(handle.pid() as i64),
-            };
-            db::runs::update_status(&self.global_pool, &run.id, &update).await?;
-
-            // Track the active run
-            self.active_runs.insert(run.id.clone(), handle);
+            self.start_run(&run, None).await?;
         }
 
         Ok(())
@@ -460,7 +1229,7 @@ impl WorkerRuntime {
         status: WorkerStatus,
         error_message: Option<String>,
     ) -> Result<()> {
-        let pid = if status == WorkerStatus::Running {
+        let pid = if matches!(status, WorkerStatus::Running | WorkerStatus::Paused) {
             Some(std::process::id() as i64)
         } else {
             None
@@ -477,7 +1246,8 @@ impl WorkerRuntime {
 
     /// Perform graceful shutdown.
     ///
-    /// This waits for active runs to complete or kills them after a timeout.
+    /// This waits for active runs to complete, then stops the ones left
+    /// after a timeout: SIGTERM, and SIGKILL after the grace period.
     async fn graceful_shutdown(&mut self) -> Result<()> {
         if self.active_runs.is_empty() {
             return Ok(());
@@ -502,29 +1272,56 @@ impl WorkerRuntime {
             }
 
             if tokio::time::Instant::now() >= deadline {
-                // Timeout - kill remaining processes
+                // Timeout - stop remaining processes, all at once
                 eprintln!(
-                    "[worker:{}] Shutdown timeout: killing {} remaining processes",
+                    "[worker:{}] Shutdown timeout: stopping {} remaining processes",
                     self.worker.id,
                     self.active_runs.len()
                 );
 
-                for (run_id, mut handle) in self.active_runs.drain() {
-                    if let Err(e) = handle.kill().await {
-                        eprintln!(
-                            "[worker:{}] Failed to kill run {}: {}",
+                let grace = self.grace_period;
+                self.stopping.clear();
+                self.progress_readers.clear();
+                let stopped = futures::future::join_all(self.active_runs.drain().map(
+                    |(run_id, mut handle)| async move {
+                        let stopped = match handle.stop(grace).await {
+                            Ok(graceful) => handle.wait().await.map(|exit| (graceful, exit.usage)),
+                            Err(e) => Err(e),
+                        };
+                        (run_id, stopped)
+                    },
+                ))
+                .await;
+                for (run_id, stopped) in stopped {
+                    match stopped {
+                        Ok((graceful, usage)) => {
+                            db::runs::set_exited_gracefully(&self.global_pool, &run_id, graceful)
+                                .await?;
+                            db::runs::set_usage(&self.global_pool, &run_id, &usage).await?;
+                        }
+                        Err(e) => eprintln!(
+                            "[worker:{}] Failed to stop run {}: {}",
                             self.worker.id, run_id, e
-                        );
+                        ),
                     }
 
                     // Mark run as cancelled
                     let update = UpdateRunStatus {
                         status: RunStatus::Cancelled,
                         exit_code: None,
-                        error_message: Some("Killed during worker shutdown".to_string()),
+                        error_message: Some("Stopped during worker shutdown".to_string()),
                         pid: None,
                     };
                     db::runs::update_status(&self.global_pool, &run_id, &update).await?;
+                    if let Some(run) = db::runs::get(&self.global_pool, &run_id).await? {
+                        self.emit_run_event(
+                            EventType::RunCancelled,
+                            &run,
+                            None,
+                            update.error_message,
+                        )
+                        .await;
+                    }
                 }
                 break;
             }
@@ -613,6 +1410,139 @@ pub async fn start_worker_runtime(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::connection::{create_pool, run_migrations};
+    use crate::models::worker::CreateWorker;
+    use tempfile::TempDir;
+
+    /// A runtime for a worker in a fresh workspace whose config file is
+    /// `workspace_config`, with its global database and logs in the same
+    /// temporary directory
+    async fn setup_runtime(
+        create: CreateWorker,
+        workspace_config: &str,
+    ) -> (WorkerRuntime, TempDir) {
+        let temp = TempDir::new().unwrap();
+        let global_pool = create_pool(&temp.path().join("workers.db")).await.unwrap();
+        run_migrations(&global_pool).await.unwrap();
+        let root = temp.path().join("workspace");
+        std::fs::create_dir(&root).unwrap();
+        let workspace = Workspace::create(&root).unwrap();
+        let workspace_pool = workspace.init_db().await.unwrap();
+        std::fs::write(workspace_config::path(&workspace), workspace_config).unwrap();
+
+        let worker = db::workers::create(
+            &global_pool,
+            &CreateWorker {
+                event_type: "task.unblocked".to_string(),
+                instance_path: root.display().to_string(),
+                ..create
+            },
+        )
+        .await
+        .unwrap();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        let config = WorkerRuntimeConfig {
+            log_dir: Some(temp.path().join("logs")),
+            ..Default::default()
+        };
+        let runtime =
+            WorkerRuntime::new(worker, global_pool, workspace_pool, shutdown_rx, config).unwrap();
+        (runtime, temp)
+    }
+
+    /// Queue a run of the runtime's worker
+    async fn create_run(runtime: &WorkerRuntime, max_attempts: i32) -> Run {
+        db::runs::create(
+            &runtime.global_pool,
+            &CreateRun {
+                worker_id: runtime.worker.id.clone(),
+                command: runtime.worker.command.clone(),
+                args: runtime.worker.args_vec(),
+                max_attempts,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_env_file_cannot_set_path() {
+        let (mut runtime, temp) = setup_runtime(
+            CreateWorker {
+                runner_name: Some("agent".to_string()),
+                command: "sh".to_string(),
+                args: vec!["-c".to_string(), "true".to_string()],
+                ..Default::default()
+            },
+            "[runners.agent]\ncommand = \"sh\"\nenv_file = \".env\"\n",
+        )
+        .await;
+        std::fs::write(temp.path().join("workspace/.env"), "PATH=./bin\n").unwrap();
+
+        let run = create_run(&runtime, 1).await;
+        let result = runtime.start_run(&run, None).await;
+        assert!(matches!(result, Err(GranaryError::CommandNotAllowed(_))));
+        assert!(runtime.active_runs.is_empty());
+        let run = db::runs::get(&runtime.global_pool, &run.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(run.status_enum(), RunStatus::Failed);
+        assert!(run.error_message.unwrap().contains("may not set PATH"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_run_is_killed_and_retried_only_when_asked() {
+        for retry_timeouts in [false, true] {
+            let (mut runtime, _temp) = setup_runtime(
+                CreateWorker {
+                    // Ignores SIGTERM, so only the kill after the grace period stops it
+                    command: "sh".to_string(),
+                    args: vec!["-c".to_string(), "trap '' TERM; exec sleep 30".to_string()],
+                    timeout_secs: Some(1),
+                    retry_policy: Some(RetryPolicy {
+                        retry_timeouts,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                "",
+            )
+            .await;
+            runtime.grace_period = Duration::from_millis(200);
+
+            let run = create_run(&runtime, 2).await;
+            runtime.start_run(&run, None).await.unwrap();
+            let pid = runtime.active_runs[&run.id].pid() as i32;
+
+            let deadline = Instant::now() + Duration::from_secs(15);
+            while !runtime.active_runs.is_empty() {
+                assert!(Instant::now() < deadline, "run was not stopped");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                runtime.check_completed_runs().await.unwrap();
+            }
+            // SAFETY: signal 0 only checks whether the process group exists
+            assert_eq!(unsafe { libc::kill(-pid, 0) }, -1);
+
+            let run = db::runs::get(&runtime.global_pool, &run.id)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(run.exited_gracefully, Some(false));
+            assert_eq!(run.error_message.as_deref(), Some("Timed out after 1s"));
+            if retry_timeouts {
+                assert_eq!(run.status_enum(), RunStatus::Pending);
+                assert_eq!(run.attempt, 2);
+                assert!(run.next_retry_at.is_some());
+            } else {
+                assert_eq!(run.status_enum(), RunStatus::TimedOut);
+                assert_eq!(run.attempt, 1);
+            }
+        }
+    }
 
     #[test]
     fn test_calculate_backoff_first_attempt() {
@@ -4,7 +4,10 @@
 
 #[cfg(test)]
 mod tests {
-    use crate::models::worker::{CreateWorker, UpdateWorkerStatus, Worker, WorkerStatus};
+    use crate::models::worker::{
+        CreateWorker, PauseMode, UpdateWorkerStatus, Worker, WorkerStatus,
+    };
+    use crate::services::schedule;
 
     // ==========================================
     // WorkerStatus Tests
@@ -14,6 +17,7 @@ mod tests {
     fn test_worker_status_as_str() {
         assert_eq!(WorkerStatus::Pending.as_str(), "pending");
         assert_eq!(WorkerStatus::Running.as_str(), "running");
+        assert_eq!(WorkerStatus::Paused.as_str(), "paused");
         assert_eq!(WorkerStatus::Stopped.as_str(), "stopped");
         assert_eq!(WorkerStatus::Error.as_str(), "error");
     }
@@ -28,6 +32,10 @@ mod tests {
             "running".parse::<WorkerStatus>().unwrap(),
             WorkerStatus::Running
         );
+        assert_eq!(
+            "paused".parse::<WorkerStatus>().unwrap(),
+            WorkerStatus::Paused
+        );
         assert_eq!(
             "stopped".parse::<WorkerStatus>().unwrap(),
             WorkerStatus::Stopped
@@ -95,9 +103,52 @@ mod tests {
             stopped_at: None,
             poll_cooldown_secs: 300,
             last_event_id: 100,
+            schedule: None,
+            next_fire_at: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: "queue".to_string(),
         }
     }
 
+    #[test]
+    fn test_worker_is_scheduled() {
+        let mut worker = create_test_worker();
+        assert!(!worker.is_scheduled());
+
+        worker.schedule = Some("0 9 * * MON-FRI".to_string());
+        assert!(worker.is_scheduled());
+    }
+
+    #[test]
+    fn test_worker_serves_workspace() {
+        let mut worker = create_test_worker();
+        assert!(worker.serves_workspace(&worker.instance_path.clone()));
+        assert!(!worker.serves_workspace("/other/workspace"));
+
+        worker.global = true;
+        assert!(worker.serves_workspace("/other/workspace"));
+    }
+
+    #[test]
+    fn test_schedule_fired_event() {
+        let mut worker = create_test_worker();
+        worker.schedule = Some("*/5 * * * *".to_string());
+
+        let event = schedule::fired_event(&worker, "2026-01-15T10:05:00+00:00");
+        assert_eq!(event.id, 0);
+        assert_eq!(event.event_type, schedule::SCHEDULE_EVENT_TYPE);
+        assert_eq!(event.entity_id, worker.id);
+
+        let payload = event.payload_json();
+        assert_eq!(payload["schedule"], "*/5 * * * *");
+        assert_eq!(payload["scheduled_at"], "2026-01-15T10:05:00+00:00");
+    }
+
     #[test]
     fn test_worker_status_enum() {
         let mut worker = create_test_worker();
@@ -235,6 +286,14 @@ mod tests {
             instance_path: "/home/user/project".to_string(),
             poll_cooldown_secs: 600, // 10 minutes
             detached: true,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         };
 
         assert_eq!(create.runner_name, Some("claude".to_string()));
@@ -363,6 +422,14 @@ mod tests {
             instance_path: "/projects/myapp".to_string(),
             poll_cooldown_secs: 300,
             detached: false,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         };
 
         assert!(create.runner_name.is_some());
@@ -381,6 +448,14 @@ mod tests {
             instance_path: "/projects/myapp".to_string(),
             poll_cooldown_secs: 300,
             detached: false,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         };
 
         assert!(create.runner_name.is_none());
@@ -403,6 +478,14 @@ mod tests {
             instance_path: "/projects/backend".to_string(),
             poll_cooldown_secs: 300,
             detached: true,
+            schedule: None,
+            retry_policy: None,
+            timeout_secs: None,
+            debounce_secs: None,
+            throttle_secs: None,
+            stdin: false,
+            global: false,
+            on_pause: PauseMode::Queue,
         };
 
         assert_eq!(create.filters.len(), 3);
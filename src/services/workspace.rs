@@ -1,10 +1,15 @@
 use std::env;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use sqlx::SqlitePool;
 
-use crate::db::connection::{create_pool, run_migrations};
+use crate::db::connection::{
+    create_encrypted_pool, create_pool, create_recorded_pool, run_migrations,
+};
+use crate::db::{encryption, operations};
 use crate::error::{GranaryError, Result};
+use crate::services::{doctor, global_config_service};
 
 /// The name of the workspace directory
 pub const WORKSPACE_DIR: &str = ".granary";
@@ -17,6 +22,9 @@ pub const WORKSPACE_ENV: &str = "GRANARY_HOME";
 /// Environment variable for current session
 pub const SESSION_ENV: &str = "GRANARY_SESSION";
 
+/// Workspace root chosen with `--workspace`, used in place of the search
+static ROOT_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
 /// Workspace represents a Granary workspace directory
 #[derive(Debug)]
 pub struct Workspace {
@@ -32,6 +40,10 @@ impl Workspace {
     /// Find the workspace by walking up from the current directory
     /// Similar to how Git finds .git/
     pub fn find() -> Result<Self> {
+        if let Some(root) = ROOT_OVERRIDE.get() {
+            return Self::open(root);
+        }
+
         // Check for environment variable override first
         if let Ok(path) = env::var(WORKSPACE_ENV) {
             let root = PathBuf::from(path);
@@ -68,6 +80,12 @@ impl Workspace {
         Err(GranaryError::WorkspaceNotFound)
     }
 
+    /// Make `find()` return the workspace at `root` for the rest of the
+    /// process, as chosen with `--workspace`
+    pub fn set_root_override(root: PathBuf) {
+        let _ = ROOT_OVERRIDE.set(root);
+    }
+
     /// Find workspace or create one at the specified path
     pub fn find_or_create(path: Option<&Path>) -> Result<Self> {
         // Try to find existing workspace first
@@ -129,12 +147,31 @@ impl Workspace {
         Ok(pool)
     }
 
-    /// Get a connection pool to the database
+    /// Create an encrypted database keyed with `key` and run migrations
+    pub async fn init_encrypted_db(&self, key: &str) -> Result<SqlitePool> {
+        if self.db_path.exists() && !encryption::is_encrypted(&self.db_path) {
+            return Err(GranaryError::Encryption(format!(
+                "{} already exists unencrypted. Export it, then import into a workspace created with `granary init --encrypted`",
+                self.db_path.display()
+            )));
+        }
+        let pool = create_encrypted_pool(&self.db_path, key).await?;
+        run_migrations(&pool).await?;
+        Ok(pool)
+    }
+
+    /// Get a connection pool to the database.
+    ///
+    /// When this process records an operation for `granary undo`, changes
+    /// made through the pool are logged under it.
     pub async fn pool(&self) -> Result<SqlitePool> {
         if !self.db_path.exists() {
             return Err(GranaryError::WorkspaceNotFound);
         }
-        let pool = create_pool(&self.db_path).await?;
+        let pool = match operations::recording() {
+            Some(operation_id) => create_recorded_pool(&self.db_path, operation_id).await?,
+            None => create_pool(&self.db_path).await?,
+        };
         // Run migrations to ensure schema is up to date
         run_migrations(&pool).await?;
         Ok(pool)
@@ -179,7 +216,9 @@ impl Workspace {
     }
 
     /// Run diagnostic checks on the workspace
-    pub async fn doctor(&self) -> Result<Vec<DiagnosticResult>> {
+    /// Check workspace health. With `fix`, repairable problems are repaired
+    /// and reported as fixed.
+    pub async fn doctor(&self, fix: bool) -> Result<Vec<DiagnosticResult>> {
         let mut results = Vec::new();
 
         // Check .granary directory
@@ -204,6 +243,11 @@ impl Workspace {
             message: format!("{}", self.db_path.display()),
         });
 
+        // Failed migrations stop the database from opening, so check them first
+        if self.db_path.exists() {
+            results.push(doctor::check_migrations("Migrations", &self.db_path, fix).await);
+        }
+
         // Check database connection
         match self.pool().await {
             Ok(pool) => {
@@ -227,6 +271,16 @@ impl Workspace {
                     message: wal_check.unwrap_or_else(|e| e.to_string()),
                 });
 
+                results.push(DiagnosticResult {
+                    check: "Encryption".to_string(),
+                    status: DiagnosticStatus::Info,
+                    message: if encryption::is_encrypted(&self.db_path) {
+                        "Encrypted with SQLCipher".to_string()
+                    } else {
+                        "Not encrypted".to_string()
+                    },
+                });
+
                 // Check foreign keys
                 let fk_check = sqlx::query_scalar::<_, i32>("PRAGMA foreign_keys")
                     .fetch_one(&pool)
@@ -264,6 +318,10 @@ impl Workspace {
                         project_count, task_count, session_count
                     ),
                 });
+
+                results.push(doctor::check_orphaned_tasks(&pool, fix).await);
+                results.push(doctor::check_integrity(&pool, fix).await);
+                results.push(doctor::check_fts_indexes(&pool, fix).await);
             }
             Err(e) => {
                 results.push(DiagnosticResult {
@@ -289,6 +347,27 @@ impl Workspace {
         };
         results.push(session_status);
 
+        // Workers and runs live in the global database
+        if let Ok(global_db_path) = global_config_service::global_db_path()
+            && global_db_path.exists()
+        {
+            results.push(doctor::check_migrations("Global migrations", &global_db_path, fix).await);
+            if let Ok(global_pool) = global_config_service::global_pool().await {
+                results.push(doctor::check_orphaned_runs(&global_pool, fix).await);
+            }
+        }
+        if let Ok(pid_path) = global_config_service::daemon_pid_path() {
+            #[cfg(unix)]
+            let socket_path = global_config_service::daemon_socket_path().ok();
+            #[cfg(not(unix))]
+            let socket_path: Option<PathBuf> = None;
+            results.push(doctor::check_daemon_files(
+                &pid_path,
+                socket_path.as_deref(),
+                fix,
+            ));
+        }
+
         Ok(results)
     }
 }
@@ -299,6 +378,8 @@ pub enum DiagnosticStatus {
     Warning,
     Error,
     Info,
+    /// A problem `granary doctor --fix` repaired
+    Fixed,
 }
 
 #[derive(Debug)]
@@ -315,6 +396,7 @@ impl DiagnosticResult {
             DiagnosticStatus::Warning => "[WARN]",
             DiagnosticStatus::Error => "[ERR]",
             DiagnosticStatus::Info => "[INFO]",
+            DiagnosticStatus::Fixed => "[FIXED]",
         }
     }
 }
@@ -0,0 +1,424 @@
+//! Workspace configuration file, `.granary/granary.toml`.
+//!
+//! Settings are resolved in this order, the first that sets one winning:
+//!
+//! 1. Command-line flags
+//! 2. Environment variables (`GRANARY_PROFILE`, `GRANARY_ACTOR`, ...)
+//! 3. `.granary/granary.toml` of the workspace in use
+//! 4. The active profile in `~/.granary/config.toml`
+//! 5. The top level of `~/.granary/config.toml`
+//! 6. Built-in defaults
+//!
+//! Workspace config keys (`granary config set`) are separate settings, not
+//! another layer: no key has the same meaning as a setting in a file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use tokio::io::AsyncWriteExt;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::global_config::{
+    DateConfig, GlobalConfig, LogRetentionConfig, NotificationConfig, NotifierConfig, RunnerConfig,
+    SecurityConfig, UpdateConfig, UserConfig, WebhookConfig, resolve_runner,
+};
+use crate::models::workspace_config::{
+    HookConfig, ModeConfig, OutputPreferences, WorkspaceConfig, WorkspaceDefaults,
+};
+use crate::models::{Event, TaskPriority, TaskStatus};
+use crate::output::OutputFormat;
+use crate::services::global_config as global_config_service;
+use crate::services::workspace::Workspace;
+use crate::services::{recommendation, summary_sections};
+
+/// Name of the file in the `.granary` directory
+pub const FILE_NAME: &str = "granary.toml";
+
+/// Path of a workspace's config file
+pub fn path(workspace: &Workspace) -> PathBuf {
+    workspace.granary_dir.join(FILE_NAME)
+}
+
+/// Load a workspace's config file. Returns the default config if the
+/// workspace has none.
+pub fn load(workspace: &Workspace) -> Result<WorkspaceConfig> {
+    let path = path(workspace);
+    if !path.exists() {
+        return Ok(WorkspaceConfig::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    parse(&content)
+        .map_err(|e| GranaryError::InvalidArgument(format!("Invalid {}: {}", path.display(), e)))
+}
+
+/// Load the config file of the workspace in use, if there is one
+pub fn load_current() -> Result<WorkspaceConfig> {
+    match Workspace::find() {
+        Ok(workspace) => load(&workspace),
+        Err(_) => Ok(WorkspaceConfig::default()),
+    }
+}
+
+/// A setting in a workspace config file with an invalid value
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSetting {
+    /// Path to the setting, e.g. `["hooks", "0", "on"]`
+    pub key: Vec<String>,
+    pub message: String,
+}
+
+/// Parse and validate a workspace config file
+pub fn parse(content: &str) -> std::result::Result<WorkspaceConfig, String> {
+    let config: WorkspaceConfig = toml::from_str(content).map_err(|e| e.to_string())?;
+    validate(&config).map_err(|e| e.message)?;
+    Ok(config)
+}
+
+/// Check the values that deserialize but aren't valid settings
+pub fn validate(config: &WorkspaceConfig) -> std::result::Result<(), InvalidSetting> {
+    let invalid = |key: &[&str], message: String| InvalidSetting {
+        key: key.iter().map(|k| k.to_string()).collect(),
+        message,
+    };
+    if let Some(priority) = &config.defaults.priority
+        && priority.parse::<TaskPriority>().is_err()
+    {
+        return Err(invalid(
+            &["defaults", "priority"],
+            format!("defaults.priority must be P0-P4, got '{}'", priority),
+        ));
+    }
+    if let Some(format) = &config.output.format
+        && format.parse::<OutputFormat>().is_err()
+    {
+        return Err(invalid(
+            &["output", "format"],
+            format!(
+                "output.format must be table, json, yaml, md or prompt, got '{}'",
+                format
+            ),
+        ));
+    }
+    for (i, hook) in config.hooks.iter().enumerate() {
+        if hook.on.is_empty() || hook.command.trim().is_empty() {
+            return Err(invalid(
+                &["hooks", &i.to_string()],
+                "each [[hooks]] entry needs `on` events and a `command`".to_string(),
+            ));
+        }
+    }
+    let sections: Vec<&str> = summary_sections::sections()
+        .iter()
+        .map(|s| s.name())
+        .collect();
+    let mut modes: Vec<_> = config.modes.iter().collect();
+    modes.sort_by_key(|(name, _)| *name);
+    for (name, mode) in modes {
+        if let Some(section) = mode
+            .emphasize
+            .iter()
+            .find(|s| !sections.contains(&s.as_str()))
+        {
+            return Err(invalid(
+                &["modes", name, "emphasize"],
+                format!(
+                    "modes.{}.emphasize: unknown summary section '{}'. Expected one of: {}",
+                    name,
+                    section,
+                    sections.join(", ")
+                ),
+            ));
+        }
+        if let Some(status) = mode
+            .statuses
+            .iter()
+            .flatten()
+            .find(|s| s.parse::<TaskStatus>().is_err())
+        {
+            return Err(invalid(
+                &["modes", name, "statuses"],
+                format!(
+                    "modes.{}.statuses: unknown status '{}'. Expected draft, todo, in_progress, done or blocked",
+                    name, status
+                ),
+            ));
+        }
+        let mut weights: Vec<_> = mode.next_weights.iter().collect();
+        weights.sort_by_key(|(factor, _)| *factor);
+        for (factor, weight) in weights {
+            if !recommendation::FACTORS.contains(&factor.as_str()) {
+                return Err(invalid(
+                    &["modes", name, "next_weights", factor],
+                    format!(
+                        "modes.{}.next_weights: unknown factor '{}'. Expected one of: {}",
+                        name,
+                        factor,
+                        recommendation::FACTORS.join(", ")
+                    ),
+                ));
+            }
+            if !weight.is_finite() || *weight < 0.0 {
+                return Err(invalid(
+                    &["modes", name, "next_weights", factor],
+                    format!(
+                        "modes.{}.next_weights.{} must be a non-negative number",
+                        name, factor
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Get a runner by name, with the runners it extends applied: from the
+/// workspace in use, then the global config
+pub fn get_runner(name: &str) -> Result<Option<RunnerConfig>> {
+    resolve(load_current()?, name)
+}
+
+/// Get a runner by name, with the runners it extends applied: from the
+/// workspace at `root`, then the global config
+pub fn get_runner_in(root: &Path, name: &str) -> Result<Option<RunnerConfig>> {
+    let config = match Workspace::open(root) {
+        Ok(workspace) => load(&workspace)?,
+        Err(_) => WorkspaceConfig::default(),
+    };
+    resolve(config, name)
+}
+
+/// Resolve a runner among the global runners and a workspace's, which
+/// replace global runners of the same name and may extend any of them
+fn resolve(config: WorkspaceConfig, name: &str) -> Result<Option<RunnerConfig>> {
+    let mut runners = global_config_service::load()?.runners;
+    runners.extend(config.runners);
+    resolve_runner(&runners, name).map_err(GranaryError::GlobalConfig)
+}
+
+/// Priority for a new task: the one given, else the workspace default
+pub fn task_priority(given: Option<String>) -> Result<TaskPriority> {
+    let priority = match given {
+        Some(priority) => Some(priority),
+        None => load_current()?.defaults.priority,
+    };
+    Ok(priority.and_then(|p| p.parse().ok()).unwrap_or_default())
+}
+
+/// Everything that applies in a workspace, for `granary config show --effective`
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfig {
+    /// The active profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Root of the workspace in use
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<PathBuf>,
+    pub user: UserConfig,
+    pub date: DateConfig,
+    pub defaults: WorkspaceDefaults,
+    pub output: OutputPreferences,
+    pub logs: LogRetentionConfig,
+    pub update: UpdateConfig,
+    pub notifications: NotificationConfig,
+    pub runners: BTreeMap<String, RunnerConfig>,
+    pub hooks: Vec<HookConfig>,
+    pub modes: BTreeMap<String, ModeConfig>,
+    pub webhooks: Vec<WebhookConfig>,
+    pub notifiers: Vec<NotifierConfig>,
+    pub security: SecurityConfig,
+    /// Workspace config keys set with `granary config set`
+    pub values: BTreeMap<String, String>,
+}
+
+/// Merge a workspace config over the global config (with its profile
+/// applied) and the workspace's config keys
+pub fn effective(
+    global: GlobalConfig,
+    workspace_config: WorkspaceConfig,
+    workspace: Option<&Workspace>,
+    values: Vec<(String, String)>,
+) -> EffectiveConfig {
+    let mut all_runners = global.runners;
+    all_runners.extend(workspace_config.runners);
+    // A runner that can't be resolved is shown as written
+    let runners = all_runners
+        .iter()
+        .map(|(name, runner)| {
+            let resolved = resolve_runner(&all_runners, name).ok().flatten();
+            (name.clone(), resolved.unwrap_or_else(|| runner.clone()))
+        })
+        .collect();
+    EffectiveConfig {
+        profile: global_config_service::active_profile(),
+        workspace: workspace.map(|w| w.root.clone()),
+        user: global.user,
+        date: global.date,
+        defaults: workspace_config.defaults,
+        output: workspace_config.output,
+        logs: global.logs,
+        update: global.update,
+        notifications: global.notifications,
+        runners,
+        hooks: workspace_config.hooks,
+        modes: workspace_config.modes.into_iter().collect(),
+        webhooks: global.webhooks,
+        notifiers: global.notifiers,
+        security: global.security,
+        values: values.into_iter().collect(),
+    }
+}
+
+/// Hooks waiting for the events of a command that is about to run
+pub struct PendingHooks {
+    workspace: Workspace,
+    pool: SqlitePool,
+    hooks: Vec<HookConfig>,
+    since_id: i64,
+}
+
+impl PendingHooks {
+    /// Note where the event log ends, if the workspace in use has hooks
+    pub async fn watch(config: &WorkspaceConfig) -> Option<Self> {
+        if config.hooks.is_empty() {
+            return None;
+        }
+        let workspace = Workspace::find().ok()?;
+        let pool = workspace.pool().await.ok()?;
+        let since_id = db::events::latest_id(&pool).await.ok()?;
+        Some(Self {
+            workspace,
+            pool,
+            hooks: config.hooks.clone(),
+            since_id,
+        })
+    }
+
+    /// Run the hooks for the events emitted since [`PendingHooks::watch`],
+    /// in order. Returns a warning for each hook that failed.
+    pub async fn run(self) -> Vec<String> {
+        let events = match db::events::list_since_id(&self.pool, self.since_id).await {
+            Ok(events) => events,
+            Err(e) => return vec![format!("could not read events for hooks: {}", e)],
+        };
+        let mut warnings = Vec::new();
+        for event in &events {
+            for hook in self.hooks.iter().filter(|h| h.matches(&event.event_type)) {
+                if let Err(e) = run_hook(&self.workspace.root, hook, event).await {
+                    warnings.push(format!(
+                        "hook `{}` failed for {} {}: {}",
+                        hook.command, event.event_type, event.entity_id, e
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+async fn run_hook(root: &Path, hook: &HookConfig, event: &Event) -> Result<()> {
+    #[cfg(unix)]
+    let mut command = tokio::process::Command::new("sh");
+    #[cfg(unix)]
+    command.arg("-c");
+    #[cfg(windows)]
+    let mut command = tokio::process::Command::new("cmd");
+    #[cfg(windows)]
+    command.arg("/C");
+
+    let mut child = command
+        .arg(&hook.command)
+        .current_dir(root)
+        .env("GRANARY_EVENT_ID", event.id.to_string())
+        .env("GRANARY_EVENT_TYPE", &event.event_type)
+        .env("GRANARY_ENTITY_ID", &event.entity_id)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    let payload = serde_json::json!({
+        "id": event.id,
+        "event_type": event.event_type,
+        "entity_type": event.entity_type,
+        "entity_id": event.entity_id,
+        "actor": event.actor,
+        "session_id": event.session_id,
+        "payload": event.payload_json(),
+        "created_at": event.created_at,
+    });
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input closes the pipe early
+        let _ = stdin.write_all(payload.to_string().as_bytes()).await;
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(GranaryError::Other(format!("exited with {}", status)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateProject, CreateTask};
+    use crate::services;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_validates_values() {
+        assert!(parse("[defaults]\npriority = \"P1\"").is_ok());
+        assert!(parse("[defaults]\npriority = \"urgent\"").is_err());
+        assert!(parse("[output]\nformat = \"xml\"").is_err());
+        assert!(parse("[[hooks]]\non = []\ncommand = \"true\"").is_err());
+        assert!(parse("[modes.triage]\nemphasize = [\"blockers\"]\nstatuses = [\"todo\"]").is_ok());
+        assert!(parse("[modes.triage]\nemphasize = [\"gossip\"]").is_err());
+        assert!(parse("[modes.triage]\nstatuses = [\"shipped\"]").is_err());
+        assert!(parse("[modes.triage.next_weights]\ndue = 2.0\nstale = 0").is_ok());
+        assert!(parse("[modes.triage.next_weights]\nluck = 2.0").is_err());
+        assert!(parse("[modes.triage.next_weights]\ndue = -1.0").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hooks_run_for_new_events() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = Workspace::create(temp_dir.path()).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = services::create_project(
+            &pool,
+            CreateProject {
+                name: "Hooks".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let out = temp_dir.path().join("hook.out");
+        let pending = PendingHooks {
+            workspace: Workspace::open(temp_dir.path()).unwrap(),
+            pool: pool.clone(),
+            hooks: vec![HookConfig {
+                on: vec!["task.created".to_string()],
+                command: format!("echo \"$GRANARY_EVENT_TYPE\" >> {}", out.display()),
+            }],
+            since_id: db::events::latest_id(&pool).await.unwrap(),
+        };
+        services::create_task(
+            &pool,
+            CreateTask {
+                project_id: project.id,
+                title: "Run the hook".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(pending.run().await.is_empty());
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "task.created\n");
+    }
+}
@@ -0,0 +1,261 @@
+//! The registry of workspaces on this machine.
+//!
+//! Workspaces are registered in the global database when they are created
+//! and each time a command runs in them, so ones created before the
+//! registry existed are picked up on first use. `granary workspaces` lists
+//! them, and `--workspace <name>` runs a command in one from anywhere.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{GranaryError, Result};
+use crate::models::*;
+use crate::services::{Workspace, global_config_service};
+
+/// Name used when the workspace root has no directory name, e.g. `/`
+const FALLBACK_NAME: &str = "workspace";
+
+/// Add a workspace to the registry, or mark it used if it is already there.
+/// New workspaces are named after their directory, with a number appended
+/// when another workspace already has that name.
+pub async fn register_workspace(
+    global: &SqlitePool,
+    workspace: &Workspace,
+) -> Result<RegisteredWorkspace> {
+    let root = canonical_root(&workspace.root);
+    if let Some(existing) = db::workspaces::get_by_root(global, &root).await? {
+        return db::workspaces::register(global, &existing.name, &root).await;
+    }
+
+    let base = Path::new(&root)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| FALLBACK_NAME.to_string());
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while db::workspaces::get(global, &name).await?.is_some() {
+        name = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    db::workspaces::register(global, &name, &root).await
+}
+
+/// Register a workspace in the global database, ignoring failures: the
+/// registry is a convenience and never stops a command
+pub async fn try_register_workspace(workspace: &Workspace) {
+    if let Ok(global) = global_config_service::global_pool().await {
+        let _ = register_workspace(&global, workspace).await;
+    }
+}
+
+/// Every registered workspace with its task counts and last activity.
+/// Workspaces that cannot be opened are listed with the reason.
+pub async fn list_workspaces(global: &SqlitePool) -> Result<Vec<WorkspaceOverview>> {
+    let mut overviews = Vec::new();
+    for registered in db::workspaces::list(global).await? {
+        let mut overview = WorkspaceOverview {
+            name: registered.name,
+            root: registered.root,
+            tasks: None,
+            open_tasks: None,
+            last_activity: registered.last_used_at,
+            error: None,
+        };
+        match task_activity(&overview.root).await {
+            Ok((tasks, open_tasks, last_update)) => {
+                overview.tasks = Some(tasks);
+                overview.open_tasks = Some(open_tasks);
+                if let Some(last_update) = last_update
+                    && ConflictStrategy::Newest.resolve(&overview.last_activity, &last_update)
+                        == SyncDirection::Pull
+                {
+                    overview.last_activity = last_update;
+                }
+            }
+            Err(e) => overview.error = Some(e.to_string()),
+        }
+        overviews.push(overview);
+    }
+    Ok(overviews)
+}
+
+async fn task_activity(root: &str) -> Result<(i64, i64, Option<String>)> {
+    let pool = Workspace::open(root)?.pool().await?;
+    db::workspaces::task_activity(&pool).await
+}
+
+pub async fn rename_workspace(
+    global: &SqlitePool,
+    name: &str,
+    new_name: &str,
+) -> Result<RegisteredWorkspace> {
+    if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name.trim() != new_name {
+        return Err(GranaryError::InvalidArgument(format!(
+            "Invalid workspace name: {:?}",
+            new_name
+        )));
+    }
+    if db::workspaces::get(global, new_name).await?.is_some() {
+        return Err(GranaryError::Conflict(format!(
+            "A workspace named {} already exists",
+            new_name
+        )));
+    }
+    if !db::workspaces::rename(global, name, new_name).await? {
+        return Err(unknown_workspace(name));
+    }
+    db::workspaces::get(global, new_name)
+        .await?
+        .ok_or_else(|| unknown_workspace(new_name))
+}
+
+/// Remove a workspace from the registry; its files are left alone
+pub async fn forget_workspace(global: &SqlitePool, name: &str) -> Result<()> {
+    if !db::workspaces::delete(global, name).await? {
+        return Err(unknown_workspace(name));
+    }
+    Ok(())
+}
+
+/// The root of the workspace `--workspace` refers to: a directory holding
+/// `.granary/`, or else the name of a registered workspace
+pub async fn workspace_root(value: &str) -> Result<PathBuf> {
+    if let Ok(workspace) = Workspace::open(value) {
+        return Ok(workspace.root);
+    }
+    let global = global_config_service::global_pool().await?;
+    find_workspace(&global, value).await
+}
+
+/// The root of a registered workspace
+pub async fn find_workspace(global: &SqlitePool, name: &str) -> Result<PathBuf> {
+    let registered = db::workspaces::get(global, name)
+        .await?
+        .ok_or_else(|| unknown_workspace(name))?;
+    let workspace = Workspace::open(&registered.root).map_err(|_| {
+        GranaryError::InvalidArgument(format!(
+            "Workspace {} no longer exists at {}. Forget it with: granary workspaces remove {}",
+            name, registered.root, name
+        ))
+    })?;
+    Ok(workspace.root)
+}
+
+fn unknown_workspace(name: &str) -> GranaryError {
+    GranaryError::InvalidArgument(format!(
+        "No workspace named {}. See: granary workspaces list",
+        name
+    ))
+}
+
+fn canonical_root(root: &Path) -> String {
+    root.canonicalize()
+        .unwrap_or_else(|_| root.to_path_buf())
+        .display()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{create_project, create_task};
+    use tempfile::TempDir;
+
+    async fn global_pool(dir: &TempDir) -> SqlitePool {
+        let pool = db::connection::create_pool(&dir.path().join("workers.db"))
+            .await
+            .unwrap();
+        db::connection::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_register_names_workspaces_after_their_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = global_pool(&temp_dir).await;
+        let first = temp_dir.path().join("one").join("app");
+        let second = temp_dir.path().join("two").join("app");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        let first = Workspace::create(&first).unwrap();
+        let second = Workspace::create(&second).unwrap();
+
+        let registered = register_workspace(&global, &first).await.unwrap();
+        assert_eq!(registered.name, "app");
+        assert_eq!(
+            register_workspace(&global, &second).await.unwrap().name,
+            "app-2"
+        );
+        // Registering again keeps the name
+        let again = register_workspace(&global, &first).await.unwrap();
+        assert_eq!(again.name, "app");
+        assert_eq!(again.created_at, registered.created_at);
+
+        rename_workspace(&global, "app-2", "billing").await.unwrap();
+        assert!(matches!(
+            rename_workspace(&global, "app", "billing").await,
+            Err(GranaryError::Conflict(_))
+        ));
+        assert_eq!(
+            find_workspace(&global, "billing").await.unwrap(),
+            second.root
+        );
+        assert!(find_workspace(&global, "app-2").await.is_err());
+
+        forget_workspace(&global, "billing").await.unwrap();
+        assert_eq!(db::workspaces::list(&global).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_workspaces_counts_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let global = global_pool(&temp_dir).await;
+        let root = temp_dir.path().join("app");
+        std::fs::create_dir_all(&root).unwrap();
+        let workspace = Workspace::create(&root).unwrap();
+        let pool = workspace.init_db().await.unwrap();
+        let project = create_project(
+            &pool,
+            CreateProject {
+                name: "App".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        for title in ["One", "Two"] {
+            create_task(
+                &pool,
+                CreateTask {
+                    project_id: project.id.clone(),
+                    title: title.to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        }
+        register_workspace(&global, &workspace).await.unwrap();
+
+        let gone = temp_dir.path().join("gone");
+        std::fs::create_dir_all(&gone).unwrap();
+        let gone_workspace = Workspace::create(&gone).unwrap();
+        register_workspace(&global, &gone_workspace).await.unwrap();
+        std::fs::remove_dir_all(&gone).unwrap();
+
+        let overviews = list_workspaces(&global).await.unwrap();
+        assert_eq!(overviews.len(), 2);
+        assert_eq!(overviews[0].name, "app");
+        assert_eq!(
+            (overviews[0].tasks, overviews[0].open_tasks),
+            (Some(2), Some(2))
+        );
+        assert!(overviews[0].error.is_none());
+        assert_eq!(overviews[1].name, "gone");
+        assert!(overviews[1].tasks.is_none());
+        assert!(overviews[1].error.is_some());
+    }
+}
@@ -13,6 +13,7 @@ use tokio::time::sleep;
 
 use granary::daemon::DaemonClient;
 use granary::daemon::protocol::StartWorkerRequest;
+use granary::models::PauseMode;
 
 /// Test helper to start a test daemon in isolation.
 ///
@@ -318,6 +319,14 @@ async fn test_daemon_start_stop_worker() {
         instance_path: workspace_path.to_string_lossy().to_string(),
         attach: false,
         poll_cooldown_secs: None,
+        schedule: None,
+        retry_policy: None,
+        timeout_secs: None,
+        debounce_secs: None,
+        throttle_secs: None,
+        stdin: false,
+        global: false,
+        on_pause: PauseMode::Queue,
     };
 
     // Note: This will likely fail because the workspace doesn't have a proper granary DB
@@ -414,9 +423,8 @@ async fn test_daemon_shutdown() {
     // Connection should fail now (daemon has shut down)
     let connect_result = daemon.try_connect().await;
     // Either the socket is gone or connection is refused
-    if connect_result.is_ok() {
+    if let Ok(mut client) = connect_result {
         // If we can still connect, ping should fail
-        let mut client = connect_result.unwrap();
         let ping_result = client.ping().await;
         // The ping might succeed if daemon hasn't fully shut down yet,
         // or fail if it has